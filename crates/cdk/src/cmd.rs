@@ -7,6 +7,7 @@ use fluvio_cli_common::version_cmd::BasicVersionCmd;
 use crate::build::BuildCmd;
 use crate::generate::GenerateCmd;
 use crate::deploy::DeployCmd;
+use crate::lock::LockCmd;
 use crate::test::TestCmd;
 
 /// Connector Development Kit
@@ -16,6 +17,7 @@ pub enum CdkCommand {
     Test(TestCmd),
     Generate(GenerateCmd),
     Deploy(DeployCmd),
+    Lock(LockCmd),
     Version(BasicVersionCmd),
 }
 
@@ -25,6 +27,7 @@ impl CdkCommand {
             CdkCommand::Build(opt) => opt.process(),
             CdkCommand::Test(opt) => opt.process(),
             CdkCommand::Deploy(opt) => opt.process(),
+            CdkCommand::Lock(opt) => opt.process(),
             CdkCommand::Generate(opt) => opt.process(),
             CdkCommand::Version(opt) => opt.process("CDK"),
         }
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+
+use fluvio::FluvioClusterConfig;
+use fluvio_connector_common::smartmodule::lock_smartmodules;
+use fluvio_connector_package::config::ConnectorConfig;
+use fluvio_connector_package::lock::SMARTMODULE_LOCK_FILENAME;
+use fluvio_sc_schema::smartmodule::SmartModuleApiClient;
+
+/// Pin the SmartModules used by the transforms of a connector config to the
+/// versions installed in the cluster
+#[derive(Debug, Parser)]
+pub struct LockCmd {
+    /// Path to configuration file in YAML format
+    #[arg(short, long, value_name = "PATH")]
+    config: PathBuf,
+
+    /// Path of the lock file, defaults to `smartmodules.lock` next to the
+    /// config file, where connectors look it up at startup
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+impl LockCmd {
+    pub(crate) fn process(self) -> Result<()> {
+        let config = ConnectorConfig::from_file(&self.config)?;
+        let output = self.output.unwrap_or_else(|| {
+            self.config
+                .parent()
+                .map(|dir| dir.join(SMARTMODULE_LOCK_FILENAME))
+                .unwrap_or_else(|| PathBuf::from(SMARTMODULE_LOCK_FILENAME))
+        });
+
+        let lock = fluvio_future::task::run_block_on(async {
+            let api_client =
+                SmartModuleApiClient::connect_with_config(FluvioClusterConfig::load()?.try_into()?)
+                    .await?;
+            lock_smartmodules(&config, &api_client).await
+        })?;
+
+        lock.write_to_file(&output)?;
+
+        for locked in lock.smartmodules.iter() {
+            println!("{} -> {} ({})", locked.uses, locked.name, locked.sha256);
+        }
+        println!("Lock file written to {}", output.display());
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ mod generate;
 mod build;
 mod test;
 mod deploy;
+mod lock;
 
 pub(crate) mod utils;
 
@@ -0,0 +1,266 @@
+//! Archive Extraction Limits
+//!
+//! Artifacts are extracted from archives downloaded from mirrors which may be
+//! compromised, so extraction is metered by an [`ExtractionBudget`]: it
+//! fails with an [`ExtractionError`] once an archive has too many entries,
+//! extracts more bytes than allowed, or expands far beyond its compressed
+//! size, before a decompression bomb fills the disk.
+//!
+//! The limits default to [`ExtractionLimits::default`] and are overridden
+//! with `FLUVIO_EXTRACT_MAX_SIZE`, `FLUVIO_EXTRACT_MAX_ENTRIES` and
+//! `FLUVIO_EXTRACT_MAX_RATIO`.
+
+use std::io::{ErrorKind, Read, Write};
+
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+pub const MAX_SIZE_ENV: &str = "FLUVIO_EXTRACT_MAX_SIZE";
+pub const MAX_ENTRIES_ENV: &str = "FLUVIO_EXTRACT_MAX_ENTRIES";
+pub const MAX_RATIO_ENV: &str = "FLUVIO_EXTRACT_MAX_RATIO";
+
+/// Default limit of the bytes extracted from an archive
+pub const DEFAULT_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Default limit of the entries of an archive
+pub const DEFAULT_MAX_ENTRIES: u64 = 10_000;
+
+/// Default limit of the ratio of extracted to compressed bytes, binaries
+/// rarely compress more than 10 times
+pub const DEFAULT_MAX_RATIO: u64 = 200;
+
+/// Extracted bytes below which the compression ratio is not checked, small
+/// text files such as completions compress very well
+pub const RATIO_CHECK_THRESHOLD: u64 = 1024 * 1024;
+
+/// Size of the reads of [`ExtractionBudget::copy`]
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ExtractionError {
+    #[error("Archive has more than {limit} entries")]
+    TooManyEntries { limit: u64 },
+    #[error("Archive extracts to more than {limit} bytes")]
+    TooLarge { limit: u64 },
+    #[error(
+        "Archive entry {name} expands more than {limit} times its compressed size, possible decompression bomb"
+    )]
+    CompressionRatio { name: String, limit: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtractionLimits {
+    /// Bytes extracted from an archive, across its entries
+    pub max_size: u64,
+    pub max_entries: u64,
+    /// Ratio of extracted to compressed bytes, of each entry and of the
+    /// whole archive
+    pub max_ratio: u64,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_SIZE,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_ratio: DEFAULT_MAX_RATIO,
+        }
+    }
+}
+
+impl ExtractionLimits {
+    /// Default limits, overridden by the environment
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+
+        Ok(Self {
+            max_size: env_limit(MAX_SIZE_ENV, defaults.max_size)?,
+            max_entries: env_limit(MAX_ENTRIES_ENV, defaults.max_entries)?,
+            max_ratio: env_limit(MAX_RATIO_ENV, defaults.max_ratio)?,
+        })
+    }
+
+    /// Budget for extracting an archive of `archive_size` bytes
+    pub fn budget(self, archive_size: u64) -> ExtractionBudget {
+        ExtractionBudget {
+            limits: self,
+            archive_size,
+            extracted: 0,
+            entries: 0,
+        }
+    }
+}
+
+fn env_limit(name: &str, default: u64) -> Result<u64> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .with_context(|| format!("Invalid {name} value \"{value}\"")),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Entries and bytes extracted from an archive so far, see
+/// [`ExtractionLimits::budget`]
+#[derive(Clone, Debug)]
+pub struct ExtractionBudget {
+    limits: ExtractionLimits,
+    archive_size: u64,
+    extracted: u64,
+    entries: u64,
+}
+
+impl ExtractionBudget {
+    /// Checks the entry count announced by the archive, e.g. by the central
+    /// directory of a zip archive
+    pub fn check_entries(&self, entries: u64) -> Result<(), ExtractionError> {
+        if entries > self.limits.max_entries {
+            return Err(ExtractionError::TooManyEntries {
+                limit: self.limits.max_entries,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Counts an entry of the archive, whether or not it is extracted
+    pub fn count_entry(&mut self) -> Result<(), ExtractionError> {
+        self.entries += 1;
+        self.check_entries(self.entries)
+    }
+
+    /// Copies the entry `name` from `reader` to `writer`, failing with an
+    /// [`ExtractionError`] as soon as a limit is exceeded. `compressed_size`
+    /// is the size of the entry in the archive when known.
+    pub fn copy(
+        &mut self,
+        name: &str,
+        compressed_size: Option<u64>,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+    ) -> Result<u64> {
+        let mut chunk = vec![0u8; COPY_CHUNK_SIZE];
+        let mut written = 0u64;
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            written += read as u64;
+            self.extracted += read as u64;
+            self.check(name, written, compressed_size)?;
+            writer.write_all(&chunk[..read])?;
+        }
+
+        Ok(written)
+    }
+
+    fn check(
+        &self,
+        name: &str,
+        written: u64,
+        compressed_size: Option<u64>,
+    ) -> Result<(), ExtractionError> {
+        let limits = &self.limits;
+
+        if self.extracted > limits.max_size {
+            return Err(ExtractionError::TooLarge {
+                limit: limits.max_size,
+            });
+        }
+
+        let exceeds_ratio = |extracted: u64, compressed: u64| {
+            extracted > RATIO_CHECK_THRESHOLD
+                && extracted > compressed.max(1).saturating_mul(limits.max_ratio)
+        };
+
+        if compressed_size.is_some_and(|compressed| exceeds_ratio(written, compressed))
+            || exceeds_ratio(self.extracted, self.archive_size)
+        {
+            return Err(ExtractionError::CompressionRatio {
+                name: name.to_string(),
+                limit: limits.max_ratio,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ExtractionLimits {
+        ExtractionLimits {
+            max_size: 4 * RATIO_CHECK_THRESHOLD,
+            max_entries: 2,
+            max_ratio: 10,
+        }
+    }
+
+    fn copy(budget: &mut ExtractionBudget, size: usize, compressed: Option<u64>) -> Result<u64> {
+        budget.copy(
+            "fluvio",
+            compressed,
+            &mut vec![0u8; size].as_slice(),
+            &mut Vec::new(),
+        )
+    }
+
+    fn error(result: Result<u64>) -> ExtractionError {
+        result
+            .unwrap_err()
+            .downcast::<ExtractionError>()
+            .expect("limit error")
+    }
+
+    #[test]
+    fn limits_entries_and_size() {
+        let mut budget = limits().budget(u64::MAX);
+
+        budget.count_entry().unwrap();
+        budget.count_entry().unwrap();
+        assert_eq!(
+            budget.count_entry(),
+            Err(ExtractionError::TooManyEntries { limit: 2 })
+        );
+        assert!(budget.check_entries(3).is_err());
+
+        let size = 3 * RATIO_CHECK_THRESHOLD as usize;
+
+        assert_eq!(copy(&mut budget, size, None).unwrap(), size as u64);
+        assert_eq!(
+            error(copy(&mut budget, size, None)),
+            ExtractionError::TooLarge {
+                limit: 4 * RATIO_CHECK_THRESHOLD
+            }
+        );
+    }
+
+    #[test]
+    fn detects_compression_bombs() {
+        let size = 2 * RATIO_CHECK_THRESHOLD as usize;
+        let mut budget = limits().budget(u64::MAX);
+
+        // Small entries may compress very well
+        copy(&mut budget, RATIO_CHECK_THRESHOLD as usize, Some(1)).unwrap();
+        assert!(matches!(
+            error(copy(&mut budget, size, Some(1024))),
+            ExtractionError::CompressionRatio { limit: 10, .. }
+        ));
+
+        // Entries of unknown compressed size are checked against the archive
+        let mut budget = limits().budget(1024);
+
+        assert!(matches!(
+            error(copy(&mut budget, size, None)),
+            ExtractionError::CompressionRatio { .. }
+        ));
+        assert!(copy(&mut limits().budget(size as u64), size, None).is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+//! Failure Causes
+//!
+//! Errors are mostly reported as text. A [`Failure`] tags an error with the
+//! [`FailureKind`] of its cause where the cause is known, so callers such as
+//! FVM tell network failures, integrity failures and missing releases apart
+//! without parsing messages. [`failure_kind`] finds the cause of an error.
+
+use std::io::ErrorKind;
+
+use http::StatusCode;
+use thiserror::Error;
+
+use crate::extraction::ExtractionError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// A server could not be reached, or answered with a server error
+    Network,
+    /// Downloaded bytes do not match their digest, or an archive exceeds the
+    /// extraction limits
+    Integrity,
+    /// A release, version or artifact does not exist
+    NotFound,
+    /// Artifacts are not published for the target, or the host does not
+    /// meet the requirements of a release
+    IncompatibleTarget,
+    /// A resource is in use by other processes
+    Locked,
+}
+
+impl FailureKind {
+    /// Kind of failure reported by a response with `status`
+    pub fn of_status(status: StatusCode) -> Option<Self> {
+        if status == StatusCode::NOT_FOUND {
+            Some(Self::NotFound)
+        } else if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Some(Self::Network)
+        } else {
+            None
+        }
+    }
+
+    /// Kind of failure reported by the GitHub API client
+    pub fn of_github(err: &octocrab::Error) -> Option<Self> {
+        match err {
+            octocrab::Error::GitHub { source, .. } => {
+                Self::of_status(StatusCode::from_u16(source.status_code.as_u16()).ok()?)
+            }
+            octocrab::Error::Http { .. }
+            | octocrab::Error::Hyper { .. }
+            | octocrab::Error::Service { .. } => Some(Self::Network),
+            _ => None,
+        }
+    }
+}
+
+/// Error tagged with the kind of its cause, displayed as its message
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(kind: FailureKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// Error for a response with `status`, tagged when the status tells the
+/// kind of failure
+pub fn status_error(status: StatusCode, message: impl Into<String>) -> anyhow::Error {
+    match FailureKind::of_status(status) {
+        Some(kind) => Failure::new(kind, message).into(),
+        None => anyhow::Error::msg(message.into()),
+    }
+}
+
+/// Error of the GitHub API client, tagged when its kind is known
+pub fn github_error(err: &octocrab::Error, message: impl Into<String>) -> anyhow::Error {
+    match FailureKind::of_github(err) {
+        Some(kind) => Failure::new(kind, message).into(),
+        None => anyhow::Error::msg(message.into()),
+    }
+}
+
+/// Kind of the first cause of `err` whose kind is known
+pub fn failure_kind(err: &anyhow::Error) -> Option<FailureKind> {
+    err.chain().find_map(|cause| {
+        if let Some(failure) = cause.downcast_ref::<Failure>() {
+            return Some(failure.kind);
+        }
+
+        if cause.is::<ExtractionError>() {
+            return Some(FailureKind::Integrity);
+        }
+
+        let io = cause.downcast_ref::<std::io::Error>()?;
+
+        matches!(
+            io.kind(),
+            ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::HostUnreachable
+                | ErrorKind::NetworkUnreachable
+                | ErrorKind::NetworkDown
+                | ErrorKind::TimedOut
+        )
+        .then_some(FailureKind::Network)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn finds_the_kind_of_the_cause() {
+        let err = anyhow::Error::from(Failure::new(FailureKind::Integrity, "checksum mismatch"))
+            .context("Failed to install fluvio");
+
+        assert_eq!(failure_kind(&err), Some(FailureKind::Integrity));
+        assert_eq!(err.root_cause().to_string(), "checksum mismatch");
+
+        let err = Err::<(), _>(std::io::Error::from(ErrorKind::ConnectionRefused))
+            .context("Failed to download")
+            .unwrap_err();
+
+        assert_eq!(failure_kind(&err), Some(FailureKind::Network));
+        assert_eq!(
+            failure_kind(&ExtractionError::TooManyEntries { limit: 1 }.into()),
+            Some(FailureKind::Integrity)
+        );
+        assert_eq!(failure_kind(&anyhow::anyhow!("unknown")), None);
+        assert_eq!(
+            failure_kind(&status_error(StatusCode::NOT_FOUND, "missing")),
+            Some(FailureKind::NotFound)
+        );
+        assert_eq!(
+            failure_kind(&status_error(StatusCode::BAD_GATEWAY, "unavailable")),
+            Some(FailureKind::Network)
+        );
+        assert_eq!(
+            failure_kind(&status_error(StatusCode::FORBIDDEN, "denied")),
+            None
+        );
+    }
+}
@@ -0,0 +1,671 @@
+//! Test Fixture Server
+//!
+//! A local HTTP server serving synthetic releases, raw repository files and
+//! Hub packages, so integration tests exercise the download and install
+//! pipeline without reaching GitHub or the Hub. Enabled with the `fixture`
+//! feature, usually from `dev-dependencies`.
+//!
+//! The server answers:
+//!
+//! - the GitHub releases and contents API under `/api/v3`, point a
+//!   [`GitHubRepo`] at it with [`FixtureServer::github_repo`]
+//! - raw repository files under `/raw`, see [`GitHubRepo::raw_file_url`]
+//! - release asset downloads under `/download/<tag>/<asset>`
+//! - Hub binary package downloads, with [`FixtureServer::url`] as the remote
+//! - any other path registered with [`FixtureServer::route`]
+//!
+//! Every repository is served the same releases and files.
+
+mod release;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use anyhow::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde_json::json;
+
+use crate::github::GitHubRepo;
+use crate::hub::HUB_API_BPKG_DOWNLOAD;
+
+pub use release::{FixtureAsset, FixtureRelease, zip_archive};
+
+/// Prefix of the GitHub API, as served by GitHub Enterprise Server
+const API_PREFIX: &str = "/api/v3";
+
+/// A canned response served by the fixture server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl FixtureResponse {
+    pub fn new(status: u16, content_type: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type: content_type.into(),
+            body: body.into(),
+        }
+    }
+
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self::new(200, "application/json", value.to_string())
+    }
+
+    pub fn bytes(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200, "application/octet-stream", body)
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(
+            404,
+            "application/json",
+            json!({
+                "message": "Not Found",
+                "documentation_url": "https://docs.github.com/rest",
+            })
+            .to_string(),
+        )
+    }
+}
+
+/// A request received by the fixture server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureRequest {
+    pub method: String,
+    /// Path and query of the request
+    pub target: String,
+    /// `Range` header of the request
+    pub range: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct FixtureState {
+    releases: Vec<FixtureRelease>,
+    /// Repository files by ref (branch or tag) and path
+    files: HashMap<(String, String), Vec<u8>>,
+    routes: HashMap<String, FixtureResponse>,
+    requests: Vec<FixtureRequest>,
+}
+
+/// Local HTTP server for integration tests, stopped on drop
+pub struct FixtureServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<FixtureState>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FixtureServer {
+    /// Starts the server on a free port of the loopback interface
+    pub fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(FixtureState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            let base_url = format!("http://{addr}");
+
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+
+                    if let Err(err) = serve(stream, &state, &base_url) {
+                        tracing::debug!(%err, "Fixture server failed to serve request");
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:41234`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Repository served by the fixture server
+    pub fn github_repo(&self) -> GitHubRepo {
+        GitHubRepo::default().with_api_url(format!("{}{API_PREFIX}", self.url()))
+    }
+
+    /// Publishes `release`, the VERSION file at the release tag holds the
+    /// release version. Releases are listed from the most recently added.
+    pub fn add_release(&self, release: FixtureRelease) {
+        let mut state = self.state();
+
+        state.files.insert(
+            (release.tag.clone(), "VERSION".to_string()),
+            format!("{}\n", release.version).into_bytes(),
+        );
+        state
+            .releases
+            .retain(|published| published.tag != release.tag);
+        state.releases.push(release);
+    }
+
+    /// Serves `contents` as the repository file at `path` on `git_ref`, e.g.
+    /// `release-tools/eol.json` on `master`
+    pub fn add_file(&self, git_ref: &str, path: &str, contents: impl Into<Vec<u8>>) {
+        self.state()
+            .files
+            .insert((git_ref.to_string(), path.to_string()), contents.into());
+    }
+
+    /// Serves the binary package `group/name` at `version` for `target` to
+    /// Hub clients using [`FixtureServer::url`] as remote
+    pub fn add_hub_package(
+        &self,
+        group: &str,
+        name: &str,
+        version: &str,
+        target: &str,
+        package: impl Into<Vec<u8>>,
+    ) {
+        self.route(
+            &format!("/{HUB_API_BPKG_DOWNLOAD}/{group}/{name}/{version}/{target}"),
+            FixtureResponse::bytes(package),
+        );
+    }
+
+    /// Serves `response` for requests to `path`, regardless of the query
+    pub fn route(&self, path: &str, response: FixtureResponse) {
+        self.state().routes.insert(path.to_string(), response);
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<FixtureRequest> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, FixtureState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        // Wakes up the accept loop so it notices the shutdown
+        let _ = TcpStream::connect(self.addr);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Reads a request from `stream` and writes its response, connections are
+/// closed after each response
+fn serve(mut stream: TcpStream, state: &Mutex<FixtureState>, base_url: &str) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+
+    reader.read_line(&mut request_line)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let mut content_length = 0;
+    let mut range = None;
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().unwrap_or_default();
+        } else if name.trim().eq_ignore_ascii_case("range") {
+            range = Some(value.trim().to_string());
+        }
+    }
+
+    let mut body = vec![0; content_length];
+
+    reader.read_exact(&mut body)?;
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+
+        state.requests.push(FixtureRequest {
+            method: method.to_string(),
+            target: target.to_string(),
+            range: range.clone(),
+        });
+        respond(&state, target, base_url)
+    };
+    let (response, content_range) = match range.as_deref() {
+        Some(range) if response.status == 200 => partial(response, range),
+        _ => (response, None),
+    };
+    let content_range = content_range
+        .map(|value| format!("Content-Range: {value}\r\n"))
+        .unwrap_or_default();
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{content_range}Connection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+fn respond(state: &FixtureState, target: &str, base_url: &str) -> FixtureResponse {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if let Some(response) = state.routes.get(path) {
+        return response.clone();
+    }
+
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let release_json = |release: &FixtureRelease| {
+        let id = state
+            .releases
+            .iter()
+            .position(|published| published.tag == release.tag)
+            .unwrap_or_default() as u64
+            + 1;
+
+        release.to_json(id, base_url)
+    };
+
+    match segments.as_slice() {
+        ["api", "v3", "repos", _, _, "releases"] => {
+            if query_param(query, "page").is_some_and(|page| page != "1") {
+                return FixtureResponse::json(&json!([]));
+            }
+
+            let releases: Vec<_> = state.releases.iter().rev().map(release_json).collect();
+
+            FixtureResponse::json(&json!(releases))
+        }
+        ["api", "v3", "repos", _, _, "releases", "latest"] => state
+            .releases
+            .iter()
+            .rev()
+            .find(|release| !release.draft && !release.prerelease && release.tag != "dev")
+            .map(|release| FixtureResponse::json(&release_json(release)))
+            .unwrap_or_else(FixtureResponse::not_found),
+        ["api", "v3", "repos", _, _, "releases", "tags", tag] => state
+            .releases
+            .iter()
+            .find(|release| release.tag == *tag)
+            .map(|release| FixtureResponse::json(&release_json(release)))
+            .unwrap_or_else(FixtureResponse::not_found),
+        ["api", "v3", "repos", _, _, "releases", id, "assets"] => {
+            if query_param(query, "page").is_some_and(|page| page != "1") {
+                return FixtureResponse::json(&json!([]));
+            }
+
+            id.parse::<usize>()
+                .ok()
+                .and_then(|id| state.releases.get(id.checked_sub(1)?))
+                .map(|release| FixtureResponse::json(&release_json(release)["assets"]))
+                .unwrap_or_else(FixtureResponse::not_found)
+        }
+        ["api", "v3", "repos", _, _, "contents", file_path @ ..] => {
+            let git_ref = query_param(query, "ref").unwrap_or("master");
+            let file_path = file_path.join("/");
+
+            state
+                .files
+                .get(&(git_ref.to_string(), file_path.clone()))
+                .map(|contents| {
+                    FixtureResponse::json(&content_json(&file_path, contents, base_url))
+                })
+                .unwrap_or_else(FixtureResponse::not_found)
+        }
+        ["raw", _, _, git_ref, file_path @ ..] => state
+            .files
+            .get(&(git_ref.to_string(), file_path.join("/")))
+            .map(|contents| FixtureResponse::new(200, "text/plain", contents.clone()))
+            .unwrap_or_else(FixtureResponse::not_found),
+        ["download", tag, name] => state
+            .releases
+            .iter()
+            .find(|release| release.tag == *tag)
+            .and_then(|release| release.asset(name))
+            .map(|asset| FixtureResponse::new(200, asset.content_type.clone(), asset.bytes.clone()))
+            .unwrap_or_else(FixtureResponse::not_found),
+        _ => FixtureResponse::not_found(),
+    }
+}
+
+/// A repository file as rendered by the GitHub contents API
+fn content_json(path: &str, contents: &[u8], base_url: &str) -> serde_json::Value {
+    let url = format!("{base_url}{API_PREFIX}/repos/fixture/fixture/contents/{path}");
+
+    json!({
+        "name": path.rsplit('/').next().unwrap_or(path),
+        "path": path,
+        "sha": "0000000000000000000000000000000000000000",
+        "encoding": "base64",
+        "content": BASE64.encode(contents),
+        "size": contents.len(),
+        "url": url,
+        "html_url": null,
+        "git_url": null,
+        "download_url": null,
+        "type": "file",
+        "_links": { "self": url, "git": null, "html": null },
+        "license": null,
+    })
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// The bytes of `response` requested by the `Range` header `range`, as
+/// `bytes=<start>-[<end>]` or `bytes=-<suffix length>`, with their
+/// `Content-Range`. Other ranges are ignored.
+fn partial(response: FixtureResponse, range: &str) -> (FixtureResponse, Option<String>) {
+    let len = response.body.len();
+    let bounds = range.strip_prefix("bytes=").and_then(|range| {
+        let (start, end) = range.split_once('-')?;
+
+        if start.is_empty() {
+            let suffix: usize = end.parse().ok()?;
+
+            return Some((len.saturating_sub(suffix), len.checked_sub(1)?));
+        }
+
+        let start: usize = start.parse().ok()?;
+        let end = match end {
+            "" => len.checked_sub(1)?,
+            end => end.parse::<usize>().ok()?.min(len.checked_sub(1)?),
+        };
+
+        (start <= end).then_some((start, end))
+    });
+
+    match bounds {
+        Some((start, end)) => (
+            FixtureResponse::new(
+                206,
+                response.content_type,
+                response.body[start..=end].to_vec(),
+            ),
+            Some(format!("bytes {start}-{end}/{len}")),
+        ),
+        None => (response, None),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        206 => "Partial Content",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::fvm::{CHANNELS_METADATA_PATH, Channel, Client};
+    use crate::remote_zip::RemoteZipIndex;
+    use crate::{htclient, sha256_digest_reader};
+
+    use super::*;
+
+    const TARGET: &str = "x86_64-unknown-linux-musl";
+
+    fn server() -> FixtureServer {
+        let server = FixtureServer::start().unwrap();
+        let release = FixtureRelease::new(Version::new(0, 11, 8))
+            .with_binary("fluvio", TARGET, b"#!/bin/sh\necho fluvio")
+            .unwrap()
+            .with_binary("fluvio-run", TARGET, b"#!/bin/sh\necho fluvio-run")
+            .unwrap();
+
+        server.add_release(release);
+        server.add_release(
+            FixtureRelease::tagged("dev", Version::parse("0.11.9-dev.1").unwrap())
+                .with_prerelease(true),
+        );
+        server
+    }
+
+    #[fluvio_future::test]
+    async fn serves_package_sets() {
+        let server = server();
+        let client = Client::new(server.github_repo());
+        let pkgset = client
+            .fetch_package_set(&Channel::Stable, TARGET)
+            .await
+            .unwrap();
+
+        assert_eq!(pkgset.pkgset, Version::new(0, 11, 8));
+        assert_eq!(pkgset.artifacts.len(), 2);
+
+        let latest = client
+            .fetch_package_set(&Channel::Latest, TARGET)
+            .await
+            .unwrap_err();
+
+        assert!(latest.to_string().contains("does not have artifacts"));
+    }
+
+    #[fluvio_future::test]
+    async fn resolves_channels_from_channels_metadata() {
+        let server = server();
+        let client = Client::new(server.github_repo());
+
+        server.add_release(
+            FixtureRelease::new(Version::new(0, 11, 7))
+                .with_binary("fluvio", TARGET, b"#!/bin/sh\necho fluvio")
+                .unwrap(),
+        );
+        assert_eq!(client.fetch_channels_metadata().await.unwrap(), None);
+
+        server.add_file(
+            "master",
+            CHANNELS_METADATA_PATH,
+            r#"{"channels":{"stable":"v0.11.7"}}"#,
+        );
+
+        let stable = client
+            .fetch_package_set(&Channel::Stable, TARGET)
+            .await
+            .unwrap();
+        let tagged = client
+            .fetch_package_set(&Channel::Tag(Version::new(0, 11, 8)), TARGET)
+            .await
+            .unwrap();
+
+        assert_eq!(stable.pkgset, Version::new(0, 11, 7));
+        assert_eq!(tagged.pkgset, Version::new(0, 11, 8));
+    }
+
+    #[fluvio_future::test]
+    async fn serves_downloads() {
+        let server = server();
+        let pkgset = Client::new(server.github_repo())
+            .fetch_package_set(&Channel::Tag(Version::new(0, 11, 8)), TARGET)
+            .await
+            .unwrap();
+        let fluvio = pkgset
+            .artifacts
+            .iter()
+            .find(|artifact| artifact.name == "fluvio")
+            .unwrap();
+        let archive = htclient::download(&fluvio.download_url).await.unwrap();
+        let checksum = htclient::get(format!("{}.sha256", fluvio.download_url))
+            .await
+            .unwrap();
+        let digest = sha256_digest_reader(archive.body().as_slice()).unwrap();
+
+        assert_eq!(
+            fluvio.sha256_digest.as_deref(),
+            Some(format!("sha256:{digest}").as_str())
+        );
+        assert_eq!(
+            String::from_utf8_lossy(checksum.body()),
+            format!("{digest}  fluvio-{TARGET}.zip\n")
+        );
+    }
+
+    #[fluvio_future::test]
+    async fn backfills_digests_of_releases_without_digests() {
+        let server = server();
+        let archive = zip_archive(&[("fluvio", b"#!/bin/sh\necho fluvio")]).unwrap();
+        let digest = sha256_digest_reader(archive.as_slice()).unwrap();
+        let client = Client::new(server.github_repo());
+
+        server.add_release(
+            FixtureRelease::new(Version::new(0, 10, 0))
+                .with_asset(
+                    FixtureAsset::new(format!("fluvio-{TARGET}.zip"), "application/zip", archive)
+                        .with_digest(None),
+                )
+                .with_asset(
+                    FixtureAsset::new("fluvio-run.zip", "application/zip", b"run".to_vec())
+                        .with_digest(None),
+                ),
+        );
+
+        let backfills = client.backfill_digests(2).await.unwrap();
+
+        assert_eq!(backfills.len(), 1);
+        assert_eq!(backfills[0].tag, "v0.10.0");
+        assert!(backfills[0].is_complete());
+        assert_eq!(backfills[0].hashed.len(), 2);
+        assert_eq!(
+            backfills[0].manifest.get(&format!("fluvio-{TARGET}.zip")),
+            Some(digest.as_str())
+        );
+
+        let published = client.backfill_release_digests("v0.11.8", 2).await.unwrap();
+
+        assert!(published.hashed.is_empty());
+        assert_eq!(published.manifest.len(), 4);
+    }
+
+    #[fluvio_future::test]
+    async fn inspects_archives_with_range_requests() {
+        let server = server();
+        let pkgset = Client::new(server.github_repo())
+            .fetch_package_set(&Channel::Tag(Version::new(0, 11, 8)), TARGET)
+            .await
+            .unwrap();
+        let mut fluvio = pkgset
+            .artifacts
+            .into_iter()
+            .find(|artifact| artifact.name == "fluvio")
+            .unwrap();
+        let index = fluvio.inspect_archive().await.unwrap();
+
+        assert_eq!(index.entries.len(), 1);
+        assert_eq!(index.entries[0].name, "fluvio");
+        assert_eq!(index.extracted_size(), 21);
+        assert!(server.requests().iter().all(|request| {
+            !request.target.starts_with("/download/") || request.range.is_some()
+        }));
+
+        fluvio.name = "smdk".to_string();
+        assert!(fluvio.inspect_archive().await.is_err());
+
+        // The central directory precedes the tail read first
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+        writer
+            .start_file("fluvio", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"fluvio").unwrap();
+        writer.set_comment("x".repeat(u16::MAX as usize));
+        server.route(
+            "/large.zip",
+            FixtureResponse::bytes(writer.finish().unwrap().into_inner()),
+        );
+
+        let index = RemoteZipIndex::fetch(&format!("{}/large.zip", server.url()))
+            .await
+            .unwrap();
+        let ranges: Vec<_> = server
+            .requests()
+            .into_iter()
+            .filter(|request| request.target == "/large.zip")
+            .filter_map(|request| request.range)
+            .collect();
+
+        assert_eq!(index.entries[0].name, "fluvio");
+        assert_eq!(ranges.len(), 2);
+    }
+
+    #[fluvio_future::test]
+    async fn serves_raw_files_and_hub_packages() {
+        let server = server();
+
+        server.add_file("master", "release-tools/eol.json", r#"{"notices":[]}"#);
+        server.add_hub_package("infinyon", "fvm-doctor", "0.1.0", TARGET, b"ipkg".to_vec());
+
+        let eol = Client::new(server.github_repo())
+            .fetch_eol_metadata()
+            .await
+            .unwrap();
+        let package = htclient::get(crate::hub::binary_package_uri(
+            &server.url(),
+            "infinyon",
+            "fvm-doctor",
+            "0.1.0",
+            TARGET,
+        ))
+        .await
+        .unwrap();
+
+        assert!(eol.notices.is_empty());
+        assert_eq!(package.body(), b"ipkg");
+        assert!(
+            server
+                .requests()
+                .iter()
+                .any(|request| request.target.starts_with("/raw/"))
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Synthetic Releases
+//!
+//! Releases are rendered as the GitHub API renders them, with assets
+//! downloaded from the fixture server.
+
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use semver::Version;
+use serde_json::{Value, json};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::fvm::AssetNameScheme;
+use crate::sha256_digest_reader;
+
+/// Timestamp used for every date rendered by the fixture server
+const FIXTURE_TIMESTAMP: &str = "2024-01-01T00:00:00Z";
+
+/// An asset published with a [`FixtureRelease`]
+#[derive(Clone, Debug)]
+pub struct FixtureAsset {
+    pub name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+    /// Digest published by the API, e.g. `sha256:<hex>`
+    pub digest: Option<String>,
+}
+
+impl FixtureAsset {
+    /// An asset published with the digest of `bytes`
+    pub fn new(name: impl Into<String>, content_type: impl Into<String>, bytes: Vec<u8>) -> Self {
+        let digest = sha256_digest_reader(bytes.as_slice())
+            .ok()
+            .map(|digest| format!("sha256:{digest}"));
+
+        Self {
+            name: name.into(),
+            content_type: content_type.into(),
+            bytes,
+            digest,
+        }
+    }
+
+    /// Publishes `digest` instead of the digest of the asset, e.g. to
+    /// exercise checksum failures
+    pub fn with_digest(mut self, digest: Option<String>) -> Self {
+        self.digest = digest;
+        self
+    }
+}
+
+/// A release served by the fixture server
+#[derive(Clone, Debug)]
+pub struct FixtureRelease {
+    pub tag: String,
+    /// Version in the VERSION file at the release tag
+    pub version: Version,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub assets: Vec<FixtureAsset>,
+}
+
+impl FixtureRelease {
+    /// A stable release tagged `v<version>`
+    pub fn new(version: Version) -> Self {
+        Self::tagged(format!("v{version}"), version)
+    }
+
+    /// A release tagged `tag`, e.g. the `dev` release
+    pub fn tagged(tag: impl Into<String>, version: Version) -> Self {
+        Self {
+            tag: tag.into(),
+            version,
+            draft: false,
+            prerelease: false,
+            assets: Vec::new(),
+        }
+    }
+
+    pub fn with_prerelease(mut self, prerelease: bool) -> Self {
+        self.prerelease = prerelease;
+        self
+    }
+
+    pub fn with_draft(mut self, draft: bool) -> Self {
+        self.draft = draft;
+        self
+    }
+
+    pub fn with_asset(mut self, asset: FixtureAsset) -> Self {
+        self.assets.push(asset);
+        self
+    }
+
+    /// Publishes the binary `name` for `target` as `<name>-<target>.zip`,
+    /// along with a `<name>-<target>.zip.sha256` checksum file
+    pub fn with_binary(self, name: &str, target: &str, contents: &[u8]) -> Result<Self> {
+        let asset_name = AssetNameScheme::CURRENT.build(name, &self.version, target, None);
+        let archive = zip_archive(&[(name, contents)])?;
+
+        Ok(self.with_archive(asset_name, archive))
+    }
+
+    /// Publishes the zip `archive` as `asset_name`, along with its checksum
+    /// file
+    pub fn with_archive(self, asset_name: impl Into<String>, archive: Vec<u8>) -> Self {
+        let asset = FixtureAsset::new(asset_name, "application/zip", archive);
+        let checksum = checksum_file(&asset);
+
+        self.with_asset(asset).with_asset(checksum)
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&FixtureAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    /// The release as rendered by the GitHub releases API
+    pub(crate) fn to_json(&self, id: u64, base_url: &str) -> Value {
+        let api_url = format!("{base_url}/api/v3/repos/fixture/fixture/releases/{id}");
+        let assets: Vec<Value> = self
+            .assets
+            .iter()
+            .enumerate()
+            .map(|(idx, asset)| {
+                let asset_id = id * 1000 + idx as u64;
+
+                json!({
+                    "url": format!("{base_url}/api/v3/repos/fixture/fixture/releases/assets/{asset_id}"),
+                    "browser_download_url": format!("{base_url}/download/{}/{}", self.tag, asset.name),
+                    "id": asset_id,
+                    "node_id": format!("RA_{asset_id}"),
+                    "name": asset.name,
+                    "label": null,
+                    "state": "uploaded",
+                    "content_type": asset.content_type,
+                    "size": asset.bytes.len(),
+                    "digest": asset.digest,
+                    "download_count": 0,
+                    "created_at": FIXTURE_TIMESTAMP,
+                    "updated_at": FIXTURE_TIMESTAMP,
+                    "uploader": null,
+                })
+            })
+            .collect();
+
+        json!({
+            "url": api_url,
+            "html_url": format!("{base_url}/releases/tag/{}", self.tag),
+            "assets_url": format!("{api_url}/assets"),
+            "upload_url": format!("{api_url}/assets{{?name,label}}"),
+            "tarball_url": null,
+            "zipball_url": null,
+            "id": id,
+            "node_id": format!("RE_{id}"),
+            "tag_name": self.tag,
+            "target_commitish": "master",
+            "name": self.tag,
+            "body": null,
+            "draft": self.draft,
+            "prerelease": self.prerelease,
+            "created_at": FIXTURE_TIMESTAMP,
+            "published_at": FIXTURE_TIMESTAMP,
+            "author": null,
+            "assets": assets,
+        })
+    }
+}
+
+/// Zip archive holding `entries` of name and contents
+pub fn zip_archive(entries: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, contents) in entries {
+        writer.start_file(*name, SimpleFileOptions::default().unix_permissions(0o755))?;
+        writer.write_all(contents)?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Checksum file of `asset` in the `sha256sum` format
+fn checksum_file(asset: &FixtureAsset) -> FixtureAsset {
+    let digest = asset
+        .digest
+        .as_deref()
+        .map(|digest| digest.trim_start_matches("sha256:"))
+        .unwrap_or_default();
+
+    FixtureAsset::new(
+        format!("{}.sha256", asset.name),
+        "text/plain",
+        format!("{digest}  {}\n", asset.name).into_bytes(),
+    )
+}
@@ -0,0 +1,110 @@
+//! Release Advisories
+//!
+//! Security and critical-bug advisories for Fluvio releases are listed in
+//! `release-tools/advisories.json` in the Fluvio repository. Each advisory
+//! covers a range of versions and may point to the version which fixes it.
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::github::GitHubRepo;
+
+/// Path of the advisories feed in the Fluvio repository
+pub const ADVISORIES_FEED_PATH: &str = "release-tools/advisories.json";
+
+/// URL of the advisories feed on the default branch of `repo`
+pub fn advisories_feed_url(repo: &GitHubRepo) -> String {
+    repo.raw_file_url("master", ADVISORIES_FEED_PATH)
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdvisoryKind {
+    Security,
+    CriticalBug,
+}
+
+impl std::fmt::Display for AdvisoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Security => write!(f, "security"),
+            Self::CriticalBug => write!(f, "critical-bug"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Advisory {
+    /// Identifier of the advisory, e.g. `FLV-2024-001`
+    pub id: String,
+    pub kind: AdvisoryKind,
+    /// Versions affected by the advisory, e.g. `>=0.11.0, <0.11.4`
+    pub versions: VersionReq,
+    /// One line description of the issue
+    pub summary: String,
+    /// Link to the details of the advisory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Version fixing the issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_in: Option<Version>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AdvisoriesFeed {
+    #[serde(default)]
+    pub advisories: Vec<Advisory>,
+}
+
+impl AdvisoriesFeed {
+    /// Advisories which apply to `version`
+    pub fn advisories_for(&self, version: &Version) -> Vec<&Advisory> {
+        self.advisories
+            .iter()
+            .filter(|advisory| advisory.versions.matches(version))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"{
+        "advisories": [
+            {
+                "id": "FLV-2024-001",
+                "kind": "security",
+                "versions": "<0.11.2",
+                "summary": "SmartModule parameters are logged in plain text",
+                "fixed_in": "0.11.2"
+            },
+            {
+                "id": "FLV-2024-002",
+                "kind": "critical-bug",
+                "versions": ">=0.11.0, <0.11.4",
+                "summary": "Consumer offsets are lost on SPU restart",
+                "url": "https://github.com/infinyon/fluvio/issues/1"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn finds_advisories_for_version() {
+        let feed: AdvisoriesFeed = serde_json::from_str(FEED).unwrap();
+        let ids = |version| {
+            feed.advisories_for(&version)
+                .into_iter()
+                .map(|advisory| advisory.id.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(Version::new(0, 11, 1)),
+            ["FLV-2024-001", "FLV-2024-002"]
+        );
+        assert_eq!(ids(Version::new(0, 11, 3)), ["FLV-2024-002"]);
+        assert!(ids(Version::new(0, 11, 4)).is_empty());
+        assert_eq!(feed.advisories[1].kind, AdvisoryKind::CriticalBug);
+    }
+}
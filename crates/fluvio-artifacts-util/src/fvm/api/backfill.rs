@@ -0,0 +1,199 @@
+//! Digest Backfill
+//!
+//! Computes the checksums manifest of releases published before GitHub
+//! computed asset digests, see [`ChecksumsManifest`]. Assets with a
+//! published SHA-256 digest are listed as published, the others are
+//! downloaded and hashed by a bounded number of worker threads, so a release
+//! with hundreds of assets neither downloads them one by one nor opens a
+//! connection per asset. Workers are scoped to the backfill of a release,
+//! no download outlives it.
+
+use std::sync::Mutex;
+
+use anyhow::{Result, anyhow};
+use http::StatusCode;
+
+use crate::fvm::{CHECKSUMS_ASSET_NAME, ChecksumsManifest};
+use crate::{htclient, sha256_digest_reader};
+
+/// Assets downloaded at once by default
+pub const DEFAULT_BACKFILL_CONCURRENCY: usize = 4;
+
+/// Asset which could not be downloaded or hashed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetFailure {
+    pub name: String,
+    pub error: String,
+}
+
+/// Checksums manifest computed for a release
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReleaseBackfill {
+    pub tag: String,
+    pub manifest: ChecksumsManifest,
+    /// Assets downloaded and hashed, the others are listed with their
+    /// published digest
+    pub hashed: Vec<String>,
+    /// Assets missing from the manifest
+    pub failures: Vec<AssetFailure>,
+}
+
+impl ReleaseBackfill {
+    /// Whether every asset of the release is listed in the manifest
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Release asset as listed by the releases API
+#[derive(Clone, Debug)]
+pub(super) struct BackfillAsset {
+    pub name: String,
+    pub download_url: String,
+    /// Published digest, e.g. `sha256:<hex>`
+    pub digest: Option<String>,
+}
+
+/// Whether a release publishing `assets` lacks digests for some of them and
+/// has no checksums manifest attached
+pub(super) fn lacks_digests(assets: &[BackfillAsset]) -> bool {
+    !assets
+        .iter()
+        .any(|asset| asset.name == CHECKSUMS_ASSET_NAME)
+        && assets.iter().any(|asset| published_sha256(asset).is_none())
+}
+
+/// Computes the checksums manifest of the release `tag` publishing `assets`,
+/// downloading the assets without a published digest on at most
+/// `concurrency` threads
+pub(super) fn backfill_assets(
+    tag: &str,
+    assets: &[BackfillAsset],
+    concurrency: usize,
+) -> ReleaseBackfill {
+    let mut manifest = ChecksumsManifest::default();
+    let mut pending = Vec::new();
+
+    for asset in assets
+        .iter()
+        .filter(|asset| asset.name != CHECKSUMS_ASSET_NAME)
+    {
+        match published_sha256(asset) {
+            Some(digest) => manifest.insert(&asset.name, digest),
+            None => pending.push(asset),
+        }
+    }
+
+    let queue = Mutex::new(pending.iter());
+    let results = Mutex::new(Vec::with_capacity(pending.len()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, pending.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let Some(asset) = queue.lock().unwrap_or_else(|err| err.into_inner()).next()
+                    else {
+                        break;
+                    };
+                    let result = hash_asset(&asset.download_url);
+
+                    tracing::debug!(tag, asset = asset.name, ok = result.is_ok(), "Hashed asset");
+                    results
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .push((asset.name.clone(), result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|err| err.into_inner());
+    let mut hashed = Vec::new();
+    let mut failures = Vec::new();
+
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (name, result) in results {
+        match result {
+            Ok(digest) => {
+                manifest.insert(&name, digest);
+                hashed.push(name);
+            }
+            Err(err) => failures.push(AssetFailure {
+                name,
+                error: err.to_string(),
+            }),
+        }
+    }
+
+    ReleaseBackfill {
+        tag: tag.to_string(),
+        manifest,
+        hashed,
+        failures,
+    }
+}
+
+/// Hex encoded SHA-256 digest published for `asset`
+fn published_sha256(asset: &BackfillAsset) -> Option<&str> {
+    asset
+        .digest
+        .as_deref()
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+}
+
+/// Downloads the asset at `url` and hashes it, on the calling thread
+fn hash_asset(url: &str) -> Result<String> {
+    let response = futures_lite::future::block_on(htclient::download(url))?;
+
+    if response.status() != StatusCode::OK {
+        return Err(anyhow!("Download failed with status {}", response.status()));
+    }
+
+    Ok(sha256_digest_reader(response.body().as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str, digest: Option<&str>) -> BackfillAsset {
+        BackfillAsset {
+            name: name.to_string(),
+            download_url: format!("http://127.0.0.1:1/{name}"),
+            digest: digest.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn lists_published_digests_and_reports_failures() {
+        let digest = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let published = format!("sha256:{digest}");
+        let assets = [
+            asset("fluvio-x86_64-unknown-linux-musl.zip", Some(&published)),
+            asset("smdk-x86_64-unknown-linux-musl.zip", None),
+        ];
+
+        assert!(lacks_digests(&assets));
+        assert!(!lacks_digests(&assets[..1]));
+        assert!(!lacks_digests(&[
+            assets[1].clone(),
+            asset(CHECKSUMS_ASSET_NAME, None)
+        ]));
+
+        let backfill = backfill_assets("v0.10.0", &assets, 2);
+
+        assert_eq!(
+            backfill
+                .manifest
+                .get("fluvio-x86_64-unknown-linux-musl.zip"),
+            Some(digest)
+        );
+        assert!(backfill.hashed.is_empty());
+        assert!(!backfill.is_complete());
+        assert_eq!(
+            backfill.failures[0].name,
+            "smdk-x86_64-unknown-linux-musl.zip"
+        );
+    }
+}
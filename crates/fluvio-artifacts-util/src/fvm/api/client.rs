@@ -1,108 +1,209 @@
 //! Hub FVM API Client
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::{Result};
 use octocrab::Octocrab;
 use semver::Version;
 
 use crate::{
-    REPO_OWNER, REPO_NAME,
-    fvm::{Artifact, Channel, PackageSet},
+    github::GitHubRepo,
+    fvm::{
+        AdvisoriesFeed, ArchiveFormat, Artifact, ArtifactMetadata, AssetNameScheme, Channel,
+        ChannelsMetadata, CompatibilityMatrix, ComponentSelection, CpuVariant, DEV_VERSION_CHANNEL,
+        EolMetadata, PackageManifest, PackageSet, ReleaseInfo, RequirementsMetadata,
+        SignedVersionPolicy, TransparencyManifest, VARIANT_SEPARATOR, advisories_feed_url,
+        channels_metadata_url, compare_releases, compatibility_matrix_url, eol_metadata_url,
+        is_stable_version, newest_stable_release, parse_release_tag, requirements_metadata_url,
+    },
+    failure::{Failure, FailureKind, github_error},
+    htclient::{self, ResponseExt},
 };
 
+use super::backfill::{BackfillAsset, ReleaseBackfill, backfill_assets, lacks_digests};
+use super::consistency::{DEV_RELEASE_CHECK_ATTEMPTS, DEV_RELEASE_CHECK_DELAY, version_drift};
+use super::rate_limit::with_rate_limit_retry;
+
 // List of binaries that are installable via FVM
 // We may consider a more flexible approach in the future
-const FVM_INSTALLABLE_BINARIES: &[&str] = &["fluvio", "fluvio-run", "cdk", "smdk"];
+pub const FVM_INSTALLABLE_BINARIES: &[&str] = &["fluvio", "fluvio-run", "cdk", "smdk"];
+
+/// Number of recent releases searched by [`Client::find_release_for`]
+const RELEASE_SEARCH_LIMIT: u8 = 100;
+
+/// Release assets requested per page, the maximum allowed by GitHub
+const ASSETS_PER_PAGE: u8 = 100;
+
+/// Upper bound of release asset pages fetched for a single release
+const MAX_ASSET_PAGES: u32 = 20;
+
+/// Number of prior stable releases checked for complete assets when the
+/// newest stable release is incomplete
+const STABLE_FALLBACK_LIMIT: usize = 5;
+
+/// Newest stable release skipped because some of its assets were not
+/// published for the requested architecture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteRelease {
+    pub version: Version,
+    /// Installable binaries published by the prior release but not by this one
+    pub missing: Vec<String>,
+}
+
+/// Target triple published by a release, see [`Client::list_targets`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseTarget {
+    pub target: String,
+    /// Installable binaries with a baseline asset for the target
+    pub binaries: Vec<String>,
+    /// Installable binaries published for other targets of the release but
+    /// not for this one
+    pub missing: Vec<String>,
+}
+
+impl ReleaseTarget {
+    /// Whether the target has every installable binary of the release
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Prior stable release with every installable binary published, see
+/// [`Client::fetch_stable_package_set_with_fallback`]
+struct CompleteRelease {
+    version: Version,
+    release: octocrab::models::repos::Release,
+    assets: Vec<ReleaseAsset>,
+    /// Installable binaries the release publishes
+    binaries: Vec<&'static str>,
+}
+
 /// HTTP Client for interacting with the Hub FVM API
 #[derive(Debug, Default)]
-pub struct Client;
+pub struct Client {
+    repo: GitHubRepo,
+}
 
 impl Client {
+    /// Client fetching the releases of `repo`
+    pub fn new(repo: GitHubRepo) -> Self {
+        Self { repo }
+    }
+
     /// Internal helper: resolves the GitHub release and semantic version for
-    /// a given FVM channel.
+    /// a given FVM channel. Channels listed in the channels metadata resolve
+    /// to the release tag they point to, see [`ChannelsMetadata`].
     async fn fetch_release_and_version(
         &self,
         channel: &Channel,
     ) -> Result<(octocrab::models::repos::Release, Version)> {
-        let octocrab = Octocrab::builder().build()?;
+        let octocrab = self.repo.octocrab()?;
+
+        if let Some(tag) = self.channel_pointer(channel).await {
+            tracing::debug!(%channel, tag, "Resolving channel from channels metadata");
+            return self.fetch_release_by_tag(&tag).await;
+        }
 
         let (release, version) = match channel {
             Channel::Stable => {
                 // we have to fetch last release id from github
-                let release = octocrab
-                    .repos(REPO_OWNER, REPO_NAME)
-                    .releases()
-                    .get_latest()
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Unable to retrieve stable release: {e}"))?;
+                let release = with_rate_limit_retry(|| async {
+                    octocrab
+                        .repos(&self.repo.owner, &self.repo.name)
+                        .releases()
+                        .get_latest()
+                        .await
+                })
+                .await
+                .map_err(|e| github_error(&e, format!("Unable to retrieve stable release: {e}")))?;
                 let version = Version::parse(release.tag_name.trim_start_matches('v'))?;
 
-                (release, version)
+                if is_stable_version(&version) {
+                    (release, version)
+                } else {
+                    // The release was published without the prerelease flag
+                    tracing::warn!(
+                        tag = release.tag_name,
+                        "Latest release is a pre-release, resolving the newest stable release"
+                    );
+                    self.fetch_newest_stable_release().await?
+                }
+            }
+            Channel::Minor(major, minor) => {
+                let page = with_rate_limit_retry(|| async {
+                    octocrab
+                        .repos(&self.repo.owner, &self.repo.name)
+                        .releases()
+                        .list()
+                        .per_page(RELEASE_SEARCH_LIMIT)
+                        .send()
+                        .await
+                })
+                .await
+                .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+                let mut releases: Vec<_> = page
+                    .items
+                    .into_iter()
+                    .filter(|release| !release.draft && !release.prerelease)
+                    .collect();
+                let (idx, version) = latest_patch_release(
+                    releases.iter().map(|release| release.tag_name.as_str()),
+                    *major,
+                    *minor,
+                )
+                .ok_or_else(|| {
+                    Failure::new(
+                        FailureKind::NotFound,
+                        format!("No stable release found for version {major}.{minor}"),
+                    )
+                })?;
+
+                (releases.swap_remove(idx), version)
             }
             Channel::Tag(ver) => {
                 let release_id = format!("v{}", ver);
-                let release = octocrab
-                    .repos(REPO_OWNER, REPO_NAME)
-                    .releases()
-                    .get_by_tag(&release_id)
-                    .await
-                    .map_err(|e| {
-                        if let octocrab::Error::GitHub { source, .. } = &e {
-                            anyhow::anyhow!(
+                let release = with_rate_limit_retry(|| async {
+                    octocrab
+                        .repos(&self.repo.owner, &self.repo.name)
+                        .releases()
+                        .get_by_tag(&release_id)
+                        .await
+                })
+                .await
+                .map_err(|e| {
+                    if let octocrab::Error::GitHub { source, .. } = &e {
+                        github_error(
+                            &e,
+                            format!(
                                 "Unable to retrieve release for tag {release_id}: {}",
                                 source.message
-                            )
-                        } else {
-                            anyhow::anyhow!("Unable to retrieve release for tag {release_id}: {e}")
-                        }
-                    })?;
-                (release, ver.clone())
-            }
-            Channel::Latest => {
-                let release = octocrab
-                    .repos(REPO_OWNER, REPO_NAME)
-                    .releases()
-                    .get_by_tag("dev")
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Unable to retrieve release for tag dev: {e}"))?;
-
-                // Derive the version for the `latest` (dev) channel from the
-                // VERSION file in the fluvio repository at the same ref as the
-                // dev release tag
-                let content_items = octocrab
-                    .repos(REPO_OWNER, REPO_NAME)
-                    .get_content()
-                    .path("VERSION")
-                    .r#ref(release.tag_name.clone())
-                    .send()
-                    .await
-                    .map_err(|e| {
-                        anyhow::anyhow!("Unable to retrieve VERSION file for dev release: {e}")
-                    })?;
-
-                let version_str = content_items
-                    .items
-                    .into_iter()
-                    .next()
-                    .and_then(|c| c.decoded_content())
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("VERSION file for dev release is missing or empty")
-                    })?;
-
-                let version = Version::parse(version_str.trim()).map_err(|e| {
-                    anyhow::anyhow!("Invalid version string in VERSION file for dev release: {e}")
+                            ),
+                        )
+                    } else {
+                        github_error(
+                            &e,
+                            format!("Unable to retrieve release for tag {release_id}: {e}"),
+                        )
+                    }
                 })?;
-
-                (release, version)
+                (release, ver.clone())
             }
+            Channel::Latest => fetch_consistent_dev_release(&octocrab, &self.repo).await?,
             Channel::Other(release) => {
-                let release = octocrab
-                    .repos(REPO_OWNER, REPO_NAME)
-                    .releases()
-                    .get_by_tag(release)
-                    .await
-                    .map_err(|e| {
-                        anyhow::anyhow!("Unable to retrieve release for tag {release}: {e}")
-                    })?;
+                let release = with_rate_limit_retry(|| async {
+                    octocrab
+                        .repos(&self.repo.owner, &self.repo.name)
+                        .releases()
+                        .get_by_tag(release)
+                        .await
+                })
+                .await
+                .map_err(|e| {
+                    github_error(
+                        &e,
+                        format!("Unable to retrieve release for tag {release}: {e}"),
+                    )
+                })?;
                 let version = Version::parse(release.tag_name.trim_start_matches('v'))?;
                 (release, version)
             }
@@ -111,67 +212,1414 @@ impl Client {
         Ok((release, version))
     }
 
+    /// Release tag `channel` points to in the channels metadata, `None` when
+    /// the metadata is not published, cannot be fetched or does not list the
+    /// channel
+    async fn channel_pointer(&self, channel: &Channel) -> Option<String> {
+        if channel.is_version_tag() {
+            return None;
+        }
+
+        match self.fetch_channels_metadata().await {
+            Ok(metadata) => metadata
+                .as_ref()
+                .and_then(|metadata| metadata.tag_for(channel))
+                .map(str::to_string),
+            Err(err) => {
+                tracing::warn!(%err, "Unable to fetch channels metadata, resolving channels from release tags");
+                None
+            }
+        }
+    }
+
+    /// Release tagged `tag` along with its version, the version of the `dev`
+    /// release is read from its VERSION file
+    async fn fetch_release_by_tag(
+        &self,
+        tag: &str,
+    ) -> Result<(octocrab::models::repos::Release, Version)> {
+        let octocrab = self.repo.octocrab()?;
+
+        if tag == DEV_VERSION_CHANNEL {
+            return fetch_consistent_dev_release(&octocrab, &self.repo).await;
+        }
+
+        let release = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .get_by_tag(tag)
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to retrieve release for tag {tag}: {e}")))?;
+        let version = parse_release_tag(&release.tag_name)
+            .ok_or_else(|| anyhow::anyhow!("Release tag {tag} is not a version"))?;
+
+        Ok((release, version))
+    }
+
+    /// Newest release with a stable version out of the most recent releases,
+    /// ranked by version rather than by publication date
+    async fn fetch_newest_stable_release(
+        &self,
+    ) -> Result<(octocrab::models::repos::Release, Version)> {
+        let octocrab = self.repo.octocrab()?;
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .list()
+                .per_page(RELEASE_SEARCH_LIMIT)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+        let mut releases: Vec<_> = page
+            .items
+            .into_iter()
+            .filter(|release| !release.draft && !release.prerelease)
+            .collect();
+        let (idx, version) =
+            newest_stable_release(releases.iter().map(|release| release.tag_name.as_str()))
+                .ok_or_else(|| Failure::new(FailureKind::NotFound, "No stable release found"))?;
+
+        Ok((releases.swap_remove(idx), version))
+    }
+
+    /// Searches the most recent releases for the ones shipping `binary` at
+    /// `version`, e.g. `fluvio-run` at `0.11.8`, and returns their tags
+    /// sorted from the most recent.
+    ///
+    /// Binaries are versioned together with the release they are published
+    /// in, so a release matches if its version is `version` and it has an
+    /// asset for `binary` on any target.
+    pub async fn find_release_for(&self, binary: &str, version: &Version) -> Result<Vec<String>> {
+        let octocrab = self.repo.octocrab()?;
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .list()
+                .per_page(RELEASE_SEARCH_LIMIT)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+        let mut tags = Vec::new();
+
+        for release in page.items {
+            let release_assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+            let assets: Vec<&str> = release_assets
+                .iter()
+                .map(|asset| asset.name.as_str())
+                .collect();
+
+            if !has_binary_asset(&assets, binary) {
+                continue;
+            }
+
+            let release_version = match Version::parse(release.tag_name.trim_start_matches('v')) {
+                Ok(release_version) => release_version,
+                Err(_) if release.tag_name == "dev" => {
+                    fetch_dev_version(&octocrab, &self.repo, &release.tag_name).await?
+                }
+                Err(_) => continue,
+            };
+
+            if release_version == *version {
+                tags.push(release.tag_name);
+            }
+        }
+
+        Ok(tags)
+    }
+
+    /// Fetches the channels metadata, `None` when it is not published
+    pub async fn fetch_channels_metadata(&self) -> Result<Option<ChannelsMetadata>> {
+        let url = channels_metadata_url(&self.repo);
+        let response = htclient::get(&url).await?;
+
+        if response.status() == htclient::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json().map(Some)
+    }
+
+    /// Fetches the security and critical-bug advisories for Fluvio releases
+    pub async fn fetch_advisories(&self) -> Result<AdvisoriesFeed> {
+        let url = advisories_feed_url(&self.repo);
+        let response = htclient::get(&url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
+    /// Fetches the end-of-life notices for Fluvio releases
+    pub async fn fetch_eol_metadata(&self) -> Result<EolMetadata> {
+        let url = eol_metadata_url(&self.repo);
+        let response = htclient::get(&url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
+    /// Fetches the minimum host system requirements of Fluvio releases
+    pub async fn fetch_requirements_metadata(&self) -> Result<RequirementsMetadata> {
+        let url = requirements_metadata_url(&self.repo);
+        let response = htclient::get(&url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
+    /// Fetches the CLI and platform compatibility matrix
+    pub async fn fetch_compatibility_matrix(&self) -> Result<CompatibilityMatrix> {
+        let url = compatibility_matrix_url(&self.repo);
+        let response = htclient::get(&url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
+    /// Versions of the most recent stable releases, sorted from the newest.
+    /// Drafts, prereleases and tags which are not versions are skipped.
+    pub async fn fetch_stable_versions(&self) -> Result<Vec<Version>> {
+        let octocrab = self.repo.octocrab()?;
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .list()
+                .per_page(RELEASE_SEARCH_LIMIT)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+
+        Ok(stable_versions(
+            page.items
+                .iter()
+                .filter(|release| !release.draft && !release.prerelease)
+                .map(|release| release.tag_name.as_str()),
+        ))
+    }
+
+    /// Fetches the transparency manifest published at `url`
+    pub async fn fetch_transparency_manifest(&self, url: &str) -> Result<TransparencyManifest> {
+        let response = htclient::get(url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
+    /// Fetches the signed version policy published at `url`, its signature
+    /// is not verified
+    pub async fn fetch_version_policy(&self, url: &str) -> Result<SignedVersionPolicy> {
+        let response = htclient::get(url).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Server responded with Status Code {} for url {url}",
+                response.status()
+            ));
+        }
+
+        response.json()
+    }
+
     /// Fetches a [`PackageSet`] from GitHub that includes only the
     /// "installable" binaries (e.g. fluvio, fluvio-run, cdk, smdk).
     pub async fn fetch_default_package_set(
         &self,
         channel: &Channel,
         arch: &str,
+    ) -> Result<PackageSet> {
+        self.fetch_default_package_set_with_variants(channel, arch, &[])
+            .await
+    }
+
+    /// Same as [`Client::fetch_default_package_set`] but prefers CPU
+    /// optimized artifacts from `variants` (sorted by preference) when the
+    /// release publishes them. Artifacts without a compatible variant fall
+    /// back to the baseline build.
+    pub async fn fetch_default_package_set_with_variants(
+        &self,
+        channel: &Channel,
+        arch: &str,
+        variants: &[CpuVariant],
     ) -> Result<PackageSet> {
         // Start from the unfiltered package set (which includes all
         // arch-specific artifacts) and then filter down to the
         // "installable" binaries.
-        let mut pkgset = self.fetch_package_set(channel, arch).await?;
+        let mut pkgset = self
+            .fetch_package_set_with_variants(channel, arch, variants)
+            .await?;
 
-        pkgset.artifacts.retain(|artifact| {
-            FVM_INSTALLABLE_BINARIES
-                .iter()
-                .any(|bin| artifact.name == *bin || artifact.name == format!("{bin}.exe"))
-        });
+        retain_installable(&mut pkgset.artifacts);
 
         if pkgset.artifacts.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Release \"{}\" does not have installable artifacts for architecture: \"{arch}\"",
-                pkgset.pkgset
-            ));
+            return Err(Failure::new(
+                FailureKind::IncompatibleTarget,
+                format!(
+                    "Release \"{}\" does not have installable artifacts for architecture: \"{arch}\"",
+                    pkgset.pkgset
+                ),
+            )
+            .into());
         }
 
         Ok(pkgset)
     }
 
+    /// Same as [`Client::fetch_default_package_set_with_variants`] for the
+    /// stable channel, checking that the newest stable release publishes
+    /// every installable binary the prior stable release did for `arch`.
+    ///
+    /// Assets are uploaded one by one after a release is created, so for a
+    /// while the newest release may miss some of them. With `fallback` the
+    /// most recent prior release with complete assets is returned along with
+    /// the skipped release, otherwise the incomplete release is an error.
+    pub async fn fetch_stable_package_set_with_fallback(
+        &self,
+        arch: &str,
+        variants: &[CpuVariant],
+        fallback: bool,
+    ) -> Result<(PackageSet, Option<IncompleteRelease>)> {
+        let (release, version) = self.fetch_release_and_version(&Channel::Stable).await?;
+        let octocrab = self.repo.octocrab()?;
+        let assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+        let published = installable_binaries(&assets, arch);
+
+        let previous = match self.fetch_previous_complete_release(&version, arch).await {
+            Ok(previous) => previous,
+            Err(err) => {
+                tracing::warn!(%err, "Unable to check prior stable releases");
+                None
+            }
+        };
+        let missing = match &previous {
+            Some(previous) => previous
+                .binaries
+                .iter()
+                .filter(|binary| !published.contains(binary))
+                .map(|binary| binary.to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if !published.is_empty() && missing.is_empty() {
+            let mut package_set = installable_package_set(version, &assets, arch, variants)?;
+
+            package_set.release = Some(release_info(&octocrab, &self.repo, &release).await);
+            return Ok((package_set, None));
+        }
+
+        let incomplete = IncompleteRelease { version, missing };
+
+        match previous {
+            Some(previous) if fallback => {
+                tracing::warn!(
+                    skipped = %incomplete.version,
+                    using = %previous.version,
+                    "Newest stable release is incomplete, using the prior release"
+                );
+                let mut package_set =
+                    installable_package_set(previous.version, &previous.assets, arch, variants)?;
+
+                package_set.release =
+                    Some(release_info(&octocrab, &self.repo, &previous.release).await);
+
+                Ok((package_set, Some(incomplete)))
+            }
+            _ if incomplete.missing.is_empty() => Err(Failure::new(
+                FailureKind::IncompatibleTarget,
+                format!(
+                    "Release \"{}\" does not have installable artifacts for architecture: \"{arch}\"",
+                    incomplete.version
+                ),
+            )
+            .into()),
+            _ => Err(Failure::new(
+                FailureKind::IncompatibleTarget,
+                format!(
+                    "Release \"{}\" is missing artifacts for architecture \"{arch}\": {}",
+                    incomplete.version,
+                    incomplete.missing.join(", ")
+                ),
+            )
+            .into()),
+        }
+    }
+
+    /// Most recent stable release prior to `version` publishing installable
+    /// binaries for `arch`
+    async fn fetch_previous_complete_release(
+        &self,
+        version: &Version,
+        arch: &str,
+    ) -> Result<Option<CompleteRelease>> {
+        let octocrab = self.repo.octocrab()?;
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .list()
+                .per_page(RELEASE_SEARCH_LIMIT)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+
+        let mut releases: Vec<_> = page
+            .items
+            .into_iter()
+            .filter(|release| !release.draft && !release.prerelease)
+            .filter_map(|release| {
+                let release_version = parse_release_tag(&release.tag_name)?;
+
+                (is_stable_version(&release_version)
+                    && compare_releases(&release_version, version).is_lt())
+                .then_some((release_version, release))
+            })
+            .collect();
+        releases.sort_unstable_by(|(a, _), (b, _)| compare_releases(b, a));
+
+        for (release_version, release) in releases.into_iter().take(STABLE_FALLBACK_LIMIT) {
+            let assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+            let binaries = installable_binaries(&assets, arch);
+
+            if !binaries.is_empty() {
+                return Ok(Some(CompleteRelease {
+                    version: release_version,
+                    release,
+                    assets,
+                    binaries,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Composes `components`, each fetched from the default package set of
+    /// its channel, into `base`. The artifacts replace those with the same
+    /// name in `base` and record the channel they were selected from.
+    pub async fn compose_package_set(
+        &self,
+        mut base: PackageSet,
+        components: &[ComponentSelection],
+        variants: &[CpuVariant],
+    ) -> Result<PackageSet> {
+        let mut by_channel: BTreeMap<&Channel, Vec<&str>> = BTreeMap::new();
+
+        for (idx, component) in components.iter().enumerate() {
+            if components[..idx].iter().any(|c| c.name == component.name) {
+                return Err(anyhow::anyhow!(
+                    "Component \"{}\" is selected more than once",
+                    component.name
+                ));
+            }
+
+            by_channel
+                .entry(&component.channel)
+                .or_default()
+                .push(&component.name);
+        }
+
+        for (channel, names) in by_channel {
+            let pkgset = self
+                .fetch_default_package_set_with_variants(channel, &base.arch, variants)
+                .await?;
+
+            for name in names {
+                let artifact = pkgset
+                    .artifacts
+                    .iter()
+                    .find(|artifact| artifact.name == name)
+                    .ok_or_else(|| {
+                        Failure::new(
+                            FailureKind::IncompatibleTarget,
+                            format!(
+                                "Release \"{}\" of channel {channel} does not publish {name} for architecture: \"{}\"",
+                                pkgset.pkgset,
+                                base.arch
+                            ),
+                        )
+                    })?;
+
+                base.compose(artifact.to_owned(), channel);
+            }
+        }
+
+        Ok(base)
+    }
+
+    /// Targets published by the release of `channel`, sorted by name, with
+    /// the installable binaries published for each of them
+    pub async fn list_targets(&self, channel: &Channel) -> Result<Vec<ReleaseTarget>> {
+        let (release, version) = self.fetch_release_and_version(channel).await?;
+        let octocrab = self.repo.octocrab()?;
+        let assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+
+        Ok(release_targets(&assets, &version))
+    }
+
     /// Fetches a [`PackageSet`] from GitHub without filtering binaries by the
     /// `FVM_INSTALLABLE_BINARIES` list.
     pub async fn fetch_package_set(&self, channel: &Channel, arch: &str) -> Result<PackageSet> {
-        let (release, version) = self.fetch_release_and_version(channel).await?;
+        self.fetch_package_set_with_variants(channel, arch, &[])
+            .await
+    }
 
-        let artifacts: Vec<_> = release
-            .assets
-            .iter()
-            .filter(|asset| asset.name.ends_with(&format!("{arch}.zip")))
-            .map(|asset| Artifact {
-                name: asset
-                    .name
-                    .trim_end_matches(&format!("-{arch}.zip"))
-                    .to_string(),
-                version: version.clone(),
-                download_url: asset.browser_download_url.to_string(),
-                sha256_digest: asset.digest.clone(),
-            })
-            .collect();
+    /// Same as [`Client::fetch_package_set`] but prefers CPU optimized
+    /// artifacts from `variants` (sorted by preference) when available.
+    pub async fn fetch_package_set_with_variants(
+        &self,
+        channel: &Channel,
+        arch: &str,
+        variants: &[CpuVariant],
+    ) -> Result<PackageSet> {
+        let (release, version) = self.fetch_release_and_version(channel).await?;
+        let octocrab = self.repo.octocrab()?;
+        let assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+        let artifacts = select_artifacts(&assets, &version, arch, variants);
 
         if artifacts.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Release \"{}\" does not have artifacts for architecture: \"{arch}\"",
-                release.tag_name
-            ));
+            return Err(Failure::new(
+                FailureKind::IncompatibleTarget,
+                format!(
+                    "Release \"{}\" does not have artifacts for architecture: \"{arch}\"{}",
+                    release.tag_name,
+                    available_targets(&assets, &version)
+                ),
+            )
+            .into());
         }
 
         let package_set = PackageSet {
             arch: arch.to_string(),
             pkgset: version,
             artifacts,
+            release: Some(release_info(&octocrab, &self.repo, &release).await),
         };
 
         Ok(package_set)
     }
+
+    /// Fetches the artifact names, sizes and digests of the default package
+    /// set of `channel` out of release metadata, without downloading any
+    /// artifact
+    pub async fn fetch_package_manifest(
+        &self,
+        channel: &Channel,
+        arch: &str,
+    ) -> Result<PackageManifest> {
+        self.fetch_package_manifest_with_variants(channel, arch, &[])
+            .await
+    }
+
+    /// Same as [`Client::fetch_package_manifest`] but prefers CPU optimized
+    /// artifacts from `variants` (sorted by preference) when available.
+    pub async fn fetch_package_manifest_with_variants(
+        &self,
+        channel: &Channel,
+        arch: &str,
+        variants: &[CpuVariant],
+    ) -> Result<PackageManifest> {
+        let (release, version) = self.fetch_release_and_version(channel).await?;
+        let octocrab = self.repo.octocrab()?;
+        let assets = fetch_release_assets(&octocrab, &self.repo, &release).await?;
+        let mut package_set = installable_package_set(version, &assets, arch, variants)?;
+
+        package_set.release = Some(release_info(&octocrab, &self.repo, &release).await);
+
+        Ok(package_manifest(package_set, &assets))
+    }
+
+    /// Computes the checksums manifest of each of the most recent releases
+    /// with assets lacking a published digest and no checksums manifest
+    /// attached, downloading at most `concurrency` assets at once. Draft
+    /// releases are skipped.
+    pub async fn backfill_digests(&self, concurrency: usize) -> Result<Vec<ReleaseBackfill>> {
+        let octocrab = self.repo.octocrab()?;
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .list()
+                .per_page(RELEASE_SEARCH_LIMIT)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list releases: {e}")))?;
+        let mut backfills = Vec::new();
+
+        for release in page.items.iter().filter(|release| !release.draft) {
+            let assets = backfill_assets_of(&octocrab, &self.repo, release).await?;
+
+            if lacks_digests(&assets) {
+                backfills.push(backfill_assets(&release.tag_name, &assets, concurrency));
+            }
+        }
+
+        Ok(backfills)
+    }
+
+    /// Computes the checksums manifest of the release tagged `tag`, whether
+    /// or not its assets have published digests, see
+    /// [`Client::backfill_digests`]
+    pub async fn backfill_release_digests(
+        &self,
+        tag: &str,
+        concurrency: usize,
+    ) -> Result<ReleaseBackfill> {
+        let octocrab = self.repo.octocrab()?;
+        let release = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&self.repo.owner, &self.repo.name)
+                .releases()
+                .get_by_tag(tag)
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to retrieve release for tag {tag}: {e}")))?;
+        let assets = backfill_assets_of(&octocrab, &self.repo, &release).await?;
+
+        Ok(backfill_assets(&release.tag_name, &assets, concurrency))
+    }
+}
+
+/// Every asset of `release`, as hashed by the digest backfill
+async fn backfill_assets_of(
+    octocrab: &Octocrab,
+    repo: &GitHubRepo,
+    release: &octocrab::models::repos::Release,
+) -> Result<Vec<BackfillAsset>> {
+    Ok(fetch_release_assets(octocrab, repo, release)
+        .await?
+        .into_iter()
+        .map(|asset| BackfillAsset {
+            name: asset.name,
+            download_url: asset.download_url,
+            digest: asset.digest,
+        })
+        .collect())
+}
+
+/// Manifest of `package_set`, sized after the release `assets` its
+/// artifacts are downloaded from
+fn package_manifest(package_set: PackageSet, assets: &[ReleaseAsset]) -> PackageManifest {
+    let artifacts = package_set
+        .artifacts
+        .into_iter()
+        .map(|artifact| {
+            let size = assets
+                .iter()
+                .find(|asset| asset.download_url == artifact.download_url)
+                .map(|asset| asset.size)
+                .unwrap_or_default();
+
+            ArtifactMetadata { artifact, size }
+        })
+        .collect();
+
+    PackageManifest {
+        pkgset: package_set.pkgset,
+        arch: package_set.arch,
+        artifacts,
+        release: package_set.release,
+    }
+}
+
+/// Tag, commit and publication date of `release`. Releases usually target
+/// a branch, in which case the commit is resolved from the release tag, and
+/// left unknown when that fails.
+async fn release_info(
+    octocrab: &Octocrab,
+    repo: &GitHubRepo,
+    release: &octocrab::models::repos::Release,
+) -> ReleaseInfo {
+    let commitish = &release.target_commitish;
+    let commit = if commitish.len() == 40 && commitish.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(commitish.to_ascii_lowercase())
+    } else {
+        match octocrab
+            .commits(&repo.owner, &repo.name)
+            .get(release.tag_name.as_str())
+            .await
+        {
+            Ok(commit) => Some(commit.sha),
+            Err(err) => {
+                tracing::debug!(%err, tag = release.tag_name, "Unable to resolve release commit");
+                None
+            }
+        }
+    };
+
+    ReleaseInfo {
+        tag: release.tag_name.clone(),
+        commit,
+        published_at: release.published_at,
+    }
+}
+
+/// Retrieves the `dev` release along with its version, reading it again
+/// after [`DEV_RELEASE_CHECK_DELAY`] while the VERSION file disagrees with the
+/// versions embedded in the release assets
+async fn fetch_consistent_dev_release(
+    octocrab: &Octocrab,
+    repo: &GitHubRepo,
+) -> Result<(octocrab::models::repos::Release, Version)> {
+    let mut attempt = 1;
+
+    loop {
+        let release = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&repo.owner, &repo.name)
+                .releases()
+                .get_by_tag("dev")
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to retrieve release for tag dev: {e}")))?;
+        let version = fetch_dev_version(octocrab, repo, &release.tag_name).await?;
+        let assets = fetch_release_assets(octocrab, repo, &release).await?;
+        let drift = version_drift(&version, assets.iter().map(|asset| asset.name.as_str()));
+
+        if drift.is_empty() {
+            return Ok((release, version));
+        }
+
+        let drift = drift
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if attempt >= DEV_RELEASE_CHECK_ATTEMPTS {
+            return Err(anyhow::anyhow!(
+                "The dev release is being published, VERSION {version} disagrees with assets for {drift}. Try again later"
+            ));
+        }
+
+        tracing::warn!(
+            "VERSION {version} disagrees with dev release assets for {drift}, retrying in {}s (attempt {attempt} of {DEV_RELEASE_CHECK_ATTEMPTS})",
+            DEV_RELEASE_CHECK_DELAY.as_secs()
+        );
+        fluvio_future::timer::sleep(DEV_RELEASE_CHECK_DELAY).await;
+        attempt += 1;
+    }
+}
+
+/// Derives the version of the `dev` release from the VERSION file in the
+/// fluvio repository at the same ref as the release tag
+async fn fetch_dev_version(octocrab: &Octocrab, repo: &GitHubRepo, tag: &str) -> Result<Version> {
+    let content_items = with_rate_limit_retry(|| async {
+        octocrab
+            .repos(&repo.owner, &repo.name)
+            .get_content()
+            .path("VERSION")
+            .r#ref(tag)
+            .send()
+            .await
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Unable to retrieve VERSION file for dev release: {e}"))?;
+
+    let version_str = content_items
+        .items
+        .into_iter()
+        .next()
+        .and_then(|c| c.decoded_content())
+        .ok_or_else(|| anyhow::anyhow!("VERSION file for dev release is missing or empty"))?;
+
+    Version::parse(version_str.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid version string in VERSION file for dev release: {e}"))
+}
+
+/// Position and version of the most recent patch release of `major.minor`
+/// out of the published release tags, prerelease versions are skipped
+fn latest_patch_release<'a>(
+    tags: impl IntoIterator<Item = &'a str>,
+    major: u64,
+    minor: u64,
+) -> Option<(usize, Version)> {
+    tags.into_iter()
+        .enumerate()
+        .filter_map(|(idx, tag)| Some((idx, parse_release_tag(tag)?)))
+        .filter(|(_, version)| {
+            version.major == major && version.minor == minor && is_stable_version(version)
+        })
+        .max_by(|(_, a), (_, b)| compare_releases(a, b))
+}
+
+/// Stable versions among release `tags`, sorted from the newest
+fn stable_versions<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<Version> {
+    let mut versions: Vec<Version> = tags
+        .into_iter()
+        .filter_map(parse_release_tag)
+        .filter(is_stable_version)
+        .collect();
+
+    versions.sort_unstable_by(|a, b| compare_releases(b, a));
+    versions.dedup();
+    versions
+}
+
+/// Retrieves every asset of `release`.
+///
+/// Releases embed a single page of assets, so when the embedded list is
+/// full the assets are listed again page by page, otherwise artifacts for
+/// some targets would appear to be missing.
+async fn fetch_release_assets(
+    octocrab: &Octocrab,
+    repo: &GitHubRepo,
+    release: &octocrab::models::repos::Release,
+) -> Result<Vec<ReleaseAsset>> {
+    if release.assets.len() < ASSETS_PER_PAGE as usize {
+        return Ok(release.assets.iter().map(ReleaseAsset::from).collect());
+    }
+
+    let release_id = release.id.into_inner();
+
+    collect_asset_pages(|page| async move {
+        let page = with_rate_limit_retry(|| async {
+            octocrab
+                .repos(&repo.owner, &repo.name)
+                .releases()
+                .assets(release_id)
+                .per_page(ASSETS_PER_PAGE)
+                .page(page)
+                .send()
+                .await
+        })
+        .await
+        .map_err(|e| github_error(&e, format!("Unable to list release assets: {e}")))?;
+
+        Ok(page.items.iter().map(ReleaseAsset::from).collect())
+    })
+    .await
+}
+
+/// Fetches pages of assets starting from page 1 until a page is not full
+async fn collect_asset_pages<F, Fut>(mut fetch_page: F) -> Result<Vec<ReleaseAsset>>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<Vec<ReleaseAsset>>>,
+{
+    let mut assets: Vec<ReleaseAsset> = Vec::new();
+
+    for page in 1..=MAX_ASSET_PAGES {
+        let items = fetch_page(page).await?;
+        let is_last = items.len() < ASSETS_PER_PAGE as usize;
+
+        for item in items {
+            // Assets published while paging may shift into an already fetched page
+            if !assets.iter().any(|asset| asset.name == item.name) {
+                assets.push(item);
+            }
+        }
+
+        if is_last {
+            return Ok(assets);
+        }
+    }
+
+    tracing::warn!(
+        pages = MAX_ASSET_PAGES,
+        "Release assets exceed the page limit, some assets may be missing"
+    );
+
+    Ok(assets)
+}
+
+/// Whether the release assets include `binary` for any target, assets are
+/// named `<binary>-<target>` followed by an optional `+<variant>` suffix and
+/// the archive extension, see [`AssetNameScheme`]
+fn has_binary_asset(assets: &[&str], binary: &str) -> bool {
+    let prefix = format!("{binary}-");
+    // `fluvio-` also prefixes the assets of `fluvio-run`
+    let other_prefixes: Vec<String> = FVM_INSTALLABLE_BINARIES
+        .iter()
+        .filter(|other| other.len() > binary.len() && other.starts_with(&prefix))
+        .map(|other| format!("{other}-"))
+        .collect();
+
+    assets.iter().any(|asset| {
+        ArchiveFormat::split(asset).is_some()
+            && asset.starts_with(&prefix)
+            && !other_prefixes.iter().any(|other| asset.starts_with(other))
+    })
+}
+
+/// Installable binaries with a baseline asset for `arch`
+fn installable_binaries(assets: &[ReleaseAsset], arch: &str) -> Vec<&'static str> {
+    let Some(scheme) = detect_scheme(assets, arch) else {
+        return Vec::new();
+    };
+
+    FVM_INSTALLABLE_BINARIES
+        .iter()
+        .copied()
+        .filter(|binary| {
+            assets.iter().any(|asset| {
+                scheme
+                    .parse(&asset.name, arch)
+                    .is_some_and(|name| name.binary == *binary && name.variant.is_none())
+            })
+        })
+        .collect()
+}
+
+/// Targets with assets of installable binaries, sorted by name.
+///
+/// Target triples are not known beforehand, so the name of each asset of an
+/// installable binary, stripped of the binary, version, variant and
+/// extension, is a candidate target. `fluvio-` also prefixes the assets of
+/// `fluvio-run`, leaving candidates such as `run-<target>` which are dropped
+/// as they end with another candidate.
+fn release_targets(assets: &[ReleaseAsset], version: &Version) -> Vec<ReleaseTarget> {
+    let versions = [format!("{version}-"), format!("v{version}-")];
+    let mut candidates = BTreeSet::new();
+
+    for asset in assets {
+        let Some((stem, _)) = ArchiveFormat::split(&asset.name) else {
+            continue;
+        };
+        let stem = stem
+            .split_once(VARIANT_SEPARATOR)
+            .map_or(stem, |(stem, _)| stem);
+
+        for binary in FVM_INSTALLABLE_BINARIES {
+            let Some(target) = stem
+                .strip_prefix(binary)
+                .and_then(|rest| rest.strip_prefix('-'))
+            else {
+                continue;
+            };
+            let target = versions
+                .iter()
+                .find_map(|version| target.strip_prefix(version.as_str()))
+                .unwrap_or(target);
+
+            candidates.insert(target);
+        }
+    }
+
+    let published: Vec<(&str, Vec<&str>)> = candidates
+        .iter()
+        .filter(|candidate| {
+            !candidates.iter().any(|other| {
+                candidate.len() > other.len() && candidate.ends_with(&format!("-{other}"))
+            })
+        })
+        .map(|target| (*target, installable_binaries(assets, target)))
+        .filter(|(_, binaries)| !binaries.is_empty())
+        .collect();
+
+    published
+        .iter()
+        .map(|(target, binaries)| {
+            let missing = FVM_INSTALLABLE_BINARIES
+                .iter()
+                .filter(|binary| {
+                    !binaries.contains(binary)
+                        && published.iter().any(|(_, other)| other.contains(binary))
+                })
+                .map(|binary| binary.to_string())
+                .collect();
+
+            ReleaseTarget {
+                target: target.to_string(),
+                binaries: binaries.iter().map(|binary| binary.to_string()).collect(),
+                missing,
+            }
+        })
+        .collect()
+}
+
+/// Targets published by a release, appended to errors about unpublished
+/// targets
+fn available_targets(assets: &[ReleaseAsset], version: &Version) -> String {
+    let targets: Vec<String> = release_targets(assets, version)
+        .into_iter()
+        .map(|target| target.target)
+        .collect();
+
+    if targets.is_empty() {
+        String::new()
+    } else {
+        format!(", available: {}", targets.join(", "))
+    }
+}
+
+/// Naming scheme of the assets published for `arch`
+fn detect_scheme(assets: &[ReleaseAsset], arch: &str) -> Option<AssetNameScheme> {
+    AssetNameScheme::detect(assets.iter().map(|asset| asset.name.as_str()), arch)
+}
+
+/// Keeps the artifacts of `FVM_INSTALLABLE_BINARIES`
+fn retain_installable(artifacts: &mut Vec<Artifact>) {
+    artifacts.retain(|artifact| {
+        FVM_INSTALLABLE_BINARIES
+            .iter()
+            .any(|bin| artifact.name == *bin || artifact.name == format!("{bin}.exe"))
+    });
+}
+
+/// Package set of the installable binaries of a release
+fn installable_package_set(
+    version: Version,
+    assets: &[ReleaseAsset],
+    arch: &str,
+    variants: &[CpuVariant],
+) -> Result<PackageSet> {
+    let mut artifacts = select_artifacts(assets, &version, arch, variants);
+
+    retain_installable(&mut artifacts);
+
+    if artifacts.is_empty() {
+        // Assets for `arch` named after an unknown convention
+        if detect_scheme(assets, arch).is_none()
+            && assets.iter().any(|asset| asset.name.contains(arch))
+        {
+            return Err(anyhow::anyhow!(
+                "Release \"{version}\" has assets for architecture \"{arch}\" which do not follow a known naming scheme"
+            ));
+        }
+
+        return Err(Failure::new(
+            FailureKind::IncompatibleTarget,
+            format!(
+                "Release \"{version}\" does not have installable artifacts for architecture: \"{arch}\"{}",
+                available_targets(assets, &version)
+            ),
+        )
+        .into());
+    }
+
+    Ok(PackageSet {
+        arch: arch.to_string(),
+        pkgset: version,
+        artifacts,
+        release: None,
+    })
+}
+
+/// Subset of GitHub release asset fields used to build artifacts
+struct ReleaseAsset {
+    name: String,
+    download_url: String,
+    digest: Option<String>,
+    /// Size in bytes
+    size: u64,
+}
+
+impl From<&octocrab::models::repos::Asset> for ReleaseAsset {
+    fn from(asset: &octocrab::models::repos::Asset) -> Self {
+        Self {
+            name: asset.name.to_owned(),
+            download_url: asset.browser_download_url.to_string(),
+            digest: asset.digest.clone(),
+            size: u64::try_from(asset.size).unwrap_or_default(),
+        }
+    }
+}
+
+/// Builds the artifacts for `arch` out of release assets, replacing baseline
+/// assets with the first published variant in `variants`.
+fn select_artifacts(
+    assets: &[ReleaseAsset],
+    version: &Version,
+    arch: &str,
+    variants: &[CpuVariant],
+) -> Vec<Artifact> {
+    let Some(scheme) = detect_scheme(assets, arch) else {
+        return Vec::new();
+    };
+
+    assets
+        .iter()
+        .filter_map(|asset| {
+            let name = scheme.parse(&asset.name, arch)?;
+
+            name.variant.is_none().then_some((asset, name))
+        })
+        .map(|(baseline, name)| {
+            let asset_version = name.version.as_ref().unwrap_or(version);
+            let selected = variants.iter().find_map(|variant| {
+                let variant_name = scheme.build(&name.binary, asset_version, arch, Some(*variant));
+
+                assets
+                    .iter()
+                    .find(|asset| asset.name == variant_name)
+                    .map(|asset| (asset, Some(*variant)))
+            });
+
+            if selected.is_none() && !variants.is_empty() {
+                tracing::debug!(
+                    asset = baseline.name,
+                    "No optimized variant published, using baseline artifact"
+                );
+            }
+
+            let (asset, variant) = selected.unwrap_or((baseline, None));
+
+            Artifact {
+                name: name.binary,
+                version: version.clone(),
+                download_url: asset.download_url.to_owned(),
+                sha256_digest: asset.digest.clone(),
+                variant,
+                channel: None,
+                entry: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARCH: &str = "x86_64-unknown-linux-musl";
+
+    fn asset(name: &str) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            download_url: format!("https://example.com/{name}"),
+            digest: Some(format!("sha256:{name}")),
+            size: name.len() as u64,
+        }
+    }
+
+    fn release_assets() -> Vec<ReleaseAsset> {
+        vec![
+            asset("fluvio-x86_64-unknown-linux-musl.zip"),
+            asset("fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip"),
+            asset("fluvio-run-x86_64-unknown-linux-musl.zip"),
+            asset("fluvio-aarch64-unknown-linux-musl.zip"),
+        ]
+    }
+
+    #[test]
+    fn selects_best_published_variant() {
+        let version = Version::new(0, 11, 0);
+        let artifacts = select_artifacts(
+            &release_assets(),
+            &version,
+            ARCH,
+            &[CpuVariant::X86_64V4, CpuVariant::X86_64V3],
+        );
+
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0].name, "fluvio");
+        assert_eq!(artifacts[0].variant, Some(CpuVariant::X86_64V3));
+        assert_eq!(
+            artifacts[0].download_url,
+            "https://example.com/fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip"
+        );
+        assert_eq!(
+            artifacts[0].sha256_digest.as_deref(),
+            Some("sha256:fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip")
+        );
+        assert_eq!(artifacts[1].name, "fluvio-run");
+        assert_eq!(
+            artifacts[1].variant, None,
+            "artifacts without variants fall back to baseline"
+        );
+    }
+
+    #[test]
+    fn matches_binary_assets() {
+        let assets = [
+            "fluvio-run-x86_64-unknown-linux-musl.zip",
+            "cdk-aarch64-apple-darwin+apple-m1.zip",
+            "install.sh",
+        ];
+
+        assert!(has_binary_asset(&assets, "fluvio-run"));
+        assert!(has_binary_asset(&assets, "cdk"));
+        assert!(!has_binary_asset(&assets, "fluvio"));
+        assert!(!has_binary_asset(&assets, "smdk"));
+        assert!(has_binary_asset(
+            &["fluvio-x86_64-pc-windows-gnu.zip"],
+            "fluvio"
+        ));
+    }
+
+    #[test]
+    fn lists_installable_binaries_for_arch() {
+        let mut assets = release_assets();
+        assets.push(asset("smdk-aarch64-unknown-linux-musl.zip"));
+        assets.push(asset("fluvio-channel-x86_64-unknown-linux-musl.zip"));
+
+        assert_eq!(
+            installable_binaries(&assets, ARCH),
+            vec!["fluvio", "fluvio-run"]
+        );
+        assert_eq!(
+            installable_binaries(&assets, "aarch64-unknown-linux-musl"),
+            vec!["fluvio", "smdk"]
+        );
+        assert!(installable_binaries(&assets, "x86_64-apple-darwin").is_empty());
+    }
+
+    #[test]
+    fn lists_release_targets() {
+        let mut assets = release_assets();
+        assets.push(asset("smdk-aarch64-unknown-linux-musl.zip"));
+        assets.push(asset("fluvio-channel-x86_64-unknown-linux-musl.zip"));
+        assets.push(asset("fluvio-0.11.8-aarch64-apple-darwin.tar.gz"));
+        assets.push(asset("install.sh"));
+        let version = Version::parse("0.11.8").unwrap();
+
+        let targets = release_targets(&assets, &version);
+        let names: Vec<_> = targets
+            .iter()
+            .map(|target| target.target.as_str())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "aarch64-apple-darwin",
+                "aarch64-unknown-linux-musl",
+                "x86_64-unknown-linux-musl"
+            ]
+        );
+        assert_eq!(targets[1].binaries, vec!["fluvio", "smdk"]);
+        assert_eq!(targets[1].missing, vec!["fluvio-run"]);
+        assert_eq!(targets[2].binaries, vec!["fluvio", "fluvio-run"]);
+        assert_eq!(targets[2].missing, vec!["smdk"]);
+        assert!(!targets[0].is_complete());
+
+        let err =
+            installable_package_set(version, &assets, "x86_64-apple-darwin", &[]).unwrap_err();
+
+        assert!(err.to_string().ends_with(
+            "available: aarch64-apple-darwin, aarch64-unknown-linux-musl, x86_64-unknown-linux-musl"
+        ));
+    }
+
+    #[test]
+    fn builds_installable_package_set() {
+        let mut assets = release_assets();
+        assets.push(asset("fluvio-channel-x86_64-unknown-linux-musl.zip"));
+        let version = Version::parse("0.11.8").unwrap();
+
+        let pkgset = installable_package_set(version.clone(), &assets, ARCH, &[]).unwrap();
+        let names: Vec<_> = pkgset
+            .artifacts
+            .iter()
+            .map(|art| art.name.as_str())
+            .collect();
+
+        assert_eq!(pkgset.pkgset, version);
+        assert_eq!(names, vec!["fluvio", "fluvio-run"]);
+        assert!(installable_package_set(version, &assets, "x86_64-apple-darwin", &[]).is_err());
+    }
+
+    #[test]
+    fn sizes_package_manifest_after_selected_assets() {
+        let assets = release_assets();
+        let version = Version::new(0, 11, 0);
+        let pkgset =
+            installable_package_set(version.clone(), &assets, ARCH, &[CpuVariant::X86_64V3])
+                .unwrap();
+
+        let manifest = package_manifest(pkgset, &assets);
+        let sizes: Vec<_> = manifest
+            .artifacts
+            .iter()
+            .map(|metadata| (metadata.artifact.name.as_str(), metadata.size))
+            .collect();
+
+        assert_eq!(manifest.pkgset, version);
+        assert_eq!(
+            sizes,
+            vec![
+                (
+                    "fluvio",
+                    "fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip".len() as u64
+                ),
+                (
+                    "fluvio-run",
+                    "fluvio-run-x86_64-unknown-linux-musl.zip".len() as u64
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_package_set_of_other_naming_schemes() {
+        let version = Version::new(0, 12, 0);
+        let assets = vec![
+            asset("fluvio-0.12.0-x86_64-unknown-linux-musl.tar.gz"),
+            asset("fluvio-0.12.0-x86_64-unknown-linux-musl+x86_64-v3.tar.gz"),
+            asset("fluvio-0.12.0-x86_64-unknown-linux-musl.tar.gz.sha256"),
+            asset("cdk-0.12.0-x86_64-unknown-linux-musl.tar.gz"),
+        ];
+
+        let pkgset =
+            installable_package_set(version.clone(), &assets, ARCH, &[CpuVariant::X86_64V3])
+                .unwrap();
+
+        assert_eq!(installable_binaries(&assets, ARCH), vec!["fluvio", "cdk"]);
+        assert_eq!(pkgset.artifacts.len(), 2);
+        assert_eq!(pkgset.artifacts[0].name, "fluvio");
+        assert_eq!(pkgset.artifacts[0].variant, Some(CpuVariant::X86_64V3));
+        assert_eq!(pkgset.artifacts[1].name, "cdk");
+
+        let unknown = vec![asset("fluvio_x86_64-unknown-linux-musl.tgz")];
+        let err = installable_package_set(version, &unknown, ARCH, &[]).unwrap_err();
+
+        assert!(err.to_string().contains("known naming scheme"));
+    }
+
+    #[test]
+    fn selects_latest_patch_release() {
+        let tags = ["v0.11.8", "v0.11.10", "v0.11.12-rc1", "v0.12.0", "dev"];
+
+        assert_eq!(
+            latest_patch_release(tags, 0, 11),
+            Some((1, Version::new(0, 11, 10)))
+        );
+        assert_eq!(latest_patch_release(tags, 0, 9), None);
+    }
+
+    #[test]
+    fn sorts_stable_versions() {
+        let tags = [
+            "v0.11.8",
+            "v0.12.0",
+            "v0.12.1-rc1",
+            "dev",
+            "v0.11.10",
+            "0.12.0",
+            "v0.12.0+1",
+        ];
+
+        assert_eq!(
+            stable_versions(tags),
+            vec![
+                Version::parse("0.12.0+1").unwrap(),
+                Version::new(0, 12, 0),
+                Version::new(0, 11, 10),
+                Version::new(0, 11, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn collects_paginated_assets() {
+        let names: Vec<String> = (0..250)
+            .map(|idx| format!("fluvio-target-{idx}.zip"))
+            .collect();
+        let mut requested = Vec::new();
+        let assets = futures_lite::future::block_on(collect_asset_pages(|page| {
+            requested.push(page);
+            let start = (page as usize - 1) * ASSETS_PER_PAGE as usize;
+            let items = names
+                .iter()
+                .skip(start)
+                .take(ASSETS_PER_PAGE as usize)
+                .map(|name| asset(name))
+                .collect();
+
+            async move { Ok(items) }
+        }))
+        .unwrap();
+
+        assert_eq!(requested, vec![1, 2, 3]);
+        assert_eq!(assets.len(), 250);
+        assert_eq!(assets.last().unwrap().name, "fluvio-target-249.zip");
+    }
+
+    #[test]
+    fn stops_after_full_last_page() {
+        let mut requested = Vec::new();
+        let assets = futures_lite::future::block_on(collect_asset_pages(|page| {
+            requested.push(page);
+            let items = match page {
+                1 => (0..100).map(|idx| asset(&format!("a-{idx}.zip"))).collect(),
+                _ => Vec::new(),
+            };
+
+            async move { Ok(items) }
+        }))
+        .unwrap();
+
+        assert_eq!(requested, vec![1, 2]);
+        assert_eq!(assets.len(), 100);
+    }
+
+    #[test]
+    fn fails_on_page_error() {
+        let result = futures_lite::future::block_on(collect_asset_pages(|page| async move {
+            match page {
+                1 => Ok((0..100).map(|idx| asset(&format!("a-{idx}.zip"))).collect()),
+                _ => Err(anyhow::anyhow!("rate limited")),
+            }
+        }));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn uses_baseline_when_no_variants_requested() {
+        let version = Version::new(0, 11, 0);
+        let artifacts = select_artifacts(&release_assets(), &version, ARCH, &[]);
+
+        assert_eq!(artifacts.len(), 2);
+        assert!(artifacts.iter().all(|art| art.variant.is_none()));
+        assert_eq!(
+            artifacts[0].download_url,
+            "https://example.com/fluvio-x86_64-unknown-linux-musl.zip"
+        );
+    }
 }
@@ -0,0 +1,97 @@
+//! Dev Release Consistency
+//!
+//! The version of the `dev` release is read from the VERSION file, while its
+//! assets are uploaded by the publish workflow. During a publish window both
+//! can drift, e.g. VERSION is bumped before the new assets are uploaded, and
+//! installing would mix binaries of one version with the metadata of another.
+//!
+//! Version strings embedded in the asset names, e.g. `fluvio-0.11.9.tar.gz`,
+//! are compared with VERSION. On a mismatch the release is fetched again
+//! after a delay, giving the workflow time to finish.
+
+use std::time::Duration;
+
+use semver::Version;
+
+/// Attempts at reading a consistent `dev` release before giving up
+pub const DEV_RELEASE_CHECK_ATTEMPTS: u32 = 3;
+
+/// Wait before reading the `dev` release again after a mismatch
+pub const DEV_RELEASE_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// Versions embedded in `names` which disagree with `version`, sorted and
+/// deduplicated. Only the `major.minor.patch` part of versions is compared.
+pub fn version_drift<'a>(
+    version: &Version,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Vec<Version> {
+    let mut drift: Vec<Version> = names
+        .into_iter()
+        .flat_map(embedded_versions)
+        .filter(|embedded| {
+            (embedded.major, embedded.minor, embedded.patch)
+                != (version.major, version.minor, version.patch)
+        })
+        .collect();
+
+    drift.sort_unstable();
+    drift.dedup();
+    drift
+}
+
+/// Versions embedded in `name`, between separators and optionally prefixed
+/// with `v`
+fn embedded_versions(name: &str) -> Vec<Version> {
+    name.split(['-', '_', '+', '/'])
+        .filter_map(|token| parse_version_prefix(token.strip_prefix('v').unwrap_or(token)))
+        .collect()
+}
+
+/// Parses the leading `major.minor.patch` of `token`, e.g. `0.11.9.zip`
+fn parse_version_prefix(token: &str) -> Option<Version> {
+    let mut parts = token.split('.');
+    let mut next = || {
+        parts
+            .next()
+            .filter(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|part| part.parse::<u64>().ok())
+    };
+
+    Some(Version::new(next()?, next()?, next()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_embedded_versions() {
+        assert_eq!(
+            embedded_versions("fluvio-v0.11.9-x86_64-unknown-linux-musl.zip"),
+            vec![Version::new(0, 11, 9)]
+        );
+        assert_eq!(
+            embedded_versions("fluvio_0.11.10.tar.gz"),
+            vec![Version::new(0, 11, 10)]
+        );
+        assert!(embedded_versions("fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip").is_empty());
+        assert!(embedded_versions("fluvio-1.2-aarch64-apple-darwin.zip").is_empty());
+    }
+
+    #[test]
+    fn reports_drifting_versions() {
+        let version = Version::parse("0.11.9").unwrap();
+        let names = [
+            "fluvio-x86_64-unknown-linux-musl.zip",
+            "fluvio-0.11.9-x86_64-unknown-linux-musl.tar.gz",
+            "fluvio-run-0.11.8-x86_64-unknown-linux-musl.tar.gz",
+            "cdk-0.11.8-x86_64-unknown-linux-musl.tar.gz",
+        ];
+
+        assert_eq!(version_drift(&version, names), vec![Version::new(0, 11, 8)]);
+        assert!(version_drift(&version, names[..2].iter().copied()).is_empty());
+
+        let dev = Version::parse("0.11.9-dev.1").unwrap();
+        assert!(version_drift(&dev, names[..2].iter().copied()).is_empty());
+    }
+}
@@ -1,45 +1,124 @@
 //! Download API for downloading the artifacts from the server
 
 use std::path::{Path, PathBuf};
-use std::io::{Cursor, copy};
-use std::fs::File;
+use std::io::{Cursor, Read, Seek, copy};
+use std::fs::{File, create_dir_all};
 
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use http::StatusCode;
-use sha2::{Digest, Sha256};
+use http::{HeaderMap, StatusCode};
 use tracing::instrument;
 
+use crate::extraction::{ExtractionBudget, ExtractionLimits};
+use crate::failure::{Failure, FailureKind, status_error};
 use crate::fvm::Artifact;
-use crate::htclient;
+use crate::fvm::assets::{AssetKind, asset_path};
+use crate::htclient::handle::DownloadHandle;
+use crate::htclient::transfer::ProgressCallback;
+use crate::quarantine::{Quarantine, QuarantineRecord};
+use crate::remote_zip::RemoteZipIndex;
+use crate::store::ContentStore;
+use crate::verification::VerifiedDownload;
+use crate::htclient::{BodyDigest, Response};
+use crate::{htclient, sha256_digest_reader};
 
 #[async_trait]
 pub trait Download {
-    /// Downloads the artifact to the specified directory
+    /// Downloads the artifact to the specified directory, usually a
+    /// directory within the layout temporary directory which is then moved
+    /// into the version directory, see [`crate::layout::Layout`].
     ///
     /// Checksum validation, when metadata is available, is performed against
     /// the raw bytes returned from the artifact's `download_url` (for example
     /// a `.zip` archive) **before** any extraction. The checksum does not
     /// currently apply to any binary extracted from an archive.
     ///
+    /// Artifacts with a digest already in the content store are linked from
+    /// the store instead of being downloaded.
+    ///
+    /// Assets archived next to the binary, such as shell completions, are
+    /// extracted to `assets/<name>` in `target_dir`, see
+    /// [`crate::fvm::AssetKind`]. Artifacts linked from the content store
+    /// have no assets.
+    ///
     /// Returns the path to the downloaded (and, if applicable, extracted)
     /// artifact.
-    async fn download(&self, target_dir: PathBuf) -> Result<PathBuf>;
+    async fn download(&self, target_dir: PathBuf) -> Result<PathBuf> {
+        Ok(self.download_verified(target_dir).await?.0)
+    }
+
+    /// Same as [`Download::download`], also returning the verification of
+    /// the downloaded bytes for verification reports, see
+    /// [`crate::verification::VerificationReport`]
+    async fn download_verified(&self, target_dir: PathBuf) -> Result<(PathBuf, VerifiedDownload)> {
+        self.download_with_progress(target_dir, &mut |_| {}).await
+    }
+
+    /// Same as [`Download::download_verified`], reporting the bytes
+    /// received, speed and ETA of the transfer to `on_progress`. Nothing is
+    /// reported for artifacts linked from the content store.
+    async fn download_with_progress(
+        &self,
+        target_dir: PathBuf,
+        on_progress: &mut ProgressCallback<'_>,
+    ) -> Result<(PathBuf, VerifiedDownload)> {
+        self.download_controlled(target_dir, on_progress, &DownloadHandle::new())
+            .await
+    }
+
+    /// Same as [`Download::download_verified`], controlled through `handle`
+    /// from another task or thread, see [`DownloadHandle`]
+    async fn download_with_handle(
+        &self,
+        target_dir: PathBuf,
+        handle: &DownloadHandle,
+    ) -> Result<(PathBuf, VerifiedDownload)> {
+        self.download_controlled(target_dir, &mut |_| {}, handle)
+            .await
+    }
+
+    /// Same as [`Download::download_with_progress`], controlled through
+    /// `handle`
+    async fn download_controlled(
+        &self,
+        target_dir: PathBuf,
+        on_progress: &mut ProgressCallback<'_>,
+        handle: &DownloadHandle,
+    ) -> Result<(PathBuf, VerifiedDownload)>;
 }
 
 #[async_trait]
 impl Download for Artifact {
-    #[instrument(skip(self, target_dir))]
-    async fn download(&self, target_dir: PathBuf) -> Result<PathBuf> {
+    #[instrument(skip(self, target_dir, on_progress, handle))]
+    async fn download_controlled(
+        &self,
+        target_dir: PathBuf,
+        on_progress: &mut ProgressCallback<'_>,
+        handle: &DownloadHandle,
+    ) -> Result<(PathBuf, VerifiedDownload)> {
+        let store = ContentStore::open_default();
+
+        if let Some(store) = &store
+            && let Some(out_path) = from_store(store, self, &target_dir)
+        {
+            let verified = VerifiedDownload::from_store(
+                &self.name,
+                self.version.to_string(),
+                &self.download_url,
+                self.sha256_digest.as_deref(),
+                &out_path,
+            )?;
+
+            return Ok((out_path, verified));
+        }
+
         tracing::info!(
             name = self.name,
             download_url = ?self.download_url,
             "Downloading artifact"
         );
 
-        let res = htclient::get(&self.download_url)
-            .await
-            .map_err(|err| Error::msg(err.to_string()))?;
+        let res = htclient::get_with_handle(&self.download_url, on_progress, handle).await?;
 
         let status = http::StatusCode::from_u16(res.status().as_u16())?;
         if status == StatusCode::OK {
@@ -49,68 +128,213 @@ impl Download for Artifact {
                 .and_then(|v| v.to_str().ok())
                 .map(|s| s.to_ascii_lowercase());
 
+            let headers = res.headers().clone();
+            let digest = body_digest(&res)?;
             let bytes = res.into_body();
 
             // delegate to helper which is easier to test
-            return process_downloaded_bytes(&bytes, content_type, self, &target_dir);
+            let out_path =
+                process_downloaded_bytes(&bytes, &digest, content_type, self, &target_dir)
+                    .map_err(|err| {
+                        quarantine_mismatch(
+                            err,
+                            self,
+                            &bytes,
+                            &digest,
+                            &headers,
+                            Quarantine::open_default().as_ref(),
+                        )
+                    })?;
+            let verified = VerifiedDownload::from_digest(
+                &self.name,
+                self.version.to_string(),
+                &self.download_url,
+                self.sha256_digest.as_deref(),
+                digest,
+                bytes.len() as u64,
+            );
+
+            if let Some(store) = &store
+                && let Err(err) = store.dedup(&out_path).and_then(|_| {
+                    store.record_source(&verified.computed_sha256, &self.download_url)
+                })
+            {
+                tracing::warn!(%err, name = self.name, "Failed to add artifact to content store");
+            }
+
+            return Ok((out_path, verified));
         }
 
-        Err(Error::msg(format!(
-            "Server responded with Status Code {} for url {}",
+        Err(status_error(
             res.status(),
-            self.download_url,
-        )))
+            format!(
+                "Server responded with Status Code {} for url {}",
+                res.status(),
+                self.download_url,
+            ),
+        ))
+    }
+}
+
+impl Artifact {
+    /// Lists the zip archive of the artifact from its central directory,
+    /// without downloading it, see [`RemoteZipIndex`]. Fails when no entry
+    /// of the archive is named after the binary.
+    pub async fn inspect_archive(&self) -> Result<RemoteZipIndex> {
+        let index = RemoteZipIndex::fetch(&self.download_url).await?;
+        let has_binary = index.files().any(|entry| {
+            entry.name.ends_with(&self.name)
+                && AssetKind::classify(Path::new(&entry.name)).is_none()
+        });
+
+        if !has_binary {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!(
+                    "Archive at {} does not contain the {} binary",
+                    self.download_url, self.name
+                ),
+            )
+            .into());
+        }
+
+        Ok(index)
+    }
+}
+
+/// Links the artifact from the content store into `target_dir` when its
+/// published digest matches a stored object. Store failures fall back to
+/// downloading the artifact.
+fn from_store(store: &ContentStore, artifact: &Artifact, target_dir: &Path) -> Option<PathBuf> {
+    let digest = artifact.sha256_digest.as_deref()?;
+    let out_path = target_dir.join(&artifact.name);
+
+    match store.materialize(digest, &out_path) {
+        Ok(Some(materialized)) => {
+            tracing::info!(
+                name = artifact.name,
+                ?materialized,
+                "Using artifact from content store"
+            );
+            Some(out_path)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            tracing::warn!(%err, name = artifact.name, "Failed to read content store");
+            None
+        }
+    }
+}
+
+/// Sha256 checksum of the body of `response`, computed while the body was
+/// received when [`htclient`] provides it
+fn body_digest(response: &Response<Vec<u8>>) -> Result<String> {
+    match response.extensions().get::<BodyDigest>() {
+        Some(BodyDigest(digest)) => Ok(digest.clone()),
+        None => Ok(sha256_digest_reader(&response.body()[..])?),
+    }
+}
+
+/// Expected and `actual` digests of the downloaded bytes when they don't
+/// match the published digest of the artifact, `None` when they match or no
+/// digest is published
+fn digest_mismatch(actual: &str, artifact: &Artifact) -> Option<(String, String)> {
+    let expected_digest = artifact.sha256_digest.as_ref()?;
+    let expected = expected_digest.trim();
+    let expected = expected
+        .strip_prefix("sha256:")
+        .unwrap_or(expected)
+        .to_ascii_lowercase();
+
+    if actual != expected {
+        return Some((expected, actual.to_string()));
+    }
+
+    tracing::debug!(
+        name = artifact.name,
+        %expected,
+        %actual,
+        digest_scope = "archive",
+        "Checksum validation succeeded for downloaded artifact (archive) bytes",
+    );
+
+    None
+}
+
+/// Keeps `bytes` in `quarantine` when `err` is caused by their checksum
+/// `digest` not matching, reporting the quarantined path in the returned
+/// error. Other errors are returned as they are.
+fn quarantine_mismatch(
+    err: Error,
+    artifact: &Artifact,
+    bytes: &[u8],
+    digest: &str,
+    headers: &HeaderMap,
+    quarantine: Option<&Quarantine>,
+) -> Error {
+    let Some(quarantine) = quarantine else {
+        return err;
+    };
+    let Some((expected, actual)) = digest_mismatch(digest, artifact) else {
+        return err;
+    };
+    let record = QuarantineRecord::new(
+        &artifact.name,
+        artifact.version.to_string(),
+        &artifact.download_url,
+        expected,
+        actual,
+        bytes.len() as u64,
+    )
+    .with_headers(headers);
+
+    match quarantine.store(bytes, &record) {
+        Ok(path) => Failure::new(
+            FailureKind::Integrity,
+            format!(
+                "{err}, the downloaded bytes were quarantined at {}",
+                path.display()
+            ),
+        )
+        .into(),
+        Err(quarantine_err) => {
+            tracing::warn!(%quarantine_err, name = artifact.name, "Failed to quarantine download");
+            err
+        }
     }
 }
 
 /// Internal helper that implements the logic for handling downloaded bytes.
-/// Extracts files if zip, validates checksum if provided, writes final file
-/// to `target_dir` and returns the path.
+/// Extracts files if zip, validates the checksum `digest` of `bytes` if one
+/// is published, writes final file to `target_dir` and returns the path.
+/// Extraction is limited by the [`ExtractionLimits`] of the environment.
 fn process_downloaded_bytes(
     bytes: &[u8],
+    digest: &str,
     content_type: Option<String>,
     artifact: &Artifact,
     target_dir: &Path,
 ) -> Result<PathBuf> {
     let out_path = target_dir.join(&artifact.name);
 
-    if let Some(expected_digest) = &artifact.sha256_digest {
-        let expected = expected_digest.trim();
-        let expected = expected
-            .strip_prefix("sha256:")
-            .unwrap_or(expected)
-            .to_ascii_lowercase();
-
-        let mut hasher = Sha256::new();
-        hasher.update(bytes);
-        let actual = format!("{:x}", hasher.finalize());
-
-        if actual != expected {
-            let msg = format!(
-                "DANGER: Downloaded artifact checksum did not match for {}",
-                artifact.name
-            );
-            tracing::error!(
-                name = artifact.name,
-                %expected,
-                %actual,
-                digest_scope = "archive",
-                "Checksum validation failed for downloaded artifact (archive) bytes",
-            );
-
-            return Err(Error::msg(msg));
-        }
-
-        tracing::debug!(
+    if let Some((expected, actual)) = digest_mismatch(digest, artifact) {
+        let msg = format!(
+            "DANGER: Downloaded artifact checksum did not match for {}",
+            artifact.name
+        );
+        tracing::error!(
             name = artifact.name,
             %expected,
             %actual,
             digest_scope = "archive",
-            "Checksum validation succeeded for downloaded artifact (archive) bytes",
+            "Checksum validation failed for downloaded artifact (archive) bytes",
         );
+
+        return Err(Failure::new(FailureKind::Integrity, msg).into());
     }
 
     let mut file = File::create(&out_path)?;
+    let mut budget = ExtractionLimits::from_env()?.budget(bytes.len() as u64);
 
     let is_zip_ct = content_type.as_deref().is_some_and(|ct| ct.contains("zip"));
 
@@ -122,35 +346,20 @@ fn process_downloaded_bytes(
             return Err(Error::msg("Downloaded zip archive is empty"));
         }
 
-        let mut selected_index: Option<usize> = None;
-
-        // look file entries to find the one that matches the artifact name
-        for i in 0..zip.len() {
-            let file_in_zip = zip.by_index(i)?;
-
-            if file_in_zip.is_dir() {
-                continue;
-            }
-
-            let entry_name = file_in_zip.name();
-
-            if selected_index.is_none() {
-                selected_index = Some(i);
-            }
+        budget.check_entries(zip.len() as u64)?;
 
-            if entry_name.ends_with(&artifact.name) {
-                selected_index = Some(i);
-                break;
-            }
-        }
-
-        let selected_index = selected_index.ok_or_else(|| {
-            Error::msg("Downloaded zip archive does not contain any file entries")
-        })?;
+        let selected_index = select_binary_entry(zip.file_names(), artifact)?;
 
         let mut zipped_file = zip.by_index(selected_index)?;
         let expected_size = zipped_file.size();
-        let written = copy(&mut zipped_file, &mut file)?;
+        let entry_name = zipped_file.name().to_string();
+        let compressed_size = zipped_file.compressed_size();
+        let written = budget.copy(
+            &entry_name,
+            Some(compressed_size),
+            &mut zipped_file,
+            &mut file,
+        )?;
 
         if written == 0 {
             return Err(Error::msg("Downloaded zip entry is empty"));
@@ -161,6 +370,11 @@ fn process_downloaded_bytes(
                 "Extracted file size does not match zip entry size",
             ));
         }
+
+        drop(zipped_file);
+        extract_assets(&mut zip, selected_index, artifact, target_dir, &mut budget)?;
+    } else if is_gzip_archive(bytes) {
+        extract_tar_gz(bytes, artifact, target_dir, &mut file, &mut budget)?;
     } else {
         let mut buf = Cursor::new(&bytes);
         let written = copy(&mut buf, &mut file)?;
@@ -179,6 +393,231 @@ fn process_downloaded_bytes(
     Ok(out_path)
 }
 
+/// Index of the entry holding the binary of `artifact` among the archive
+/// entry `names`. Directories and assets are never selected, and the entry
+/// is, in order of precedence:
+///
+/// 1. the entry at [`Artifact::entry`], when set
+/// 2. the entry with the shortest path among the ones named after the
+///    artifact, e.g. `fluvio` over `bin/fluvio`
+/// 3. the entry with the shortest path
+///
+/// Paths are compared by their number of components, so entries as deep as
+/// each other are ambiguous, e.g. `release/fluvio` and `debug/fluvio`, and
+/// fail the selection with the list of candidates.
+fn select_binary_entry<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    artifact: &Artifact,
+) -> Result<usize> {
+    let candidates: Vec<(usize, &str)> = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry_name)| (i, entry_name.strip_prefix("./").unwrap_or(entry_name)))
+        // assets such as `completions/_fluvio` are extracted next to the binary
+        .filter(|(_, entry_name)| {
+            !entry_name.is_empty()
+                && !entry_name.ends_with('/')
+                && AssetKind::classify(Path::new(entry_name)).is_none()
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(Error::msg(
+            "Downloaded archive does not contain any file entries",
+        ));
+    }
+
+    if let Some(entry) = &artifact.entry {
+        let entry = entry.strip_prefix("./").unwrap_or(entry);
+
+        return candidates
+            .iter()
+            .find(|(_, entry_name)| *entry_name == entry)
+            .map(|(i, _)| *i)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Downloaded archive has no entry {entry} for {}, candidates are:\n{}",
+                    artifact.name,
+                    list_entries(&candidates)
+                )
+            });
+    }
+
+    let named: Vec<(usize, &str)> = candidates
+        .iter()
+        .filter(|(_, entry_name)| {
+            Path::new(entry_name)
+                .file_name()
+                .is_some_and(|file_name| file_name == artifact.name.as_str())
+        })
+        .copied()
+        .collect();
+    let matches = if named.is_empty() {
+        &candidates
+    } else {
+        &named
+    };
+    let depth = |entry_name: &str| Path::new(entry_name).components().count();
+    let shortest = matches
+        .iter()
+        .map(|(_, entry_name)| depth(entry_name))
+        .min()
+        .unwrap_or_default();
+    let selected: Vec<(usize, &str)> = matches
+        .iter()
+        .filter(|(_, entry_name)| depth(entry_name) == shortest)
+        .copied()
+        .collect();
+
+    match selected[..] {
+        [(i, _)] => Ok(i),
+        _ => Err(anyhow::anyhow!(
+            "Downloaded archive has several entries which may be the {} binary, set the entry of the artifact to one of:\n{}",
+            artifact.name,
+            list_entries(&selected)
+        )),
+    }
+}
+
+fn list_entries(entries: &[(usize, &str)]) -> String {
+    entries
+        .iter()
+        .map(|(_, entry_name)| format!("  - {entry_name}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Extracts the assets archived next to the binary, see [`AssetKind`]
+fn extract_assets<R: Read + Seek>(
+    zip: &mut zip::ZipArchive<R>,
+    binary_index: usize,
+    artifact: &Artifact,
+    target_dir: &Path,
+    budget: &mut ExtractionBudget,
+) -> Result<()> {
+    for i in 0..zip.len() {
+        if i == binary_index {
+            continue;
+        }
+
+        let mut file_in_zip = zip.by_index(i)?;
+
+        if file_in_zip.is_dir() {
+            continue;
+        }
+
+        let Some(entry) = file_in_zip.enclosed_name() else {
+            continue;
+        };
+        let Some(kind) = AssetKind::classify(&entry) else {
+            continue;
+        };
+        let Some(asset_path) = asset_path(&artifact.name, &entry) else {
+            continue;
+        };
+        let out_path = target_dir.join(asset_path);
+
+        if let Some(parent) = out_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let compressed_size = file_in_zip.compressed_size();
+
+        budget.copy(
+            &entry.to_string_lossy(),
+            Some(compressed_size),
+            &mut file_in_zip,
+            &mut File::create(&out_path)?,
+        )?;
+        tracing::debug!(name = artifact.name, ?kind, ?out_path, "Extracted asset");
+    }
+
+    Ok(())
+}
+
+/// Extracts the binary of a `.tar.gz` artifact into `file` and its assets
+/// into `target_dir`. The binary is selected like in zip archives, see
+/// [`select_binary_entry`], so the archive is read twice: once to list its
+/// entries and extract the assets, and once to extract the binary.
+fn extract_tar_gz(
+    bytes: &[u8],
+    artifact: &Artifact,
+    target_dir: &Path,
+    file: &mut File,
+    budget: &mut ExtractionBudget,
+) -> Result<()> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let mut files: Vec<String> = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        budget.count_entry()?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path()?.into_owned();
+        let entry_path = entry_path.strip_prefix(".").unwrap_or(&entry_path);
+
+        if let Some(kind) = AssetKind::classify(entry_path) {
+            let Some(asset_path) = asset_path(&artifact.name, entry_path) else {
+                continue;
+            };
+            let out_path = target_dir.join(asset_path);
+
+            if let Some(parent) = out_path.parent() {
+                create_dir_all(parent)?;
+            }
+
+            budget.copy(
+                &entry_path.to_string_lossy(),
+                None,
+                &mut entry,
+                &mut File::create(&out_path)?,
+            )?;
+            tracing::debug!(name = artifact.name, ?kind, ?out_path, "Extracted asset");
+            continue;
+        }
+
+        files.push(entry_path.to_string_lossy().into_owned());
+    }
+
+    let selected = &files[select_binary_entry(files.iter().map(String::as_str), artifact)?];
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path()?.into_owned();
+        let entry_path = entry_path.strip_prefix(".").unwrap_or(&entry_path);
+
+        if entry_path.to_string_lossy() != selected.as_str() {
+            continue;
+        }
+
+        if budget.copy(selected, None, &mut entry, file)? == 0 {
+            return Err(Error::msg("Downloaded tar entry is empty"));
+        }
+
+        return Ok(());
+    }
+
+    Err(Error::msg(
+        "Downloaded tar archive changed while extracting",
+    ))
+}
+
+fn is_gzip_archive(bytes: &[u8]) -> bool {
+    const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+    bytes.len() >= GZIP_MAGIC.len() && bytes[..GZIP_MAGIC.len()] == GZIP_MAGIC
+}
+
 fn is_zip_archive(bytes: &[u8]) -> bool {
     const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
     bytes.len() >= ZIP_MAGIC.len() && bytes[..ZIP_MAGIC.len()] == ZIP_MAGIC
@@ -187,7 +626,9 @@ fn is_zip_archive(bytes: &[u8]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::quarantine::metadata_path;
     use tempfile::TempDir;
+    use crate::extraction::ExtractionError;
     use std::io::Write;
     use sha2::{Digest, Sha256};
 
@@ -227,10 +668,14 @@ mod tests {
             version: semver::Version::new(0, 0, 0),
             download_url: "http://example.com".to_string(),
             sha256_digest: Some(format!("sha256:{}", digest)),
+            variant: None,
+            channel: None,
+            entry: None,
         };
 
         let out = process_downloaded_bytes(
             &bytes,
+            &sha256_hex(&bytes),
             Some("application/zip".to_string()),
             &artifact,
             &target_dir,
@@ -241,6 +686,106 @@ mod tests {
         assert_eq!(content, b"expected-binary-data");
     }
 
+    #[test]
+    fn extracts_binary_and_assets_from_tar_gz() {
+        let tmp = TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+
+        for (path, contents) in [
+            (
+                "./completions/_myartifact",
+                b"#compdef myartifact".as_slice(),
+            ),
+            ("./README.md", b"readme".as_slice()),
+            ("./bin/myartifact", b"expected-binary-data".as_slice()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents).unwrap();
+        }
+
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+        let artifact = Artifact {
+            name: "myartifact".to_string(),
+            version: semver::Version::new(0, 0, 0),
+            download_url: "http://example.com".to_string(),
+            sha256_digest: Some(sha256_hex(&bytes)),
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+
+        let out =
+            process_downloaded_bytes(&bytes, &sha256_hex(&bytes), None, &artifact, tmp.path())
+                .unwrap();
+
+        assert_eq!(std::fs::read(out).unwrap(), b"expected-binary-data");
+        assert!(
+            tmp.path()
+                .join(crate::fvm::ARTIFACT_ASSETS_DIR)
+                .join("myartifact")
+                .join("completions")
+                .join("_myartifact")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn extracts_assets_next_to_binary() {
+        let tmp = TempDir::new().unwrap();
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options: FileOptions<'_, ()> = FileOptions::default();
+
+            zip.start_file("completions/_fluvio", options).unwrap();
+            zip.write_all(b"#compdef fluvio").unwrap();
+
+            zip.start_file("fluvio", options).unwrap();
+            zip.write_all(b"expected-binary-data").unwrap();
+
+            zip.start_file("man/fluvio.1", options).unwrap();
+            zip.write_all(b".TH FLUVIO 1").unwrap();
+
+            zip.start_file("README.md", options).unwrap();
+            zip.write_all(b"readme").unwrap();
+
+            zip.finish().unwrap();
+        }
+        let artifact = Artifact {
+            name: "fluvio".to_string(),
+            version: semver::Version::new(0, 0, 0),
+            download_url: "http://example.com".to_string(),
+            sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+
+        let bytes = buffer.into_inner();
+        let out =
+            process_downloaded_bytes(&bytes, &sha256_hex(&bytes), None, &artifact, tmp.path())
+                .expect("should extract binary");
+        let assets = tmp
+            .path()
+            .join(crate::fvm::ARTIFACT_ASSETS_DIR)
+            .join("fluvio");
+
+        assert_eq!(std::fs::read(out).unwrap(), b"expected-binary-data");
+        assert_eq!(
+            std::fs::read(assets.join("completions/_fluvio")).unwrap(),
+            b"#compdef fluvio"
+        );
+        assert!(assets.join("man/fluvio.1").is_file());
+        assert!(!assets.join("README.md").exists());
+    }
+
     #[test]
     fn fails_on_checksum_mismatch() {
         let tmp = TempDir::new().unwrap();
@@ -256,17 +801,198 @@ mod tests {
                 "sha256:0000000000000000000000000000000000000000000000000000000000000000"
                     .to_string(),
             ),
+            variant: None,
+            channel: None,
+            entry: None,
         };
 
         let res = process_downloaded_bytes(
             &bytes,
+            &sha256_hex(&bytes),
             Some("application/octet-stream".to_string()),
             &artifact,
             &target_dir,
         );
         assert!(res.is_err());
-        let msg = format!("{}", res.unwrap_err());
+        let err = res.unwrap_err();
+        let msg = format!("{}", err);
         assert!(msg.contains("checksum") || msg.contains("DANGER"));
+        assert_eq!(
+            crate::failure::failure_kind(&err),
+            Some(FailureKind::Integrity)
+        );
+    }
+
+    #[test]
+    fn uses_digest_computed_while_receiving() {
+        let response = Response::builder()
+            .extension(BodyDigest("computed".to_string()))
+            .body(b"bytes".to_vec())
+            .unwrap();
+
+        assert_eq!(body_digest(&response).unwrap(), "computed");
+        assert_eq!(
+            body_digest(&Response::new(b"bytes".to_vec())).unwrap(),
+            sha256_hex(b"bytes")
+        );
+    }
+
+    #[test]
+    fn quarantines_checksum_mismatches() {
+        let tmp = TempDir::new().unwrap();
+        let quarantine = Quarantine::new(tmp.path().join("quarantine"));
+        let artifact = Artifact {
+            name: "foo".to_string(),
+            version: semver::Version::new(0, 11, 8),
+            download_url: "http://example.com/foo".to_string(),
+            sha256_digest: Some(sha256_hex(b"expected")),
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+        let digest = sha256_hex(b"tampered");
+        let err = process_downloaded_bytes(b"tampered", &digest, None, &artifact, tmp.path())
+            .unwrap_err();
+        let err = quarantine_mismatch(
+            err,
+            &artifact,
+            b"tampered",
+            &digest,
+            &HeaderMap::new(),
+            Some(&quarantine),
+        );
+        let path = tmp.path().join("quarantine").join(format!(
+            "foo-0.11.8-{}.quarantined",
+            &sha256_hex(b"tampered")[..12]
+        ));
+
+        assert!(err.to_string().contains(&path.display().to_string()));
+        assert_eq!(
+            crate::failure::failure_kind(&err),
+            Some(FailureKind::Integrity)
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"tampered");
+
+        let record: QuarantineRecord =
+            serde_json::from_slice(&std::fs::read(metadata_path(&path)).unwrap()).unwrap();
+
+        assert_eq!(record.expected_sha256, sha256_hex(b"expected"));
+        assert_eq!(record.url, "http://example.com/foo");
+
+        // Other failures are not quarantined
+        let err = quarantine_mismatch(
+            Error::msg("empty"),
+            &artifact,
+            b"expected",
+            &sha256_hex(b"expected"),
+            &HeaderMap::new(),
+            Some(&quarantine),
+        );
+
+        assert_eq!(err.to_string(), "empty");
+    }
+
+    #[test]
+    fn selects_binary_entry_deterministically() {
+        let mut artifact = Artifact {
+            name: "fluvio".to_string(),
+            version: semver::Version::new(0, 0, 0),
+            download_url: "http://example.com".to_string(),
+            sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+        let select = |names: &[&str], artifact: &Artifact| {
+            select_binary_entry(names.iter().copied(), artifact)
+        };
+
+        // exact basename over suffix, then shortest path
+        assert_eq!(
+            select(&["myfluvio", "bin/fluvio", "fluvio"], &artifact).unwrap(),
+            2
+        );
+        assert_eq!(
+            select(
+                &["completions/_fluvio", "README", "./bin/fluvio"],
+                &artifact
+            )
+            .unwrap(),
+            2
+        );
+        // no entry named after the artifact
+        assert_eq!(
+            select(&["dist/", "bin/fluvio-x86", "fluvio-x86"], &artifact).unwrap(),
+            2
+        );
+
+        let err = select(&["debug/fluvio", "release/fluvio", "README"], &artifact)
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("several entries"));
+        assert!(err.contains("  - debug/fluvio\n  - release/fluvio"));
+        assert!(!err.contains("README"));
+
+        artifact.entry = Some("release/fluvio".to_string());
+        assert_eq!(
+            select(&["debug/fluvio", "release/fluvio"], &artifact).unwrap(),
+            1
+        );
+
+        artifact.entry = Some("fluvio".to_string());
+        assert!(
+            select(&["debug/fluvio", "release/fluvio"], &artifact)
+                .unwrap_err()
+                .to_string()
+                .contains("no entry fluvio")
+        );
+        assert!(select(&["dist/", "completions/_fluvio"], &artifact).is_err());
+    }
+
+    #[test]
+    fn extracts_overridden_entry_from_tar_gz() {
+        let tmp = TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+
+        for (path, contents) in [
+            ("./debug/myartifact", b"debug-binary-data".as_slice()),
+            ("./release/myartifact", b"expected-binary-data".as_slice()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents).unwrap();
+        }
+
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+        let mut artifact = Artifact {
+            name: "myartifact".to_string(),
+            version: semver::Version::new(0, 0, 0),
+            download_url: "http://example.com".to_string(),
+            sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+
+        assert!(
+            process_downloaded_bytes(&bytes, &sha256_hex(&bytes), None, &artifact, tmp.path())
+                .is_err()
+        );
+
+        artifact.entry = Some("release/myartifact".to_string());
+
+        let out =
+            process_downloaded_bytes(&bytes, &sha256_hex(&bytes), None, &artifact, tmp.path())
+                .unwrap();
+
+        assert_eq!(std::fs::read(out).unwrap(), b"expected-binary-data");
     }
 
     #[test]
@@ -287,10 +1013,14 @@ mod tests {
             version: semver::Version::new(0, 0, 0),
             download_url: "http://example.com".to_string(),
             sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
         };
 
         let res = process_downloaded_bytes(
             &bytes,
+            &sha256_hex(&bytes),
             Some("application/zip".to_string()),
             &artifact,
             &target_dir,
@@ -320,10 +1050,14 @@ mod tests {
             version: semver::Version::new(0, 0, 0),
             download_url: "http://example.com".to_string(),
             sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
         };
 
         let res = process_downloaded_bytes(
             &bytes,
+            &sha256_hex(&bytes),
             Some("application/zip".to_string()),
             &artifact,
             &target_dir,
@@ -332,4 +1066,50 @@ mod tests {
         let msg = format!("{}", res.unwrap_err());
         assert!(msg.contains("zip entry is empty"));
     }
+
+    #[test]
+    fn fails_on_zip_bomb() {
+        let tmp = TempDir::new().unwrap();
+        let target_dir = tmp.path().to_path_buf();
+
+        // 64 MiB of zeros deflate to about 64 KiB
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options: FileOptions<'_, ()> =
+                FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+            let zeros = vec![0u8; 1024 * 1024];
+
+            zip.start_file("fluvio", options).unwrap();
+            for _ in 0..64 {
+                zip.write_all(&zeros).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        let bytes = buffer.into_inner();
+
+        let artifact = Artifact {
+            name: "fluvio".to_string(),
+            version: semver::Version::new(0, 0, 0),
+            download_url: "http://example.com".to_string(),
+            sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
+        };
+
+        let err = process_downloaded_bytes(
+            &bytes,
+            &sha256_hex(&bytes),
+            Some("application/zip".to_string()),
+            &artifact,
+            &target_dir,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ExtractionError>(),
+            Some(ExtractionError::CompressionRatio { .. })
+        ));
+    }
 }
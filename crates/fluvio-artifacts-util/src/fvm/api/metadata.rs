@@ -0,0 +1,147 @@
+//! Version Metadata
+//!
+//! Answers questions about released Fluvio versions for crates other than
+//! FVM, e.g. the CLI checking for updates or the cluster installer picking a
+//! platform version: the newest stable platform compatible with a CLI is
+//! resolved from the stable releases and the compatibility matrix.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use semver::Version;
+
+use crate::fvm::CompatibilityMatrix;
+use crate::state::{load_state, write_state};
+
+use super::Client;
+
+/// The name of the compatibility matrix cache file stored in the cache
+/// directory
+pub const COMPATIBILITY_CACHE_FILENAME: &str = "compatibility.json";
+
+/// Age after which the cached compatibility matrix is fetched again
+pub const COMPATIBILITY_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Version metadata backed by the releases of the [`Client`] repository
+#[derive(Debug, Default)]
+pub struct VersionMetadata {
+    client: Client,
+    /// Directory caching the compatibility matrix, nothing is cached when
+    /// unset
+    cache_dir: Option<PathBuf>,
+}
+
+impl VersionMetadata {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            cache_dir: None,
+        }
+    }
+
+    /// Caches the compatibility matrix in `cache_dir`, e.g. the `cache_dir`
+    /// of the [`Layout`](crate::layout::Layout)
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// The compatibility matrix, read from the cache when fresh.
+    ///
+    /// If fetching fails, a stale cached matrix is used when available.
+    pub async fn compatibility_matrix(&self) -> Result<CompatibilityMatrix> {
+        let cache_path = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(COMPATIBILITY_CACHE_FILENAME));
+
+        if let Some(matrix) = cache_path
+            .as_deref()
+            .and_then(|path| read_cache(path, Some(COMPATIBILITY_CACHE_TTL)))
+        {
+            return Ok(matrix);
+        }
+
+        match self.client.fetch_compatibility_matrix().await {
+            Ok(matrix) => {
+                if let Some(path) = cache_path.as_deref()
+                    && let Err(err) = write_cache(path, &matrix)
+                {
+                    tracing::debug!(%err, "Failed to cache compatibility matrix");
+                }
+
+                Ok(matrix)
+            }
+            Err(err) => cache_path
+                .as_deref()
+                .and_then(|path| read_cache(path, None))
+                .ok_or(err),
+        }
+    }
+
+    /// The newest stable platform version compatible with the CLI at `cli`,
+    /// `None` if no stable release is compatible
+    pub async fn newest_compatible_platform(&self, cli: &Version) -> Result<Option<Version>> {
+        let matrix = self.compatibility_matrix().await?;
+        let versions = self.client.fetch_stable_versions().await?;
+
+        Ok(matrix.newest_compatible(cli, &versions).cloned())
+    }
+
+    /// The newest stable CLI version compatible with the platform at
+    /// `platform`, `None` if no stable release is compatible
+    pub async fn newest_compatible_cli(&self, platform: &Version) -> Result<Option<Version>> {
+        let matrix = self.compatibility_matrix().await?;
+        let versions = self.client.fetch_stable_versions().await?;
+
+        Ok(matrix.newest_compatible_cli(platform, &versions).cloned())
+    }
+}
+
+fn write_cache(path: &Path, matrix: &CompatibilityMatrix) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(matrix)?)
+}
+
+/// Reads the cached matrix if the cache is younger than `ttl`
+fn read_cache(path: &Path, ttl: Option<Duration>) -> Option<CompatibilityMatrix> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+
+    if let Some(ttl) = ttl
+        && modified.elapsed().map_or(true, |age| age > ttl)
+    {
+        return None;
+    }
+
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn reads_fresh_cache_only() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("cache").join(COMPATIBILITY_CACHE_FILENAME);
+        let matrix: CompatibilityMatrix = serde_json::from_str(
+            r#"{ "rules": [{ "cli": ">=0.12.0, <0.13.0", "platform": ">=0.11.5" }] }"#,
+        )
+        .unwrap();
+
+        assert!(read_cache(&path, None).is_none());
+
+        write_cache(&path, &matrix).unwrap();
+
+        assert_eq!(
+            read_cache(&path, Some(COMPATIBILITY_CACHE_TTL)),
+            Some(matrix.clone())
+        );
+        assert!(read_cache(&path, Some(Duration::ZERO)).is_none());
+        assert_eq!(read_cache(&path, None), Some(matrix));
+    }
+}
@@ -1,5 +1,11 @@
+mod backfill;
 mod client;
+mod consistency;
 mod download;
+mod metadata;
+mod rate_limit;
 
-pub use client::Client;
+pub use backfill::{AssetFailure, DEFAULT_BACKFILL_CONCURRENCY, ReleaseBackfill};
+pub use client::{Client, FVM_INSTALLABLE_BINARIES, IncompleteRelease, ReleaseTarget};
 pub use download::Download;
+pub use metadata::{COMPATIBILITY_CACHE_FILENAME, COMPATIBILITY_CACHE_TTL, VersionMetadata};
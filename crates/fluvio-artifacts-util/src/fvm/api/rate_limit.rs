@@ -0,0 +1,195 @@
+//! GitHub Secondary Rate Limits
+//!
+//! GitHub answers bursts of requests with `403 Forbidden` or
+//! `429 Too Many Requests` once a secondary rate limit is hit, asking clients
+//! to wait at least a minute before retrying. Requests hitting the limit are
+//! retried with an exponential backoff, bounded by [`MAX_RATE_LIMIT_WAIT`].
+//!
+//! The GitHub client does not expose response headers on errors, so the
+//! `Retry-After` header cannot be honored and the documented minimum wait is
+//! used instead.
+
+use std::time::Duration;
+
+use http::StatusCode;
+use octocrab::Error;
+
+/// Wait before the first retry, the minimum advised by GitHub
+pub const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Upper bound of a single wait
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// Retries attempted before giving up on a rate limited request
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Whether `err` is a GitHub secondary rate limit response
+pub fn is_secondary_rate_limit(err: &Error) -> bool {
+    match err {
+        Error::GitHub { source, .. } => {
+            is_secondary_rate_limit_response(source.status_code, &source.message)
+        }
+        _ => false,
+    }
+}
+
+fn is_secondary_rate_limit_response(status: StatusCode, message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+
+    matches!(
+        status,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ) && (message.contains("secondary rate limit") || message.contains("abuse detection"))
+}
+
+/// Wait before retry number `attempt`, starting from zero
+fn backoff(attempt: u32) -> Duration {
+    SECONDARY_RATE_LIMIT_WAIT
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RATE_LIMIT_WAIT)
+}
+
+/// Sends the request built by `request`, waiting and retrying when it hits a
+/// secondary rate limit
+pub async fn with_rate_limit_retry<T, F, Fut>(request: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    retry_rate_limited(
+        request,
+        is_secondary_rate_limit,
+        fluvio_future::timer::sleep,
+    )
+    .await
+}
+
+async fn retry_rate_limited<T, E, F, Fut, S, SFut>(
+    mut request: F,
+    is_rate_limited: impl Fn(&E) -> bool,
+    mut sleep: S,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    S: FnMut(Duration) -> SFut,
+    SFut: Future,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request().await {
+            Err(err) if attempt < MAX_RATE_LIMIT_RETRIES && is_rate_limited(&err) => {
+                let wait = backoff(attempt);
+
+                attempt += 1;
+                tracing::warn!(
+                    "GitHub secondary rate limit reached, retrying in {}s (attempt {attempt} of {MAX_RATE_LIMIT_RETRIES})",
+                    wait.as_secs()
+                );
+                sleep(wait).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    const SECONDARY: &str =
+        "You have exceeded a secondary rate limit. Please wait a few minutes before you try again.";
+
+    fn is_forbidden(status: &StatusCode) -> bool {
+        *status == StatusCode::FORBIDDEN
+    }
+
+    #[test]
+    fn detects_secondary_rate_limits() {
+        assert!(is_secondary_rate_limit_response(
+            StatusCode::FORBIDDEN,
+            SECONDARY
+        ));
+        assert!(is_secondary_rate_limit_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            SECONDARY
+        ));
+        assert!(!is_secondary_rate_limit_response(
+            StatusCode::FORBIDDEN,
+            "API rate limit exceeded"
+        ));
+        assert!(!is_secondary_rate_limit_response(
+            StatusCode::NOT_FOUND,
+            SECONDARY
+        ));
+    }
+
+    #[test]
+    fn bounds_backoff() {
+        assert_eq!(backoff(0), SECONDARY_RATE_LIMIT_WAIT);
+        assert_eq!(backoff(1), Duration::from_secs(120));
+        assert_eq!(backoff(3), MAX_RATE_LIMIT_WAIT);
+        assert_eq!(backoff(40), MAX_RATE_LIMIT_WAIT);
+    }
+
+    #[test]
+    fn retries_secondary_rate_limits() {
+        let waits = RefCell::new(Vec::new());
+        let mut calls = 0;
+        let result = futures_lite::future::block_on(retry_rate_limited(
+            || {
+                calls += 1;
+                let result = if calls < 3 {
+                    Err(StatusCode::FORBIDDEN)
+                } else {
+                    Ok(calls)
+                };
+
+                async move { result }
+            },
+            is_forbidden,
+            |wait| {
+                waits.borrow_mut().push(wait);
+                async {}
+            },
+        ));
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(
+            waits.into_inner(),
+            vec![Duration::from_secs(60), Duration::from_secs(120)]
+        );
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let mut calls = 0;
+        let result: Result<(), _> = futures_lite::future::block_on(retry_rate_limited(
+            || {
+                calls += 1;
+                async { Err(StatusCode::FORBIDDEN) }
+            },
+            is_forbidden,
+            |_| async {},
+        ));
+
+        assert_eq!(result, Err(StatusCode::FORBIDDEN));
+        assert_eq!(calls, MAX_RATE_LIMIT_RETRIES + 1);
+
+        let mut calls = 0;
+        let result: Result<(), _> = futures_lite::future::block_on(retry_rate_limited(
+            || {
+                calls += 1;
+                async { Err(StatusCode::NOT_FOUND) }
+            },
+            is_forbidden,
+            |_| async {},
+        ));
+
+        assert_eq!(result, Err(StatusCode::NOT_FOUND));
+        assert_eq!(calls, 1);
+    }
+}
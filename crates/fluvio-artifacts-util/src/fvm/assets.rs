@@ -0,0 +1,117 @@
+//! Release Assets
+//!
+//! Release archives may ship assets next to the binary, such as shell
+//! completions, man pages or example configs. Assets are recognized by the
+//! directory they are archived in and extracted next to the binary, under
+//! `assets/<binary>`, so FVM can place them into standard locations.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory, relative to the download directory, where the assets of each
+/// artifact are extracted, e.g. `assets/fluvio/completions/fluvio.bash`
+pub const ARTIFACT_ASSETS_DIR: &str = "assets";
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssetKind {
+    BashCompletion,
+    ZshCompletion,
+    FishCompletion,
+    /// Man page, the section is the extension of the file, e.g. `fluvio.1`
+    ManPage,
+    /// Example configuration file
+    Example,
+}
+
+impl AssetKind {
+    /// Classifies an archive entry by the directory it is archived in, e.g.
+    /// `completions/fluvio.bash` or `man/fluvio.1`. Entries outside of the
+    /// asset directories are not assets.
+    pub fn classify(entry: &Path) -> Option<Self> {
+        let dirs: Vec<&str> = entry
+            .parent()?
+            .components()
+            .filter_map(|component| component.as_os_str().to_str())
+            .collect();
+        let file_name = entry.file_name()?.to_str()?;
+        let in_dir = |name: &str| dirs.contains(&name);
+
+        if in_dir("completions") || in_dir("completion") {
+            if file_name.ends_with(".bash") || in_dir("bash") {
+                Some(Self::BashCompletion)
+            } else if file_name.ends_with(".zsh") || file_name.starts_with('_') || in_dir("zsh") {
+                Some(Self::ZshCompletion)
+            } else if file_name.ends_with(".fish") || in_dir("fish") {
+                Some(Self::FishCompletion)
+            } else {
+                None
+            }
+        } else if in_dir("man") {
+            man_section(file_name).map(|_| Self::ManPage)
+        } else if in_dir("examples") || in_dir("config") {
+            Some(Self::Example)
+        } else {
+            None
+        }
+    }
+}
+
+/// Section of a man page file name, e.g. `1` for `fluvio.1` or `fluvio.1.gz`
+pub fn man_section(file_name: &str) -> Option<char> {
+    let name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    let (_, ext) = name.rsplit_once('.')?;
+    let mut chars = ext.chars();
+    let section = chars.next()?;
+
+    (section.is_ascii_digit() && section != '0' && chars.next().is_none()).then_some(section)
+}
+
+/// Path where an asset entry is extracted to, relative to the download
+/// directory. Entries with absolute paths or parent components are rejected.
+pub fn asset_path(binary: &str, entry: &Path) -> Option<PathBuf> {
+    let is_relative = entry
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+
+    is_relative.then(|| Path::new(ARTIFACT_ASSETS_DIR).join(binary).join(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_assets() {
+        let cases = [
+            ("completions/fluvio.bash", Some(AssetKind::BashCompletion)),
+            (
+                "share/completions/zsh/_fluvio",
+                Some(AssetKind::ZshCompletion),
+            ),
+            ("completions/fluvio.fish", Some(AssetKind::FishCompletion)),
+            ("completions/fluvio.ps1", None),
+            ("man/fluvio.1", Some(AssetKind::ManPage)),
+            ("man/fluvio-consume.1.gz", Some(AssetKind::ManPage)),
+            ("man/README.md", None),
+            ("examples/connector.yaml", Some(AssetKind::Example)),
+            ("fluvio", None),
+            ("bin/fluvio", None),
+        ];
+
+        for (entry, kind) in cases {
+            assert_eq!(AssetKind::classify(Path::new(entry)), kind, "{entry}");
+        }
+    }
+
+    #[test]
+    fn builds_asset_paths() {
+        assert_eq!(
+            asset_path("fluvio", Path::new("man/fluvio.1")),
+            Some(PathBuf::from("assets/fluvio/man/fluvio.1"))
+        );
+        assert_eq!(asset_path("fluvio", Path::new("../man/fluvio.1")), None);
+        assert_eq!(asset_path("fluvio", Path::new("/etc/fluvio.1")), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Channel Pointers
+//!
+//! Channels are resolved from release tag conventions: `stable` is the
+//! newest stable release, `latest` the `dev` release and `X.Y` the newest
+//! patch release of the minor version. Maintainers may override them in
+//! `release-tools/channels.json` in the Fluvio repository, which maps
+//! channel names to the release tags they point to:
+//!
+//! ```json
+//! { "channels": { "stable": "v0.11.7", "0.11": "v0.11.7" } }
+//! ```
+//!
+//! Listed channels resolve to their tag, so a channel can be moved, e.g.
+//! back to the previous release after a faulty one was published, without
+//! re-tagging releases. Channels which are not listed are resolved from tags.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::github::GitHubRepo;
+
+use super::Channel;
+
+/// Path of the channels metadata file in the Fluvio repository
+pub const CHANNELS_METADATA_PATH: &str = "release-tools/channels.json";
+
+/// URL of the channels metadata file on the default branch of `repo`
+pub fn channels_metadata_url(repo: &GitHubRepo) -> String {
+    repo.raw_file_url("master", CHANNELS_METADATA_PATH)
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ChannelsMetadata {
+    /// Release tag of each channel, keyed by channel name
+    #[serde(default)]
+    pub channels: BTreeMap<String, String>,
+}
+
+impl ChannelsMetadata {
+    /// Release tag `channel` points to. Channel names are matched as parsed,
+    /// so `dev` points `latest` as well. Version tags never move and are not
+    /// looked up.
+    pub fn tag_for(&self, channel: &Channel) -> Option<&str> {
+        if channel.is_version_tag() {
+            return None;
+        }
+
+        self.channels
+            .iter()
+            .find(|(name, _)| Channel::parse(name).is_ok_and(|named| named == *channel))
+            .map(|(_, tag)| tag.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::*;
+
+    #[test]
+    fn finds_channel_tags() {
+        let metadata: ChannelsMetadata = serde_json::from_str(
+            r#"{ "channels": { "stable": "v0.11.7", "dev": "dev", "0.11": "v0.11.7", "v0.11.8": "v0.11.7" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.tag_for(&Channel::Stable), Some("v0.11.7"));
+        assert_eq!(metadata.tag_for(&Channel::Latest), Some("dev"));
+        assert_eq!(metadata.tag_for(&Channel::Minor(0, 11)), Some("v0.11.7"));
+        assert_eq!(metadata.tag_for(&Channel::Minor(0, 10)), None);
+        assert_eq!(
+            metadata.tag_for(&Channel::Tag(Version::new(0, 11, 8))),
+            None
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Release Checksums Manifests
+//!
+//! GitHub publishes a digest for each release asset uploaded after it
+//! started computing them, older releases have none. A checksums manifest
+//! lists the SHA-256 digest of every asset of a release in the `sha256sum`
+//! format, so once attached to the release as [`CHECKSUMS_ASSET_NAME`] it
+//! can be checked with `sha256sum -c` and older versions gain integrity
+//! coverage retroactively.
+//!
+//! Manifests are also generated for local builds, listing the binaries of a
+//! build directory such as `target/release`, so FVM registers them as a
+//! version whose binaries are verified like the ones of a release.
+
+use std::collections::BTreeMap;
+use std::env::consts::EXE_SUFFIX;
+use std::fmt::Display;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+
+use crate::sha256_digest;
+
+/// Name of the checksums manifest attached to a release
+pub const CHECKSUMS_ASSET_NAME: &str = "checksums.sha256";
+
+/// SHA-256 digests of release assets
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChecksumsManifest {
+    /// Hex encoded digest of each asset, keyed by asset name
+    pub digests: BTreeMap<String, String>,
+}
+
+impl ChecksumsManifest {
+    /// Parses a manifest in the `sha256sum` format, binary mode markers
+    /// (`*name`) are accepted
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut digests = BTreeMap::new();
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let (digest, name) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow!("Invalid checksums manifest line: {line}"))?;
+            let name = name.trim_start();
+            let name = name.strip_prefix('*').unwrap_or(name);
+
+            if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!("Invalid SHA-256 digest for {name}: {digest}"));
+            }
+
+            digests.insert(name.to_string(), digest.to_ascii_lowercase());
+        }
+
+        Ok(Self { digests })
+    }
+
+    /// Manifest of the binaries `names` found in the build directory `dir`,
+    /// keyed by file name. Binaries not built are skipped.
+    pub fn from_local_build(dir: &Path, names: &[&str]) -> Result<Self> {
+        let mut digests = BTreeMap::new();
+
+        for name in names {
+            let file_name = format!("{name}{EXE_SUFFIX}");
+            let path = dir.join(&file_name);
+
+            if path.is_file() {
+                digests.insert(file_name, sha256_digest(&path)?);
+            }
+        }
+
+        Ok(Self { digests })
+    }
+
+    /// Hex encoded digest of the asset `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.digests.get(name).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, digest: impl Into<String>) {
+        self.digests.insert(name.into(), digest.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+}
+
+impl Display for ChecksumsManifest {
+    /// One `<digest>  <name>` line per asset, sorted by name
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, digest) in &self.digests {
+            writeln!(f, "{digest}  {name}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn parses_sha256sum_format() {
+        let contents = format!(
+            "{DIGEST}  fluvio-x86_64-unknown-linux-musl.zip\n{}  *smdk-x86_64-unknown-linux-musl.zip\n\n",
+            DIGEST.to_ascii_uppercase()
+        );
+        let manifest = ChecksumsManifest::parse(&contents).unwrap();
+
+        assert_eq!(manifest.len(), 2);
+        assert_eq!(
+            manifest.get("smdk-x86_64-unknown-linux-musl.zip"),
+            Some(DIGEST)
+        );
+        assert_eq!(
+            manifest.to_string(),
+            format!(
+                "{DIGEST}  fluvio-x86_64-unknown-linux-musl.zip\n{DIGEST}  smdk-x86_64-unknown-linux-musl.zip\n"
+            )
+        );
+        assert!(ChecksumsManifest::parse("not-a-digest  fluvio.zip").is_err());
+        assert!(ChecksumsManifest::parse(DIGEST).is_err());
+    }
+
+    #[test]
+    fn hashes_binaries_of_local_builds() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        std::fs::write(dir.path().join(format!("fluvio{EXE_SUFFIX}")), b"").unwrap();
+        std::fs::write(dir.path().join("fluvio.d"), b"dependencies").unwrap();
+
+        let manifest =
+            ChecksumsManifest::from_local_build(dir.path(), &["fluvio", "smdk"]).unwrap();
+
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest.get(&format!("fluvio{EXE_SUFFIX}")), Some(DIGEST));
+    }
+}
@@ -0,0 +1,141 @@
+//! CLI and Platform Compatibility
+//!
+//! Which platform versions a CLI version works with is listed in
+//! `release-tools/compatibility.json` in the Fluvio repository. A rule pairs
+//! a range of CLI versions with the range of platform versions they are
+//! compatible with. CLI versions without a rule are compatible with platform
+//! versions of the same minor version, e.g. CLI `0.11.8` with `0.11.x`.
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::github::GitHubRepo;
+
+use super::{compare_releases, is_stable_version};
+
+/// Path of the compatibility matrix in the Fluvio repository
+pub const COMPATIBILITY_METADATA_PATH: &str = "release-tools/compatibility.json";
+
+/// URL of the compatibility matrix on the default branch of `repo`
+pub fn compatibility_matrix_url(repo: &GitHubRepo) -> String {
+    repo.raw_file_url("master", COMPATIBILITY_METADATA_PATH)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CompatibilityRule {
+    /// CLI versions the rule applies to
+    pub cli: VersionReq,
+    /// Platform versions compatible with these CLI versions
+    pub platform: VersionReq,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CompatibilityMatrix {
+    #[serde(default)]
+    pub rules: Vec<CompatibilityRule>,
+}
+
+impl CompatibilityMatrix {
+    /// Whether the CLI at `cli` works with the platform at `platform`
+    pub fn is_compatible(&self, cli: &Version, platform: &Version) -> bool {
+        let mut rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.cli.matches(cli))
+            .peekable();
+
+        if rules.peek().is_none() {
+            return cli.major == platform.major && cli.minor == platform.minor;
+        }
+
+        rules.any(|rule| rule.platform.matches(platform))
+    }
+
+    /// The newest of the platform `versions` compatible with `cli`,
+    /// prerelease versions are skipped
+    pub fn newest_compatible<'a>(
+        &self,
+        cli: &Version,
+        versions: impl IntoIterator<Item = &'a Version>,
+    ) -> Option<&'a Version> {
+        versions
+            .into_iter()
+            .filter(|version| is_stable_version(version) && self.is_compatible(cli, version))
+            .max_by(|a, b| compare_releases(a, b))
+    }
+
+    /// The newest of the CLI `versions` compatible with the platform at
+    /// `platform`, prerelease versions are skipped
+    pub fn newest_compatible_cli<'a>(
+        &self,
+        platform: &Version,
+        versions: impl IntoIterator<Item = &'a Version>,
+    ) -> Option<&'a Version> {
+        versions
+            .into_iter()
+            .filter(|version| is_stable_version(version) && self.is_compatible(version, platform))
+            .max_by(|a, b| compare_releases(a, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATRIX: &str = r#"{
+        "rules": [
+            { "cli": ">=0.12.0, <0.13.0", "platform": ">=0.11.5, <0.13.0" }
+        ]
+    }"#;
+
+    #[test]
+    fn finds_newest_compatible_platform() {
+        let matrix: CompatibilityMatrix = serde_json::from_str(MATRIX).unwrap();
+        let platforms = [
+            Version::new(0, 11, 4),
+            Version::new(0, 11, 9),
+            Version::new(0, 12, 1),
+            Version::parse("0.12.2-dev.1").unwrap(),
+            Version::new(0, 13, 0),
+        ];
+
+        assert!(matrix.is_compatible(&Version::new(0, 12, 0), &Version::new(0, 11, 5)));
+        assert!(!matrix.is_compatible(&Version::new(0, 12, 0), &Version::new(0, 11, 4)));
+        assert_eq!(
+            matrix.newest_compatible(&Version::new(0, 12, 0), &platforms),
+            Some(&Version::new(0, 12, 1))
+        );
+        // Without a rule, the same minor version is compatible
+        assert_eq!(
+            matrix.newest_compatible(&Version::new(0, 11, 0), &platforms),
+            Some(&Version::new(0, 11, 9))
+        );
+        assert_eq!(
+            matrix.newest_compatible(&Version::new(0, 14, 0), &platforms),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_newest_compatible_cli() {
+        let matrix: CompatibilityMatrix = serde_json::from_str(MATRIX).unwrap();
+        let clis = [
+            Version::new(0, 11, 9),
+            Version::new(0, 12, 1),
+            Version::parse("0.12.2-dev.1").unwrap(),
+        ];
+
+        assert_eq!(
+            matrix.newest_compatible_cli(&Version::new(0, 11, 6), &clis),
+            Some(&Version::new(0, 12, 1))
+        );
+        assert_eq!(
+            matrix.newest_compatible_cli(&Version::new(0, 11, 4), &clis),
+            Some(&Version::new(0, 11, 9))
+        );
+        assert_eq!(
+            matrix.newest_compatible_cli(&Version::new(0, 10, 0), &clis),
+            None
+        );
+    }
+}
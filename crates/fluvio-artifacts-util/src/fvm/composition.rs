@@ -0,0 +1,137 @@
+//! Package Set Composition
+//!
+//! A package set may mix binaries from several releases, e.g. `fluvio` from
+//! `stable` with `smdk` from `latest`. Components are selected as
+//! `<binary>@<channel>`, and artifacts composed into a package set record
+//! the channel they were selected from.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Artifact, Channel, Error, PackageSet};
+
+/// Binary installed from a channel other than the one of its package set
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ComponentSelection {
+    /// Binary name, e.g. `smdk`
+    pub name: String,
+    pub channel: Channel,
+}
+
+impl ComponentSelection {
+    pub fn new(name: impl Into<String>, channel: Channel) -> Self {
+        Self {
+            name: name.into(),
+            channel,
+        }
+    }
+}
+
+impl Display for ComponentSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.channel)
+    }
+}
+
+impl FromStr for ComponentSelection {
+    type Err = Error;
+
+    /// Parses `<binary>@<channel>`, e.g. `smdk@latest` or `fluvio@0.11.8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, channel) = s
+            .trim()
+            .split_once('@')
+            .filter(|(name, _)| !name.is_empty())
+            .ok_or_else(|| Error::InvalidComponent(s.to_string()))?;
+
+        Ok(Self::new(name, channel.parse()?))
+    }
+}
+
+impl PackageSet {
+    /// Replaces the artifact with the name of `artifact`, or adds it,
+    /// recording `channel` as the channel it was selected from
+    pub fn compose(&mut self, mut artifact: Artifact, channel: &Channel) {
+        artifact.channel = Some(channel.to_owned());
+
+        match self
+            .artifacts
+            .iter_mut()
+            .find(|ours| ours.name == artifact.name)
+        {
+            Some(ours) => *ours = artifact,
+            None => self.artifacts.push(artifact),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::*;
+
+    fn artifact(name: &str, version: Version) -> Artifact {
+        Artifact {
+            name: name.to_string(),
+            download_url: format!("https://example.com/{name}/{version}"),
+            version,
+            sha256_digest: None,
+            variant: None,
+            channel: None,
+            entry: None,
+        }
+    }
+
+    #[test]
+    fn parses_component_selections() {
+        assert_eq!(
+            "smdk@latest".parse::<ComponentSelection>().unwrap(),
+            ComponentSelection::new("smdk", Channel::Latest)
+        );
+        assert_eq!(
+            "fluvio@v0.11.8".parse::<ComponentSelection>().unwrap(),
+            ComponentSelection::new("fluvio", Channel::Tag(Version::new(0, 11, 8)))
+        );
+        assert_eq!(
+            ComponentSelection::new("cdk", Channel::Minor(0, 11)).to_string(),
+            "cdk@0.11"
+        );
+
+        for invalid in ["smdk", "@latest", "smdk@"] {
+            assert!(invalid.parse::<ComponentSelection>().is_err(), "{invalid}");
+        }
+
+        assert!(matches!(
+            "smdk@lates".parse::<ComponentSelection>().unwrap_err(),
+            Error::MisspelledChannel(_, "latest")
+        ));
+    }
+
+    #[test]
+    fn composes_artifacts_from_other_channels() {
+        let stable = Version::new(0, 11, 8);
+        let latest = Version::new(0, 12, 0);
+        let mut pkgset = PackageSet {
+            pkgset: stable.clone(),
+            arch: String::from("aarch64-apple-darwin"),
+            release: None,
+            artifacts: vec![
+                artifact("fluvio", stable.clone()),
+                artifact("smdk", stable.clone()),
+            ],
+        };
+
+        pkgset.compose(artifact("smdk", latest.clone()), &Channel::Latest);
+        pkgset.compose(artifact("cdk", latest.clone()), &Channel::Latest);
+
+        assert_eq!(pkgset.pkgset, stable);
+        assert_eq!(pkgset.artifacts.len(), 3);
+        assert_eq!(pkgset.artifacts[0].channel, None);
+        assert_eq!(pkgset.artifacts[1].version, latest);
+        assert_eq!(pkgset.artifacts[1].channel, Some(Channel::Latest));
+        assert_eq!(pkgset.artifacts[2].name, "cdk");
+    }
+}
@@ -0,0 +1,97 @@
+//! End-of-Life Notices
+//!
+//! Releases which reached end-of-life or have known critical issues are
+//! listed in `release-tools/eol.json` in the Fluvio repository. Each notice
+//! covers a range of versions and may point to the minimal version which
+//! fixes the issue.
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::github::GitHubRepo;
+
+/// Path of the EOL metadata file in the Fluvio repository
+pub const EOL_METADATA_PATH: &str = "release-tools/eol.json";
+
+/// URL of the EOL metadata file on the default branch of `repo`
+pub fn eol_metadata_url(repo: &GitHubRepo) -> String {
+    repo.raw_file_url("master", EOL_METADATA_PATH)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct EolNotice {
+    /// Versions the notice applies to, e.g. `<0.11.0`
+    pub versions: VersionReq,
+    /// Whether the versions no longer receive fixes
+    #[serde(default)]
+    pub eol: bool,
+    /// Description of a known critical issue in these versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<String>,
+    /// Minimal version without the issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade_to: Option<Version>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct EolMetadata {
+    #[serde(default)]
+    pub notices: Vec<EolNotice>,
+}
+
+impl EolMetadata {
+    /// Notices which apply to `version`
+    pub fn notices_for(&self, version: &Version) -> Vec<&EolNotice> {
+        self.notices
+            .iter()
+            .filter(|notice| notice.versions.matches(version))
+            .collect()
+    }
+
+    pub fn is_eol(&self, version: &Version) -> bool {
+        self.notices_for(version).iter().any(|notice| notice.eol)
+    }
+
+    /// The minimal version which leaves every notice for `version` behind,
+    /// this is the highest `upgrade_to` of the matching notices
+    pub fn minimal_safe_upgrade(&self, version: &Version) -> Option<Version> {
+        self.notices_for(version)
+            .into_iter()
+            .filter_map(|notice| notice.upgrade_to.clone())
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"{
+        "notices": [
+            { "versions": "<0.11.0", "eol": true },
+            {
+                "versions": ">=0.10.14, <0.10.16",
+                "critical": "Consumer offsets are lost on SPU restart",
+                "upgrade_to": "0.10.16"
+            },
+            { "versions": "<0.11.0", "upgrade_to": "0.11.0" }
+        ]
+    }"#;
+
+    #[test]
+    fn finds_notices_and_safe_upgrade() {
+        let metadata: EolMetadata = serde_json::from_str(METADATA).unwrap();
+        let affected = Version::new(0, 10, 15);
+        let current = Version::new(0, 11, 8);
+
+        assert_eq!(metadata.notices_for(&affected).len(), 3);
+        assert!(metadata.is_eol(&affected));
+        assert_eq!(
+            metadata.minimal_safe_upgrade(&affected),
+            Some(Version::new(0, 11, 0))
+        );
+        assert!(metadata.notices_for(&current).is_empty());
+        assert!(!metadata.is_eol(&current));
+        assert_eq!(metadata.minimal_safe_upgrade(&current), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! Package Manifests
+//!
+//! Describes the artifacts of a package set, with the size of each archive,
+//! out of release metadata alone. Nothing is downloaded, so a manifest is
+//! cheap to fetch when planning an install or update: reporting what would
+//! be installed, checking disk space, or sizing the artifacts an update has
+//! to download.
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::{Artifact, PackageSet, ReleaseInfo};
+
+/// Artifact of a [`PackageManifest`]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ArtifactMetadata {
+    #[serde(flatten)]
+    pub artifact: Artifact,
+    /// Size in bytes of the archive served at the artifact download URL
+    pub size: u64,
+}
+
+/// Metadata of a [`PackageSet`] and the size of its artifacts, see
+/// [`super::Client::fetch_package_manifest`]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PackageManifest {
+    pub pkgset: Version,
+    pub arch: String,
+    pub artifacts: Vec<ArtifactMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseInfo>,
+}
+
+impl PackageManifest {
+    /// Bytes downloaded to install every artifact
+    pub fn total_size(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    /// Bytes downloaded to update `installed` to this package set, only
+    /// added and changed artifacts are downloaded, see [`PackageSet::diff`]
+    pub fn download_size(&self, installed: &PackageSet) -> u64 {
+        let downloads = self.package_set().diff(installed).downloads();
+
+        self.artifacts
+            .iter()
+            .filter(|metadata| {
+                downloads
+                    .iter()
+                    .any(|artifact| artifact.name == metadata.artifact.name)
+            })
+            .map(|metadata| metadata.size)
+            .sum()
+    }
+
+    /// The package set described by this manifest
+    pub fn package_set(&self) -> PackageSet {
+        PackageSet {
+            pkgset: self.pkgset.clone(),
+            arch: self.arch.clone(),
+            artifacts: self
+                .artifacts
+                .iter()
+                .map(|metadata| metadata.artifact.clone())
+                .collect(),
+            release: self.release.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(name: &str, digest: &str, size: u64) -> ArtifactMetadata {
+        ArtifactMetadata {
+            artifact: Artifact {
+                name: name.to_string(),
+                version: Version::new(0, 12, 0),
+                download_url: format!("https://example.com/{name}.zip"),
+                sha256_digest: Some(format!("sha256:{digest}")),
+                variant: None,
+                channel: None,
+                entry: None,
+            },
+            size,
+        }
+    }
+
+    #[test]
+    fn sizes_install_and_update_downloads() {
+        let manifest = PackageManifest {
+            pkgset: Version::new(0, 12, 0),
+            arch: "x86_64-unknown-linux-musl".to_string(),
+            artifacts: vec![
+                metadata("fluvio", "aaa", 30),
+                metadata("fluvio-run", "bbb", 50),
+                metadata("cdk", "ccc", 20),
+            ],
+            release: None,
+        };
+        let mut installed = manifest.package_set();
+
+        installed
+            .artifacts
+            .retain(|artifact| artifact.name != "cdk");
+        installed.artifacts[1].sha256_digest = Some("sha256:old".to_string());
+
+        assert_eq!(manifest.total_size(), 100);
+        assert_eq!(manifest.download_size(&installed), 70);
+        assert_eq!(manifest.download_size(&manifest.package_set()), 0);
+
+        let json = serde_json::to_string(&manifest).unwrap();
+
+        assert!(json.contains(r#""name":"fluvio","#));
+        assert_eq!(
+            serde_json::from_str::<PackageManifest>(&json).unwrap(),
+            manifest
+        );
+    }
+}
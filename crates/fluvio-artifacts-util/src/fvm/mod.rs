@@ -1,26 +1,80 @@
 //! Fluvio Version Manager (FVM) Types and HTTP Client.
 
+mod advisories;
 mod api;
+mod assets;
+mod channels;
+mod checksums;
+mod compatibility;
+mod composition;
+mod eol;
+mod manifest;
+mod naming;
+mod ordering;
+mod policy;
+mod requirements;
+mod transparency;
+mod variant;
 
 use std::fmt::Display;
 use std::cmp::Ordering;
 use std::str::FromStr;
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 use serde::{Deserialize, Serialize};
 use semver::Version;
 
-pub use api::{Client, Download};
+pub use advisories::{
+    ADVISORIES_FEED_PATH, AdvisoriesFeed, Advisory, AdvisoryKind, advisories_feed_url,
+};
+pub use api::{
+    AssetFailure, COMPATIBILITY_CACHE_FILENAME, COMPATIBILITY_CACHE_TTL, Client,
+    DEFAULT_BACKFILL_CONCURRENCY, Download, FVM_INSTALLABLE_BINARIES, IncompleteRelease,
+    ReleaseBackfill, ReleaseTarget, VersionMetadata,
+};
+pub use assets::{ARTIFACT_ASSETS_DIR, AssetKind, asset_path, man_section};
+pub use channels::{CHANNELS_METADATA_PATH, ChannelsMetadata, channels_metadata_url};
+pub use checksums::{CHECKSUMS_ASSET_NAME, ChecksumsManifest};
+pub use compatibility::{
+    COMPATIBILITY_METADATA_PATH, CompatibilityMatrix, CompatibilityRule, compatibility_matrix_url,
+};
+pub use composition::ComponentSelection;
+pub use eol::{EOL_METADATA_PATH, EolMetadata, EolNotice, eol_metadata_url};
+pub use manifest::{ArtifactMetadata, PackageManifest};
+pub use naming::{ArchiveFormat, AssetName, AssetNameScheme};
+pub use ordering::{
+    compare_releases, is_stable_version, newest_release, newest_stable_release, parse_release_tag,
+};
+pub use policy::{SignedVersionPolicy, VersionPolicy};
+pub use requirements::{
+    HostSystem, Libc, OsVersion, REQUIREMENTS_METADATA_PATH, Requirement, RequirementsMetadata,
+    UnmetRequirement, requirements_metadata_url,
+};
+pub use transparency::{
+    TransparencyIssue, TransparencyManifest, transparency_key, transparency_manifest_url,
+};
+pub use variant::{CpuVariant, VARIANT_SEPARATOR};
 
 pub const STABLE_VERSION_CHANNEL: &str = "stable";
 pub const LATEST_VERSION_CHANNEL: &str = "latest";
+/// Alias of [`LATEST_VERSION_CHANNEL`], named after the GitHub release tag
+pub const DEV_VERSION_CHANNEL: &str = "dev";
 pub const DEFAULT_PKGSET: &str = "default";
 
 #[derive(Clone, Debug, Error)]
 pub enum Error {
-    #[error("Invalid Fluvio Channel \"{0}\"")]
+    #[error(
+        "Invalid Fluvio Channel \"{0}\", expected \"stable\", \"latest\" (or \"dev\"), a minor version \"X.Y\" or a version \"X.Y.Z\" optionally prefixed with \"v\""
+    )]
     InvalidChannel(String),
+    #[error("Invalid Fluvio Channel \"{0}\", did you mean \"{1}\"?")]
+    MisspelledChannel(String, &'static str),
+    #[error("Invalid CPU variant \"{0}\"")]
+    InvalidVariant(String),
+    #[error("Invalid component \"{0}\", expected <binary>@<channel>, e.g. smdk@latest")]
+    InvalidComponent(String),
 }
 
 /// Package Set Channels based on Fluvio Channels
@@ -29,6 +83,8 @@ pub enum Error {
 pub enum Channel {
     Stable,
     Latest,
+    /// Most recent patch release of a minor version, e.g. `0.11`
+    Minor(u64, u64),
     Tag(Version),
     Other(String),
 }
@@ -38,6 +94,7 @@ impl Display for Channel {
         match self {
             Channel::Stable => write!(f, "{STABLE_VERSION_CHANNEL}"),
             Channel::Latest => write!(f, "{LATEST_VERSION_CHANNEL}"),
+            Channel::Minor(major, minor) => write!(f, "{major}.{minor}"),
             Channel::Tag(version) => write!(f, "{version}"),
             Channel::Other(version) => write!(f, "{version}"),
         }
@@ -57,24 +114,37 @@ impl Ord for Channel {
             Channel::Stable => match other {
                 Channel::Stable => Ordering::Equal,
                 Channel::Latest => Ordering::Greater,
+                Channel::Minor(_, _) => Ordering::Greater,
                 Channel::Tag(_) => Ordering::Greater,
                 Channel::Other(_) => Ordering::Greater,
             },
             Channel::Latest => match other {
                 Channel::Stable => Ordering::Less,
                 Channel::Latest => Ordering::Equal,
+                Channel::Minor(_, _) => Ordering::Greater,
+                Channel::Tag(_) => Ordering::Greater,
+                Channel::Other(_) => Ordering::Greater,
+            },
+            Channel::Minor(major, minor) => match other {
+                Channel::Stable => Ordering::Less,
+                Channel::Latest => Ordering::Less,
+                Channel::Minor(other_major, other_minor) => {
+                    (major, minor).cmp(&(other_major, other_minor))
+                }
                 Channel::Tag(_) => Ordering::Greater,
                 Channel::Other(_) => Ordering::Greater,
             },
             Channel::Tag(version) => match other {
                 Channel::Stable => Ordering::Less,
                 Channel::Latest => Ordering::Less,
+                Channel::Minor(_, _) => Ordering::Less,
                 Channel::Tag(tag_version) => version.cmp(tag_version),
                 Channel::Other(_) => Ordering::Greater,
             },
             Channel::Other(version) => match other {
                 Channel::Stable => Ordering::Less,
                 Channel::Latest => Ordering::Less,
+                Channel::Minor(_, _) => Ordering::Less,
                 Channel::Tag(_) => Ordering::Less,
                 Channel::Other(other_version) => version.cmp(other_version),
             },
@@ -99,19 +169,83 @@ impl Channel {
 impl FromStr for Channel {
     type Err = Error;
 
+    /// Parses channels and their aliases:
+    ///
+    /// - `stable`
+    /// - `latest`, or `dev`
+    /// - `X.Y` for the most recent patch release of a minor version
+    /// - `X.Y.Z` or `vX.Y.Z` for a version tag
+    ///
+    /// Other release tags, such as `ssdk-preview1`, are accepted as long as
+    /// they include a digit, so typos of channel names are rejected instead
+    /// of being looked up as release tags.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_ascii_lowercase().as_str() {
-            STABLE_VERSION_CHANNEL => Ok(Self::Stable),
-            LATEST_VERSION_CHANNEL => Ok(Self::Latest),
-            _ => {
-                if let Ok(version) = Version::parse(s) {
-                    Ok(Self::Tag(version))
-                } else {
-                    Ok(Self::Other(s.to_string()))
-                }
-            }
+        let s = s.trim();
+        let lowercase = s.to_ascii_lowercase();
+
+        match lowercase.as_str() {
+            STABLE_VERSION_CHANNEL => return Ok(Self::Stable),
+            LATEST_VERSION_CHANNEL | DEV_VERSION_CHANNEL => return Ok(Self::Latest),
+            _ => {}
+        }
+
+        let unprefixed = lowercase
+            .strip_prefix('v')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(&lowercase);
+
+        if let Ok(version) = Version::parse(unprefixed) {
+            return Ok(Self::Tag(version));
+        }
+
+        if unprefixed.starts_with(|c: char| c.is_ascii_digit()) {
+            return match unprefixed.split_once('.') {
+                Some((major, minor)) => match (major.parse(), minor.parse()) {
+                    (Ok(major), Ok(minor)) => Ok(Self::Minor(major, minor)),
+                    _ => Err(Error::InvalidChannel(s.to_string())),
+                },
+                None => Err(Error::InvalidChannel(s.to_string())),
+            };
+        }
+
+        let suggestion = [
+            STABLE_VERSION_CHANNEL,
+            LATEST_VERSION_CHANNEL,
+            DEV_VERSION_CHANNEL,
+        ]
+        .into_iter()
+        .find(|channel| edit_distance(&lowercase, channel) <= 2);
+
+        if let Some(suggestion) = suggestion {
+            return Err(Error::MisspelledChannel(s.to_string(), suggestion));
         }
+
+        if s.is_empty() || !s.chars().any(|c| c.is_ascii_digit()) {
+            return Err(Error::InvalidChannel(s.to_string()));
+        }
+
+        Ok(Self::Other(s.to_string()))
+    }
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+
+        prev = curr;
     }
+
+    prev[b.len()]
 }
 
 /// Artifact metadata for a single downloadable item.
@@ -129,6 +263,17 @@ pub struct Artifact {
     /// `download_url` (e.g. the full `.zip` archive), not of any
     /// extracted inner binary.
     pub sha256_digest: Option<String>,
+    /// CPU optimized variant of the artifact, `None` for baseline builds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<CpuVariant>,
+    /// Channel the artifact was selected from when composed into a package
+    /// set of another channel, see [`PackageSet::compose`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
+    /// Path of the binary within the archive, e.g. `release/fluvio`,
+    /// overriding the selection of the archive entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
 }
 
 /// Fluvio Version Manager Package for a specific architecture and version.
@@ -149,6 +294,7 @@ impl From<PackageSetRecord> for PackageSet {
         PackageSet {
             pkgset: fluvio_version,
             arch: value.arch,
+            release: None,
             artifacts: value.artifacts,
         }
     }
@@ -160,6 +306,22 @@ pub struct PackageSet {
     pub pkgset: Version,
     pub arch: String,
     pub artifacts: Vec<Artifact>,
+    /// Release the package set was resolved from, `None` for package sets
+    /// not fetched from a release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseInfo>,
+}
+
+/// GitHub release a [`PackageSet`] was resolved from
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    /// Release tag, e.g. `v0.11.8` or `dev`
+    pub tag: String,
+    /// SHA of the commit the release was created from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<DateTime<Utc>>,
 }
 
 impl PackageSet {
@@ -202,13 +364,80 @@ impl PackageSet {
 
         new_artifacts
     }
+
+    /// Computes the artifacts to download for the `installed` [`PackageSet`]
+    /// to match this one.
+    ///
+    /// Artifacts are compared by digest when both sides have one, otherwise
+    /// by version.
+    pub fn diff(&self, installed: &PackageSet) -> PackageSetDiff {
+        let mut diff = PackageSetDiff::default();
+
+        for artifact in self.artifacts.iter() {
+            match installed
+                .artifacts
+                .iter()
+                .find(|ours| ours.name == artifact.name)
+            {
+                None => diff.added.push(artifact.to_owned()),
+                Some(ours) if ours.differs_from(artifact) => diff.changed.push(artifact.to_owned()),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = installed
+            .artifacts
+            .iter()
+            .filter(|ours| !self.artifacts.iter().any(|art| art.name == ours.name))
+            .map(|ours| ours.name.to_owned())
+            .collect();
+
+        diff
+    }
+}
+
+impl Artifact {
+    fn differs_from(&self, other: &Artifact) -> bool {
+        match (&self.sha256_digest, &other.sha256_digest) {
+            (Some(ours), Some(theirs)) => {
+                ours.trim_start_matches("sha256:") != theirs.trim_start_matches("sha256:")
+            }
+            _ => self.version != other.version,
+        }
+    }
+}
+
+/// Result of [`PackageSet::diff`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackageSetDiff {
+    /// Artifacts which are not installed
+    pub added: Vec<Artifact>,
+    /// Installed artifacts with a different digest or version upstream
+    pub changed: Vec<Artifact>,
+    /// Names of installed artifacts which are no longer published
+    pub removed: Vec<String>,
+}
+
+impl PackageSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Artifacts which have to be downloaded
+    pub fn downloads(&self) -> Vec<Artifact> {
+        self.added
+            .iter()
+            .chain(self.changed.iter())
+            .cloned()
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use super::{Artifact, Channel, PackageSet, Version};
+    use super::{Artifact, Channel, Error, PackageSet, PackageSetDiff, Version};
 
     #[test]
     fn parses_latest_channel_from_str() {
@@ -250,6 +479,46 @@ mod tests {
         assert!(ssdkp2 > ssdkp1);
     }
 
+    #[test]
+    fn parses_channel_aliases() {
+        assert_eq!(Channel::parse("dev").unwrap(), Channel::Latest);
+        assert_eq!(Channel::parse(" Stable ").unwrap(), Channel::Stable);
+        assert_eq!(Channel::parse("0.11").unwrap(), Channel::Minor(0, 11));
+        assert_eq!(
+            Channel::parse("v0.11.8").unwrap(),
+            Channel::Tag(Version::new(0, 11, 8))
+        );
+        assert_eq!(Channel::Minor(0, 11).to_string(), "0.11");
+    }
+
+    #[test]
+    fn rejects_invalid_channels() {
+        let err = Channel::parse("stabel").unwrap_err();
+
+        assert!(matches!(err, Error::MisspelledChannel(_, "stable")));
+        assert!(err.to_string().contains("did you mean \"stable\""));
+        assert!(matches!(
+            Channel::parse("lastest").unwrap_err(),
+            Error::MisspelledChannel(_, "latest")
+        ));
+
+        for invalid in ["", "nightly", "0", "0.11.x", "v1.a"] {
+            let err = Channel::parse(invalid).unwrap_err();
+
+            assert!(matches!(err, Error::InvalidChannel(_)), "{invalid}");
+            assert!(err.to_string().contains("X.Y.Z"));
+        }
+    }
+
+    #[test]
+    fn orders_minor_channels_between_latest_and_tags() {
+        let minor = Channel::parse("0.11").unwrap();
+
+        assert!(Channel::Latest > minor);
+        assert!(minor > Channel::parse("0.12.0").unwrap());
+        assert!(Channel::parse("0.12").unwrap() > minor);
+    }
+
     #[test]
     fn determines_tag_is_greater_than_other() {
         let tag = Channel::parse("0.1.0").unwrap();
@@ -258,6 +527,59 @@ mod tests {
         assert!(tag > other);
     }
 
+    fn artifact(name: &str, version: &str, digest: Option<&str>) -> Artifact {
+        Artifact {
+            name: name.to_string(),
+            version: Version::from_str(version).unwrap(),
+            download_url: format!("https://example.com/{name}"),
+            sha256_digest: digest.map(str::to_string),
+            variant: None,
+            channel: None,
+            entry: None,
+        }
+    }
+
+    #[test]
+    fn diffs_installed_package_set_by_digest() {
+        let installed = PackageSet {
+            pkgset: Version::new(0, 11, 8),
+            arch: String::from("aarch64-apple-darwin"),
+            release: None,
+            artifacts: vec![
+                artifact("fluvio", "0.11.8", Some("sha256:aaa")),
+                artifact("fluvio-run", "0.11.8", Some("sha256:bbb")),
+                artifact("cdk", "0.11.8", None),
+                artifact("smdk", "0.11.8", Some("sha256:ddd")),
+            ],
+        };
+        let upstream = PackageSet {
+            pkgset: Version::new(0, 11, 9),
+            arch: String::from("aarch64-apple-darwin"),
+            release: None,
+            artifacts: vec![
+                artifact("fluvio", "0.11.9", Some("aaa")),
+                artifact("fluvio-run", "0.11.9", Some("sha256:ccc")),
+                artifact("cdk", "0.11.9", Some("sha256:eee")),
+                artifact("fluvio-cloud", "0.2.19", None),
+            ],
+        };
+        let diff = upstream.diff(&installed);
+
+        assert_eq!(
+            diff,
+            PackageSetDiff {
+                added: vec![artifact("fluvio-cloud", "0.2.19", None)],
+                changed: vec![
+                    artifact("fluvio-run", "0.11.9", Some("sha256:ccc")),
+                    artifact("cdk", "0.11.9", Some("sha256:eee")),
+                ],
+                removed: vec![String::from("smdk")],
+            }
+        );
+        assert_eq!(diff.downloads().len(), 3);
+        assert!(installed.diff(&installed).is_empty());
+    }
+
     #[test]
     fn determines_if_other_packageset_includes_diff_artifacts() {
         let package_sets = vec![
@@ -265,6 +587,7 @@ mod tests {
                 PackageSet {
                     pkgset: Version::from_str("0.1.0").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -272,11 +595,15 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 PackageSet {
                     pkgset: Version::from_str("0.1.0").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.11.6").unwrap(),
@@ -284,6 +611,9 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 1,
@@ -292,6 +622,7 @@ mod tests {
                 PackageSet {
                     pkgset: Version::from_str("0.2.0").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -299,11 +630,15 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 PackageSet {
                     pkgset: Version::from_str("0.2.1").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -311,6 +646,9 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 0,
@@ -319,6 +657,7 @@ mod tests {
                 PackageSet {
                     pkgset: Version::from_str("0.3.1").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -326,11 +665,15 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 PackageSet {
                     pkgset: Version::from_str("0.3.2").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![],
                 },
                 0,
@@ -339,6 +682,7 @@ mod tests {
                 PackageSet {
                     pkgset: Version::from_str("0.4.7").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -346,11 +690,15 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 PackageSet {
                     pkgset: Version::from_str("0.4.7").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("new-pkg"),
                         version: Version::from_str("0.1.0").unwrap(),
@@ -358,6 +706,9 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 1,
@@ -366,11 +717,13 @@ mod tests {
                 PackageSet {
                     pkgset: Version::from_str("0.3.1").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![],
                 },
                 PackageSet {
                     pkgset: Version::from_str("0.3.2").unwrap(),
                     arch: String::from("aarch64-apple-darwin"),
+                    release: None,
                     artifacts: vec![Artifact {
                         name: String::from("fluvio-cloud"),
                         version: Version::from_str("0.2.19").unwrap(),
@@ -378,6 +731,9 @@ mod tests {
                             "https://packages.fluvio.io/fluvio-cloud/aarch64-apple-darwin/0.2.19",
                         ),
                         sha256_digest: None,
+                        variant: None,
+                        channel: None,
+                        entry: None,
                     }],
                 },
                 1,
@@ -0,0 +1,254 @@
+//! Release Asset Names
+//!
+//! Releases publish an archive per binary and target, currently named
+//! `<binary>-<target>.zip`, e.g. `fluvio-x86_64-unknown-linux-musl.zip`,
+//! with an optional `+<variant>` after the target for CPU optimized builds.
+//!
+//! Each naming convention is an [`AssetNameScheme`]. The assets of a release
+//! are matched against the [known schemes](AssetNameScheme::KNOWN) in order,
+//! so releases named after a newer convention, such as
+//! `fluvio-0.12.0-x86_64-unknown-linux-musl.tar.gz`, still resolve to
+//! package sets instead of empty ones.
+
+use semver::Version;
+
+use super::{CpuVariant, VARIANT_SEPARATOR};
+
+/// Archive format of release assets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub const ALL: [ArchiveFormat; 2] = [Self::Zip, Self::TarGz];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Zip => ".zip",
+            Self::TarGz => ".tar.gz",
+        }
+    }
+
+    /// Splits `name` into its stem and archive format, `None` if `name` is
+    /// not an archive of a known format, e.g. a `.sha256` checksum file
+    pub fn split(name: &str) -> Option<(&str, Self)> {
+        Self::ALL.iter().find_map(|format| {
+            name.strip_suffix(format.extension())
+                .map(|stem| (stem, *format))
+        })
+    }
+}
+
+/// Binary, target and variant parsed out of an asset name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetName {
+    pub binary: String,
+    /// Version embedded in the name, with versioned schemes only
+    pub version: Option<Version>,
+    pub target: String,
+    pub variant: Option<CpuVariant>,
+}
+
+/// Convention release assets are named after
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssetNameScheme {
+    pub format: ArchiveFormat,
+    /// Whether the version follows the binary name, as in
+    /// `fluvio-0.12.0-<target>.zip`
+    pub versioned: bool,
+}
+
+impl AssetNameScheme {
+    /// `<binary>-<target>[+<variant>].zip`, the convention of current releases
+    pub const CURRENT: Self = Self {
+        format: ArchiveFormat::Zip,
+        versioned: false,
+    };
+
+    /// Schemes in the order they are matched against release assets
+    pub const KNOWN: [Self; 4] = [
+        Self::CURRENT,
+        Self {
+            format: ArchiveFormat::TarGz,
+            versioned: false,
+        },
+        Self {
+            format: ArchiveFormat::Zip,
+            versioned: true,
+        },
+        Self {
+            format: ArchiveFormat::TarGz,
+            versioned: true,
+        },
+    ];
+
+    /// Name of the asset of `binary` at `version` for `target`, `version` is
+    /// only included by versioned schemes
+    pub fn build(
+        &self,
+        binary: &str,
+        version: &Version,
+        target: &str,
+        variant: Option<CpuVariant>,
+    ) -> String {
+        let mut name = if self.versioned {
+            format!("{binary}-{version}-{target}")
+        } else {
+            format!("{binary}-{target}")
+        };
+
+        if let Some(variant) = variant {
+            name.push(VARIANT_SEPARATOR);
+            name.push_str(variant.as_str());
+        }
+
+        name.push_str(self.format.extension());
+        name
+    }
+
+    /// Parses `name` as the asset of a binary for `target`, `None` if it
+    /// does not follow this scheme
+    pub fn parse(&self, name: &str, target: &str) -> Option<AssetName> {
+        let (stem, format) = ArchiveFormat::split(name)?;
+
+        if format != self.format {
+            return None;
+        }
+
+        let (stem, variant) = match stem.split_once(VARIANT_SEPARATOR) {
+            Some((stem, variant)) => (stem, Some(variant.parse().ok()?)),
+            None => (stem, None),
+        };
+        let prefix = stem.strip_suffix(target)?.strip_suffix('-')?;
+        // Versions may contain `-`, as in `0.12.0-rc1`, so the leftmost
+        // separator followed by a version splits the binary from the version
+        let embedded = prefix.match_indices('-').find_map(|(idx, _)| {
+            let version = &prefix[idx + 1..];
+            let version = Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()?;
+
+            Some((&prefix[..idx], version))
+        });
+        let (binary, version) = match (self.versioned, embedded) {
+            (true, Some((binary, version))) => (binary, Some(version)),
+            (false, None) => (prefix, None),
+            _ => return None,
+        };
+
+        if binary.is_empty() {
+            return None;
+        }
+
+        Some(AssetName {
+            binary: binary.to_string(),
+            version,
+            target: target.to_string(),
+            variant,
+        })
+    }
+
+    /// The first known scheme with a baseline asset for `target` among
+    /// `names`
+    pub fn detect<'a>(names: impl IntoIterator<Item = &'a str>, target: &str) -> Option<Self> {
+        let names: Vec<&str> = names.into_iter().collect();
+
+        Self::KNOWN.into_iter().find(|scheme| {
+            names.iter().any(|name| {
+                scheme
+                    .parse(name, target)
+                    .is_some_and(|asset| asset.variant.is_none())
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "x86_64-unknown-linux-musl";
+
+    #[test]
+    fn builds_and_parses_names() {
+        let version = Version::new(0, 12, 0);
+
+        for scheme in AssetNameScheme::KNOWN {
+            for variant in [None, Some(CpuVariant::X86_64V3)] {
+                let name = scheme.build("fluvio-run", &version, TARGET, variant);
+                let asset = scheme.parse(&name, TARGET).unwrap();
+
+                assert_eq!(asset.binary, "fluvio-run");
+                assert_eq!(asset.version, scheme.versioned.then(|| version.clone()));
+                assert_eq!(asset.variant, variant);
+                assert!(scheme.parse(&name, "aarch64-apple-darwin").is_none());
+            }
+        }
+
+        assert_eq!(
+            AssetNameScheme::CURRENT.build("fluvio", &version, TARGET, Some(CpuVariant::X86_64V3)),
+            "fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip"
+        );
+    }
+
+    #[test]
+    fn rejects_names_of_other_schemes() {
+        let current = AssetNameScheme::CURRENT;
+
+        assert!(
+            current
+                .parse("fluvio-x86_64-unknown-linux-musl.zip.sha256", TARGET)
+                .is_none()
+        );
+        assert!(
+            current
+                .parse("fluvio-x86_64-unknown-linux-musl.tar.gz", TARGET)
+                .is_none()
+        );
+        assert!(
+            current
+                .parse("fluvio-v0.12.0-x86_64-unknown-linux-musl.zip", TARGET)
+                .is_none()
+        );
+        assert!(
+            current
+                .parse("fluvio-x86_64-unknown-linux-musl+x86_64-v9.zip", TARGET)
+                .is_none()
+        );
+        assert!(
+            AssetNameScheme::KNOWN[2]
+                .parse("fluvio-x86_64-unknown-linux-musl.zip", TARGET)
+                .is_none()
+        );
+        assert_eq!(
+            AssetNameScheme::KNOWN[2]
+                .parse("fluvio-v0.12.0-x86_64-unknown-linux-musl.zip", TARGET)
+                .unwrap()
+                .version,
+            Some(Version::new(0, 12, 0))
+        );
+    }
+
+    #[test]
+    fn detects_scheme_of_release() {
+        let current = [
+            "fluvio-x86_64-unknown-linux-musl.zip",
+            "cdk-aarch64-apple-darwin.zip",
+        ];
+        let next = [
+            "fluvio-0.12.0-x86_64-unknown-linux-musl+x86_64-v3.tar.gz",
+            "fluvio-0.12.0-x86_64-unknown-linux-musl.tar.gz",
+            "fluvio-0.12.0-x86_64-unknown-linux-musl.tar.gz.sha256",
+        ];
+
+        assert_eq!(
+            AssetNameScheme::detect(current, TARGET),
+            Some(AssetNameScheme::CURRENT)
+        );
+        assert_eq!(
+            AssetNameScheme::detect(next, TARGET),
+            Some(AssetNameScheme::KNOWN[3])
+        );
+        assert_eq!(AssetNameScheme::detect(next, "aarch64-apple-darwin"), None);
+    }
+}
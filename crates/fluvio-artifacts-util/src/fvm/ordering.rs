@@ -0,0 +1,109 @@
+//! Release Ordering
+//!
+//! Rules used to resolve channels out of published releases, regardless of
+//! how the releases are labelled on GitHub:
+//!
+//! - The `stable` channel only resolves versions without a pre-release tag,
+//!   so a release tagged `v0.12.0-rc1` but not flagged as a pre-release is
+//!   never picked as the latest stable release.
+//! - Releases rank by semver precedence, a pre-release ranks below its
+//!   release, and build metadata breaks ties between versions of equal
+//!   precedence, e.g. `0.12.0+2` ranks above `0.12.0+1`.
+
+use std::cmp::Ordering;
+
+use semver::Version;
+
+/// Whether `version` can be resolved by the `stable` channel
+pub fn is_stable_version(version: &Version) -> bool {
+    version.pre.is_empty()
+}
+
+/// Ranks releases by semver precedence, then by build metadata
+pub fn compare_releases(a: &Version, b: &Version) -> Ordering {
+    a.cmp_precedence(b).then_with(|| a.build.cmp(&b.build))
+}
+
+/// Parses a release tag such as `v0.11.8`, `None` for tags which are not
+/// versions such as `dev`
+pub fn parse_release_tag(tag: &str) -> Option<Version> {
+    Version::parse(tag.trim().trim_start_matches('v')).ok()
+}
+
+/// Position and version of the newest stable release out of release `tags`
+pub fn newest_stable_release<'a>(
+    tags: impl IntoIterator<Item = &'a str>,
+) -> Option<(usize, Version)> {
+    tags.into_iter()
+        .enumerate()
+        .filter_map(|(idx, tag)| Some((idx, parse_release_tag(tag)?)))
+        .filter(|(_, version)| is_stable_version(version))
+        .max_by(|(_, a), (_, b)| compare_releases(a, b))
+}
+
+/// Position and version of the newest release out of release `tags`,
+/// including pre-releases
+pub fn newest_release<'a>(tags: impl IntoIterator<Item = &'a str>) -> Option<(usize, Version)> {
+    tags.into_iter()
+        .enumerate()
+        .filter_map(|(idx, tag)| Some((idx, parse_release_tag(tag)?)))
+        .max_by(|(_, a), (_, b)| compare_releases(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn ranks_by_precedence_then_build_metadata() {
+        let mut versions = [
+            "0.12.0+2",
+            "0.12.0-rc.10",
+            "0.11.10",
+            "0.12.0-rc.2",
+            "0.12.0+1",
+            "0.12.0",
+            "0.12.0-alpha",
+        ]
+        .map(version);
+
+        versions.sort_by(compare_releases);
+
+        assert_eq!(
+            versions.map(|version| version.to_string()),
+            [
+                "0.11.10",
+                "0.12.0-alpha",
+                "0.12.0-rc.2",
+                "0.12.0-rc.10",
+                "0.12.0",
+                "0.12.0+1",
+                "0.12.0+2",
+            ]
+        );
+    }
+
+    #[test]
+    fn stable_excludes_pre_releases() {
+        let tags = [
+            "v0.11.9",
+            "dev",
+            "v0.12.0-rc1",
+            "v0.11.10+build.2",
+            "v0.11.10",
+        ];
+
+        assert!(!is_stable_version(&version("0.12.0-rc1")));
+        assert!(is_stable_version(&version("0.11.10+build.2")));
+        assert_eq!(
+            newest_stable_release(tags),
+            Some((3, version("0.11.10+build.2")))
+        );
+        assert_eq!(newest_release(tags), Some((2, version("0.12.0-rc1"))));
+        assert_eq!(newest_stable_release(["v0.12.0-rc1", "dev"]), None);
+    }
+}
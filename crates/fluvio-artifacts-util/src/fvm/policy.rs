@@ -0,0 +1,135 @@
+//! Version Policies
+//!
+//! Platform teams can restrict the Fluvio versions installed and used on
+//! shared infrastructure to an allow-list published at a URL they control.
+//! The policy is signed with an Ed25519 key, so a compromised host serving
+//! it cannot widen the allow-list.
+//!
+//! Policies are JSON files such as:
+//!
+//! ```json
+//! {
+//!   "payload": "{\"allowed\": [\">=0.11.8, <0.12.0\"], \"contact\": \"#platform\"}",
+//!   "signature": "<base64 Ed25519 signature of the payload>"
+//! }
+//! ```
+//!
+//! The payload is signed as is, so it is kept as a string rather than
+//! re-serialized before verifying it.
+
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rustls::pki_types::alg_id;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// Versions allowed by a team
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct VersionPolicy {
+    /// Allowed version ranges, a version matching any of them is allowed
+    pub allowed: Vec<VersionReq>,
+    /// Who to ask for another version, e.g. a chat channel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+}
+
+impl VersionPolicy {
+    pub fn allows(&self, version: &Version) -> bool {
+        self.allowed.iter().any(|req| req.matches(version))
+    }
+}
+
+/// Version policy as published, with the signature of its payload
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SignedVersionPolicy {
+    /// The JSON encoded [`VersionPolicy`]
+    pub payload: String,
+    /// Base64 encoded Ed25519 signature of `payload`
+    pub signature: String,
+}
+
+impl SignedVersionPolicy {
+    /// Verifies the signature with `public_key`, a base64 encoded Ed25519
+    /// public key, and parses the policy
+    pub fn verify(&self, public_key: &str) -> Result<VersionPolicy> {
+        let public_key = STANDARD
+            .decode(public_key.trim())
+            .map_err(|err| anyhow!("Invalid version policy key: {err}"))?;
+        let signature = STANDARD
+            .decode(self.signature.trim())
+            .map_err(|err| anyhow!("Invalid version policy signature: {err}"))?;
+
+        verify_ed25519(&public_key, self.payload.as_bytes(), &signature)?;
+
+        serde_json::from_str(&self.payload)
+            .map_err(|err| anyhow!("Invalid version policy payload: {err}"))
+    }
+}
+
+/// Verifies an Ed25519 `signature` of `message` with a raw 32 bytes
+/// `public_key`
+fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+    let algorithm = rustls::crypto::aws_lc_rs::default_provider()
+        .signature_verification_algorithms
+        .all
+        .iter()
+        .find(|algorithm| algorithm.public_key_alg_id() == alg_id::ED25519)
+        .copied()
+        .ok_or_else(|| anyhow!("Ed25519 signatures are not supported"))?;
+
+    algorithm
+        .verify_signature(public_key, message, signature)
+        .map_err(|_| anyhow!("Version policy signature is not valid for the configured key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::SignatureScheme;
+    use rustls::pki_types::PrivatePkcs8KeyDer;
+
+    use super::*;
+
+    /// PKCS#8 encoded Ed25519 test key
+    const PRIVATE_KEY: &str = "MC4CAQAwBQYDK2VwBCIEIPAomoG7AKsLkTDOhUzh9zpjUfXuUXdXOdaJpA9i3bWa";
+    const PUBLIC_KEY: &str = "wwG1I3EWI5RWF1Be8X16RHIF0eZ+Jx3kkHpXTLwJkuA=";
+
+    fn sign(payload: &str) -> SignedVersionPolicy {
+        let der = PrivatePkcs8KeyDer::from(STANDARD.decode(PRIVATE_KEY).unwrap());
+        let signer = rustls::crypto::aws_lc_rs::sign::any_eddsa_type(&der)
+            .unwrap()
+            .choose_scheme(&[SignatureScheme::ED25519])
+            .unwrap();
+
+        SignedVersionPolicy {
+            payload: payload.to_string(),
+            signature: STANDARD.encode(signer.sign(payload.as_bytes()).unwrap()),
+        }
+    }
+
+    #[test]
+    fn verifies_signed_policies() {
+        let signed = sign(r#"{ "allowed": [">=0.11.8, <0.12.0", "=0.12.1"] }"#);
+        let policy = signed.verify(PUBLIC_KEY).unwrap();
+
+        assert!(policy.allows(&Version::new(0, 11, 9)));
+        assert!(policy.allows(&Version::new(0, 12, 1)));
+        assert!(!policy.allows(&Version::new(0, 12, 0)));
+        assert!(!VersionPolicy::default().allows(&Version::new(0, 11, 9)));
+    }
+
+    #[test]
+    fn rejects_tampered_policies() {
+        let mut signed = sign(r#"{ "allowed": ["=0.11.8"] }"#);
+
+        signed.payload = r#"{ "allowed": ["*"] }"#.to_string();
+
+        assert!(signed.verify(PUBLIC_KEY).is_err());
+        assert!(
+            sign(r#"{ "allowed": ["=0.11.8"] }"#)
+                .verify("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=")
+                .is_err()
+        );
+        assert!(sign("not a policy").verify(PUBLIC_KEY).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Host System Requirements
+//!
+//! Minimum C library and operating system versions of released binaries are
+//! listed in `release-tools/requirements.json` in the Fluvio repository. A
+//! requirement covers a range of versions and, optionally, the targets it
+//! applies to, e.g. binaries for `x86_64-unknown-linux-gnu` from `0.12.0`
+//! on need glibc `2.34`. Binaries for musl targets are statically linked and
+//! have no C library requirement.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::process::Command;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::github::GitHubRepo;
+
+/// Path of the requirements metadata file in the Fluvio repository
+pub const REQUIREMENTS_METADATA_PATH: &str = "release-tools/requirements.json";
+
+/// URL of the requirements metadata file on the default branch of `repo`
+pub fn requirements_metadata_url(repo: &GitHubRepo) -> String {
+    repo.raw_file_url("master", REQUIREMENTS_METADATA_PATH)
+}
+
+/// Dotted version of a C library or operating system, e.g. `2.34` or `11.0`.
+/// Missing components compare as zero, so `11` equals `11.0.0`.
+#[derive(Clone, Debug)]
+pub struct OsVersion(Vec<u64>);
+
+impl OsVersion {
+    fn component(&self, idx: usize) -> u64 {
+        self.0.get(idx).copied().unwrap_or_default()
+    }
+}
+
+impl PartialEq for OsVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OsVersion {}
+
+impl PartialOrd for OsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OsVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (0..self.0.len().max(other.0.len()))
+            .map(|idx| self.component(idx).cmp(&other.component(idx)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl Display for OsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let components: Vec<String> = self.0.iter().map(u64::to_string).collect();
+
+        write!(f, "{}", components.join("."))
+    }
+}
+
+impl FromStr for OsVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components = s
+            .trim()
+            .split('.')
+            .map(str::parse)
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| anyhow!("invalid version \"{s}\", expected e.g. 2.34"))?;
+
+        Ok(Self(components))
+    }
+}
+
+impl Serialize for OsVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OsVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Requirement {
+    /// Versions the requirement applies to, e.g. `>=0.12.0`
+    pub versions: VersionReq,
+    /// Targets the requirement applies to, every target when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Minimum glibc version, only checked for `*-linux-gnu*` targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glibc: Option<OsVersion>,
+    /// Minimum macOS version, only checked for `*-apple-darwin` targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub macos: Option<OsVersion>,
+}
+
+impl Requirement {
+    fn applies_to(&self, version: &Version, target: &str) -> bool {
+        self.versions.matches(version)
+            && (self.targets.is_empty() || self.targets.iter().any(|t| t == target))
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RequirementsMetadata {
+    #[serde(default)]
+    pub requirements: Vec<Requirement>,
+}
+
+impl RequirementsMetadata {
+    /// Requirements of the binaries of `version` for `target` which `host`
+    /// does not meet
+    pub fn unmet(
+        &self,
+        version: &Version,
+        target: &str,
+        host: &HostSystem,
+    ) -> Vec<UnmetRequirement> {
+        let applicable: Vec<&Requirement> = self
+            .requirements
+            .iter()
+            .filter(|requirement| requirement.applies_to(version, target))
+            .collect();
+        let mut unmet = Vec::new();
+
+        if target.contains("-linux-gnu")
+            && let Some(required) = applicable.iter().filter_map(|req| req.glibc.clone()).max()
+        {
+            let found = match &host.libc {
+                Libc::Glibc(found) if *found >= required => None,
+                Libc::Glibc(found) => Some(Some(format!("glibc {found}"))),
+                Libc::Musl => Some(Some(String::from("musl"))),
+                Libc::Unknown => Some(None),
+            };
+
+            if let Some(found) = found {
+                unmet.push(UnmetRequirement {
+                    component: "glibc",
+                    required,
+                    found,
+                });
+            }
+        }
+
+        if target.ends_with("-apple-darwin")
+            && let Some(required) = applicable.iter().filter_map(|req| req.macos.clone()).max()
+        {
+            let found = match &host.macos {
+                Some(found) if *found >= required => None,
+                Some(found) => Some(Some(format!("macOS {found}"))),
+                None => Some(None),
+            };
+
+            if let Some(found) = found {
+                unmet.push(UnmetRequirement {
+                    component: "macOS",
+                    required,
+                    found,
+                });
+            }
+        }
+
+        unmet
+    }
+}
+
+/// Requirement the host does not meet, or could not be checked
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnmetRequirement {
+    /// Name of the required component, e.g. `glibc`
+    pub component: &'static str,
+    pub required: OsVersion,
+    /// What the host has instead, `None` if it could not be detected
+    pub found: Option<String>,
+}
+
+impl UnmetRequirement {
+    /// Whether the host is known not to meet the requirement, rather than
+    /// its version being unknown
+    pub fn is_known(&self) -> bool {
+        self.found.is_some()
+    }
+}
+
+impl Display for UnmetRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} or later is required",
+            self.component, self.required
+        )?;
+
+        match &self.found {
+            Some(found) => write!(f, ", found {found}"),
+            None => write!(f, ", but its version could not be detected"),
+        }
+    }
+}
+
+/// C library of a Linux host
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Libc {
+    Glibc(OsVersion),
+    Musl,
+    Unknown,
+}
+
+/// C library and operating system versions of the host
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostSystem {
+    pub libc: Libc,
+    pub macos: Option<OsVersion>,
+}
+
+impl HostSystem {
+    /// Detects the host C library and macOS version with `getconf`, `ldd`
+    /// and `sw_vers`
+    pub fn detect() -> Self {
+        let libc = if cfg!(target_os = "linux") {
+            detect_libc()
+        } else {
+            Libc::Unknown
+        };
+        let macos = if cfg!(target_os = "macos") {
+            command_output("sw_vers", &["-productVersion"])
+                .and_then(|output| output.trim().parse().ok())
+        } else {
+            None
+        };
+
+        Self { libc, macos }
+    }
+}
+
+fn detect_libc() -> Libc {
+    // e.g. `glibc 2.35`, not supported on musl
+    if let Some(version) = command_output("getconf", &["GNU_LIBC_VERSION"])
+        .and_then(|output| output.trim().strip_prefix("glibc ")?.parse().ok())
+    {
+        return Libc::Glibc(version);
+    }
+
+    command_output("ldd", &["--version"])
+        .map(|output| parse_ldd_version(&output))
+        .unwrap_or(Libc::Unknown)
+}
+
+/// Parses the output of `ldd --version`, e.g. `ldd (GNU libc) 2.35` or
+/// `musl libc (x86_64)`
+fn parse_ldd_version(output: &str) -> Libc {
+    let first_line = output.lines().next().unwrap_or_default();
+
+    if output.contains("musl") {
+        return Libc::Musl;
+    }
+
+    first_line
+        .split_whitespace()
+        .last()
+        .and_then(|version| version.parse().ok())
+        .map(Libc::Glibc)
+        .unwrap_or(Libc::Unknown)
+}
+
+/// Standard output and error of `program`, `None` if it could not be run.
+/// `ldd --version` on musl prints to standard error and fails.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Some(text).filter(|text| !text.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"{
+        "requirements": [
+            { "versions": ">=0.11.0", "glibc": "2.31", "macos": "11.0" },
+            {
+                "versions": ">=0.12.0",
+                "targets": ["x86_64-unknown-linux-gnu"],
+                "glibc": "2.34"
+            }
+        ]
+    }"#;
+
+    fn host(libc: Libc, macos: Option<&str>) -> HostSystem {
+        HostSystem {
+            libc,
+            macos: macos.map(|version| version.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn compares_os_versions() {
+        let parse = |s: &str| s.parse::<OsVersion>().unwrap();
+
+        assert!(parse("2.34") > parse("2.9"));
+        assert_eq!(parse("11"), parse("11.0.0"));
+        assert!(parse("10.15.7") < parse("11"));
+        assert!("2.x".parse::<OsVersion>().is_err());
+    }
+
+    #[test]
+    fn finds_unmet_requirements() {
+        let metadata: RequirementsMetadata = serde_json::from_str(METADATA).unwrap();
+        let version = Version::new(0, 12, 1);
+        let gnu = "x86_64-unknown-linux-gnu";
+        let old_glibc = host(Libc::Glibc("2.31".parse().unwrap()), None);
+
+        let unmet = metadata.unmet(&version, gnu, &old_glibc);
+
+        assert_eq!(unmet.len(), 1);
+        assert_eq!(
+            unmet[0].to_string(),
+            "glibc 2.34 or later is required, found glibc 2.31"
+        );
+        assert!(
+            metadata
+                .unmet(&Version::new(0, 11, 9), gnu, &old_glibc)
+                .is_empty()
+        );
+        assert!(
+            metadata
+                .unmet(&version, "aarch64-unknown-linux-gnu", &old_glibc)
+                .is_empty()
+        );
+        // Statically linked
+        assert!(
+            metadata
+                .unmet(
+                    &version,
+                    "x86_64-unknown-linux-musl",
+                    &host(Libc::Musl, None)
+                )
+                .is_empty()
+        );
+        assert_eq!(
+            metadata.unmet(&version, gnu, &host(Libc::Musl, None))[0].found,
+            Some(String::from("musl"))
+        );
+        assert!(!metadata.unmet(&version, gnu, &host(Libc::Unknown, None))[0].is_known());
+        assert_eq!(
+            metadata.unmet(
+                &version,
+                "aarch64-apple-darwin",
+                &host(Libc::Unknown, Some("10.15.7"))
+            )[0]
+            .to_string(),
+            "macOS 11.0 or later is required, found macOS 10.15.7"
+        );
+        assert!(
+            metadata
+                .unmet(
+                    &version,
+                    "aarch64-apple-darwin",
+                    &host(Libc::Unknown, Some("14.2"))
+                )
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parses_ldd_output() {
+        assert_eq!(
+            parse_ldd_version("ldd (Ubuntu GLIBC 2.35-0ubuntu3.1) 2.35\nCopyright"),
+            Libc::Glibc("2.35".parse().unwrap())
+        );
+        assert_eq!(
+            parse_ldd_version("musl libc (x86_64)\nVersion 1.2.4"),
+            Libc::Musl
+        );
+        assert_eq!(parse_ldd_version("unexpected"), Libc::Unknown);
+    }
+}
@@ -0,0 +1,241 @@
+//! Digest Transparency
+//!
+//! A package set is self-consistent when each downloaded artifact matches
+//! the digest listed next to it, which a poisoned mirror serving both the
+//! artifacts and the digests can satisfy. The transparency manifest is an
+//! independently published list of the digests the project released for a
+//! version and target, artifacts are only installed if their digest is
+//! listed there.
+//!
+//! Manifests are JSON files such as:
+//!
+//! ```json
+//! {
+//!   "version": "0.11.8",
+//!   "target": "x86_64-unknown-linux-musl",
+//!   "artifacts": {
+//!     "fluvio": "<sha256>",
+//!     "fluvio+x86_64-v3": "<sha256>"
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::{Artifact, VARIANT_SEPARATOR};
+
+/// Placeholder for the version in transparency URL templates
+pub const TRANSPARENCY_VERSION_PLACEHOLDER: &str = "{version}";
+
+/// Placeholder for the target in transparency URL templates
+pub const TRANSPARENCY_TARGET_PLACEHOLDER: &str = "{target}";
+
+/// URL of the transparency manifest for `version` and `target`.
+///
+/// The placeholders `{version}` and `{target}` in `template` are replaced,
+/// templates without placeholders are treated as base URLs and the manifest
+/// is expected at `<base>/<version>/<target>.json`.
+pub fn transparency_manifest_url(template: &str, version: &Version, target: &str) -> String {
+    if template.contains(TRANSPARENCY_VERSION_PLACEHOLDER)
+        || template.contains(TRANSPARENCY_TARGET_PLACEHOLDER)
+    {
+        return template
+            .replace(TRANSPARENCY_VERSION_PLACEHOLDER, &version.to_string())
+            .replace(TRANSPARENCY_TARGET_PLACEHOLDER, target);
+    }
+
+    format!("{}/{version}/{target}.json", template.trim_end_matches('/'))
+}
+
+/// Name of `artifact` in transparency manifests, variant builds are suffixed
+/// with their variant, e.g. `fluvio+x86_64-v3`
+pub fn transparency_key(artifact: &Artifact) -> String {
+    match artifact.variant {
+        Some(variant) => format!("{}{VARIANT_SEPARATOR}{variant}", artifact.name),
+        None => artifact.name.clone(),
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TransparencyManifest {
+    pub version: Version,
+    pub target: String,
+    /// Released SHA-256 digests by artifact name
+    pub artifacts: BTreeMap<String, String>,
+}
+
+/// A discrepancy between an artifact and the transparency manifest
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransparencyIssue {
+    /// The manifest is published for another version or target
+    WrongRelease { version: Version, target: String },
+    /// The artifact has no digest to compare with
+    MissingDigest { name: String },
+    /// The manifest doesn't list the artifact
+    Unlisted { name: String },
+    /// The artifact digest differs from the released digest
+    DigestMismatch {
+        name: String,
+        released: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for TransparencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongRelease { version, target } => {
+                write!(f, "manifest is published for {version} on {target}")
+            }
+            Self::MissingDigest { name } => write!(f, "{name} has no digest to verify"),
+            Self::Unlisted { name } => write!(f, "{name} is not a released artifact"),
+            Self::DigestMismatch {
+                name,
+                released,
+                actual,
+            } => write!(
+                f,
+                "{name} digest {actual} differs from the released digest {released}"
+            ),
+        }
+    }
+}
+
+impl TransparencyManifest {
+    /// Compares `artifacts` of `version` for `target` with the released
+    /// digests, returning every discrepancy found
+    pub fn verify(
+        &self,
+        version: &Version,
+        target: &str,
+        artifacts: &[Artifact],
+    ) -> Vec<TransparencyIssue> {
+        if self.version != *version || self.target != target {
+            return vec![TransparencyIssue::WrongRelease {
+                version: self.version.clone(),
+                target: self.target.clone(),
+            }];
+        }
+
+        artifacts
+            .iter()
+            .filter_map(|artifact| {
+                let name = transparency_key(artifact);
+                let Some(actual) = &artifact.sha256_digest else {
+                    return Some(TransparencyIssue::MissingDigest { name });
+                };
+                let Some(released) = self.artifacts.get(&name) else {
+                    return Some(TransparencyIssue::Unlisted { name });
+                };
+
+                (!released.eq_ignore_ascii_case(actual)).then(|| {
+                    TransparencyIssue::DigestMismatch {
+                        name,
+                        released: released.clone(),
+                        actual: actual.clone(),
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fvm::CpuVariant;
+
+    use super::*;
+
+    const TARGET: &str = "x86_64-unknown-linux-musl";
+
+    fn artifact(name: &str, digest: Option<&str>) -> Artifact {
+        Artifact {
+            name: name.to_string(),
+            version: Version::new(0, 11, 8),
+            download_url: format!("https://example.com/{name}.zip"),
+            sha256_digest: digest.map(str::to_string),
+            variant: None,
+            channel: None,
+            entry: None,
+        }
+    }
+
+    fn manifest() -> TransparencyManifest {
+        serde_json::from_str(
+            r#"{
+                "version": "0.11.8",
+                "target": "x86_64-unknown-linux-musl",
+                "artifacts": { "fluvio": "aa11", "fluvio+x86_64-v3": "bb22", "cdk": "cc33" }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn builds_manifest_urls() {
+        let version = Version::new(0, 11, 8);
+
+        assert_eq!(
+            transparency_manifest_url("https://releases.example.com/", &version, TARGET),
+            "https://releases.example.com/0.11.8/x86_64-unknown-linux-musl.json"
+        );
+        assert_eq!(
+            transparency_manifest_url(
+                "https://example.com/digests?v={version}&t={target}",
+                &version,
+                TARGET
+            ),
+            "https://example.com/digests?v=0.11.8&t=x86_64-unknown-linux-musl"
+        );
+    }
+
+    #[test]
+    fn accepts_released_digests() {
+        let mut optimized = artifact("fluvio", Some("BB22"));
+
+        optimized.variant = Some(CpuVariant::X86_64V3);
+
+        let artifacts = vec![optimized, artifact("cdk", Some("cc33"))];
+
+        assert!(
+            manifest()
+                .verify(&Version::new(0, 11, 8), TARGET, &artifacts)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn reports_unreleased_digests() {
+        let artifacts = vec![
+            artifact("fluvio", Some("ff00")),
+            artifact("smdk", Some("dd44")),
+            artifact("cdk", None),
+        ];
+        let issues = manifest().verify(&Version::new(0, 11, 8), TARGET, &artifacts);
+
+        assert_eq!(
+            issues,
+            vec![
+                TransparencyIssue::DigestMismatch {
+                    name: "fluvio".to_string(),
+                    released: "aa11".to_string(),
+                    actual: "ff00".to_string(),
+                },
+                TransparencyIssue::Unlisted {
+                    name: "smdk".to_string()
+                },
+                TransparencyIssue::MissingDigest {
+                    name: "cdk".to_string()
+                },
+            ]
+        );
+        assert!(matches!(
+            manifest().verify(&Version::new(0, 11, 9), TARGET, &artifacts)[..],
+            [TransparencyIssue::WrongRelease { .. }]
+        ));
+    }
+}
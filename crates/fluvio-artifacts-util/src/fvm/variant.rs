@@ -0,0 +1,153 @@
+//! CPU Optimized Artifact Variants
+//!
+//! Releases may publish artifacts built for specific CPU feature levels in
+//! addition to the baseline artifacts. Variant assets are named after the
+//! baseline asset with a `+<variant>` suffix, e.g.
+//! `fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::Error;
+
+/// Separator between the target triple and the variant in asset names
+pub const VARIANT_SEPARATOR: char = '+';
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum CpuVariant {
+    /// x86-64 microarchitecture level 2 (SSSE3, SSE4.1, SSE4.2, POPCNT, CMPXCHG16B)
+    #[serde(rename = "x86_64-v2")]
+    X86_64V2,
+    /// x86-64 microarchitecture level 3 (AVX, AVX2, BMI1, BMI2, F16C, FMA, LZCNT, MOVBE)
+    #[serde(rename = "x86_64-v3")]
+    X86_64V3,
+    /// x86-64 microarchitecture level 4 (AVX-512 F, BW, CD, DQ and VL)
+    #[serde(rename = "x86_64-v4")]
+    X86_64V4,
+    /// AArch64 with cryptography extensions (AES, SHA2)
+    #[serde(rename = "aarch64-crypto")]
+    Aarch64Crypto,
+}
+
+impl CpuVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X86_64V2 => "x86_64-v2",
+            Self::X86_64V3 => "x86_64-v3",
+            Self::X86_64V4 => "x86_64-v4",
+            Self::Aarch64Crypto => "aarch64-crypto",
+        }
+    }
+
+    /// Detects the variants supported by the host CPU, sorted from the most
+    /// to the least optimized.
+    pub fn detect() -> Vec<CpuVariant> {
+        #[allow(unused_mut)]
+        let mut variants = Vec::new();
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            // Feature levels as defined by the x86-64 psABI
+            let v2 = std::arch::is_x86_feature_detected!("cmpxchg16b")
+                && std::arch::is_x86_feature_detected!("popcnt")
+                && std::arch::is_x86_feature_detected!("sse3")
+                && std::arch::is_x86_feature_detected!("ssse3")
+                && std::arch::is_x86_feature_detected!("sse4.1")
+                && std::arch::is_x86_feature_detected!("sse4.2");
+            let v3 = v2
+                && std::arch::is_x86_feature_detected!("avx")
+                && std::arch::is_x86_feature_detected!("avx2")
+                && std::arch::is_x86_feature_detected!("bmi1")
+                && std::arch::is_x86_feature_detected!("bmi2")
+                && std::arch::is_x86_feature_detected!("f16c")
+                && std::arch::is_x86_feature_detected!("fma")
+                && std::arch::is_x86_feature_detected!("lzcnt")
+                && std::arch::is_x86_feature_detected!("movbe")
+                && std::arch::is_x86_feature_detected!("xsave");
+            let v4 = v3
+                && std::arch::is_x86_feature_detected!("avx512f")
+                && std::arch::is_x86_feature_detected!("avx512bw")
+                && std::arch::is_x86_feature_detected!("avx512cd")
+                && std::arch::is_x86_feature_detected!("avx512dq")
+                && std::arch::is_x86_feature_detected!("avx512vl");
+
+            if v4 {
+                variants.push(Self::X86_64V4);
+            }
+
+            if v3 {
+                variants.push(Self::X86_64V3);
+            }
+
+            if v2 {
+                variants.push(Self::X86_64V2);
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("aes")
+                && std::arch::is_aarch64_feature_detected!("sha2")
+            {
+                variants.push(Self::Aarch64Crypto);
+            }
+        }
+
+        variants
+    }
+
+    /// Builds the asset name for this variant given the baseline asset stem
+    /// (e.g. `fluvio-x86_64-unknown-linux-musl`)
+    pub fn asset_name(&self, stem: &str) -> String {
+        format!("{stem}{VARIANT_SEPARATOR}{self}.zip")
+    }
+}
+
+impl Display for CpuVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CpuVariant {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x86_64-v2" => Ok(Self::X86_64V2),
+            "x86_64-v3" => Ok(Self::X86_64V3),
+            "x86_64-v4" => Ok(Self::X86_64V4),
+            "aarch64-crypto" => Ok(Self::Aarch64Crypto),
+            _ => Err(Error::InvalidVariant(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_displays_variants() {
+        for variant in [
+            CpuVariant::X86_64V2,
+            CpuVariant::X86_64V3,
+            CpuVariant::X86_64V4,
+            CpuVariant::Aarch64Crypto,
+        ] {
+            assert_eq!(CpuVariant::from_str(variant.as_str()).unwrap(), variant);
+        }
+
+        assert!(CpuVariant::from_str("x86_64-v9").is_err());
+    }
+
+    #[test]
+    fn builds_variant_asset_names() {
+        assert_eq!(
+            CpuVariant::X86_64V3.asset_name("fluvio-x86_64-unknown-linux-musl"),
+            "fluvio-x86_64-unknown-linux-musl+x86_64-v3.zip"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! GitHub Repository of the Releases
+//!
+//! Releases are fetched from [`REPO_OWNER`]/[`REPO_NAME`] on github.com by
+//! default. Forks hosting releases on GitHub Enterprise Server point
+//! [`GitHubRepo`] at their API, e.g. `https://ghe.example.com/api/v3`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use octocrab::Octocrab;
+
+use crate::{REPO_NAME, REPO_OWNER};
+
+/// API of github.com
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Raw file host of github.com
+pub const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com";
+
+/// A GitHub repository and the API it is served from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitHubRepo {
+    /// API base URL, github.com when not set
+    pub api_url: Option<String>,
+    pub owner: String,
+    pub name: String,
+}
+
+impl Default for GitHubRepo {
+    fn default() -> Self {
+        Self {
+            api_url: None,
+            owner: REPO_OWNER.to_string(),
+            name: REPO_NAME.to_string(),
+        }
+    }
+}
+
+impl GitHubRepo {
+    /// Uses the API at `api_url` instead of github.com
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = Some(api_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Uses the repository `owner/name`
+    pub fn with_repo(mut self, owner: impl Into<String>, name: impl Into<String>) -> Self {
+        self.owner = owner.into();
+        self.name = name.into();
+        self
+    }
+
+    /// GitHub client for the API of the repository
+    pub fn octocrab(&self) -> Result<Octocrab> {
+        let mut builder = Octocrab::builder();
+
+        if let Some(api_url) = &self.api_url {
+            builder = builder.base_uri(api_url.as_str())?;
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// URL of the raw contents of the file at `path` on `branch`.
+    ///
+    /// GitHub Enterprise Server serves raw files under `/raw` of its host,
+    /// which is derived from the API URL.
+    pub fn raw_file_url(&self, branch: &str, path: &str) -> String {
+        let Some(api_url) = self.api_url.as_deref().filter(|url| *url != GITHUB_API_URL) else {
+            return format!(
+                "{GITHUB_RAW_URL}/{}/{}/{branch}/{path}",
+                self.owner, self.name
+            );
+        };
+        let host = api_url.trim_end_matches("/api/v3");
+
+        format!("{host}/raw/{}/{}/{branch}/{path}", self.owner, self.name)
+    }
+}
+
+impl fmt::Display for GitHubRepo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+/// Parses `owner/name`, keeping the github.com API
+impl FromStr for GitHubRepo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(Self::default().with_repo(owner, name))
+            }
+            _ => bail!("Invalid GitHub repository \"{s}\", expected <owner>/<name>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_repositories() {
+        let repo: GitHubRepo = "acme/fluvio".parse().unwrap();
+
+        assert_eq!(repo.owner, "acme");
+        assert_eq!(repo.name, "fluvio");
+        assert_eq!(repo.api_url, None);
+        assert!("acme".parse::<GitHubRepo>().is_err());
+        assert!("acme/fluvio/extra".parse::<GitHubRepo>().is_err());
+    }
+
+    #[test]
+    fn builds_raw_file_urls() {
+        assert_eq!(
+            GitHubRepo::default().raw_file_url("master", "VERSION"),
+            format!("https://raw.githubusercontent.com/{REPO_OWNER}/{REPO_NAME}/master/VERSION")
+        );
+        assert_eq!(
+            GitHubRepo::default()
+                .with_api_url("https://ghe.example.com/api/v3/")
+                .with_repo("acme", "fluvio")
+                .raw_file_url("master", "release-tools/eol.json"),
+            "https://ghe.example.com/raw/acme/fluvio/master/release-tools/eol.json"
+        );
+    }
+}
@@ -2,41 +2,174 @@ pub use http;
 pub use http::StatusCode;
 pub use http::{Request, Response};
 
+pub mod dns;
+pub mod encoding;
+pub mod handle;
+pub mod happy_eyeballs;
+pub mod probe;
+pub mod record;
+pub mod stats;
+
+pub use fluvio_progress::transfer;
+
 use std::env;
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
 use serde::de::DeserializeOwned;
 
 use ureq::{Agent, AgentBuilder, Proxy, OrAnyStatus};
 
-/// for simple get requests
+use crate::Sha256Digest;
+use crate::failure::{Failure, FailureKind};
+
+use encoding::{ACCEPT_ENCODING, decode_response, max_decoded_body};
+use handle::DownloadHandle;
+use dns::DnsOverrides;
+use happy_eyeballs::{HappyEyeballsResolver, IpPreference};
+use stats::{CountingMiddleware, CountingResolver, OpenConnection};
+use transfer::{ProgressCallback, TransferProgress, TransferStats};
+
+/// Size of the reads from the response body
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Minimum period between two progress callbacks
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sha256 checksum of the body of a response, computed while the body was
+/// received. Set in the extensions of the responses of [`get_with_handle`]
+/// whose body was not decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BodyDigest(pub String);
+
+/// for simple get requests of metadata, compressed bodies are negotiated and
+/// decoded, see [`encoding`]
 pub async fn get(uri: impl AsRef<str>) -> Result<Response<Vec<u8>>> {
-    use std::io::Read;
+    get_body(uri.as_ref(), &mut |_| {}, &DownloadHandle::new(), true).await
+}
+
+/// Downloads the body at `uri` as it is served, without negotiating a
+/// content encoding, for files whose digest is checked
+pub async fn download(uri: impl AsRef<str>) -> Result<Response<Vec<u8>>> {
+    get_with_progress(uri, &mut |_| {}).await
+}
+
+/// Same as [`download`], reporting the progress of the body transfer to
+/// `on_progress` at most every 100ms, and once the body is received
+pub async fn get_with_progress(
+    uri: impl AsRef<str>,
+    on_progress: &mut ProgressCallback<'_>,
+) -> Result<Response<Vec<u8>>> {
+    get_with_handle(uri, on_progress, &DownloadHandle::new()).await
+}
+
+/// Same as [`get_with_progress`], also sending the progress to the
+/// subscribers of `handle`. The transfer waits while `handle` is paused and
+/// fails with [`handle::DownloadCancelled`] once it is cancelled.
+pub async fn get_with_handle(
+    uri: impl AsRef<str>,
+    on_progress: &mut ProgressCallback<'_>,
+    handle: &DownloadHandle,
+) -> Result<Response<Vec<u8>>> {
+    get_body(uri.as_ref(), on_progress, handle, false).await
+}
+
+/// Gets the body at `uri`, negotiating its encoding and decoding it only if
+/// `negotiate_encoding` is set
+async fn get_body(
+    uri: &str,
+    on_progress: &mut ProgressCallback<'_>,
+    handle: &DownloadHandle,
+    negotiate_encoding: bool,
+) -> Result<Response<Vec<u8>>> {
+    let mut report = |progress: &TransferProgress| {
+        handle.report(progress);
+        on_progress(progress);
+    };
+
+    handle.checkpoint().await?;
 
-    let uri = uri.as_ref();
-    let agent = configure_ureq_proxy()?; // Create agent with proxy
+    if let Some(replayer) = record::replayer()? {
+        let response = replayer.replay("GET", uri)?;
+        let mut stats = TransferStats::new(Some(response.body().len() as u64));
 
-    let req = agent.get(uri);
+        report(&stats.record(response.body().len()));
+        return Ok(response);
+    }
+
+    let agent = shared_agent()?;
+    let _connection = OpenConnection::track();
+
+    let accept_encoding = if negotiate_encoding {
+        ACCEPT_ENCODING
+    } else {
+        "identity"
+    };
+    let req = agent.get(uri).set("Accept-Encoding", accept_encoding);
     let resp = req
         .call()
         .or_any_status()
-        .map_err(|e| anyhow!("get transport error : {e}"))?;
+        .map_err(|e| Failure::new(FailureKind::Network, format!("get transport error : {e}")))?;
 
     let status = resp.status();
     let content_type = resp.header("Content-Type").map(|v| v.to_string());
+    let content_encoding = resp.header("Content-Encoding").map(|v| v.to_string());
     let len: usize = match resp.header("Content-Length") {
         Some(hdr) => hdr.parse()?,
         None => 0usize,
     };
 
     let mut bytes: Vec<u8> = Vec::with_capacity(len);
-    resp.into_reader().read_to_end(&mut bytes)?;
+    let mut stats = TransferStats::new((len > 0).then_some(len as u64));
+    let mut digest = Sha256Digest::new();
+    let mut reader = resp.into_reader();
+    let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+    let mut reported_at = Instant::now();
+
+    loop {
+        handle.checkpoint().await?;
+
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let progress = stats.record(read);
+
+        stats::record_received(read);
+
+        digest.update(&chunk[..read]);
+        bytes.extend_from_slice(&chunk[..read]);
+
+        if reported_at.elapsed() >= PROGRESS_INTERVAL {
+            report(&progress);
+            reported_at = Instant::now();
+        }
+    }
+
+    report(&stats.progress());
 
     let mut builder = Response::builder().status(status);
     if let Some(ct) = content_type {
         builder = builder.header(http::header::CONTENT_TYPE, ct);
     }
+    let decode = negotiate_encoding && content_encoding.is_some();
+    if let Some(ce) = content_encoding {
+        builder = builder.header(http::header::CONTENT_ENCODING, ce);
+    }
+    if !decode {
+        builder = builder.extension(BodyDigest(digest.finalize()));
+    }
     let response = builder.body(bytes)?;
+    let response = if decode {
+        decode_response(response, max_decoded_body()?)?
+    } else {
+        response
+    };
+    record::record("GET", uri, &http::HeaderMap::new(), &[], &response);
 
     Ok(response)
 }
@@ -46,72 +179,423 @@ where
     T: Into<Vec<u8>> + std::fmt::Debug,
 {
     let (parts, body) = request.into_parts();
-    let agent = configure_ureq_proxy()?; // Create agent with proxy
-    let mut ureq_request = agent.request(parts.method.as_ref(), &parts.uri.to_string());
-    for (name, value) in parts.headers {
-        let Some(name) = name else {
-            continue;
-        };
+    let uri = parts.uri.to_string();
+
+    if let Some(replayer) = record::replayer()? {
+        return replayer.replay(parts.method.as_str(), &uri);
+    }
+
+    let agent = shared_agent()?;
+    let _connection = OpenConnection::track();
+    let mut ureq_request = agent.request(parts.method.as_ref(), &uri);
+    for (name, value) in &parts.headers {
         let value_str = value
             .to_str()
             .map_err(|e| anyhow!("invalid UTF-8 in header '{}': {e}", name.as_str()))?;
         ureq_request = ureq_request.set(name.as_ref(), value_str);
     }
+    // Bodies are only decoded when the encoding is negotiated here, callers
+    // setting `Accept-Encoding` get the body as it is served
+    let negotiate_encoding = !parts.headers.contains_key(http::header::ACCEPT_ENCODING);
+    if negotiate_encoding {
+        ureq_request = ureq_request.set("Accept-Encoding", ACCEPT_ENCODING);
+    }
 
     let body_u8: Vec<u8> = body.into();
     let response = ureq_request
         .send_bytes(&body_u8)
         .or_any_status()
-        .map_err(|e| anyhow!("error: {e}"))?;
-    Ok(response.into())
+        .map_err(|e| Failure::new(FailureKind::Network, format!("error: {e}")))?;
+    let response: Response<Vec<u8>> = response.into();
+
+    stats::record_sent(body_u8.len());
+    stats::record_received(response.body().len());
+
+    let response = if negotiate_encoding {
+        decode_response(response, max_decoded_body()?)?
+    } else {
+        response
+    };
+    record::record(
+        parts.method.as_str(),
+        &uri,
+        &parts.headers,
+        &body_u8,
+        &response,
+    );
+
+    Ok(response)
+}
+
+/// Settings read from the environment the shared agent was built with
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AgentSettings {
+    preference: IpPreference,
+    overrides: DnsOverrides,
+    proxy: Option<(String, &'static str)>,
+}
+
+impl AgentSettings {
+    fn from_env() -> Result<Self> {
+        Ok(Self {
+            preference: IpPreference::from_env()?,
+            overrides: dns::dns_overrides()?,
+            proxy: proxy_var(),
+        })
+    }
+}
+
+static SHARED_AGENT: Mutex<Option<(AgentSettings, Agent)>> = Mutex::new(None);
+
+/// Agent shared by every request so keep-alive connections are reused
+/// across requests and threads. It is rebuilt when the proxy, IP preference
+/// or DNS overrides in the environment change.
+fn shared_agent() -> Result<Agent> {
+    let settings = AgentSettings::from_env()?;
+    let mut shared = SHARED_AGENT
+        .lock()
+        .map_err(|_| anyhow!("shared HTTP agent lock poisoned"))?;
+
+    if let Some((built_with, agent)) = shared.as_ref()
+        && *built_with == settings
+    {
+        return Ok(agent.clone());
+    }
+
+    let agent = configure_ureq_proxy(&settings)?;
+
+    *shared = Some((settings, agent.clone()));
+    Ok(agent)
 }
 
 /// Configures a `ureq::Agent` with a proxy, if one is defined in the environment.
 //  TODO: If `ureq` version is updated to 3.0.8, you can replace this function with `try_from_env` here, see more [PR #4438]
-fn configure_ureq_proxy() -> Result<Agent> {
-    let agent_builder = AgentBuilder::new();
+fn configure_ureq_proxy(settings: &AgentSettings) -> Result<Agent> {
+    let resolver =
+        HappyEyeballsResolver::new(settings.preference).with_overrides(settings.overrides.clone());
+    let agent_builder = AgentBuilder::new()
+        .resolver(CountingResolver::new(resolver, stats::counters()))
+        .middleware(CountingMiddleware::new(stats::counters()));
 
+    if let Some((proxy_str, proxy_type)) = &settings.proxy {
+        let proxy = Proxy::new(proxy_str)
+            .with_context(|| format!("Failed to create {proxy_type} proxy"))?;
+
+        return Ok(agent_builder.proxy(proxy).build());
+    }
+
+    Ok(agent_builder.build())
+}
+
+/// Proxy requests are sent through, read from `ALL_PROXY`, `HTTPS_PROXY` or
+/// `HTTP_PROXY` in this order
+pub fn proxy_from_env() -> Option<String> {
+    proxy_var().map(|(proxy, _)| proxy)
+}
+
+fn proxy_var() -> Option<(String, &'static str)> {
     let proxy_vars = [
         ("ALL_PROXY", "all_proxy", "ALL"),
         ("HTTPS_PROXY", "https_proxy", "HTTPS"),
         ("HTTP_PROXY", "http_proxy", "HTTP"),
     ];
 
-    let proxy_creation = |proxy_str: &str, proxy_type: &str| -> Result<Proxy> {
-        Proxy::new(proxy_str).with_context(|| format!("Failed to create {proxy_type} proxy"))
-    };
+    proxy_vars
+        .iter()
+        .find_map(|&(upper_var, lower_var, proxy_type)| {
+            let proxy = env::var(upper_var).or_else(|_| env::var(lower_var)).ok()?;
+
+            Some((proxy, proxy_type))
+        })
+}
+
+/// Limits enforced when reading a response body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseLimits {
+    /// Maximum size of the body in bytes
+    pub max_body: usize,
+    /// Accepted media types, any media type is accepted when empty.
+    /// Responses without a `Content-Type` header are always accepted.
+    pub content_types: Vec<String>,
+}
+
+/// Default limit of JSON response bodies
+pub const DEFAULT_MAX_JSON_BODY: usize = 32 * 1024 * 1024;
+
+/// Default limit of text response bodies
+pub const DEFAULT_MAX_TEXT_BODY: usize = 8 * 1024 * 1024;
 
-    for &(upper_var, lower_var, proxy_type) in &proxy_vars {
-        if let Ok(proxy_str) = env::var(upper_var).or_else(|_| env::var(lower_var)) {
-            let proxy = proxy_creation(&proxy_str, proxy_type)?;
-            return Ok(agent_builder.proxy(proxy).build());
+/// Media types accepted for JSON bodies by default, in addition to any
+/// `+json` media type. Raw file hosts serve JSON as plain text or bytes.
+pub const JSON_CONTENT_TYPES: [&str; 4] = [
+    "application/json",
+    "text/json",
+    "text/plain",
+    "application/octet-stream",
+];
+
+impl ResponseLimits {
+    /// Limits for JSON bodies
+    pub fn json() -> Self {
+        Self {
+            max_body: DEFAULT_MAX_JSON_BODY,
+            content_types: JSON_CONTENT_TYPES.iter().map(|it| it.to_string()).collect(),
         }
     }
 
-    Ok(agent_builder.build())
+    /// Limits for text bodies, accepting any media type
+    pub fn text() -> Self {
+        Self {
+            max_body: DEFAULT_MAX_TEXT_BODY,
+            content_types: Vec::new(),
+        }
+    }
+
+    pub fn with_max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+
+    pub fn with_content_types<I, S>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.content_types = content_types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Checks `response` against the limits, returning its body
+    pub fn check<'a>(&self, response: &'a Response<Vec<u8>>) -> Result<&'a [u8], ResponseError> {
+        let body = response.body();
+
+        if body.len() > self.max_body {
+            return Err(ResponseError::BodyTooLarge {
+                size: body.len(),
+                limit: self.max_body,
+            });
+        }
+
+        if let Some(content_type) = response.headers().get(http::header::CONTENT_TYPE) {
+            let content_type = content_type.to_str().unwrap_or_default();
+            let media_type = content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+
+            if !self.accepts(&media_type) {
+                return Err(ResponseError::UnexpectedContentType {
+                    content_type: content_type.to_string(),
+                    expected: self.content_types.join(", "),
+                });
+            }
+        }
+
+        Ok(body)
+    }
+
+    fn accepts(&self, media_type: &str) -> bool {
+        if self.content_types.is_empty() {
+            return true;
+        }
+
+        let json = self
+            .content_types
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case("application/json"));
+
+        self.content_types
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(media_type))
+            || (json && media_type.ends_with("+json"))
+    }
+}
+
+/// Failure reading a response body
+#[derive(Debug, thiserror::Error)]
+pub enum ResponseError {
+    #[error("response body of {size} bytes exceeds the limit of {limit} bytes")]
+    BodyTooLarge { size: usize, limit: usize },
+    #[error("unexpected response content type \"{content_type}\", expected one of {expected}")]
+    UnexpectedContentType {
+        content_type: String,
+        expected: String,
+    },
+    #[error("invalid JSON response: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("response body is not UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
 }
 
 pub trait ResponseExt {
+    /// Parses the body as JSON within [`ResponseLimits::json`]
     fn json<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.json_within(&ResponseLimits::json())
+    }
+
+    /// Reads the body as text within [`ResponseLimits::text`]
+    fn body_string(&self) -> Result<String> {
+        self.body_string_within(&ResponseLimits::text())
+    }
+
+    fn json_within<T>(&self, limits: &ResponseLimits) -> Result<T>
     where
         T: DeserializeOwned;
 
-    fn body_string(&self) -> Result<String>;
+    fn body_string_within(&self, limits: &ResponseLimits) -> Result<String>;
 }
 
 impl ResponseExt for Response<Vec<u8>> {
-    fn json<T>(&self) -> Result<T>
+    fn json_within<T>(&self, limits: &ResponseLimits) -> Result<T>
     where
         T: DeserializeOwned,
     {
-        let body = self.body();
-        let result = serde_json::from_slice(body)?;
+        let body = limits.check(self)?;
+        let result = serde_json::from_slice(body).map_err(ResponseError::from)?;
         Ok(result)
     }
 
-    fn body_string(&self) -> Result<String> {
-        let body = self.body();
-        let bstr = std::str::from_utf8(body)?;
+    fn body_string_within(&self, limits: &ResponseLimits) -> Result<String> {
+        let body = limits.check(self)?;
+        let bstr = std::str::from_utf8(body).map_err(ResponseError::from)?;
         Ok(bstr.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn response(content_type: Option<&str>, body: &str) -> Response<Vec<u8>> {
+        let mut builder = Response::builder();
+
+        if let Some(content_type) = content_type {
+            builder = builder.header(http::header::CONTENT_TYPE, content_type);
+        }
+
+        builder.body(body.as_bytes().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn accepts_json_media_types() {
+        for content_type in [
+            None,
+            Some("application/json; charset=utf-8"),
+            Some("application/vnd.github+json"),
+            Some("text/plain"),
+        ] {
+            let value: Value = response(content_type, r#"{"stable":"0.11.8"}"#)
+                .json()
+                .unwrap();
+
+            assert_eq!(value["stable"], "0.11.8");
+        }
+    }
+
+    #[test]
+    fn rejects_html_error_pages() {
+        let err = response(Some("text/html"), "<html>Bad Gateway</html>")
+            .json::<Value>()
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ResponseError>(),
+            Some(ResponseError::UnexpectedContentType { .. })
+        ));
+        assert_eq!(
+            response(Some("text/html"), "<html></html>")
+                .body_string()
+                .unwrap(),
+            "<html></html>"
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_bodies() {
+        let limits = ResponseLimits::json().with_max_body(8);
+        let err = response(None, r#"{"stable":"0.11.8"}"#)
+            .json_within::<Value>(&limits)
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ResponseError>(),
+            Some(ResponseError::BodyTooLarge { size: 19, limit: 8 })
+        ));
+
+        let limits = ResponseLimits::text().with_content_types(["text/csv"]);
+
+        assert!(
+            response(Some("text/plain"), "a,b")
+                .body_string_within(&limits)
+                .is_err()
+        );
+    }
+    #[fluvio_future::test]
+    async fn downloads_encoded_bodies_as_served() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+
+        gzip.write_all(b"release").unwrap();
+
+        let body = gzip.finish().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/fluvio.tar.gz", listener.local_addr().unwrap());
+        let served = body.clone();
+        let server = std::thread::spawn(move || {
+            let mut accept_encodings = Vec::new();
+
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let read = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_ascii_lowercase();
+
+                accept_encodings.push(
+                    request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("accept-encoding: "))
+                        .map(str::to_string),
+                );
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    served.len()
+                )
+                .unwrap();
+                stream.write_all(&served).unwrap();
+            }
+
+            accept_encodings
+        });
+
+        let downloaded = download(&url).await.unwrap();
+        let decoded = get(&url).await.unwrap();
+        let accept_encodings = server.join().unwrap();
+
+        assert_eq!(downloaded.body(), &body);
+        assert_eq!(
+            downloaded.extensions().get::<BodyDigest>(),
+            Some(&BodyDigest(crate::sha256_digest_reader(&body[..]).unwrap()))
+        );
+        assert_eq!(decoded.body(), b"release");
+        assert_eq!(
+            accept_encodings,
+            vec![
+                Some("identity".to_string()),
+                Some(ACCEPT_ENCODING.to_string())
+            ]
+        );
+    }
+}
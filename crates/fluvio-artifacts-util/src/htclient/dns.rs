@@ -0,0 +1,213 @@
+//! DNS Resolution Overrides
+//!
+//! Hostnames can be pinned to addresses before they are resolved, so
+//! air-gapped or split-horizon environments can point `github.com` or
+//! `objects.githubusercontent.com` at internal mirrors without editing
+//! `/etc/hosts`, and tests can send requests to fixture servers.
+//!
+//! Overrides are read from `FLUVIO_DNS_OVERRIDES` as comma separated
+//! `host=address` entries, e.g.
+//! `github.com=10.0.0.5,objects.githubusercontent.com=[fd00::5]:8443`. An
+//! address without a port keeps the port of the request. Repeating a host
+//! adds addresses to it. Applications may add overrides from their own
+//! settings with [`set_dns_overrides`], entries from the environment take
+//! precedence.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow};
+
+pub const DNS_OVERRIDES_ENV: &str = "FLUVIO_DNS_OVERRIDES";
+
+/// Overrides set by the application, see [`set_dns_overrides`]
+static CONFIGURED: OnceLock<DnsOverrides> = OnceLock::new();
+
+/// Address a host is pinned to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverrideTarget {
+    pub ip: IpAddr,
+    /// Port to connect to instead of the port of the request
+    pub port: Option<u16>,
+}
+
+impl FromStr for OverrideTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+
+        if let Ok(addr) = s.parse::<SocketAddr>() {
+            return Ok(Self {
+                ip: addr.ip(),
+                port: Some(addr.port()),
+            });
+        }
+
+        let ip = s
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .map_err(|_| {
+                anyhow!("invalid address \"{s}\", expected an IP with an optional port")
+            })?;
+
+        Ok(Self { ip, port: None })
+    }
+}
+
+/// Hostnames pinned to addresses
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DnsOverrides {
+    hosts: HashMap<String, Vec<OverrideTarget>>,
+}
+
+impl DnsOverrides {
+    /// Overrides from `FLUVIO_DNS_OVERRIDES`, empty if it is not set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(DNS_OVERRIDES_ENV) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Pins `host` to `target`, in addition to the addresses it already has
+    pub fn insert(&mut self, host: &str, target: OverrideTarget) {
+        let targets = self.hosts.entry(normalize_host(host)).or_default();
+
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// Addresses for `netloc`, a `host:port` pair, if its host is pinned
+    pub fn resolve(&self, netloc: &str) -> Option<Vec<SocketAddr>> {
+        let (host, port) = split_netloc(netloc)?;
+        let targets = self.hosts.get(&normalize_host(host))?;
+
+        Some(
+            targets
+                .iter()
+                .map(|target| SocketAddr::new(target.ip, target.port.unwrap_or(port)))
+                .collect(),
+        )
+    }
+
+    /// Overrides of `self`, replacing the hosts also pinned in `other` with
+    /// the addresses of `other`
+    fn overridden_by(mut self, other: Self) -> Self {
+        self.hosts.extend(other.hosts);
+        self
+    }
+}
+
+/// Parses comma separated `host=address` entries
+impl FromStr for DnsOverrides {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut overrides = Self::default();
+
+        for entry in s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (host, target) = entry
+                .split_once('=')
+                .filter(|(host, _)| !host.trim().is_empty())
+                .ok_or_else(|| {
+                    anyhow!("invalid {DNS_OVERRIDES_ENV} entry \"{entry}\", expected host=address")
+                })?;
+
+            overrides.insert(host.trim(), target.parse()?);
+        }
+
+        Ok(overrides)
+    }
+}
+
+/// Sets the overrides read from application settings for the rest of the
+/// process, only the first call has an effect
+pub fn set_dns_overrides(overrides: DnsOverrides) {
+    if CONFIGURED.set(overrides).is_err() {
+        tracing::debug!("DNS overrides are already set");
+    }
+}
+
+/// Overrides in effect: the configured ones, updated with the environment
+pub fn dns_overrides() -> Result<DnsOverrides> {
+    let configured = CONFIGURED.get().cloned().unwrap_or_default();
+
+    Ok(configured.overridden_by(DnsOverrides::from_env()?))
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim()
+        .trim_end_matches('.')
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase()
+}
+
+/// Splits `host:port`, IPv6 hosts are enclosed in brackets
+fn split_netloc(netloc: &str) -> Option<(&str, u16)> {
+    let (host, port) = netloc.rsplit_once(':')?;
+
+    Some((host, port.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+        addrs.iter().map(|addr| addr.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let overrides: DnsOverrides =
+            "GitHub.com=10.0.0.5, github.com=[fd00::5], objects.githubusercontent.com=127.0.0.1:8080"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            overrides.resolve("github.com:443"),
+            Some(addrs(&["10.0.0.5:443", "[fd00::5]:443"]))
+        );
+        assert_eq!(
+            overrides.resolve("objects.githubusercontent.com:443"),
+            Some(addrs(&["127.0.0.1:8080"]))
+        );
+        assert_eq!(overrides.resolve("example.com:443"), None);
+        assert!("".parse::<DnsOverrides>().unwrap().is_empty());
+        assert!("github.com".parse::<DnsOverrides>().is_err());
+        assert!("github.com=mirror.local".parse::<DnsOverrides>().is_err());
+        assert!("=10.0.0.5".parse::<DnsOverrides>().is_err());
+    }
+
+    #[test]
+    fn environment_takes_precedence() {
+        let configured: DnsOverrides = "github.com=10.0.0.5,hub.infinyon.cloud=10.0.0.6"
+            .parse()
+            .unwrap();
+        let env: DnsOverrides = "github.com=10.0.0.7".parse().unwrap();
+        let overrides = configured.overridden_by(env);
+
+        assert_eq!(
+            overrides.resolve("github.com:443"),
+            Some(addrs(&["10.0.0.7:443"]))
+        );
+        assert_eq!(
+            overrides.resolve("hub.infinyon.cloud:443"),
+            Some(addrs(&["10.0.0.6:443"]))
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Response Content Encoding
+//!
+//! Metadata requests advertise the encodings in [`ACCEPT_ENCODING`] and
+//! compressed response bodies are decoded before they are handed to callers,
+//! so [`super::ResponseExt::json`] works with mirrors serving
+//! gzip-compressed metadata. Brotli is decoded by `ureq` itself.
+//!
+//! Downloads of files whose digest is checked, see [`super::download`],
+//! request the `identity` encoding and are never decoded, so a `.tar.gz`
+//! served with `Content-Encoding: gzip` keeps the bytes its digest covers.
+//!
+//! Decoded bodies are limited to [`DEFAULT_MAX_DECODED_BODY`] bytes, or the
+//! value of `FLUVIO_HTTP_MAX_DECODED_BODY`, to guard against decompression
+//! bombs.
+
+use std::io::Read;
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use http::Response;
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+
+pub const MAX_DECODED_BODY_ENV: &str = "FLUVIO_HTTP_MAX_DECODED_BODY";
+
+/// Encodings advertised in the `Accept-Encoding` header of requests
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Default limit of the size of decoded response bodies
+pub const DEFAULT_MAX_DECODED_BODY: u64 = 512 * 1024 * 1024;
+
+/// Limit of the size of decoded response bodies, in bytes
+pub fn max_decoded_body() -> Result<u64> {
+    match std::env::var(MAX_DECODED_BODY_ENV) {
+        Ok(value) => value
+            .parse()
+            .with_context(|| format!("Invalid {MAX_DECODED_BODY_ENV} value \"{value}\"")),
+        Err(_) => Ok(DEFAULT_MAX_DECODED_BODY),
+    }
+}
+
+/// Decodes the body of `response` according to its `Content-Encoding`
+/// header, which is removed along with the `Content-Length` of the encoded
+/// body
+pub fn decode_response(response: Response<Vec<u8>>, limit: u64) -> Result<Response<Vec<u8>>> {
+    let Some(encoding) = response.headers().get(CONTENT_ENCODING) else {
+        return Ok(response);
+    };
+    let encoding = encoding
+        .to_str()
+        .map_err(|err| anyhow!("invalid Content-Encoding header: {err}"))?
+        .to_owned();
+
+    let (mut parts, body) = response.into_parts();
+    let body = decode_body(&encoding, body, limit)?;
+
+    parts.headers.remove(CONTENT_ENCODING);
+    parts.headers.remove(CONTENT_LENGTH);
+
+    Ok(Response::from_parts(parts, body))
+}
+
+/// Decodes `body` encoded with the comma separated `encoding`s, in the
+/// reverse order they were applied
+pub fn decode_body(encoding: &str, mut body: Vec<u8>, limit: u64) -> Result<Vec<u8>> {
+    for coding in encoding.rsplit(',').map(str::trim) {
+        body = match coding.to_ascii_lowercase().as_str() {
+            "" | "identity" => body,
+            "gzip" | "x-gzip" => read_limited(MultiGzDecoder::new(&body[..]), limit)?,
+            // `deflate` is zlib wrapped, though some servers send raw deflate
+            "deflate" => match read_limited(ZlibDecoder::new(&body[..]), limit) {
+                Ok(decoded) => decoded,
+                Err(err) if err.is::<std::io::Error>() => {
+                    read_limited(DeflateDecoder::new(&body[..]), limit)?
+                }
+                Err(err) => return Err(err),
+            },
+            other => bail!("unsupported Content-Encoding \"{other}\""),
+        };
+    }
+
+    Ok(body)
+}
+
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut decoded)?;
+
+    if decoded.len() as u64 > limit {
+        bail!("decoded response body exceeds the limit of {limit} bytes");
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+
+    use super::*;
+
+    const JSON: &[u8] = br#"{"channel":"stable","version":"0.11.8"}"#;
+
+    #[test]
+    fn decodes_gzip_responses() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+
+        gzip.write_all(JSON).unwrap();
+
+        let response = Response::builder()
+            .header(CONTENT_ENCODING, "gzip")
+            .header(CONTENT_LENGTH, "42")
+            .body(gzip.finish().unwrap())
+            .unwrap();
+        let decoded = decode_response(response, DEFAULT_MAX_DECODED_BODY).unwrap();
+
+        assert_eq!(decoded.body(), JSON);
+        assert!(decoded.headers().get(CONTENT_ENCODING).is_none());
+        assert!(decoded.headers().get(CONTENT_LENGTH).is_none());
+    }
+
+    #[test]
+    fn decodes_zlib_and_raw_deflate() {
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+
+        zlib.write_all(JSON).unwrap();
+        raw.write_all(JSON).unwrap();
+
+        for body in [zlib.finish().unwrap(), raw.finish().unwrap()] {
+            assert_eq!(
+                decode_body("deflate", body, DEFAULT_MAX_DECODED_BODY).unwrap(),
+                JSON
+            );
+        }
+        assert_eq!(
+            decode_body("identity", JSON.to_vec(), DEFAULT_MAX_DECODED_BODY).unwrap(),
+            JSON
+        );
+        assert!(decode_body("zstd", JSON.to_vec(), DEFAULT_MAX_DECODED_BODY).is_err());
+    }
+
+    #[test]
+    fn rejects_decompression_bombs() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::best());
+
+        gzip.write_all(&vec![0u8; 1024 * 1024]).unwrap();
+
+        let bomb = gzip.finish().unwrap();
+        let err = decode_body("gzip", bomb.clone(), 64 * 1024).unwrap_err();
+
+        assert!(err.to_string().contains("exceeds the limit"));
+        assert_eq!(
+            decode_body("gzip", bomb, 1024 * 1024).unwrap().len(),
+            1024 * 1024
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Download Handles
+//!
+//! Applications embedding the download APIs, such as desktop installers or
+//! IDE plugins, control a transfer through a [`DownloadHandle`] shared with
+//! the task running it: the transfer can be cancelled or paused between two
+//! reads of the response body, and its progress is sent to every subscriber.
+//!
+//! ```no_run
+//! # async fn example(artifact: fluvio_artifacts_util::fvm::Artifact) -> anyhow::Result<()> {
+//! use fluvio_artifacts_util::fvm::Download;
+//! use fluvio_artifacts_util::htclient::handle::DownloadHandle;
+//!
+//! let handle = DownloadHandle::new();
+//! let progress = handle.subscribe();
+//! let ui_handle = handle.clone();
+//!
+//! std::thread::spawn(move || {
+//!     for progress in progress {
+//!         println!("{progress}");
+//!
+//!         // e.g. when the user clicks "Cancel"
+//!         if progress.received > 100 * 1024 * 1024 {
+//!             ui_handle.cancel();
+//!         }
+//!     }
+//! });
+//!
+//! artifact.download_with_handle("/tmp/fluvio".into(), &handle).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex};
+
+use event_listener::Event;
+
+use super::transfer::TransferProgress;
+
+/// Error of a transfer cancelled with [`DownloadHandle::cancel`], found
+/// with `err.is::<DownloadCancelled>()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Download cancelled")]
+pub struct DownloadCancelled;
+
+#[derive(Debug, Default)]
+struct HandleState {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    /// Notified when the transfer is paused, resumed or cancelled
+    changed: Event,
+    subscribers: Mutex<Vec<Sender<TransferProgress>>>,
+    latest: Mutex<Option<TransferProgress>>,
+}
+
+/// Controls a transfer from another task or thread, clones share the same
+/// transfer
+#[derive(Debug, Clone, Default)]
+pub struct DownloadHandle {
+    state: Arc<HandleState>,
+}
+
+impl DownloadHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the transfer, which then fails with [`DownloadCancelled`].
+    /// Cancelling a paused transfer stops it too.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.changed.notify(usize::MAX);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Stops reading the response body until [`DownloadHandle::resume`].
+    /// Servers may close connections paused for too long, failing the
+    /// transfer.
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::SeqCst);
+        self.state.changed.notify(usize::MAX);
+    }
+
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.state.changed.notify(usize::MAX);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// Receives the progress of the transfer from now on, the receiver is
+    /// disconnected when the handle and its clones are dropped
+    pub fn subscribe(&self) -> Receiver<TransferProgress> {
+        let (sender, receiver) = channel();
+
+        if let Ok(mut subscribers) = self.state.subscribers.lock() {
+            subscribers.push(sender);
+        }
+
+        receiver
+    }
+
+    /// Most recent progress of the transfer, `None` before it started
+    pub fn progress(&self) -> Option<TransferProgress> {
+        self.state.latest.lock().ok().and_then(|latest| *latest)
+    }
+
+    /// Sends `progress` to the subscribers, forgetting disconnected ones
+    pub(crate) fn report(&self, progress: &TransferProgress) {
+        if let Ok(mut latest) = self.state.latest.lock() {
+            *latest = Some(*progress);
+        }
+
+        if let Ok(mut subscribers) = self.state.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(*progress).is_ok());
+        }
+    }
+
+    /// Waits while the transfer is paused, failing if it was cancelled.
+    /// The task is woken by [`DownloadHandle::resume`] or
+    /// [`DownloadHandle::cancel`] instead of polling.
+    pub(crate) async fn checkpoint(&self) -> Result<(), DownloadCancelled> {
+        loop {
+            if self.is_cancelled() {
+                return Err(DownloadCancelled);
+            }
+
+            if !self.is_paused() {
+                return Ok(());
+            }
+
+            // Listening before checking again so no notification is missed
+            let listener = self.state.changed.listen();
+
+            if self.is_paused() && !self.is_cancelled() {
+                listener.await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use futures_lite::future::block_on;
+
+    use super::*;
+
+    fn progress(received: u64) -> TransferProgress {
+        TransferProgress {
+            received,
+            total: Some(100),
+            elapsed: Duration::from_secs(1),
+            speed: 0.0,
+            eta: None,
+        }
+    }
+
+    #[test]
+    fn sends_progress_to_subscribers() {
+        let handle = DownloadHandle::new();
+        let first = handle.subscribe();
+        let second = handle.clone().subscribe();
+
+        assert_eq!(handle.progress(), None);
+
+        drop(second);
+        handle.report(&progress(10));
+        handle.report(&progress(20));
+
+        assert_eq!(
+            first.try_iter().collect::<Vec<_>>(),
+            vec![progress(10), progress(20)]
+        );
+        assert_eq!(handle.progress(), Some(progress(20)));
+        assert_eq!(handle.state.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn waits_while_paused_until_cancelled() {
+        let handle = DownloadHandle::new();
+
+        assert_eq!(block_on(handle.checkpoint()), Ok(()));
+
+        handle.pause();
+
+        let remote = handle.clone();
+        let started = Instant::now();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            remote.cancel();
+        });
+
+        assert_eq!(block_on(handle.checkpoint()), Err(DownloadCancelled));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn waits_while_paused_until_resumed() {
+        let handle = DownloadHandle::new();
+
+        handle.pause();
+
+        let remote = handle.clone();
+        let resumer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            remote.resume();
+        });
+
+        assert_eq!(block_on(handle.checkpoint()), Ok(()));
+        assert!(!handle.is_paused());
+        resumer.join().unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+//! Address Selection for Dual-Stack Hosts
+//!
+//! The transport connects to resolved addresses one at a time, so a host
+//! whose IPv6 route is broken can stall every request until the connect
+//! timeout. Addresses are interleaved by family, preferred family first,
+//! and raced with staggered attempts (RFC 8305, "Happy Eyeballs"). The
+//! first address to accept a connection is used, and remembered for the
+//! rest of the process.
+//!
+//! `FLUVIO_IP_PREFER=v4|v6` selects the family tried first, by default the
+//! resolver order is kept.
+//!
+//! Hosts pinned with [`super::dns`] overrides are not looked up or raced,
+//! their addresses are used in the order they were given.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use ureq::Resolver;
+
+use super::dns::DnsOverrides;
+
+pub const IP_PREFER_ENV: &str = "FLUVIO_IP_PREFER";
+
+/// Delay before starting the connection attempt to the next address
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Time allowed for each connection attempt in the race
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IpPreference {
+    /// Keep the resolver order
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl IpPreference {
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(IP_PREFER_ENV) {
+            Ok(value) if !value.is_empty() => value.parse(),
+            _ => Ok(Self::Any),
+        }
+    }
+}
+
+impl FromStr for IpPreference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "any" => Ok(Self::Any),
+            "v4" | "ipv4" => Ok(Self::V4),
+            "v6" | "ipv6" => Ok(Self::V6),
+            _ => Err(anyhow!(
+                "invalid {IP_PREFER_ENV} value \"{s}\", expected v4 or v6"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct HappyEyeballsResolver {
+    preference: IpPreference,
+    overrides: DnsOverrides,
+}
+
+impl HappyEyeballsResolver {
+    pub(crate) fn new(preference: IpPreference) -> Self {
+        Self {
+            preference,
+            overrides: DnsOverrides::default(),
+        }
+    }
+
+    /// Consults `overrides` before resolving hosts
+    pub(crate) fn with_overrides(mut self, overrides: DnsOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+}
+
+impl Resolver for HappyEyeballsResolver {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        if let Some(addrs) = self.overrides.resolve(netloc) {
+            tracing::debug!(?addrs, netloc, "Using DNS override");
+            return Ok(addrs);
+        }
+
+        let mut addrs = sort_addresses(netloc.to_socket_addrs()?.collect(), self.preference);
+
+        if addrs.len() < 2 {
+            return Ok(addrs);
+        }
+
+        let winners = winners();
+        let known = winners
+            .lock()
+            .ok()
+            .and_then(|winners| winners.get(netloc).copied());
+        let winner = known.or_else(|| {
+            let winner = race(&addrs)?;
+
+            if let Ok(mut winners) = winners.lock() {
+                winners.insert(netloc.to_string(), winner);
+            }
+
+            Some(winner)
+        });
+
+        // The transport still falls back to the remaining addresses
+        if let Some(winner) = winner
+            && let Some(idx) = addrs.iter().position(|addr| *addr == winner)
+        {
+            let winner = addrs.remove(idx);
+
+            tracing::debug!(%winner, netloc, "Selected address");
+            addrs.insert(0, winner);
+        }
+
+        Ok(addrs)
+    }
+}
+
+fn winners() -> &'static Mutex<HashMap<String, SocketAddr>> {
+    static WINNERS: OnceLock<Mutex<HashMap<String, SocketAddr>>> = OnceLock::new();
+
+    WINNERS.get_or_init(Default::default)
+}
+
+/// Interleaves IPv4 and IPv6 addresses starting with the preferred family,
+/// or with the family of the first resolved address for [`IpPreference::Any`]
+pub fn sort_addresses(addrs: Vec<SocketAddr>, preference: IpPreference) -> Vec<SocketAddr> {
+    let first_v6 = match preference {
+        IpPreference::Any => addrs.first().is_some_and(SocketAddr::is_ipv6),
+        IpPreference::V4 => false,
+        IpPreference::V6 => true,
+    };
+    let (v6, v4): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        addrs.into_iter().partition(SocketAddr::is_ipv6);
+    let (mut first, mut second) = if first_v6 {
+        (v6.into_iter(), v4.into_iter())
+    } else {
+        (v4.into_iter(), v6.into_iter())
+    };
+    let mut sorted = Vec::new();
+
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => sorted.extend(a.into_iter().chain(b)),
+        }
+    }
+
+    sorted
+}
+
+/// Starts a connection attempt to each address, one every
+/// [`CONNECTION_ATTEMPT_DELAY`] until one succeeds, and returns the first
+/// address which accepted a connection
+fn race(addrs: &[SocketAddr]) -> Option<SocketAddr> {
+    let (tx, rx) = mpsc::channel();
+
+    for addr in addrs.iter().copied() {
+        let tx = tx.clone();
+
+        thread::spawn(move || {
+            let result = TcpStream::connect_timeout(&addr, ATTEMPT_TIMEOUT).map(|_| addr);
+            let _ = tx.send(result);
+        });
+
+        // Wait for the attempt delay, or less if an attempt succeeds first
+        if let Some(winner) = wait_for_winner(&rx, Some(CONNECTION_ATTEMPT_DELAY)) {
+            return Some(winner);
+        }
+    }
+
+    drop(tx);
+    wait_for_winner(&rx, None)
+}
+
+/// Receives attempt results until one succeeds, for up to `delay` or until
+/// every attempt finished if `delay` is `None`
+fn wait_for_winner(
+    rx: &mpsc::Receiver<io::Result<SocketAddr>>,
+    delay: Option<Duration>,
+) -> Option<SocketAddr> {
+    let deadline = delay.map(|delay| Instant::now() + delay);
+
+    loop {
+        let result = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                rx.recv_timeout(remaining).ok()?
+            }
+            None => rx.recv().ok()?,
+        };
+
+        match result {
+            Ok(addr) => return Some(addr),
+            Err(err) => tracing::debug!(%err, "Connection attempt failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn addrs(addrs: &[&str]) -> Vec<SocketAddr> {
+        addrs.iter().map(|addr| addr.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn interleaves_address_families() {
+        let resolved = addrs(&["[::1]:80", "[::2]:80", "127.0.0.1:80", "127.0.0.2:80"]);
+
+        assert_eq!(
+            sort_addresses(resolved.clone(), IpPreference::Any),
+            addrs(&["[::1]:80", "127.0.0.1:80", "[::2]:80", "127.0.0.2:80"])
+        );
+        assert_eq!(
+            sort_addresses(resolved, IpPreference::V4),
+            addrs(&["127.0.0.1:80", "[::1]:80", "127.0.0.2:80", "[::2]:80"])
+        );
+        assert_eq!("v6".parse::<IpPreference>().unwrap(), IpPreference::V6);
+        assert!("v5".parse::<IpPreference>().is_err());
+    }
+
+    #[test]
+    fn races_to_listening_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let open = listener.local_addr().unwrap();
+
+        assert_eq!(race(&[closed, open]), Some(open));
+        assert_eq!(race(&[closed]), None);
+    }
+
+    #[test]
+    fn resolves_overridden_hosts() {
+        let resolver = HappyEyeballsResolver::new(IpPreference::V6)
+            .with_overrides("github.com=127.0.0.1,github.com=[::1]".parse().unwrap());
+
+        // Kept in the given order, without a lookup
+        assert_eq!(
+            resolver.resolve("GitHub.com:443").unwrap(),
+            addrs(&["127.0.0.1:443", "[::1]:443"])
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Connection Statistics
+//!
+//! Requests sent with [`super::get`] and [`super::send`] share one agent,
+//! and so its pool of keep-alive connections, across threads. The agent
+//! counts the requests it sends, the connections it opens or reuses and the
+//! body bytes it transfers, read at any time through [`stats`] to report
+//! network behavior, e.g. in verbose output or health endpoints.
+//!
+//! Only the bytes of request and response bodies are counted, before any
+//! content decoding. Replayed responses, see [`super::record`], are not
+//! counted.
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use ureq::{Middleware, MiddlewareNext, Resolver};
+
+use super::transfer::format_bytes;
+
+static COUNTERS: Counters = Counters::new();
+
+thread_local! {
+    /// Connections opened by the request in progress on this thread, ureq
+    /// sends requests on the calling thread
+    static OPENED_BY_REQUEST: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Counters of the shared agent, since the start of the process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct HttpStats {
+    pub requests: u64,
+    /// Connections opened, including connections to proxies
+    pub connections_opened: u64,
+    /// Requests sent over a pooled keep-alive connection
+    pub connections_reused: u64,
+    /// Connections currently serving a request, idle pooled connections are
+    /// not counted
+    pub open_connections: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl Display for HttpStats {
+    /// e.g. `3 requests, 1 connections opened, 2 reused, 0 open, 0 B sent,
+    /// 12.3 MiB received`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requests, {} connections opened, {} reused, {} open, {} sent, {} received",
+            self.requests,
+            self.connections_opened,
+            self.connections_reused,
+            self.open_connections,
+            format_bytes(self.bytes_sent),
+            format_bytes(self.bytes_received)
+        )
+    }
+}
+
+/// Reads the counters of the shared agent, see [`stats`]
+#[derive(Debug, Clone, Copy)]
+pub struct StatsHandle {
+    counters: &'static Counters,
+}
+
+impl StatsHandle {
+    pub fn snapshot(&self) -> HttpStats {
+        self.counters.snapshot()
+    }
+}
+
+/// Handle on the counters of the agent shared by [`super::get`] and
+/// [`super::send`]
+pub fn stats() -> StatsHandle {
+    StatsHandle {
+        counters: &COUNTERS,
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Counters {
+    requests: AtomicU64,
+    connections_opened: AtomicU64,
+    connections_reused: AtomicU64,
+    open_connections: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            connections_opened: AtomicU64::new(0),
+            connections_reused: AtomicU64::new(0),
+            open_connections: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+        }
+    }
+
+    fn snapshot(&self) -> HttpStats {
+        HttpStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            connections_opened: self.connections_opened.load(Ordering::Relaxed),
+            connections_reused: self.connections_reused.load(Ordering::Relaxed),
+            open_connections: self.open_connections.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of the shared agent, updated by the transfers
+pub(crate) fn counters() -> &'static Counters {
+    &COUNTERS
+}
+
+pub(crate) fn record_sent(bytes: usize) {
+    COUNTERS
+        .bytes_sent
+        .fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+pub(crate) fn record_received(bytes: usize) {
+    COUNTERS
+        .bytes_received
+        .fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// Counts a connection as open until dropped, held while a request is sent
+/// and its response body read
+pub(crate) struct OpenConnection;
+
+impl OpenConnection {
+    pub(crate) fn track() -> Self {
+        COUNTERS.open_connections.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        COUNTERS.open_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts the connections opened by the agent, which only resolves a host
+/// when no pooled connection to it is available
+#[derive(Debug)]
+pub(crate) struct CountingResolver<R> {
+    inner: R,
+    counters: &'static Counters,
+}
+
+impl<R> CountingResolver<R> {
+    pub(crate) fn new(inner: R, counters: &'static Counters) -> Self {
+        Self { inner, counters }
+    }
+}
+
+impl<R: Resolver> Resolver for CountingResolver<R> {
+    fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+        self.counters
+            .connections_opened
+            .fetch_add(1, Ordering::Relaxed);
+        OPENED_BY_REQUEST.with(|opened| opened.set(opened.get() + 1));
+
+        self.inner.resolve(netloc)
+    }
+}
+
+/// Counts the requests of the agent, and those which did not open a
+/// connection as reused
+#[derive(Debug)]
+pub(crate) struct CountingMiddleware {
+    counters: &'static Counters,
+}
+
+impl CountingMiddleware {
+    pub(crate) fn new(counters: &'static Counters) -> Self {
+        Self { counters }
+    }
+}
+
+impl Middleware for CountingMiddleware {
+    fn handle(
+        &self,
+        request: ureq::Request,
+        next: MiddlewareNext,
+    ) -> Result<ureq::Response, ureq::Error> {
+        OPENED_BY_REQUEST.with(|opened| opened.set(0));
+
+        let response = next.handle(request);
+
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+
+        if OPENED_BY_REQUEST.with(|opened| opened.get()) == 0 {
+            self.counters
+                .connections_reused
+                .fetch_add(1, Ordering::Relaxed);
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, ToSocketAddrs};
+
+    use ureq::AgentBuilder;
+
+    use super::*;
+
+    struct SystemResolver;
+
+    impl Resolver for SystemResolver {
+        fn resolve(&self, netloc: &str) -> io::Result<Vec<SocketAddr>> {
+            Ok(netloc.to_socket_addrs()?.collect())
+        }
+    }
+
+    /// Serves `ok` to every request of a single keep-alive connection
+    fn serve_keep_alive(listener: TcpListener) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut line = String::new();
+
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            if line == "\r\n" {
+                writer
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                    .unwrap();
+            }
+
+            line.clear();
+        }
+    }
+
+    #[test]
+    fn counts_opened_and_reused_connections() {
+        let counters: &'static Counters = Box::leak(Box::new(Counters::new()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || serve_keep_alive(listener));
+        let agent = AgentBuilder::new()
+            .resolver(CountingResolver::new(SystemResolver, counters))
+            .middleware(CountingMiddleware::new(counters))
+            .build();
+
+        for _ in 0..3 {
+            assert_eq!(agent.get(&url).call().unwrap().into_string().unwrap(), "ok");
+        }
+
+        drop(agent);
+        server.join().unwrap();
+
+        let stats = counters.snapshot();
+
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.connections_opened, 1);
+        assert_eq!(stats.connections_reused, 2);
+        assert_eq!(
+            stats.to_string(),
+            "3 requests, 1 connections opened, 2 reused, 0 open, 0 B sent, 0 B received"
+        );
+    }
+}
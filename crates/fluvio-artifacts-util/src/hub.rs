@@ -0,0 +1,612 @@
+//! Hub Client Access
+//!
+//! Access tokens for private Hub packages are kept in the `hub` directory of
+//! the CLI config, e.g. `~/.fluvio/hub/tokens.toml`. Each token is scoped to
+//! actions on all package groups or on a single group, so an organization can
+//! hand out read-only tokens for its proprietary connectors.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use http::{Request, Response, StatusCode};
+use semver::Version;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::debug;
+
+use fluvio_hub_protocol::constants::CLI_CONFIG_HUB;
+use fluvio_hub_protocol::{HubError, Result};
+use fluvio_types::defaults::CLI_CONFIG_PATH;
+
+use crate::htclient::{self, ResponseExt};
+use crate::package_meta_ext::package_meta_from_bytes;
+use crate::state::{load_state, write_atomic, write_private_state};
+use crate::store::ContentStore;
+use crate::timestamp::{TimestampTrust, verify_published_at};
+
+pub const HUB_TOKENS_FILE: &str = "tokens.toml";
+pub const HUB_API_TOKEN_REFRESH: &str = "hub/v1/auth/refresh";
+pub const HUB_API_BPKG_DOWNLOAD: &str = "hub/v1/bpkg/download";
+
+/// Tokens expiring within this margin are refreshed before use
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeAction {
+    Read,
+    Publish,
+}
+
+/// Action allowed by a token, optionally limited to a package group.
+///
+/// Represented as `action` or `action:group`, e.g. `read:infinyon`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenScope {
+    pub action: ScopeAction,
+    pub group: Option<String>,
+}
+
+impl TokenScope {
+    /// Publishing to a group also grants reading from it
+    pub fn allows(&self, action: ScopeAction, group: &str) -> bool {
+        let action_allowed = self.action == action || self.action == ScopeAction::Publish;
+        let group_allowed = self.group.as_deref().is_none_or(|scope| scope == group);
+
+        action_allowed && group_allowed
+    }
+}
+
+impl Display for TokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.action {
+            ScopeAction::Read => "read",
+            ScopeAction::Publish => "publish",
+        };
+
+        match &self.group {
+            Some(group) => write!(f, "{action}:{group}"),
+            None => write!(f, "{action}"),
+        }
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (action, group) = match s.split_once(':') {
+            Some((action, group)) if !group.is_empty() => (action, Some(group.to_string())),
+            Some(_) => return Err(HubError::General(format!("Invalid token scope: {s}"))),
+            None => (s, None),
+        };
+        let action = match action {
+            "read" => ScopeAction::Read,
+            "publish" => ScopeAction::Publish,
+            _ => return Err(HubError::General(format!("Invalid token scope: {s}"))),
+        };
+
+        Ok(Self { action, group })
+    }
+}
+
+impl Serialize for TokenScope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenScope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let scope = String::deserialize(deserializer)?;
+
+        scope.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HubAccessToken {
+    /// Hub remote the token was issued by, e.g. `https://hub.infinyon.cloud`
+    pub remote: String,
+    pub token: String,
+    #[serde(default)]
+    pub scopes: Vec<TokenScope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl HubAccessToken {
+    pub fn allows(&self, action: ScopeAction, group: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.allows(action, group))
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at - Duration::seconds(EXPIRY_MARGIN_SECS) <= now)
+    }
+
+    pub fn authorization(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HubTokenStore {
+    #[serde(default, rename = "token")]
+    pub tokens: Vec<HubAccessToken>,
+}
+
+impl HubTokenStore {
+    /// `~/.fluvio/hub/tokens.toml`
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| HubError::General("Unable to find home directory".into()))?;
+
+        Ok(home
+            .join(CLI_CONFIG_PATH)
+            .join(CLI_CONFIG_HUB)
+            .join(HUB_TOKENS_FILE))
+    }
+
+    /// Reads the store at `path`, an empty store is returned if the file does
+    /// not exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let store = load_state(path, |contents| Ok(toml::from_str(contents)?)).map_err(|err| {
+            HubError::General(format!(
+                "Unable to read hub tokens file {}: {err}",
+                path.display()
+            ))
+        })?;
+
+        Ok(store.unwrap_or_default())
+    }
+
+    /// Writes the store to `path`, readable and writable only by its owner
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = toml::to_string(self)
+            .map_err(|err| HubError::General(format!("Unable to serialize hub tokens: {err}")))?;
+
+        write_private_state(path, &contents).map_err(|err| {
+            HubError::General(format!(
+                "Unable to save hub tokens file {}: {err}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Finds a token for `remote` allowing `action` on packages of `group`,
+    /// tokens scoped to the group are preferred over unscoped ones
+    pub fn find(&self, remote: &str, action: ScopeAction, group: &str) -> Option<&HubAccessToken> {
+        self.tokens
+            .iter()
+            .filter(|token| token.remote == remote && token.allows(action, group))
+            .max_by_key(|token| {
+                token
+                    .scopes
+                    .iter()
+                    .any(|scope| scope.group.as_deref() == Some(group))
+            })
+    }
+
+    /// Adds `token`, replacing a token with the same remote and scopes
+    pub fn upsert(&mut self, token: HubAccessToken) {
+        match self
+            .tokens
+            .iter_mut()
+            .find(|stored| stored.remote == token.remote && stored.scopes == token.scopes)
+        {
+            Some(stored) => *stored = token,
+            None => self.tokens.push(token),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RefreshRequest<'a> {
+    refresh_token: &'a str,
+}
+
+#[derive(Deserialize)]
+struct RefreshResponse {
+    token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Exchanges the refresh token of `token` for a new access token
+pub async fn refresh_access_token(token: &mut HubAccessToken) -> Result<()> {
+    let Some(refresh_token) = &token.refresh_token else {
+        return Err(HubError::Unauthorized(format!(
+            "access token for {} expired and cannot be refreshed, please login again",
+            token.remote
+        )));
+    };
+    let uri = format!(
+        "{}/{HUB_API_TOKEN_REFRESH}",
+        token.remote.trim_end_matches('/')
+    );
+    let body = serde_json::to_vec(&RefreshRequest { refresh_token })?;
+    let request = Request::post(&uri)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .map_err(|err| HubError::HubAccess(err.to_string()))?;
+    let response = htclient::send(request)
+        .await
+        .map_err(|err| HubError::HubAccess(err.to_string()))?;
+
+    check_access(&response, &uri)?;
+
+    let refreshed: RefreshResponse = response
+        .json()
+        .map_err(|err| HubError::HubAccess(format!("Invalid token refresh response: {err}")))?;
+
+    debug!(remote = token.remote, "Refreshed hub access token");
+    token.token = refreshed.token;
+    token.expires_at = refreshed.expires_at;
+
+    if refreshed.refresh_token.is_some() {
+        token.refresh_token = refreshed.refresh_token;
+    }
+
+    Ok(())
+}
+
+/// Maps authentication and authorization failures to [`HubError`]
+pub fn check_access(response: &Response<Vec<u8>>, uri: &str) -> Result<()> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED => Err(HubError::Unauthorized(format!(
+            "{uri} requires a valid access token, please login or add a token to {}",
+            HUB_TOKENS_FILE
+        ))),
+        StatusCode::FORBIDDEN => Err(HubError::Forbidden(format!(
+            "the access token is not allowed to access {uri}, check the token scopes"
+        ))),
+        status if !status.is_success() => Err(HubError::HubAccess(format!(
+            "Server responded with Status Code {status} for url {uri}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Downloads `uri` from `remote` authenticating with a token from `store`
+/// allowed to read packages of `group`.
+///
+/// Expired tokens are refreshed and persisted to `store_path` before the
+/// request, and once more if the Hub rejects the token. Public packages are
+/// requested without a token when the store has none.
+pub async fn get_package(
+    uri: &str,
+    remote: &str,
+    group: &str,
+    store: &mut HubTokenStore,
+    store_path: &Path,
+) -> Result<Response<Vec<u8>>> {
+    let Some(mut token) = store.find(remote, ScopeAction::Read, group).cloned() else {
+        let response = htclient::download(uri)
+            .await
+            .map_err(|err| HubError::HubAccess(err.to_string()))?;
+
+        check_access(&response, uri)?;
+        return Ok(response);
+    };
+
+    if token.is_expired(Utc::now()) {
+        refresh_access_token(&mut token).await?;
+        store.upsert(token.clone());
+        store.save(store_path)?;
+    }
+
+    let mut response = get_with_token(uri, &token).await?;
+
+    if response.status() == StatusCode::UNAUTHORIZED && token.refresh_token.is_some() {
+        refresh_access_token(&mut token).await?;
+        store.upsert(token.clone());
+        store.save(store_path)?;
+        response = get_with_token(uri, &token).await?;
+    }
+
+    check_access(&response, uri)?;
+    Ok(response)
+}
+
+/// URI of the binary package `group/name` at `version` built for `target`,
+/// e.g. `https://hub.infinyon.cloud/hub/v1/bpkg/download/infinyon/fvm-doctor/0.1.0/x86_64-unknown-linux-musl`
+pub fn binary_package_uri(
+    remote: &str,
+    group: &str,
+    name: &str,
+    version: &str,
+    target: &str,
+) -> String {
+    format!(
+        "{}/{HUB_API_BPKG_DOWNLOAD}/{group}/{name}/{version}/{target}",
+        remote.trim_end_matches('/')
+    )
+}
+
+/// Fails if the package downloaded with [`get_package`] declares it is
+/// incompatible with the install `target` or the `platform_version` it runs
+/// with. Binary packages are plain executables without package meta, they
+/// are assumed to be compatible.
+pub fn check_package_compatibility(
+    bytes: &[u8],
+    target: &str,
+    platform_version: Option<&Version>,
+) -> Result<()> {
+    match package_meta_from_bytes(bytes) {
+        Ok(meta) => meta.check_compatibility(Some(target), platform_version),
+        Err(HubError::UnableGetPackageMeta(_)) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Verifies the publish date proof of the package downloaded with
+/// [`get_package`] with the logs of `trust`, returning when the package was
+/// proven to exist. Packages without package meta or without a proof have
+/// no proven date.
+pub fn verify_package_published_at(
+    bytes: &[u8],
+    trust: &TimestampTrust,
+) -> Result<Option<DateTime<Utc>>> {
+    match package_meta_from_bytes(bytes) {
+        Ok(meta) if meta.published_at_proof.is_some() => {
+            verify_published_at(&meta, trust).map(Some)
+        }
+        Ok(_) | Err(HubError::UnableGetPackageMeta(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes the package downloaded with [`get_package`] to `dst`. Packages
+/// share storage with identical files through the content store, unless it
+/// is disabled with `FLUVIO_CONTENT_STORE=off`.
+pub fn save_package(bytes: &[u8], dst: &Path) -> Result<()> {
+    if let Some(store) = ContentStore::open_default() {
+        let stored = store
+            .insert_bytes(bytes)
+            .and_then(|digest| store.materialize(&digest, dst));
+
+        match stored {
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, ?dst, "Failed to store package in content store"),
+        }
+    }
+
+    write_atomic(dst, bytes).map_err(|err| {
+        HubError::General(format!("Unable to save package {}: {err}", dst.display()))
+    })
+}
+
+async fn get_with_token(uri: &str, token: &HubAccessToken) -> Result<Response<Vec<u8>>> {
+    let request = Request::get(uri)
+        .header(http::header::AUTHORIZATION, token.authorization())
+        .header(http::header::ACCEPT_ENCODING, "identity")
+        .body(Vec::new())
+        .map_err(|err| HubError::HubAccess(err.to_string()))?;
+
+    htclient::send(request)
+        .await
+        .map_err(|err| HubError::HubAccess(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use fluvio_hub_protocol::constants::HUB_PACKAGE_META;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn token(scopes: &[&str]) -> HubAccessToken {
+        HubAccessToken {
+            remote: "https://hub.example.com".into(),
+            token: scopes.join(","),
+            scopes: scopes.iter().map(|scope| scope.parse().unwrap()).collect(),
+            refresh_token: None,
+            expires_at: None,
+        }
+    }
+
+    fn package_with_meta(meta: &str) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+
+        header.set_size(meta.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, HUB_PACKAGE_META, meta.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn checks_package_compatibility() {
+        let package = package_with_meta(
+            r#"
+package_format_version: '0.3'
+name: example
+version: 0.0.1
+group: infinyon
+description: example
+license: Apache-2.0
+manifest: []
+visibility: public
+compatibility:
+  targets: [aarch64-apple-darwin]
+  min_platform_version: 0.12.0
+"#,
+        );
+        let current = Version::new(0, 11, 9);
+
+        assert!(check_package_compatibility(&package, "aarch64-apple-darwin", None).is_ok());
+        assert!(check_package_compatibility(&package, "x86_64-unknown-linux-musl", None).is_err());
+        assert!(
+            check_package_compatibility(&package, "aarch64-apple-darwin", Some(&current)).is_err()
+        );
+        // Binary packages carry no package meta
+        assert!(
+            check_package_compatibility(b"\x7fELF", "x86_64-unknown-linux-musl", Some(&current))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn verifies_package_publish_date_proofs() {
+        let meta = r#"
+package_format_version: '0.3'
+name: example
+version: 0.0.1
+group: infinyon
+description: example
+license: Apache-2.0
+manifest: []
+visibility: public
+tags:
+  - tag: inf::meta::published_at
+    value: 2024-05-01T10:00:00Z
+"#;
+        let proof = r#"published_at_proof:
+  kind: transparency_log
+  log: hub.example.com/log
+  leaf_index: 0
+  tree_size: 1
+  root_hash: ab12
+  hashes: []
+  integrated_at: 2024-05-01T10:01:00Z
+  signature: c2ln
+"#;
+        let trust = TimestampTrust::default();
+
+        assert_eq!(
+            verify_package_published_at(&package_with_meta(meta), &trust).unwrap(),
+            None
+        );
+        assert!(
+            verify_package_published_at(&package_with_meta(&format!("{meta}{proof}")), &trust)
+                .is_err()
+        );
+        assert_eq!(
+            verify_package_published_at(b"\x7fELF", &trust).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_token_scopes() {
+        let scope: TokenScope = "read:acme".parse().unwrap();
+
+        assert_eq!(scope.action, ScopeAction::Read);
+        assert_eq!(scope.group.as_deref(), Some("acme"));
+        assert_eq!(scope.to_string(), "read:acme");
+        assert!(scope.allows(ScopeAction::Read, "acme"));
+        assert!(!scope.allows(ScopeAction::Read, "infinyon"));
+        assert!(!scope.allows(ScopeAction::Publish, "acme"));
+        assert!(
+            "publish"
+                .parse::<TokenScope>()
+                .unwrap()
+                .allows(ScopeAction::Read, "any")
+        );
+        assert!("write:acme".parse::<TokenScope>().is_err());
+        assert!("read:".parse::<TokenScope>().is_err());
+    }
+
+    #[test]
+    fn finds_most_specific_token() {
+        let store = HubTokenStore {
+            tokens: vec![token(&["read"]), token(&["read:acme"])],
+        };
+
+        assert_eq!(
+            store
+                .find("https://hub.example.com", ScopeAction::Read, "acme")
+                .unwrap()
+                .token,
+            "read:acme"
+        );
+        assert_eq!(
+            store
+                .find("https://hub.example.com", ScopeAction::Read, "infinyon")
+                .unwrap()
+                .token,
+            "read"
+        );
+        assert!(
+            store
+                .find("https://hub.example.com", ScopeAction::Publish, "acme")
+                .is_none()
+        );
+        assert!(
+            store
+                .find("https://other.example.com", ScopeAction::Read, "acme")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn detects_expired_tokens() {
+        let now = Utc::now();
+        let mut token = token(&["read"]);
+
+        assert!(!token.is_expired(now));
+
+        token.expires_at = Some(now + Duration::seconds(30));
+        assert!(token.is_expired(now));
+
+        token.expires_at = Some(now + Duration::hours(1));
+        assert!(!token.is_expired(now));
+    }
+
+    #[test]
+    fn saves_and_loads_token_store() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("hub").join(HUB_TOKENS_FILE);
+        let mut store = HubTokenStore::load(&path).unwrap();
+
+        assert!(store.tokens.is_empty());
+
+        store.upsert(token(&["read:acme"]));
+        store.upsert(HubAccessToken {
+            refresh_token: Some("refresh".into()),
+            ..token(&["read:acme"])
+        });
+        store.save(&path).unwrap();
+
+        let loaded = HubTokenStore::load(&path).unwrap();
+
+        assert_eq!(loaded.tokens.len(), 1);
+        assert_eq!(loaded, store);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn maps_access_errors() {
+        let response = |status: u16| Response::builder().status(status).body(vec![]).unwrap();
+
+        assert!(matches!(
+            check_access(&response(401), "uri"),
+            Err(HubError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            check_access(&response(403), "uri"),
+            Err(HubError::Forbidden(_))
+        ));
+        assert!(matches!(
+            check_access(&response(500), "uri"),
+            Err(HubError::HubAccess(_))
+        ));
+        assert!(check_access(&response(200), "uri").is_ok());
+    }
+}
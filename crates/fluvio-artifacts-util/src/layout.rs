@@ -0,0 +1,262 @@
+//! Artifact Directory Layout
+//!
+//! Where installed versions, binaries, caches and scratch files live. The
+//! `home` layout keeps everything under `~/.fvm` and `~/.fluvio`, as FVM has
+//! always done. The `platform` layout follows the conventions of the host:
+//! XDG base directories on Linux, `~/Library` on macOS and `%LOCALAPPDATA%`
+//! on Windows.
+//!
+//! Each directory can be overridden on its own with an environment variable,
+//! e.g. `FLUVIO_VERSIONS_DIR`.
+
+use std::fmt::Display;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use fluvio_types::defaults::CLI_CONFIG_PATH;
+
+/// Environment variable selecting the layout style, `home` or `platform`
+pub const LAYOUT_ENV: &str = "FLUVIO_LAYOUT";
+pub const VERSIONS_DIR_ENV: &str = "FLUVIO_VERSIONS_DIR";
+pub const BIN_DIR_ENV: &str = "FLUVIO_BIN_DIR";
+pub const CACHE_DIR_ENV: &str = "FLUVIO_CACHE_DIR";
+pub const TEMP_DIR_ENV: &str = "FLUVIO_TEMP_DIR";
+
+/// Default FVM home directory name, relative to the user home
+pub const FVM_HOME_DIR: &str = ".fvm";
+
+/// Application directory name used by the platform layout
+const PLATFORM_APP_DIR: &str = "fluvio";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutStyle {
+    /// `~/.fvm` and `~/.fluvio`
+    #[default]
+    Home,
+    /// Host conventions, e.g. XDG base directories on Linux
+    Platform,
+}
+
+impl LayoutStyle {
+    /// Reads the style from `FLUVIO_LAYOUT`, `None` if it is not set
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(LAYOUT_ENV) {
+            Ok(value) if !value.is_empty() => value.parse().map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Display for LayoutStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Home => write!(f, "home"),
+            Self::Platform => write!(f, "platform"),
+        }
+    }
+}
+
+impl FromStr for LayoutStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "home" => Ok(Self::Home),
+            "platform" | "xdg" => Ok(Self::Platform),
+            _ => Err(anyhow!(
+                "invalid layout \"{s}\", expected \"home\" or \"platform\""
+            )),
+        }
+    }
+}
+
+/// Directories used to install and run Fluvio versions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Installed versions, one directory per channel or version
+    pub versions_dir: PathBuf,
+    /// Binaries of the active version, expected to be in `PATH`
+    pub bin_dir: PathBuf,
+    /// Disposable metadata, such as cached release information
+    pub cache_dir: PathBuf,
+    /// Scratch space for downloads, on the same filesystem as
+    /// `versions_dir` by default so staged files are moved with a rename
+    pub temp_dir: PathBuf,
+}
+
+impl Layout {
+    /// Layout rooted in the FVM and Fluvio home directories
+    pub fn home(fvm_home: &Path, fluvio_home: &Path) -> Self {
+        Self {
+            versions_dir: fvm_home.join("versions"),
+            bin_dir: fluvio_home.join("bin"),
+            cache_dir: fvm_home.join("cache"),
+            temp_dir: fvm_home.join("tmp"),
+        }
+    }
+
+    /// Layout following the host conventions
+    pub fn platform() -> Result<Self> {
+        let data_dir = dirs::data_local_dir()
+            .ok_or_else(|| anyhow!("Failed to resolve the local data directory"))?;
+        let cache_dir =
+            dirs::cache_dir().ok_or_else(|| anyhow!("Failed to resolve the cache directory"))?;
+
+        Ok(Self::platform_in(
+            &data_dir,
+            &cache_dir,
+            dirs::executable_dir().as_deref(),
+        ))
+    }
+
+    /// Builds the platform layout out of the host data, cache and
+    /// executable directories. Only Linux has a per-user executable
+    /// directory (`~/.local/bin`), elsewhere binaries live in the data
+    /// directory.
+    fn platform_in(data_dir: &Path, cache_dir: &Path, executable_dir: Option<&Path>) -> Self {
+        let app_data_dir = data_dir.join(PLATFORM_APP_DIR);
+
+        Self {
+            versions_dir: app_data_dir.join("versions"),
+            bin_dir: executable_dir
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| app_data_dir.join("bin")),
+            cache_dir: cache_dir.join(PLATFORM_APP_DIR),
+            temp_dir: app_data_dir.join("tmp"),
+        }
+    }
+
+    /// Resolves the layout for `style`, the home layout is rooted in
+    /// `fvm_home` and `fluvio_home`
+    pub fn resolve(style: LayoutStyle, fvm_home: &Path, fluvio_home: &Path) -> Result<Self> {
+        match style {
+            LayoutStyle::Home => Ok(Self::home(fvm_home, fluvio_home)),
+            LayoutStyle::Platform => Self::platform(),
+        }
+    }
+
+    /// Layout for library consumers: the style from `FLUVIO_LAYOUT`, rooted
+    /// in the user home, with the environment overrides applied
+    pub fn from_env() -> Result<Self> {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!("Failed to resolve home directory"))?;
+        let style = LayoutStyle::from_env()?.unwrap_or_default();
+        let layout = Self::resolve(style, &home.join(FVM_HOME_DIR), &home.join(CLI_CONFIG_PATH))?;
+
+        Ok(layout.with_env_overrides())
+    }
+
+    /// Applies the per directory environment variable overrides
+    pub fn with_env_overrides(self) -> Self {
+        self.with_overrides(|key| std::env::var(key).ok())
+    }
+
+    fn with_overrides(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let dirs = [
+            (VERSIONS_DIR_ENV, &mut self.versions_dir),
+            (BIN_DIR_ENV, &mut self.bin_dir),
+            (CACHE_DIR_ENV, &mut self.cache_dir),
+            (TEMP_DIR_ENV, &mut self.temp_dir),
+        ];
+
+        for (key, dir) in dirs {
+            if let Some(value) = lookup(key).filter(|value| !value.is_empty()) {
+                *dir = PathBuf::from(value);
+            }
+        }
+
+        self
+    }
+
+    /// Directory for the version installed from `channel`, e.g.
+    /// `~/.fvm/versions/stable`
+    pub fn version_dir(&self, channel: impl Display) -> PathBuf {
+        self.versions_dir.join(channel.to_string())
+    }
+
+    /// Creates every directory of the layout
+    pub fn create_dirs(&self) -> Result<()> {
+        for dir in [
+            &self.versions_dir,
+            &self.bin_dir,
+            &self.cache_dir,
+            &self.temp_dir,
+        ] {
+            create_dir_all(dir)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_home_layout() {
+        let layout = Layout::home(
+            Path::new("/home/user/.fvm"),
+            Path::new("/home/user/.fluvio"),
+        );
+
+        assert_eq!(layout.versions_dir, Path::new("/home/user/.fvm/versions"));
+        assert_eq!(layout.bin_dir, Path::new("/home/user/.fluvio/bin"));
+        assert_eq!(layout.cache_dir, Path::new("/home/user/.fvm/cache"));
+        assert_eq!(layout.temp_dir, Path::new("/home/user/.fvm/tmp"));
+        assert_eq!(
+            layout.version_dir("stable"),
+            Path::new("/home/user/.fvm/versions/stable")
+        );
+    }
+
+    #[test]
+    fn resolves_platform_layout() {
+        let xdg = Layout::platform_in(
+            Path::new("/home/user/.local/share"),
+            Path::new("/home/user/.cache"),
+            Some(Path::new("/home/user/.local/bin")),
+        );
+
+        assert_eq!(
+            xdg.versions_dir,
+            Path::new("/home/user/.local/share/fluvio/versions")
+        );
+        assert_eq!(xdg.bin_dir, Path::new("/home/user/.local/bin"));
+        assert_eq!(xdg.cache_dir, Path::new("/home/user/.cache/fluvio"));
+        assert_eq!(
+            xdg.temp_dir,
+            Path::new("/home/user/.local/share/fluvio/tmp")
+        );
+
+        let macos = Layout::platform_in(
+            Path::new("/Users/user/Library/Application Support"),
+            Path::new("/Users/user/Library/Caches"),
+            None,
+        );
+
+        assert_eq!(
+            macos.bin_dir,
+            Path::new("/Users/user/Library/Application Support/fluvio/bin")
+        );
+    }
+
+    #[test]
+    fn applies_overrides() {
+        let layout =
+            Layout::home(Path::new("/fvm"), Path::new("/fluvio")).with_overrides(|key| match key {
+                VERSIONS_DIR_ENV => Some(String::from("/mnt/versions")),
+                TEMP_DIR_ENV => Some(String::new()),
+                _ => None,
+            });
+
+        assert_eq!(layout.versions_dir, Path::new("/mnt/versions"));
+        assert_eq!(layout.temp_dir, Path::new("/fvm/tmp"));
+        assert_eq!("xdg".parse::<LayoutStyle>().unwrap(), LayoutStyle::Platform);
+        assert!("opt".parse::<LayoutStyle>().is_err());
+    }
+}
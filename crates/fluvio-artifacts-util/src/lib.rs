@@ -1,11 +1,27 @@
 mod package_meta_ext;
 mod utils;
 
+pub mod extraction;
+pub mod failure;
 pub mod htclient;
+pub mod hub;
+pub mod layout;
+pub mod package_contents;
+pub mod quarantine;
+pub mod remote_zip;
+pub mod state;
+pub mod store;
+pub mod timestamp;
+pub mod verification;
 
 pub mod fvm;
+pub mod github;
+
+#[cfg(feature = "fixture")]
+pub mod fixture;
 
 pub use http;
+pub use fluvio_progress as progress;
 pub use package_meta_ext::*;
 pub use utils::*;
 pub use utils::sha256_digest;
@@ -0,0 +1,243 @@
+//! Hub Package Contents
+//!
+//! A Hub package is a tarball holding the package meta and the manifest
+//! blob, a gzipped tarball with the files listed in the package meta
+//! `manifest`. [`PackageContents`] lists the files of the manifest blob with
+//! their sizes and digests by streaming it through the hasher, nothing is
+//! written to disk, so packages are inspected without being extracted and
+//! checked to contain exactly the files they declare.
+
+use std::collections::BTreeSet;
+use std::io::{Read, copy};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+use fluvio_hub_protocol::{HubError, HubLayout, PackageMeta, Result};
+
+use crate::utils::Sha256Digest;
+
+/// A file of the manifest blob of a package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackageFile {
+    /// Path of the file in the manifest blob
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 digest of the file
+    pub sha256: String,
+}
+
+/// Package meta and manifest blob files of a package
+#[derive(Clone, Debug)]
+pub struct PackageContents {
+    pub meta: PackageMeta,
+    pub files: Vec<PackageFile>,
+}
+
+impl PackageContents {
+    /// Reads the package meta and lists the manifest blob files of the
+    /// package tarball read from `reader`, in any known [`HubLayout`]
+    pub fn read(reader: impl Read) -> Result<Self> {
+        let mut archive = tar::Archive::new(reader);
+        let mut meta: Option<PackageMeta> = None;
+        let mut files = None;
+
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let path = entry.path()?.into_owned();
+
+            if HubLayout::KNOWN
+                .iter()
+                .any(|layout| path == Path::new(layout.package_meta))
+            {
+                meta = Some(serde_yaml::from_reader(entry)?);
+            } else if HubLayout::KNOWN
+                .iter()
+                .any(|layout| path == Path::new(layout.manifest_blob))
+            {
+                files = Some(list_manifest_blob(entry)?);
+            }
+        }
+
+        let meta = meta.ok_or_else(|| {
+            HubError::UnableGetPackageMeta("package has no package meta file".into())
+        })?;
+        let layout = meta.layout();
+        let files =
+            files.ok_or_else(|| HubError::PackageMissingFile(layout.manifest_blob.into()))?;
+
+        Ok(Self { meta, files })
+    }
+
+    /// Reads the package file at `path`, see [`Self::read`]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::read(std::fs::File::open(path)?)
+    }
+
+    /// Files declared in the package meta `manifest` but not in the package
+    pub fn missing(&self) -> Vec<&str> {
+        let files: BTreeSet<&str> = self.files.iter().map(|file| file.path.as_str()).collect();
+
+        self.declared()
+            .into_iter()
+            .filter(|path| !files.contains(path))
+            .collect()
+    }
+
+    /// Files in the package but not declared in the package meta `manifest`
+    pub fn undeclared(&self) -> Vec<&str> {
+        let declared = self.declared();
+
+        self.files
+            .iter()
+            .map(|file| file.path.as_str())
+            .filter(|path| !declared.contains(path))
+            .collect()
+    }
+
+    /// Checks the package contains exactly the files declared in its package
+    /// meta `manifest`
+    pub fn check_declared(&self) -> Result<()> {
+        if let Some(path) = self.missing().first() {
+            return Err(HubError::ManifestMissingFile(path.to_string()));
+        }
+
+        if let Some(path) = self.undeclared().first() {
+            return Err(HubError::ManifestInvalidFile(format!(
+                "{path} is in the package but not declared in the manifest"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Total size of the files, once extracted
+    pub fn extracted_size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+
+    fn declared(&self) -> BTreeSet<&str> {
+        self.meta
+            .manifest
+            .iter()
+            .map(|path| normalize(path))
+            .collect()
+    }
+}
+
+/// Lists the files of the gzipped manifest blob read from `reader`, hashing
+/// each file as it is read
+pub fn list_manifest_blob(reader: impl Read) -> Result<Vec<PackageFile>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut files = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut digest = Sha256Digest::new();
+
+        copy(&mut entry, &mut digest)?;
+        files.push(PackageFile {
+            path: normalize(&path).to_string(),
+            size: digest.len(),
+            sha256: digest.finalize(),
+        });
+    }
+
+    Ok(files)
+}
+
+fn normalize(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use super::*;
+
+    fn append(builder: &mut tar::Builder<impl std::io::Write>, path: &str, bytes: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, bytes).unwrap();
+    }
+
+    fn package(manifest: &[&str], files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut blob = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        for (path, bytes) in files {
+            append(&mut blob, path, bytes);
+        }
+
+        let blob = blob.into_inner().unwrap().finish().unwrap();
+        let meta = PackageMeta {
+            group: "infinyon".into(),
+            name: "example".into(),
+            version: "0.0.1".into(),
+            manifest: manifest.iter().map(|path| path.to_string()).collect(),
+            ..PackageMeta::default()
+        };
+        let mut package = tar::Builder::new(Vec::new());
+
+        append(
+            &mut package,
+            HubLayout::CURRENT.package_meta,
+            serde_yaml::to_string(&meta).unwrap().as_bytes(),
+        );
+        append(&mut package, HubLayout::CURRENT.manifest_blob, &blob);
+        package.into_inner().unwrap()
+    }
+
+    #[test]
+    fn lists_manifest_blob_files() {
+        let bytes = package(
+            &["module.wasm", "README.md"],
+            &[("./module.wasm", b"wasm"), ("README.md", b"")],
+        );
+        let contents = PackageContents::read(bytes.as_slice()).unwrap();
+
+        assert_eq!(contents.meta.name, "example");
+        assert_eq!(
+            contents.files[0],
+            PackageFile {
+                path: "module.wasm".into(),
+                size: 4,
+                sha256: "336154bf67f765f8f75d16a0accee61b5ee5f6a75b2a2905703df913bd550f3e".into(),
+            }
+        );
+        assert_eq!(contents.extracted_size(), 4);
+        assert!(contents.check_declared().is_ok());
+    }
+
+    #[test]
+    fn checks_files_match_the_manifest() {
+        let bytes = package(&["module.wasm", "README.md"], &[("module.wasm", b"wasm")]);
+        let contents = PackageContents::read(bytes.as_slice()).unwrap();
+
+        assert_eq!(contents.missing(), vec!["README.md"]);
+        assert!(matches!(
+            contents.check_declared(),
+            Err(HubError::ManifestMissingFile(_))
+        ));
+
+        let bytes = package(&[], &[("module.wasm", b"wasm")]);
+        let contents = PackageContents::read(bytes.as_slice()).unwrap();
+
+        assert_eq!(contents.undeclared(), vec!["module.wasm"]);
+        assert!(matches!(
+            contents.check_declared(),
+            Err(HubError::ManifestInvalidFile(_))
+        ));
+        assert!(PackageContents::read(&b"not a package"[..]).is_err());
+    }
+}
@@ -2,13 +2,15 @@ use std::fs;
 use std::io::Read;
 use std::path::Path;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use tracing::debug;
 
-use fluvio_hub_protocol::{HubError, PackageMeta, PkgTag};
-use fluvio_hub_protocol::constants::{HUB_PACKAGE_META, PKG_TAG_META_PUBLISHED_AT};
+use fluvio_hub_protocol::{HubError, HubLayout, PackageMeta, PkgTag};
+use fluvio_hub_protocol::constants::PKG_TAG_META_PUBLISHED_AT;
 use fluvio_hub_protocol::validate_allowedchars;
 
+use crate::timestamp::{self, TimestampTrust};
+
 type Result<T> = std::result::Result<T, HubError>;
 
 pub trait PackageMetaExt {
@@ -17,6 +19,10 @@ pub trait PackageMetaExt {
     fn write<P: AsRef<Path>>(&self, pmetapath: P) -> Result<()>;
     fn update_from_cargo_toml<P: AsRef<Path>>(&mut self, fpath: P) -> Result<()>;
     fn published_at(&self) -> Result<DateTime<Utc>>;
+    fn set_published_at(&mut self, published_at: DateTime<Utc>);
+    fn validate_published_at(&self) -> Result<()>;
+    fn validate_for_publish(&self) -> Result<()>;
+    fn verify_published_at(&self, trust: &TimestampTrust) -> Result<DateTime<Utc>>;
 }
 
 impl PackageMetaExt for PackageMeta {
@@ -68,11 +74,82 @@ impl PackageMetaExt for PackageMeta {
                 "Missing {PKG_TAG_META_PUBLISHED_AT} tag"
             )))?;
 
-        let publisted_at = DateTime::parse_from_rfc2822(published_at_pkg_tag.value.as_str())
+        // Packages published before the tag was RFC3339 use RFC2822
+        let value = published_at_pkg_tag.value.as_str();
+        let published_at = DateTime::parse_from_rfc3339(value)
+            .or_else(|_| DateTime::parse_from_rfc2822(value))
             .map_err(|err| HubError::General(format!("Failed to parse publish date. {err}")))?;
 
-        Ok(publisted_at.to_utc())
+        Ok(published_at.to_utc())
+    }
+
+    /// Sets the reserved publish date tag as RFC3339, replacing any previous
+    /// value
+    fn set_published_at(&mut self, published_at: DateTime<Utc>) {
+        self.tag_remove(PKG_TAG_META_PUBLISHED_AT);
+        self.tag_add(
+            PKG_TAG_META_PUBLISHED_AT,
+            &published_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+    }
+
+    /// Checks the publish date tag, if set, is a RFC3339 timestamp
+    fn validate_published_at(&self) -> Result<()> {
+        let Some(value) = self.tag_value(PKG_TAG_META_PUBLISHED_AT) else {
+            return Ok(());
+        };
+
+        DateTime::parse_from_rfc3339(value).map_err(|err| {
+            HubError::InvalidPackageTag(format!(
+                "{PKG_TAG_META_PUBLISHED_AT} {value} is not a RFC3339 timestamp: {err}"
+            ))
+        })?;
+
+        Ok(())
+    }
+
+    /// Checks performed before publishing the package: names, tags and the
+    /// publish date format
+    fn validate_for_publish(&self) -> Result<()> {
+        self.naming_check()?;
+        self.validate_published_at()
     }
+
+    /// Verifies the publish date proof with the keys of `trust`, returning
+    /// when the package version was proven to exist, see [`crate::timestamp`]
+    fn verify_published_at(&self, trust: &TimestampTrust) -> Result<DateTime<Utc>> {
+        timestamp::verify_published_at(self, trust)
+    }
+}
+
+/// Sorts packages from the most recently published, packages without a
+/// valid publish date are sorted last
+pub fn sort_by_published_at(pkgs: &mut [PackageMeta]) {
+    pkgs.sort_by_cached_key(|pm| std::cmp::Reverse(pm.published_at().ok()));
+}
+
+/// Packages published at or after `since`, in their original order
+pub fn published_since<'a>(
+    pkgs: impl IntoIterator<Item = &'a PackageMeta>,
+    since: DateTime<Utc>,
+) -> Vec<&'a PackageMeta> {
+    pkgs.into_iter()
+        .filter(|pm| {
+            pm.published_at()
+                .is_ok_and(|published_at| published_at >= since)
+        })
+        .collect()
+}
+
+/// The most recently published package, packages without a valid publish
+/// date are ignored
+pub fn newest_published<'a>(
+    pkgs: impl IntoIterator<Item = &'a PackageMeta>,
+) -> Option<&'a PackageMeta> {
+    pkgs.into_iter()
+        .filter_map(|pm| Some((pm.published_at().ok()?, pm)))
+        .max_by_key(|(published_at, _)| *published_at)
+        .map(|(_, pm)| pm)
 }
 
 pub fn packagename_validate(pkgname: &str) -> Result<()> {
@@ -118,7 +195,6 @@ pub fn packagename_transform(pkgname: &str) -> Result<String> {
 /// package.
 pub fn package_meta_from_bytes(reader: &[u8]) -> Result<PackageMeta> {
     let mut tarfile = tar::Archive::new(reader);
-    let pkg_meta = Path::new(HUB_PACKAGE_META);
     let entries = tarfile.entries()?;
     for file in entries {
         if file.is_err() {
@@ -126,7 +202,9 @@ pub fn package_meta_from_bytes(reader: &[u8]) -> Result<PackageMeta> {
         }
         let mut f = file?;
         if let Ok(fp) = f.path()
-            && fp == pkg_meta
+            && HubLayout::KNOWN
+                .iter()
+                .any(|layout| fp == Path::new(layout.package_meta))
         {
             let mut buf = String::new();
             f.read_to_string(&mut buf)?;
@@ -368,6 +446,90 @@ mod tests {
         assert_eq!(published_at.to_string(), "2022-11-22 21:24:11 UTC");
     }
 
+    #[test]
+    fn inf_meta_set_published_at() {
+        let mut pm = PackageMeta::default();
+        let published_at = DateTime::parse_from_rfc3339("2022-11-22T21:24:11Z")
+            .unwrap()
+            .to_utc();
+
+        pm.set_published_at(Utc::now());
+        pm.set_published_at(published_at);
+
+        assert_eq!(pm.published_at().unwrap(), published_at);
+        assert_eq!(pm.tag_get(PKG_TAG_META_PUBLISHED_AT).unwrap().len(), 1);
+        assert!(pm.validate_tags().is_ok());
+    }
+
+    #[test]
+    fn inf_meta_published_at_rfc3339() {
+        let mut pm = PackageMeta::default();
+
+        pm.set_published_at(
+            DateTime::parse_from_rfc3339("2022-11-22T21:24:11Z")
+                .unwrap()
+                .to_utc(),
+        );
+
+        assert_eq!(
+            pm.tag_value(PKG_TAG_META_PUBLISHED_AT),
+            Some("2022-11-22T21:24:11Z")
+        );
+        assert!(pm.validate_published_at().is_ok());
+
+        pm.tag_set_reserved(PKG_TAG_META_PUBLISHED_AT, "Tue, 22 Nov 2022 21:24:11 GMT")
+            .unwrap();
+
+        assert!(pm.published_at().is_ok(), "legacy dates are still read");
+        assert!(matches!(
+            pm.validate_published_at(),
+            Err(HubError::InvalidPackageTag(_))
+        ));
+    }
+
+    #[test]
+    fn inf_meta_sorts_by_published_at() {
+        let published = |version: &str, published_at: Option<&str>| {
+            let mut pm = PackageMeta {
+                version: version.into(),
+                ..PackageMeta::default()
+            };
+
+            if let Some(published_at) = published_at {
+                pm.tag_add(PKG_TAG_META_PUBLISHED_AT, published_at);
+            }
+
+            pm
+        };
+        let mut pkgs = vec![
+            published("0.1.0", Some("2022-11-22T21:24:11Z")),
+            published("0.0.0", None),
+            published("0.3.0", Some("2023-02-01T08:00:00+01:00")),
+            published("0.2.0", Some("Wed, 04 Jan 2023 10:00:00 GMT")),
+        ];
+        let since = DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        assert_eq!(newest_published(&pkgs).unwrap().version, "0.3.0");
+        assert_eq!(
+            published_since(&pkgs, since)
+                .iter()
+                .map(|pm| pm.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["0.3.0", "0.2.0"]
+        );
+
+        sort_by_published_at(&mut pkgs);
+
+        assert_eq!(
+            pkgs.iter()
+                .map(|pm| pm.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["0.3.0", "0.2.0", "0.1.0", "0.0.0"]
+        );
+    }
+
     #[test]
     fn inf_meta_published_at_missing_tag() {
         let pm = PackageMeta {
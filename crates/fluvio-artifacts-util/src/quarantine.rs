@@ -0,0 +1,198 @@
+//! Download Quarantine
+//!
+//! Downloaded bytes which don't match their published digest are evidence
+//! of a corrupted mirror or a tampered connection. Rather than discarding
+//! them, they are kept in the quarantine directory, e.g.
+//! `~/.fluvio/quarantine`, next to a JSON sidecar recording where they were
+//! downloaded from, the expected and actual digests, the response headers
+//! and when they were quarantined, so users and maintainers can investigate
+//! the incident.
+//!
+//! Quarantined files are never executed nor linked anywhere, and are named
+//! after their actual digest, so the same corrupted download is kept once.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use fluvio_types::defaults::CLI_CONFIG_PATH;
+
+use crate::state::write_atomic;
+
+/// Environment variable with the path to the quarantine directory,
+/// quarantining is disabled if it is set to `off`
+pub const QUARANTINE_ENV: &str = "FLUVIO_QUARANTINE";
+
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Extension appended to quarantined files for their metadata sidecar
+pub const QUARANTINE_METADATA_EXT: &str = "json";
+
+/// What is known about a quarantined download
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    /// Artifact name, e.g. `fluvio`
+    pub name: String,
+    pub version: String,
+    /// URL the bytes were downloaded from
+    pub url: String,
+    /// Published SHA-256 digest
+    pub expected_sha256: String,
+    /// SHA-256 digest of the downloaded bytes
+    pub actual_sha256: String,
+    pub size: u64,
+    /// Response headers, values of repeated headers are joined with `, `
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// When the bytes were quarantined, in RFC 3339 format
+    pub quarantined_at: String,
+}
+
+impl QuarantineRecord {
+    pub fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        url: impl Into<String>,
+        expected_sha256: impl Into<String>,
+        actual_sha256: impl Into<String>,
+        size: u64,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            url: url.into(),
+            expected_sha256: expected_sha256.into(),
+            actual_sha256: actual_sha256.into(),
+            size,
+            headers: BTreeMap::new(),
+            quarantined_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+
+    /// Records the response `headers`
+    pub fn with_headers(mut self, headers: &HeaderMap) -> Self {
+        for (name, value) in headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+
+            self.headers
+                .entry(name.to_string())
+                .and_modify(|values| {
+                    values.push_str(", ");
+                    values.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    root: PathBuf,
+}
+
+impl Quarantine {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Quarantine directory from [`QUARANTINE_ENV`], or `~/.fluvio/quarantine`.
+    /// `None` if quarantining is disabled or there is no home directory.
+    pub fn open_default() -> Option<Self> {
+        match std::env::var(QUARANTINE_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("off") => None,
+            Ok(value) if !value.is_empty() => Some(Self::new(value)),
+            _ => {
+                let home = dirs::home_dir()?;
+
+                Some(Self::new(home.join(CLI_CONFIG_PATH).join(QUARANTINE_DIR)))
+            }
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Keeps `bytes` along with the metadata sidecar of `record`, returning
+    /// the path of the quarantined file, e.g.
+    /// `fluvio-0.11.8-ab12cd34ef56.quarantined`
+    pub fn store(&self, bytes: &[u8], record: &QuarantineRecord) -> Result<PathBuf> {
+        let digest = record
+            .actual_sha256
+            .get(..12)
+            .unwrap_or(&record.actual_sha256);
+        let path = self.root.join(format!(
+            "{}-{}-{digest}.quarantined",
+            record.name, record.version
+        ));
+        let metadata = serde_json::to_vec_pretty(record)?;
+
+        write_atomic(&path, bytes)?;
+        write_atomic(metadata_path(&path), &metadata)?;
+
+        tracing::warn!(
+            name = record.name,
+            url = record.url,
+            path = %path.display(),
+            "Quarantined download failing its checksum"
+        );
+
+        Ok(path)
+    }
+}
+
+/// Path of the metadata sidecar of the quarantined file at `path`
+pub fn metadata_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_os_string();
+
+    sidecar.push(format!(".{QUARANTINE_METADATA_EXT}"));
+    PathBuf::from(sidecar)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn keeps_bytes_with_metadata_sidecar() {
+        let dir = TempDir::new().unwrap();
+        let quarantine = Quarantine::new(dir.path());
+        let mut headers = HeaderMap::new();
+
+        headers.insert("server", HeaderValue::from_static("mirror"));
+        headers.append("via", HeaderValue::from_static("proxy-a"));
+        headers.append("via", HeaderValue::from_static("proxy-b"));
+
+        let record = QuarantineRecord::new(
+            "fluvio",
+            "0.11.8",
+            "https://mirror.example.com/fluvio.zip",
+            "00".repeat(32),
+            "ab12cd34ef5678".repeat(4),
+            8,
+        )
+        .with_headers(&headers);
+        let path = quarantine.store(b"tampered", &record).unwrap();
+
+        assert_eq!(
+            path,
+            dir.path().join("fluvio-0.11.8-ab12cd34ef56.quarantined")
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"tampered");
+
+        let sidecar: QuarantineRecord =
+            serde_json::from_slice(&std::fs::read(metadata_path(&path)).unwrap()).unwrap();
+
+        assert_eq!(sidecar, record);
+        assert_eq!(sidecar.headers["via"], "proxy-a, proxy-b");
+    }
+}
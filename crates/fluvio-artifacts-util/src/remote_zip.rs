@@ -0,0 +1,404 @@
+//! Remote Zip Inspection
+//!
+//! Lists the entries of a zip archive served over HTTP without downloading
+//! it: the end of central directory record is read from the tail of the
+//! archive with a suffix range request, followed by the central directory it
+//! points to, usually a few KiB even for archives of hundreds of MiB. Each
+//! entry is listed with its sizes and CRC-32, so installs are planned
+//! accurately and archives missing the expected binary are detected before
+//! being downloaded.
+//!
+//! Servers ignoring range requests answer with the whole archive, which is
+//! then inspected in memory.
+
+use anyhow::{Context, Result, anyhow};
+use http::{Request, StatusCode, header};
+
+use crate::extraction::ExtractionLimits;
+use crate::failure::status_error;
+use crate::htclient;
+
+/// Bytes read from the end of an archive, the end of central directory
+/// record followed by the longest archive comment
+pub const TAIL_SIZE: u64 = END_RECORD_SIZE as u64 + u16::MAX as u64;
+
+/// Largest central directory read, 64 bytes per entry for the default limit
+/// of entries is well below
+pub const MAX_CENTRAL_DIRECTORY_SIZE: u64 = 16 * 1024 * 1024;
+
+const END_RECORD_SIGNATURE: u32 = 0x0605_4b50;
+const END_RECORD_SIZE: usize = 22;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_LOCATOR_SIZE: usize = 20;
+const ZIP64_END_RECORD_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_END_RECORD_SIZE: usize = 56;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_SIZE: usize = 46;
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+
+/// Entry listed in the central directory of an archive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteZipEntry {
+    pub name: String,
+    pub compressed_size: u64,
+    /// Size once extracted
+    pub size: u64,
+    pub crc32: u32,
+}
+
+impl RemoteZipEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Entries of a remote archive, see [`RemoteZipIndex::fetch`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteZipIndex {
+    /// Size of the archive
+    pub archive_size: u64,
+    pub entries: Vec<RemoteZipEntry>,
+}
+
+impl RemoteZipIndex {
+    /// Reads the central directory of the archive at `url`
+    pub async fn fetch(url: &str) -> Result<Self> {
+        let tail = fetch_range(url, ByteRange::Suffix(TAIL_SIZE)).await?;
+        let end = EndRecord::find(&tail.bytes, tail.offset)
+            .with_context(|| format!("Unable to read zip archive at {url}"))?;
+        let end = match end.zip64_end_offset {
+            Some(offset) => {
+                let record = match tail.slice(offset, ZIP64_END_RECORD_SIZE as u64) {
+                    Some(record) => record.to_vec(),
+                    None => {
+                        let range = ByteRange::Span(offset, ZIP64_END_RECORD_SIZE as u64);
+
+                        fetch_range(url, range).await?.bytes
+                    }
+                };
+
+                EndRecord::parse_zip64(&record)?
+            }
+            None => end,
+        };
+
+        if end.directory_size > MAX_CENTRAL_DIRECTORY_SIZE {
+            return Err(anyhow!(
+                "Central directory of {} bytes exceeds the limit of {MAX_CENTRAL_DIRECTORY_SIZE} bytes",
+                end.directory_size
+            ));
+        }
+
+        ExtractionLimits::from_env()?
+            .budget(tail.total)
+            .check_entries(end.entries)?;
+
+        let directory = match tail.slice(end.directory_offset, end.directory_size) {
+            Some(directory) => directory.to_vec(),
+            None => {
+                let range = ByteRange::Span(end.directory_offset, end.directory_size);
+
+                fetch_range(url, range).await?.bytes
+            }
+        };
+
+        Ok(Self {
+            archive_size: tail.total,
+            entries: parse_central_directory(&directory, end.entries)?,
+        })
+    }
+
+    /// Entries which are files
+    pub fn files(&self) -> impl Iterator<Item = &RemoteZipEntry> {
+        self.entries.iter().filter(|entry| !entry.is_dir())
+    }
+
+    /// Bytes extracted from the archive
+    pub fn extracted_size(&self) -> u64 {
+        self.files().map(|entry| entry.size).sum()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ByteRange {
+    /// Last bytes of the resource
+    Suffix(u64),
+    /// Bytes from an offset
+    Span(u64, u64),
+}
+
+/// Bytes of a resource starting at `offset`, out of `total`
+struct RangeBytes {
+    bytes: Vec<u8>,
+    offset: u64,
+    total: u64,
+}
+
+impl RangeBytes {
+    /// The `len` bytes at `offset` of the resource, if read
+    fn slice(&self, offset: u64, len: u64) -> Option<&[u8]> {
+        let start = usize::try_from(offset.checked_sub(self.offset)?).ok()?;
+        let end = start.checked_add(usize::try_from(len).ok()?)?;
+
+        self.bytes.get(start..end)
+    }
+}
+
+async fn fetch_range(url: &str, range: ByteRange) -> Result<RangeBytes> {
+    let value = match range {
+        ByteRange::Suffix(len) => format!("bytes=-{len}"),
+        ByteRange::Span(offset, len) => {
+            format!("bytes={offset}-{}", offset + len.max(1) - 1)
+        }
+    };
+    let request = Request::get(url)
+        .header(header::RANGE, value)
+        // Ranges of an encoded body are not ranges of the archive
+        .header(header::ACCEPT_ENCODING, "identity")
+        .body(Vec::new())?;
+    let response = htclient::send(request).await?;
+
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            let content_range = response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("Partial response from {url} lacks Content-Range"))?;
+            let (offset, total) = parse_content_range(content_range)
+                .ok_or_else(|| anyhow!("Invalid Content-Range \"{content_range}\" from {url}"))?;
+
+            Ok(RangeBytes {
+                bytes: response.into_body(),
+                offset,
+                total,
+            })
+        }
+        // The server ignored the range and sent the whole archive
+        StatusCode::OK => {
+            let bytes = response.into_body();
+            let total = bytes.len() as u64;
+
+            Ok(RangeBytes {
+                bytes,
+                offset: 0,
+                total,
+            })
+        }
+        status => Err(status_error(
+            status,
+            format!("Server responded with Status Code {status} for url {url}"),
+        )),
+    }
+}
+
+/// Offset and total size of a `Content-Range` such as `bytes 10-19/100`
+fn parse_content_range(value: &str) -> Option<(u64, u64)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _) = range.split_once('-')?;
+
+    Some((start.parse().ok()?, total.parse().ok()?))
+}
+
+/// Location of the central directory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct EndRecord {
+    entries: u64,
+    directory_size: u64,
+    directory_offset: u64,
+    /// Offset of the ZIP64 end record holding the actual values, for
+    /// archives exceeding the limits of the classic format
+    zip64_end_offset: Option<u64>,
+}
+
+impl EndRecord {
+    /// Finds the end record in `tail`, the bytes of the archive from `offset`
+    fn find(tail: &[u8], offset: u64) -> Result<Self> {
+        let position = (0..=tail.len().saturating_sub(END_RECORD_SIZE))
+            .rev()
+            .find(|&position| read_u32(tail, position) == Some(END_RECORD_SIGNATURE))
+            .ok_or_else(|| anyhow!("Not a zip archive, end of central directory not found"))?;
+        let record = &tail[position..];
+        let field = |at| read_u16(record, at).map(u64::from);
+        let mut end = Self {
+            entries: field(10).unwrap_or_default(),
+            directory_size: read_u32(record, 12).map(u64::from).unwrap_or_default(),
+            directory_offset: read_u32(record, 16).map(u64::from).unwrap_or_default(),
+            zip64_end_offset: None,
+        };
+
+        if let Some(locator) = position.checked_sub(ZIP64_LOCATOR_SIZE)
+            && read_u32(tail, locator) == Some(ZIP64_LOCATOR_SIGNATURE)
+        {
+            end.zip64_end_offset = read_u64(tail, locator + 8);
+        }
+
+        if end.zip64_end_offset.is_none() && offset + (position as u64) < end.directory_offset {
+            return Err(anyhow!(
+                "Invalid zip archive, central directory out of bounds"
+            ));
+        }
+
+        Ok(end)
+    }
+
+    fn parse_zip64(record: &[u8]) -> Result<Self> {
+        if read_u32(record, 0) != Some(ZIP64_END_RECORD_SIGNATURE) {
+            return Err(anyhow!("Invalid zip archive, ZIP64 end record not found"));
+        }
+
+        let field = |at| read_u64(record, at).ok_or_else(|| anyhow!("Truncated ZIP64 end record"));
+
+        Ok(Self {
+            entries: field(32)?,
+            directory_size: field(40)?,
+            directory_offset: field(48)?,
+            zip64_end_offset: None,
+        })
+    }
+}
+
+fn parse_central_directory(directory: &[u8], entries: u64) -> Result<Vec<RemoteZipEntry>> {
+    let truncated = || anyhow!("Truncated central directory");
+    let mut parsed = Vec::new();
+    let mut position = 0;
+
+    for _ in 0..entries {
+        let header = directory.get(position..).ok_or_else(truncated)?;
+
+        if read_u32(header, 0) != Some(CENTRAL_HEADER_SIGNATURE) {
+            return Err(anyhow!("Invalid central directory entry at {position}"));
+        }
+
+        let u16_at = |at| read_u16(header, at).map(usize::from).ok_or_else(truncated);
+        let u32_at = |at| read_u32(header, at).ok_or_else(truncated);
+        let name_len = u16_at(28)?;
+        let extra_len = u16_at(30)?;
+        let comment_len = u16_at(32)?;
+        let name_end = CENTRAL_HEADER_SIZE + name_len;
+        let name = header
+            .get(CENTRAL_HEADER_SIZE..name_end)
+            .ok_or_else(truncated)?;
+        let extra = header
+            .get(name_end..name_end + extra_len)
+            .ok_or_else(truncated)?;
+        let mut compressed_size = u64::from(u32_at(20)?);
+        let mut size = u64::from(u32_at(24)?);
+
+        // ZIP64 sizes replace the saturated ones, uncompressed size first
+        if let Some(mut zip64) = extra_field(extra, ZIP64_EXTRA_FIELD_ID) {
+            for value in [&mut size, &mut compressed_size] {
+                if *value == u64::from(u32::MAX)
+                    && let Some(actual) = read_u64(zip64, 0)
+                {
+                    *value = actual;
+                    zip64 = &zip64[8..];
+                }
+            }
+        }
+
+        parsed.push(RemoteZipEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            compressed_size,
+            size,
+            crc32: u32_at(16)?,
+        });
+        position += name_end + extra_len + comment_len;
+    }
+
+    Ok(parsed)
+}
+
+/// Data of the extra field `id` among `extra`
+fn extra_field(mut extra: &[u8], id: u16) -> Option<&[u8]> {
+    while let (Some(field_id), Some(len)) = (read_u16(extra, 0), read_u16(extra, 2)) {
+        let data = extra.get(4..4 + usize::from(len))?;
+
+        if field_id == id {
+            return Some(data);
+        }
+
+        extra = &extra[4 + usize::from(len)..];
+    }
+
+    None
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    fn archive(comment: &str) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        writer
+            .add_directory("completions/", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .start_file("completions/_fluvio", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"#compdef fluvio").unwrap();
+        writer
+            .start_file("fluvio", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&[7u8; 4096]).unwrap();
+        writer.set_comment(comment);
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn index(bytes: &[u8], offset: usize) -> Vec<RemoteZipEntry> {
+        let end = EndRecord::find(&bytes[offset..], offset as u64).unwrap();
+        let start = end.directory_offset as usize;
+        let directory = &bytes[start..start + end.directory_size as usize];
+
+        parse_central_directory(directory, end.entries).unwrap()
+    }
+
+    #[test]
+    fn lists_central_directory_entries() {
+        let bytes = archive("built by CI");
+        let mut zip = zip::ZipArchive::new(Cursor::new(&bytes)).unwrap();
+        let entries = index(&bytes, 0);
+
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_dir());
+        assert_eq!(entries[2].name, "fluvio");
+        assert_eq!(entries[2].size, 4096);
+
+        for entry in &entries {
+            let file = zip.by_name(&entry.name).unwrap();
+
+            assert_eq!(entry.crc32, file.crc32());
+            assert_eq!(entry.compressed_size, file.compressed_size());
+        }
+
+        // The record is found past the archive comment
+        let bytes = archive(&"x".repeat(1000));
+
+        assert_eq!(index(&bytes, 100), entries);
+        assert!(EndRecord::find(b"not a zip archive", 0).is_err());
+    }
+
+    #[test]
+    fn parses_content_ranges() {
+        assert_eq!(parse_content_range("bytes 10-19/100"), Some((10, 100)));
+        assert_eq!(parse_content_range("bytes */100"), None);
+    }
+}
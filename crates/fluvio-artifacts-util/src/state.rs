@@ -0,0 +1,271 @@
+//! State Files
+//!
+//! Settings, manifests and caches are written atomically: the contents go
+//! to a temporary file in the same directory, which is synced and renamed
+//! over the previous file, so a crash leaves either the old or the new
+//! contents in place.
+//!
+//! State files end with a footer line holding the SHA-256 of the contents,
+//! e.g. `# checksum: sha256:ab12...`, which detects files damaged in other
+//! ways, such as a full disk or a copy cut short. Files which fail the check
+//! or cannot be parsed are moved aside with a `.corrupt-<timestamp>` suffix
+//! so they can be inspected, and the state is rebuilt from scratch. Files
+//! meant to be edited by hand, such as `settings.toml`, are only written
+//! with [`write_atomic`], as any edit would fail the check.
+
+use std::fs::{File, create_dir_all, read_to_string, rename};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+
+/// Start of the footer line holding the checksum of a state file
+pub const CHECKSUM_FOOTER_PREFIX: &str = "# checksum: sha256:";
+
+/// Suffix of state files moved aside by [`quarantine`], followed by the
+/// time they were moved at
+pub const QUARANTINE_SUFFIX: &str = ".corrupt-";
+
+/// Replaces the contents of `path` with `contents`, leaving either the
+/// previous or the new contents if the process is interrupted
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> Result<()> {
+    write_staged(path.as_ref(), contents, false)
+}
+
+/// Writes `contents` to `path` atomically, followed by the checksum footer
+pub fn write_state(path: impl AsRef<Path>, contents: &str) -> Result<()> {
+    write_atomic(path, with_checksum_footer(contents).as_bytes())
+}
+
+/// Like [`write_state`], for files holding credentials: on Unix the file is
+/// only readable and writable by its owner, whatever the umask
+pub fn write_private_state(path: impl AsRef<Path>, contents: &str) -> Result<()> {
+    write_staged(
+        path.as_ref(),
+        with_checksum_footer(contents).as_bytes(),
+        true,
+    )
+}
+
+fn write_staged(path: &Path, contents: &[u8], private: bool) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    create_dir_all(parent)?;
+
+    let mut staged = NamedTempFile::new_in(parent)?;
+
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+
+        staged
+            .as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    staged.write_all(contents)?;
+    staged.as_file().sync_all()?;
+    staged.persist(path)?;
+
+    // Persists the rename, not supported by every platform
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Reads the state file at `path` and parses it with `parse`.
+///
+/// Returns `None` if the file does not exist, or if it failed the checksum
+/// or could not be parsed, in which case it is moved aside with
+/// [`quarantine`]. Files without a footer, written before checksums were
+/// introduced, are parsed as they are.
+pub fn load_state<T>(
+    path: impl AsRef<Path>,
+    parse: impl FnOnce(&str) -> Result<T>,
+) -> Result<Option<T>> {
+    let path = path.as_ref();
+    let raw = match read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) if err.kind() == ErrorKind::InvalidData => {
+            quarantine_corrupt(path, &anyhow!("file is not valid UTF-8"))?;
+            return Ok(None);
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    match strip_checksum_footer(&raw).and_then(parse) {
+        Ok(state) => Ok(Some(state)),
+        Err(err) => {
+            quarantine_corrupt(path, &err)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Moves the file at `path` aside, next to where it was, and returns its
+/// new path
+pub fn quarantine(path: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a file", path.display()))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let mut quarantined = file_name.to_os_string();
+
+    quarantined.push(format!("{QUARANTINE_SUFFIX}{timestamp}"));
+
+    let quarantined = path.with_file_name(quarantined);
+
+    rename(path, &quarantined)?;
+
+    Ok(quarantined)
+}
+
+fn quarantine_corrupt(path: &Path, err: &anyhow::Error) -> Result<()> {
+    let quarantined = quarantine(path)?;
+
+    tracing::warn!(
+        path = %path.display(),
+        quarantined = %quarantined.display(),
+        %err,
+        "Corrupt state file moved aside"
+    );
+
+    Ok(())
+}
+
+/// `contents` ending with a newline, followed by the checksum footer
+fn with_checksum_footer(contents: &str) -> String {
+    let mut body = contents.to_string();
+
+    if !body.ends_with('\n') {
+        body.push('\n');
+    }
+
+    let checksum = hex::encode(Sha256::digest(body.as_bytes()));
+
+    format!("{body}{CHECKSUM_FOOTER_PREFIX}{checksum}\n")
+}
+
+/// Contents of a state file without its footer, failing if the checksum
+/// does not match. Contents without a footer are returned as they are.
+pub fn strip_checksum_footer(raw: &str) -> Result<&str> {
+    let footer_start = match raw.rfind(CHECKSUM_FOOTER_PREFIX) {
+        Some(0) => 0,
+        Some(idx) if raw[..idx].ends_with('\n') => idx,
+        _ => return Ok(raw),
+    };
+    let (body, footer) = raw.split_at(footer_start);
+    let expected = footer[CHECKSUM_FOOTER_PREFIX.len()..].trim_end();
+    let actual = hex::encode(Sha256::digest(body.as_bytes()));
+
+    if expected != actual {
+        return Err(anyhow!(
+            "checksum mismatch, expected {expected} but contents hash to {actual}"
+        ));
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read_dir, write};
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn parse_number(contents: &str) -> Result<u32> {
+        Ok(contents.trim().parse()?)
+    }
+
+    #[test]
+    fn writes_and_loads_state() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("state.txt");
+
+        write_state(&path, "42").unwrap();
+
+        let raw = read_to_string(&path).unwrap();
+
+        assert!(raw.starts_with("42\n# checksum: sha256:"));
+        assert_eq!(load_state(&path, parse_number).unwrap(), Some(42));
+        assert_eq!(
+            load_state(dir.path().join("missing"), parse_number).unwrap(),
+            None
+        );
+        // Only the state file is left in the directory
+        assert_eq!(read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn loads_state_without_footer() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.txt");
+
+        write(&path, "7\n").unwrap();
+
+        assert_eq!(load_state(&path, parse_number).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn quarantines_corrupt_state() {
+        let dir = TempDir::new().unwrap();
+        let tampered = dir.path().join("tampered.txt");
+        let unparsable = dir.path().join("unparsable.txt");
+
+        write_state(&tampered, "42").unwrap();
+        write(
+            &tampered,
+            read_to_string(&tampered).unwrap().replace("42\n", "43\n"),
+        )
+        .unwrap();
+        write(&unparsable, "not a number").unwrap();
+
+        assert_eq!(load_state(&tampered, parse_number).unwrap(), None);
+        assert_eq!(load_state(&unparsable, parse_number).unwrap(), None);
+        assert!(!tampered.exists());
+        assert!(!unparsable.exists());
+
+        let quarantined: Vec<String> = read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(quarantined.len(), 2);
+        assert!(
+            quarantined
+                .iter()
+                .all(|name| name.contains(QUARANTINE_SUFFIX))
+        );
+    }
+
+    #[test]
+    fn strips_checksum_footer() {
+        let raw = with_checksum_footer("a = 1\n");
+
+        assert_eq!(strip_checksum_footer(&raw).unwrap(), "a = 1\n");
+        assert!(strip_checksum_footer(&raw.replace("1", "2")).is_err());
+        assert!(strip_checksum_footer(&raw[..raw.len() - 10]).is_err());
+        // A footer prefix which is not on its own line is contents
+        assert_eq!(
+            strip_checksum_footer("note = \"# checksum: sha256:\"").unwrap(),
+            "note = \"# checksum: sha256:\""
+        );
+    }
+}
@@ -0,0 +1,539 @@
+//! Content Store
+//!
+//! Files downloaded by the Hub client and by FVM are kept once in a content
+//! addressed store, e.g. `~/.fluvio/store/sha256/ab/ab12...`, and hard
+//! linked into the directories where they are used. Objects are hashed
+//! again before being linked, so an object modified through one of its
+//! links is discarded instead of being handed out.
+//!
+//! The store manifest, `manifest.json` in the store directory, records the
+//! URL objects were downloaded from and the size and modification time of
+//! objects when they were last verified. [`ContentStore::verify`] only hashes
+//! objects which changed since, and corrupt objects with a known source can
+//! be downloaded again with [`ContentStore::refetch`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{copy, create_dir_all, hard_link, read_dir, remove_file, rename};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use fluvio_types::defaults::CLI_CONFIG_PATH;
+
+use crate::htclient;
+use crate::sha256_digest;
+use crate::state::{load_state, write_state};
+
+/// Environment variable with the path to the content store, the store is
+/// disabled if it is set to `off`
+pub const CONTENT_STORE_ENV: &str = "FLUVIO_CONTENT_STORE";
+
+pub const CONTENT_STORE_DIR: &str = "store";
+
+/// File in the store directory recording what is known about its objects
+pub const STORE_MANIFEST_FILENAME: &str = "manifest.json";
+
+const DIGEST_ALGORITHM: &str = "sha256";
+
+/// How a store object was placed in a consumer directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Materialized {
+    /// The destination shares the store object
+    Hardlink,
+    /// The destination is a copy, used when hard links are not supported,
+    /// e.g. when the store is on a different filesystem
+    Copy,
+}
+
+/// Size and modification time of an object when its digest was verified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectStamp {
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl ObjectStamp {
+    fn read(path: &Path) -> Result<Self> {
+        let metadata = path.metadata()?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        Ok(Self {
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreEntry {
+    /// URL the object was downloaded from, if it can be downloaded again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The object when its digest was last verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<ObjectStamp>,
+}
+
+/// Entries of the store manifest by digest
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreManifest {
+    #[serde(default)]
+    pub entries: BTreeMap<String, StoreEntry>,
+}
+
+/// Object whose contents don't match its digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptObject {
+    pub digest: String,
+    pub path: PathBuf,
+    /// URL the object can be downloaded again from
+    pub source: Option<String>,
+}
+
+/// Outcome of [`ContentStore::verify`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreVerification {
+    /// Objects hashed
+    pub hashed: usize,
+    /// Objects skipped because they didn't change since they were verified
+    pub unchanged: usize,
+    pub corrupt: Vec<CorruptObject>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    root: PathBuf,
+}
+
+impl ContentStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Store shared by the Hub client and FVM, `~/.fluvio/store` unless
+    /// `FLUVIO_CONTENT_STORE` is set. Returns `None` if the store is disabled.
+    pub fn open_default() -> Option<Self> {
+        match std::env::var(CONTENT_STORE_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("off") => None,
+            Ok(value) if !value.is_empty() => Some(Self::new(value)),
+            _ => {
+                let home = dirs::home_dir()?;
+
+                Some(Self::new(
+                    home.join(CLI_CONFIG_PATH).join(CONTENT_STORE_DIR),
+                ))
+            }
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the object for `digest`, which may not exist
+    pub fn object_path(&self, digest: &str) -> Result<PathBuf> {
+        let digest = normalize_digest(digest)?;
+
+        Ok(self
+            .root
+            .join(DIGEST_ALGORITHM)
+            .join(&digest[..2])
+            .join(digest))
+    }
+
+    /// Returns the object for `digest` if it exists and its contents still
+    /// match the digest. Corrupted objects are removed.
+    pub fn get(&self, digest: &str) -> Result<Option<PathBuf>> {
+        let path = self.object_path(digest)?;
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let actual = sha256_digest(&path)?;
+
+        if actual != normalize_digest(digest)? {
+            tracing::warn!(?path, %actual, "Removing corrupted content store object");
+            remove_file(&path)?;
+            return Ok(None);
+        }
+
+        Ok(Some(path))
+    }
+
+    /// Adds the file at `path` to the store and returns its digest. The file
+    /// itself is left in place.
+    pub fn insert(&self, path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref();
+        let digest = sha256_digest(path)?;
+
+        if self.get(&digest)?.is_none() {
+            let object = self.object_path(&digest)?;
+            let staged = self.stage()?;
+
+            copy(path, staged.path())?;
+            persist(staged, &object)?;
+        }
+
+        Ok(digest)
+    }
+
+    /// Adds `bytes` to the store and returns their digest
+    pub fn insert_bytes(&self, bytes: &[u8]) -> Result<String> {
+        let digest = crate::sha256_digest_reader(bytes)?;
+
+        if self.get(&digest)?.is_none() {
+            let object = self.object_path(&digest)?;
+            let mut staged = self.stage()?;
+
+            staged.write_all(bytes)?;
+            staged.flush()?;
+            persist(staged, &object)?;
+        }
+
+        Ok(digest)
+    }
+
+    /// Places the object for `digest` at `dst`, replacing any existing file.
+    /// Returns `None` if the store doesn't have a valid object for `digest`.
+    pub fn materialize(&self, digest: &str, dst: impl AsRef<Path>) -> Result<Option<Materialized>> {
+        let Some(object) = self.get(digest)? else {
+            return Ok(None);
+        };
+        let dst = dst.as_ref();
+
+        if dst.exists() {
+            remove_file(dst)?;
+        }
+
+        if let Err(err) = hard_link(&object, dst) {
+            tracing::debug!(%err, ?dst, "Hard link not available, copying store object");
+            copy(&object, dst)?;
+            return Ok(Some(Materialized::Copy));
+        }
+
+        Ok(Some(Materialized::Hardlink))
+    }
+
+    /// Adds the file at `path` to the store and replaces it with a link to
+    /// the stored object, so identical files share storage. The file is kept
+    /// as is when it can't be linked.
+    pub fn dedup(&self, path: impl AsRef<Path>) -> Result<Materialized> {
+        let path = path.as_ref();
+        let digest = self.insert(path)?;
+        let object = self
+            .get(&digest)?
+            .ok_or_else(|| anyhow!("content store object for {digest} is missing"))?;
+        let link = path.with_extension("store-link");
+
+        if link.exists() {
+            remove_file(&link)?;
+        }
+
+        if let Err(err) = hard_link(&object, &link) {
+            tracing::debug!(%err, ?path, "Hard link not available, keeping file");
+            return Ok(Materialized::Copy);
+        }
+
+        rename(&link, path)?;
+        Ok(Materialized::Hardlink)
+    }
+
+    pub fn manifest(&self) -> Result<StoreManifest> {
+        Ok(
+            load_state(self.root.join(STORE_MANIFEST_FILENAME), |contents| {
+                Ok(serde_json::from_str(contents)?)
+            })?
+            .unwrap_or_default(),
+        )
+    }
+
+    fn save_manifest(&self, manifest: &StoreManifest) -> Result<()> {
+        write_state(
+            self.root.join(STORE_MANIFEST_FILENAME),
+            &serde_json::to_string_pretty(manifest)?,
+        )
+    }
+
+    /// Records `source` as the URL the object for `digest` was downloaded
+    /// from. Ignored if the store has no object for `digest`, e.g. for
+    /// archives whose extracted contents were stored instead.
+    pub fn record_source(&self, digest: &str, source: &str) -> Result<()> {
+        let digest = normalize_digest(digest)?;
+
+        if !self.object_path(&digest)?.is_file() {
+            return Ok(());
+        }
+
+        let mut manifest = self.manifest()?;
+
+        manifest.entries.entry(digest).or_default().source = Some(source.to_string());
+        self.save_manifest(&manifest)
+    }
+
+    /// Digests and paths of the objects in the store
+    pub fn objects(&self) -> Result<Vec<(String, PathBuf)>> {
+        let algorithm_dir = self.root.join(DIGEST_ALGORITHM);
+        let mut objects = Vec::new();
+
+        if !algorithm_dir.is_dir() {
+            return Ok(objects);
+        }
+
+        for prefix in read_dir(algorithm_dir)? {
+            let prefix = prefix?.path();
+
+            if !prefix.is_dir() {
+                continue;
+            }
+
+            for object in read_dir(prefix)? {
+                let path = object?.path();
+                let digest = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| normalize_digest(name).ok());
+
+                if let Some(digest) = digest
+                    && path.is_file()
+                {
+                    objects.push((digest, path));
+                }
+            }
+        }
+
+        objects.sort();
+        Ok(objects)
+    }
+
+    /// Hashes the objects of the store against their digests. Objects with
+    /// the same size and modification time as when they were last verified
+    /// are skipped unless `full` is set. Corrupt objects are reported, not
+    /// removed, see [`ContentStore::evict`].
+    pub fn verify(&self, full: bool) -> Result<StoreVerification> {
+        let mut manifest = self.manifest()?;
+        let mut verification = StoreVerification::default();
+        let mut present = HashSet::new();
+
+        for (digest, path) in self.objects()? {
+            let stamp = ObjectStamp::read(&path)?;
+            let entry = manifest.entries.entry(digest.clone()).or_default();
+
+            present.insert(digest.clone());
+
+            if !full && entry.verified == Some(stamp) {
+                verification.unchanged += 1;
+                continue;
+            }
+
+            verification.hashed += 1;
+
+            if sha256_digest(&path)? == digest {
+                entry.verified = Some(stamp);
+            } else {
+                entry.verified = None;
+                verification.corrupt.push(CorruptObject {
+                    digest,
+                    path,
+                    source: entry.source.clone(),
+                });
+            }
+        }
+
+        manifest
+            .entries
+            .retain(|digest, _| present.contains(digest));
+
+        if self.root.is_dir() {
+            self.save_manifest(&manifest)?;
+        }
+
+        Ok(verification)
+    }
+
+    /// Removes the object for `digest`, the files linked to it are kept
+    pub fn evict(&self, digest: &str) -> Result<()> {
+        let path = self.object_path(digest)?;
+
+        if path.is_file() {
+            remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the object for `digest` again from `source`, failing if
+    /// the downloaded contents don't match the digest
+    pub async fn refetch(&self, digest: &str, source: &str) -> Result<PathBuf> {
+        let digest = normalize_digest(digest)?;
+        let response = htclient::download(source).await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Server responded with Status Code {} for url {source}",
+                response.status()
+            ));
+        }
+
+        let actual = crate::sha256_digest_reader(response.body().as_slice())?;
+
+        if actual != digest {
+            return Err(anyhow!(
+                "{source} served contents with digest {actual}, expected {digest}"
+            ));
+        }
+
+        self.insert_bytes(response.body())?;
+        self.record_source(&digest, source)?;
+
+        self.get(&digest)?
+            .ok_or_else(|| anyhow!("content store object for {digest} is missing"))
+    }
+
+    /// Temporary file in the store, on the same filesystem as the objects so
+    /// it can be moved into place atomically
+    fn stage(&self) -> Result<NamedTempFile> {
+        create_dir_all(&self.root)?;
+
+        Ok(NamedTempFile::new_in(&self.root)?)
+    }
+}
+
+fn persist(staged: NamedTempFile, object: &Path) -> Result<()> {
+    if let Some(parent) = object.parent() {
+        create_dir_all(parent)?;
+    }
+
+    staged.as_file().sync_all()?;
+    staged.persist(object)?;
+
+    Ok(())
+}
+
+/// Strips the optional `sha256:` prefix and validates a hex encoded digest
+fn normalize_digest(digest: &str) -> Result<String> {
+    let digest = digest.trim();
+    let digest = digest
+        .strip_prefix("sha256:")
+        .unwrap_or(digest)
+        .to_ascii_lowercase();
+
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("invalid sha256 digest: {digest}"));
+    }
+
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read, write};
+
+    use tempfile::TempDir;
+
+    use crate::sha256_digest_reader;
+
+    use super::*;
+
+    #[test]
+    fn dedups_identical_files() {
+        let tmp = TempDir::new().unwrap();
+        let store = ContentStore::new(tmp.path().join("store"));
+        let hub_pkg = tmp.path().join("hub-binary");
+        let fvm_bin = tmp.path().join("fvm-binary");
+
+        write(&hub_pkg, b"binary contents").unwrap();
+        write(&fvm_bin, b"binary contents").unwrap();
+
+        assert_eq!(store.dedup(&hub_pkg).unwrap(), Materialized::Hardlink);
+        assert_eq!(store.dedup(&fvm_bin).unwrap(), Materialized::Hardlink);
+
+        let digest = sha256_digest(&fvm_bin).unwrap();
+        let object = store.get(&format!("sha256:{digest}")).unwrap().unwrap();
+
+        assert_eq!(read(&object).unwrap(), b"binary contents");
+        assert_eq!(read(&hub_pkg).unwrap(), b"binary contents");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            assert_eq!(object.metadata().unwrap().nlink(), 3);
+        }
+    }
+
+    #[test]
+    fn discards_corrupted_objects() {
+        let tmp = TempDir::new().unwrap();
+        let store = ContentStore::new(tmp.path());
+        let digest = store.insert_bytes(b"original").unwrap();
+        let object = store.object_path(&digest).unwrap();
+
+        write(&object, b"tampered").unwrap();
+
+        assert_eq!(store.get(&digest).unwrap(), None);
+        assert!(!object.exists());
+        assert_eq!(
+            store.materialize(&digest, tmp.path().join("dst")).unwrap(),
+            None
+        );
+        assert!(store.object_path("not-a-digest").is_err());
+    }
+
+    #[test]
+    fn verifies_changed_objects() {
+        let tmp = TempDir::new().unwrap();
+        let store = ContentStore::new(tmp.path());
+        let good = store.insert_bytes(b"good").unwrap();
+        let bad = store.insert_bytes(b"bad").unwrap();
+
+        store
+            .record_source(&bad, "https://example.com/bad")
+            .unwrap();
+        store
+            .record_source(&sha256_digest_reader(&b"missing"[..]).unwrap(), "ignored")
+            .unwrap();
+
+        let verification = store.verify(false).unwrap();
+
+        assert_eq!(verification.hashed, 2);
+        assert!(verification.corrupt.is_empty());
+        assert_eq!(store.manifest().unwrap().entries.len(), 2);
+
+        let verification = store.verify(false).unwrap();
+
+        assert_eq!((verification.hashed, verification.unchanged), (0, 2));
+
+        write(store.object_path(&bad).unwrap(), b"tampered").unwrap();
+
+        let verification = store.verify(false).unwrap();
+
+        assert_eq!(verification.hashed, 1);
+        assert_eq!(
+            verification.corrupt,
+            vec![CorruptObject {
+                digest: bad.clone(),
+                path: store.object_path(&bad).unwrap(),
+                source: Some("https://example.com/bad".to_string()),
+            }]
+        );
+        assert_eq!(store.verify(true).unwrap().hashed, 2);
+
+        store.evict(&bad).unwrap();
+
+        assert_eq!(
+            store.objects().unwrap(),
+            vec![(good.clone(), store.object_path(&good).unwrap())]
+        );
+        assert!(store.verify(false).unwrap().corrupt.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+//! Publish Date Proofs
+//!
+//! The `inf::meta::published_at` tag is set by the publisher, so on its own
+//! it only tells when the publisher claims a package version was published.
+//! A [`PublishedAtProof`] stored in the package meta backs the claim with
+//! independent evidence the publish statement, see
+//! [`PackageMeta::published_at_statement`], existed at a point in time: an
+//! RFC 9162 inclusion proof of the statement in a transparency log, along
+//! with the log checkpoint signed by the log operator.
+//!
+//! Consumers verify proofs against the keys of the logs they trust,
+//! configured as a [`TimestampTrust`], to audit when a package version
+//! existed.
+
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Duration, Utc};
+use rustls::pki_types::alg_id;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use fluvio_hub_protocol::{HubError, LogInclusionProof, PackageMeta, PublishedAtProof, Result};
+
+use crate::PackageMetaExt;
+
+/// Tolerated difference between the clocks of the publisher and the log
+pub const MAX_CLOCK_SKEW: Duration = Duration::minutes(5);
+
+/// Keys of the transparency logs trusted to prove publish dates
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TimestampTrust {
+    /// Base64 encoded Ed25519 public key of each trusted log, keyed by log
+    /// name
+    #[serde(default)]
+    pub logs: BTreeMap<String, String>,
+}
+
+/// RFC 9162 leaf hash of the publish statement in a transparency log
+pub fn log_leaf_hash(statement: &str) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0x00])
+        .chain_update(statement.as_bytes())
+        .finalize()
+        .into()
+}
+
+/// Verifies the publish date proof of `meta` with the keys of `trust`,
+/// returning when the publish statement was proven to exist.
+///
+/// The publish date is expected no later than the proven time, give or take
+/// [`MAX_CLOCK_SKEW`], a later date was claimed before it happened.
+pub fn verify_published_at(meta: &PackageMeta, trust: &TimestampTrust) -> Result<DateTime<Utc>> {
+    let proof = meta
+        .published_at_proof
+        .as_ref()
+        .ok_or_else(|| invalid("package has no publish date proof"))?;
+    let statement = meta
+        .published_at_statement()
+        .ok_or_else(|| invalid("package has no publish date"))?;
+    let published_at = meta.published_at()?;
+    let PublishedAtProof::TransparencyLog(proof) = proof;
+    let key = trust
+        .logs
+        .get(&proof.log)
+        .ok_or_else(|| invalid(format!("transparency log {} is not trusted", proof.log)))?;
+    let proven_at = verify_log_inclusion(proof, &statement, key)?;
+
+    if published_at > proven_at + MAX_CLOCK_SKEW {
+        return Err(invalid(format!(
+            "publish date {published_at} is later than the proven time {proven_at}"
+        )));
+    }
+
+    Ok(proven_at)
+}
+
+/// Verifies `statement` is included in the log of `proof`, and the log
+/// checkpoint is signed with `key`, returning when the log integrated it
+pub fn verify_log_inclusion(
+    proof: &LogInclusionProof,
+    statement: &str,
+    key: &str,
+) -> Result<DateTime<Utc>> {
+    let key = decode(key, "transparency log key")?;
+    let signature = decode(&proof.signature, "checkpoint signature")?;
+    let algorithm = rustls::crypto::aws_lc_rs::default_provider()
+        .signature_verification_algorithms
+        .all
+        .iter()
+        .find(|algorithm| algorithm.public_key_alg_id() == alg_id::ED25519)
+        .copied()
+        .ok_or_else(|| invalid("Ed25519 signatures are not supported"))?;
+
+    algorithm
+        .verify_signature(&key, proof.checkpoint().as_bytes(), &signature)
+        .map_err(|_| invalid(format!("checkpoint of {} is not validly signed", proof.log)))?;
+
+    let root_hash = decode_hash(&proof.root_hash)?;
+    let hashes = proof
+        .hashes
+        .iter()
+        .map(|hash| decode_hash(hash))
+        .collect::<Result<Vec<_>>>()?;
+    let root = inclusion_root(
+        proof.leaf_index,
+        proof.tree_size,
+        log_leaf_hash(statement),
+        &hashes,
+    );
+
+    if root != Some(root_hash) {
+        return Err(invalid(format!(
+            "publish statement is not included in {} at the signed checkpoint",
+            proof.log
+        )));
+    }
+
+    DateTime::parse_from_rfc3339(&proof.integrated_at)
+        .map(|integrated_at| integrated_at.to_utc())
+        .map_err(|err| invalid(format!("invalid log integration time: {err}")))
+}
+
+/// Root hash computed from the audit path `hashes` of the leaf at
+/// `leaf_index` in a tree of `tree_size` leaves, RFC 9162 section 2.1.3.2.
+/// `None` if the path does not fit the tree.
+fn inclusion_root(
+    leaf_index: u64,
+    tree_size: u64,
+    leaf_hash: [u8; 32],
+    hashes: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if leaf_index >= tree_size {
+        return None;
+    }
+
+    let mut fn_ = leaf_index;
+    let mut sn = tree_size - 1;
+    let mut root = leaf_hash;
+
+    for hash in hashes {
+        if sn == 0 {
+            return None;
+        }
+
+        if fn_ & 1 == 1 || fn_ == sn {
+            root = node_hash(hash, &root);
+
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            root = node_hash(&root, hash);
+        }
+
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    (sn == 0).then_some(root)
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0x01])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+fn decode(value: &str, what: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value.trim())
+        .map_err(|err| invalid(format!("invalid {what}: {err}")))
+}
+
+fn decode_hash(hash: &str) -> Result<[u8; 32]> {
+    hex::decode(hash)
+        .ok()
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or_else(|| invalid(format!("invalid SHA-256 hash {hash}")))
+}
+
+fn invalid(message: impl Into<String>) -> HubError {
+    HubError::PackageVerify(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::SignatureScheme;
+    use rustls::pki_types::PrivatePkcs8KeyDer;
+
+    use super::*;
+
+    /// PKCS#8 encoded Ed25519 key of the test transparency log
+    const LOG_PRIVATE_KEY: &str =
+        "MC4CAQAwBQYDK2VwBCIEIJjOoFaiWqNvRSB20/VUNpfySIxkVPRC+35et0Y59KqF";
+    const LOG_PUBLIC_KEY: &str = "EUcewbZ5SwSDvE2eXEJpSr8IaNAPcJqZ+IIrKeAwujQ=";
+    const LOG: &str = "hub.example.com/log";
+    fn sign(key: &str, message: &[u8]) -> Vec<u8> {
+        let der = PrivatePkcs8KeyDer::from(STANDARD.decode(key).unwrap());
+
+        rustls::crypto::aws_lc_rs::sign::any_eddsa_type(&der)
+            .unwrap()
+            .choose_scheme(&[SignatureScheme::ED25519])
+            .unwrap()
+            .sign(message)
+            .unwrap()
+    }
+
+    /// Proof of `statement` as the last leaf of a log of 3 leaves
+    fn log_proof(statement: &str, integrated_at: &str) -> LogInclusionProof {
+        let sibling = node_hash(&log_leaf_hash("a"), &log_leaf_hash("b"));
+        let root = node_hash(&sibling, &log_leaf_hash(statement));
+        let mut proof = LogInclusionProof {
+            log: LOG.into(),
+            leaf_index: 2,
+            tree_size: 3,
+            root_hash: hex::encode(root),
+            hashes: vec![hex::encode(sibling)],
+            integrated_at: integrated_at.into(),
+            signature: String::new(),
+        };
+
+        proof.signature = STANDARD.encode(sign(LOG_PRIVATE_KEY, proof.checkpoint().as_bytes()));
+        proof
+    }
+
+    fn package_meta(proof: PublishedAtProof) -> PackageMeta {
+        let mut meta = PackageMeta {
+            group: "infinyon".into(),
+            name: "example".into(),
+            version: "0.0.1".into(),
+            published_at_proof: Some(proof),
+            ..PackageMeta::default()
+        };
+
+        meta.set_published_at("2024-05-01T10:00:00Z".parse().unwrap());
+        meta
+    }
+
+    fn trust() -> TimestampTrust {
+        TimestampTrust {
+            logs: BTreeMap::from([(LOG.into(), LOG_PUBLIC_KEY.into())]),
+        }
+    }
+
+    #[test]
+    fn verifies_transparency_log_inclusion() {
+        let statement = "infinyon/example@0.0.1 2024-05-01T10:00:00Z";
+        let proof = log_proof(statement, "2024-05-01T10:01:00Z");
+        let meta = package_meta(PublishedAtProof::TransparencyLog(proof.clone()));
+
+        assert_eq!(
+            verify_published_at(&meta, &trust()).unwrap(),
+            "2024-05-01T10:01:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        let mut tampered = proof.clone();
+
+        tampered.integrated_at = "2024-05-01T09:00:00Z".into();
+        assert!(verify_log_inclusion(&tampered, statement, LOG_PUBLIC_KEY).is_err());
+        assert!(verify_log_inclusion(&proof, "infinyon/example@0.0.2", LOG_PUBLIC_KEY).is_err());
+
+        let mut untrusted = trust();
+
+        untrusted.logs.clear();
+        assert!(verify_published_at(&meta, &untrusted).is_err());
+        assert_eq!(
+            inclusion_root(0, 1, log_leaf_hash(statement), &[]),
+            Some(log_leaf_hash(statement))
+        );
+        assert_eq!(inclusion_root(3, 3, log_leaf_hash(statement), &[]), None);
+    }
+}
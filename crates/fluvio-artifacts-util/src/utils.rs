@@ -1,32 +1,174 @@
+use std::fmt::{self, Display};
 use std::fs::File;
-use std::path::PathBuf;
-use std::io::copy;
+use std::path::Path;
+use std::str::FromStr;
+use std::io::{Read, Write, copy};
 
 use sha2::{Digest, Sha256};
 
-use fluvio_hub_protocol::{Result};
-use fluvio_hub_protocol::constants::HUB_PACKAGE_EXT;
+use fluvio_hub_protocol::{HubError, HubLayout, PackageMeta, Result};
 
 /// non validating function to make canonical filenames from
-/// org pkg version triples
+/// org pkg version triples, e.g. `infinyon-json-sql-0.0.2.ipkg`
+///
+/// org and pkg can't be told apart in the resulting name when the org
+/// contains dashes, use [`PackageFileName::parse_in_group`] to parse names
+/// of a known org
 pub fn make_filename(org: &str, pkg: &str, ver: &str) -> String {
+    let layout = HubLayout::CURRENT;
+
     if org.is_empty() {
-        format!("{pkg}-{ver}.{HUB_PACKAGE_EXT}")
+        layout.package_file_name(pkg, ver)
     } else {
-        format!("{org}-{pkg}-{ver}.{HUB_PACKAGE_EXT}")
+        layout.package_file_name(&format!("{org}-{pkg}"), ver)
+    }
+}
+
+/// Package file name which can be parsed back into its parts.
+///
+/// Formatted by [`make_filename`] as `{group}-{name}-{version}.ipkg`, or
+/// `{name}-{version}.ipkg` without a group. Packages of any known
+/// [`HubLayout`] are parsed, and names are formatted with the current layout.
+/// Dots are not allowed in groups and package names and versions are SemVer,
+/// so the version is found even if the name contains dashes. The group is
+/// only split from the name when it is known, see
+/// [`PackageFileName::parse_in_group`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PackageFileName {
+    pub group: Option<String>,
+    pub name: String,
+    pub version: semver::Version,
+}
+
+impl PackageFileName {
+    pub fn new(group: Option<&str>, name: &str, version: semver::Version) -> Self {
+        Self {
+            group: group
+                .filter(|group| !group.is_empty())
+                .map(|group| group.to_string()),
+            name: name.to_string(),
+            version,
+        }
+    }
+
+    pub fn from_meta(meta: &PackageMeta) -> Result<Self> {
+        let version = semver::Version::parse(&meta.version)
+            .map_err(|err| HubError::SemVerError(err.to_string()))?;
+
+        Ok(Self::new(Some(&meta.group), &meta.name, version))
+    }
+
+    /// Parses a file name without splitting the group from the name, the
+    /// name of `infinyon-json-sql-0.0.2.ipkg` is `infinyon-json-sql`
+    pub fn parse(file_name: &str) -> Result<Self> {
+        let invalid = || HubError::InvalidPackageName(file_name.to_string());
+        let name_version = HubLayout::KNOWN
+            .iter()
+            .find_map(|layout| layout.strip_package_ext(file_name))
+            .ok_or_else(invalid)?;
+        let (name, version) = name_version
+            .match_indices('-')
+            .find_map(|(at, _)| {
+                let version = semver::Version::parse(&name_version[at + 1..]).ok()?;
+
+                Some((&name_version[..at], version))
+            })
+            .ok_or_else(invalid)?;
+
+        if name.is_empty() || name.contains('.') {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(None, name, version))
+    }
+
+    /// Parses the file name of a package of `group`, e.g.
+    /// `infinyon-json-sql-0.0.2.ipkg` in the `infinyon` group
+    pub fn parse_in_group(file_name: &str, group: &str) -> Result<Self> {
+        let parsed = Self::parse(file_name)?;
+        let name = parsed
+            .name
+            .strip_prefix(group)
+            .and_then(|name| name.strip_prefix('-'))
+            .filter(|name| !group.is_empty() && !name.is_empty())
+            .ok_or_else(|| HubError::InvalidPackageName(file_name.to_string()))?;
+
+        Ok(Self::new(Some(group), name, parsed.version))
+    }
+}
+
+impl Display for PackageFileName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let group = self.group.as_deref().unwrap_or_default();
+
+        f.write_str(&make_filename(group, &self.name, &self.version.to_string()))
+    }
+}
+
+impl FromStr for PackageFileName {
+    type Err = HubError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
     }
 }
 
 /// Generates Sha256 checksum for a given file
-pub fn sha256_digest(path: &PathBuf) -> Result<String> {
-    let mut hasher = Sha256::new();
-    let mut file = File::open(path)?;
+pub fn sha256_digest(path: impl AsRef<Path>) -> Result<String> {
+    sha256_digest_reader(File::open(path)?)
+}
+
+/// Generates Sha256 checksum for the contents of a reader, without buffering
+/// the whole contents in memory
+pub fn sha256_digest_reader(mut reader: impl Read) -> Result<String> {
+    let mut digest = Sha256Digest::new();
 
-    copy(&mut file, &mut hasher)?;
+    copy(&mut reader, &mut digest)?;
 
-    let hash_bytes = hasher.finalize();
+    Ok(digest.finalize())
+}
 
-    Ok(hex::encode(hash_bytes))
+/// Incremental Sha256 checksum, for data processed in chunks
+#[derive(Clone, Default)]
+pub struct Sha256Digest {
+    hasher: Sha256,
+    len: u64,
+}
+
+impl Sha256Digest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+        self.len += bytes.len() as u64;
+    }
+
+    /// Number of bytes hashed so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Hex encoded checksum of the bytes hashed so far
+    pub fn finalize(self) -> String {
+        hex::encode(self.hasher.finalize())
+    }
+}
+
+impl Write for Sha256Digest {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -86,4 +228,81 @@ mod util_tests {
 
         assert_eq!(foo_a_checksum, foo_b_checksum);
     }
+
+    #[test]
+    fn round_trips_package_file_names() {
+        use crate::PackageFileName;
+
+        use super::make_filename;
+
+        let cases = [
+            (Some("infinyon"), "json-sql", "0.0.2"),
+            (Some("my-org"), "my-pkg-2", "1.0.0-beta.1"),
+            (None, "regex", "0.1.0+build.5"),
+            (Some("org"), "sink:v2", "10.2.3-rc-1"),
+        ];
+
+        for (group, name, version) in cases {
+            let file_name =
+                PackageFileName::new(group, name, semver::Version::parse(version).unwrap());
+            let formatted = file_name.to_string();
+
+            assert_eq!(
+                formatted,
+                make_filename(group.unwrap_or_default(), name, version)
+            );
+
+            let parsed = match group {
+                Some(group) => PackageFileName::parse_in_group(&formatted, group).unwrap(),
+                None => formatted.parse::<PackageFileName>().unwrap(),
+            };
+
+            assert_eq!(parsed, file_name, "{formatted}");
+        }
+
+        assert_eq!(
+            PackageFileName::parse("my-org-my-pkg-2-1.0.0-beta.1.ipkg").unwrap(),
+            PackageFileName::new(
+                None,
+                "my-org-my-pkg-2",
+                semver::Version::parse("1.0.0-beta.1").unwrap()
+            )
+        );
+
+        for invalid in [
+            "json-sql-0.0.2.tar",
+            "json-sql.ipkg",
+            "-0.0.2.ipkg",
+            "json-sql-0.0.ipkg",
+        ] {
+            assert!(PackageFileName::parse(invalid).is_err(), "{invalid}");
+        }
+
+        for (invalid, group) in [
+            ("json-sql-0.0.2.ipkg", "infinyon"),
+            ("infinyon-0.0.2.ipkg", "infinyon"),
+            ("json-sql-0.0.2.ipkg", ""),
+        ] {
+            assert!(
+                PackageFileName::parse_in_group(invalid, group).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn digests_readers_incrementally() {
+        use crate::{Sha256Digest, sha256_digest_reader};
+
+        let foo = "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+        assert_eq!(sha256_digest_reader(&b"foo"[..]).unwrap(), foo);
+
+        let mut digest = Sha256Digest::new();
+
+        digest.update(b"f");
+        digest.update(b"oo");
+        assert_eq!(digest.len(), 3);
+        assert_eq!(digest.finalize(), foo);
+    }
 }
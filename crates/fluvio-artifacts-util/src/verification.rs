@@ -0,0 +1,237 @@
+//! Download Verification Reports
+//!
+//! A [`VerificationReport`] records what was downloaded for an installation:
+//! the URLs, the expected and computed SHA-256 digests, sizes and times, and
+//! the version of the tool which downloaded them. Reports are written next
+//! to the installed artifacts so they can be archived as evidence of the
+//! verification performed.
+
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::write_atomic;
+use crate::{sha256_digest, sha256_digest_reader};
+
+/// File name of the verification report in installation directories
+pub const VERIFICATION_REPORT_FILENAME: &str = "verification-report.json";
+
+/// Where the verified contents came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerificationSource {
+    /// Downloaded from the URL
+    Download,
+    /// Linked from the local content store, see [`crate::store::ContentStore`]
+    ContentStore,
+}
+
+/// A downloaded file and the result of its verification
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedDownload {
+    pub name: String,
+    pub version: String,
+    pub url: String,
+    pub source: VerificationSource,
+    /// Published digest, when one is available
+    pub expected_sha256: Option<String>,
+    /// Digest of the downloaded bytes
+    pub computed_sha256: String,
+    /// Whether the computed digest matches the published digest
+    pub digest_matches: Option<bool>,
+    /// Size of the downloaded bytes
+    pub size: u64,
+    pub downloaded_at: DateTime<Utc>,
+}
+
+impl VerifiedDownload {
+    /// Verification of the downloaded `bytes`
+    pub fn from_bytes(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        url: impl Into<String>,
+        expected_sha256: Option<&str>,
+        bytes: &[u8],
+    ) -> Result<Self> {
+        Ok(Self::from_digest(
+            name,
+            version,
+            url,
+            expected_sha256,
+            sha256_digest_reader(bytes)?,
+            bytes.len() as u64,
+        ))
+    }
+
+    /// Verification of `size` downloaded bytes whose digest was computed
+    /// while they were received
+    pub fn from_digest(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        url: impl Into<String>,
+        expected_sha256: Option<&str>,
+        computed_sha256: String,
+        size: u64,
+    ) -> Self {
+        Self::new(
+            name.into(),
+            version.into(),
+            url.into(),
+            VerificationSource::Download,
+            expected_sha256,
+            computed_sha256,
+            size,
+        )
+    }
+
+    /// Verification of the file at `path`, linked from the content store
+    pub fn from_store(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        url: impl Into<String>,
+        expected_sha256: Option<&str>,
+        path: &Path,
+    ) -> Result<Self> {
+        Ok(Self::new(
+            name.into(),
+            version.into(),
+            url.into(),
+            VerificationSource::ContentStore,
+            expected_sha256,
+            sha256_digest(path)?,
+            path.metadata()?.len(),
+        ))
+    }
+
+    fn new(
+        name: String,
+        version: String,
+        url: String,
+        source: VerificationSource,
+        expected_sha256: Option<&str>,
+        computed_sha256: String,
+        size: u64,
+    ) -> Self {
+        let expected_sha256 = expected_sha256.map(|digest| {
+            let digest = digest.trim();
+
+            digest
+                .strip_prefix("sha256:")
+                .unwrap_or(digest)
+                .to_ascii_lowercase()
+        });
+        let digest_matches = expected_sha256
+            .as_ref()
+            .map(|expected| *expected == computed_sha256);
+
+        Self {
+            name,
+            version,
+            url,
+            source,
+            expected_sha256,
+            computed_sha256,
+            digest_matches,
+            size,
+            downloaded_at: Utc::now(),
+        }
+    }
+}
+
+/// Verification of the downloads of an installation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// What was installed, e.g. `fluvio 0.11.8`
+    pub subject: String,
+    pub target: String,
+    /// Name of the tool which performed the downloads
+    pub tool: String,
+    pub tool_version: String,
+    pub created_at: DateTime<Utc>,
+    pub downloads: Vec<VerifiedDownload>,
+}
+
+impl VerificationReport {
+    pub fn new(
+        subject: impl Into<String>,
+        target: impl Into<String>,
+        tool: impl Into<String>,
+        tool_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            subject: subject.into(),
+            target: target.into(),
+            tool: tool.into(),
+            tool_version: tool_version.into(),
+            created_at: Utc::now(),
+            downloads: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, download: VerifiedDownload) {
+        self.downloads.push(download);
+    }
+
+    /// Whether every download with a published digest matches it
+    pub fn is_verified(&self) -> bool {
+        self.downloads
+            .iter()
+            .all(|download| download.digest_matches != Some(false))
+    }
+
+    /// Writes the report as pretty printed JSON to `path`
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn records_computed_and_expected_digests() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(VERIFICATION_REPORT_FILENAME);
+        let computed = sha256_digest_reader(&b"fluvio"[..]).unwrap();
+        let mut report = VerificationReport::new(
+            "fluvio 0.11.8",
+            "x86_64-unknown-linux-musl",
+            "fvm",
+            "0.11.8",
+        );
+
+        report.push(
+            VerifiedDownload::from_bytes(
+                "fluvio",
+                "0.11.8",
+                "https://example.com/fluvio.zip",
+                Some(&format!("sha256:{}", computed.to_ascii_uppercase())),
+                b"fluvio",
+            )
+            .unwrap(),
+        );
+        report.push(
+            VerifiedDownload::from_bytes("cdk", "0.11.8", "https://example.com/cdk", None, b"cdk")
+                .unwrap(),
+        );
+
+        assert_eq!(report.downloads[0].digest_matches, Some(true));
+        assert_eq!(report.downloads[0].size, 6);
+        assert_eq!(report.downloads[1].digest_matches, None);
+        assert!(report.is_verified());
+
+        report.write(&path).unwrap();
+
+        let written: VerificationReport =
+            serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(written, report);
+    }
+}
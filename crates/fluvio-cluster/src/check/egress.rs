@@ -0,0 +1,267 @@
+//! Outbound connectivity checks
+//!
+//! SmartModules and connectors are downloaded by the cluster, not by the
+//! client, so a cluster with restrictive egress fails at runtime even though
+//! `fluvio hub` commands work from the workstation. This check runs a
+//! short-lived pod in the cluster which requests each endpoint through the
+//! egress path of the cluster.
+
+use std::fmt;
+use std::process::Command;
+
+use async_trait::async_trait;
+use rand::Rng;
+
+use crate::render::ProgressRenderer;
+
+use super::{
+    CheckResult, CheckStatus, ClusterCheck, ClusterCheckError, FluvioClusterComponent,
+    UnrecoverableCheckStatus,
+};
+
+/// Image of the probe pod, it needs `sh` and `curl`
+pub const DEFAULT_PROBE_IMAGE: &str = "curlimages/curl:8.10.1";
+
+/// Seconds each endpoint has to respond
+const REQUEST_TIMEOUT_SECS: u32 = 10;
+
+/// Seconds the probe pod has to start, including pulling its image
+const POD_START_TIMEOUT: &str = "90s";
+
+/// Endpoint the cluster downloads packages from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressEndpoint {
+    pub name: String,
+    pub url: String,
+}
+
+impl EgressEndpoint {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+        }
+    }
+}
+
+/// The hub and the GitHub endpoints serving release artifacts
+pub fn default_egress_endpoints() -> Vec<EgressEndpoint> {
+    vec![
+        EgressEndpoint::new("Hub", "https://hub.infinyon.cloud"),
+        EgressEndpoint::new("GitHub", "https://github.com"),
+        EgressEndpoint::new("GitHub artifacts", "https://objects.githubusercontent.com"),
+    ]
+}
+
+/// An endpoint the cluster cannot reach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressIssue {
+    pub endpoint: EgressEndpoint,
+    /// Why the request failed, e.g. `connection failed`
+    pub reason: String,
+}
+
+impl fmt::Display for EgressIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) is unreachable: {}",
+            self.endpoint.name, self.endpoint.url, self.reason
+        )
+    }
+}
+
+/// Shell script requesting each endpoint, printing `<url> <http code>` per
+/// endpoint. curl prints `000` when no response was received.
+fn probe_script(endpoints: &[EgressEndpoint]) -> String {
+    let urls: Vec<String> = endpoints
+        .iter()
+        .map(|endpoint| format!("'{}'", endpoint.url))
+        .collect();
+
+    format!(
+        "for url in {}; do code=$(curl -s -o /dev/null -m {REQUEST_TIMEOUT_SECS} -w '%{{http_code}}' \"$url\"); echo \"$url ${{code:-000}}\"; done",
+        urls.join(" ")
+    )
+}
+
+/// Finds the endpoints without a response in the output of the probe
+/// script. Any HTTP response means the endpoint is reachable, endpoints
+/// missing from the output are reported as not probed.
+pub fn inspect_probe_output(endpoints: &[EgressEndpoint], output: &str) -> Vec<EgressIssue> {
+    endpoints
+        .iter()
+        .filter_map(|endpoint| {
+            let code = output.lines().find_map(|line| {
+                let (url, code) = line.trim().rsplit_once(' ')?;
+
+                (url == endpoint.url).then_some(code)
+            });
+            let reason = match code {
+                None => "not probed".to_string(),
+                Some("000") => "connection failed or timed out".to_string(),
+                Some(code) if code.parse::<u16>().is_ok() => return None,
+                Some(code) => format!("unexpected probe output {code}"),
+            };
+
+            Some(EgressIssue {
+                endpoint: endpoint.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Checks the cluster can reach the hub and the artifact endpoints by
+/// running a probe pod in `namespace`, or in the namespace of the current
+/// context if `None`
+#[derive(Debug)]
+pub(crate) struct OutboundConnectivityCheck {
+    namespace: Option<String>,
+    image: String,
+    endpoints: Vec<EgressEndpoint>,
+}
+
+impl OutboundConnectivityCheck {
+    pub(crate) fn new(namespace: Option<String>) -> Self {
+        Self {
+            namespace,
+            image: DEFAULT_PROBE_IMAGE.to_string(),
+            endpoints: default_egress_endpoints(),
+        }
+    }
+
+    /// Runs the probe pod, returning its output, or an error message if the
+    /// pod did not run
+    fn run_probe(&self) -> Result<Result<String, String>, ClusterCheckError> {
+        let pod_name = format!(
+            "fluvio-egress-check-{:06x}",
+            rand::thread_rng().gen_range(0..0xffffff)
+        );
+        let mut command = Command::new("kubectl");
+
+        command
+            .arg("run")
+            .arg(&pod_name)
+            .arg("--rm")
+            .arg("--attach")
+            .arg("--quiet")
+            .arg("--restart=Never")
+            .arg(format!("--image={}", self.image))
+            .arg(format!("--pod-running-timeout={POD_START_TIMEOUT}"))
+            .arg("--labels=app=fluvio-egress-check");
+
+        if let Some(namespace) = &self.namespace {
+            command.arg("--namespace").arg(namespace);
+        }
+
+        command
+            .arg("--command")
+            .arg("--")
+            .arg("sh")
+            .arg("-c")
+            .arg(probe_script(&self.endpoints));
+
+        let output = command
+            .output()
+            .map_err(ClusterCheckError::KubectlNotFoundError)?;
+
+        if !output.status.success() {
+            return Ok(Err(String::from_utf8_lossy(&output.stderr)
+                .trim()
+                .to_string()));
+        }
+
+        Ok(Ok(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+}
+
+#[async_trait]
+impl ClusterCheck for OutboundConnectivityCheck {
+    async fn perform_check(&self, _pb: &ProgressRenderer) -> CheckResult {
+        let output = match self.run_probe()? {
+            Ok(output) => output,
+            // The probe image may itself be blocked by the egress policy
+            Err(err) => {
+                return Ok(CheckStatus::warning(format!(
+                    "Outbound connectivity could not be checked, the probe pod with image {} did not run: {err}",
+                    self.image
+                )));
+            }
+        };
+        let issues = inspect_probe_output(&self.endpoints, &output);
+
+        if !issues.is_empty() {
+            return Ok(CheckStatus::Unrecoverable(
+                UnrecoverableCheckStatus::OutboundConnectivity(
+                    issues
+                        .iter()
+                        .map(|issue| issue.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; "),
+                ),
+            ));
+        }
+
+        Ok(CheckStatus::pass(format!(
+            "Cluster can reach {}",
+            self.endpoints
+                .iter()
+                .map(|endpoint| endpoint.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )))
+    }
+
+    fn required_components(&self) -> Vec<FluvioClusterComponent> {
+        vec![FluvioClusterComponent::Kubernetes]
+    }
+
+    fn label(&self) -> &str {
+        "Outbound connectivity"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_script() {
+        let script = probe_script(&[EgressEndpoint::new("Hub", "https://hub.infinyon.cloud")]);
+
+        assert_eq!(
+            script,
+            "for url in 'https://hub.infinyon.cloud'; do code=$(curl -s -o /dev/null -m 10 -w '%{http_code}' \"$url\"); echo \"$url ${code:-000}\"; done"
+        );
+    }
+
+    #[test]
+    fn test_inspect_probe_output() {
+        let endpoints = default_egress_endpoints();
+        let output = "https://hub.infinyon.cloud 200\nhttps://github.com 301\n";
+
+        assert!(
+            inspect_probe_output(
+                &endpoints[..2],
+                "https://hub.infinyon.cloud 404\nhttps://github.com 301\n"
+            )
+            .is_empty()
+        );
+
+        let issues = inspect_probe_output(
+            &endpoints,
+            &format!("{output}https://objects.githubusercontent.com 000\n"),
+        );
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].to_string(),
+            "GitHub artifacts (https://objects.githubusercontent.com) is unreachable: connection failed or timed out"
+        );
+        assert_eq!(
+            inspect_probe_output(&endpoints, output)[0].reason,
+            "not probed"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Progress events of a cluster check run
+//!
+//! Frontends embedding the check experience, such as GUIs or web installers,
+//! consume these events instead of parsing the rendered terminal output.
+//! [`JsonLinesStdout`] writes each event as a JSON object on its own line.
+
+use std::fmt::Debug;
+use std::io::Write;
+
+use serde::Serialize;
+
+use super::report::CheckOutcome;
+
+/// Progress of a check run, in the order the events happen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum CheckEvent {
+    /// A check started
+    Started { label: String },
+    /// A check changed status before completing, e.g. started fixing
+    Status { label: String, message: String },
+    /// A check suggests an action to fix its failure
+    Suggestion { label: String, suggestion: String },
+    /// A check completed
+    Completed {
+        label: String,
+        outcome: CheckOutcome,
+        message: String,
+    },
+    /// All checks completed
+    Finished { passed: bool },
+}
+
+/// Receives the progress events of a check run
+pub trait CheckEventSink: Debug + Send + Sync {
+    fn emit(&self, event: &CheckEvent);
+}
+
+/// Writes each event as a line of JSON to stdout
+#[derive(Debug, Default)]
+pub struct JsonLinesStdout;
+
+impl CheckEventSink for JsonLinesStdout {
+    fn emit(&self, event: &CheckEvent) {
+        let mut stdout = std::io::stdout().lock();
+
+        match serde_json::to_string(event) {
+            Ok(line) => {
+                let _ = writeln!(stdout, "{line}").and_then(|_| stdout.flush());
+            }
+            Err(err) => tracing::debug!(%err, "failed to serialize check event"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_events_as_tagged_objects() {
+        let event = CheckEvent::Completed {
+            label: "Helm".to_string(),
+            outcome: CheckOutcome::Passed,
+            message: "Supported helm version 3.12.0 is installed".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"completed","label":"Helm","outcome":"passed","message":"Supported helm version 3.12.0 is installed"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&CheckEvent::Finished { passed: false }).unwrap(),
+            r#"{"event":"finished","passed":false}"#
+        );
+    }
+}
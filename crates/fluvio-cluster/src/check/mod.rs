@@ -5,7 +5,19 @@ use std::fmt::Debug;
 use std::process::Command;
 use std::time::Duration;
 
+mod egress;
+pub mod events;
 pub mod render;
+pub mod report;
+mod resources;
+mod spu;
+mod tls;
+
+pub use resources::LocalResources;
+use egress::OutboundConnectivityCheck;
+use resources::LocalResourcesCheck;
+use spu::SpuSchedulingCheck;
+use tls::TlsCertificateCheck;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -28,6 +40,9 @@ use crate::render::ProgressRenderer;
 use crate::charts::{ChartConfig, ChartInstaller, ChartInstallError, SYS_CHART_NAME};
 use crate::LocalConfig;
 
+use events::{CheckEvent, CheckEventSink};
+use report::{CheckOutcome, CheckReport, Severity};
+
 const KUBE_VERSION: &str = "1.7.0";
 const RESOURCE_SERVICE: &str = "service";
 const RESOURCE_CRD: &str = "customresourcedefinitions";
@@ -131,6 +146,8 @@ pub type CheckStatuses = Vec<CheckStatus>;
 pub enum CheckStatus {
     /// This check has passed and has the given success message
     Pass(CheckSucceeded),
+    /// This check has passed, but found something needing attention
+    Warning(String),
     /// This check has failed but can be recovered
     AutoFixableError {
         message: String,
@@ -145,6 +162,11 @@ impl CheckStatus {
     pub(crate) fn pass(msg: impl Into<String>) -> Self {
         Self::Pass(msg.into())
     }
+
+    /// Creates a passing check status with a warning message
+    pub(crate) fn warning(msg: impl Into<String>) -> Self {
+        Self::Warning(msg.into())
+    }
 }
 
 /// A successful check yields a success message
@@ -252,6 +274,22 @@ pub enum UnrecoverableCheckStatus {
     #[error("Helm client error")]
     HelmClientError,
 
+    /// The TLS certificates of the profile or local cluster are not valid
+    #[error("Invalid TLS certificates: {0}")]
+    InvalidTlsCertificates(String),
+
+    /// The cluster cannot reach the hub or artifact endpoints
+    #[error("Cluster cannot reach package endpoints: {0}")]
+    OutboundConnectivity(String),
+
+    /// SPU pods cannot be scheduled or are scheduled unsafely
+    #[error("SPU scheduling issues: {issues}")]
+    SpuScheduling { issues: String, suggestion: String },
+
+    /// The host lacks memory, file descriptors or ports for a local cluster
+    #[error("Insufficient system resources: {issues}")]
+    LocalResources { issues: String, suggestion: String },
+
     /// Other misc
     #[error("Other failure: {0}")]
     Other(String),
@@ -259,7 +297,18 @@ pub enum UnrecoverableCheckStatus {
 
 impl CheckSuggestion for UnrecoverableCheckStatus {
     fn suggestion(&self) -> Option<String> {
-        None
+        match self {
+            Self::InvalidTlsCertificates(_) => Some(
+                "Regenerate the certificates with the CA configured for the profile and restart the cluster with them"
+                    .to_string(),
+            ),
+            Self::OutboundConnectivity(_) => Some(
+                "Allow egress to these endpoints from the cluster namespace in its network policies, firewall or proxy, SmartModule and connector downloads fail without it".to_string(),
+            ),
+            Self::SpuScheduling { suggestion, .. } => Some(suggestion.clone()),
+            Self::LocalResources { suggestion, .. } => Some(suggestion.clone()),
+            _ => None,
+        }
     }
 }
 
@@ -784,6 +833,8 @@ impl ClusterCheck for LocalClusterVersionCheck {
 #[non_exhaustive]
 pub struct ClusterChecker {
     checks: Vec<Box<dyn ClusterCheck>>,
+    events: Option<Box<dyn CheckEventSink>>,
+    fail_on: Severity,
 }
 
 impl ClusterChecker {
@@ -801,7 +852,11 @@ impl ClusterChecker {
     ///
     /// [`with_check`]: ClusterChecker::with_check
     pub fn empty() -> Self {
-        ClusterChecker { checks: vec![] }
+        ClusterChecker {
+            checks: vec![],
+            events: None,
+            fail_on: Severity::default(),
+        }
     }
 
     /// Adds a check to this `ClusterChecker`
@@ -810,6 +865,20 @@ impl ClusterChecker {
         self
     }
 
+    /// Sends the progress events of the run to `sink`, in addition to the
+    /// rendered progress
+    pub fn with_event_sink(mut self, sink: impl CheckEventSink + 'static) -> Self {
+        self.events = Some(Box::new(sink));
+        self
+    }
+
+    /// Fails the run on checks ending with `fail_on` severity or worse,
+    /// defaults to [`Severity::Error`]
+    pub fn with_fail_on(mut self, fail_on: Severity) -> Self {
+        self.fail_on = fail_on;
+        self
+    }
+
     /// Adds all preflight checks to this checker.
     ///
     /// Note that no checks are run until the [`run`] method is invoked.
@@ -845,6 +914,30 @@ impl ClusterChecker {
         self.with_check(LocalClusterVersionCheck(version))
     }
 
+    /// Adds a check of the TLS certificates of `profile`, or of the current
+    /// profile if `None`
+    pub fn with_tls_certificates(self, profile: Option<String>) -> Self {
+        self.with_check(TlsCertificateCheck::new(profile))
+    }
+
+    /// Adds a check of the scheduling of the SPU pods in `namespace`, or in
+    /// the namespace of the current context if `None`
+    pub fn with_spu_scheduling(self, namespace: Option<String>) -> Self {
+        self.with_check(SpuSchedulingCheck::new(namespace))
+    }
+
+    /// Adds a check that pods in `namespace`, or in the namespace of the
+    /// current context if `None`, can reach the hub and artifact endpoints
+    pub fn with_outbound_connectivity(self, namespace: Option<String>) -> Self {
+        self.with_check(OutboundConnectivityCheck::new(namespace))
+    }
+
+    /// Adds a check of the memory, open file limit and ports needed to start
+    /// a local cluster with `resources`
+    pub fn with_local_resources(self, resources: LocalResources) -> Self {
+        self.with_check(LocalResourcesCheck::new(resources))
+    }
+
     /// Adds all checks required for starting a cluster on minikube.
     ///
     /// Note that no checks are run until the [`run`] method is invoked.
@@ -878,6 +971,49 @@ impl ClusterChecker {
 
     /// Performs checks and fixes as required.
     pub async fn run(self, pb_factory: &ProgressBarFactory, fix_recoverable: bool) -> Result<bool> {
+        let fail_on = self.fail_on;
+        let report = self.run_with_report(pb_factory, fix_recoverable).await?;
+
+        Self::finish_with_policy(pb_factory, &report, fail_on)
+    }
+
+    /// Prints the overall result of a check run, failing if any check failed
+    pub fn finish(pb_factory: &ProgressBarFactory, report: &CheckReport) -> Result<bool> {
+        Self::finish_with_policy(pb_factory, report, Severity::Error)
+    }
+
+    /// Prints the overall result of a check run, failing if any check ended
+    /// with `fail_on` severity or worse
+    pub fn finish_with_policy(
+        pb_factory: &ProgressBarFactory,
+        report: &CheckReport,
+        fail_on: Severity,
+    ) -> Result<bool> {
+        if report.failed() {
+            pb_factory.println(format!("💔 {}", "Some pre-flight check failed!".bold()));
+            Err(ClusterCheckError::PreCheckFlightFailure.into())
+        } else if report.fails(fail_on) {
+            pb_factory.println(format!(
+                "💔 {}",
+                "Some pre-flight check has warnings, failing on warnings!".bold()
+            ));
+            Err(ClusterCheckError::PreCheckFlightFailure.into())
+        } else if report.warned() {
+            pb_factory.println(format!("🎉 {}", "All checks passed with warnings".bold()));
+            Ok(true)
+        } else {
+            pb_factory.println(format!("🎉 {}", "All checks passed!".bold()));
+            Ok(true)
+        }
+    }
+
+    /// Performs checks and fixes as required, returning the outcome of each
+    /// check without failing on failed checks.
+    pub async fn run_with_report(
+        self,
+        pb_factory: &ProgressBarFactory,
+        fix_recoverable: bool,
+    ) -> Result<CheckReport> {
         macro_rules! pad_format {
             ( $e:expr ) => {
                 format!("{:>3} {}", "", $e)
@@ -890,9 +1026,22 @@ impl ClusterChecker {
         let mut sorted_checks = self.checks;
         sorted_checks.sort_by(check_compare);
 
-        let mut failed = false;
+        let events = self.events;
+        let emit = |event: CheckEvent| {
+            if let Some(sink) = &events {
+                sink.emit(&event);
+            }
+        };
+
+        let mut report = CheckReport::new();
+        let group = pb_factory.group("Checks", sorted_checks.len());
         for check in sorted_checks {
-            let pb = pb_factory.create()?;
+            let task = group.add_task(pad_format!(format!(
+                "{} Checking {}",
+                "📝".bold(),
+                check.label()
+            )));
+            let pb = task.renderer();
             let mut passed = false;
             let required_components = check.required_components();
             let component = check.component();
@@ -902,23 +1051,26 @@ impl ClusterChecker {
                 .count()
                 == required_components.len()
             {
-                pb.set_message(pad_format!(format!(
-                    "{} Checking {}",
-                    "📝".bold(),
-                    check.label()
-                )));
+                emit(CheckEvent::Started {
+                    label: check.label().to_string(),
+                });
                 sleep(Duration::from_millis(100)).await; // dummy delay for debugging
-                match check.perform_check(&pb).await? {
+                match check.perform_check(pb).await? {
                     CheckStatus::AutoFixableError { message, fixer } => {
                         if fix_recoverable {
                             pb.set_message(pad_format!(format!("{} {}", "🟡️".bold(), message)));
-                            match fixer.attempt_fix(&pb).await {
+                            emit(CheckEvent::Status {
+                                label: check.label().to_string(),
+                                message: message.clone(),
+                            });
+                            match fixer.attempt_fix(pb).await {
                                 Ok(status) => {
                                     pb.println(pad_format!(format!(
                                         "{} Fixed: {}",
                                         "✅".bold(),
                                         status
                                     )));
+                                    report.record(check.label(), CheckOutcome::Fixed, status);
                                     passed = true;
                                 }
                                 Err(err) => {
@@ -930,7 +1082,11 @@ impl ClusterChecker {
                                         err
                                     )));
 
-                                    failed = true;
+                                    report.record(
+                                        check.label(),
+                                        CheckOutcome::Failed,
+                                        format!("{message}, auto fix failed: {err}"),
+                                    );
                                 }
                             }
                         } else {
@@ -940,12 +1096,18 @@ impl ClusterChecker {
                                 check.label().italic(),
                             )));
 
-                            failed = true;
+                            report.record(check.label(), CheckOutcome::Failed, message);
                         }
                     }
                     CheckStatus::Pass(status) => {
                         passed = true;
                         pb.println(pad_format!(format!("{} {}", "✅".bold(), status)));
+                        report.record(check.label(), CheckOutcome::Passed, status);
+                    }
+                    CheckStatus::Warning(warning) => {
+                        passed = true;
+                        pb.println(pad_format!(format!("{} {}", "⚠️".bold(), warning.yellow())));
+                        report.record(check.label(), CheckOutcome::Warned, warning);
                     }
                     CheckStatus::Unrecoverable(err) => {
                         debug!("failed: {}", err);
@@ -957,7 +1119,15 @@ impl ClusterChecker {
                             err.to_string().red()
                         )));
 
-                        failed = true;
+                        if let Some(suggestion) = err.suggestion() {
+                            pb.println(pad_format!(format!("{} {}", "💡", suggestion)));
+                            emit(CheckEvent::Suggestion {
+                                label: check.label().to_string(),
+                                suggestion,
+                            });
+                        }
+
+                        report.record(check.label(), CheckOutcome::Failed, err.to_string());
                     }
                 }
             } else {
@@ -965,7 +1135,19 @@ impl ClusterChecker {
                     "❌ skipping check: {} because required components are not met",
                     check.label()
                 )));
-                failed = true;
+                report.record(
+                    check.label(),
+                    CheckOutcome::Skipped,
+                    "required components are not met",
+                );
+            }
+
+            if let Some(record) = report.checks.last() {
+                emit(CheckEvent::Completed {
+                    label: record.label.clone(),
+                    outcome: record.outcome,
+                    message: record.message.clone(),
+                });
             }
 
             if passed && let Some(component) = component {
@@ -973,16 +1155,15 @@ impl ClusterChecker {
                 components.insert(component);
             }
 
-            pb.finish_and_clear();
+            task.done(passed, 0);
         }
 
-        if failed {
-            pb_factory.println(format!("💔 {}", "Some pre-flight check failed!".bold()));
-            Err(ClusterCheckError::PreCheckFlightFailure.into())
-        } else {
-            pb_factory.println(format!("🎉 {}", "All checks passed!".bold()));
-            Ok(true)
-        }
+        group.finish();
+        emit(CheckEvent::Finished {
+            passed: !report.fails(self.fail_on),
+        });
+
+        Ok(report)
     }
 }
 
@@ -1053,4 +1234,61 @@ mod tests {
         // since per depends on k8, k8 should be less
         assert_eq!(check_compare(&k8, &perm), Ordering::Less);
     }
+
+    #[derive(Debug, Default)]
+    struct CollectEvents(std::sync::Arc<std::sync::Mutex<Vec<CheckEvent>>>);
+
+    impl CheckEventSink for CollectEvents {
+        fn emit(&self, event: &CheckEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingCheck;
+
+    #[async_trait]
+    impl ClusterCheck for FailingCheck {
+        async fn perform_check(&self, _pb: &ProgressRenderer) -> CheckResult {
+            Ok(CheckStatus::Unrecoverable(
+                UnrecoverableCheckStatus::InvalidTlsCertificates("expired".to_string()),
+            ))
+        }
+
+        fn label(&self) -> &str {
+            "Failing"
+        }
+    }
+
+    #[fluvio_future::test]
+    async fn test_emits_check_events() {
+        let sink = CollectEvents::default();
+        let events = sink.0.clone();
+        let report = ClusterChecker::empty()
+            .with_check(FailingCheck)
+            .with_event_sink(sink)
+            .run_with_report(&ProgressBarFactory::new(true), false)
+            .await
+            .unwrap();
+        let events = events.lock().unwrap();
+
+        assert!(report.failed());
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            CheckEvent::Started {
+                label: "Failing".to_string()
+            }
+        );
+        assert!(matches!(events[1], CheckEvent::Suggestion { .. }));
+        assert_eq!(
+            events[2],
+            CheckEvent::Completed {
+                label: "Failing".to_string(),
+                outcome: CheckOutcome::Failed,
+                message: "Invalid TLS certificates: expired".to_string(),
+            }
+        );
+        assert_eq!(events[3], CheckEvent::Finished { passed: false });
+    }
 }
@@ -0,0 +1,304 @@
+//! Structured results of a cluster check run
+//!
+//! The most recent report is kept in `~/.fluvio/check-report.json` so
+//! `fluvio cluster check --diff` can show which checks changed status since
+//! the previous run.
+
+use std::fmt;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// How serious a check result is, warnings are only failures when the
+/// failure policy asks for it, e.g. `--fail-on warning` in CI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    /// The cluster works, but needs attention
+    Warning,
+    /// The cluster cannot work
+    #[default]
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// How a check ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckOutcome {
+    Passed,
+    /// Passed with a warning
+    Warned,
+    /// Failed and was fixed automatically
+    Fixed,
+    Failed,
+    /// Not performed because a required component check did not pass
+    Skipped,
+}
+
+impl CheckOutcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Failed | Self::Skipped)
+    }
+
+    /// Severity of the outcome, `None` if the check passed
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            Self::Passed | Self::Fixed => None,
+            Self::Warned => Some(Severity::Warning),
+            Self::Failed | Self::Skipped => Some(Severity::Error),
+        }
+    }
+}
+
+impl fmt::Display for CheckOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self {
+            Self::Passed => "passed",
+            Self::Warned => "warned",
+            Self::Fixed => "fixed",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        };
+
+        f.write_str(outcome)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub label: String,
+    pub outcome: CheckOutcome,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// RFC 3339 time of the run
+    pub created_at: String,
+    pub checks: Vec<CheckRecord>,
+}
+
+/// Difference in a check between two reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckChange {
+    /// The check was not performed in the previous run
+    Added {
+        label: String,
+        outcome: CheckOutcome,
+    },
+    /// The check is no longer performed
+    Removed {
+        label: String,
+        outcome: CheckOutcome,
+    },
+    Changed {
+        label: String,
+        from: CheckOutcome,
+        to: CheckOutcome,
+        message: String,
+    },
+}
+
+impl fmt::Display for CheckChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { label, outcome } => write!(f, "{label}: new check, {outcome}"),
+            Self::Removed { label, outcome } => {
+                write!(f, "{label}: not checked anymore, was {outcome}")
+            }
+            Self::Changed {
+                label,
+                from,
+                to,
+                message,
+            } => write!(f, "{label}: {from} -> {to}, {message}"),
+        }
+    }
+}
+
+impl CheckReport {
+    pub fn new() -> Self {
+        Self {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            checks: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, label: &str, outcome: CheckOutcome, message: impl Into<String>) {
+        self.checks.push(CheckRecord {
+            label: label.to_string(),
+            outcome,
+            message: message.into(),
+        });
+    }
+
+    /// Returns true if any check failed or was skipped
+    pub fn failed(&self) -> bool {
+        self.checks.iter().any(|check| check.outcome.is_failure())
+    }
+
+    /// Returns true if any check ended with a warning
+    pub fn warned(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.outcome == CheckOutcome::Warned)
+    }
+
+    /// Returns true if any check ended with `fail_on` severity or worse
+    pub fn fails(&self, fail_on: Severity) -> bool {
+        self.checks
+            .iter()
+            .filter_map(|check| check.outcome.severity())
+            .any(|severity| severity >= fail_on)
+    }
+
+    /// Loads a report, returns `None` if there is no report at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&read_to_string(path)?)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Checks whose outcome changed since the `previous` report, in the order
+    /// of this report followed by checks no longer performed
+    pub fn diff(&self, previous: &CheckReport) -> Vec<CheckChange> {
+        let find = |report: &CheckReport, label: &str| {
+            report
+                .checks
+                .iter()
+                .find(|check| check.label == label)
+                .cloned()
+        };
+        let mut changes: Vec<CheckChange> = self
+            .checks
+            .iter()
+            .filter_map(|check| match find(previous, &check.label) {
+                None => Some(CheckChange::Added {
+                    label: check.label.clone(),
+                    outcome: check.outcome,
+                }),
+                Some(before) if before.outcome != check.outcome => Some(CheckChange::Changed {
+                    label: check.label.clone(),
+                    from: before.outcome,
+                    to: check.outcome,
+                    message: check.message.clone(),
+                }),
+                Some(_) => None,
+            })
+            .collect();
+
+        changes.extend(
+            previous
+                .checks
+                .iter()
+                .filter(|check| find(self, &check.label).is_none())
+                .map(|check| CheckChange::Removed {
+                    label: check.label.clone(),
+                    outcome: check.outcome,
+                }),
+        );
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_diff() {
+        let mut previous = CheckReport::new();
+        previous.record("Helm", CheckOutcome::Passed, "helm 3.14 installed");
+        previous.record("TLS certificates", CheckOutcome::Failed, "expired");
+        previous.record("Fluvio Sys Chart", CheckOutcome::Failed, "not installed");
+
+        let mut current = CheckReport::new();
+        current.record("Helm", CheckOutcome::Passed, "helm 3.15 installed");
+        current.record("TLS certificates", CheckOutcome::Passed, "valid");
+        current.record("Kubernetes version", CheckOutcome::Skipped, "missing");
+
+        assert!(previous.failed());
+        assert!(current.failed());
+        assert_eq!(
+            current.diff(&previous),
+            vec![
+                CheckChange::Changed {
+                    label: "TLS certificates".to_string(),
+                    from: CheckOutcome::Failed,
+                    to: CheckOutcome::Passed,
+                    message: "valid".to_string(),
+                },
+                CheckChange::Added {
+                    label: "Kubernetes version".to_string(),
+                    outcome: CheckOutcome::Skipped,
+                },
+                CheckChange::Removed {
+                    label: "Fluvio Sys Chart".to_string(),
+                    outcome: CheckOutcome::Failed,
+                },
+            ]
+        );
+        assert!(current.diff(&current).is_empty());
+    }
+
+    #[test]
+    fn test_report_failure_policy() {
+        let mut report = CheckReport::new();
+        report.record("Helm", CheckOutcome::Fixed, "installed");
+
+        assert!(!report.fails(Severity::Warning));
+
+        report.record("TLS certificates", CheckOutcome::Warned, "expires soon");
+
+        assert!(report.warned());
+        assert!(!report.failed());
+        assert!(!report.fails(Severity::Error));
+        assert!(report.fails(Severity::Warning));
+
+        report.record("Kubernetes version", CheckOutcome::Skipped, "missing");
+
+        assert!(report.fails(Severity::Error));
+    }
+
+    #[test]
+    fn test_report_save_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("check-report.json");
+
+        assert_eq!(CheckReport::load(&path).unwrap(), None);
+
+        let mut report = CheckReport::new();
+        report.record("Helm", CheckOutcome::Fixed, "installed");
+        report.save(&path).unwrap();
+
+        assert_eq!(CheckReport::load(&path).unwrap(), Some(report));
+    }
+}
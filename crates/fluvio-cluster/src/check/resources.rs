@@ -0,0 +1,351 @@
+//! Local system resource checks
+//!
+//! Inspects the host before starting a local cluster: processes started
+//! without enough memory, with a low open file limit or on ports already in
+//! use otherwise fail late and surface as timeouts waiting for the SC or the
+//! SPUs.
+
+use std::fmt;
+use std::net::{Ipv4Addr, TcpListener};
+use std::process::Command;
+
+use async_trait::async_trait;
+use bytesize::ByteSize;
+use sysinfo::System;
+
+use fluvio_types::defaults::{SC_PRIVATE_PORT, SC_PUBLIC_PORT};
+
+use crate::render::ProgressRenderer;
+use crate::runtime::local::local_spu_ports;
+
+use super::{CheckResult, CheckStatus, ClusterCheck, UnrecoverableCheckStatus};
+
+/// Memory needed by the SC
+const SC_MIN_MEMORY: u64 = 256 * 1024 * 1024;
+
+/// Memory needed by each SPU
+const SPU_MIN_MEMORY: u64 = 256 * 1024 * 1024;
+
+/// Open file limit below which the cluster fails to start
+pub const MIN_OPEN_FILES: u64 = 256;
+
+/// Open file limit below which SPUs may run out of file descriptors as
+/// partitions are added
+pub const RECOMMENDED_OPEN_FILES: u64 = 4096;
+
+/// Ports and processes of a local cluster
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalResources {
+    /// Ports the SC and the SPUs listen on, with what listens on them
+    pub ports: Vec<(u16, String)>,
+    pub spus: u16,
+}
+
+impl LocalResources {
+    pub fn new(sc_public_port: u16, sc_private_port: u16, spus: u16) -> Self {
+        let mut ports = vec![
+            (sc_public_port, String::from("SC public")),
+            (sc_private_port, String::from("SC private")),
+        ];
+
+        for (idx, (public, private)) in local_spu_ports(spus).into_iter().enumerate() {
+            ports.push((public, format!("SPU {idx} public")));
+            ports.push((private, format!("SPU {idx} private")));
+        }
+
+        Self { ports, spus }
+    }
+
+    /// Memory needed by the SC and the SPUs
+    pub fn required_memory(&self) -> u64 {
+        SC_MIN_MEMORY + SPU_MIN_MEMORY * u64::from(self.spus)
+    }
+}
+
+impl Default for LocalResources {
+    fn default() -> Self {
+        Self::new(SC_PUBLIC_PORT, SC_PRIVATE_PORT, 1)
+    }
+}
+
+/// State of the host relevant to a local cluster, `None` when unknown
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostResources {
+    pub available_memory: Option<u64>,
+    pub open_files_limit: Option<u64>,
+    /// Ports which cannot be listened on
+    pub busy_ports: Vec<u16>,
+}
+
+impl HostResources {
+    /// Reads the available memory and open file limit, and tries to listen
+    /// on the `ports`
+    pub fn detect(ports: &[u16]) -> Self {
+        let mut sys = System::new();
+
+        sys.refresh_memory();
+
+        Self {
+            available_memory: Some(sys.available_memory()).filter(|memory| *memory > 0),
+            open_files_limit: open_files_limit(),
+            busy_ports: ports
+                .iter()
+                .copied()
+                .filter(|port| is_port_in_use(*port))
+                .collect(),
+        }
+    }
+}
+
+/// A problem found in the resources of the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceIssue {
+    /// Less memory is available than the cluster needs
+    LowMemory { available: u64, required: u64 },
+    /// The open file limit is too low to start the cluster
+    OpenFilesLimit { limit: u64 },
+    /// The open file limit may be exhausted as partitions are added
+    LowOpenFilesLimit { limit: u64 },
+    /// A port the cluster listens on is used by another process
+    PortInUse { port: u16, purpose: String },
+}
+
+impl ResourceIssue {
+    /// Issues which don't prevent the cluster from starting
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Self::LowOpenFilesLimit { .. })
+    }
+
+    pub fn suggestion(&self) -> String {
+        match self {
+            Self::LowMemory { .. } => {
+                "Free memory or start fewer SPUs with the `--spu` option".to_string()
+            }
+            Self::OpenFilesLimit { .. } | Self::LowOpenFilesLimit { .. } => format!(
+                "Raise the open file limit with `ulimit -n {RECOMMENDED_OPEN_FILES}` in the shell starting the cluster"
+            ),
+            Self::PortInUse { port, .. } => format!(
+                "Stop the process listening on port {port}, found with `lsof -i :{port}`, or delete a previous cluster with `fluvio cluster delete`"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ResourceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LowMemory {
+                available,
+                required,
+            } => write!(
+                f,
+                "{} of memory available, {} required",
+                ByteSize::b(*available),
+                ByteSize::b(*required)
+            ),
+            Self::OpenFilesLimit { limit } => write!(
+                f,
+                "open file limit is {limit}, at least {MIN_OPEN_FILES} required"
+            ),
+            Self::LowOpenFilesLimit { limit } => write!(
+                f,
+                "open file limit is {limit}, {RECOMMENDED_OPEN_FILES} recommended"
+            ),
+            Self::PortInUse { port, purpose } => {
+                write!(f, "port {port} ({purpose}) is already in use")
+            }
+        }
+    }
+}
+
+/// Finds the issues of `host` for starting a cluster needing `resources`
+pub fn inspect_resources(resources: &LocalResources, host: &HostResources) -> Vec<ResourceIssue> {
+    let mut issues = Vec::new();
+    let required = resources.required_memory();
+
+    if let Some(available) = host.available_memory
+        && available < required
+    {
+        issues.push(ResourceIssue::LowMemory {
+            available,
+            required,
+        });
+    }
+
+    match host.open_files_limit {
+        Some(limit) if limit < MIN_OPEN_FILES => {
+            issues.push(ResourceIssue::OpenFilesLimit { limit })
+        }
+        Some(limit) if limit < RECOMMENDED_OPEN_FILES => {
+            issues.push(ResourceIssue::LowOpenFilesLimit { limit })
+        }
+        _ => {}
+    }
+
+    for (port, purpose) in resources.ports.iter() {
+        if host.busy_ports.contains(port) {
+            issues.push(ResourceIssue::PortInUse {
+                port: *port,
+                purpose: purpose.clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Whether another process listens on `port`
+fn is_port_in_use(port: u16) -> bool {
+    [Ipv4Addr::LOCALHOST, Ipv4Addr::UNSPECIFIED]
+        .into_iter()
+        .any(|ip| TcpListener::bind((ip, port)).is_err())
+}
+
+/// Soft limit of open files inherited by the cluster processes
+fn open_files_limit() -> Option<u64> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg("ulimit -n")
+        .output()
+        .ok()?;
+
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "unlimited" => Some(u64::MAX),
+        limit => limit.parse().ok(),
+    }
+}
+
+/// Checks the memory, open file limit and ports needed by a local cluster
+#[derive(Debug)]
+pub(crate) struct LocalResourcesCheck {
+    resources: LocalResources,
+}
+
+impl LocalResourcesCheck {
+    pub(crate) fn new(resources: LocalResources) -> Self {
+        Self { resources }
+    }
+}
+
+#[async_trait]
+impl ClusterCheck for LocalResourcesCheck {
+    async fn perform_check(&self, _pb: &ProgressRenderer) -> CheckResult {
+        let ports: Vec<u16> = self.resources.ports.iter().map(|(port, _)| *port).collect();
+        let host = HostResources::detect(&ports);
+        let issues = inspect_resources(&self.resources, &host);
+        let suggestions = |issues: &[ResourceIssue]| {
+            let mut suggestions: Vec<String> =
+                issues.iter().map(|issue| issue.suggestion()).collect();
+
+            suggestions.dedup();
+            suggestions.join(". ")
+        };
+        let (warnings, errors): (Vec<_>, Vec<_>) =
+            issues.into_iter().partition(|issue| issue.is_warning());
+
+        if !errors.is_empty() {
+            let issues: Vec<ResourceIssue> = errors.into_iter().chain(warnings).collect();
+
+            return Ok(CheckStatus::Unrecoverable(
+                UnrecoverableCheckStatus::LocalResources {
+                    issues: issues
+                        .iter()
+                        .map(|issue| issue.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; "),
+                    suggestion: suggestions(&issues),
+                },
+            ));
+        }
+
+        if !warnings.is_empty() {
+            return Ok(CheckStatus::warning(format!(
+                "System resources are sufficient, but {}. {}",
+                warnings
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; "),
+                suggestions(&warnings)
+            )));
+        }
+
+        Ok(CheckStatus::pass(format!(
+            "System resources are sufficient for {} SPUs",
+            self.resources.spus
+        )))
+    }
+
+    fn label(&self) -> &str {
+        "Local system resources"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_local_resources_ports() {
+        let resources = LocalResources::new(9003, 9004, 2);
+
+        assert_eq!(
+            resources
+                .ports
+                .iter()
+                .map(|(port, _)| *port)
+                .collect::<Vec<_>>(),
+            vec![9003, 9004, 9010, 9011, 9020, 9021]
+        );
+        assert_eq!(resources.required_memory(), 768 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_inspect_resources() {
+        let resources = LocalResources::default();
+        let healthy = HostResources {
+            available_memory: Some(8 * GIB),
+            open_files_limit: Some(65536),
+            busy_ports: vec![],
+        };
+
+        assert!(inspect_resources(&resources, &healthy).is_empty());
+        assert!(inspect_resources(&resources, &HostResources::default()).is_empty());
+
+        let constrained = HostResources {
+            available_memory: Some(100 * 1024 * 1024),
+            open_files_limit: Some(1024),
+            busy_ports: vec![9010],
+        };
+        let issues = inspect_resources(&resources, &constrained);
+
+        assert_eq!(issues.len(), 3);
+        assert!(matches!(issues[0], ResourceIssue::LowMemory { .. }));
+        assert!(issues[1].is_warning());
+        assert_eq!(
+            issues[2].to_string(),
+            "port 9010 (SPU 0 public) is already in use"
+        );
+        assert!(issues[2].suggestion().contains("lsof -i :9010"));
+        assert_eq!(
+            inspect_resources(
+                &resources,
+                &HostResources {
+                    open_files_limit: Some(128),
+                    ..Default::default()
+                }
+            ),
+            vec![ResourceIssue::OpenFilesLimit { limit: 128 }]
+        );
+    }
+
+    #[test]
+    fn test_detects_port_in_use() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert_eq!(HostResources::detect(&[port]).busy_ports, vec![port]);
+    }
+}
@@ -0,0 +1,490 @@
+//! SPU scheduling checks
+//!
+//! Inspects how the SPU pods of a running cluster are scheduled: SPUs sharing
+//! a node without pod anti-affinity lose replicas together when the node goes
+//! down, SPUs with different storage capacities fill up unevenly, and pending
+//! SPUs never join the cluster.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::process::Command;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use fluvio_types::defaults::SPU_DEFAULT_NAME;
+
+use crate::render::ProgressRenderer;
+
+use super::{
+    CheckResult, CheckStatus, ClusterCheck, ClusterCheckError, FluvioClusterComponent,
+    UnrecoverableCheckStatus,
+};
+
+/// Name of the SPU volume holding the replicas
+const SPU_DATA_VOLUME: &str = "data";
+
+/// Scheduling of an SPU pod
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpuPod {
+    pub name: String,
+    /// Node the pod is scheduled on
+    pub node: Option<String>,
+    /// Whether the pod declares a pod anti-affinity
+    pub anti_affinity: bool,
+    /// Reason the pod cannot be scheduled, if pending
+    pub unschedulable: Option<String>,
+    /// Storage capacity of the data volume
+    pub storage: Option<String>,
+}
+
+/// A problem found in the scheduling of the SPU pods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpuSchedulingIssue {
+    /// Multiple SPUs without anti-affinity are scheduled on the same node
+    SharedNode { node: String, spus: Vec<String> },
+    /// SPUs have data volumes of different capacities, by capacity
+    StorageMismatch {
+        capacities: BTreeMap<String, Vec<String>>,
+    },
+    /// The SPU is pending because no node satisfies its constraints
+    Unschedulable { spu: String, reason: String },
+}
+
+impl SpuSchedulingIssue {
+    /// Issues which don't prevent the cluster from working
+    pub fn is_warning(&self) -> bool {
+        !matches!(self, Self::Unschedulable { .. })
+    }
+
+    /// Helm values of the `fluvio-app` chart addressing the issue
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            Self::SharedNode { .. } => {
+                "Add nodes or pin each SPU group to its own nodes with the `spuPod.nodeSelector` helm value"
+            }
+            Self::StorageMismatch { .. } => {
+                "Use the same `spuPod.storageClass` helm value and storage size for every SPU group"
+            }
+            Self::Unschedulable { .. } => {
+                "Relax the `spuPod.nodeSelector` and `spuPod.resources` helm values or add nodes matching them"
+            }
+        }
+    }
+}
+
+impl fmt::Display for SpuSchedulingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SharedNode { node, spus } => write!(
+                f,
+                "{} are scheduled on node {node} without anti-affinity",
+                spus.join(", ")
+            ),
+            Self::StorageMismatch { capacities } => {
+                let capacities: Vec<String> = capacities
+                    .iter()
+                    .map(|(capacity, spus)| format!("{capacity} for {}", spus.join(", ")))
+                    .collect();
+
+                write!(f, "storage capacity differs: {}", capacities.join(", "))
+            }
+            Self::Unschedulable { spu, reason } => write!(f, "{spu} is pending: {reason}"),
+        }
+    }
+}
+
+/// Finds the scheduling issues of `pods`
+pub fn inspect_spu_pods(pods: &[SpuPod]) -> Vec<SpuSchedulingIssue> {
+    let mut issues = Vec::new();
+    let mut by_node: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut capacities: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for pod in pods {
+        if let Some(reason) = &pod.unschedulable {
+            issues.push(SpuSchedulingIssue::Unschedulable {
+                spu: pod.name.clone(),
+                reason: reason.clone(),
+            });
+        }
+
+        if let Some(node) = &pod.node
+            && !pod.anti_affinity
+        {
+            by_node.entry(node).or_default().push(pod.name.clone());
+        }
+
+        if let Some(storage) = &pod.storage {
+            capacities
+                .entry(storage.clone())
+                .or_default()
+                .push(pod.name.clone());
+        }
+    }
+
+    for (node, spus) in by_node {
+        if spus.len() > 1 {
+            issues.push(SpuSchedulingIssue::SharedNode {
+                node: node.to_string(),
+                spus,
+            });
+        }
+    }
+
+    if capacities.len() > 1 {
+        issues.push(SpuSchedulingIssue::StorageMismatch { capacities });
+    }
+
+    issues
+}
+
+/// Checks the scheduling of the SPU pods in `namespace`, or in the namespace
+/// of the current context if `None`
+#[derive(Debug)]
+pub(crate) struct SpuSchedulingCheck {
+    namespace: Option<String>,
+}
+
+impl SpuSchedulingCheck {
+    pub(crate) fn new(namespace: Option<String>) -> Self {
+        Self { namespace }
+    }
+
+    fn kubectl_get<T: DeserializeOwned>(&self, args: &[&str]) -> Result<List<T>> {
+        let mut command = Command::new("kubectl");
+
+        command.arg("get").args(args).arg("-o=json");
+
+        if let Some(namespace) = &self.namespace {
+            command.arg("--namespace").arg(namespace);
+        }
+
+        let output = command
+            .output()
+            .map_err(ClusterCheckError::KubectlNotFoundError)?;
+
+        if !output.status.success() {
+            return Err(ClusterCheckError::Other(format!(
+                "kubectl get {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+            .into());
+        }
+
+        serde_json::from_slice(&output.stdout).map_err(|err| {
+            ClusterCheckError::Other(format!("Unable to parse kubectl output: {err}")).into()
+        })
+    }
+
+    fn spu_pods(&self) -> Result<Vec<SpuPod>> {
+        let pods: List<Pod> =
+            self.kubectl_get(&["pods", "--selector", &format!("app={SPU_DEFAULT_NAME}")])?;
+        let claims: List<Claim> = self.kubectl_get(&["persistentvolumeclaims"])?;
+        let capacities: HashMap<String, String> = claims
+            .items
+            .into_iter()
+            .filter_map(|claim| {
+                let capacity = claim
+                    .status
+                    .capacity
+                    .or(claim.spec.resources.requests)?
+                    .remove("storage")?;
+
+                Some((claim.metadata.name, capacity))
+            })
+            .collect();
+
+        Ok(pods
+            .items
+            .into_iter()
+            .map(|pod| SpuPod::from_pod(pod, &capacities))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ClusterCheck for SpuSchedulingCheck {
+    async fn perform_check(&self, _pb: &ProgressRenderer) -> CheckResult {
+        let pods = self.spu_pods()?;
+
+        if pods.is_empty() {
+            return Ok(CheckStatus::pass("No SPU pods found"));
+        }
+
+        let issues = inspect_spu_pods(&pods);
+        let suggestions = |issues: &[SpuSchedulingIssue]| {
+            let mut suggestions: Vec<&str> =
+                issues.iter().map(|issue| issue.suggestion()).collect();
+
+            suggestions.dedup();
+            suggestions.join(". ")
+        };
+        let (warnings, errors): (Vec<_>, Vec<_>) =
+            issues.into_iter().partition(|issue| issue.is_warning());
+
+        if !errors.is_empty() {
+            let issues: Vec<SpuSchedulingIssue> = errors.into_iter().chain(warnings).collect();
+
+            return Ok(CheckStatus::Unrecoverable(
+                UnrecoverableCheckStatus::SpuScheduling {
+                    issues: issues
+                        .iter()
+                        .map(|issue| issue.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; "),
+                    suggestion: suggestions(&issues),
+                },
+            ));
+        }
+
+        if !warnings.is_empty() {
+            return Ok(CheckStatus::warning(format!(
+                "{} SPUs are scheduled, but {}. {}",
+                pods.len(),
+                warnings
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; "),
+                suggestions(&warnings)
+            )));
+        }
+
+        Ok(CheckStatus::pass(format!(
+            "{} SPUs are scheduled on distinct nodes",
+            pods.len()
+        )))
+    }
+
+    fn required_components(&self) -> Vec<FluvioClusterComponent> {
+        vec![FluvioClusterComponent::Kubernetes]
+    }
+
+    fn label(&self) -> &str {
+        "SPU scheduling"
+    }
+}
+
+impl SpuPod {
+    fn from_pod(pod: Pod, capacities: &HashMap<String, String>) -> Self {
+        let unschedulable = pod
+            .status
+            .conditions
+            .into_iter()
+            .find(|condition| {
+                condition.r#type == "PodScheduled"
+                    && condition.status == "False"
+                    && condition.reason.as_deref() == Some("Unschedulable")
+            })
+            .map(|condition| {
+                condition
+                    .message
+                    .unwrap_or_else(|| "unschedulable".to_string())
+            });
+        let storage = pod
+            .spec
+            .volumes
+            .iter()
+            .filter(|volume| volume.name == SPU_DATA_VOLUME)
+            .find_map(|volume| volume.persistent_volume_claim.as_ref())
+            .and_then(|claim| capacities.get(&claim.claim_name).cloned());
+
+        Self {
+            name: pod.metadata.name,
+            node: pod.spec.node_name,
+            anti_affinity: pod
+                .spec
+                .affinity
+                .is_some_and(|affinity| affinity.pod_anti_affinity.is_some()),
+            unschedulable,
+            storage,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ObjectMeta {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pod {
+    metadata: ObjectMeta,
+    spec: PodSpec,
+    #[serde(default)]
+    status: PodStatus,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PodSpec {
+    node_name: Option<String>,
+    affinity: Option<Affinity>,
+    #[serde(default)]
+    volumes: Vec<Volume>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Affinity {
+    pod_anti_affinity: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Volume {
+    name: String,
+    persistent_volume_claim: Option<ClaimSource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimSource {
+    claim_name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PodStatus {
+    #[serde(default)]
+    conditions: Vec<PodCondition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PodCondition {
+    r#type: String,
+    status: String,
+    reason: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claim {
+    metadata: ObjectMeta,
+    #[serde(default)]
+    spec: ClaimSpec,
+    #[serde(default)]
+    status: ClaimStatus,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClaimSpec {
+    #[serde(default)]
+    resources: ClaimResources,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClaimResources {
+    requests: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClaimStatus {
+    capacity: Option<HashMap<String, String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spu(name: &str, node: &str, storage: &str) -> SpuPod {
+        SpuPod {
+            name: name.to_string(),
+            node: Some(node.to_string()),
+            anti_affinity: false,
+            unschedulable: None,
+            storage: Some(storage.to_string()),
+        }
+    }
+
+    #[test]
+    fn passes_spread_spus() {
+        let pods = vec![
+            spu("fluvio-spg-main-0", "node-a", "10Gi"),
+            spu("fluvio-spg-main-1", "node-b", "10Gi"),
+        ];
+
+        assert!(inspect_spu_pods(&pods).is_empty());
+    }
+
+    #[test]
+    fn flags_shared_nodes_without_anti_affinity() {
+        let mut pods = vec![
+            spu("fluvio-spg-main-0", "node-a", "10Gi"),
+            spu("fluvio-spg-main-1", "node-a", "10Gi"),
+        ];
+
+        assert_eq!(
+            inspect_spu_pods(&pods),
+            vec![SpuSchedulingIssue::SharedNode {
+                node: "node-a".to_string(),
+                spus: vec![
+                    "fluvio-spg-main-0".to_string(),
+                    "fluvio-spg-main-1".to_string()
+                ],
+            }]
+        );
+
+        pods[0].anti_affinity = true;
+        assert!(inspect_spu_pods(&pods).is_empty());
+    }
+
+    #[test]
+    fn flags_storage_mismatch_and_pending_spus() {
+        let mut pending = spu("fluvio-spg-main-2", "", "10Gi");
+
+        pending.node = None;
+        pending.unschedulable = Some("0/3 nodes are available".to_string());
+
+        let pods = vec![
+            spu("fluvio-spg-main-0", "node-a", "10Gi"),
+            spu("fluvio-spg-main-1", "node-b", "20Gi"),
+            pending,
+        ];
+        let issues = inspect_spu_pods(&pods);
+
+        assert_eq!(issues.len(), 2);
+        assert!(!issues[0].is_warning());
+        assert_eq!(
+            issues[1].to_string(),
+            "storage capacity differs: 10Gi for fluvio-spg-main-0, fluvio-spg-main-2, 20Gi for fluvio-spg-main-1"
+        );
+    }
+
+    #[test]
+    fn reads_spu_pods_from_kubectl_output() {
+        let pods: List<Pod> = serde_json::from_str(
+            r#"{"items": [{
+                "metadata": {"name": "fluvio-spg-main-0"},
+                "spec": {
+                    "nodeName": "node-a",
+                    "affinity": {"podAntiAffinity": {}},
+                    "volumes": [{"name": "data", "persistentVolumeClaim": {"claimName": "data-fluvio-spg-main-0"}}]
+                },
+                "status": {"phase": "Running", "conditions": [{"type": "Ready", "status": "True"}]}
+            }]}"#,
+        )
+        .unwrap();
+        let capacities =
+            HashMap::from([("data-fluvio-spg-main-0".to_string(), "10Gi".to_string())]);
+        let pod = SpuPod::from_pod(pods.items.into_iter().next().unwrap(), &capacities);
+
+        assert_eq!(
+            pod,
+            SpuPod {
+                name: "fluvio-spg-main-0".to_string(),
+                node: Some("node-a".to_string()),
+                anti_affinity: true,
+                unschedulable: None,
+                storage: Some("10Gi".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,796 @@
+//! TLS certificate checks
+//!
+//! Validates the certificates configured for a profile before they surface as
+//! opaque handshake failures: the certificate chain up to the configured CA,
+//! validity dates and, for server certificates, whether the certificate is
+//! valid for the host of the SC endpoint.
+//!
+//! Server certificates are read from the local cluster config when there is
+//! one, and fetched from the SC endpoint otherwise.
+
+use std::fmt;
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::Pem;
+use x509_parser::prelude::FromDer;
+
+use fluvio::config::{ConfigFile, TlsConfig, TlsPolicy};
+use fluvio_types::config_file::SaveLoadConfig;
+
+use crate::LocalConfig;
+use crate::render::ProgressRenderer;
+
+use super::{CheckResult, CheckStatus, ClusterCheck, UnrecoverableCheckStatus};
+
+/// Certificates expiring within this number of days are reported
+pub const CERT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Longest certificate chain followed up to the CA
+const MAX_CHAIN_DEPTH: usize = 10;
+
+/// Timeout of the connection fetching the server certificates
+const SERVER_CERT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A problem found in a certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertificateIssue {
+    /// The certificate could not be read or parsed
+    Unreadable { name: String, reason: String },
+    /// The certificate `not_after` date is in the past
+    Expired { name: String, subject: String },
+    /// The certificate `not_before` date is in the future
+    NotYetValid { name: String, subject: String },
+    /// The certificate expires within [`CERT_EXPIRY_WARNING_DAYS`]
+    ExpiresSoon {
+        name: String,
+        subject: String,
+        days: i64,
+    },
+    /// The certificate is not signed by the configured CA
+    UntrustedChain { name: String, reason: String },
+    /// The certificate is not valid for the host clients connect to
+    HostMismatch {
+        name: String,
+        host: String,
+        names: Vec<String>,
+    },
+    /// The certificate could not be fetched from the server, which may not
+    /// be running yet
+    Unreachable {
+        name: String,
+        endpoint: String,
+        reason: String,
+    },
+}
+
+impl CertificateIssue {
+    /// Issues which don't cause handshake failures yet
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Self::ExpiresSoon { .. } | Self::Unreachable { .. })
+    }
+}
+
+impl fmt::Display for CertificateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreadable { name, reason } => write!(f, "{name} could not be read: {reason}"),
+            Self::Expired { name, subject } => write!(f, "{name} ({subject}) has expired"),
+            Self::NotYetValid { name, subject } => {
+                write!(f, "{name} ({subject}) is not valid yet")
+            }
+            Self::ExpiresSoon {
+                name,
+                subject,
+                days,
+            } => write!(f, "{name} ({subject}) expires in {days} days"),
+            Self::UntrustedChain { name, reason } => {
+                write!(f, "{name} is not trusted by the CA: {reason}")
+            }
+            Self::HostMismatch { name, host, names } => write!(
+                f,
+                "{name} is not valid for {host}, it is valid for: {}",
+                names.join(", ")
+            ),
+            Self::Unreachable {
+                name,
+                endpoint,
+                reason,
+            } => write!(f, "{name} could not be fetched from {endpoint}: {reason}"),
+        }
+    }
+}
+
+/// Checks the TLS certificates of a profile and of the SC it connects to
+#[derive(Debug)]
+pub(crate) struct TlsCertificateCheck {
+    /// Profile to check, the current profile if `None`
+    profile: Option<String>,
+}
+
+impl TlsCertificateCheck {
+    pub(crate) fn new(profile: Option<String>) -> Self {
+        Self { profile }
+    }
+}
+
+#[async_trait]
+impl ClusterCheck for TlsCertificateCheck {
+    async fn perform_check(&self, _pb: &ProgressRenderer) -> CheckResult {
+        let config_file = ConfigFile::load_default_or_new()?;
+        let config = config_file.config();
+        let (profile, cluster) = match &self.profile {
+            Some(profile) => (
+                profile.clone(),
+                config
+                    .cluster_with_profile(profile)
+                    .ok_or_else(|| anyhow!("profile {profile} not found"))?,
+            ),
+            None => (
+                config
+                    .current_profile_name()
+                    .unwrap_or_default()
+                    .to_string(),
+                config.current_cluster()?,
+            ),
+        };
+
+        let TlsPolicy::Verified(client_tls) = &cluster.tls else {
+            return Ok(CheckStatus::pass(format!(
+                "Profile {profile} does not verify TLS certificates"
+            )));
+        };
+
+        let now = unix_now();
+        let host = endpoint_host(&cluster.endpoint);
+        let mut issues = inspect_tls_config("client certificate", client_tls, None, now);
+
+        // Local clusters may not be running yet, their server certificates
+        // are read from the local config instead
+        if let Some(local_config) = crate::start::local::LOCAL_CONFIG_PATH
+            .as_ref()
+            .and_then(|path| LocalConfig::load_from(path).ok())
+            && let TlsPolicy::Verified(server_tls) = local_config.server_tls_policy()
+        {
+            issues.extend(inspect_tls_config(
+                "server certificate",
+                server_tls,
+                Some(host),
+                now,
+            ));
+        } else {
+            issues.extend(inspect_server_certificate(
+                &cluster.endpoint,
+                client_tls,
+                host,
+                now,
+            ));
+        }
+
+        let (warnings, errors): (Vec<_>, Vec<_>) =
+            issues.into_iter().partition(|issue| issue.is_warning());
+
+        if !errors.is_empty() {
+            return Ok(CheckStatus::Unrecoverable(
+                UnrecoverableCheckStatus::InvalidTlsCertificates(
+                    errors
+                        .iter()
+                        .chain(warnings.iter())
+                        .map(|issue| issue.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; "),
+                ),
+            ));
+        }
+
+        if !warnings.is_empty() {
+            return Ok(CheckStatus::warning(format!(
+                "TLS certificates for profile {profile} are valid, but {}. Regenerate them before they expire",
+                warnings
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )));
+        }
+
+        Ok(CheckStatus::pass(format!(
+            "TLS certificates for profile {profile} are valid"
+        )))
+    }
+
+    fn label(&self) -> &str {
+        "TLS certificates"
+    }
+}
+
+/// Inspects the certificate and CA of a [`TlsConfig`]
+fn inspect_tls_config(
+    name: &str,
+    config: &TlsConfig,
+    host: Option<&str>,
+    now: i64,
+) -> Vec<CertificateIssue> {
+    let pems = match config {
+        TlsConfig::Inline(certs) => Ok(certs.cert.as_bytes().to_vec()),
+        TlsConfig::Files(paths) => {
+            std::fs::read(&paths.cert).map_err(|err| format!("{}: {err}", paths.cert.display()))
+        }
+    }
+    .and_then(|cert| read_ca_cert(config).map(|ca_cert| (cert, ca_cert)));
+
+    match pems {
+        Ok((cert, ca_cert)) => inspect_certificates(name, &cert, &ca_cert, host, now),
+        Err(reason) => vec![CertificateIssue::Unreadable {
+            name: name.to_string(),
+            reason,
+        }],
+    }
+}
+
+/// Inspects the certificates presented by the SC at `endpoint` against the
+/// CA of the client `config`
+fn inspect_server_certificate(
+    endpoint: &str,
+    config: &TlsConfig,
+    host: &str,
+    now: i64,
+) -> Vec<CertificateIssue> {
+    let name = "server certificate";
+    let ca_pem = match read_ca_cert(config) {
+        Ok(ca_pem) => ca_pem,
+        // Already reported for the client certificate
+        Err(_) => return Vec::new(),
+    };
+    let chain_ders = match fetch_server_chain(endpoint, config.domain()) {
+        Ok(chain) => chain,
+        Err(reason) => {
+            return vec![CertificateIssue::Unreachable {
+                name: name.to_string(),
+                endpoint: endpoint.to_string(),
+                reason,
+            }];
+        }
+    };
+    let unreadable = |reason: String| {
+        vec![CertificateIssue::Unreadable {
+            name: name.to_string(),
+            reason,
+        }]
+    };
+    let ca_ders = match read_pems(&ca_pem) {
+        Ok(ca_ders) => ca_ders,
+        Err(err) => return unreadable(format!("CA certificate: {err}")),
+    };
+
+    match (
+        parse_certificates(&chain_ders),
+        parse_certificates(&ca_ders),
+    ) {
+        (Ok(chain), Ok(cas)) => inspect_chain(name, &chain, &cas, Some(host), now),
+        (Err(err), _) => unreadable(err),
+        (_, Err(err)) => unreadable(format!("CA certificate: {err}")),
+    }
+}
+
+fn read_ca_cert(config: &TlsConfig) -> Result<Vec<u8>, String> {
+    match config {
+        TlsConfig::Inline(certs) => Ok(certs.ca_cert.as_bytes().to_vec()),
+        TlsConfig::Files(paths) => std::fs::read(&paths.ca_cert)
+            .map_err(|err| format!("{}: {err}", paths.ca_cert.display())),
+    }
+}
+
+/// Host of an SC endpoint such as `sc.fluvio.io:9003` or `[::1]:9003`
+fn endpoint_host(endpoint: &str) -> &str {
+    if let Some(bracketed) = endpoint.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or(bracketed);
+    }
+
+    match endpoint.split_once(':') {
+        Some((host, port)) if !port.contains(':') => host,
+        _ => endpoint,
+    }
+}
+
+/// Fetches the DER certificate chain presented by the server at `endpoint`,
+/// leaf first, sending `domain` as server name. The chain is not verified,
+/// it is inspected afterwards.
+fn fetch_server_chain(endpoint: &str, domain: &str) -> Result<Vec<Vec<u8>>, String> {
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let verifier = Arc::new(CaptureServerChain::new(provider.clone()));
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    let server_name = ServerName::try_from(domain.to_string()).map_err(|err| err.to_string())?;
+    let mut connection =
+        ClientConnection::new(Arc::new(config), server_name).map_err(|err| err.to_string())?;
+    let addr = endpoint
+        .to_socket_addrs()
+        .map_err(|err| err.to_string())?
+        .next()
+        .ok_or_else(|| "endpoint has no address".to_string())?;
+    let mut socket =
+        TcpStream::connect_timeout(&addr, SERVER_CERT_TIMEOUT).map_err(|err| err.to_string())?;
+
+    socket
+        .set_read_timeout(Some(SERVER_CERT_TIMEOUT))
+        .and_then(|()| socket.set_write_timeout(Some(SERVER_CERT_TIMEOUT)))
+        .map_err(|err| err.to_string())?;
+
+    // The handshake may fail once the chain is captured, servers requiring
+    // client certificates reject this connection
+    while connection.is_handshaking() && verifier.is_empty() {
+        if let Err(err) = connection.complete_io(&mut socket) {
+            if verifier.is_empty() {
+                return Err(err.to_string());
+            }
+            break;
+        }
+    }
+
+    let chain = verifier.take();
+
+    if chain.is_empty() {
+        return Err("the server presented no certificate".to_string());
+    }
+
+    Ok(chain)
+}
+
+/// Accepts any server certificate, keeping the chain presented by the server
+#[derive(Debug)]
+struct CaptureServerChain {
+    provider: Arc<CryptoProvider>,
+    chain: Mutex<Vec<Vec<u8>>>,
+}
+
+impl CaptureServerChain {
+    fn new(provider: Arc<CryptoProvider>) -> Self {
+        Self {
+            provider,
+            chain: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.chain.lock().map_or(true, |chain| chain.is_empty())
+    }
+
+    fn take(&self) -> Vec<Vec<u8>> {
+        self.chain
+            .lock()
+            .map(|mut chain| std::mem::take(&mut *chain))
+            .unwrap_or_default()
+    }
+}
+
+impl ServerCertVerifier for CaptureServerChain {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Ok(mut chain) = self.chain.lock() {
+            *chain = std::iter::once(end_entity)
+                .chain(intermediates)
+                .map(|cert| cert.to_vec())
+                .collect();
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Inspects a PEM certificate chain, leaf first, against PEM CA certificates.
+///
+/// When `host` is provided the leaf certificate must be valid for it.
+pub fn inspect_certificates(
+    name: &str,
+    cert_pem: &[u8],
+    ca_pem: &[u8],
+    host: Option<&str>,
+    now: i64,
+) -> Vec<CertificateIssue> {
+    let unreadable = |reason: String| {
+        vec![CertificateIssue::Unreadable {
+            name: name.to_string(),
+            reason,
+        }]
+    };
+    let (chain_ders, ca_ders) = match (read_pems(cert_pem), read_pems(ca_pem)) {
+        (Ok(chain), Ok(ca)) => (chain, ca),
+        (Err(err), _) => return unreadable(err),
+        (_, Err(err)) => return unreadable(format!("CA certificate: {err}")),
+    };
+
+    match (
+        parse_certificates(&chain_ders),
+        parse_certificates(&ca_ders),
+    ) {
+        (Ok(chain), Ok(cas)) => inspect_chain(name, &chain, &cas, host, now),
+        (Err(err), _) => unreadable(err),
+        (_, Err(err)) => unreadable(format!("CA certificate: {err}")),
+    }
+}
+
+/// Inspects a certificate `chain`, leaf first, against the `cas`
+fn inspect_chain(
+    name: &str,
+    chain: &[X509Certificate],
+    cas: &[X509Certificate],
+    host: Option<&str>,
+    now: i64,
+) -> Vec<CertificateIssue> {
+    let unreadable = |reason: String| {
+        vec![CertificateIssue::Unreadable {
+            name: name.to_string(),
+            reason,
+        }]
+    };
+    let Some(leaf) = chain.first() else {
+        return unreadable("no certificate found".to_string());
+    };
+
+    if cas.is_empty() {
+        return unreadable("no CA certificate found".to_string());
+    }
+
+    let mut issues = Vec::new();
+
+    for (cert_name, cert) in chain
+        .iter()
+        .enumerate()
+        .map(|(i, cert)| {
+            let cert_name = if i == 0 {
+                name.to_string()
+            } else {
+                format!("{name} intermediate")
+            };
+
+            (cert_name, cert)
+        })
+        .chain(cas.iter().map(|ca| ("CA certificate".to_string(), ca)))
+    {
+        issues.extend(validity_issue(&cert_name, cert, now));
+    }
+
+    if let Err(reason) = verify_chain(leaf, &chain[1..], cas) {
+        issues.push(CertificateIssue::UntrustedChain {
+            name: name.to_string(),
+            reason,
+        });
+    }
+
+    if let Some(host) = host {
+        let names = certificate_names(leaf);
+
+        if !names.iter().any(|pattern| host_matches(pattern, host)) {
+            issues.push(CertificateIssue::HostMismatch {
+                name: name.to_string(),
+                host: host.to_string(),
+                names,
+            });
+        }
+    }
+
+    issues
+}
+
+/// DER contents of the PEM certificates in `bytes`
+fn read_pems(bytes: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    Pem::iter_from_buffer(bytes)
+        .filter(|pem| pem.as_ref().map_or(true, |pem| pem.label == "CERTIFICATE"))
+        .map(|pem| pem.map(|pem| pem.contents))
+        .collect::<Result<Vec<Vec<u8>>, _>>()
+        .map_err(|err| format!("invalid PEM: {err}"))
+}
+
+fn parse_certificates(ders: &[Vec<u8>]) -> Result<Vec<X509Certificate<'_>>, String> {
+    ders.iter()
+        .map(|der| {
+            X509Certificate::from_der(der)
+                .map(|(_, cert)| cert)
+                .map_err(|err| format!("invalid certificate: {err}"))
+        })
+        .collect()
+}
+
+fn validity_issue(name: &str, cert: &X509Certificate, now: i64) -> Option<CertificateIssue> {
+    let validity = cert.validity();
+    let subject = cert.subject().to_string();
+    let name = name.to_string();
+
+    if validity.not_before.timestamp() > now {
+        return Some(CertificateIssue::NotYetValid { name, subject });
+    }
+
+    let remaining = validity.not_after.timestamp() - now;
+
+    if remaining < 0 {
+        Some(CertificateIssue::Expired { name, subject })
+    } else if remaining < CERT_EXPIRY_WARNING_DAYS * SECONDS_PER_DAY {
+        Some(CertificateIssue::ExpiresSoon {
+            name,
+            subject,
+            days: remaining / SECONDS_PER_DAY,
+        })
+    } else {
+        None
+    }
+}
+
+/// Follows issuers from `leaf` through `intermediates` until a certificate
+/// signed by one of the `cas` is found
+fn verify_chain(
+    leaf: &X509Certificate,
+    intermediates: &[X509Certificate],
+    cas: &[X509Certificate],
+) -> Result<(), String> {
+    let mut current = leaf;
+
+    for _ in 0..MAX_CHAIN_DEPTH {
+        if cas.iter().any(|ca| is_issued_by(current, ca)) {
+            return Ok(());
+        }
+
+        current = intermediates
+            .iter()
+            .find(|intermediate| is_issued_by(current, intermediate))
+            .ok_or_else(|| format!("issuer {} not found", current.issuer()))?;
+    }
+
+    Err("certificate chain is too long".to_string())
+}
+
+fn is_issued_by(cert: &X509Certificate, issuer: &X509Certificate) -> bool {
+    cert.issuer() == issuer.subject() && cert.verify_signature(Some(issuer.public_key())).is_ok()
+}
+
+/// DNS names and IP addresses the certificate is valid for, falling back to
+/// the subject common name without a subject alternative name extension
+fn certificate_names(cert: &X509Certificate) -> Vec<String> {
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        return san
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => <[u8; 4]>::try_from(*bytes)
+                        .ok()
+                        .map(|ip| IpAddr::from(ip).to_string()),
+                    16 => <[u8; 16]>::try_from(*bytes)
+                        .ok()
+                        .map(|ip| IpAddr::from(ip).to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+    }
+
+    cert.subject()
+        .iter_common_name()
+        .filter_map(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string())
+        .collect()
+}
+
+/// Matches `host` against a certificate name, supporting `*.` wildcards for
+/// a single leftmost label
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == host,
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA_CERT: &str = include_str!("../../../fluvio-socket/certs/certs/ca.crt");
+    const SERVER_CERT: &str = include_str!("../../../fluvio-socket/certs/certs/server.crt");
+
+    // 2020-11-01T00:00:00Z, the CA expires on 2020-11-20
+    const NOV_2020: i64 = 1_604_188_800;
+
+    #[test]
+    fn test_inspect_valid_certificates() {
+        let issues = inspect_certificates(
+            "server certificate",
+            SERVER_CERT.as_bytes(),
+            CA_CERT.as_bytes(),
+            Some("second.testserver.com"),
+            NOV_2020,
+        );
+
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(matches!(
+            &issues[0],
+            CertificateIssue::ExpiresSoon { name, days: 19, .. } if name == "CA certificate"
+        ));
+        assert!(issues[0].is_warning());
+    }
+
+    #[test]
+    fn test_inspect_invalid_certificates() {
+        let issues = inspect_certificates(
+            "server certificate",
+            SERVER_CERT.as_bytes(),
+            SERVER_CERT.as_bytes(),
+            Some("fluvio.example.com"),
+            NOV_2020 + 365 * SECONDS_PER_DAY,
+        );
+
+        assert!(
+            issues
+                .iter()
+                .any(|issue| matches!(issue, CertificateIssue::UntrustedChain { .. }))
+        );
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            CertificateIssue::HostMismatch { names, .. } if names.contains(&"localhost".to_string())
+        )));
+        assert!(
+            !issues
+                .iter()
+                .any(|issue| matches!(issue, CertificateIssue::Expired { .. }))
+        );
+
+        let issues = inspect_certificates(
+            "client certificate",
+            SERVER_CERT.as_bytes(),
+            CA_CERT.as_bytes(),
+            None,
+            NOV_2020 + 5 * 365 * SECONDS_PER_DAY,
+        );
+
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|issue| matches!(issue, CertificateIssue::Expired { .. }))
+                .count(),
+            2
+        );
+        assert!(matches!(
+            inspect_certificates(
+                "client certificate",
+                b"",
+                CA_CERT.as_bytes(),
+                None,
+                NOV_2020
+            )
+            .as_slice(),
+            [CertificateIssue::Unreadable { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_endpoint_host() {
+        assert_eq!(endpoint_host("sc.fluvio.io:9003"), "sc.fluvio.io");
+        assert_eq!(endpoint_host("127.0.0.1:9003"), "127.0.0.1");
+        assert_eq!(endpoint_host("[::1]:9003"), "::1");
+        assert_eq!(endpoint_host("localhost"), "localhost");
+    }
+
+    #[test]
+    fn test_unreachable_server_is_a_warning() {
+        let config = TlsConfig::Inline(fluvio::config::TlsCerts {
+            domain: "fluvio.local".to_string(),
+            key: String::new(),
+            cert: SERVER_CERT.to_string(),
+            ca_cert: CA_CERT.to_string(),
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+
+        drop(listener);
+
+        let issues = inspect_server_certificate(&endpoint, &config, "127.0.0.1", NOV_2020);
+
+        assert!(
+            matches!(issues.as_slice(), [CertificateIssue::Unreachable { .. }]),
+            "{issues:?}"
+        );
+        assert!(issues[0].is_warning());
+    }
+
+    #[test]
+    fn test_fetch_server_chain() {
+        use rustls::ServerConnection;
+        use rustls::pki_types::PrivateKeyDer;
+        use rustls::pki_types::pem::PemObject;
+
+        const SERVER_KEY: &str = include_str!("../../../fluvio-socket/certs/certs/server.key");
+
+        let chain = read_pems(SERVER_CERT.as_bytes()).unwrap();
+        let server_config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            chain.iter().cloned().map(CertificateDer::from).collect(),
+            PrivateKeyDer::from_pem_slice(SERVER_KEY.as_bytes()).unwrap(),
+        )
+        .unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut connection = ServerConnection::new(Arc::new(server_config)).unwrap();
+
+            while connection.is_handshaking() && connection.complete_io(&mut socket).is_ok() {}
+        });
+
+        let fetched = fetch_server_chain(&endpoint, "localhost").unwrap();
+
+        server.join().unwrap();
+        assert_eq!(fetched, chain);
+    }
+
+    #[test]
+    fn test_host_matches() {
+        assert!(host_matches("*.fluvio.io", "sc.fluvio.io"));
+        assert!(!host_matches("*.fluvio.io", "fluvio.io"));
+        assert!(!host_matches("*.fluvio.io", "a.sc.fluvio.io"));
+        assert!(host_matches("LOCALHOST", "localhost"));
+    }
+}
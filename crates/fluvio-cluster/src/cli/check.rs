@@ -1,48 +1,102 @@
+use std::path::PathBuf;
+
 use anyhow::bail;
 use anyhow::Result;
 use fluvio_extension_common::installation::InstallationType;
 use semver::Version;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use once_cell::sync::Lazy;
 use tracing::debug;
 
 use crate::progress::ProgressBarFactory;
 use crate::{ClusterChecker, cli::get_installation_type};
-use crate::check::{SysChartCheck, ClusterCheckError};
+use crate::check::{SysChartCheck, ClusterCheckError, LocalResources};
+use crate::check::events::JsonLinesStdout;
+use crate::check::report::{CheckReport, Severity};
 use crate::charts::ChartConfig;
 
+/// Most recent check report, compared with by `fluvio cluster check --diff`
+pub static CHECK_REPORT_PATH: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    directories::BaseDirs::new().map(|it| it.home_dir().join(".fluvio/check-report.json"))
+});
+
+/// How the progress of the checks is reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CheckProgressFormat {
+    /// Progress rendered for terminals
+    #[default]
+    Human,
+    /// One JSON object per progress event on stdout, for embedding the
+    /// checks in other tools
+    Jsonl,
+}
+
 #[derive(Debug, Parser)]
 pub struct CheckOpt {
     /// Attempt to fix recoverable errors
     #[arg(long)]
     fix: bool,
+    /// Profile to check TLS certificates for, defaults to the current profile
+    #[arg(long)]
+    profile: Option<String>,
+    /// Show which checks changed status since the previous run
+    #[arg(long)]
+    diff: bool,
+    /// Kubernetes namespace of the SPU pods, defaults to the namespace of the
+    /// current context
+    #[arg(long, value_name = "Kubernetes namespace")]
+    namespace: Option<String>,
+    /// Run a short-lived pod to check the cluster can reach the hub and
+    /// artifact endpoints
+    #[arg(long)]
+    egress: bool,
+    /// Format of the progress output
+    #[arg(long, value_enum, default_value_t)]
+    progress: CheckProgressFormat,
+    /// Lowest severity of check results failing the command, `warning` lets
+    /// CI enforce a stricter bar than interactive use
+    #[arg(long, value_enum, value_name = "SEVERITY", default_value_t)]
+    fail_on: Severity,
 }
 
 impl CheckOpt {
     pub async fn process(self, platform_version: Version) -> Result<()> {
         use colored::*;
-        println!("{}", "Running pre-startup checks...".bold());
-        println!(
-            "{}",
-            "Note: This may require admin access to current Kubernetes context"
-                .bold()
-                .yellow()
-        );
+
+        let jsonl = self.progress == CheckProgressFormat::Jsonl;
+
+        if !jsonl {
+            println!("{}", "Running pre-startup checks...".bold());
+            println!(
+                "{}",
+                "Note: This may require admin access to current Kubernetes context"
+                    .bold()
+                    .yellow()
+            );
+        }
         let (installation_ty, config) = get_installation_type()?;
         debug!(?installation_ty);
 
-        let checker = match installation_ty {
+        let mut checker = match installation_ty {
             InstallationType::K8 => {
                 let sys_config: ChartConfig =
                     ChartConfig::sys_builder().build().map_err(|err| {
                         ClusterCheckError::Other(format!("chart config error: {err:#?}"))
                     })?;
-                ClusterChecker::empty()
+                let checker = ClusterChecker::empty()
                     .with_preflight_checks()
                     .with_check(SysChartCheck::new(sys_config, platform_version))
+                    .with_spu_scheduling(self.namespace.clone());
+
+                if self.egress {
+                    checker.with_outbound_connectivity(self.namespace.clone())
+                } else {
+                    checker
+                }
             }
-            InstallationType::Local | InstallationType::ReadOnly => {
-                ClusterChecker::empty().with_no_k8_checks()
-            }
+            InstallationType::Local | InstallationType::ReadOnly => ClusterChecker::empty()
+                .with_no_k8_checks()
+                .with_local_resources(LocalResources::default()),
             InstallationType::LocalK8 => ClusterChecker::empty().with_local_checks(),
             InstallationType::Cloud => {
                 let profile = config.config().current_profile_name().unwrap_or("none");
@@ -52,12 +106,53 @@ impl CheckOpt {
             }
 
             _other => ClusterChecker::empty(),
-        };
+        }
+        .with_tls_certificates(self.profile)
+        .with_fail_on(self.fail_on);
+
+        // In JSON lines mode stdout only carries events, the plain progress
+        // is still rendered to stderr
+        if jsonl {
+            checker = checker.with_event_sink(JsonLinesStdout);
+        }
 
-        let pb = ProgressBarFactory::new(false);
+        let pb = ProgressBarFactory::new(jsonl);
 
-        checker.run(&pb, self.fix).await?;
+        let report = checker.run_with_report(&pb, self.fix).await?;
+
+        if let Some(report_path) = CHECK_REPORT_PATH.as_ref() {
+            if self.diff && !jsonl {
+                match CheckReport::load(report_path)? {
+                    Some(previous) => print_diff(&report, &previous),
+                    None => println!("No previous check report to compare with"),
+                }
+            }
+
+            report.save(report_path)?;
+        }
+
+        ClusterChecker::finish_with_policy(&pb, &report, self.fail_on)?;
 
         Ok(())
     }
 }
+
+fn print_diff(report: &CheckReport, previous: &CheckReport) {
+    use colored::*;
+
+    let changes = report.diff(previous);
+
+    if changes.is_empty() {
+        println!("No checks changed status since {}", previous.created_at);
+        return;
+    }
+
+    println!(
+        "{}",
+        format!("Changes since {}:", previous.created_at).bold()
+    );
+
+    for change in changes {
+        println!("    {change}");
+    }
+}
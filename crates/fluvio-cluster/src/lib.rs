@@ -46,7 +46,8 @@ pub use start::local::{LocalInstaller, LocalConfig, LocalConfigBuilder};
 pub use error::{ClusterError, K8InstallError, LocalInstallError, UninstallError};
 pub use helm::HelmError;
 pub use check::{ClusterChecker, CheckStatus, CheckStatuses, CheckResult, CheckResults};
-pub use check::{RecoverableCheck, UnrecoverableCheckStatus, CheckSuggestion};
+pub use check::{RecoverableCheck, UnrecoverableCheckStatus, CheckSuggestion, LocalResources};
+pub use check::events::{CheckEvent, CheckEventSink, JsonLinesStdout};
 pub use delete::*;
 pub use fluvio::config as fluvio_config;
 pub use fluvio_extension_common::installation::InstallationType;
@@ -2,6 +2,8 @@ use std::{borrow::Cow, time::Duration};
 
 use indicatif::{ProgressBar, ProgressStyle};
 
+use fluvio_progress::{ProgressGroup, ProgressMode, ProgressOutput};
+
 use crate::render::{ProgressRenderedText, ProgressRenderer};
 
 use anyhow::Result;
@@ -75,13 +77,22 @@ impl ProgressBarFactory {
 
     /// create new progress bar
     pub fn create(&self) -> Result<ProgressRenderer> {
-        if self.hide || std::env::var("CI").is_ok() {
-            Ok(Default::default())
-        } else {
-            Ok(create_spinning_indicator()?.into())
+        match self.mode() {
+            ProgressMode::Lines => Ok(Default::default()),
+            ProgressMode::Bars => Ok(create_spinning_indicator()?.into()),
         }
     }
 
+    /// create a group of `total` tasks summarized as `label`, rendered like
+    /// the progress of FVM installs
+    pub fn group(&self, label: impl Into<String>, total: usize) -> ProgressGroup {
+        ProgressGroup::new(label, total, self.mode(), ProgressOutput::Stderr)
+    }
+
+    fn mode(&self) -> ProgressMode {
+        ProgressMode::detect(self.hide, ProgressOutput::Stderr)
+    }
+
     /// simple print
     pub fn println(&self, msg: impl Into<Cow<'static, str>>) {
         self.plain.println(msg);
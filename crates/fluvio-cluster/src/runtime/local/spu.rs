@@ -82,6 +82,17 @@ impl SpuTarget for LocalSpuProcess {
 const BASE_PORT: u16 = 9010;
 const BASE_SPU: u16 = 5001;
 
+/// Public and private ports of the first `spus` local SPUs
+pub(crate) fn local_spu_ports(spus: u16) -> Vec<(u16, u16)> {
+    (0..spus)
+        .map(|spu_index| {
+            let public_port = BASE_PORT + spu_index * 10;
+
+            (public_port, public_port + 1)
+        })
+        .collect()
+}
+
 /// Manage SPU Process Cluster
 pub struct LocalSpuProcessClusterManager {
     pub log_dir: PathBuf,
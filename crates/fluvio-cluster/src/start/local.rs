@@ -17,13 +17,14 @@ use fluvio_controlplane_metadata::spu::{SpuSpec, CustomSpuSpec};
 use fluvio_future::timer::sleep;
 use fluvio_command::CommandExt;
 use fluvio_types::config_file::SaveLoadConfig;
+use fluvio_types::defaults::{SC_PRIVATE_PORT, SC_PUBLIC_PORT};
 use k8_types::{InputK8Obj, InputObjectMeta};
 use k8_client::SharedK8Client;
 
 use crate::render::{ProgressRenderedText, ProgressRenderer};
 use crate::{ClusterChecker, LocalInstallError, StartStatus, UserChartLocation, InstallationType};
 use crate::charts::ChartConfig;
-use crate::check::{SysChartCheck, ClusterCheckError};
+use crate::check::{SysChartCheck, ClusterCheckError, LocalResources};
 use crate::runtime::local::{LocalSpuProcessClusterManager, ScProcess, ScMode};
 use crate::progress::{InstallProgressMessage, ProgressBarFactory};
 
@@ -214,6 +215,11 @@ impl LocalConfig {
         self.launcher.as_deref()
     }
 
+    /// The TLS policy for the SC and SPU servers
+    pub fn server_tls_policy(&self) -> &TlsPolicy {
+        &self.server_tls_policy
+    }
+
     pub fn as_spu_cluster_manager(&self) -> LocalSpuProcessClusterManager {
         LocalSpuProcessClusterManager {
             log_dir: self.log_dir.to_owned(),
@@ -391,6 +397,23 @@ impl LocalInstaller {
         }
     }
 
+    /// Ports and SPUs of the cluster to start, the SC ports default to the
+    /// ports the SC listens on without an address
+    fn local_resources(&self) -> LocalResources {
+        let port = |address: Option<&String>, default: u16| {
+            address
+                .and_then(|address| address.rsplit(':').next())
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(default)
+        };
+
+        LocalResources::new(
+            port(Some(&self.config.sc_pub_addr), SC_PUBLIC_PORT),
+            port(self.config.sc_priv_addr.as_ref(), SC_PRIVATE_PORT),
+            self.config.spu_replicas,
+        )
+    }
+
     /// Checks if all of the prerequisites for installing Fluvio locally are met
     /// and tries to auto-fix the issues observed
     pub async fn preflight_check(&self, fix: bool) -> Result<()> {
@@ -401,6 +424,7 @@ impl LocalInstaller {
 
                 ClusterChecker::empty()
                     .with_no_k8_checks()
+                    .with_local_resources(self.local_resources())
                     .run(&self.pb_factory, fix)
                     .await?;
 
@@ -420,6 +444,7 @@ impl LocalInstaller {
                     .println(InstallProgressMessage::PreFlightCheck.msg());
                 ClusterChecker::empty()
                     .with_local_checks()
+                    .with_local_resources(self.local_resources())
                     .with_check(SysChartCheck::new(
                         sys_config,
                         self.config.platform_version.clone(),
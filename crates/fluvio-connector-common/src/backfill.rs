@@ -0,0 +1,489 @@
+//! Resumable bulk backfill for source connectors.
+//!
+//! A [`Backfill`] loads historical data from a [`BackfillSource`] in chunks
+//! before handing off to the live stream. Once the records emitted so far
+//! are produced and flushed, the connector calls [`BackfillStream::commit`]
+//! to save the progress of the fully emitted chunks to a [`CheckpointStore`],
+//! so a restarted connector resumes from the last committed chunk instead of
+//! starting over. Chunks may be emitted more than once if the connector
+//! stops before committing.
+//!
+//! ```ignore
+//! let mut stream = backfill.into_stream(live);
+//!
+//! while let Some(record) = stream.next().await {
+//!     producer.send(RecordKey::NULL, record?).await?;
+//!     producer.flush().await?;
+//!     stream.commit().await?;
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fluvio_future::timer::sleep;
+use futures::stream::{LocalBoxStream, Stream, unfold};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::Result;
+use crate::checkpoint::{CheckpointStore, load_json, save_json};
+
+const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BackfillConfig {
+    /// Max number of records requested per chunk
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// Pause between chunks, only applies while backfilling
+    #[serde(default, with = "humantime_serde")]
+    pub chunk_delay: Duration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            chunk_delay: Duration::ZERO,
+        }
+    }
+}
+
+fn default_chunk_size() -> usize {
+    DEFAULT_CHUNK_SIZE
+}
+
+/// Backfill progress persisted in the checkpoint store
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BackfillCheckpoint<P> {
+    /// Position after the last committed chunk, `None` if no chunk was
+    /// committed
+    pub position: Option<P>,
+    pub chunks: u64,
+    pub records: u64,
+    /// Set once the source caught up, the live stream is used from then on
+    pub completed: bool,
+}
+
+impl<P> Default for BackfillCheckpoint<P> {
+    fn default() -> Self {
+        Self {
+            position: None,
+            chunks: 0,
+            records: 0,
+            completed: false,
+        }
+    }
+}
+
+/// Chunk of historical records returned by a [`BackfillSource`]
+#[derive(Debug)]
+pub struct BackfillChunk<I, P> {
+    pub items: Vec<I>,
+    /// Position after the last item, `None` keeps the previous position
+    pub position: Option<P>,
+    /// `true` when there is no more historical data to load
+    pub caught_up: bool,
+}
+
+#[async_trait(?Send)]
+pub trait BackfillSource {
+    type Item;
+    type Position: Clone + Serialize + DeserializeOwned;
+
+    /// Fetches up to `limit` records following `after`, or from the
+    /// beginning of history if `after` is `None`
+    async fn fetch_chunk(
+        &mut self,
+        after: Option<&Self::Position>,
+        limit: usize,
+    ) -> Result<BackfillChunk<Self::Item, Self::Position>>;
+}
+
+pub struct Backfill<S, C> {
+    key: String,
+    source: S,
+    store: Rc<C>,
+    config: BackfillConfig,
+}
+
+impl<S, C> Backfill<S, C>
+where
+    S: BackfillSource,
+    C: CheckpointStore,
+{
+    /// Creates a backfill storing its progress under `key`
+    pub fn new(key: impl Into<String>, source: S, store: C, config: BackfillConfig) -> Self {
+        Self {
+            key: key.into(),
+            source,
+            store: Rc::new(store),
+            config,
+        }
+    }
+
+    /// Returns the persisted progress, or the initial progress if the
+    /// backfill never ran
+    pub async fn checkpoint(&self) -> Result<BackfillCheckpoint<S::Position>> {
+        Ok(load_json(&*self.store, &self.key).await?.unwrap_or_default())
+    }
+
+    /// Emits the historical records followed by the records from `live`.
+    ///
+    /// `live` is not polled until the backfill caught up. The stream ends
+    /// after yielding an error. Progress is only saved by
+    /// [`BackfillStream::commit`].
+    pub fn into_stream<'a>(
+        self,
+        live: LocalBoxStream<'a, S::Item>,
+    ) -> BackfillStream<'a, S::Item, S::Position, C>
+    where
+        S: 'a,
+        C: 'a,
+    {
+        let emitted = Rc::new(RefCell::new(None));
+        let store = self.store.clone();
+        let key = self.key.clone();
+        let state = BackfillState {
+            backfill: self,
+            checkpoint: None,
+            buffer: VecDeque::new(),
+            pending: None,
+            emitted: emitted.clone(),
+            live,
+            failed: false,
+        };
+
+        let stream = unfold(state, |mut state| async move {
+            match state.next().await {
+                Ok(Some(item)) => Some((Ok(item), state)),
+                Ok(None) => None,
+                Err(err) => {
+                    state.failed = true;
+                    Some((Err(err), state))
+                }
+            }
+        })
+        .boxed_local();
+
+        BackfillStream {
+            stream,
+            emitted,
+            store,
+            key,
+        }
+    }
+}
+
+/// Records of a [`Backfill`], see [`Backfill::into_stream`]
+pub struct BackfillStream<'a, I, P, C> {
+    stream: LocalBoxStream<'a, Result<I>>,
+    /// Progress of the chunks emitted entirely, not committed yet
+    emitted: Rc<RefCell<Option<BackfillCheckpoint<P>>>>,
+    store: Rc<C>,
+    key: String,
+}
+
+impl<I, P, C> BackfillStream<'_, I, P, C>
+where
+    P: Serialize,
+    C: CheckpointStore,
+{
+    /// Saves the progress of the chunks emitted entirely so far. Call it
+    /// once the records received from the stream are produced and flushed.
+    pub async fn commit(&self) -> Result<()> {
+        let Some(checkpoint) = self.emitted.borrow_mut().take() else {
+            return Ok(());
+        };
+
+        if let Err(err) = save_json(&*self.store, &self.key, &checkpoint).await {
+            self.emitted.borrow_mut().get_or_insert(checkpoint);
+            return Err(err);
+        }
+
+        debug!(
+            chunks = checkpoint.chunks,
+            records = checkpoint.records,
+            "Backfill checkpoint saved"
+        );
+
+        Ok(())
+    }
+}
+
+impl<I, P, C> Stream for BackfillStream<'_, I, P, C> {
+    type Item = Result<I>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().stream.poll_next_unpin(cx)
+    }
+}
+
+struct BackfillState<'a, S: BackfillSource, C> {
+    backfill: Backfill<S, C>,
+    checkpoint: Option<BackfillCheckpoint<S::Position>>,
+    buffer: VecDeque<S::Item>,
+    /// Progress reached once the buffered chunk has been emitted
+    pending: Option<BackfillCheckpoint<S::Position>>,
+    /// Progress to save on the next commit
+    emitted: Rc<RefCell<Option<BackfillCheckpoint<S::Position>>>>,
+    live: LocalBoxStream<'a, S::Item>,
+    failed: bool,
+}
+
+impl<S, C> BackfillState<'_, S, C>
+where
+    S: BackfillSource,
+    C: CheckpointStore,
+{
+    async fn next(&mut self) -> Result<Option<S::Item>> {
+        if self.failed {
+            return Ok(None);
+        }
+
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Ok(Some(item));
+            }
+
+            if let Some(next) = self.pending.take() {
+                *self.emitted.borrow_mut() = Some(next.clone());
+
+                if next.completed {
+                    info!(
+                        records = next.records,
+                        "Backfill caught up, switching to live streaming"
+                    );
+                } else if !self.backfill.config.chunk_delay.is_zero() {
+                    sleep(self.backfill.config.chunk_delay).await;
+                }
+
+                self.checkpoint = Some(next);
+            }
+
+            let checkpoint = match self.checkpoint.take() {
+                Some(checkpoint) => checkpoint,
+                None => {
+                    let checkpoint = self.backfill.checkpoint().await?;
+
+                    if !checkpoint.completed {
+                        info!(
+                            chunks = checkpoint.chunks,
+                            records = checkpoint.records,
+                            "Starting backfill"
+                        );
+                    }
+
+                    checkpoint
+                }
+            };
+
+            if checkpoint.completed {
+                self.checkpoint = Some(checkpoint);
+                return Ok(self.live.next().await);
+            }
+
+            let chunk = self
+                .backfill
+                .source
+                .fetch_chunk(
+                    checkpoint.position.as_ref(),
+                    self.backfill.config.chunk_size,
+                )
+                .await;
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    self.checkpoint = Some(checkpoint);
+                    return Err(err);
+                }
+            };
+
+            self.pending = Some(BackfillCheckpoint {
+                position: chunk.position.or(checkpoint.position),
+                chunks: checkpoint.chunks + 1,
+                records: checkpoint.records + chunk.items.len() as u64,
+                completed: chunk.caught_up,
+            });
+            self.buffer.extend(chunk.items);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fluvio_future::task::run_block_on;
+    use futures::stream::iter;
+
+    use crate::checkpoint::MemoryCheckpointStore;
+
+    use super::*;
+
+    /// Source with records `0..total`, the position is the next record
+    struct RangeSource {
+        total: u64,
+        fail_at: Option<u64>,
+    }
+
+    #[async_trait(?Send)]
+    impl BackfillSource for RangeSource {
+        type Item = u64;
+        type Position = u64;
+
+        async fn fetch_chunk(
+            &mut self,
+            after: Option<&u64>,
+            limit: usize,
+        ) -> Result<BackfillChunk<u64, u64>> {
+            let start = after.copied().unwrap_or_default();
+
+            if self.fail_at == Some(start) {
+                anyhow::bail!("source unavailable");
+            }
+
+            let end = (start + limit as u64).min(self.total);
+
+            Ok(BackfillChunk {
+                items: (start..end).collect(),
+                position: Some(end),
+                caught_up: end == self.total,
+            })
+        }
+    }
+
+    fn config() -> BackfillConfig {
+        BackfillConfig {
+            chunk_size: 2,
+            ..Default::default()
+        }
+    }
+
+    /// Collects the items of `stream`, committing after every item as a
+    /// connector does once it produced the item
+    async fn collect_committed<C: CheckpointStore>(
+        mut stream: BackfillStream<'_, u64, u64, C>,
+    ) -> Vec<Result<u64>> {
+        let mut items = Vec::new();
+
+        while let Some(item) = stream.next().await {
+            stream.commit().await.expect("commit");
+            items.push(item);
+        }
+
+        items
+    }
+
+    #[test]
+    fn test_backfill_hands_off_to_live_stream() {
+        run_block_on(async {
+            let store = MemoryCheckpointStore::default();
+            let source = RangeSource {
+                total: 5,
+                fail_at: None,
+            };
+            let backfill = Backfill::new("range", source, store, config());
+            let items: Vec<u64> = backfill
+                .into_stream(iter(vec![100, 101]).boxed_local())
+                .map(|item| item.expect("item"))
+                .collect()
+                .await;
+
+            assert_eq!(items, vec![0, 1, 2, 3, 4, 100, 101]);
+        });
+    }
+
+    #[test]
+    fn test_backfill_resumes_from_checkpoint() {
+        run_block_on(async {
+            let store = MemoryCheckpointStore::default();
+            let source = RangeSource {
+                total: 5,
+                fail_at: Some(4),
+            };
+            let items = collect_committed(
+                Backfill::new("range", source, &store, config())
+                    .into_stream(iter(vec![]).boxed_local()),
+            )
+            .await;
+
+            assert_eq!(items.len(), 5);
+            assert!(items[4].is_err());
+
+            let backfill = Backfill::new(
+                "range",
+                RangeSource {
+                    total: 5,
+                    fail_at: None,
+                },
+                &store,
+                config(),
+            );
+            let checkpoint = backfill.checkpoint().await.expect("checkpoint");
+
+            assert_eq!(checkpoint.position, Some(4));
+            assert_eq!(checkpoint.records, 4);
+            assert!(!checkpoint.completed);
+
+            let items: Vec<u64> =
+                collect_committed(backfill.into_stream(iter(vec![100]).boxed_local()))
+                    .await
+                    .into_iter()
+                    .map(|item| item.expect("item"))
+                    .collect();
+
+            assert_eq!(items, vec![4, 100]);
+            assert!(
+                load_json::<BackfillCheckpoint<u64>>(&store, "range")
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .completed
+            );
+        });
+    }
+    #[test]
+    fn test_backfill_saves_progress_on_commit() {
+        run_block_on(async {
+            let store = MemoryCheckpointStore::default();
+            let source = RangeSource {
+                total: 5,
+                fail_at: None,
+            };
+            let mut stream = Backfill::new("range", source, &store, config())
+                .into_stream(iter(vec![]).boxed_local());
+            let mut items = Vec::new();
+
+            // The first chunk is emitted entirely once the next one starts
+            for _ in 0..3 {
+                items.push(stream.next().await.unwrap().unwrap());
+            }
+
+            assert_eq!(items, vec![0, 1, 2]);
+            assert_eq!(
+                load_json::<BackfillCheckpoint<u64>>(&store, "range")
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            stream.commit().await.unwrap();
+
+            let checkpoint = load_json::<BackfillCheckpoint<u64>>(&store, "range")
+                .await
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(checkpoint.position, Some(2));
+            assert_eq!(checkpoint.records, 2);
+        });
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read, remove_file, rename, write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::Result;
+
+/// Persists connector progress across restarts, values are identified by key
+#[async_trait]
+pub trait CheckpointStore {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    async fn save(&self, key: &str, value: &[u8]) -> Result<()>;
+
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: CheckpointStore + Sync + ?Sized> CheckpointStore for &T {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        (**self).load(key).await
+    }
+
+    async fn save(&self, key: &str, value: &[u8]) -> Result<()> {
+        (**self).save(key, value).await
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        (**self).remove(key).await
+    }
+}
+
+/// Loads the JSON encoded checkpoint stored under `key`
+pub async fn load_json<T: DeserializeOwned>(
+    store: &(impl CheckpointStore + ?Sized),
+    key: &str,
+) -> Result<Option<T>> {
+    match store.load(key).await? {
+        Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+        None => Ok(None),
+    }
+}
+
+/// Stores `value` JSON encoded under `key`
+pub async fn save_json<T: Serialize>(
+    store: &(impl CheckpointStore + ?Sized),
+    key: &str,
+    value: &T,
+) -> Result<()> {
+    store.save(key, &serde_json::to_vec(value)?).await
+}
+
+/// Stores checkpoints as files in a directory, one file per key
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File of `key`, bytes other than ASCII alphanumerics and `-` are
+    /// encoded as `_` followed by their hex value, so distinct keys never
+    /// share a file
+    fn path(&self, key: &str) -> PathBuf {
+        let mut name = String::with_capacity(key.len());
+
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' {
+                name.push(byte as char);
+            } else {
+                name.push_str(&format!("_{byte:02X}"));
+            }
+        }
+
+        self.dir.join(format!("{name}.checkpoint"))
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key);
+
+        if path.exists() {
+            return Ok(Some(read(path)?));
+        }
+
+        Ok(None)
+    }
+
+    async fn save(&self, key: &str, value: &[u8]) -> Result<()> {
+        let path = self.path(key);
+        let tmp = path.with_extension("checkpoint.tmp");
+
+        // Write then rename so a crash never leaves a partial checkpoint
+        create_dir_all(&self.dir)?;
+        write(&tmp, value)?;
+        rename(tmp, path)?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let path = self.path(key);
+
+        if path.exists() {
+            remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps checkpoints in memory, progress is lost when the connector stops
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore {
+    values: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn load(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let values = self
+            .values
+            .lock()
+            .map_err(|_| anyhow::anyhow!("checkpoint store lock poisoned"))?;
+
+        Ok(values.get(key).cloned())
+    }
+
+    async fn save(&self, key: &str, value: &[u8]) -> Result<()> {
+        let mut values = self
+            .values
+            .lock()
+            .map_err(|_| anyhow::anyhow!("checkpoint store lock poisoned"))?;
+
+        values.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let mut values = self
+            .values
+            .lock()
+            .map_err(|_| anyhow::anyhow!("checkpoint store lock poisoned"))?;
+
+        values.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fluvio_future::task::run_block_on;
+
+    use super::*;
+
+    #[test]
+    fn test_file_checkpoint_store() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let store = FileCheckpointStore::new(dir.path());
+
+        run_block_on(async {
+            assert_eq!(load_json::<u64>(&store, "orders/2024").await.unwrap(), None);
+
+            save_json(&store, "orders/2024", &42u64).await.unwrap();
+            assert_eq!(
+                load_json::<u64>(&store, "orders/2024").await.unwrap(),
+                Some(42)
+            );
+            assert!(dir.path().join("orders_2F2024.checkpoint").exists());
+
+            // Keys only differing by escaped characters are kept apart
+            save_json(&store, "orders_2024", &7u64).await.unwrap();
+            assert_eq!(
+                load_json::<u64>(&store, "orders/2024").await.unwrap(),
+                Some(42)
+            );
+            assert!(dir.path().join("orders_5F2024.checkpoint").exists());
+
+            store.remove("orders/2024").await.unwrap();
+            assert_eq!(store.load("orders/2024").await.unwrap(), None);
+            assert_eq!(
+                load_json::<u64>(&store, "orders_2024").await.unwrap(),
+                Some(7)
+            );
+        });
+    }
+}
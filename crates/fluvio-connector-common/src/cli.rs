@@ -0,0 +1,360 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+pub use clap::Parser;
+use serde_yaml::{Mapping, Value};
+
+use fluvio_connector_package::lock::{SmartModuleLock, SMARTMODULE_LOCK_FILENAME};
+use fluvio_connector_package::secret::{set_default_secret_store, EnvSecretStore, FileSecretStore};
+
+use crate::config::{value_from_reader, ConnectorConfig};
+use crate::smartmodule::set_smartmodule_lock;
+use crate::tracing::{debug, info};
+use crate::{render_config_str, Result};
+
+/// Prefix of environment variables overriding config values. Path segments
+/// are separated by `__`, e.g. `CONNECTOR__META__PRODUCER__LINGER=10ms`
+/// sets `meta.producer.linger`.
+pub const ENV_OVERRIDE_PREFIX: &str = "CONNECTOR__";
+
+/// Command line shared by all connector binaries
+#[derive(Debug, Parser)]
+#[command(about = "Fluvio connector", max_term_width = 100)]
+pub struct ConnectorCli {
+    /// Connector config file
+    #[arg(long, value_name = "PATH", required_unless_present = "print_schema")]
+    pub config: Option<PathBuf>,
+
+    /// File with the secrets referenced by the config, secrets are read
+    /// from environment variables otherwise
+    #[arg(long, value_name = "PATH")]
+    pub secrets: Option<PathBuf>,
+
+    /// Lock file pinning the SmartModules of the transforms, generated by
+    /// `cdk lock`. Defaults to `smartmodules.lock` next to the config file
+    /// when it exists.
+    #[arg(long, value_name = "PATH")]
+    pub lock: Option<PathBuf>,
+
+    /// Override a config value with a dotted path, e.g.
+    /// `--set meta.producer.linger=10ms`. Applied after `CONNECTOR__*`
+    /// environment variables.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    pub overrides: Vec<ConfigOverride>,
+
+    /// Check the config and exit without connecting to the cluster
+    #[arg(long)]
+    pub validate_config: bool,
+
+    /// Print the JSON schema of the common connector config and exit
+    #[arg(long)]
+    pub print_schema: bool,
+}
+
+/// Config read from the command line, with secrets resolved and overrides applied
+pub struct LoadedConfig {
+    pub common: ConnectorConfig,
+    /// Whole config, the connector specific section is read from it
+    pub value: Value,
+}
+
+impl ConnectorCli {
+    /// Sets the default secret store and the SmartModule lock, and loads the
+    /// config
+    pub fn load(&self) -> Result<LoadedConfig> {
+        match &self.secrets {
+            Some(secrets) => {
+                info!("Using FileSecretStore");
+                set_default_secret_store(Box::new(FileSecretStore::from(secrets)))?;
+            }
+            None => {
+                info!("Using EnvSecretStore");
+                set_default_secret_store(Box::new(EnvSecretStore))?;
+            }
+        };
+
+        let path = self
+            .config
+            .as_ref()
+            .ok_or_else(|| anyhow!("--config <PATH> is required"))?;
+        info!("Reading config file from: {}", path.to_string_lossy());
+
+        let config_str = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read config file {}", path.display()))?;
+        debug!(%config_str, "input config");
+
+        // Resolve any secrets/env in the config
+        let config_str = render_config_str(&config_str)?;
+        let mut value = value_from_reader(config_str.as_bytes())?;
+
+        for config_override in env_overrides(std::env::vars()) {
+            debug!(key = %config_override.key, "Applying environment override");
+            config_override.apply(&mut value)?;
+        }
+
+        for config_override in self.overrides.iter() {
+            debug!(key = %config_override.key, "Applying override");
+            config_override.apply(&mut value)?;
+        }
+
+        let common = ConnectorConfig::from_value(value.clone())?;
+
+        let lock_path = match &self.lock {
+            Some(lock) => Some(lock.clone()),
+            None => path
+                .parent()
+                .map(|dir| dir.join(SMARTMODULE_LOCK_FILENAME))
+                .filter(|lock| lock.exists()),
+        };
+        if let Some(lock_path) = lock_path {
+            info!("Using SmartModule lock file: {}", lock_path.display());
+            set_smartmodule_lock(SmartModuleLock::from_file(&lock_path)?)?;
+        }
+
+        Ok(LoadedConfig { common, value })
+    }
+}
+
+/// Prints the JSON schema of [`ConnectorConfig`]
+pub fn print_schema() -> Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&ConnectorConfig::json_schema())?
+    );
+    Ok(())
+}
+
+/// Value set at a dotted path of the config, e.g. `meta.consumer.partition=2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for ConfigOverride {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected KEY=VALUE, got `{s}`"))?;
+
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            bail!("invalid config path `{key}`");
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl ConfigOverride {
+    /// Sets the value at the path, creating missing mappings. The value is
+    /// parsed as YAML so numbers, booleans and lists keep their type, and
+    /// may reference secrets like the config file.
+    pub fn apply(&self, config: &mut Value) -> Result<()> {
+        let rendered = if self.value.contains("${{") {
+            render_config_str(&self.value)?
+        } else {
+            self.value.clone()
+        };
+        let new_value =
+            serde_yaml::from_str(&rendered).unwrap_or_else(|_| Value::String(rendered.clone()));
+        let segments: Vec<&str> = self.key.split('.').collect();
+        let (last, parents) = segments
+            .split_last()
+            .ok_or_else(|| anyhow!("invalid config path `{}`", self.key))?;
+
+        let mut current = config;
+        for segment in parents {
+            current =
+                child(current, segment).with_context(|| format!("unable to set `{}`", self.key))?;
+        }
+
+        match current {
+            Value::Mapping(mapping) => {
+                let key = mapping_key(mapping, last);
+                mapping.insert(key, new_value);
+            }
+            Value::Sequence(sequence) => {
+                let slot = last
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| sequence.get_mut(index))
+                    .ok_or_else(|| anyhow!("unable to set `{}`: no item {last}", self.key))?;
+                *slot = new_value;
+            }
+            _ => bail!("unable to set `{}`: parent is not a mapping", self.key),
+        }
+
+        Ok(())
+    }
+}
+
+/// Mutable child of `value`, a missing key creates an empty mapping
+fn child<'a>(value: &'a mut Value, segment: &str) -> Result<&'a mut Value> {
+    if value.is_null() {
+        *value = Value::Mapping(Mapping::new());
+    }
+
+    match value {
+        Value::Mapping(mapping) => {
+            let key = mapping_key(mapping, segment);
+            Ok(mapping
+                .entry(key)
+                .or_insert_with(|| Value::Mapping(Mapping::new())))
+        }
+        Value::Sequence(sequence) => segment
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| sequence.get_mut(index))
+            .ok_or_else(|| anyhow!("no item {segment}")),
+        _ => bail!("{segment} is not in a mapping"),
+    }
+}
+
+/// Existing key matching `segment`, also when written in kebab-case or
+/// snake_case, so `batch_size` overrides an existing `batch-size` key
+fn mapping_key(mapping: &Mapping, segment: &str) -> Value {
+    [
+        segment.to_string(),
+        segment.replace('_', "-"),
+        segment.replace('-', "_"),
+    ]
+    .into_iter()
+    .map(Value::String)
+    .find(|key| mapping.contains_key(key))
+    .unwrap_or_else(|| Value::String(segment.to_string()))
+}
+
+/// Overrides from `CONNECTOR__*` environment variables, in name order so
+/// the outcome does not depend on the environment order
+pub fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Vec<ConfigOverride> {
+    let mut overrides: Vec<ConfigOverride> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let path = name.strip_prefix(ENV_OVERRIDE_PREFIX)?;
+
+            if path.is_empty() || path.split("__").any(str::is_empty) {
+                return None;
+            }
+
+            Some(ConfigOverride {
+                key: path
+                    .split("__")
+                    .collect::<Vec<_>>()
+                    .join(".")
+                    .to_lowercase(),
+                value,
+            })
+        })
+        .collect();
+
+    overrides.sort_by(|a, b| a.key.cmp(&b.key));
+    overrides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Value {
+        serde_yaml::from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.1.0
+              producer:
+                batch-size: 1MB
+            http:
+              endpoint: http://localhost
+              headers:
+                - "a: 1"
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn set(config: &mut Value, arg: &str) -> Result<()> {
+        arg.parse::<ConfigOverride>()?.apply(config)
+    }
+
+    #[test]
+    fn test_parse_override() {
+        assert_eq!(
+            "meta.producer.linger=10ms"
+                .parse::<ConfigOverride>()
+                .unwrap(),
+            ConfigOverride {
+                key: "meta.producer.linger".to_string(),
+                value: "10ms".to_string(),
+            }
+        );
+        assert_eq!(
+            "http.query=a=b".parse::<ConfigOverride>().unwrap().value,
+            "a=b"
+        );
+        assert!("meta.name".parse::<ConfigOverride>().is_err());
+        assert!("meta..name=x".parse::<ConfigOverride>().is_err());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut value = config();
+
+        set(&mut value, "meta.name=renamed").unwrap();
+        set(&mut value, "meta.consumer.partition=2").unwrap();
+        set(&mut value, "meta.producer.batch_size=2MB").unwrap();
+        set(&mut value, r#"http.headers.0="b: 2""#).unwrap();
+
+        assert_eq!(value["meta"]["name"], Value::from("renamed"));
+        assert_eq!(value["meta"]["consumer"]["partition"], Value::from(2));
+        assert_eq!(value["meta"]["producer"]["batch-size"], Value::from("2MB"));
+        assert!(value["meta"]["producer"].get("batch_size").is_none());
+        assert_eq!(value["http"]["headers"][0], Value::from("b: 2"));
+
+        let config = ConnectorConfig::from_value(value).unwrap();
+        assert_eq!(config.meta().name(), "renamed");
+    }
+
+    #[test]
+    fn test_apply_override_errors() {
+        let mut value = config();
+
+        assert!(set(&mut value, "meta.name.first=x").is_err());
+        assert!(set(&mut value, "http.headers.5=x").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let overrides = env_overrides([
+            ("PATH".to_string(), "/bin".to_string()),
+            (
+                "CONNECTOR__META__PRODUCER__LINGER".to_string(),
+                "10ms".to_string(),
+            ),
+            ("CONNECTOR__HTTP__".to_string(), "x".to_string()),
+            ("CONNECTOR__META__NAME".to_string(), "env".to_string()),
+        ]);
+
+        assert_eq!(
+            overrides,
+            vec![
+                ConfigOverride {
+                    key: "meta.name".to_string(),
+                    value: "env".to_string(),
+                },
+                ConfigOverride {
+                    key: "meta.producer.linger".to_string(),
+                    value: "10ms".to_string(),
+                },
+            ]
+        );
+    }
+}
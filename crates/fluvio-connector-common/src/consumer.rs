@@ -9,7 +9,7 @@ use fluvio::{Fluvio, FluvioClusterConfig, Offset};
 use fluvio_connector_package::config::{ConsumerPartitionConfig, OffsetConfig, OffsetStrategyConfig};
 use crate::{config::ConnectorConfig, Result};
 use crate::ensure_topic_exists;
-use crate::smartmodule::smartmodule_vec_from_config;
+use crate::smartmodule::{smartmodule_vec_from_config, verify_locked_smartmodules};
 
 pub use fluvio::consumer::ConsumerStream;
 
@@ -69,7 +69,8 @@ pub async fn consumer_stream_from_config(
     if let Some(max_bytes) = config.meta().consumer().and_then(|c| c.max_bytes) {
         builder.max_bytes(max_bytes.as_u64() as i32);
     }
-    if let Some(smartmodules) = smartmodule_vec_from_config(config) {
+    verify_locked_smartmodules(config).await?;
+    if let Some(smartmodules) = smartmodule_vec_from_config(config)? {
         builder.smartmodule(smartmodules);
     }
     tracing::info!("Building config");
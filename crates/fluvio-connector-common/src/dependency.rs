@@ -0,0 +1,351 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use fluvio_connector_package::config::{DependencyProbe, WaitForConfig};
+use fluvio_future::task::spawn_blocking;
+use fluvio_future::timer::sleep;
+
+use crate::tracing::{info, warn};
+use crate::{config::ConnectorConfig, Result};
+
+const POSTGRES_DEFAULT_PORT: u16 = 5432;
+const MYSQL_DEFAULT_PORT: u16 = 3306;
+
+/// `SSLRequest` message, answered with a single `S` or `N` byte by every
+/// PostgreSQL server regardless of its authentication settings
+const POSTGRES_SSL_REQUEST: [u8; 8] = [0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f];
+const MYSQL_PROTOCOL_VERSION: u8 = 10;
+const MYSQL_ERROR_PACKET: u8 = 0xff;
+
+/// Waits until every dependency in `meta.wait-for` answers its probe.
+///
+/// Dependencies are probed in order. A failed probe is retried with a delay
+/// doubling up to `max-delay`, and the connector fails once `timeout` has
+/// elapsed for the whole wait.
+pub async fn wait_for_dependencies(config: &ConnectorConfig) -> Result<()> {
+    let Some(wait_for) = config.meta().wait_for().cloned() else {
+        return Ok(());
+    };
+    let started_at = Instant::now();
+
+    for dependency in wait_for.dependencies.iter() {
+        wait_for_dependency(dependency, &wait_for, started_at).await?;
+    }
+
+    Ok(())
+}
+
+async fn wait_for_dependency(
+    dependency: &DependencyProbe,
+    wait_for: &WaitForConfig,
+    started_at: Instant,
+) -> Result<()> {
+    let name = ProbeName(dependency);
+    let mut delay = wait_for.initial_delay;
+    let mut attempt: u32 = 1;
+
+    loop {
+        let probe = dependency.clone();
+        let probe_timeout = wait_for.probe_timeout;
+
+        match spawn_blocking(move || run_probe(&probe, probe_timeout)).await {
+            Ok(()) => {
+                info!(dependency = %name, attempt, "Dependency is available");
+                return Ok(());
+            }
+            Err(err) if started_at.elapsed() + delay > wait_for.timeout => {
+                return Err(err.context(format!(
+                    "{name} is not available after waiting {}s",
+                    wait_for.timeout.as_secs()
+                )));
+            }
+            Err(err) => {
+                warn!(dependency = %name, attempt, %err, retry_in = ?delay, "Dependency is not available");
+                sleep(delay).await;
+                delay = (delay * 2).min(wait_for.max_delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Runs a single blocking probe of `dependency`
+pub fn run_probe(dependency: &DependencyProbe, timeout: Duration) -> Result<()> {
+    match dependency {
+        DependencyProbe::Tcp(address) => connect(address, timeout).map(|_| ()),
+        DependencyProbe::Http(url) => probe_http(url, timeout),
+        DependencyProbe::Sql(url) => probe_sql(url, timeout),
+    }
+}
+
+fn connect(address: &str, timeout: Duration) -> Result<TcpStream> {
+    let mut last_error = None;
+
+    for addr in address
+        .to_socket_addrs()
+        .with_context(|| format!("failed to resolve {address}"))?
+    {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    match last_error {
+        Some(err) => Err(anyhow!(err).context(format!("failed to connect to {address}"))),
+        None => bail!("{address} did not resolve to any address"),
+    }
+}
+
+fn probe_http(url: &str, timeout: Duration) -> Result<()> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|err| anyhow!("request failed: {}", redact_url(&err.to_string())))?;
+
+    if !(200..300).contains(&response.status()) {
+        bail!("unexpected status {}", response.status());
+    }
+
+    Ok(())
+}
+
+fn probe_sql(url: &str, timeout: Duration) -> Result<()> {
+    let (flavor, address) = parse_sql_url(url)?;
+    let mut stream = connect(&address, timeout)?;
+
+    match flavor {
+        SqlFlavor::Postgres => {
+            stream.write_all(&POSTGRES_SSL_REQUEST)?;
+
+            let mut answer = [0u8; 1];
+            stream
+                .read_exact(&mut answer)
+                .context("no answer to the PostgreSQL handshake")?;
+
+            match answer[0] {
+                b'S' | b'N' => Ok(()),
+                other => bail!("unexpected answer to the PostgreSQL handshake: {other:#04x}"),
+            }
+        }
+        SqlFlavor::Mysql => {
+            // Packet header of 3 length bytes and a sequence byte
+            let mut header = [0u8; 4];
+            stream
+                .read_exact(&mut header)
+                .context("no MySQL greeting received")?;
+
+            let len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+            let mut payload = vec![0u8; len];
+            stream.read_exact(&mut payload)?;
+
+            match payload.first() {
+                Some(&MYSQL_PROTOCOL_VERSION) => Ok(()),
+                // Error code (2 bytes) followed by the message
+                Some(&MYSQL_ERROR_PACKET) => bail!(
+                    "server refused the connection: {}",
+                    String::from_utf8_lossy(payload.get(3..).unwrap_or_default())
+                ),
+                _ => bail!("unexpected MySQL greeting"),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SqlFlavor {
+    Postgres,
+    Mysql,
+}
+
+/// Database flavor and `host:port` of a database URL
+fn parse_sql_url(url: &str) -> Result<(SqlFlavor, String)> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| anyhow!("invalid database URL {}", redact_url(url)))?;
+    let (flavor, default_port) = match scheme {
+        "postgres" | "postgresql" => (SqlFlavor::Postgres, POSTGRES_DEFAULT_PORT),
+        "mysql" | "mariadb" => (SqlFlavor::Mysql, MYSQL_DEFAULT_PORT),
+        other => bail!("unsupported database scheme {other}, expected postgres or mysql"),
+    };
+
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    if host.is_empty() {
+        bail!("database URL {} has no host", redact_url(url));
+    }
+
+    // The port follows the closing bracket of IPv6 addresses
+    let has_port = host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| !port.contains(']'));
+
+    if has_port {
+        Ok((flavor, host.to_string()))
+    } else {
+        Ok((flavor, format!("{host}:{default_port}")))
+    }
+}
+
+/// Hides the credentials of `url` so it can be logged
+fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+
+    match rest[..authority_end].rsplit_once('@') {
+        Some((_, host)) => format!("{scheme}://***@{host}{}", &rest[authority_end..]),
+        None => url.to_string(),
+    }
+}
+
+pub(crate) struct ProbeName<'a>(pub(crate) &'a DependencyProbe);
+
+impl Display for ProbeName<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            DependencyProbe::Tcp(address) => write!(f, "tcp {address}"),
+            DependencyProbe::Http(url) => write!(f, "http {}", redact_url(url)),
+            DependencyProbe::Sql(url) => write!(f, "sql {}", redact_url(url)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn wait_for(dependencies: Vec<DependencyProbe>) -> WaitForConfig {
+        WaitForConfig {
+            dependencies,
+            timeout: Duration::from_millis(200),
+            probe_timeout: Duration::from_millis(200),
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+        }
+    }
+
+    #[test]
+    fn test_parse_sql_url() {
+        assert_eq!(
+            parse_sql_url("postgres://user:secret@db/app?sslmode=disable").unwrap(),
+            (SqlFlavor::Postgres, "db:5432".to_string())
+        );
+        assert_eq!(
+            parse_sql_url("mysql://db:3307/app").unwrap(),
+            (SqlFlavor::Mysql, "db:3307".to_string())
+        );
+        assert_eq!(
+            parse_sql_url("postgresql://[::1]/app").unwrap(),
+            (SqlFlavor::Postgres, "[::1]:5432".to_string())
+        );
+        assert!(parse_sql_url("sqlite://app.db").is_err());
+        assert!(parse_sql_url("db:5432").is_err());
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("postgres://user:secret@db:5432/app"),
+            "postgres://***@db:5432/app"
+        );
+        assert_eq!(
+            redact_url("http://api:8080/health?from=a@b"),
+            "http://api:8080/health?from=a@b"
+        );
+        assert_eq!(redact_url("db:5432"), "db:5432");
+    }
+
+    #[test]
+    fn test_tcp_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("addr").to_string();
+
+        assert!(
+            run_probe(
+                &DependencyProbe::Tcp(address.clone()),
+                Duration::from_secs(1)
+            )
+            .is_ok()
+        );
+
+        drop(listener);
+        assert!(run_probe(&DependencyProbe::Tcp(address), Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_postgres_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!(
+            "postgres://user:secret@{}/app",
+            listener.local_addr().expect("addr")
+        );
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut request = [0u8; 8];
+            stream.read_exact(&mut request).expect("ssl request");
+            stream.write_all(b"N").expect("answer");
+            request
+        });
+
+        run_probe(&DependencyProbe::Sql(url), Duration::from_secs(1)).expect("probe");
+        assert_eq!(server.join().unwrap(), POSTGRES_SSL_REQUEST);
+    }
+
+    #[test]
+    fn test_mysql_probe_error_packet() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("mysql://{}/app", listener.local_addr().expect("addr"));
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut packet = vec![MYSQL_ERROR_PACKET, 0x10, 0x04];
+            packet.extend_from_slice(b"Too many connections");
+            let len = (packet.len() as u32).to_le_bytes();
+            stream
+                .write_all(&[len[0], len[1], len[2], 0])
+                .expect("header");
+            stream.write_all(&packet).expect("payload");
+        });
+
+        let err = run_probe(&DependencyProbe::Sql(url), Duration::from_secs(1)).unwrap_err();
+        server.join().unwrap();
+
+        assert!(err.to_string().contains("Too many connections"));
+    }
+
+    #[test]
+    fn test_wait_for_dependency_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("addr").to_string();
+        drop(listener);
+
+        let dependency = DependencyProbe::Tcp(address);
+        let config = wait_for(vec![dependency.clone()]);
+        let err = fluvio_future::task::run_block_on(wait_for_dependency(
+            &dependency,
+            &config,
+            Instant::now(),
+        ))
+        .unwrap_err();
+
+        assert!(err.to_string().contains("is not available after waiting"));
+    }
+}
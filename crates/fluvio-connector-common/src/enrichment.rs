@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+
+use fluvio_connector_package::config::{EnrichmentConfig, EnrichmentMode, RecordMetadataField};
+
+use crate::config::ConnectorConfig;
+
+/// Adds metadata specific to a record to its `source` identifiers, e.g. the
+/// table or file the record was read from
+pub trait MetadataHook: Send + Sync {
+    fn metadata(&self, key: Option<&[u8]>, value: &[u8]) -> Map<String, Value>;
+}
+
+/// Adds provenance metadata to produced record values as configured by
+/// `meta.producer.enrichment`
+pub struct RecordEnricher {
+    config: EnrichmentConfig,
+    /// Metadata which is the same for every record
+    fixed: Map<String, Value>,
+    hooks: Vec<Arc<dyn MetadataHook>>,
+}
+
+impl RecordEnricher {
+    pub fn new(connector: &ConnectorConfig, config: EnrichmentConfig) -> Self {
+        let mut fixed = Map::new();
+
+        for field in config.include.iter() {
+            match field {
+                RecordMetadataField::Connector => {
+                    fixed.insert("connector".to_string(), connector.name().into());
+                }
+                RecordMetadataField::Type => {
+                    fixed.insert("type".to_string(), connector.r#type().into());
+                }
+                RecordMetadataField::Version => {
+                    fixed.insert("version".to_string(), connector.version().into());
+                }
+                RecordMetadataField::IngestTimestamp => {}
+            }
+        }
+
+        Self {
+            config,
+            fixed,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Enricher for the connector, if enrichment is configured
+    pub fn from_config(connector: &ConnectorConfig) -> Option<Self> {
+        connector
+            .meta()
+            .producer()
+            .and_then(|producer| producer.enrichment.clone())
+            .map(|config| Self::new(connector, config))
+    }
+
+    pub fn with_hook(mut self, hook: Arc<dyn MetadataHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Value with the metadata added, `None` if the value is produced
+    /// unchanged
+    pub fn enrich(&self, key: Option<&[u8]>, value: &[u8]) -> Option<Vec<u8>> {
+        let parsed = serde_json::from_slice::<Value>(value);
+
+        let enriched = match (self.config.mode, parsed) {
+            (EnrichmentMode::Merge, Ok(Value::Object(mut object))) => {
+                object.insert(
+                    self.config.field.clone(),
+                    Value::Object(self.metadata(key, value)),
+                );
+                Value::Object(object)
+            }
+            (EnrichmentMode::Merge, _) => return None,
+            (EnrichmentMode::Envelope, parsed) => {
+                let inner =
+                    parsed.unwrap_or_else(|_| String::from_utf8_lossy(value).into_owned().into());
+                let mut envelope = Map::new();
+
+                envelope.insert(
+                    self.config.field.clone(),
+                    Value::Object(self.metadata(key, value)),
+                );
+                envelope.insert("value".to_string(), inner);
+                Value::Object(envelope)
+            }
+        };
+
+        serde_json::to_vec(&enriched).ok()
+    }
+
+    fn metadata(&self, key: Option<&[u8]>, value: &[u8]) -> Map<String, Value> {
+        let mut metadata = self.fixed.clone();
+
+        if self
+            .config
+            .include
+            .contains(&RecordMetadataField::IngestTimestamp)
+        {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default();
+
+            metadata.insert("ingest_timestamp".to_string(), timestamp.into());
+        }
+
+        let mut source: Map<String, Value> = self
+            .config
+            .source
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone().into()))
+            .collect();
+
+        for hook in self.hooks.iter() {
+            source.extend(hook.metadata(key, value));
+        }
+
+        if !source.is_empty() {
+            metadata.insert("source".to_string(), Value::Object(source));
+        }
+
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TableHook;
+
+    impl MetadataHook for TableHook {
+        fn metadata(&self, _key: Option<&[u8]>, _value: &[u8]) -> Map<String, Value> {
+            Map::from_iter([("table".to_string(), "orders".into())])
+        }
+    }
+
+    fn enricher(enrichment: &str) -> RecordEnricher {
+        let config = ConnectorConfig::config_from_str(&format!(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.2.1
+              producer:
+                enrichment: {enrichment}
+            "#
+        ))
+        .expect("connector config");
+
+        RecordEnricher::from_config(&config).expect("enricher")
+    }
+
+    fn enrich(enricher: &RecordEnricher, value: &str) -> Option<Value> {
+        enricher
+            .enrich(None, value.as_bytes())
+            .map(|enriched| serde_json::from_slice(&enriched).unwrap())
+    }
+
+    #[test]
+    fn test_merge_into_json_objects() {
+        let enricher = enricher("{ include: [connector, type, version] }");
+
+        assert_eq!(
+            enrich(&enricher, r#"{"id":1}"#),
+            Some(serde_json::json!({
+                "id": 1,
+                "_meta": {
+                    "connector": "my-source",
+                    "type": "http-source",
+                    "version": "0.2.1"
+                }
+            }))
+        );
+        assert_eq!(enrich(&enricher, "plain text"), None);
+        assert_eq!(enrich(&enricher, "[1, 2]"), None);
+    }
+
+    #[test]
+    fn test_envelope_with_source_and_hooks() {
+        let enricher = enricher(
+            "{ include: [ingest-timestamp], source: { region: eu }, mode: envelope, field: meta }",
+        )
+        .with_hook(Arc::new(TableHook));
+
+        let enriched = enrich(&enricher, "plain text").expect("enriched");
+
+        assert_eq!(enriched["value"], "plain text");
+        assert_eq!(
+            enriched["meta"]["source"],
+            serde_json::json!({ "region": "eu", "table": "orders" })
+        );
+        assert!(enriched["meta"]["ingest_timestamp"].as_u64().unwrap() > 0);
+        assert!(enriched["meta"].get("connector").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use fluvio::{Offset, TopicProducerPool};
+use fluvio::consumer::Record;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::ConnectorConfig, Result};
+
+/// Version of the [`ErrorRecord`] format, incremented on incompatible changes
+pub const ERROR_RECORD_VERSION: u32 = 1;
+
+/// Failure produced to a dead letter queue or status topic.
+///
+/// Every connector reports failures with the same envelope, so a single
+/// consumer can process the failures of any connector. Records are encoded
+/// as JSON and keyed by the connector name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ErrorRecord {
+    pub version: u32,
+    pub connector: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Milliseconds since UNIX Epoch when the failure occurred
+    pub timestamp: u64,
+    /// Attempts made before the record was given up on
+    pub retry_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadRef>,
+}
+
+/// Stage of the connector where the failure happened
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Reading from, or writing to, the external system
+    Connection,
+    /// The payload could not be decoded or encoded
+    Serialization,
+    /// A SmartModule in the transforms chain failed
+    Transform,
+    /// The external system rejected the payload
+    Rejected,
+    /// Producing to, or consuming from, Fluvio
+    Delivery,
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// Location of the payload which failed. Only sink connectors know the
+/// offset, source connectors identify the payload by its key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PayloadRef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Record key, lossy UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Size of the payload in bytes
+    pub size: usize,
+}
+
+impl PayloadRef {
+    /// Reference to a record consumed from `topic`
+    pub fn from_record(topic: impl Into<String>, record: &Record) -> Self {
+        Self {
+            topic: Some(topic.into()),
+            partition: Some(record.partition()),
+            offset: Some(record.offset()),
+            key: record
+                .key()
+                .map(|key| String::from_utf8_lossy(key).into_owned()),
+            size: record.value().len(),
+        }
+    }
+
+    /// Reference to a payload read from the external system
+    pub fn from_payload(key: Option<&[u8]>, value: &[u8]) -> Self {
+        Self {
+            key: key.map(|key| String::from_utf8_lossy(key).into_owned()),
+            size: value.len(),
+            ..Default::default()
+        }
+    }
+
+    /// Offset of the referenced record, if consumed from Fluvio
+    pub fn record_offset(&self) -> Option<Offset> {
+        self.offset.and_then(|offset| Offset::absolute(offset).ok())
+    }
+}
+
+impl ErrorRecord {
+    pub fn new(config: &ConnectorConfig, kind: ErrorKind, message: impl Into<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            version: ERROR_RECORD_VERSION,
+            connector: config.name(),
+            type_: config.r#type(),
+            kind,
+            message: message.into(),
+            timestamp,
+            retry_count: 0,
+            payload: None,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: PayloadRef) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    pub fn with_retry_count(mut self, retry_count: u32) -> Self {
+        self.retry_count = retry_count;
+        self
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Decodes a record, failing on records from a newer format version
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let record: Self = serde_json::from_slice(bytes)?;
+
+        if record.version > ERROR_RECORD_VERSION {
+            anyhow::bail!(
+                "unsupported error record version {}, expected up to {ERROR_RECORD_VERSION}",
+                record.version
+            );
+        }
+
+        Ok(record)
+    }
+
+    /// Produces the record keyed by the connector name
+    pub async fn send(&self, producer: &TopicProducerPool) -> Result<()> {
+        producer
+            .send(self.connector.clone(), self.encode()?)
+            .await?;
+        producer.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ConnectorConfig {
+        ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-sink
+              type: http-sink
+              topic: events
+              version: 0.2.1
+        "#,
+        )
+        .expect("connector config")
+    }
+
+    #[test]
+    fn test_error_record_roundtrip() {
+        let record = ErrorRecord::new(&config(), ErrorKind::Rejected, "status 400")
+            .with_retry_count(3)
+            .with_payload(PayloadRef {
+                topic: Some("events".to_string()),
+                partition: Some(0),
+                offset: Some(42),
+                key: None,
+                size: 12,
+            });
+        let bytes = record.encode().expect("encode");
+        let value: serde_json::Value = serde_json::from_slice(&bytes).expect("json");
+
+        assert_eq!(value["version"], ERROR_RECORD_VERSION);
+        assert_eq!(value["connector"], "my-sink");
+        assert_eq!(value["type"], "http-sink");
+        assert_eq!(value["kind"], "rejected");
+        assert_eq!(value["retry_count"], 3);
+        assert_eq!(value["payload"]["offset"], 42);
+        assert!(value["payload"].get("key").is_none());
+        assert_eq!(ErrorRecord::decode(&bytes).expect("decode"), record);
+        assert_eq!(
+            record.payload.unwrap().record_offset(),
+            Some(Offset::absolute(42).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_error_record_custom_kind_and_version() {
+        let record = ErrorRecord::new(&config(), ErrorKind::Other("quota".to_string()), "");
+        let value = serde_json::to_value(&record).expect("json");
+
+        assert_eq!(value["kind"], "quota");
+        assert_eq!(
+            ErrorRecord::decode(&record.encode().unwrap()).unwrap().kind,
+            ErrorKind::Other("quota".to_string())
+        );
+
+        let mut newer = value;
+        newer["version"] = (ERROR_RECORD_VERSION + 1).into();
+        assert!(ErrorRecord::decode(&serde_json::to_vec(&newer).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use fluvio::metadata::topic::TopicSpec;
+use fluvio::metrics::ClientMetrics;
+use fluvio::{Fluvio, FluvioAdmin, TopicProducerPool};
+use fluvio_future::timer::sleep;
+use futures::future::{BoxFuture, FutureExt, pending};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use crate::{config::ConnectorConfig, Result};
+
+/// Liveness record periodically produced to the heartbeat topic
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HeartbeatRecord {
+    pub connector: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub version: String,
+    /// Milliseconds since UNIX Epoch when the record was created
+    pub timestamp: u64,
+    pub uptime_secs: u64,
+    /// Incremented for every heartbeat, resets when the connector restarts
+    pub sequence: u64,
+    pub produced_records: u64,
+    pub produced_bytes: u64,
+    pub consumed_records: u64,
+    pub consumed_bytes: u64,
+}
+
+impl HeartbeatRecord {
+    pub fn new(
+        config: &ConnectorConfig,
+        metrics: &ClientMetrics,
+        started_at: Instant,
+        sequence: u64,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            connector: config.name(),
+            type_: config.r#type(),
+            version: config.version(),
+            timestamp,
+            uptime_secs: started_at.elapsed().as_secs(),
+            sequence,
+            produced_records: metrics.producer_connector().records.load(Ordering::Relaxed),
+            produced_bytes: metrics.producer_connector().bytes.load(Ordering::Relaxed),
+            consumed_records: metrics.consumer().records.load(Ordering::Relaxed),
+            consumed_bytes: metrics.consumer().bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Returns the future producing heartbeat records if `meta.heartbeat` is
+/// configured, which never completes.
+///
+/// The connector polls it on its own task next to the connector future, so
+/// a connector blocking its task stops reporting like a crashed one, while
+/// an idle connector keeps reporting increasing `sequence` values.
+pub async fn init_heartbeat(
+    fluvio: &Fluvio,
+    config: &ConnectorConfig,
+) -> Result<BoxFuture<'static, ()>> {
+    let Some(heartbeat) = config.meta().heartbeat().cloned() else {
+        return Ok(pending().boxed());
+    };
+
+    ensure_heartbeat_topic(&fluvio.admin().await, &heartbeat.topic).await?;
+
+    let producer = fluvio.topic_producer(heartbeat.topic.clone()).await?;
+    let metrics = fluvio.metrics();
+    let config = config.clone();
+
+    info!(
+        topic = %heartbeat.topic,
+        interval = ?heartbeat.interval,
+        "Starting connector heartbeat"
+    );
+
+    Ok(run_heartbeat(producer, metrics, config, heartbeat.interval).boxed())
+}
+
+async fn run_heartbeat(
+    producer: TopicProducerPool,
+    metrics: Arc<ClientMetrics>,
+    config: ConnectorConfig,
+    interval: Duration,
+) {
+    let started_at = Instant::now();
+    let mut sequence = 0;
+
+    loop {
+        let record = HeartbeatRecord::new(&config, &metrics, started_at, sequence);
+
+        match serde_json::to_vec(&record) {
+            Ok(value) => {
+                let result = match producer.send(record.connector.clone(), value).await {
+                    Ok(_) => producer.flush().await,
+                    Err(err) => Err(err),
+                };
+
+                match result {
+                    Ok(_) => debug!(sequence, "Heartbeat produced"),
+                    Err(err) => error!(%err, "Failed to produce heartbeat"),
+                }
+            }
+            Err(err) => error!(%err, "Failed to serialize heartbeat"),
+        }
+
+        sequence += 1;
+        sleep(interval).await;
+    }
+}
+
+async fn ensure_heartbeat_topic(admin: &FluvioAdmin, topic: &str) -> Result<()> {
+    let topics = admin
+        .list::<TopicSpec, String>(vec![topic.to_string()])
+        .await?;
+
+    if !topics.iter().any(|t| t.name == topic) {
+        admin
+            .create(
+                topic.to_string(),
+                false,
+                TopicSpec::new_computed(1, 1, Some(false)),
+            )
+            .await?;
+        info!(topic, "heartbeat topic successfully created");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_record_from_config() {
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.2.1
+              heartbeat:
+                topic: connector-status
+                interval: 5s
+        "#,
+        )
+        .expect("connector config");
+        let metrics = ClientMetrics::new();
+        let record = HeartbeatRecord::new(&config, &metrics, Instant::now(), 7);
+        let value = serde_json::to_value(&record).expect("json");
+
+        assert_eq!(record.connector, "my-source");
+        assert_eq!(record.sequence, 7);
+        assert_eq!(value["type"], "http-source");
+        assert_eq!(value["version"], "0.2.1");
+        assert_eq!(value["produced_records"], 0);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use fluvio::metadata::partition::*;
+use fluvio::{Fluvio, FluvioAdmin, FluvioClusterConfig, PartitionId};
+use fluvio_future::task::spawn;
+use fluvio_future::timer::sleep;
+use serde::Serialize;
+
+use crate::tracing::{debug, warn};
+use crate::{config::ConnectorConfig, Result};
+
+/// Time between refreshes of the consumer lag
+pub const LAG_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+static SINK_HEALTH: OnceLock<Arc<SinkHealth>> = OnceLock::new();
+
+/// Lag of the sink consumer on a partition of its topic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartitionLag {
+    pub partition: PartitionId,
+    /// Offset of the last record committed by the consumer, if any
+    pub committed: Option<i64>,
+    /// High watermark of the partition, the offset of the next record
+    pub latest: i64,
+    /// Records produced but not committed yet, unknown until the consumer
+    /// commits its first offset
+    pub lag: Option<i64>,
+}
+
+/// Health of a sink connector reported by the monitoring socket: the lag of
+/// its consumer and whether it is paused, see [`crate::pause`]
+#[derive(Debug, Default)]
+pub struct SinkHealth {
+    state: Mutex<SinkHealthState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct SinkHealthState {
+    lag: Vec<PartitionLag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_lag: Option<i64>,
+    paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pause_reason: Option<String>,
+}
+
+impl SinkHealth {
+    /// Health shared by the connector, initialized by
+    /// [`init_lag_reporting`]
+    pub fn global() -> Option<Arc<Self>> {
+        SINK_HEALTH.get().cloned()
+    }
+
+    pub fn lag(&self) -> Vec<PartitionLag> {
+        self.state().lag.clone()
+    }
+
+    pub fn set_lag(&self, lag: Vec<PartitionLag>) {
+        let mut state = self.state();
+
+        state.total_lag = lag
+            .iter()
+            .filter_map(|partition| partition.lag)
+            .reduce(|total, lag| total + lag);
+        state.lag = lag;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state().paused
+    }
+
+    pub fn set_paused(&self, reason: impl Into<String>) {
+        let mut state = self.state();
+
+        state.paused = true;
+        state.pause_reason = Some(reason.into());
+    }
+
+    pub fn set_resumed(&self) {
+        let mut state = self.state();
+
+        state.paused = false;
+        state.pause_reason = None;
+    }
+
+    fn state(&self) -> MutexGuard<'_, SinkHealthState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Serialize for SinkHealth {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.state().serialize(serializer)
+    }
+}
+
+/// Lag per partition from the `committed` offsets of a consumer and the
+/// `latest` high watermarks of the partitions
+pub fn compute_lag(
+    committed: &BTreeMap<PartitionId, i64>,
+    latest: &BTreeMap<PartitionId, i64>,
+) -> Vec<PartitionLag> {
+    latest
+        .iter()
+        .map(|(partition, latest)| {
+            let committed = committed.get(partition).copied();
+
+            PartitionLag {
+                partition: *partition,
+                committed,
+                latest: *latest,
+                lag: committed.map(|committed| (latest - committed - 1).max(0)),
+            }
+        })
+        .collect()
+}
+
+/// Fetches the lag of the consumer of `meta.consumer.id` on the partitions
+/// of the connector topic
+pub async fn fetch_lag(fluvio: &Fluvio, config: &ConnectorConfig) -> Result<Vec<PartitionLag>> {
+    let meta = config.meta();
+    let topic = meta.topic();
+    let consumer_id = meta
+        .consumer()
+        .and_then(|consumer| consumer.id.as_ref())
+        .ok_or_else(|| anyhow::anyhow!("consumer lag requires `meta.consumer.id`"))?;
+
+    let committed: BTreeMap<PartitionId, i64> = fluvio
+        .consumer_offsets()
+        .await?
+        .into_iter()
+        .filter(|offset| &offset.consumer_id == consumer_id && offset.topic == topic)
+        .map(|offset| (offset.partition, offset.offset))
+        .collect();
+
+    let admin = FluvioAdmin::connect().await?;
+    let mut latest = BTreeMap::new();
+
+    for partition in admin.list::<PartitionSpec, String>(vec![]).await? {
+        let Ok(key) = ReplicaKey::try_from(partition.name.clone()) else {
+            continue;
+        };
+        let (name, id) = key.split();
+
+        if name == topic {
+            latest.insert(id, partition.status.leader.hw);
+        }
+    }
+
+    Ok(compute_lag(&committed, &latest))
+}
+
+/// Sets the [`SinkHealth`] of the sink connector, refreshing the lag of its
+/// consumer every [`LAG_REFRESH_INTERVAL`] if `meta.consumer.id` is set.
+/// Lag is only known for consumers committing their offsets.
+pub fn init_lag_reporting(config: &ConnectorConfig) -> Arc<SinkHealth> {
+    let health = SINK_HEALTH.get_or_init(Default::default).clone();
+
+    if config
+        .meta()
+        .consumer()
+        .and_then(|consumer| consumer.id.as_ref())
+        .is_none()
+    {
+        return health;
+    }
+
+    let config = config.clone();
+    let reported = health.clone();
+
+    spawn(async move {
+        let fluvio = match FluvioClusterConfig::load() {
+            Ok(cluster_config) => Fluvio::connect_with_config(&cluster_config).await,
+            Err(err) => Err(err.into()),
+        };
+        let fluvio = match fluvio {
+            Ok(fluvio) => fluvio,
+            Err(err) => {
+                warn!(%err, "Unable to connect for consumer lag reporting");
+                return;
+            }
+        };
+
+        loop {
+            match fetch_lag(&fluvio, &config).await {
+                Ok(lag) => {
+                    debug!(?lag, "Consumer lag refreshed");
+                    reported.set_lag(lag);
+                }
+                Err(err) => warn!(%err, "Failed to fetch consumer lag"),
+            }
+
+            sleep(LAG_REFRESH_INTERVAL).await;
+        }
+    });
+
+    health
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_lag() {
+        //given
+        let committed = BTreeMap::from([(0, 9), (1, 120)]);
+        let latest = BTreeMap::from([(0, 25), (1, 121), (2, 7)]);
+
+        //when
+        let lag = compute_lag(&committed, &latest);
+
+        //then
+        assert_eq!(
+            lag,
+            vec![
+                PartitionLag {
+                    partition: 0,
+                    committed: Some(9),
+                    latest: 25,
+                    lag: Some(15),
+                },
+                PartitionLag {
+                    partition: 1,
+                    committed: Some(120),
+                    latest: 121,
+                    lag: Some(0),
+                },
+                PartitionLag {
+                    partition: 2,
+                    committed: None,
+                    latest: 7,
+                    lag: None,
+                },
+            ]
+        );
+
+        let health = SinkHealth::default();
+        health.set_lag(lag);
+        health.set_paused("sink unavailable");
+
+        let reported = serde_json::to_value(&health).unwrap();
+        assert_eq!(reported["total_lag"], 15);
+        assert_eq!(reported["paused"], true);
+        assert_eq!(reported["pause_reason"], "sink unavailable");
+    }
+}
@@ -1,8 +1,21 @@
+pub mod cli;
 pub mod producer;
+pub mod enrichment;
+pub mod dependency;
 pub mod smartmodule;
 pub mod monitoring;
 pub mod consumer;
 pub mod config;
+pub mod heartbeat;
+pub mod schedule;
+pub mod partitioning;
+pub mod checkpoint;
+pub mod backfill;
+pub mod error_record;
+pub mod topic;
+pub mod windowing;
+pub mod lag;
+pub mod pause;
 
 pub use fluvio_connector_package::render_config_str;
 pub use fluvio_connector_package::secret;
@@ -42,11 +55,39 @@ pub trait Sink<I> {
 }
 
 pub async fn ensure_topic_exists(config: &config::ConnectorConfig) -> Result<()> {
-    let topic = config.meta().topic().to_string();
     let admin = fluvio::FluvioAdmin::connect().await?;
+    ensure_topic_exists_on(&admin, config.meta().topic(), config).await
+}
+
+/// Creates `topic` using the connector topic config if it does not exist in
+/// the cluster `admin` is connected to. An existing topic is validated
+/// against the settings declared in the topic config, see
+/// [`topic::topic_mismatches`].
+pub(crate) async fn ensure_topic_exists_on(
+    admin: &fluvio::FluvioAdmin,
+    topic: &str,
+    config: &config::ConnectorConfig,
+) -> Result<()> {
+    let topic = topic.to_string();
     let topics = admin.list::<TopicSpec, String>(vec![topic.clone()]).await?;
-    let topic_exists = topics.iter().any(|t| t.name.eq(&topic));
-    if !topic_exists {
+    if let Some(existing) = topics.iter().find(|t| t.name.eq(&topic)) {
+        if let Some(topic_config) = config.meta().topic_config() {
+            let mismatches = topic::topic_mismatches(topic_config, &existing.spec);
+
+            if !mismatches.is_empty() {
+                let report: Vec<String> = mismatches
+                    .iter()
+                    .map(|mismatch| format!("  - {mismatch}"))
+                    .collect();
+
+                error!(topic, "topic settings differ from the connector config");
+                return Err(anyhow::anyhow!(
+                    "topic {topic} exists with settings different from the connector config:\n{}",
+                    report.join("\n")
+                ));
+            }
+        }
+    } else {
         match admin
             .create(
                 topic.to_owned(),
@@ -9,6 +9,8 @@ use tracing::{error, info, trace};
 use serde::Serialize;
 use fluvio_smartengine::metrics::SmartModuleChainMetrics;
 
+use crate::lag::SinkHealth;
+
 const SOCKET_PATH: &str = "/tmp/fluvio-connector.sock";
 
 #[derive(Debug, Serialize)]
@@ -18,6 +20,9 @@ pub struct ConnectorMetrics {
     // Added field to capture per-SmartModule metrics
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     smartmodule_metrics: HashMap<String, SmartModuleChainMetrics>,
+    // Consumer lag and pause state of sink connectors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sink: Option<Arc<SinkHealth>>,
 }
 
 impl Default for ConnectorMetrics {
@@ -25,6 +30,7 @@ impl Default for ConnectorMetrics {
         Self {
             fluvio_metrics: Arc::new(ClientMetrics::new()),
             smartmodule_metrics: HashMap::new(),
+            sink: None,
         }
     }
 }
@@ -34,9 +40,16 @@ impl ConnectorMetrics {
         Self {
             fluvio_metrics,
             smartmodule_metrics: HashMap::new(),
+            sink: None,
         }
     }
 
+    // Report the health of a sink connector
+    pub fn with_sink_health(mut self, health: Arc<SinkHealth>) -> Self {
+        self.sink = Some(health);
+        self
+    }
+
     // Add method to update smartmodule metrics
     pub fn update_smartmodule_metrics(
         &mut self,
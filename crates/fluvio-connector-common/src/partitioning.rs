@@ -0,0 +1,238 @@
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use fluvio::{PartitionId, Partitioner, PartitionerConfig, RecordKey};
+use fluvio_connector_package::config::{KeyHashStrategy, PartitioningConfig};
+use serde_json::Value;
+use siphasher::sip::SipHasher;
+
+use crate::config::ConnectorConfig;
+
+/// Extracts record keys from JSON record values using a JSON Pointer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyExtractor {
+    pointer: String,
+}
+
+impl KeyExtractor {
+    pub fn new(pointer: impl Into<String>) -> Self {
+        Self {
+            pointer: pointer.into(),
+        }
+    }
+
+    /// Builds the extractor from `meta.producer.partitioning.key` if set
+    pub fn from_config(config: &ConnectorConfig) -> Option<Self> {
+        config
+            .meta()
+            .producer()
+            .and_then(|producer| producer.partitioning.as_ref())
+            .and_then(|partitioning| partitioning.key.as_ref())
+            .map(Self::new)
+    }
+
+    /// Returns the key bytes for `value`, `None` if the value is not JSON or
+    /// the field is missing. String fields are used as is, other values use
+    /// their JSON representation.
+    pub fn extract(&self, value: &[u8]) -> Option<Vec<u8>> {
+        let value: Value = serde_json::from_slice(value).ok()?;
+
+        match value.pointer(&self.pointer)? {
+            Value::Null => None,
+            Value::String(key) => Some(key.as_bytes().to_vec()),
+            other => Some(other.to_string().into_bytes()),
+        }
+    }
+
+    /// Returns a [`RecordKey`] for `value`, [`RecordKey::NULL`] if no key is
+    /// found.
+    pub fn record_key(&self, value: &[u8]) -> RecordKey {
+        match self.extract(value) {
+            Some(key) => RecordKey::from(key),
+            None => RecordKey::NULL,
+        }
+    }
+}
+
+/// [`Partitioner`] configured from the connector `partitioning` settings.
+///
+/// - Records with keys (or with a key extracted from their value) are
+///   assigned by hashing the key with the configured strategy
+/// - Records without keys are assigned round-robin, or stick to a partition
+///   for `batch-records` records when `sticky` is set
+pub struct ConnectorPartitioner {
+    extractor: Option<KeyExtractor>,
+    hash: KeyHashStrategy,
+    sticky_batch: u32,
+    index: AtomicU32,
+}
+
+impl ConnectorPartitioner {
+    pub fn new(config: &PartitioningConfig) -> Self {
+        Self {
+            extractor: config.key.as_ref().map(KeyExtractor::new),
+            hash: config.hash,
+            sticky_batch: config
+                .sticky
+                .as_ref()
+                .map(|sticky| sticky.batch_records.max(1))
+                .unwrap_or(1),
+            index: AtomicU32::new(0),
+        }
+    }
+
+    fn hash(&self, key: &[u8]) -> u64 {
+        match self.hash {
+            KeyHashStrategy::Siphash => {
+                let mut hasher = SipHasher::new();
+
+                key.hash(&mut hasher);
+                hasher.finish()
+            }
+            KeyHashStrategy::Fnv1a => fnv1a(key) as u64,
+            KeyHashStrategy::Murmur2 => (murmur2(key) & 0x7fffffff) as u64,
+        }
+    }
+}
+
+impl Partitioner for ConnectorPartitioner {
+    fn partition(
+        &self,
+        config: &PartitionerConfig,
+        key: Option<&[u8]>,
+        value: &[u8],
+    ) -> PartitionId {
+        let extracted = match (key, &self.extractor) {
+            (None, Some(extractor)) => extractor.extract(value),
+            _ => None,
+        };
+
+        if let Some(key) = key.or(extracted.as_deref()) {
+            return (self.hash(key) % config.partition_count() as u64) as PartitionId;
+        }
+
+        let index = self.index.fetch_add(1, Ordering::Relaxed) / self.sticky_batch;
+
+        if config.available_partitions.is_empty() {
+            return index % config.partition_count();
+        }
+
+        config.available_partitions[index as usize % config.available_partitions.len()]
+    }
+}
+
+/// 32-bit FNV-1a hash
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c9dc5, |hash: u32, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Murmur2 hash as implemented by the Kafka Java client
+fn murmur2(data: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747b28c;
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+
+    for chunk in chunks.by_ref() {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let rest = chunks.remainder();
+
+    if rest.len() == 3 {
+        h ^= (rest[2] as u32) << 16;
+    }
+
+    if rest.len() >= 2 {
+        h ^= (rest[1] as u32) << 8;
+    }
+
+    if !rest.is_empty() {
+        h ^= rest[0] as u32;
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use fluvio_connector_package::config::StickyPartitionerConfig;
+
+    use super::*;
+
+    fn partitioner_config() -> PartitionerConfig {
+        PartitionerConfig {
+            partition_count: 3,
+            available_partitions: vec![0, 1, 2],
+        }
+    }
+
+    #[test]
+    fn test_extract_key() {
+        let extractor = KeyExtractor::new("/user/id");
+
+        assert_eq!(
+            extractor.extract(br#"{"user":{"id":"abc"}}"#),
+            Some(b"abc".to_vec())
+        );
+        assert_eq!(
+            extractor.extract(br#"{"user":{"id":42}}"#),
+            Some(b"42".to_vec())
+        );
+        assert_eq!(extractor.extract(br#"{"user":{}}"#), None);
+        assert_eq!(extractor.extract(b"not json"), None);
+    }
+
+    #[test]
+    fn test_hash_functions_match_reference() {
+        // Reference values from the Kafka client test suite
+        assert_eq!(murmur2(b"21") as i32, -973932308);
+        assert_eq!(murmur2(b"foobar") as i32, -790332482);
+        assert_eq!(fnv1a(b"a"), 0xe40c292c);
+        assert_eq!(fnv1a(b"foobar"), 0xbf9cf968);
+    }
+
+    #[test]
+    fn test_partition_by_extracted_key() {
+        let partitioner = ConnectorPartitioner::new(&PartitioningConfig {
+            key: Some("/id".to_string()),
+            hash: KeyHashStrategy::Murmur2,
+            sticky: None,
+        });
+        let config = partitioner_config();
+        let extracted = partitioner.partition(&config, None, br#"{"id":"foobar"}"#);
+        let explicit = partitioner.partition(&config, Some(b"foobar"), b"");
+
+        assert_eq!(extracted, explicit);
+        assert_eq!(extracted, (-790332482i32 as u32 & 0x7fffffff) % 3);
+    }
+
+    #[test]
+    fn test_sticky_partitioning() {
+        let partitioner = ConnectorPartitioner::new(&PartitioningConfig {
+            key: None,
+            hash: KeyHashStrategy::Siphash,
+            sticky: Some(StickyPartitionerConfig { batch_records: 2 }),
+        });
+        let config = partitioner_config();
+        let partitions: Vec<PartitionId> = (0..6)
+            .map(|_| partitioner.partition(&config, None, b"value"))
+            .collect();
+
+        assert_eq!(partitions, vec![0, 0, 1, 1, 2, 2]);
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::anyhow;
+use fluvio_connector_package::config::AutoPauseConfig;
+use fluvio_future::task::spawn_blocking;
+use fluvio_future::timer::sleep;
+
+use crate::dependency::{ProbeName, run_probe};
+use crate::lag::SinkHealth;
+use crate::tracing::{info, warn};
+use crate::{config::ConnectorConfig, Result};
+
+static AUTO_PAUSE: OnceLock<AutoPause> = OnceLock::new();
+
+/// Pauses a sink connector while the system it writes to is unavailable.
+///
+/// Sinks report the outcome of every write. Once `meta.consumer.auto-pause`
+/// consecutive writes have failed, the sink awaits
+/// [`AutoPause::pause_until_available`] instead of reading the next record,
+/// so records stay in the topic until every probe of the system succeeds.
+///
+/// ```ignore
+/// match write(&record).await {
+///     Ok(()) => auto_pause.record_success(),
+///     Err(err) if auto_pause.record_failure(&err) => auto_pause.pause_until_available().await,
+///     Err(err) => return Err(err),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AutoPause {
+    config: AutoPauseConfig,
+    failures: AtomicU32,
+    health: Arc<SinkHealth>,
+}
+
+impl AutoPause {
+    pub fn new(config: AutoPauseConfig, health: Arc<SinkHealth>) -> Self {
+        Self {
+            config,
+            failures: AtomicU32::new(0),
+            health,
+        }
+    }
+
+    /// Auto-pause of the connector, initialized by [`init_auto_pause`]
+    pub fn global() -> Option<&'static Self> {
+        AUTO_PAUSE.get()
+    }
+
+    pub fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Records a failed write, returning whether the sink must pause
+    pub fn record_failure(&self, err: &anyhow::Error) -> bool {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        warn!(%err, failures, "Sink write failed");
+        failures >= self.config.after_failures
+    }
+
+    /// Consecutive failed writes since the last successful one
+    pub fn failures(&self) -> u32 {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Probes the system every `probe-interval` until every probe succeeds.
+    /// The sink is reported as paused by the monitoring socket meanwhile.
+    pub async fn pause_until_available(&self) {
+        let reason = format!("{} consecutive write failures", self.failures());
+
+        warn!(reason, "Pausing sink until the target system is available");
+        self.health.set_paused(reason);
+
+        let mut attempt: u32 = 1;
+
+        while let Err(err) = self.probe().await {
+            warn!(%err, attempt, retry_in = ?self.config.probe_interval, "Target system is not available");
+            sleep(self.config.probe_interval).await;
+            attempt += 1;
+        }
+
+        info!(attempt, "Target system is available, resuming sink");
+        self.record_success();
+        self.health.set_resumed();
+    }
+
+    async fn probe(&self) -> Result<()> {
+        for probe in self.config.probes.iter() {
+            let name = ProbeName(probe).to_string();
+            let dependency = probe.clone();
+            let timeout = self.config.probe_timeout;
+
+            spawn_blocking(move || run_probe(&dependency, timeout))
+                .await
+                .map_err(|err| err.context(name))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Initializes the [`AutoPause`] of the sink connector if
+/// `meta.consumer.auto-pause` is configured
+pub fn init_auto_pause(config: &ConnectorConfig, health: Arc<SinkHealth>) -> Result<()> {
+    let Some(auto_pause) = config
+        .meta()
+        .consumer()
+        .and_then(|consumer| consumer.auto_pause.clone())
+    else {
+        return Ok(());
+    };
+
+    if auto_pause.probes.is_empty() {
+        return Err(anyhow!("auto-pause requires at least one probe"));
+    }
+
+    AUTO_PAUSE
+        .set(AutoPause::new(auto_pause, health))
+        .map_err(|_| anyhow!("auto-pause is already initialized"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use fluvio_connector_package::config::DependencyProbe;
+
+    use super::*;
+
+    #[test]
+    fn test_pause_until_available() {
+        //given
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("addr").to_string();
+        let health = Arc::new(SinkHealth::default());
+        let auto_pause = AutoPause::new(
+            AutoPauseConfig {
+                probes: vec![DependencyProbe::Tcp(address)],
+                after_failures: 2,
+                probe_interval: Duration::from_millis(10),
+                probe_timeout: Duration::from_millis(200),
+            },
+            health.clone(),
+        );
+        let err = anyhow!("connection refused");
+
+        //when
+        assert!(!auto_pause.record_failure(&err));
+        auto_pause.record_success();
+        assert!(!auto_pause.record_failure(&err));
+        let pause = auto_pause.record_failure(&err);
+
+        //then
+        assert!(pause);
+        fluvio_future::task::run_block_on(auto_pause.pause_until_available());
+        assert_eq!(auto_pause.failures(), 0);
+        assert!(!health.is_paused());
+    }
+}
@@ -1,15 +1,113 @@
-use fluvio::{TopicProducerPool, Fluvio, FluvioClusterConfig, TopicProducerConfigBuilder};
-use crate::tracing::info;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use fluvio::{
+    BufferOverflow, DeliverySemantic, Fluvio, FluvioAdmin, FluvioClusterConfig, Isolation,
+    ProduceOutput, RecordKey, RetryPolicy, RetryStrategy, TopicProducerConfig,
+    TopicProducerConfigBuilder, TopicProducerPool,
+};
+use fluvio::dataplane::record::RecordData;
+use fluvio_connector_package::config::{
+    AckPolicy, DeliveryConfig, DeliverySemanticConfig, MemoryBudgetOverflow, RetryBackoff,
+};
+use fluvio_future::task::spawn;
+use futures::future::join_all;
+use crate::tracing::{info, warn};
 use crate::{config::ConnectorConfig, Result};
 
-use crate::{ensure_topic_exists, smartmodule::smartmodule_chain_from_config};
+use crate::{ensure_topic_exists, ensure_topic_exists_on, smartmodule::smartmodule_chain_from_config};
+use crate::partitioning::ConnectorPartitioner;
+use crate::enrichment::{MetadataHook, RecordEnricher};
+use crate::windowing::{WindowAggregator, WindowOutput};
+
+/// Records queued per mirror target, records sent while the queue is full
+/// are dropped and counted as failed for the target
+pub const MIRROR_QUEUE_CAPACITY: usize = 10_000;
 
+/// Producer for the connector topic on the primary cluster. Mirror targets,
+/// windowing and enrichment require [`connector_producer_from_config`].
 pub async fn producer_from_config(config: &ConnectorConfig) -> Result<(Fluvio, TopicProducerPool)> {
+    let fluvio = connect_primary(config).await?;
+    let producer = topic_producer(&fluvio, config.meta().topic(), config).await?;
+
+    Ok((fluvio, producer))
+}
+
+/// Producer writing to the primary cluster and to the mirror targets of
+/// `meta.producer.mirrors`
+pub async fn connector_producer_from_config(
+    config: &ConnectorConfig,
+) -> Result<(Fluvio, ConnectorProducer)> {
+    let fluvio = connect_primary(config).await?;
+    let primary = topic_producer(&fluvio, config.meta().topic(), config).await?;
+    let meta = config.meta();
+    let mut mirrors = Vec::new();
+
+    for mirror in meta
+        .producer()
+        .and_then(|producer_params| producer_params.mirrors.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        let topic = mirror.topic.as_deref().unwrap_or(meta.topic());
+        let mut cluster_config = FluvioClusterConfig::load_with_profile(&mirror.profile)?
+            .ok_or_else(|| anyhow!("profile {} not found for mirror target", mirror.profile))?;
+        cluster_config.client_id = Some(format!("fluvio_connector_{}", &config.meta().name()));
+
+        let fluvio = Fluvio::connect_with_config(&cluster_config).await?;
+        let admin = FluvioAdmin::connect_with_config(&cluster_config).await?;
+        ensure_topic_exists_on(&admin, topic, config).await?;
+
+        info!(profile = %mirror.profile, topic, "Mirroring produced records");
+        let producer = topic_producer(&fluvio, topic, config).await?;
+
+        mirrors.push(MirrorTarget::spawn(
+            mirror.profile.clone(),
+            fluvio,
+            producer,
+        ));
+    }
+
+    Ok((
+        fluvio,
+        ConnectorProducer {
+            primary,
+            mirrors,
+            enricher: RecordEnricher::from_config(config),
+            windows: WindowAggregator::from_config(config)?.map(Mutex::new),
+            sent: AtomicU64::new(0),
+        },
+    ))
+}
+
+async fn connect_primary(config: &ConnectorConfig) -> Result<Fluvio> {
     let mut cluster_config = FluvioClusterConfig::load()?;
     cluster_config.client_id = Some(format!("fluvio_connector_{}", &config.meta().name()));
 
     let fluvio = Fluvio::connect_with_config(&cluster_config).await?;
     ensure_topic_exists(config).await?;
+
+    Ok(fluvio)
+}
+
+async fn topic_producer(
+    fluvio: &Fluvio,
+    topic: &str,
+    config: &ConnectorConfig,
+) -> Result<TopicProducerPool> {
+    let producer = fluvio
+        .topic_producer_with_config(topic, producer_config(config)?)
+        .await?;
+
+    if let Some(chain) = smartmodule_chain_from_config(config).await? {
+        Ok(producer.with_chain(chain).await?)
+    } else {
+        Ok(producer)
+    }
+}
+
+fn producer_config(config: &ConnectorConfig) -> Result<TopicProducerConfig> {
     let mut config_builder = &mut TopicProducerConfigBuilder::default();
 
     if let Some(producer_params) = &config.meta().producer() {
@@ -44,16 +142,537 @@ pub async fn producer_from_config(config: &ConnectorConfig) -> Result<(Fluvio, T
         if let Some(max_request_size) = producer_params.max_request_size {
             config_builder = config_builder.max_request_size(max_request_size.as_u64() as usize)
         };
+
+        // Memory budget
+        if let Some(memory_budget) = &producer_params.memory_budget {
+            info!(?memory_budget, "Using producer memory budget");
+            config_builder = config_builder
+                .max_buffered_bytes(memory_budget.max_bytes.as_u64() as usize)
+                .buffer_overflow(match memory_budget.on_exceeded {
+                    MemoryBudgetOverflow::Block => BufferOverflow::Block,
+                    MemoryBudgetOverflow::DropOldest => BufferOverflow::DropOldest,
+                    MemoryBudgetOverflow::Error => BufferOverflow::Error,
+                })
+        };
+
+        // Partitioning
+        if let Some(partitioning) = &producer_params.partitioning {
+            info!(?partitioning, "Using connector partitioner");
+            config_builder =
+                config_builder.partitioner(Arc::new(ConnectorPartitioner::new(partitioning)))
+        };
+
+        // Delivery
+        if let Some(delivery) = &producer_params.delivery {
+            info!(?delivery, "Using delivery config");
+            config_builder = config_builder.delivery_semantic(delivery_semantic(delivery));
+
+            if let Some(ack) = delivery.ack {
+                config_builder = config_builder.isolation(match ack {
+                    AckPolicy::Leader => Isolation::ReadUncommitted,
+                    AckPolicy::All => Isolation::ReadCommitted,
+                })
+            };
+
+            if let Some(timeout) = delivery.timeout {
+                config_builder = config_builder.timeout(timeout)
+            };
+        };
     };
 
-    let producer_config = config_builder.build()?;
-    let producer = fluvio
-        .topic_producer_with_config(config.meta().topic(), producer_config)
-        .await?;
+    Ok(config_builder.build()?)
+}
 
-    if let Some(chain) = smartmodule_chain_from_config(config).await? {
-        Ok((fluvio, producer.with_chain(chain).await?))
-    } else {
-        Ok((fluvio, producer))
+/// Maps the delivery config to the producer semantic, retry settings missing
+/// from the config keep the producer defaults
+fn delivery_semantic(delivery: &DeliveryConfig) -> DeliverySemantic {
+    match delivery.semantic {
+        DeliverySemanticConfig::AtMostOnce => DeliverySemantic::AtMostOnce,
+        DeliverySemanticConfig::AtLeastOnce => {
+            let mut policy = RetryPolicy::default();
+
+            if let Some(retry) = &delivery.retry {
+                policy.max_retries = retry.max_retries.unwrap_or(policy.max_retries);
+                policy.initial_delay = retry.initial_delay.unwrap_or(policy.initial_delay);
+                policy.max_delay = retry.max_delay.unwrap_or(policy.max_delay);
+                policy.timeout = retry.timeout.unwrap_or(policy.timeout);
+
+                if let Some(backoff) = retry.backoff {
+                    policy.strategy = match backoff {
+                        RetryBackoff::Fixed => RetryStrategy::FixedDelay,
+                        RetryBackoff::Exponential => RetryStrategy::ExponentialBackoff,
+                        RetryBackoff::Fibonacci => RetryStrategy::FibonacciBackoff,
+                    };
+                }
+            }
+
+            DeliverySemantic::AtLeastOnce(policy)
+        }
+    }
+}
+
+/// Producer handle writing to the primary cluster and to every mirror target.
+///
+/// Only primary cluster errors are returned to the caller. Records are
+/// queued for each mirror target and sent by a task per target, so a slow or
+/// unavailable mirror never delays the primary cluster. Mirror errors are
+/// logged and reported per target by [`ConnectorProducer::status`].
+pub struct ConnectorProducer {
+    primary: TopicProducerPool,
+    mirrors: Vec<MirrorTarget>,
+    enricher: Option<RecordEnricher>,
+    windows: Option<Mutex<WindowAggregator>>,
+    sent: AtomicU64,
+}
+
+struct MirrorTarget {
+    profile: String,
+    queue: async_channel::Sender<MirrorCommand>,
+    stats: Arc<TargetStats>,
+}
+
+enum MirrorCommand {
+    Send(RecordKey, RecordData),
+    /// Flushes the producer, then notifies the sender
+    Flush(async_channel::Sender<()>),
+}
+
+impl MirrorTarget {
+    /// Spawns the task sending the records queued for `producer`
+    fn spawn(profile: String, fluvio: Fluvio, producer: TopicProducerPool) -> Self {
+        let (queue, commands) = async_channel::bounded(MIRROR_QUEUE_CAPACITY);
+        let target = Self::new(profile, queue);
+        let profile = target.profile.clone();
+        let stats = target.stats.clone();
+
+        spawn(async move {
+            let _fluvio = fluvio;
+
+            while let Ok(command) = commands.recv().await {
+                match command {
+                    MirrorCommand::Send(key, value) => {
+                        stats.record(&profile, producer.send(key, value).await);
+                    }
+                    MirrorCommand::Flush(done) => {
+                        if let Err(err) = producer.flush().await {
+                            stats.record_error(&profile, err);
+                        }
+
+                        let _ = done.send(()).await;
+                    }
+                }
+            }
+        });
+
+        target
+    }
+
+    fn new(profile: String, queue: async_channel::Sender<MirrorCommand>) -> Self {
+        Self {
+            profile,
+            queue,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Queues a record without waiting for the target
+    fn enqueue(&self, key: RecordKey, value: RecordData) {
+        if let Err(err) = self.queue.try_send(MirrorCommand::Send(key, value)) {
+            let reason = if err.is_full() {
+                "mirror queue is full, record dropped"
+            } else {
+                "mirror task stopped, record dropped"
+            };
+
+            self.stats.record_error(&self.profile, anyhow!(reason));
+        }
+    }
+
+    /// Waits until the records queued so far are sent and flushed
+    async fn flush(&self) {
+        let (done, flushed) = async_channel::bounded(1);
+
+        if self.queue.send(MirrorCommand::Flush(done)).await.is_err()
+            || flushed.recv().await.is_err()
+        {
+            self.stats.record_error(
+                &self.profile,
+                anyhow!("mirror task stopped before flushing"),
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct TargetStats {
+    sent: AtomicU64,
+    failed: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl TargetStats {
+    fn record<T>(&self, profile: &str, result: Result<T>) {
+        match result {
+            Ok(_) => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(err) => self.record_error(profile, err),
+        }
+    }
+
+    fn record_error(&self, profile: &str, err: anyhow::Error) {
+        warn!(profile, %err, "Failed to produce to mirror target");
+        self.failed.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = Some(err.to_string());
+        }
+    }
+}
+
+/// Delivery status of a mirror target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetStatus {
+    pub profile: String,
+    /// Records accepted by the target producer
+    pub sent: u64,
+    /// Records the target producer failed to accept
+    pub failed: u64,
+    /// Records sent to the primary cluster but not to this target
+    pub lag: u64,
+    pub last_error: Option<String>,
+}
+
+impl ConnectorProducer {
+    /// Producer for the primary cluster
+    pub fn primary(&self) -> &TopicProducerPool {
+        &self.primary
+    }
+
+    /// Adds the metadata of `hook` to every record, only used if
+    /// `meta.producer.enrichment` is configured
+    pub fn with_metadata_hook(mut self, hook: Arc<dyn MetadataHook>) -> Self {
+        self.enricher = self.enricher.map(|enricher| enricher.with_hook(hook));
+        self
+    }
+
+    /// Sends a record to the primary cluster and every mirror target
+    pub async fn send(
+        &self,
+        key: impl Into<RecordKey>,
+        value: impl Into<RecordData>,
+    ) -> Result<ProduceOutput> {
+        let key: Option<RecordData> = key.into().into();
+        let mut value = value.into();
+
+        if let Some(enriched) = self.enricher.as_ref().and_then(|enricher| {
+            enricher.enrich(key.as_ref().map(|key| key.as_ref()), value.as_ref())
+        }) {
+            value = RecordData::from(enriched);
+        }
+
+        let output = self
+            .primary
+            .send(record_key(key.clone()), value.clone())
+            .await?;
+        self.sent.fetch_add(1, Ordering::Relaxed);
+
+        for mirror in &self.mirrors {
+            mirror.enqueue(record_key(key.clone()), value.clone());
+        }
+
+        Ok(output)
+    }
+
+    /// Adds a record to the windows configured by `meta.producer.windowing`
+    /// and sends the aggregates of the windows it closes, returning how many
+    /// records were sent. Without windowing, the record is sent as is.
+    pub async fn send_aggregated(&self, value: impl Into<RecordData>) -> Result<usize> {
+        let value = value.into();
+        let closed = match &self.windows {
+            Some(windows) => lock_windows(windows).push(value.as_ref(), now_millis()),
+            None => {
+                self.send(RecordKey::NULL, value).await?;
+                return Ok(1);
+            }
+        };
+
+        self.send_windows(closed).await
+    }
+
+    /// Sends the aggregates of the windows closed by the passing of time,
+    /// for sources which may not read records for a while. Windows of event
+    /// time only close as records are read.
+    pub async fn tick(&self) -> Result<usize> {
+        let closed = match &self.windows {
+            Some(windows) => {
+                let mut windows = lock_windows(windows);
+
+                if windows.uses_event_time() {
+                    Vec::new()
+                } else {
+                    windows.advance(now_millis())
+                }
+            }
+            None => Vec::new(),
+        };
+
+        self.send_windows(closed).await
+    }
+
+    /// Sends the aggregates of every open window and flushes, so no
+    /// aggregated record is lost when the connector stops
+    pub async fn shutdown(&self) -> Result<()> {
+        let open = match &self.windows {
+            Some(windows) => lock_windows(windows).flush(),
+            None => Vec::new(),
+        };
+
+        info!(windows = open.len(), "Flushing open windows on shutdown");
+        self.send_windows(open).await?;
+        self.flush().await
+    }
+
+    async fn send_windows(&self, windows: Vec<WindowOutput>) -> Result<usize> {
+        for window in &windows {
+            let key = match &window.key {
+                Some(key) => RecordKey::from(key.clone()),
+                None => RecordKey::NULL,
+            };
+
+            self.send(key, serde_json::to_vec(&window.to_value())?)
+                .await?;
+        }
+
+        Ok(windows.len())
+    }
+
+    /// Flushes the primary cluster and every mirror target
+    pub async fn flush(&self) -> Result<()> {
+        join_all(self.mirrors.iter().map(MirrorTarget::flush)).await;
+
+        self.primary.flush().await
+    }
+
+    /// Producer for the primary cluster, for connectors taking a
+    /// [`TopicProducerPool`]. Fails if mirror targets, enrichment or
+    /// windowing are configured, as records would never be mirrored, enriched
+    /// or aggregated.
+    pub fn into_primary(self) -> Result<TopicProducerPool> {
+        if !self.mirrors.is_empty() {
+            return Err(anyhow!(
+                "`meta.producer.mirrors` requires a connector taking a ConnectorProducer"
+            ));
+        }
+
+        if self.enricher.is_some() {
+            return Err(anyhow!(
+                "`meta.producer.enrichment` requires a connector taking a ConnectorProducer"
+            ));
+        }
+
+        if self.windows.is_some() {
+            return Err(anyhow!(
+                "`meta.producer.windowing` requires a connector taking a ConnectorProducer"
+            ));
+        }
+
+        Ok(self.into())
+    }
+
+    pub fn status(&self) -> Vec<TargetStatus> {
+        let primary_sent = self.sent.load(Ordering::Relaxed);
+
+        self.mirrors
+            .iter()
+            .map(|mirror| {
+                let sent = mirror.stats.sent.load(Ordering::Relaxed);
+
+                TargetStatus {
+                    profile: mirror.profile.clone(),
+                    sent,
+                    failed: mirror.stats.failed.load(Ordering::Relaxed),
+                    lag: primary_sent.saturating_sub(sent),
+                    last_error: mirror
+                        .stats
+                        .last_error
+                        .lock()
+                        .ok()
+                        .and_then(|last_error| last_error.clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Producer argument of a connector function, the `connector` macro converts
+/// the [`ConnectorProducer`] built from the connector config into it
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a connector producer",
+    label = "expected `ConnectorProducer` or `TopicProducerPool`",
+    note = "the producer argument of a source connector must be a `ConnectorProducer` or a `TopicProducerPool`"
+)]
+pub trait FromConnectorProducer: Sized {
+    fn from_connector_producer(producer: ConnectorProducer) -> Result<Self>;
+}
+
+impl FromConnectorProducer for ConnectorProducer {
+    fn from_connector_producer(producer: ConnectorProducer) -> Result<Self> {
+        Ok(producer)
+    }
+}
+
+impl FromConnectorProducer for TopicProducerPool {
+    fn from_connector_producer(producer: ConnectorProducer) -> Result<Self> {
+        producer.into_primary()
+    }
+}
+
+/// Allows connectors taking a [`TopicProducerPool`] to keep working, mirror
+/// targets are not written to in that case.
+impl From<ConnectorProducer> for TopicProducerPool {
+    fn from(producer: ConnectorProducer) -> Self {
+        if !producer.mirrors.is_empty() {
+            warn!(
+                "Mirror targets are ignored by connectors taking a TopicProducerPool, use ConnectorProducer instead"
+            );
+        }
+
+        if producer.windows.is_some() {
+            warn!(
+                "Windowing is ignored by connectors taking a TopicProducerPool, use ConnectorProducer instead"
+            );
+        }
+
+        if producer.enricher.is_some() {
+            warn!(
+                "Record enrichment is ignored by connectors taking a TopicProducerPool, use ConnectorProducer instead"
+            );
+        }
+
+        producer.primary
+    }
+}
+
+fn lock_windows(windows: &Mutex<WindowAggregator>) -> std::sync::MutexGuard<'_, WindowAggregator> {
+    windows.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn record_key(key: Option<RecordData>) -> RecordKey {
+    match key {
+        Some(key) => RecordKey::from(key),
+        None => RecordKey::NULL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_producer_config_delivery() {
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.2.1
+              producer:
+                delivery:
+                  ack: all
+                  timeout: 5s
+                  retry:
+                    max-retries: 3
+                    backoff: fixed
+        "#,
+        )
+        .expect("connector config");
+        let producer_config = producer_config(&config).expect("producer config");
+
+        assert_eq!(producer_config.isolation(), Isolation::ReadCommitted);
+        assert_eq!(producer_config.timeout(), Duration::from_secs(5));
+        assert_eq!(
+            producer_config.delivery_semantic(),
+            DeliverySemantic::AtLeastOnce(RetryPolicy {
+                max_retries: 3,
+                strategy: RetryStrategy::FixedDelay,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_producer_config_memory_budget() {
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.2.1
+              producer:
+                memory-budget:
+                  max-bytes: 8MB
+                  on-exceeded: error
+        "#,
+        )
+        .expect("connector config");
+        let producer_config = producer_config(&config).expect("producer config");
+
+        assert_eq!(producer_config.max_buffered_bytes(), Some(8_000_000));
+        assert_eq!(producer_config.buffer_overflow(), BufferOverflow::Error);
+    }
+
+    #[test]
+    fn test_at_most_once_delivery() {
+        let delivery = DeliveryConfig {
+            semantic: DeliverySemanticConfig::AtMostOnce,
+            ..Default::default()
+        };
+
+        assert_eq!(delivery_semantic(&delivery), DeliverySemantic::AtMostOnce);
+    }
+
+    #[test]
+    fn test_mirror_queue_full() {
+        //given
+        let (queue, commands) = async_channel::bounded(1);
+        let mirror = MirrorTarget::new("mirror".to_string(), queue);
+
+        //when
+        mirror.enqueue(RecordKey::NULL, RecordData::from("first"));
+        mirror.enqueue(RecordKey::NULL, RecordData::from("second"));
+
+        //then
+        assert_eq!(commands.len(), 1);
+        assert_eq!(mirror.stats.failed.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            mirror.stats.last_error.lock().unwrap().as_deref(),
+            Some("mirror queue is full, record dropped")
+        );
+    }
+
+    #[test]
+    fn test_target_stats() {
+        let stats = TargetStats::default();
+
+        stats.record("mirror", Ok(()));
+        stats.record::<()>("mirror", Err(anyhow!("connection refused")));
+
+        assert_eq!(stats.sent.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.failed.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            stats.last_error.lock().unwrap().as_deref(),
+            Some("connection refused")
+        );
     }
 }
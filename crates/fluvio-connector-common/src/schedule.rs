@@ -0,0 +1,455 @@
+//! Poll scheduling for batch-style source connectors.
+//!
+//! Sources querying an API or a database on a schedule configure it in
+//! `meta.schedule`, either as a fixed interval or a cron expression:
+//!
+//! ```yaml
+//! meta:
+//!   schedule:
+//!     cron: "*/15 * * * *"
+//!     jitter: 30s
+//! ```
+//!
+//! and drive their polls with a [`PollScheduler`]. Polls never overlap: the
+//! next trigger is only awaited once the previous poll completes, and
+//! triggers missed while a poll overran are skipped instead of fired in a
+//! burst.
+
+use std::future::Future;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Timelike, Utc};
+use fluvio_connector_package::config::ScheduleConfig;
+use fluvio_future::timer::sleep;
+use rand::Rng;
+
+use crate::tracing::{debug, warn};
+use crate::{config::ConnectorConfig, Result};
+
+/// Days searched for the next time matching a cron expression, long enough
+/// to reach February 29 from any date
+const CRON_SEARCH_DAYS: u32 = 366 * 8;
+
+/// What triggers a poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// Polls start every interval, the first one right away
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+/// Waits for the triggers of a [`Trigger`], delayed by a random jitter
+#[derive(Debug)]
+pub struct PollScheduler {
+    trigger: Trigger,
+    jitter: Duration,
+    /// Next tick of an interval trigger
+    next_tick: Option<Instant>,
+    /// Last cron trigger awaited
+    last_cron: Option<DateTime<Utc>>,
+}
+
+impl PollScheduler {
+    pub fn new(trigger: Trigger, jitter: Duration) -> Self {
+        Self {
+            trigger,
+            jitter,
+            next_tick: None,
+            last_cron: None,
+        }
+    }
+
+    /// Scheduler of the polls configured in `meta.schedule`, `None` when the
+    /// connector has no schedule
+    pub fn from_config(config: &ConnectorConfig) -> Result<Option<Self>> {
+        config
+            .meta()
+            .schedule()
+            .map(Self::from_schedule_config)
+            .transpose()
+    }
+
+    pub fn from_schedule_config(config: &ScheduleConfig) -> Result<Self> {
+        let trigger = match (&config.interval, &config.cron) {
+            (Some(interval), None) if interval.is_zero() => {
+                bail!("schedule interval must be greater than zero")
+            }
+            (Some(interval), None) => Trigger::Interval(*interval),
+            (None, Some(cron)) => Trigger::Cron(cron.parse()?),
+            (Some(_), Some(_)) => bail!("schedule accepts either an interval or a cron expression"),
+            (None, None) => bail!("schedule requires an interval or a cron expression"),
+        };
+
+        Ok(Self::new(trigger, config.jitter))
+    }
+
+    /// Waits for the next trigger. Triggers which passed since the previous
+    /// call, while the previous poll was running, are skipped.
+    pub async fn tick(&mut self) {
+        let delay = match &self.trigger {
+            Trigger::Interval(interval) => {
+                let now = Instant::now();
+                let (due, skipped) = catch_up(self.next_tick.unwrap_or(now), *interval, now);
+
+                if skipped > 0 {
+                    warn!(
+                        skipped,
+                        "poll overran its interval, skipping missed triggers"
+                    );
+                }
+
+                self.next_tick = Some(due + *interval);
+                due.saturating_duration_since(now)
+            }
+            Trigger::Cron(cron) => {
+                let now = Utc::now();
+
+                if let Some(missed) = self
+                    .last_cron
+                    .and_then(|last| cron.next_after(last))
+                    .filter(|next| *next <= now)
+                {
+                    warn!(%missed, "poll overran its cron schedule, skipping missed triggers");
+                }
+
+                match cron.next_after(now) {
+                    Some(next) => {
+                        self.last_cron = Some(next);
+                        (next - now).to_std().unwrap_or_default()
+                    }
+                    None => {
+                        warn!("cron schedule never triggers again");
+                        std::future::pending::<()>().await;
+                        return;
+                    }
+                }
+            }
+        };
+        let delay = delay + self.random_jitter();
+
+        debug!(?delay, "waiting for next poll");
+        sleep(delay).await;
+    }
+
+    /// Runs `poll` on every trigger until it fails
+    pub async fn run<F, Fut>(mut self, mut poll: F) -> Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        loop {
+            self.tick().await;
+            poll().await?;
+        }
+    }
+
+    fn random_jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+
+        rand::thread_rng().gen_range(Duration::ZERO..=self.jitter)
+    }
+}
+
+/// First tick at or after `now`, starting from the tick `due`, along with the
+/// number of ticks skipped to reach it
+fn catch_up(due: Instant, interval: Duration, now: Instant) -> (Instant, u32) {
+    if due >= now {
+        return (due, 0);
+    }
+
+    let behind = now - due;
+    let skipped = behind.as_nanos().div_ceil(interval.as_nanos());
+    let skipped = u32::try_from(skipped).unwrap_or(u32::MAX);
+
+    (due + interval * skipped, skipped)
+}
+
+/// Five field cron expression: minute, hour, day of month, month and day of
+/// week, evaluated in UTC. Fields accept `*`, values, ranges `a-b`, steps
+/// `*/n` or `a-b/n` and lists separated by commas. Months and days of week
+/// also accept their three letter names, and `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` are accepted as well.
+///
+/// As in cron, when both the day of month and the day of week are
+/// restricted, a day matching either of them matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day of month field is not `*`
+    restricted_day_of_month: bool,
+    /// Whether the day of week field is not `*`
+    restricted_day_of_week: bool,
+}
+
+impl CronSchedule {
+    /// First minute strictly after `time` matching the expression
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = time.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let mut date = start.date_naive();
+        let (mut from_hour, mut from_minute) = (start.hour(), start.minute());
+
+        for _ in 0..CRON_SEARCH_DAYS {
+            if self.matches_date(date) {
+                for hour in (from_hour..24).filter(|hour| has(self.hours, *hour)) {
+                    let first_minute = if hour == from_hour { from_minute } else { 0 };
+
+                    if let Some(minute) = (first_minute..60).find(|m| has(self.minutes, *m)) {
+                        return Some(date.and_hms_opt(hour, minute, 0)?.and_utc());
+                    }
+                }
+            }
+
+            date = date.succ_opt()?;
+            (from_hour, from_minute) = (0, 0);
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+
+        let day_of_month = has(self.days_of_month, date.day());
+        let day_of_week = has(self.days_of_week, date.weekday().num_days_from_sunday());
+
+        if self.restricted_day_of_month && self.restricted_day_of_week {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let expression = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [
+            minutes,
+            hours,
+            day_of_month_field,
+            months,
+            day_of_week_field,
+        ] = fields[..]
+        else {
+            bail!(
+                "invalid cron expression `{s}`, expected 5 fields: minute hour day-of-month month day-of-week"
+            );
+        };
+        let parse = |field: &str, name: &str, range: (u32, u32), names: &[&str]| {
+            parse_field(field, range, names)
+                .with_context(|| format!("invalid {name} field in cron expression `{s}`"))
+        };
+        let mut days_of_week = parse(day_of_week_field, "day of week", (0, 7), &DAY_NAMES)?;
+
+        // 7 is an alias of Sunday
+        if has(days_of_week, 7) {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse(minutes, "minute", (0, 59), &[])?,
+            hours: parse(hours, "hour", (0, 23), &[])?,
+            days_of_month: parse(day_of_month_field, "day of month", (1, 31), &[])?,
+            months: parse(months, "month", (1, 12), &MONTH_NAMES)?,
+            days_of_week,
+            restricted_day_of_month: !day_of_month_field.starts_with('*'),
+            restricted_day_of_week: !day_of_week_field.starts_with('*'),
+        })
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parses a cron field into a bit set of the values it matches within the
+/// inclusive `range`. `names` are the names of the values from the start of
+/// the range.
+fn parse_field(field: &str, (min, max): (u32, u32), names: &[&str]) -> Result<u64> {
+    let value = |value: &str| -> Result<u32> {
+        let lowercase = value.to_ascii_lowercase();
+        let parsed = match names.iter().position(|name| *name == lowercase) {
+            Some(idx) => min + idx as u32,
+            None => value
+                .parse()
+                .map_err(|_| anyhow!("`{value}` is not a number"))?,
+        };
+
+        if parsed < min || parsed > max {
+            bail!("{parsed} is out of range {min}-{max}");
+        }
+
+        Ok(parsed)
+    };
+    let mut set = 0;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow!("`{step}` is not a step"))?;
+
+                if step == 0 {
+                    bail!("step must be greater than zero");
+                }
+
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `a/n` steps from `a` to the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let value = value(range)?;
+                    (value, value)
+                }
+            },
+        };
+
+        if start > end {
+            bail!("range {start}-{end} is reversed");
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_next_cron_triggers() {
+        let every_quarter: CronSchedule = "*/15 * * * *".parse().unwrap();
+        let weekdays: CronSchedule = "30 9 * * mon-fri".parse().unwrap();
+        let leap_day: CronSchedule = "0 0 29 feb *".parse().unwrap();
+
+        assert_eq!(
+            every_quarter.next_after(utc(2026, 10, 15, 10, 7)),
+            Some(utc(2026, 10, 15, 10, 15))
+        );
+        assert_eq!(
+            every_quarter.next_after(utc(2026, 10, 15, 23, 45)),
+            Some(utc(2026, 10, 16, 0, 0))
+        );
+        // 2026-10-16 is a Friday
+        assert_eq!(
+            weekdays.next_after(utc(2026, 10, 16, 9, 30)),
+            Some(utc(2026, 10, 19, 9, 30))
+        );
+        assert_eq!(
+            leap_day.next_after(utc(2026, 10, 15, 0, 0)),
+            Some(utc(2028, 2, 29, 0, 0))
+        );
+        assert_eq!(
+            "@daily"
+                .parse::<CronSchedule>()
+                .unwrap()
+                .next_after(utc(2026, 10, 15, 0, 0)),
+            Some(utc(2026, 10, 16, 0, 0))
+        );
+    }
+
+    #[test]
+    fn matches_either_restricted_day() {
+        // The 1st of the month or any Sunday
+        let schedule: CronSchedule = "0 12 1 * 7".parse().unwrap();
+
+        // 2026-10-18 is a Sunday
+        assert_eq!(
+            schedule.next_after(utc(2026, 10, 15, 0, 0)),
+            Some(utc(2026, 10, 18, 12, 0))
+        );
+        assert_eq!(
+            schedule.next_after(utc(2026, 10, 25, 12, 0)),
+            Some(utc(2026, 11, 1, 12, 0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_cron_expressions() {
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("0 5-1 * * *".parse::<CronSchedule>().is_err());
+        assert!("0 0 * foo *".parse::<CronSchedule>().is_err());
+    }
+
+    #[test]
+    fn skips_missed_interval_ticks() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+
+        assert_eq!(catch_up(start, interval, start), (start, 0));
+        assert_eq!(
+            catch_up(start, interval, start + Duration::from_secs(25)),
+            (start + Duration::from_secs(30), 3)
+        );
+        assert_eq!(
+            catch_up(start, interval, start + Duration::from_secs(20)),
+            (start + Duration::from_secs(20), 2)
+        );
+    }
+
+    #[test]
+    fn builds_scheduler_from_config() {
+        let config = |interval: Option<u64>, cron: Option<&str>| ScheduleConfig {
+            interval: interval.map(Duration::from_secs),
+            cron: cron.map(str::to_string),
+            jitter: Duration::ZERO,
+        };
+
+        assert_eq!(
+            PollScheduler::from_schedule_config(&config(Some(60), None))
+                .unwrap()
+                .trigger,
+            Trigger::Interval(Duration::from_secs(60))
+        );
+        assert!(PollScheduler::from_schedule_config(&config(None, Some("@hourly"))).is_ok());
+        assert!(PollScheduler::from_schedule_config(&config(Some(60), Some("@hourly"))).is_err());
+        assert!(PollScheduler::from_schedule_config(&config(None, None)).is_err());
+        assert!(PollScheduler::from_schedule_config(&config(Some(0), None)).is_err());
+    }
+}
@@ -1,29 +1,210 @@
-use fluvio::{FluvioClusterConfig, SmartModuleInvocation, SmartModuleKind, SmartModuleExtraParams};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use fluvio::{
+    FluvioClusterConfig, SmartModuleInvocation, SmartModuleInvocationWasm, SmartModuleKind,
+    SmartModuleExtraParams,
+};
+use fluvio_connector_package::lock::{
+    InstalledSmartModule, LockedSmartModule, SmartModuleLock, SmartModuleReference, wasm_digest,
+};
+use fluvio_sc_schema::smartmodule::SmartModuleApiClient;
 
 use crate::{config::ConnectorConfig, Result};
 
+static SMARTMODULE_LOCK: OnceLock<SmartModuleLock> = OnceLock::new();
+
+/// Pins the SmartModules of the transforms to the ones in `lock`, see
+/// [`fluvio_connector_package::lock`]
+pub fn set_smartmodule_lock(lock: SmartModuleLock) -> Result<()> {
+    SMARTMODULE_LOCK
+        .set(lock)
+        .map_err(|_| anyhow!("smartmodule lock is already set"))
+}
+
+/// Prefix of `uses` values referring to a SmartModule file on the local
+/// filesystem, e.g. `file://./target/wasm32-wasip1/release/my_sm.wasm`
+pub const LOCAL_SMARTMODULE_PREFIX: &str = "file://";
+
+/// Where the WASM of a transformation step is loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmartModuleSource {
+    /// SmartModule published to the cluster, e.g. `infinyon/jolt@0.4.1`
+    Cluster(String),
+    /// `.wasm` file on the local filesystem, so a SmartModule can be tested
+    /// with a connector without being published to the hub
+    Local(PathBuf),
+}
+
+impl SmartModuleSource {
+    /// Parses the `uses` value of a transformation step. Values prefixed
+    /// with `file://` or ending with `.wasm` refer to local files, relative
+    /// paths are resolved against the working directory.
+    pub fn from_uses(uses: &str) -> Self {
+        if let Some(path) = uses.strip_prefix(LOCAL_SMARTMODULE_PREFIX) {
+            return Self::Local(PathBuf::from(path));
+        }
+
+        if Path::new(uses)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+        {
+            return Self::Local(PathBuf::from(uses));
+        }
+
+        Self::Cluster(uses.to_string())
+    }
+}
+
+/// The cluster SmartModule to load for `uses`, pinned by `lock` if any
+fn locked_smartmodule<'a>(
+    lock: Option<&'a SmartModuleLock>,
+    uses: &str,
+) -> Result<(String, Option<&'a LockedSmartModule>)> {
+    let Some(lock) = lock else {
+        return Ok((uses.to_string(), None));
+    };
+    let locked = lock.find(uses).ok_or_else(|| {
+        anyhow!("smartmodule {uses} is not in the lock file, regenerate it with `cdk lock`")
+    })?;
+
+    Ok((locked.name.clone(), Some(locked)))
+}
+
+/// Fetches the WASM of the cluster SmartModule used as `uses`, verifying it
+/// against the lock file when one is set
+async fn fetch_cluster_wasm(api_client: &SmartModuleApiClient, uses: &str) -> Result<Vec<u8>> {
+    let (name, locked) = locked_smartmodule(SMARTMODULE_LOCK.get(), uses)?;
+    let wasm = api_client
+        .get(name.clone())
+        .await?
+        .ok_or_else(|| anyhow!("smartmodule {name} not found"))?
+        .wasm
+        .as_raw_wasm()?;
+
+    if let Some(locked) = locked {
+        locked.verify(&wasm)?;
+    }
+
+    Ok(wasm)
+}
+
+async fn connect_smartmodule_api() -> Result<SmartModuleApiClient> {
+    SmartModuleApiClient::connect_with_config(FluvioClusterConfig::load()?.try_into()?).await
+}
+
+/// Resolves the cluster SmartModules used by the transforms of `config` to
+/// the newest installed SmartModules matching them, and pins them with the
+/// digest of their WASM
+pub async fn lock_smartmodules(
+    config: &ConnectorConfig,
+    api_client: &SmartModuleApiClient,
+) -> Result<SmartModuleLock> {
+    let installed: Vec<InstalledSmartModule> = api_client
+        .list_with_params::<String>(Vec::new(), true)
+        .await?
+        .into_iter()
+        .map(|smartmodule| {
+            let package = smartmodule.spec.meta.as_ref().map(|meta| &meta.package);
+
+            InstalledSmartModule {
+                name: smartmodule.name,
+                package: package.map(|package| format!("{}/{}", package.group, package.name)),
+                version: package
+                    .and_then(|package| semver::Version::parse(&package.version.to_string()).ok()),
+            }
+        })
+        .collect();
+    let mut lock = SmartModuleLock::default();
+
+    for step in config.transforms() {
+        let SmartModuleSource::Cluster(uses) = SmartModuleSource::from_uses(&step.uses) else {
+            continue;
+        };
+
+        if lock.find(&uses).is_some() {
+            continue;
+        }
+
+        let reference = SmartModuleReference::parse(&uses)?;
+        let selected = reference
+            .select(&installed)
+            .ok_or_else(|| anyhow!("no smartmodule installed in the cluster matches {uses}"))?;
+        let wasm = api_client
+            .get(selected.name.clone())
+            .await?
+            .ok_or_else(|| anyhow!("smartmodule {} not found", selected.name))?
+            .wasm
+            .as_raw_wasm()?;
+
+        lock.smartmodules.push(LockedSmartModule {
+            uses,
+            name: selected.name.clone(),
+            version: selected.version.clone(),
+            sha256: wasm_digest(&wasm),
+        });
+    }
+
+    Ok(lock)
+}
+
+/// Fails if a cluster SmartModule pinned in the lock file was replaced since
+/// the lock file was generated. Does nothing without a lock file.
+pub async fn verify_locked_smartmodules(config: &ConnectorConfig) -> Result<()> {
+    if SMARTMODULE_LOCK.get().is_none() {
+        return Ok(());
+    }
+
+    let mut api_client = None;
+
+    for step in config.transforms() {
+        if let SmartModuleSource::Cluster(uses) = SmartModuleSource::from_uses(&step.uses) {
+            let api_client = match &mut api_client {
+                Some(api_client) => api_client,
+                None => api_client.insert(connect_smartmodule_api().await?),
+            };
+
+            fetch_cluster_wasm(api_client, &uses).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_local_wasm(path: &Path) -> Result<Vec<u8>> {
+    tracing::info!(path = %path.display(), "loading local smartmodule");
+
+    std::fs::read(path)
+        .map_err(|err| anyhow!("unable to read smartmodule {}: {err}", path.display()))
+}
+
 pub async fn smartmodule_chain_from_config(
     config: &ConnectorConfig,
 ) -> Result<Option<fluvio::SmartModuleChainBuilder>> {
-    use fluvio_sc_schema::smartmodule::SmartModuleApiClient;
-
     let transforms = config.transforms();
 
     if transforms.is_empty() {
         return Ok(None);
     }
 
-    let api_client =
-        SmartModuleApiClient::connect_with_config(FluvioClusterConfig::load()?.try_into()?).await?;
+    let mut api_client = None;
     let mut builder = fluvio::SmartModuleChainBuilder::default();
 
     for step in transforms {
-        let wasm = api_client
-            .get(step.uses.clone())
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("smartmodule {} not found", step.uses))?
-            .wasm
-            .as_raw_wasm()?;
+        let wasm = match SmartModuleSource::from_uses(&step.uses) {
+            SmartModuleSource::Local(path) => read_local_wasm(&path)?,
+            SmartModuleSource::Cluster(uses) => {
+                // only connect to the cluster when a step needs it, local
+                // chains work without a running cluster
+                let api_client = match &mut api_client {
+                    Some(api_client) => api_client,
+                    None => api_client.insert(connect_smartmodule_api().await?),
+                };
+
+                fetch_cluster_wasm(api_client, &uses).await?
+            }
+        };
 
         // this ::from adds the smartmodule_name to the config
         let config = fluvio::SmartModuleConfig::from(step.clone());
@@ -33,18 +214,32 @@ pub async fn smartmodule_chain_from_config(
     Ok(Some(builder))
 }
 
-pub fn smartmodule_vec_from_config(config: &ConnectorConfig) -> Option<Vec<SmartModuleInvocation>> {
+/// Builds the consumer invocations of the transforms, local SmartModules are
+/// sent to the SPU as ad-hoc WASM. Cluster SmartModules are pinned by the
+/// lock file when one is set, see [`verify_locked_smartmodules`].
+pub fn smartmodule_vec_from_config(
+    config: &ConnectorConfig,
+) -> Result<Option<Vec<SmartModuleInvocation>>> {
     let transforms = config.transforms();
 
     if transforms.is_empty() {
-        return Some(Vec::default());
+        return Ok(Some(Vec::default()));
     }
 
-    Some(
-        transforms
-            .iter()
-            .map(|s| SmartModuleInvocation {
-                wasm: fluvio::SmartModuleInvocationWasm::Predefined(s.uses.clone()),
+    transforms
+        .iter()
+        .map(|s| {
+            let wasm = match SmartModuleSource::from_uses(&s.uses) {
+                SmartModuleSource::Local(path) => {
+                    SmartModuleInvocationWasm::adhoc_from_bytes(&read_local_wasm(&path)?)?
+                }
+                SmartModuleSource::Cluster(uses) => SmartModuleInvocationWasm::Predefined(
+                    locked_smartmodule(SMARTMODULE_LOCK.get(), &uses)?.0,
+                ),
+            };
+
+            Ok(SmartModuleInvocation {
+                wasm,
                 kind: SmartModuleKind::Generic(Default::default()),
                 params: SmartModuleExtraParams::new(
                     s.with
@@ -55,15 +250,15 @@ pub fn smartmodule_vec_from_config(config: &ConnectorConfig) -> Option<Vec<Smart
                 ),
                 name: Some(s.uses.clone()),
             })
-            .collect(),
-    )
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use fluvio::SmartModuleInvocationWasm;
     use fluvio_connector_package::config::ConnectorConfigV1;
     use fluvio_smartengine::transformation::{TransformationStep, Lookback};
 
@@ -85,7 +280,7 @@ mod tests {
         });
 
         //when
-        let res = smartmodule_vec_from_config(&config);
+        let res = smartmodule_vec_from_config(&config).unwrap();
 
         //then
         assert!(res.is_some());
@@ -104,4 +299,79 @@ mod tests {
             Some(Duration::from_secs(10))
         );
     }
+
+    #[test]
+    fn test_smartmodule_source_from_uses() {
+        assert_eq!(
+            SmartModuleSource::from_uses("infinyon/jolt@0.4.1"),
+            SmartModuleSource::Cluster("infinyon/jolt@0.4.1".to_string())
+        );
+        assert_eq!(
+            SmartModuleSource::from_uses("file://./target/my_sm.wasm"),
+            SmartModuleSource::Local(PathBuf::from("./target/my_sm.wasm"))
+        );
+        assert_eq!(
+            SmartModuleSource::from_uses("/tmp/my_sm.WASM"),
+            SmartModuleSource::Local(PathBuf::from("/tmp/my_sm.WASM"))
+        );
+    }
+
+    #[test]
+    fn test_locked_smartmodule() {
+        let lock = SmartModuleLock {
+            smartmodules: vec![LockedSmartModule {
+                uses: "infinyon/jolt@^0.4".to_string(),
+                name: "infinyon/jolt@0.4.3".to_string(),
+                version: Some(semver::Version::new(0, 4, 3)),
+                sha256: wasm_digest(b"wasm"),
+            }],
+        };
+
+        assert_eq!(
+            locked_smartmodule(None, "infinyon/jolt@^0.4").unwrap(),
+            ("infinyon/jolt@^0.4".to_string(), None)
+        );
+        assert_eq!(
+            locked_smartmodule(Some(&lock), "infinyon/jolt@^0.4").unwrap(),
+            (
+                "infinyon/jolt@0.4.3".to_string(),
+                Some(&lock.smartmodules[0])
+            )
+        );
+        assert!(locked_smartmodule(Some(&lock), "infinyon/regex-filter").is_err());
+    }
+
+    #[test]
+    fn test_local_smartmodule_to_vec() {
+        //given
+        let dir = tempfile::tempdir().unwrap();
+        let wasm_path = dir.path().join("my_sm.wasm");
+        std::fs::write(&wasm_path, b"\0asm").unwrap();
+        let step = |uses: String| TransformationStep {
+            uses,
+            ..Default::default()
+        };
+        let config = ConnectorConfig::V0_1_0(ConnectorConfigV1 {
+            meta: Default::default(),
+            transforms: vec![step(format!(
+                "{LOCAL_SMARTMODULE_PREFIX}{}",
+                wasm_path.display()
+            ))],
+        });
+
+        //when
+        let inv = smartmodule_vec_from_config(&config)
+            .unwrap()
+            .unwrap()
+            .remove(0);
+
+        //then
+        assert_eq!(inv.wasm.into_raw().unwrap(), b"\0asm");
+
+        let missing = ConnectorConfig::V0_1_0(ConnectorConfigV1 {
+            meta: Default::default(),
+            transforms: vec![step("missing/my_sm.wasm".to_string())],
+        });
+        assert!(smartmodule_vec_from_config(&missing).is_err());
+    }
 }
@@ -0,0 +1,174 @@
+use std::fmt::Display;
+
+use fluvio::metadata::topic::{CompressionAlgorithm, TopicSpec};
+use fluvio_connector_package::config::topic_config::{PartitionConfig, TopicConfig};
+
+/// Setting of an existing topic which differs from the connector topic config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicMismatch {
+    pub setting: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for TopicMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.setting, self.expected, self.actual
+        )
+    }
+}
+
+/// Compares the settings declared in `expected` with the `actual` topic.
+///
+/// Settings missing from the config are not compared, neither is the
+/// `partition` section when it is omitted and left to its defaults.
+pub fn topic_mismatches(expected: &TopicConfig, actual: &TopicSpec) -> Vec<TopicMismatch> {
+    let mut mismatches = Vec::new();
+    let mut compare = |setting: &'static str, expected: Option<String>, actual: String| {
+        if let Some(expected) = expected
+            && expected != actual
+        {
+            mismatches.push(TopicMismatch {
+                setting,
+                expected,
+                actual,
+            });
+        }
+    };
+    let partition = &expected.partition;
+    let storage = actual.get_storage();
+
+    if *partition != PartitionConfig::default() && partition.maps.is_none() {
+        compare(
+            "partitions",
+            partition.count.map(|count| count.to_string()),
+            actual.replicas().partitions().to_string(),
+        );
+        compare(
+            "replication",
+            partition
+                .replication
+                .map(|replication| replication.to_string()),
+            display_option(actual.replicas().replication_factor()),
+        );
+    }
+
+    compare(
+        "max partition size",
+        partition.max_size.map(|size| size.as_u64().to_string()),
+        display_option(storage.and_then(|storage| storage.max_partition_size)),
+    );
+    compare(
+        "retention time",
+        expected
+            .retention
+            .time
+            .map(|time| format!("{}s", time.as_secs())),
+        display_option(
+            actual
+                .get_clean_policy()
+                .map(|policy| format!("{}s", policy.retention_secs())),
+        ),
+    );
+    compare(
+        "segment size",
+        expected
+            .retention
+            .segment_size
+            .map(|size| size.as_u64().to_string()),
+        display_option(storage.and_then(|storage| storage.segment_size)),
+    );
+
+    let compression = &expected.compression.type_;
+
+    compare(
+        "compression",
+        (*compression != CompressionAlgorithm::Any).then(|| compression.to_string()),
+        actual.get_compression_type().to_string(),
+    );
+
+    mismatches
+}
+
+fn display_option(value: Option<impl Display>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "unset".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic_config(yaml: &str) -> TopicConfig {
+        yaml.parse().expect("topic config")
+    }
+
+    #[test]
+    fn test_matching_topic_has_no_mismatches() {
+        let config = topic_config(
+            r#"
+            meta:
+              name: events
+            partition:
+              count: 3
+              replication: 1
+            retention:
+              time: 2h
+            compression:
+              type: Gzip
+            "#,
+        );
+        let spec = TopicSpec::from(config.clone());
+
+        assert!(topic_mismatches(&config, &spec).is_empty());
+    }
+
+    #[test]
+    fn test_topic_mismatches() {
+        let config = topic_config(
+            r#"
+            meta:
+              name: events
+            partition:
+              count: 3
+            retention:
+              time: 2h
+              segment-size: 10 MB
+            compression:
+              type: Gzip
+            "#,
+        );
+        let spec = TopicSpec::new_computed(1, 1, Some(false));
+        let mismatches = topic_mismatches(&config, &spec);
+        let settings: Vec<&str> = mismatches.iter().map(|m| m.setting).collect();
+
+        assert_eq!(
+            settings,
+            vec![
+                "partitions",
+                "retention time",
+                "segment size",
+                "compression"
+            ]
+        );
+        assert_eq!(mismatches[0].to_string(), "partitions: expected 3, found 1");
+        assert_eq!(mismatches[2].actual, "unset");
+    }
+
+    #[test]
+    fn test_default_partition_config_is_not_compared() {
+        let config = topic_config(
+            r#"
+            meta:
+              name: events
+            "#,
+        );
+        let spec = TopicSpec::new_computed(4, 2, Some(false));
+
+        assert!(topic_mismatches(&config, &spec).is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use chrono::DateTime;
+use serde_json::{Map, Value};
+
+use fluvio_connector_package::config::{WindowKind, WindowingConfig};
+
+use crate::Result;
+use crate::config::ConnectorConfig;
+use crate::partitioning::KeyExtractor;
+use crate::tracing::warn;
+
+/// Aggregates records into the time windows configured by
+/// `meta.producer.windowing`.
+///
+/// Windows close once the watermark, the latest event time minus the
+/// allowed lateness, passes their end. Records falling only in closed
+/// windows are late and dropped.
+pub struct WindowAggregator {
+    size: i64,
+    slide: i64,
+    allowed_lateness: i64,
+    key: Option<KeyExtractor>,
+    timestamp: Option<String>,
+    fields: Vec<String>,
+    /// Open windows by start and key
+    windows: BTreeMap<(i64, Option<Vec<u8>>), WindowState>,
+    watermark: i64,
+    late: u64,
+}
+
+#[derive(Default)]
+struct WindowState {
+    count: u64,
+    fields: BTreeMap<String, FieldSummary>,
+}
+
+/// Sum, minimum and maximum of a numeric field in a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldSummary {
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl FieldSummary {
+    fn add(summary: Option<Self>, value: f64) -> Self {
+        match summary {
+            Some(summary) => Self {
+                sum: summary.sum + value,
+                min: summary.min.min(value),
+                max: summary.max.max(value),
+            },
+            None => Self {
+                sum: value,
+                min: value,
+                max: value,
+            },
+        }
+    }
+}
+
+/// Aggregate of the records of a closed window
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowOutput {
+    pub key: Option<Vec<u8>>,
+    /// Start of the window in milliseconds since UNIX Epoch, inclusive
+    pub start: i64,
+    /// End of the window in milliseconds since UNIX Epoch, exclusive
+    pub end: i64,
+    pub count: u64,
+    /// Summary of each configured field, by JSON Pointer
+    pub fields: BTreeMap<String, FieldSummary>,
+}
+
+impl WindowOutput {
+    /// JSON value produced for the window, e.g.
+    /// `{"key":"a","window_start":0,"window_end":60000,"count":3,"fields":{}}`
+    pub fn to_value(&self) -> Value {
+        let mut value = Map::new();
+
+        if let Some(key) = &self.key {
+            value.insert("key".to_string(), String::from_utf8_lossy(key).into());
+        }
+
+        value.insert("window_start".to_string(), self.start.into());
+        value.insert("window_end".to_string(), self.end.into());
+        value.insert("count".to_string(), self.count.into());
+        value.insert(
+            "fields".to_string(),
+            self.fields
+                .iter()
+                .map(|(field, summary)| {
+                    (
+                        field.clone(),
+                        serde_json::json!({
+                            "sum": summary.sum,
+                            "min": summary.min,
+                            "max": summary.max,
+                        }),
+                    )
+                })
+                .collect::<Map<String, Value>>()
+                .into(),
+        );
+
+        Value::Object(value)
+    }
+}
+
+impl WindowAggregator {
+    pub fn new(config: &WindowingConfig) -> Result<Self> {
+        let size = config.size.as_millis() as i64;
+        let slide = match config.kind {
+            WindowKind::Tumbling => size,
+            WindowKind::Sliding => config.slide.map_or(size, |slide| slide.as_millis() as i64),
+        };
+
+        if size <= 0 || slide <= 0 || slide > size {
+            return Err(anyhow!(
+                "windowing size must be positive and slide between 1ms and the size, got size {:?} and slide {:?}",
+                config.size,
+                config.slide
+            ));
+        }
+
+        Ok(Self {
+            size,
+            slide,
+            allowed_lateness: config.allowed_lateness.as_millis() as i64,
+            key: config.key.as_ref().map(KeyExtractor::new),
+            timestamp: config.timestamp.clone(),
+            fields: config.fields.clone(),
+            windows: BTreeMap::new(),
+            watermark: i64::MIN,
+            late: 0,
+        })
+    }
+
+    /// Aggregator for the connector, if windowing is configured
+    pub fn from_config(config: &ConnectorConfig) -> Result<Option<Self>> {
+        config
+            .meta()
+            .producer()
+            .and_then(|producer| producer.windowing.as_ref())
+            .map(Self::new)
+            .transpose()
+    }
+
+    /// Adds the record `value` read at `now`, in milliseconds since UNIX
+    /// Epoch, returning the windows closed since the last call
+    pub fn push(&mut self, value: &[u8], now: i64) -> Vec<WindowOutput> {
+        let parsed: Option<Value> = serde_json::from_slice(value).ok();
+        let time = match (&self.timestamp, &parsed) {
+            (Some(pointer), Some(parsed)) => {
+                parsed.pointer(pointer).and_then(event_time).unwrap_or(now)
+            }
+            _ => now,
+        };
+        let key = self.key.as_ref().and_then(|key| key.extract(value));
+        let mut start = time - time.rem_euclid(self.slide);
+        let mut added = false;
+
+        while start > time - self.size {
+            if start + self.size > self.watermark {
+                let window = self.windows.entry((start, key.clone())).or_default();
+
+                window.count += 1;
+
+                for field in &self.fields {
+                    if let Some(number) = parsed
+                        .as_ref()
+                        .and_then(|parsed| parsed.pointer(field))
+                        .and_then(Value::as_f64)
+                    {
+                        let summary = window.fields.get(field).copied();
+
+                        window
+                            .fields
+                            .insert(field.clone(), FieldSummary::add(summary, number));
+                    }
+                }
+
+                added = true;
+            }
+
+            start -= self.slide;
+        }
+
+        if !added {
+            self.late += 1;
+            warn!(time, watermark = self.watermark, "dropping late record");
+        }
+
+        self.advance(time)
+    }
+
+    /// Moves the watermark to `time` minus the allowed lateness, returning
+    /// the windows it closes. Used to close windows while no record is read.
+    pub fn advance(&mut self, time: i64) -> Vec<WindowOutput> {
+        self.watermark = self
+            .watermark
+            .max(time.saturating_sub(self.allowed_lateness));
+
+        let closed: Vec<_> = self
+            .windows
+            .keys()
+            .filter(|(start, _)| start + self.size <= self.watermark)
+            .cloned()
+            .collect();
+
+        closed
+            .into_iter()
+            .filter_map(|window| self.close(window))
+            .collect()
+    }
+
+    /// Closes every open window, e.g. on shutdown
+    pub fn flush(&mut self) -> Vec<WindowOutput> {
+        let open: Vec<_> = self.windows.keys().cloned().collect();
+
+        open.into_iter()
+            .filter_map(|window| self.close(window))
+            .collect()
+    }
+
+    /// Whether records are windowed by their event time rather than the
+    /// time they are read
+    pub fn uses_event_time(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    /// Records dropped for arriving after their windows closed
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+
+    fn close(&mut self, window: (i64, Option<Vec<u8>>)) -> Option<WindowOutput> {
+        let state = self.windows.remove(&window)?;
+        let (start, key) = window;
+
+        Some(WindowOutput {
+            key,
+            start,
+            end: start + self.size,
+            count: state.count,
+            fields: state.fields,
+        })
+    }
+}
+
+/// Milliseconds since UNIX Epoch of a number of milliseconds or an RFC 3339
+/// string
+fn event_time(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => number.as_i64(),
+        Value::String(time) => DateTime::parse_from_rfc3339(time)
+            .ok()
+            .map(|time| time.timestamp_millis()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn aggregator(kind: WindowKind, slide: Option<u64>) -> WindowAggregator {
+        WindowAggregator::new(&WindowingConfig {
+            kind,
+            size: Duration::from_secs(10),
+            slide: slide.map(Duration::from_secs),
+            key: Some("/device".to_string()),
+            timestamp: Some("/time".to_string()),
+            allowed_lateness: Duration::from_secs(2),
+            fields: vec!["/temperature".to_string()],
+        })
+        .expect("aggregator")
+    }
+
+    fn record(device: &str, time: i64, temperature: f64) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "device": device,
+            "time": time,
+            "temperature": temperature,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tumbling_windows_close_on_watermark() {
+        let mut windows = aggregator(WindowKind::Tumbling, None);
+
+        assert!(windows.push(&record("a", 1_000, 20.0), 0).is_empty());
+        assert!(windows.push(&record("b", 2_000, 30.0), 0).is_empty());
+        assert!(windows.push(&record("a", 9_000, 22.0), 0).is_empty());
+        // within the allowed lateness of the first window
+        assert!(windows.push(&record("a", 11_000, 25.0), 0).is_empty());
+
+        let closed = windows.push(&record("a", 12_000, 26.0), 0);
+
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].key.as_deref(), Some(&b"a"[..]));
+        assert_eq!((closed[0].start, closed[0].end), (0, 10_000));
+        assert_eq!(closed[0].count, 2);
+        assert_eq!(
+            closed[0].fields["/temperature"],
+            FieldSummary {
+                sum: 42.0,
+                min: 20.0,
+                max: 22.0
+            }
+        );
+        assert_eq!(closed[0].to_value()["fields"]["/temperature"]["max"], 22.0);
+        assert_eq!(closed[1].key.as_deref(), Some(&b"b"[..]));
+
+        // the window of late records is closed already
+        assert!(windows.push(&record("a", 3_000, 0.0), 0).is_empty());
+        assert_eq!(windows.late(), 1);
+
+        let flushed = windows.flush();
+
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].count, 2);
+        assert!(windows.flush().is_empty());
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        let mut windows = aggregator(WindowKind::Sliding, Some(5));
+
+        windows.push(&record("a", 7_000, 1.0), 0);
+
+        let flushed = windows.flush();
+        let starts: Vec<i64> = flushed.iter().map(|window| window.start).collect();
+
+        assert_eq!(starts, vec![0, 5_000]);
+        assert!(
+            WindowAggregator::new(&WindowingConfig {
+                kind: WindowKind::Sliding,
+                size: Duration::from_secs(1),
+                slide: Some(Duration::from_secs(2)),
+                key: None,
+                timestamp: None,
+                allowed_lateness: Duration::ZERO,
+                fields: Vec::new(),
+            })
+            .is_err()
+        );
+    }
+}
@@ -23,10 +23,13 @@ fn generate_source(func: &ConnectorFn) -> TokenStream {
             let stop_signal = ::fluvio_connector_common::consumer::init_ctrlc()?;
 
             ::fluvio_connector_common::future::run_block_on(async {
-                let (fluvio, producer) = ::fluvio_connector_common::producer::producer_from_config(&common_config).await?;
+                ::fluvio_connector_common::dependency::wait_for_dependencies(&common_config).await?;
+                let (fluvio, producer) = ::fluvio_connector_common::producer::connector_producer_from_config(&common_config).await?;
+                let producer = ::fluvio_connector_common::producer::FromConnectorProducer::from_connector_producer(producer)?;
 
                 let metrics = ::std::sync::Arc::new(::fluvio_connector_common::monitoring::ConnectorMetrics::new(fluvio.metrics()));
                 ::fluvio_connector_common::monitoring::init_monitoring(metrics);
+                let heartbeat = ::fluvio_connector_common::heartbeat::init_heartbeat(&fluvio, &common_config).await?;
 
                 ::fluvio_connector_common::future::select! {
                     user_fn_result = async {
@@ -40,6 +43,7 @@ fn generate_source(func: &ConnectorFn) -> TokenStream {
                             },
                         }
                     },
+                    _ = heartbeat => {},
                     _ = stop_signal.recv() => {
                         ::fluvio_connector_common::tracing::info!("Stop signal received, shutting down connector.");
                     },
@@ -66,10 +70,14 @@ fn generate_sink(func: &ConnectorFn) -> TokenStream {
             let stop_signal = ::fluvio_connector_common::consumer::init_ctrlc()?;
 
             ::fluvio_connector_common::future::run_block_on(async {
+                ::fluvio_connector_common::dependency::wait_for_dependencies(&common_config).await?;
                 let (fluvio, mut stream) = ::fluvio_connector_common::consumer::consumer_stream_from_config(&common_config).await?;
 
-                let metrics = ::std::sync::Arc::new(::fluvio_connector_common::monitoring::ConnectorMetrics::new(fluvio.metrics()));
+                let sink_health = ::fluvio_connector_common::lag::init_lag_reporting(&common_config);
+                ::fluvio_connector_common::pause::init_auto_pause(&common_config, sink_health.clone())?;
+                let metrics = ::std::sync::Arc::new(::fluvio_connector_common::monitoring::ConnectorMetrics::new(fluvio.metrics()).with_sink_health(sink_health));
                 ::fluvio_connector_common::monitoring::init_monitoring(metrics);
+                let heartbeat = ::fluvio_connector_common::heartbeat::init_heartbeat(&fluvio, &common_config).await?;
 
                 ::fluvio_connector_common::future::select! {
                     user_fn_result = async {
@@ -83,6 +91,7 @@ fn generate_sink(func: &ConnectorFn) -> TokenStream {
                             },
                         }
                     },
+                    _ = heartbeat => {},
                     _ = stop_signal.recv() => {
                         ::fluvio_connector_common::tracing::info!("Stop signal received, shutting down connector.");
                     },
@@ -99,66 +108,23 @@ fn generate_sink(func: &ConnectorFn) -> TokenStream {
 
 fn init_and_parse_config(config_type_path: &Path) -> TokenStream {
     quote! {
-        #[derive(Debug)]
-        pub struct ConnectorOpt {
-            config: ::std::path::PathBuf,
-            secrets: Option<::std::path::PathBuf>
-        }
-
-        impl ConnectorOpt {
-            fn parse() -> Self {
-                let path = ::std::env::args()
-                    .enumerate()
-                    .find(|(_, a)| a.eq("--config"))
-                    .and_then(|(i, _)| ::std::env::args().nth(i + 1))
-                    .map(::std::path::PathBuf::from);
-                let secrets = ::std::env::args()
-                    .enumerate()
-                    .find(|(_, a)| a.eq("--secrets"))
-                    .and_then(|(i, _)| ::std::env::args().nth(i + 1))
-                    .map(::std::path::PathBuf::from);
-
-                match path {
-                    Some(config) => Self {config, secrets},
-                    None => {
-                        eprintln!("error: The following required arguments were not provided:\n  --config <PATH>");
-                        ::std::process::exit(1)
-                    }
-                }
-            }
-        }
-
         ::fluvio_connector_common::future::init_logger();
 
-        let opts = ConnectorOpt::parse();
-
-        match &opts.secrets {
-            Some(secrets) => {
-                ::fluvio_connector_common::tracing::info!("Using FileSecretStore");
-                ::fluvio_connector_common::secret::set_default_secret_store(
-                    ::std::boxed::Box::new(::fluvio_connector_common::secret::FileSecretStore::from(secrets)))?;
-            },
-            None => {
-                ::fluvio_connector_common::tracing::info!("Using EnvSecretStore");
-                ::fluvio_connector_common::secret::set_default_secret_store(
-                    ::std::boxed::Box::new(::fluvio_connector_common::secret::EnvSecretStore))?;
-            }
-        };
+        let cli = <::fluvio_connector_common::cli::ConnectorCli as ::fluvio_connector_common::cli::Parser>::parse();
 
-        ::fluvio_connector_common::tracing::info!("Reading config file from: {}", opts.config.to_string_lossy());
-
-        let config_str = ::std::fs::read_to_string(opts.config.as_path())?;
-        ::fluvio_connector_common::tracing::debug!(%config_str, "input config");
-
-        /// Resolve any secrets/env in the config
-        let config_str_resolved =::fluvio_connector_common::render_config_str(&config_str)?;
-
-        let config_value = ::fluvio_connector_common::config::value_from_reader(config_str_resolved.as_bytes())?;
+        if cli.print_schema {
+            return ::fluvio_connector_common::cli::print_schema();
+        }
 
-        let common_config = ::fluvio_connector_common::config::ConnectorConfig::from_value(config_value.clone())?;
+        let ::fluvio_connector_common::cli::LoadedConfig { common: common_config, value: config_value } = cli.load()?;
 
         let user_config: #config_type_path = ::fluvio_connector_common::config::from_value(config_value, Some(#config_type_path::__config_name()))?;
 
+        if cli.validate_config {
+            println!("Config is valid");
+            return Ok(());
+        }
+
         ::fluvio_connector_common::tracing::info!(conn_type=common_config.r#type(), conn_name=common_config.name(), conn_version=common_config.version(), "Starting Processing");
     }
 }
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::ops::Deref;
@@ -6,7 +7,7 @@ use std::path::{PathBuf, Path};
 use std::str::FromStr;
 use std::time::Duration;
 
-use schemars::JsonSchema;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema, schema_for};
 use serde::de::{Visitor, SeqAccess};
 use serde::ser::{SerializeMap, SerializeSeq};
 use tracing::debug;
@@ -129,6 +130,20 @@ mod v1 {
 
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub secrets: Option<Vec<SecretConfig>>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub heartbeat: Option<HeartbeatConfig>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub schedule: Option<ScheduleConfig>,
+
+        #[serde(
+            rename = "wait-for",
+            alias = "wait_for",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub wait_for: Option<WaitForConfig>,
     }
 
     impl MetaConfigV1 {
@@ -155,7 +170,7 @@ mod v2 {
 
     use super::*;
 
-    #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
     pub struct ConnectorConfigV2 {
         pub meta: MetaConfigV2,
 
@@ -163,7 +178,7 @@ mod v2 {
         pub transforms: Vec<TransformationStep>,
     }
 
-    #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
     pub struct MetaConfigV2 {
         pub name: String,
 
@@ -182,6 +197,20 @@ mod v2 {
 
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub secrets: Option<Vec<SecretConfig>>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub heartbeat: Option<HeartbeatConfig>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub schedule: Option<ScheduleConfig>,
+
+        #[serde(
+            rename = "wait-for",
+            alias = "wait_for",
+            skip_serializing_if = "Option::is_none",
+            default
+        )]
+        pub wait_for: Option<WaitForConfig>,
     }
 
     impl MetaConfigV2 {
@@ -266,6 +295,27 @@ impl MetaConfig<'_> {
         }
     }
 
+    pub fn heartbeat(&self) -> Option<&HeartbeatConfig> {
+        match self {
+            MetaConfig::V0_1_0(inner) => inner.heartbeat.as_ref(),
+            MetaConfig::V0_2_0(inner) => inner.heartbeat.as_ref(),
+        }
+    }
+
+    pub fn schedule(&self) -> Option<&ScheduleConfig> {
+        match self {
+            MetaConfig::V0_1_0(inner) => inner.schedule.as_ref(),
+            MetaConfig::V0_2_0(inner) => inner.schedule.as_ref(),
+        }
+    }
+
+    pub fn wait_for(&self) -> Option<&WaitForConfig> {
+        match self {
+            MetaConfig::V0_1_0(inner) => inner.wait_for.as_ref(),
+            MetaConfig::V0_2_0(inner) => inner.wait_for.as_ref(),
+        }
+    }
+
     pub fn topic_config(&self) -> Option<&topic_config::TopicConfig> {
         match self {
             MetaConfig::V0_1_0(_) => None,
@@ -274,7 +324,7 @@ impl MetaConfig<'_> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct ConsumerParameters {
     #[serde(default)]
@@ -285,11 +335,56 @@ pub struct ConsumerParameters {
         default,
         alias = "max_bytes"
     )]
+    #[schemars(with = "Option::<String>")]
     pub max_bytes: Option<ByteSize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offset: Option<ConsumerOffsetConfig>,
+    /// Pausing of sinks while the system they write to is unavailable
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "auto_pause")]
+    pub auto_pause: Option<AutoPauseConfig>,
+}
+
+/// Sinks pause consuming after consecutive write failures, and resume once
+/// every probe of the system they write to succeeds
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct AutoPauseConfig {
+    pub probes: Vec<DependencyProbe>,
+
+    /// Consecutive write failures before the sink pauses
+    #[serde(
+        default = "default_auto_pause_after_failures",
+        alias = "after_failures"
+    )]
+    pub after_failures: u32,
+
+    /// Time between probes while the sink is paused
+    #[serde(
+        with = "humantime_serde",
+        default = "default_auto_pause_probe_interval",
+        alias = "probe_interval"
+    )]
+    #[schemars(with = "String")]
+    pub probe_interval: Duration,
+
+    /// Time a single probe may take
+    #[serde(
+        with = "humantime_serde",
+        default = "default_probe_timeout",
+        alias = "probe_timeout"
+    )]
+    #[schemars(with = "String")]
+    pub probe_timeout: Duration,
+}
+
+fn default_auto_pause_after_failures() -> u32 {
+    3
+}
+
+fn default_auto_pause_probe_interval() -> Duration {
+    Duration::from_secs(5)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
@@ -321,7 +416,501 @@ pub struct ProducerParameters {
     )]
     #[schemars(skip)]
     pub max_request_size: Option<ByteSize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitioning: Option<PartitioningConfig>,
+
+    /// Additional clusters receiving a copy of every produced record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirrors: Option<Vec<MirrorTargetConfig>>,
+
+    /// Delivery guarantees of produced records
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<DeliveryConfig>,
+
+    /// Limit of the bytes buffered by the producer before records are sent
+    #[serde(
+        rename = "memory-budget",
+        alias = "memory_budget",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub memory_budget: Option<MemoryBudgetConfig>,
+
+    /// Provenance metadata added to every produced record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<EnrichmentConfig>,
+
+    /// Aggregation of records into time windows before they are produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windowing: Option<WindowingConfig>,
+}
+
+/// Metadata describing where a record comes from, added to the record value
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct EnrichmentConfig {
+    /// Field of the record holding the metadata
+    #[serde(default = "default_enrichment_field")]
+    pub field: String,
+
+    /// Metadata about the connector and the ingestion
+    #[serde(default = "default_enrichment_include")]
+    pub include: Vec<RecordMetadataField>,
+
+    /// Fixed source identifiers, e.g. `region: eu-west-1`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub mode: EnrichmentMode,
+}
+
+fn default_enrichment_field() -> String {
+    "_meta".to_string()
+}
+
+fn default_enrichment_include() -> Vec<RecordMetadataField> {
+    vec![
+        RecordMetadataField::Connector,
+        RecordMetadataField::Version,
+        RecordMetadataField::IngestTimestamp,
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordMetadataField {
+    /// Connector name
+    Connector,
+    /// Connector type, e.g. `http-source`
+    Type,
+    /// Connector version
+    Version,
+    /// Milliseconds since UNIX Epoch when the record was produced
+    IngestTimestamp,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnrichmentMode {
+    /// Insert the metadata field into JSON object values, other values are
+    /// produced unchanged
+    #[default]
+    Merge,
+    /// Wrap every value in a JSON object with the metadata field and a
+    /// `value` field, values which are not JSON become strings
+    Envelope,
+}
+
+/// Records aggregated client-side into time windows, one record is produced
+/// per window and key once the watermark passes the end of the window
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowingConfig {
+    #[serde(default)]
+    pub kind: WindowKind,
+
+    /// Length of each window
+    #[serde(with = "humantime_serde")]
+    #[schemars(with = "String")]
+    pub size: Duration,
+
+    /// Interval between the starts of sliding windows, defaults to `size`
+    #[serde(with = "humantime_serde")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option::<String>")]
+    pub slide: Option<Duration>,
+
+    /// JSON Pointer to the field records are grouped by, e.g. `/device`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// JSON Pointer to the event time of records, in milliseconds since UNIX
+    /// Epoch or RFC 3339. Records are windowed by the time they are read if
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+
+    /// Delay of the watermark behind the latest event time, records arriving
+    /// later than that are dropped
+    #[serde(with = "humantime_serde", default, alias = "allowed_lateness")]
+    #[schemars(with = "String")]
+    pub allowed_lateness: Duration,
+
+    /// JSON Pointers to numeric fields summarized with their sum, minimum and
+    /// maximum in each window
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowKind {
+    /// Consecutive windows which don't overlap
+    #[default]
+    Tumbling,
+    /// Windows starting every `slide`, a record belongs to every window it
+    /// falls in
+    Sliding,
+}
+
+/// Bytes the producer may buffer across the batches of all partitions, and
+/// what happens to new records once the budget is used up
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct MemoryBudgetConfig {
+    #[serde(with = "::bytesize_serde", alias = "max_bytes")]
+    #[schemars(with = "String")]
+    pub max_bytes: ByteSize,
+
+    #[serde(default, alias = "on_exceeded")]
+    pub on_exceeded: MemoryBudgetOverflow,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemoryBudgetOverflow {
+    /// Wait until buffered records are sent
+    #[default]
+    Block,
+    /// Discard the oldest buffered batch, its records are lost
+    DropOldest,
+    /// Fail the record being produced
+    Error,
+}
+
+/// Delivery semantic, acknowledgement policy and retries of the producer
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeliveryConfig {
+    #[serde(default)]
+    pub semantic: DeliverySemanticConfig,
+
+    /// Acknowledgement required before a record is considered produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ack: Option<AckPolicy>,
+
+    /// Time the SPU is allowed to take to process a produce request
+    #[serde(with = "humantime_serde")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option::<String>")]
+    pub timeout: Option<Duration>,
+
+    /// Retries of failed produce requests, only used by `at-least-once`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeliverySemanticConfig {
+    /// Records are sent without waiting for a response, failed records are
+    /// lost
+    AtMostOnce,
+    /// Records are retried until the SPU acknowledges them
+    #[default]
+    AtLeastOnce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AckPolicy {
+    /// The partition leader accepted the record
+    Leader,
+    /// The record was replicated to the in-sync replicas
+    All,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetryConfig {
+    /// Retries of a produce request, `0` disables retries
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        alias = "max_retries"
+    )]
+    pub max_retries: Option<usize>,
+
+    /// Delay before the first retry
+    #[serde(with = "humantime_serde", alias = "initial_delay")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option::<String>")]
+    pub initial_delay: Option<Duration>,
+
+    /// Upper limit of the delay between retries
+    #[serde(with = "humantime_serde", alias = "max_delay")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option::<String>")]
+    pub max_delay: Option<Duration>,
+
+    /// Time spent retrying before the record is given up on
+    #[serde(with = "humantime_serde")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option::<String>")]
+    pub timeout: Option<Duration>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff: Option<RetryBackoff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetryBackoff {
+    Fixed,
+    Exponential,
+    Fibonacci,
+}
+
+/// Cluster receiving a copy of the records produced to the primary cluster
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct MirrorTargetConfig {
+    /// Fluvio profile used to connect to the cluster
+    pub profile: String,
+
+    /// Topic to produce to, defaults to the connector topic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+}
+
+/// Record key extraction and partition assignment for produced records
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct PartitioningConfig {
+    /// JSON Pointer (RFC 6901) to the record field used as key, e.g. `/user/id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Hashing function used to map keys to partitions
+    #[serde(default)]
+    pub hash: KeyHashStrategy,
+
+    /// Assign records without key to the same partition for a number of
+    /// records before moving to the next one, instead of round-robin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sticky: Option<StickyPartitionerConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyHashStrategy {
+    /// Same hashing used by the Fluvio producer by default
+    #[default]
+    Siphash,
+    /// 32-bit FNV-1a
+    Fnv1a,
+    /// Murmur2, compatible with the Kafka default partitioner
+    Murmur2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct StickyPartitionerConfig {
+    /// Number of records sent to a partition before switching to the next one
+    #[serde(default = "default_sticky_batch_records", alias = "batch_records")]
+    pub batch_records: u32,
+}
+
+impl Default for StickyPartitionerConfig {
+    fn default() -> Self {
+        Self {
+            batch_records: default_sticky_batch_records(),
+        }
+    }
+}
+
+fn default_sticky_batch_records() -> u32 {
+    100
+}
+
+/// Periodic liveness records produced by the connector
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct HeartbeatConfig {
+    /// Topic where heartbeat records are produced
+    pub topic: String,
+
+    /// Time between heartbeat records, greater than zero
+    #[serde(
+        serialize_with = "humantime_serde::serialize",
+        deserialize_with = "deserialize_heartbeat_interval",
+        default = "default_heartbeat_interval"
+    )]
+    #[schemars(with = "String")]
+    pub interval: Duration,
+}
+
+fn default_heartbeat_interval() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn deserialize_heartbeat_interval<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let interval: Duration = humantime_serde::deserialize(deserializer)?;
+
+    if interval.is_zero() {
+        return Err(serde::de::Error::custom(
+            "heartbeat interval must be greater than zero",
+        ));
+    }
+
+    Ok(interval)
+}
+
+/// Triggers of the polls of batch-style source connectors, either a fixed
+/// `interval` or a `cron` expression
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScheduleConfig {
+    /// Time between polls, the first poll starts right away
+    #[serde(
+        with = "humantime_serde",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>")]
+    pub interval: Option<Duration>,
+
+    /// Cron expression polls are triggered on, in UTC, e.g. `*/15 * * * *`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+
+    /// Upper bound of a random delay added to every trigger, spreading the
+    /// polls of connectors sharing a schedule
+    #[serde(with = "humantime_serde", default)]
+    #[schemars(with = "String")]
+    pub jitter: Duration,
+}
+
+/// External systems probed before the connector connects to the cluster
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct WaitForConfig {
+    pub dependencies: Vec<DependencyProbe>,
+
+    /// Time spent waiting for all dependencies before the connector fails
+    #[serde(with = "humantime_serde", default = "default_wait_for_timeout")]
+    #[schemars(with = "String")]
+    pub timeout: Duration,
+
+    /// Time a single probe may take
+    #[serde(
+        with = "humantime_serde",
+        default = "default_probe_timeout",
+        alias = "probe_timeout"
+    )]
+    #[schemars(with = "String")]
+    pub probe_timeout: Duration,
+
+    /// Delay before probing a dependency again, doubled after every failure
+    #[serde(
+        with = "humantime_serde",
+        default = "default_wait_for_initial_delay",
+        alias = "initial_delay"
+    )]
+    #[schemars(with = "String")]
+    pub initial_delay: Duration,
+
+    /// Upper limit of the delay between probes
+    #[serde(
+        with = "humantime_serde",
+        default = "default_wait_for_max_delay",
+        alias = "max_delay"
+    )]
+    #[schemars(with = "String")]
+    pub max_delay: Duration,
+}
+
+fn default_wait_for_timeout() -> Duration {
+    Duration::from_secs(300)
+}
+
+fn default_probe_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_wait_for_initial_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_wait_for_max_delay() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// Check that an external system accepts connections, written as a single
+/// key map, e.g. `tcp: broker:9092`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(try_from = "ProbeFields", into = "ProbeFields")]
+#[schemars(with = "ProbeFields")]
+pub enum DependencyProbe {
+    /// `host:port` accepting TCP connections
+    Tcp(String),
+    /// URL answering a GET request with a 2xx status
+    Http(String),
+    /// `postgres://` or `mysql://` URL of a database server answering the
+    /// protocol handshake
+    Sql(String),
+}
+
+#[derive(Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ProbeFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tcp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    http: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sql: Option<String>,
+}
+
+impl TryFrom<ProbeFields> for DependencyProbe {
+    type Error = String;
+
+    fn try_from(fields: ProbeFields) -> std::result::Result<Self, Self::Error> {
+        match fields {
+            ProbeFields {
+                tcp: Some(address),
+                http: None,
+                sql: None,
+            } => Ok(Self::Tcp(address)),
+            ProbeFields {
+                tcp: None,
+                http: Some(url),
+                sql: None,
+            } => Ok(Self::Http(url)),
+            ProbeFields {
+                tcp: None,
+                http: None,
+                sql: Some(url),
+            } => Ok(Self::Sql(url)),
+            _ => Err("dependency probe must have exactly one of tcp, http or sql".to_string()),
+        }
+    }
 }
+
+impl From<DependencyProbe> for ProbeFields {
+    fn from(probe: DependencyProbe) -> Self {
+        match probe {
+            DependencyProbe::Tcp(address) => Self {
+                tcp: Some(address),
+                ..Default::default()
+            },
+            DependencyProbe::Http(url) => Self {
+                http: Some(url),
+                ..Default::default()
+            },
+            DependencyProbe::Sql(url) => Self {
+                sql: Some(url),
+                ..Default::default()
+            },
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash, JsonSchema)]
 pub struct SecretConfig {
     /// The name of the secret. It can only contain alphanumeric ASCII characters and underscores. It cannot start with a number.
@@ -417,6 +1006,23 @@ pub enum ConsumerPartitionConfig {
     Many(Vec<PartitionId>),
 }
 
+impl JsonSchema for ConsumerPartitionConfig {
+    fn schema_name() -> Cow<'static, str> {
+        "ConsumerPartitionConfig".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "`all`, a partition or a list of partitions",
+            "anyOf": [
+                { "const": "all" },
+                { "type": "integer", "minimum": 0 },
+                { "type": "array", "items": { "type": "integer", "minimum": 0 } }
+            ]
+        })
+    }
+}
+
 struct PartitionConfigVisitor;
 impl<'de> Visitor<'de> for PartitionConfigVisitor {
     type Value = ConsumerPartitionConfig;
@@ -494,7 +1100,7 @@ impl Serialize for ConsumerPartitionConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct ConsumerOffsetConfig {
     #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -540,6 +1146,32 @@ impl Serialize for OffsetConfig {
     }
 }
 
+impl JsonSchema for OffsetConfig {
+    fn schema_name() -> Cow<'static, str> {
+        "OffsetConfig".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        let offset = |key: &str| {
+            json_schema!({
+                "type": "object",
+                "properties": { key: { "type": "integer" } },
+                "required": [key],
+                "additionalProperties": false
+            })
+        };
+
+        json_schema!({
+            "anyOf": [
+                { "enum": ["beginning", "end"] },
+                offset("absolute"),
+                offset("from-beginning"),
+                offset("from-end")
+            ]
+        })
+    }
+}
+
 struct OffsetConfigVisitor;
 impl<'de> Visitor<'de> for OffsetConfigVisitor {
     type Value = OffsetConfig;
@@ -589,7 +1221,7 @@ impl<'de> Deserialize<'de> for OffsetConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum OffsetStrategyConfig {
     None,
@@ -598,6 +1230,24 @@ pub enum OffsetStrategyConfig {
 }
 
 impl ConnectorConfig {
+    /// JSON schema of the latest config version. Sections of the connector
+    /// specific config are allowed but not described.
+    pub fn json_schema() -> Schema {
+        let mut schema = schema_for!(ConnectorConfigV2);
+
+        if let Some(properties) = schema
+            .get_mut("properties")
+            .and_then(|properties| properties.as_object_mut())
+        {
+            properties.insert(
+                "apiVersion".to_string(),
+                json_schema!({ "const": "0.2.0" }).to_value(),
+            );
+        }
+
+        schema
+    }
+
     pub fn from_file(path: impl Into<PathBuf>) -> Result<Self> {
         let mut file = File::open(path.into())?;
         let mut contents = String::new();
@@ -708,16 +1358,26 @@ mod tests {
                     compression: Some(Compression::Gzip),
                     batch_size: Some(ByteSize::mb(44)),
                     max_request_size: None,
+                    partitioning: None,
+                    mirrors: None,
+                    delivery: None,
+                    memory_budget: None,
+                    enrichment: None,
+                    windowing: None,
                 }),
                 consumer: Some(ConsumerParameters {
                     partition: ConsumerPartitionConfig::One(10),
                     max_bytes: Some(ByteSize::mb(1)),
                     id: None,
                     offset: None,
+                    auto_pause: None,
                 }),
                 secrets: Some(vec![SecretConfig {
                     name: "secret1".parse().unwrap(),
                 }]),
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: vec![TransformationStep {
                 uses: "infinyon/json-sql".to_string(),
@@ -787,6 +1447,12 @@ mod tests {
                     compression: Some(Compression::Gzip),
                     batch_size: Some(ByteSize::mb(44)),
                     max_request_size: None,
+                    partitioning: None,
+                    mirrors: None,
+                    delivery: None,
+                    memory_budget: None,
+                    enrichment: None,
+                    windowing: None,
                 }),
                 consumer: Some(ConsumerParameters {
                     partition: ConsumerPartitionConfig::One(10),
@@ -797,10 +1463,14 @@ mod tests {
                         strategy: OffsetStrategyConfig::Auto,
                         flush_period: Some(Duration::from_secs(160)),
                     }),
+                    auto_pause: None,
                 }),
                 secrets: Some(vec![SecretConfig {
                     name: "secret1".parse().unwrap(),
                 }]),
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: vec![TransformationStep {
                 uses: "infinyon/json-sql".to_string(),
@@ -835,6 +1505,9 @@ mod tests {
                 producer: None,
                 consumer: None,
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -928,6 +1601,9 @@ mod tests {
                 producer: None,
                 consumer: None,
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -960,6 +1636,9 @@ mod tests {
                 producer: None,
                 consumer: None,
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -999,14 +1678,24 @@ mod tests {
                     compression: None,
                     batch_size: Some(ByteSize::b(1600)),
                     max_request_size: None,
+                    partitioning: None,
+                    mirrors: None,
+                    delivery: None,
+                    memory_budget: None,
+                    enrichment: None,
+                    windowing: None,
                 }),
                 consumer: Some(ConsumerParameters {
                     max_bytes: Some(ByteSize::b(1400)),
                     partition: Default::default(),
                     id: None,
                     offset: None,
+                    auto_pause: None,
                 }),
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -1050,6 +1739,9 @@ mod tests {
                 producer: None,
                 consumer: None,
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -1224,14 +1916,24 @@ mod tests {
                     compression: None,
                     batch_size: Some(ByteSize::b(1600)),
                     max_request_size: None,
+                    partitioning: None,
+                    mirrors: None,
+                    delivery: None,
+                    memory_budget: None,
+                    enrichment: None,
+                    windowing: None,
                 }),
                 consumer: Some(ConsumerParameters {
                     max_bytes: Some(ByteSize::b(1400)),
                     partition: Default::default(),
                     id: None,
                     offset: None,
+                    auto_pause: None,
                 }),
                 secrets: None,
+                heartbeat: None,
+                schedule: None,
+                wait_for: None,
             },
             transforms: Vec::default(),
         });
@@ -1308,6 +2010,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deser_auto_pause_config() {
+        //given
+        //when
+        let consumer: ConsumerParameters = serde_yaml::from_str(
+            r#"
+            partition: all
+            auto-pause:
+              probes:
+                - http: http://api:8080/health
+              after-failures: 5
+        "#,
+        )
+        .expect("consumer config");
+
+        //then
+        assert_eq!(
+            consumer.auto_pause,
+            Some(AutoPauseConfig {
+                probes: vec![DependencyProbe::Http("http://api:8080/health".to_string())],
+                after_failures: 5,
+                probe_interval: Duration::from_secs(5),
+                probe_timeout: Duration::from_secs(5),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deser_auto_pause_config_snake_case() {
+        //given
+        //when
+        let consumer: ConsumerParameters = serde_yaml::from_str(
+            r#"
+            partition: all
+            auto_pause:
+              probes:
+                - tcp: db:5432
+              after_failures: 2
+              probe_interval: 1s
+              probe_timeout: 2s
+        "#,
+        )
+        .expect("consumer config");
+
+        //then
+        assert_eq!(
+            consumer.auto_pause,
+            Some(AutoPauseConfig {
+                probes: vec![DependencyProbe::Tcp("db:5432".to_string())],
+                after_failures: 2,
+                probe_interval: Duration::from_secs(1),
+                probe_timeout: Duration::from_secs(2),
+            })
+        );
+    }
+
     #[test]
     fn test_ser_partition_config() {
         //given
@@ -1316,12 +2074,14 @@ mod tests {
             max_bytes: Default::default(),
             id: None,
             offset: None,
+            auto_pause: None,
         };
         let many = ConsumerParameters {
             partition: ConsumerPartitionConfig::Many(vec![2, 3]),
             max_bytes: Default::default(),
             id: None,
             offset: None,
+            auto_pause: None,
         };
 
         let all = ConsumerParameters {
@@ -1329,6 +2089,7 @@ mod tests {
             max_bytes: Default::default(),
             id: None,
             offset: None,
+            auto_pause: None,
         };
 
         //when
@@ -1454,4 +2215,404 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_deser_partitioning_config() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            partitioning:
+              key: /user/id
+              hash: murmur2
+              sticky: {}
+        "#,
+        )
+        .expect("producer config");
+
+        //then
+        assert_eq!(
+            producer.partitioning,
+            Some(PartitioningConfig {
+                key: Some("/user/id".to_string()),
+                hash: KeyHashStrategy::Murmur2,
+                sticky: Some(StickyPartitionerConfig { batch_records: 100 }),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deser_partitioning_config_defaults() {
+        //given
+        //when
+        let partitioning: PartitioningConfig =
+            serde_yaml::from_str("key: /id").expect("partitioning config");
+
+        //then
+        assert_eq!(partitioning.hash, KeyHashStrategy::Siphash);
+        assert!(partitioning.sticky.is_none());
+
+        let sticky: StickyPartitionerConfig =
+            serde_yaml::from_str("batch_records: 10").expect("sticky config");
+        assert_eq!(sticky.batch_records, 10);
+    }
+
+    #[test]
+    fn test_deser_memory_budget_config() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            memory-budget:
+              max-bytes: 64MB
+              on-exceeded: drop-oldest
+        "#,
+        )
+        .expect("producer config");
+        let default_overflow: MemoryBudgetConfig =
+            serde_yaml::from_str("max-bytes: 1MB").expect("memory budget config");
+        let snake_case: MemoryBudgetConfig =
+            serde_yaml::from_str("max_bytes: 2MB\non_exceeded: error")
+                .expect("memory budget config");
+
+        //then
+        assert_eq!(
+            producer.memory_budget,
+            Some(MemoryBudgetConfig {
+                max_bytes: ByteSize::mb(64),
+                on_exceeded: MemoryBudgetOverflow::DropOldest,
+            })
+        );
+        assert_eq!(default_overflow.on_exceeded, MemoryBudgetOverflow::Block);
+        assert_eq!(
+            snake_case,
+            MemoryBudgetConfig {
+                max_bytes: ByteSize::mb(2),
+                on_exceeded: MemoryBudgetOverflow::Error,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deser_enrichment_config() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            enrichment:
+              include: [connector, ingest-timestamp]
+              source:
+                region: eu-west-1
+              mode: envelope
+        "#,
+        )
+        .expect("producer config");
+        let defaults: EnrichmentConfig = serde_yaml::from_str("{}").expect("enrichment config");
+
+        //then
+        assert_eq!(
+            producer.enrichment,
+            Some(EnrichmentConfig {
+                field: "_meta".to_string(),
+                include: vec![
+                    RecordMetadataField::Connector,
+                    RecordMetadataField::IngestTimestamp
+                ],
+                source: BTreeMap::from([("region".to_string(), "eu-west-1".to_string())]),
+                mode: EnrichmentMode::Envelope,
+            })
+        );
+        assert_eq!(defaults.include.len(), 3);
+        assert_eq!(defaults.mode, EnrichmentMode::Merge);
+    }
+
+    #[test]
+    fn test_deser_windowing_config() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            windowing:
+              kind: sliding
+              size: 1m
+              slide: 10s
+              key: /device
+              timestamp: /time
+              allowed-lateness: 5s
+              fields: [/temperature]
+        "#,
+        )
+        .expect("producer config");
+        let defaults: WindowingConfig =
+            serde_yaml::from_str("size: 30s").expect("windowing config");
+        let snake_case: WindowingConfig =
+            serde_yaml::from_str("size: 30s\nallowed_lateness: 2s").expect("windowing config");
+
+        //then
+        assert_eq!(
+            producer.windowing,
+            Some(WindowingConfig {
+                kind: WindowKind::Sliding,
+                size: Duration::from_secs(60),
+                slide: Some(Duration::from_secs(10)),
+                key: Some("/device".to_string()),
+                timestamp: Some("/time".to_string()),
+                allowed_lateness: Duration::from_secs(5),
+                fields: vec!["/temperature".to_string()],
+            })
+        );
+        assert_eq!(defaults.kind, WindowKind::Tumbling);
+        assert_eq!(defaults.allowed_lateness, Duration::ZERO);
+        assert_eq!(snake_case.allowed_lateness, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_deser_delivery_config() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            delivery:
+              semantic: at-least-once
+              ack: all
+              timeout: 5s
+              retry:
+                max-retries: 10
+                initial-delay: 100ms
+                backoff: fibonacci
+        "#,
+        )
+        .expect("producer config");
+
+        //then
+        assert_eq!(
+            producer.delivery,
+            Some(DeliveryConfig {
+                semantic: DeliverySemanticConfig::AtLeastOnce,
+                ack: Some(AckPolicy::All),
+                timeout: Some(Duration::from_secs(5)),
+                retry: Some(RetryConfig {
+                    max_retries: Some(10),
+                    initial_delay: Some(Duration::from_millis(100)),
+                    backoff: Some(RetryBackoff::Fibonacci),
+                    ..Default::default()
+                }),
+            })
+        );
+
+        let at_most_once: DeliveryConfig =
+            serde_yaml::from_str("semantic: at-most-once").expect("delivery config");
+        assert_eq!(at_most_once.semantic, DeliverySemanticConfig::AtMostOnce);
+        assert!(serde_yaml::from_str::<DeliveryConfig>("semantic: exactly-once").is_err());
+
+        let snake_case: RetryConfig =
+            serde_yaml::from_str("max_retries: 3\ninitial_delay: 1s\nmax_delay: 5s")
+                .expect("retry config");
+        assert_eq!(
+            snake_case,
+            RetryConfig {
+                max_retries: Some(3),
+                initial_delay: Some(Duration::from_secs(1)),
+                max_delay: Some(Duration::from_secs(5)),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_deser_mirror_targets() {
+        //given
+        //when
+        let producer: ProducerParameters = serde_yaml::from_str(
+            r#"
+            mirrors:
+              - profile: us-east
+              - profile: eu-west
+                topic: events-mirror
+        "#,
+        )
+        .expect("producer config");
+
+        //then
+        assert_eq!(
+            producer.mirrors,
+            Some(vec![
+                MirrorTargetConfig {
+                    profile: "us-east".to_string(),
+                    topic: None,
+                },
+                MirrorTargetConfig {
+                    profile: "eu-west".to_string(),
+                    topic: Some("events-mirror".to_string()),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_deser_wait_for_config() {
+        //given
+        //when
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-sink
+              type: sql-sink
+              topic: events
+              version: 0.1.0
+              wait-for:
+                timeout: 2m
+                dependencies:
+                  - tcp: broker:9092
+                  - http: http://api:8080/health
+                  - sql: postgres://db:5432/app
+        "#,
+        )
+        .expect("connector config");
+
+        //then
+        assert_eq!(
+            config.meta().wait_for(),
+            Some(&WaitForConfig {
+                dependencies: vec![
+                    DependencyProbe::Tcp("broker:9092".to_string()),
+                    DependencyProbe::Http("http://api:8080/health".to_string()),
+                    DependencyProbe::Sql("postgres://db:5432/app".to_string()),
+                ],
+                timeout: Duration::from_secs(120),
+                probe_timeout: Duration::from_secs(5),
+                initial_delay: Duration::from_secs(1),
+                max_delay: Duration::from_secs(30),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deser_wait_for_config_snake_case() {
+        //given
+        //when
+        let wait_for: WaitForConfig = serde_yaml::from_str(
+            r#"
+            dependencies:
+              - tcp: broker:9092
+            probe_timeout: 2s
+            initial_delay: 500ms
+            max_delay: 10s
+        "#,
+        )
+        .expect("wait-for config");
+
+        //then
+        assert_eq!(wait_for.probe_timeout, Duration::from_secs(2));
+        assert_eq!(wait_for.initial_delay, Duration::from_millis(500));
+        assert_eq!(wait_for.max_delay, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_connector_config_json_schema() {
+        //when
+        let schema = ConnectorConfig::json_schema();
+
+        //then
+        let properties = schema
+            .get("properties")
+            .and_then(|properties| properties.as_object())
+            .expect("properties");
+        assert!(properties.contains_key("apiVersion"));
+        assert!(properties.contains_key("meta"));
+        assert!(properties.contains_key("transforms"));
+
+        let meta = schema
+            .pointer("/$defs/MetaConfigV2/properties")
+            .and_then(|meta| meta.as_object())
+            .expect("meta properties");
+        assert!(meta.contains_key("wait-for"));
+        assert!(meta.contains_key("consumer"));
+    }
+
+    #[test]
+    fn test_deser_heartbeat_config() {
+        //given
+        //when
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.1.0
+              heartbeat:
+                topic: connector-status
+        "#,
+        )
+        .expect("connector config");
+
+        //then
+        assert_eq!(
+            config.meta().heartbeat(),
+            Some(&HeartbeatConfig {
+                topic: "connector-status".to_string(),
+                interval: Duration::from_secs(30),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deser_heartbeat_zero_interval() {
+        //given
+        //when
+        let res = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.1.0
+              heartbeat:
+                topic: connector-status
+                interval: 0s
+        "#,
+        );
+
+        //then
+        let err = res.expect_err("zero interval");
+        assert!(
+            err.to_string()
+                .contains("heartbeat interval must be greater than zero"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_deser_schedule_config() {
+        //given
+        //when
+        let config = ConnectorConfig::config_from_str(
+            r#"
+            apiVersion: 0.1.0
+            meta:
+              name: my-source
+              type: http-source
+              topic: events
+              version: 0.1.0
+              schedule:
+                cron: "*/15 * * * *"
+                jitter: 30s
+        "#,
+        )
+        .expect("connector config");
+
+        //then
+        assert_eq!(
+            config.meta().schedule(),
+            Some(&ScheduleConfig {
+                interval: None,
+                cron: Some("*/15 * * * *".to_string()),
+                jitter: Duration::from_secs(30),
+            })
+        );
+    }
 }
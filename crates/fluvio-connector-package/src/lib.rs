@@ -1,5 +1,6 @@
 pub mod metadata;
 pub mod config;
+pub mod lock;
 pub mod secret;
 mod render;
 
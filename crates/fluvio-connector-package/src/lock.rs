@@ -0,0 +1,281 @@
+//! SmartModule Lock
+//!
+//! Transforms refer to SmartModules by package and an optional version
+//! requirement, e.g. `infinyon/jolt@^0.4`, so the SmartModule a connector
+//! runs depends on what is installed in the cluster when it starts. A lock
+//! file pins each reference to the exact SmartModule it was resolved to,
+//! along with the digest of its WASM, and connectors load the pinned
+//! SmartModules at startup so every deployment runs the same ones.
+//!
+//! ```yaml
+//! smartmodules:
+//!   - uses: infinyon/jolt@^0.4
+//!     name: infinyon/jolt@0.4.1
+//!     version: 0.4.1
+//!     sha256: 4c1e7c4b...
+//! ```
+
+use std::fmt::{self, Display};
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Name of the lock file, looked up next to the connector config
+pub const SMARTMODULE_LOCK_FILENAME: &str = "smartmodules.lock";
+
+const LOCK_FILE_HEADER: &str = "# Generated by `cdk lock`, regenerate it instead of editing it\n";
+
+/// SmartModules pinned for the transforms of a connector
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartModuleLock {
+    #[serde(default)]
+    pub smartmodules: Vec<LockedSmartModule>,
+}
+
+/// SmartModule a transform reference was resolved to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSmartModule {
+    /// The `uses` value of the transform, e.g. `infinyon/jolt@^0.4`
+    pub uses: String,
+    /// Name of the SmartModule in the cluster, e.g. `infinyon/jolt@0.4.1`
+    pub name: String,
+    /// Package version, `None` for SmartModules created without a package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /// Hex encoded SHA-256 digest of the WASM
+    pub sha256: String,
+}
+
+impl SmartModuleLock {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = read_to_string(path)
+            .with_context(|| format!("unable to read lock file {}", path.display()))?;
+
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("unable to parse lock file {}", path.display()))
+    }
+
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write(
+            path,
+            format!("{LOCK_FILE_HEADER}{}", serde_yaml::to_string(self)?),
+        )?;
+        Ok(())
+    }
+
+    /// The SmartModule pinned for the transform using `uses`
+    pub fn find(&self, uses: &str) -> Option<&LockedSmartModule> {
+        self.smartmodules.iter().find(|locked| locked.uses == uses)
+    }
+}
+
+impl LockedSmartModule {
+    /// Fails if `wasm` is not the WASM this SmartModule was locked with
+    pub fn verify(&self, wasm: &[u8]) -> Result<()> {
+        let digest = wasm_digest(wasm);
+
+        if digest != self.sha256 {
+            return Err(anyhow!(
+                "smartmodule {} does not match the lock file, expected sha256 {} but got {digest}",
+                self.name,
+                self.sha256
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Hex encoded SHA-256 digest of `wasm`
+pub fn wasm_digest(wasm: &[u8]) -> String {
+    hex::encode(Sha256::digest(wasm))
+}
+
+/// Versions a transform accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSelector {
+    /// Newest stable version
+    Any,
+    Exact(Version),
+    Range(VersionReq),
+}
+
+/// A `uses` value referring to a SmartModule package, e.g. `infinyon/jolt`,
+/// `infinyon/jolt@0.4.1` or `infinyon/jolt@>=0.4, <0.6`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartModuleReference {
+    /// Package as `group/name`, or the SmartModule name without a group
+    pub package: String,
+    pub version: VersionSelector,
+}
+
+/// A SmartModule installed in the cluster
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledSmartModule {
+    pub name: String,
+    /// Package as `group/name`, `None` for SmartModules created without a
+    /// package
+    pub package: Option<String>,
+    pub version: Option<Version>,
+}
+
+impl SmartModuleReference {
+    pub fn parse(uses: &str) -> Result<Self> {
+        let (package, version) = match uses.split_once('@') {
+            Some((package, version)) => {
+                let version = version.trim();
+                let selector = match Version::parse(version) {
+                    Ok(version) => VersionSelector::Exact(version),
+                    Err(_) => {
+                        VersionSelector::Range(VersionReq::parse(version).map_err(|err| {
+                            anyhow!("invalid version requirement in `{uses}`: {err}")
+                        })?)
+                    }
+                };
+
+                (package, selector)
+            }
+            None => (uses, VersionSelector::Any),
+        };
+
+        if package.is_empty() {
+            return Err(anyhow!("invalid smartmodule reference `{uses}`"));
+        }
+
+        Ok(Self {
+            package: package.to_string(),
+            version,
+        })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        match &self.version {
+            VersionSelector::Any => version.pre.is_empty(),
+            VersionSelector::Exact(exact) => version == exact,
+            VersionSelector::Range(req) => req.matches(version),
+        }
+    }
+
+    /// The newest of the `installed` SmartModules matching this reference.
+    /// SmartModules without a package only match references to their name
+    /// without a version.
+    pub fn select<'a>(
+        &self,
+        installed: &'a [InstalledSmartModule],
+    ) -> Option<&'a InstalledSmartModule> {
+        installed
+            .iter()
+            .filter(
+                |smartmodule| match (&smartmodule.package, &smartmodule.version) {
+                    (Some(package), Some(version)) => {
+                        *package == self.package && self.matches(version)
+                    }
+                    _ => smartmodule.name == self.package && self.version == VersionSelector::Any,
+                },
+            )
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+}
+
+impl Display for SmartModuleReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            VersionSelector::Any => write!(f, "{}", self.package),
+            VersionSelector::Exact(version) => write!(f, "{}@{version}", self.package),
+            VersionSelector::Range(req) => write!(f, "{}@{req}", self.package),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(package: &str, version: &str) -> InstalledSmartModule {
+        InstalledSmartModule {
+            name: format!("{package}@{version}"),
+            package: Some(package.to_string()),
+            version: Some(Version::parse(version).unwrap()),
+        }
+    }
+
+    #[test]
+    fn parses_references() {
+        let reference = SmartModuleReference::parse("infinyon/jolt@>=0.4, <0.6").unwrap();
+
+        assert_eq!(reference.package, "infinyon/jolt");
+        assert!(reference.matches(&Version::new(0, 5, 2)));
+        assert!(!reference.matches(&Version::new(0, 6, 0)));
+        assert_eq!(
+            SmartModuleReference::parse("infinyon/jolt@0.4.1")
+                .unwrap()
+                .version,
+            VersionSelector::Exact(Version::new(0, 4, 1))
+        );
+        assert_eq!(
+            SmartModuleReference::parse("my-filter").unwrap().version,
+            VersionSelector::Any
+        );
+        assert!(SmartModuleReference::parse("infinyon/jolt@latest").is_err());
+        assert!(SmartModuleReference::parse("@0.4.1").is_err());
+    }
+
+    #[test]
+    fn selects_newest_matching_smartmodule() {
+        let smartmodules = vec![
+            installed("infinyon/jolt", "0.4.1"),
+            installed("infinyon/jolt", "0.5.0-rc.1"),
+            installed("infinyon/jolt", "0.4.3"),
+            installed("infinyon/jolt", "0.3.0"),
+            installed("infinyon/regex-filter", "0.5.0"),
+            InstalledSmartModule {
+                name: "my-filter".to_string(),
+                package: None,
+                version: None,
+            },
+        ];
+        let select = |uses: &str| {
+            SmartModuleReference::parse(uses)
+                .unwrap()
+                .select(&smartmodules)
+                .map(|smartmodule| smartmodule.name.as_str())
+        };
+
+        assert_eq!(select("infinyon/jolt"), Some("infinyon/jolt@0.4.3"));
+        assert_eq!(select("infinyon/jolt@~0.4.0"), Some("infinyon/jolt@0.4.3"));
+        assert_eq!(select("infinyon/jolt@0.4.1"), Some("infinyon/jolt@0.4.1"));
+        assert_eq!(select("infinyon/jolt@^0.6"), None);
+        assert_eq!(select("my-filter"), Some("my-filter"));
+        assert_eq!(select("my-filter@0.1.0"), None);
+    }
+
+    #[test]
+    fn round_trips_lock_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(SMARTMODULE_LOCK_FILENAME);
+        let locked = LockedSmartModule {
+            uses: "infinyon/jolt@^0.4".to_string(),
+            name: "infinyon/jolt@0.4.3".to_string(),
+            version: Some(Version::new(0, 4, 3)),
+            sha256: wasm_digest(b"wasm"),
+        };
+        let lock = SmartModuleLock {
+            smartmodules: vec![locked.clone()],
+        };
+
+        lock.write_to_file(&path).unwrap();
+
+        let read = SmartModuleLock::from_file(&path).unwrap();
+
+        assert_eq!(read, lock);
+        assert_eq!(read.find("infinyon/jolt@^0.4"), Some(&locked));
+        assert!(read.find("infinyon/jolt").is_none());
+        assert!(locked.verify(b"wasm").is_ok());
+        assert!(locked.verify(b"other").is_err());
+    }
+}
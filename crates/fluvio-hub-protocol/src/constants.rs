@@ -1,13 +1,32 @@
+use crate::HubLayout;
+
 pub const CLI_CONFIG_HUB: &str = "hub";
 
-pub const HUB_MANIFEST_BLOB: &str = "manifest.tar.gz";
-pub const HUB_PACKAGE_EXT: &str = "ipkg";
-pub const HUB_PACKAGE_META: &str = "package-meta.yaml";
-pub const HUB_PACKAGE_META_CLEAN: &str = "package-meta-clean.yaml";
-pub const HUB_PACKAGE_VERSION: &str = "0.3";
+// Names of the current package layout, new code should use `HubLayout` to
+// support packages in other formats
+pub const HUB_MANIFEST_BLOB: &str = HubLayout::CURRENT.manifest_blob;
+pub const HUB_PACKAGE_EXT: &str = HubLayout::CURRENT.package_ext;
+pub const HUB_PACKAGE_META: &str = HubLayout::CURRENT.package_meta;
+pub const HUB_PACKAGE_META_CLEAN: &str = HubLayout::CURRENT.package_meta_clean;
+pub const HUB_PACKAGE_VERSION: &str = HubLayout::CURRENT.format_version;
 
 pub const DEF_CARGO_TOML_PATH: &str = "Cargo.toml";
 pub const DEF_HUB_INIT_DIR: &str = ".hub";
 
+/// Namespace of the Package Meta's [`PkgTag`] names reserved for the Hub
+pub const PKG_TAG_RESERVED_NAMESPACE: &str = "inf::";
+
+/// Prefix of user defined Package Meta's [`PkgTag`] names
+pub const PKG_TAG_USER_PREFIX: &str = "x-";
+
 /// Package Meta's [`PkgTag`] reserved tag names
 pub const PKG_TAG_META_PUBLISHED_AT: &str = "inf::meta::published_at";
+pub const PKG_TAG_META_LICENSE: &str = "inf::meta::license";
+pub const PKG_TAG_META_TARGETS: &str = "inf::meta::targets";
+
+/// Every reserved tag name, each tag is expected at most once
+pub const PKG_TAG_META_RESERVED: &[&str] = &[
+    PKG_TAG_META_PUBLISHED_AT,
+    PKG_TAG_META_LICENSE,
+    PKG_TAG_META_TARGETS,
+];
@@ -14,12 +14,21 @@ pub enum HubError {
     #[error("Hub access: {0}")]
     HubAccess(String),
 
+    #[error("Hub access unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Hub access forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Invalid keypair file: {0}")]
     InvalidKeyPairFile(String),
 
     #[error("Invalid package name: {0}")]
     InvalidPackageName(String),
 
+    #[error("Invalid package tag: {0}")]
+    InvalidPackageTag(String),
+
     #[error("Invalid public key file: {0}")]
     InvalidPublicKeyFile(String),
 
@@ -56,6 +65,9 @@ pub enum HubError {
     #[error("Package verification: {0}")]
     PackageVerify(String),
 
+    #[error("Package incompatible: {0}")]
+    PackageIncompatible(String),
+
     #[error("Package already published: {0}")]
     PackageAlreadyPublished(String),
 
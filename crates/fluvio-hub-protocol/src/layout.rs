@@ -0,0 +1,135 @@
+//! Hub Package Layout
+//!
+//! File names and extensions used by Hub packages of a given
+//! `package_format_version`. Code building package paths uses a
+//! [`HubLayout`] instead of hardcoding names, so a future package format
+//! can change them while packages in existing formats keep being read with
+//! their own layout.
+
+/// Names of the files making up a Hub package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HubLayout {
+    /// `package_format_version` of the packages following this layout
+    pub format_version: &'static str,
+    /// Extension of signed package files
+    pub package_ext: &'static str,
+    /// Extension of unsigned package files
+    pub unsigned_ext: &'static str,
+    /// Package meta file stored in packages
+    pub package_meta: &'static str,
+    /// Package meta file written without signing information
+    pub package_meta_clean: &'static str,
+    /// Blob holding the package contents
+    pub manifest_blob: &'static str,
+}
+
+impl HubLayout {
+    pub const V0_3: HubLayout = HubLayout {
+        format_version: "0.3",
+        package_ext: "ipkg",
+        unsigned_ext: "tar",
+        package_meta: "package-meta.yaml",
+        package_meta_clean: "package-meta-clean.yaml",
+        manifest_blob: "manifest.tar.gz",
+    };
+
+    /// Layout of newly built packages
+    pub const CURRENT: HubLayout = HubLayout::V0_3;
+
+    /// Every layout packages can be read with
+    pub const KNOWN: &'static [HubLayout] = &[HubLayout::V0_3];
+
+    /// Layout of packages in the `package_format_version` format
+    pub fn for_format_version(format_version: &str) -> Option<Self> {
+        Self::KNOWN
+            .iter()
+            .find(|layout| layout.format_version == format_version)
+            .copied()
+    }
+
+    /// Signed package file name, e.g. `example-0.0.1.ipkg`
+    pub fn package_file_name(&self, name: &str, version: &str) -> String {
+        format!("{name}-{version}.{}", self.package_ext)
+    }
+
+    /// Unsigned package file name, e.g. `example-0.0.1.tar`
+    pub fn unsigned_file_name(&self, name: &str, version: &str) -> String {
+        format!("{name}-{version}.{}", self.unsigned_ext)
+    }
+
+    /// Object name of a package in the Hub store, e.g.
+    /// `infinyon/example-0.0.1.ipkg`
+    pub fn object_name(&self, group: &str, name: &str, version: &str) -> String {
+        format!("{group}/{}", self.package_file_name(name, version))
+    }
+
+    /// `file_name` without the signed package extension, `None` if the file
+    /// is not a package of this layout
+    pub fn strip_package_ext<'a>(&self, file_name: &'a str) -> Option<&'a str> {
+        file_name
+            .strip_suffix(self.package_ext)
+            .and_then(|stem| stem.strip_suffix('.'))
+    }
+}
+
+impl Default for HubLayout {
+    fn default() -> Self {
+        Self::CURRENT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_package_names() {
+        let layout = HubLayout::default();
+
+        assert_eq!(
+            layout.package_file_name("example", "0.0.1"),
+            "example-0.0.1.ipkg"
+        );
+        assert_eq!(
+            layout.unsigned_file_name("example", "0.0.1"),
+            "example-0.0.1.tar"
+        );
+        assert_eq!(
+            layout.object_name("infinyon", "example", "0.0.1"),
+            "infinyon/example-0.0.1.ipkg"
+        );
+        assert_eq!(
+            layout.strip_package_ext("example-0.0.1.ipkg"),
+            Some("example-0.0.1")
+        );
+        assert_eq!(layout.strip_package_ext("example-0.0.1.tar"), None);
+    }
+
+    #[test]
+    fn finds_layout_by_format_version() {
+        assert_eq!(HubLayout::for_format_version("0.3"), Some(HubLayout::V0_3));
+        assert_eq!(HubLayout::for_format_version("9.9"), None);
+    }
+
+    /// A future format can use other names without changing how packages
+    /// in existing formats are named
+    #[test]
+    fn supports_alternative_layouts() {
+        let v2 = HubLayout {
+            format_version: "2.0",
+            package_ext: "ipkg2",
+            manifest_blob: "contents.tar.zst",
+            ..HubLayout::CURRENT
+        };
+
+        assert_eq!(
+            v2.package_file_name("example", "0.0.1"),
+            "example-0.0.1.ipkg2"
+        );
+        assert_eq!(v2.strip_package_ext("example-0.0.1.ipkg"), None);
+        assert_eq!(
+            HubLayout::CURRENT.package_file_name("example", "0.0.1"),
+            "example-0.0.1.ipkg"
+        );
+    }
+}
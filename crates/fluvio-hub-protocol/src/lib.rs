@@ -1,9 +1,13 @@
 mod errors;
+mod layout;
 mod package_meta;
 
 pub mod constants;
 pub mod infinyon_tok;
 
 pub use errors::{Result, HubError};
-pub use package_meta::{PackageMeta, PkgTag, PkgVisibility};
-pub use package_meta::{validate_allowedchars, validate_noleading_punct};
+pub use layout::HubLayout;
+pub use package_meta::{PackageMeta, PkgTag, PkgTagKind, PkgVisibility};
+pub use package_meta::{LogInclusionProof, PublishedAtProof};
+pub use package_meta::{PkgCompatibility, PkgCompatibilityIssue};
+pub use package_meta::{validate_allowedchars, validate_noleading_punct, validate_user_tag_name};
@@ -1,5 +1,6 @@
 use std::default::Default;
 
+use semver::Version;
 use serde::{Deserialize, Serialize};
 use tracing::{info, error};
 use url::Url;
@@ -9,8 +10,11 @@ use fluvio_controlplane_metadata::smartmodule::SmartModulePackageKey;
 use fluvio_controlplane_metadata::smartmodule::SmartModuleVisibility;
 use fluvio_controlplane_metadata::smartmodule as smpkg;
 
-use crate::{HubError, Result};
-use crate::constants::{HUB_PACKAGE_EXT, HUB_PACKAGE_VERSION};
+use crate::{HubError, HubLayout, Result};
+use crate::constants::{
+    PKG_TAG_META_LICENSE, PKG_TAG_META_PUBLISHED_AT, PKG_TAG_META_RESERVED, PKG_TAG_META_TARGETS,
+    PKG_TAG_RESERVED_NAMESPACE, PKG_TAG_USER_PREFIX,
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 /// defines hub package metadata
@@ -27,6 +31,12 @@ pub struct PackageMeta {
 
     #[serde(default = "PackageMeta::visibility_if_missing")]
     pub visibility: PkgVisibility, // private is default if missing
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<PkgCompatibility>, // any target and platform if missing
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at_proof: Option<PublishedAtProof>, // unproven publish date if missing
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Default, Clone)]
@@ -43,10 +53,88 @@ pub struct PkgTag {
     pub value: String,
 }
 
+/// namespace of a [`PkgTag`] name
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PkgTagKind {
+    /// `inf::` tags set by the Hub and the publishing tools
+    Reserved,
+    /// `x-` tags set by package authors
+    User,
+    /// tags outside of both namespaces, from packages published before
+    /// namespaces were introduced
+    Legacy,
+}
+
+/// declares where the package can be installed
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Default, Clone)]
+pub struct PkgCompatibility {
+    /// target triples supported by the package binaries, e.g.
+    /// `x86_64-unknown-linux-musl` or `wasm32-wasip1`, empty means any target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+
+    /// minimum fluvio platform version required, SemVer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_platform_version: Option<String>,
+}
+
+/// independent evidence the [`PackageMeta::published_at_statement`] existed at
+/// a point in time, backing the `inf::meta::published_at` tag
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PublishedAtProof {
+    /// inclusion of the statement in a transparency log
+    TransparencyLog(LogInclusionProof),
+}
+
+/// RFC 9162 inclusion proof of a statement in a transparency log, with the
+/// checkpoint of the log signed by the log operator
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct LogInclusionProof {
+    /// name of the log, e.g. `hub.infinyon.cloud/log`
+    pub log: String,
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    /// hex encoded root hash of the tree of `tree_size` leaves
+    pub root_hash: String,
+    /// hex encoded hashes of the audit path, from the leaf to the root
+    pub hashes: Vec<String>,
+    /// when the log integrated the statement, RFC3339
+    pub integrated_at: String,
+    /// base64 encoded Ed25519 signature of the checkpoint, see
+    /// [`LogInclusionProof::checkpoint`]
+    pub signature: String,
+}
+
+impl LogInclusionProof {
+    /// text signed by the log operator: the log name, tree size, root hash
+    /// and integration time, one per line
+    pub fn checkpoint(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n",
+            self.log, self.tree_size, self.root_hash, self.integrated_at
+        )
+    }
+}
+
+/// reasons a package cannot be installed on a target or cluster
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PkgCompatibilityIssue {
+    UnsupportedTarget {
+        target: String,
+        supported: Vec<String>,
+    },
+    PlatformTooOld {
+        required: String,
+        current: String,
+    },
+    InvalidMinPlatformVersion(String),
+}
+
 impl Default for PackageMeta {
     fn default() -> PackageMeta {
         PackageMeta {
-            package_format_version: HUB_PACKAGE_VERSION.into(),
+            package_format_version: HubLayout::CURRENT.format_version.into(),
             name: "NameOfThePackage".into(),
             version: "0.0".into(),
             group: "NameOfContributingGroup".into(),
@@ -56,6 +144,8 @@ impl Default for PackageMeta {
             manifest: Vec::new(),
             tags: None,
             repository_url: None,
+            compatibility: None,
+            published_at_proof: None,
         }
     }
 }
@@ -81,12 +171,16 @@ impl PackageMeta {
         format!("{}/{}@{}", self.group, self.name, self.version)
     }
 
+    /// Retrieves the [`HubLayout`] of the package format version, packages
+    /// in unknown formats use the current layout
+    pub fn layout(&self) -> HubLayout {
+        HubLayout::for_format_version(&self.package_format_version).unwrap_or_default()
+    }
+
     /// Retrives the S3's object name from this package. Eg: `infinyon/example-0.0.1.ipkg`
     pub fn obj_name(&self) -> String {
-        format!(
-            "{}/{}-{}.{HUB_PACKAGE_EXT}",
-            self.group, self.name, self.version
-        )
+        self.layout()
+            .object_name(&self.group, &self.name, &self.version)
     }
 
     /// Builds the S3 object path from the provided package name.
@@ -109,20 +203,17 @@ impl PackageMeta {
             return Err(HubError::InvalidPackageName(pkg_name.into()));
         }
 
-        Ok(format!(
-            "{}/{}-{}.{HUB_PACKAGE_EXT}",
-            parts[0], name_version[0], name_version[1]
-        ))
+        Ok(HubLayout::CURRENT.object_name(parts[0], name_version[0], name_version[1]))
     }
 
     /// the packagefile name as defined by the package meta data
     pub fn packagefile_name(&self) -> String {
-        self.name.clone() + "-" + &self.version + "." + HUB_PACKAGE_EXT
+        self.layout().package_file_name(&self.name, &self.version)
     }
 
     /// the packagefile name as defined by the package meta data
     pub fn packagefile_name_unsigned(&self) -> String {
-        self.name.clone() + "-" + &self.version + ".tar"
+        self.layout().unsigned_file_name(&self.name, &self.version)
     }
 
     /// used by serde to fill in private field if missing on parse
@@ -162,6 +253,10 @@ impl PackageMeta {
         advice.push_str(&validate_allowedchars(&self.name, "package name"));
         advice.push_str(&validate_notempty(&self.name, "package name"));
 
+        if let Err(HubError::InvalidPackageTag(tag_advice)) = self.validate_tags() {
+            advice.push_str(&tag_advice);
+        }
+
         if !advice.is_empty() {
             Err(HubError::PackageVerify(advice))
         } else {
@@ -169,6 +264,77 @@ impl PackageMeta {
         }
     }
 
+    /// Lists the reasons this package is incompatible with the install
+    /// `target` and the cluster `platform_version`, if known. Packages
+    /// without compatibility info are assumed to be compatible.
+    pub fn compatibility_issues(
+        &self,
+        target: Option<&str>,
+        platform_version: Option<&Version>,
+    ) -> Vec<PkgCompatibilityIssue> {
+        let Some(compat) = &self.compatibility else {
+            return Vec::new();
+        };
+        let mut issues = Vec::new();
+
+        if let Some(target) = target
+            && !compat.targets.is_empty()
+            && !compat.targets.iter().any(|t| t == target)
+        {
+            issues.push(PkgCompatibilityIssue::UnsupportedTarget {
+                target: target.to_string(),
+                supported: compat.targets.clone(),
+            });
+        }
+
+        if let Some(required) = &compat.min_platform_version {
+            match Version::parse(required) {
+                Ok(min) => {
+                    if let Some(current) = platform_version
+                        && current < &min
+                    {
+                        issues.push(PkgCompatibilityIssue::PlatformTooOld {
+                            required: required.clone(),
+                            current: current.to_string(),
+                        });
+                    }
+                }
+                Err(_) => {
+                    issues.push(PkgCompatibilityIssue::InvalidMinPlatformVersion(
+                        required.clone(),
+                    ));
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Fails with [`HubError::PackageIncompatible`] if there are any
+    /// [`PackageMeta::compatibility_issues`]
+    pub fn check_compatibility(
+        &self,
+        target: Option<&str>,
+        platform_version: Option<&Version>,
+    ) -> Result<()> {
+        let issues = self.compatibility_issues(target, platform_version);
+
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        let advice = issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<String>>()
+            .join("; ");
+
+        Err(HubError::PackageIncompatible(format!(
+            "{}: {advice}",
+            self.pkg_name()
+        )))
+    }
+
     pub fn tag_add(&mut self, tagname: &str, tagval: &str) {
         let pkgtag = PkgTag::new(tagname, tagval);
         if let Some(ref mut tagvec) = self.tags {
@@ -195,6 +361,130 @@ impl PackageMeta {
             None
         }
     }
+
+    /// value of the first tag named `tagname`
+    pub fn tag_value(&self, tagname: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .flatten()
+            .find(|tv| tv.tag == tagname)
+            .map(|tv| tv.value.as_str())
+    }
+
+    /// statement timestamped by a [`PublishedAtProof`], binding the package
+    /// version to its publish date, e.g.
+    /// `infinyon/example@0.0.1 2024-05-01T10:00:00Z`. `None` without a publish
+    /// date tag
+    pub fn published_at_statement(&self) -> Option<String> {
+        let published_at = self.tag_value(PKG_TAG_META_PUBLISHED_AT)?;
+
+        Some(format!(
+            "{}/{}@{} {published_at}",
+            self.group, self.name, self.version
+        ))
+    }
+
+    /// removes every tag named `tagname`
+    pub fn tag_remove(&mut self, tagname: &str) {
+        if let Some(ref mut tagvec) = self.tags {
+            tagvec.retain(|tv| tv.tag != tagname);
+        }
+    }
+
+    /// adds a user tag, the name must be under the `x-` prefix
+    pub fn tag_add_user(&mut self, tagname: &str, tagval: &str) -> Result<()> {
+        let advice = validate_user_tag_name(tagname);
+
+        if !advice.is_empty() {
+            return Err(HubError::InvalidPackageTag(advice));
+        }
+
+        self.tag_add(tagname, tagval);
+        Ok(())
+    }
+
+    /// sets a reserved `inf::` tag, replacing its previous value
+    pub fn tag_set_reserved(&mut self, tagname: &str, tagval: &str) -> Result<()> {
+        if !PKG_TAG_META_RESERVED.contains(&tagname) {
+            return Err(HubError::InvalidPackageTag(format!(
+                "{tagname} is not a known reserved tag"
+            )));
+        }
+
+        self.tag_remove(tagname);
+        self.tag_add(tagname, tagval);
+        Ok(())
+    }
+
+    /// license recorded by the Hub, which may differ from the declared
+    /// `license` field
+    pub fn license_tag(&self) -> Option<&str> {
+        self.tag_value(PKG_TAG_META_LICENSE)
+    }
+
+    pub fn set_license_tag(&mut self, license: &str) {
+        self.tag_remove(PKG_TAG_META_LICENSE);
+        self.tag_add(PKG_TAG_META_LICENSE, license);
+    }
+
+    /// target triples the package was published for
+    pub fn targets_tag(&self) -> Vec<String> {
+        self.tag_value(PKG_TAG_META_TARGETS)
+            .map(|targets| {
+                targets
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|target| !target.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_targets_tag(&mut self, targets: &[String]) {
+        self.tag_remove(PKG_TAG_META_TARGETS);
+        self.tag_add(PKG_TAG_META_TARGETS, &targets.join(","));
+    }
+
+    /// checks tag names against their namespaces: reserved tags must be
+    /// known and set at most once, `x-` tags must be valid user tags. Legacy
+    /// tags are accepted as is so packages published before namespaces were
+    /// introduced still validate, new tags are added with
+    /// [`Self::tag_add_user`]
+    pub fn validate_tags(&self) -> Result<()> {
+        let mut advice = String::new();
+
+        for (idx, tv) in self.tags.iter().flatten().enumerate() {
+            match tv.kind() {
+                PkgTagKind::Reserved => {
+                    if !PKG_TAG_META_RESERVED.contains(&tv.tag.as_str()) {
+                        advice.push_str(&format!(
+                            "tag {} uses the reserved {PKG_TAG_RESERVED_NAMESPACE} namespace\n",
+                            tv.tag
+                        ));
+                    } else if self
+                        .tags
+                        .iter()
+                        .flatten()
+                        .take(idx)
+                        .any(|prev| prev.tag == tv.tag)
+                    {
+                        advice.push_str(&format!("tag {} is set more than once\n", tv.tag));
+                    }
+                }
+                PkgTagKind::User => {
+                    advice.push_str(&validate_user_tag_name(&tv.tag));
+                }
+                PkgTagKind::Legacy => {}
+            }
+        }
+
+        if !advice.is_empty() {
+            Err(HubError::InvalidPackageTag(advice))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl PkgTag {
@@ -204,6 +494,30 @@ impl PkgTag {
             value: val.to_string(),
         }
     }
+
+    pub fn kind(&self) -> PkgTagKind {
+        if self.tag.starts_with(PKG_TAG_RESERVED_NAMESPACE) {
+            PkgTagKind::Reserved
+        } else if self.tag.starts_with(PKG_TAG_USER_PREFIX) {
+            PkgTagKind::User
+        } else {
+            PkgTagKind::Legacy
+        }
+    }
+}
+
+pub fn validate_user_tag_name(tagname: &str) -> String {
+    let Some(name) = tagname.strip_prefix(PKG_TAG_USER_PREFIX) else {
+        return format!("tag {tagname} should start with {PKG_TAG_USER_PREFIX}\n");
+    };
+    let mut advice = validate_notempty(name, "tag name");
+
+    if !name.is_empty() {
+        advice.push_str(&validate_allowedchars(name, "tag"));
+        advice.push_str(&validate_noleading_punct(name, "tag"));
+    }
+
+    advice
 }
 pub fn packagename_validate(pkgname: &str) -> Result<()> {
     let mut advice = String::new();
@@ -254,6 +568,25 @@ pub fn validate_noleading_punct(val: &str, name: &str) -> String {
     String::new()
 }
 
+impl std::fmt::Display for PkgCompatibilityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            PkgCompatibilityIssue::UnsupportedTarget { target, supported } => write!(
+                f,
+                "target {target} is not supported, expected one of: {}",
+                supported.join(", ")
+            ),
+            PkgCompatibilityIssue::PlatformTooOld { required, current } => write!(
+                f,
+                "requires fluvio platform {required} or newer, found {current}"
+            ),
+            PkgCompatibilityIssue::InvalidMinPlatformVersion(version) => {
+                write!(f, "invalid min_platform_version {version}")
+            }
+        }
+    }
+}
+
 // from Smartmodule to package vaisiblity
 impl From<&SmartModuleVisibility> for PkgVisibility {
     fn from(sm: &SmartModuleVisibility) -> Self {
@@ -425,3 +758,175 @@ fn hub_packagemeta_tags() {
     let atag = atag.unwrap();
     assert_eq!(atag.len(), 2);
 }
+
+#[test]
+fn hub_packagemeta_namespaced_tags() {
+    let mut pm = PackageMeta::default();
+
+    pm.tag_add_user("x-team", "streaming").unwrap();
+    assert_eq!(pm.tag_value("x-team"), Some("streaming"));
+    assert!(pm.tag_add_user("team", "streaming").is_err());
+    assert!(pm.tag_add_user("x-", "empty").is_err());
+    assert!(pm.tag_add_user("x-Team", "uppercase").is_err());
+    assert!(
+        pm.tag_add_user(PKG_TAG_META_LICENSE, "MIT").is_err(),
+        "user tags must not collide with reserved tags"
+    );
+
+    pm.set_license_tag("Apache-2.0");
+    pm.set_license_tag("MIT");
+    pm.set_targets_tag(&["wasm32-wasip1".into(), "x86_64-unknown-linux-musl".into()]);
+    assert_eq!(pm.license_tag(), Some("MIT"));
+    assert_eq!(
+        pm.targets_tag(),
+        vec![
+            "wasm32-wasip1".to_string(),
+            "x86_64-unknown-linux-musl".to_string()
+        ]
+    );
+    assert!(pm.validate_tags().is_ok());
+
+    pm.tag_add("atag", "legacy");
+    assert_eq!(PkgTag::new("atag", "legacy").kind(), PkgTagKind::Legacy);
+    assert!(
+        pm.validate_tags().is_ok(),
+        "tags published before namespaces must still validate"
+    );
+
+    assert!(pm.tag_set_reserved("inf::meta::owner", "me").is_err());
+    pm.tag_add("inf::meta::owner", "me");
+    pm.tag_add(PKG_TAG_META_LICENSE, "GPL");
+    pm.tag_add("x-Team", "uppercase");
+
+    let Err(HubError::InvalidPackageTag(advice)) = pm.validate_tags() else {
+        panic!("expected invalid tags");
+    };
+
+    assert!(advice.contains("inf::meta::owner uses the reserved inf:: namespace"));
+    assert!(advice.contains("inf::meta::license is set more than once"));
+    assert!(advice.contains("tag Team should be alphanumeric"));
+    assert!(!advice.contains("atag"));
+    assert!(pm.naming_check().is_err());
+}
+
+#[test]
+fn hub_packagemeta_compatibility() {
+    let pm = PackageMeta {
+        group: "infinyon".into(),
+        name: "example".into(),
+        version: "0.0.1".into(),
+        compatibility: Some(PkgCompatibility {
+            targets: vec!["x86_64-unknown-linux-musl".into()],
+            min_platform_version: Some("0.12.0".into()),
+        }),
+        ..PackageMeta::default()
+    };
+    let old = Version::parse("0.11.9").unwrap();
+    let new = Version::parse("0.12.1").unwrap();
+
+    assert!(
+        pm.check_compatibility(Some("x86_64-unknown-linux-musl"), Some(&new))
+            .is_ok()
+    );
+    assert_eq!(
+        pm.compatibility_issues(Some("aarch64-apple-darwin"), Some(&old)),
+        vec![
+            PkgCompatibilityIssue::UnsupportedTarget {
+                target: "aarch64-apple-darwin".into(),
+                supported: vec!["x86_64-unknown-linux-musl".into()],
+            },
+            PkgCompatibilityIssue::PlatformTooOld {
+                required: "0.12.0".into(),
+                current: "0.11.9".into(),
+            },
+        ]
+    );
+
+    // unknown target and platform are not checked
+    assert!(pm.check_compatibility(None, None).is_ok());
+    // packages without compatibility info install anywhere
+    assert!(
+        PackageMeta::default()
+            .check_compatibility(Some("aarch64-apple-darwin"), Some(&old))
+            .is_ok()
+    );
+}
+
+#[test]
+fn hub_packagemeta_compatibility_yaml() {
+    let pm: PackageMeta = serde_yaml::from_str(
+        r#"
+package_format_version: "0.3"
+name: example
+version: 0.0.1
+group: infinyon
+description: ""
+license: ""
+manifest: []
+repository_url: ~
+tags: ~
+compatibility:
+  targets:
+    - wasm32-wasip1
+  min_platform_version: 0.12.0
+"#,
+    )
+    .unwrap();
+    let compat = pm.compatibility.unwrap();
+
+    assert_eq!(compat.targets, vec!["wasm32-wasip1".to_string()]);
+    assert_eq!(compat.min_platform_version, Some("0.12.0".to_string()));
+}
+
+#[test]
+fn hub_packagemeta_published_at_proof_yaml() {
+    let mut pm: PackageMeta = serde_yaml::from_str(
+        r#"
+package_format_version: "0.3"
+name: example
+version: 0.0.1
+group: infinyon
+description: ""
+license: ""
+manifest: []
+repository_url: ~
+tags: ~
+published_at_proof:
+  kind: transparency_log
+  log: hub.example.com/log
+  leaf_index: 2
+  tree_size: 3
+  root_hash: ab12
+  hashes: []
+  integrated_at: 2024-05-01T10:01:00Z
+  signature: c2ln
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        pm.published_at_proof,
+        Some(PublishedAtProof::TransparencyLog(LogInclusionProof {
+            log: "hub.example.com/log".into(),
+            leaf_index: 2,
+            tree_size: 3,
+            root_hash: "ab12".into(),
+            hashes: vec![],
+            integrated_at: "2024-05-01T10:01:00Z".into(),
+            signature: "c2ln".into(),
+        }))
+    );
+    assert_eq!(pm.published_at_statement(), None);
+
+    pm.tag_add(PKG_TAG_META_PUBLISHED_AT, "2024-05-01T10:00:00Z");
+
+    assert_eq!(
+        pm.published_at_statement().as_deref(),
+        Some("infinyon/example@0.0.1 2024-05-01T10:00:00Z")
+    );
+    assert!(
+        !serde_yaml::to_string(&PackageMeta::default())
+            .unwrap()
+            .contains("published_at_proof")
+    );
+}
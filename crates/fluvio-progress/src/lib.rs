@@ -0,0 +1,436 @@
+//! Progress Rendering
+//!
+//! Progress of concurrent tasks, such as the downloads of the artifacts of
+//! a release or the checks of a cluster, is rendered as one bar per task
+//! above a summary line when the output is a terminal. Otherwise, e.g. in CI
+//! or when the output is piped to a file, progress degrades to whole log
+//! lines written as tasks start and finish, so the output of concurrent
+//! tasks never interleaves.
+//!
+//! Kept apart from `fluvio-artifacts-util` so crates rendering progress,
+//! such as `fluvio-cluster`, don't depend on the artifact clients.
+
+pub mod transfer;
+
+use std::borrow::Cow;
+use std::fmt::{self, Debug};
+use std::io::{IsTerminal, stderr, stdout};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use crate::transfer::{TransferProgress, format_bytes};
+
+/// Stream progress is rendered to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressOutput {
+    Stdout,
+    #[default]
+    Stderr,
+}
+
+impl ProgressOutput {
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Self::Stdout => stdout().is_terminal(),
+            Self::Stderr => stderr().is_terminal(),
+        }
+    }
+
+    fn draw_target(&self) -> ProgressDrawTarget {
+        match self {
+            Self::Stdout => ProgressDrawTarget::stdout(),
+            Self::Stderr => ProgressDrawTarget::stderr(),
+        }
+    }
+}
+
+/// Whether progress is rendered as bars or as log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    Bars,
+    Lines,
+}
+
+impl ProgressMode {
+    /// Bars when `output` is a terminal, unless `hide` is set or running in
+    /// CI
+    pub fn detect(hide: bool, output: ProgressOutput) -> Self {
+        if hide || std::env::var("CI").is_ok() || !output.is_terminal() {
+            Self::Lines
+        } else {
+            Self::Bars
+        }
+    }
+}
+
+/// Writes whole lines of progress, e.g. through the notifications of a CLI
+#[derive(Clone)]
+pub struct LineSink(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl LineSink {
+    pub fn new(write: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(write))
+    }
+
+    pub fn write(&self, line: &str) {
+        (self.0)(line)
+    }
+}
+
+impl Debug for LineSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LineSink")
+    }
+}
+
+impl From<ProgressOutput> for LineSink {
+    fn from(output: ProgressOutput) -> Self {
+        match output {
+            ProgressOutput::Stdout => Self::new(|line| println!("{line}")),
+            ProgressOutput::Stderr => Self::new(|line| eprintln!("{line}")),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub enum ProgressRenderer {
+    /// Render the progress using eprintln macro
+    #[default]
+    Std,
+    /// Render the progress as lines written to a sink
+    Lines(LineSink),
+    /// Render the progress using Indicatiff
+    Indicatiff(ProgressBar),
+}
+
+impl ProgressRenderer {
+    pub fn println(&self, msg: impl Into<Cow<'static, str>>) {
+        match self {
+            ProgressRenderer::Std => eprintln!("{}", msg.into()),
+            ProgressRenderer::Lines(sink) => sink.write(&msg.into()),
+            ProgressRenderer::Indicatiff(pb) => pb.println(msg.into()),
+        }
+    }
+
+    pub fn set_message(&self, msg: impl Into<Cow<'static, str>>) {
+        let msg = msg.into();
+        match self {
+            ProgressRenderer::Std => eprintln!("{msg}"),
+            ProgressRenderer::Lines(sink) => sink.write(&msg),
+            ProgressRenderer::Indicatiff(pb) => pb.set_message(msg),
+        }
+    }
+
+    pub fn finish_and_clear(&self) {
+        if let ProgressRenderer::Indicatiff(pb) = self {
+            pb.finish_and_clear();
+        }
+    }
+}
+
+impl From<ProgressBar> for ProgressRenderer {
+    fn from(pb: ProgressBar) -> Self {
+        Self::Indicatiff(pb)
+    }
+}
+
+#[derive(Debug, Default)]
+struct GroupState {
+    finished: usize,
+    failed: usize,
+    /// Bytes received by the finished transfers
+    bytes: u64,
+}
+
+/// Progress of a group of concurrent tasks, rendered as one bar per task
+/// above a summary line, or as log lines, see [`ProgressMode`].
+///
+/// Groups are cheap to clone and tasks are updated from any thread.
+#[derive(Debug, Clone)]
+pub struct ProgressGroup {
+    label: String,
+    total: usize,
+    multi: Option<MultiProgress>,
+    summary: Option<ProgressBar>,
+    sink: LineSink,
+    state: Arc<Mutex<GroupState>>,
+}
+
+impl ProgressGroup {
+    /// Group of `total` tasks, summarized as `label`, e.g. `Downloads`
+    pub fn new(
+        label: impl Into<String>,
+        total: usize,
+        mode: ProgressMode,
+        output: ProgressOutput,
+    ) -> Self {
+        let mut group = Self {
+            label: label.into(),
+            total,
+            multi: None,
+            summary: None,
+            sink: output.into(),
+            state: Arc::default(),
+        };
+
+        if mode == ProgressMode::Bars {
+            let multi = MultiProgress::with_draw_target(output.draw_target());
+            let summary = multi.add(ProgressBar::new(total as u64));
+
+            summary.set_style(ProgressStyle::with_template("{msg}").expect("valid template"));
+            summary.set_message(group.summary());
+            group.multi = Some(multi);
+            group.summary = Some(summary);
+        }
+
+        group
+    }
+
+    /// Writes log lines to `sink` rather than to the output of the group
+    pub fn with_line_sink(mut self, sink: LineSink) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    pub fn mode(&self) -> ProgressMode {
+        match self.multi {
+            Some(_) => ProgressMode::Bars,
+            None => ProgressMode::Lines,
+        }
+    }
+
+    /// Adds a transfer named `name` of `total` bytes, if known. `message` is
+    /// shown until the transfer reports progress, or logged in
+    /// [`ProgressMode::Lines`].
+    pub fn add_transfer(
+        &self,
+        name: impl Into<String>,
+        total: Option<u64>,
+        message: impl Into<String>,
+    ) -> ProgressTask {
+        let bar = hidden_bar(total.unwrap_or_default()).with_style(
+            ProgressStyle::with_template("{prefix:>12.bold} [{bar:30}] {msg}")
+                .expect("valid template")
+                .progress_chars("=> "),
+        );
+
+        self.add(name.into(), bar, message.into())
+    }
+
+    /// Adds a task with a spinner showing `message`, or logging it in
+    /// [`ProgressMode::Lines`]
+    pub fn add_task(&self, message: impl Into<String>) -> ProgressTask {
+        let bar = hidden_bar(1).with_style(
+            ProgressStyle::with_template("{msg} {spinner}")
+                .expect("valid template")
+                .tick_chars("/-\\|"),
+        );
+        let task = self.add(String::new(), bar, message.into());
+
+        if let ProgressRenderer::Indicatiff(bar) = &task.renderer {
+            bar.enable_steady_tick(Duration::from_millis(100));
+        }
+
+        task
+    }
+
+    fn add(&self, name: String, bar: ProgressBar, message: String) -> ProgressTask {
+        let renderer = match (&self.multi, &self.summary) {
+            (Some(multi), Some(summary)) => {
+                let bar = multi.insert_before(summary, bar);
+
+                bar.set_prefix(name.clone());
+                bar.set_message(message);
+                ProgressRenderer::Indicatiff(bar)
+            }
+            _ => {
+                self.sink.write(&message);
+                ProgressRenderer::Lines(self.sink.clone())
+            }
+        };
+
+        ProgressTask {
+            name,
+            renderer,
+            group: self.clone(),
+        }
+    }
+
+    /// Summary of the group, e.g. `Downloads: 2/3 done, 1 failed, 12.0 MiB`
+    pub fn summary(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let mut summary = format!("{}: {}/{} done", self.label, state.finished, self.total);
+
+        if state.failed > 0 {
+            summary.push_str(&format!(", {} failed", state.failed));
+        }
+
+        if state.bytes > 0 {
+            summary.push_str(&format!(", {}", format_bytes(state.bytes)));
+        }
+
+        summary
+    }
+
+    /// Clears the bars of the group and logs its summary
+    pub fn finish(&self) {
+        if let Some(summary) = &self.summary {
+            summary.finish_and_clear();
+        }
+
+        if let Some(multi) = &self.multi {
+            let _ = multi.clear();
+        }
+
+        self.sink.write(&self.summary());
+    }
+
+    /// Logs `line` without disturbing the bars of the group
+    pub fn println(&self, line: impl AsRef<str>) {
+        match &self.multi {
+            Some(multi) => multi.suspend(|| self.sink.write(line.as_ref())),
+            None => self.sink.write(line.as_ref()),
+        }
+    }
+
+    fn record(&self, passed: bool, bytes: u64) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        state.finished += 1;
+        state.bytes += bytes;
+
+        if !passed {
+            state.failed += 1;
+        }
+
+        drop(state);
+
+        if let Some(summary) = &self.summary {
+            summary.inc(1);
+            summary.set_message(self.summary());
+        }
+    }
+}
+
+/// Bar drawn once added to the bars of a group
+fn hidden_bar(len: u64) -> ProgressBar {
+    ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::hidden())
+}
+
+/// A task of a [`ProgressGroup`]
+#[derive(Debug)]
+pub struct ProgressTask {
+    name: String,
+    renderer: ProgressRenderer,
+    group: ProgressGroup,
+}
+
+impl ProgressTask {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Renderer of the task, e.g. to log lines above the bars of the group
+    pub fn renderer(&self) -> &ProgressRenderer {
+        &self.renderer
+    }
+
+    /// Renders the `progress` of a transfer, log lines are only written when
+    /// the task finishes
+    pub fn update(&self, progress: &TransferProgress) {
+        if let ProgressRenderer::Indicatiff(bar) = &self.renderer {
+            if let Some(total) = progress.total {
+                bar.set_length(total);
+            }
+
+            bar.set_position(progress.received);
+            bar.set_message(progress.to_string());
+        }
+    }
+
+    /// Finishes the task, logging `message`
+    pub fn finish(self, message: impl AsRef<str>) {
+        self.group.println(message);
+        self.done(true, 0);
+    }
+
+    /// Finishes the transfer with its last `progress`, logging `message`
+    pub fn finish_transfer(self, progress: Option<&TransferProgress>, message: impl AsRef<str>) {
+        self.group.println(message);
+        self.done(
+            true,
+            progress
+                .map(|progress| progress.received)
+                .unwrap_or_default(),
+        );
+    }
+
+    /// Fails the task, logging `message`
+    pub fn fail(self, message: impl AsRef<str>) {
+        self.group.println(message);
+        self.done(false, 0);
+    }
+
+    /// Finishes the task without logging, counting it as failed unless
+    /// `passed`
+    pub fn done(self, passed: bool, bytes: u64) {
+        self.renderer.finish_and_clear();
+        self.group.record(passed, bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect() -> (LineSink, Arc<Mutex<Vec<String>>>) {
+        let lines: Arc<Mutex<Vec<String>>> = Arc::default();
+        let sink = {
+            let lines = lines.clone();
+
+            LineSink::new(move |line| lines.lock().unwrap().push(line.to_string()))
+        };
+
+        (sink, lines)
+    }
+
+    #[test]
+    fn degrades_to_sequential_lines() {
+        let (sink, lines) = collect();
+        let group = ProgressGroup::new("Downloads", 3, ProgressMode::Lines, ProgressOutput::Stderr)
+            .with_line_sink(sink);
+        let fluvio = group.add_transfer("fluvio", Some(8), "Downloading fluvio");
+        let smdk = group.add_transfer("smdk", None, "Downloading smdk");
+        let progress = TransferProgress {
+            received: 2048,
+            total: Some(2048),
+            elapsed: Duration::from_secs(1),
+            speed: 2048.0,
+            eta: None,
+        };
+
+        assert_eq!(group.mode(), ProgressMode::Lines);
+
+        fluvio.update(&progress);
+        smdk.fail("Failed to download smdk");
+        fluvio.finish_transfer(Some(&progress), "Downloaded fluvio");
+        group.finish();
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "Downloading fluvio",
+                "Downloading smdk",
+                "Failed to download smdk",
+                "Downloaded fluvio",
+                "Downloads: 2/3 done, 1 failed, 2.0 KiB",
+            ]
+        );
+        assert_eq!(
+            ProgressMode::detect(true, ProgressOutput::Stdout),
+            ProgressMode::Lines
+        );
+    }
+}
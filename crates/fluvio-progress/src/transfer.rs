@@ -0,0 +1,259 @@
+//! Transfer Statistics
+//!
+//! Speed and ETA of a download, computed from the bytes received over a
+//! rolling window so the estimate follows changes in throughput instead of
+//! averaging over the whole transfer.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// Period the transfer speed is averaged over
+pub const SPEED_WINDOW: Duration = Duration::from_secs(5);
+
+/// Callback receiving the progress of a transfer
+pub type ProgressCallback<'a> = dyn FnMut(&TransferProgress) + Send + 'a;
+
+/// Progress of a transfer, passed to progress callbacks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransferProgress {
+    /// Bytes received so far
+    pub received: u64,
+    /// Size of the transfer from `Content-Length`, if known
+    pub total: Option<u64>,
+    /// Time since the transfer started
+    pub elapsed: Duration,
+    /// Bytes per second over the last [`SPEED_WINDOW`]
+    pub speed: f64,
+    /// Estimated time left, `None` if the size or the speed is unknown
+    pub eta: Option<Duration>,
+}
+
+impl TransferProgress {
+    /// Fraction of the transfer completed, between 0 and 1
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.received as f64 / total as f64).min(1.0))
+    }
+
+    /// Bytes per second since the transfer started
+    pub fn average_speed(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.received as f64 / secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Whether every byte of a transfer of known size was received
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.received >= total)
+    }
+}
+
+impl Display for TransferProgress {
+    /// e.g. `12.3 MiB / 40.0 MiB, 2.1 MiB/s, ETA 13s`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_bytes(self.received))?;
+
+        if let Some(total) = self.total {
+            write!(f, " / {}", format_bytes(total))?;
+        }
+
+        write!(f, ", {}/s", format_bytes(self.speed as u64))?;
+
+        if let Some(eta) = self.eta {
+            write!(f, ", ETA {}", format_duration(eta))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Accumulates the bytes of a transfer to estimate its speed and ETA
+#[derive(Debug, Clone)]
+pub struct TransferStats {
+    started: Instant,
+    total: Option<u64>,
+    received: u64,
+    window: Duration,
+    /// Time and bytes received so far at each update within the window,
+    /// preceded by the last update before the window
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl TransferStats {
+    /// Statistics for a transfer of `total` bytes, if known, starting now
+    pub fn new(total: Option<u64>) -> Self {
+        Self::started_at(Instant::now(), total)
+    }
+
+    fn started_at(started: Instant, total: Option<u64>) -> Self {
+        Self {
+            started,
+            total,
+            received: 0,
+            window: SPEED_WINDOW,
+            samples: VecDeque::from([(started, 0)]),
+        }
+    }
+
+    /// Averages the speed over `window` instead of [`SPEED_WINDOW`]
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Records `bytes` received now and returns the updated progress
+    pub fn record(&mut self, bytes: usize) -> TransferProgress {
+        self.record_at(Instant::now(), bytes)
+    }
+
+    /// Progress as of now
+    pub fn progress(&self) -> TransferProgress {
+        self.progress_at(Instant::now())
+    }
+
+    fn record_at(&mut self, now: Instant, bytes: usize) -> TransferProgress {
+        self.received += bytes as u64;
+        self.samples.push_back((now, self.received));
+
+        // Keeps the newest sample older than the window as the baseline
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|(at, _)| now.duration_since(*at) >= self.window)
+        {
+            self.samples.pop_front();
+        }
+
+        self.progress_at(now)
+    }
+
+    fn progress_at(&self, now: Instant) -> TransferProgress {
+        let (since, baseline) = self.samples.front().copied().unwrap_or((self.started, 0));
+        let period = now.duration_since(since).as_secs_f64();
+        let speed = if period > 0.0 {
+            (self.received - baseline) as f64 / period
+        } else {
+            0.0
+        };
+        let eta = self.total.and_then(|total| {
+            let left = total.saturating_sub(self.received);
+
+            if left == 0 {
+                Some(Duration::ZERO)
+            } else if speed > 0.0 {
+                Some(Duration::from_secs_f64(left as f64 / speed))
+            } else {
+                None
+            }
+        });
+
+        TransferProgress {
+            received: self.received,
+            total: self.total,
+            elapsed: now.duration_since(self.started),
+            speed,
+            eta,
+        }
+    }
+}
+
+/// Formats `bytes` with a binary unit, e.g. `1.5 MiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Formats `duration` rounded to seconds, e.g. `1h02m`, `3m05s` or `42s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64().round() as u64;
+
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, secs) => format!("{secs}s"),
+        (0, mins, secs) => format!("{mins}m{secs:02}s"),
+        (hours, mins, _) => format!("{hours}h{mins:02}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_speed_and_eta() {
+        let start = Instant::now();
+        let mut stats = TransferStats::started_at(start, Some(10_000));
+
+        stats.record_at(start + Duration::from_secs(1), 1_000);
+        let progress = stats.record_at(start + Duration::from_secs(2), 1_000);
+
+        assert_eq!(progress.received, 2_000);
+        assert_eq!(progress.speed, 1_000.0);
+        assert_eq!(progress.eta, Some(Duration::from_secs(8)));
+        assert_eq!(progress.fraction(), Some(0.2));
+        assert_eq!(progress.average_speed(), 1_000.0);
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn averages_speed_over_window() {
+        let start = Instant::now();
+        let mut stats = TransferStats::started_at(start, None).with_window(Duration::from_secs(2));
+
+        // Fast start, then a slower steady rate
+        stats.record_at(start + Duration::from_secs(1), 10_000);
+        for secs in 2..=6 {
+            stats.record_at(start + Duration::from_secs(secs), 100);
+        }
+
+        let progress = stats.progress_at(start + Duration::from_secs(6));
+
+        assert_eq!(progress.speed, 100.0);
+        assert_eq!(progress.eta, None);
+        assert_eq!(progress.elapsed, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn completes_transfer() {
+        let start = Instant::now();
+        let mut stats = TransferStats::started_at(start, Some(100));
+        let progress = stats.record_at(start, 100);
+
+        assert!(progress.is_complete());
+        assert_eq!(progress.eta, Some(Duration::ZERO));
+        assert_eq!(progress.speed, 0.0);
+    }
+
+    #[test]
+    fn formats_progress() {
+        let progress = TransferProgress {
+            received: 3 * 1024 * 1024 / 2,
+            total: Some(4 * 1024 * 1024),
+            elapsed: Duration::from_secs(3),
+            speed: 512.0 * 1024.0,
+            eta: Some(Duration::from_secs(185)),
+        };
+
+        assert_eq!(
+            progress.to_string(),
+            "1.5 MiB / 4.0 MiB, 512.0 KiB/s, ETA 3m05s"
+        );
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h02m");
+    }
+}
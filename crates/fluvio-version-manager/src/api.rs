@@ -0,0 +1,422 @@
+//! Library API for FVM operations
+//!
+//! Installs, activates, lists and uninstalls Fluvio Versions in the FVM
+//! workdir. Messages are suppressed unless a [`Notify`] is provided.
+
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::{Channel, ComponentSelection, CpuVariant, HostSystem};
+
+use crate::common::TARGET;
+use crate::common::eol::{check_eol, load_eol_metadata};
+use crate::common::github::fvm_client;
+use crate::common::install_profile::InstallProfile;
+use crate::common::lease::live_executions;
+use crate::common::manifest::VersionManifest;
+use crate::common::notify::Notify;
+use crate::common::remote_versions::record_remote_version;
+use crate::common::requirements::{check_requirements, load_requirements_metadata};
+use crate::common::version_policy::{enforce_package_set_policy, enforce_version_policy};
+use crate::common::settings::Settings;
+use crate::common::shim::remove_shims;
+use crate::common::usage::UsageTracker;
+use crate::common::version_directory::VersionDirectory;
+use crate::common::version_installer::VersionInstaller;
+use crate::common::workdir::fvm_versions_path;
+
+/// A Fluvio Version installed in the FVM `versions` directory
+#[derive(Debug, Clone)]
+pub struct InstalledVersion {
+    pub manifest: VersionManifest,
+    /// Path to the version directory, e.g. `~/.fvm/versions/stable`
+    pub path: PathBuf,
+    /// Whether this is the active version
+    pub active: bool,
+}
+
+impl InstalledVersion {
+    fn open(path: PathBuf, active: bool) -> Result<Self> {
+        let manifest = VersionDirectory::open(path.clone())?.manifest;
+
+        Ok(Self {
+            manifest,
+            path,
+            active,
+        })
+    }
+}
+
+/// Installs and uninstalls Fluvio Versions
+#[derive(Debug, Clone)]
+pub struct Installer {
+    target: String,
+    generic: bool,
+    accept_eol: bool,
+    transparency_url: Option<String>,
+    profile: Option<InstallProfile>,
+    verification_report: Option<bool>,
+    strict: bool,
+    ignore_requirements: bool,
+    components: Vec<ComponentSelection>,
+    notify: Notify,
+}
+
+impl Default for Installer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Installer {
+    /// Installer for the host target with output suppressed
+    pub fn new() -> Self {
+        Self {
+            target: TARGET.to_string(),
+            generic: false,
+            accept_eol: false,
+            transparency_url: None,
+            profile: None,
+            verification_report: None,
+            strict: false,
+            ignore_requirements: false,
+            components: Vec::new(),
+            notify: Notify::new(true),
+        }
+    }
+
+    /// Installs binaries for `target` instead of the host target
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    /// Installs baseline artifacts even if CPU optimized variants are available
+    pub fn with_generic(mut self, generic: bool) -> Self {
+        self.generic = generic;
+        self
+    }
+
+    /// Installs end-of-life versions instead of failing
+    pub fn with_accept_eol(mut self, accept_eol: bool) -> Self {
+        self.accept_eol = accept_eol;
+        self
+    }
+
+    /// Verifies artifact digests against the transparency manifest at
+    /// `transparency_url`, defaults to `FVM_TRANSPARENCY_URL` or the
+    /// `transparency_url` key in `settings.toml`
+    pub fn with_transparency_url(mut self, transparency_url: Option<String>) -> Self {
+        self.transparency_url = transparency_url;
+        self
+    }
+
+    /// Installs only the components of `profile`, defaults to the profile
+    /// chosen with `fvm init`
+    pub fn with_profile(mut self, profile: Option<InstallProfile>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Writes a verification report next to the installed binaries,
+    /// defaults to `FVM_VERIFICATION_REPORT` or the `verification_report` key
+    /// in `settings.toml`
+    pub fn with_verification_report(mut self, verification_report: Option<bool>) -> Self {
+        self.verification_report = verification_report;
+        self
+    }
+
+    /// Fails when the newest stable release is missing artifacts instead of
+    /// installing the prior stable release
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Installs binaries even if the host does not meet their minimum glibc
+    /// or macOS version
+    pub fn with_ignore_requirements(mut self, ignore_requirements: bool) -> Self {
+        self.ignore_requirements = ignore_requirements;
+        self
+    }
+
+    /// Installs the selected binaries from their own channels instead of
+    /// the installed channel, e.g. `smdk` from `latest` into `stable`
+    pub fn with_components(mut self, components: Vec<ComponentSelection>) -> Self {
+        self.components = components;
+        self
+    }
+
+    pub fn with_notify(mut self, notify: Notify) -> Self {
+        self.notify = notify;
+        self
+    }
+
+    /// CPU variants to prefer, only detected when installing binaries for
+    /// the host target
+    pub fn cpu_variants(&self) -> Vec<CpuVariant> {
+        if self.generic || self.target != TARGET {
+            return Vec::new();
+        }
+
+        let variants = CpuVariant::detect();
+
+        tracing::debug!(?variants, "Detected CPU variants");
+        variants
+    }
+
+    /// Installs `channel` and sets it as the active version
+    pub async fn install(&self, channel: &Channel) -> Result<InstalledVersion> {
+        let versions_path = fvm_versions_path()?;
+
+        if !versions_path.exists() {
+            tracing::info!(?versions_path, "Creating versions directory");
+            create_dir_all(&versions_path)?;
+        }
+
+        let variants = self.cpu_variants();
+        let pkgset = match channel {
+            Channel::Stable => {
+                let (pkgset, skipped) = fvm_client()?
+                    .fetch_stable_package_set_with_fallback(&self.target, &variants, !self.strict)
+                    .await?;
+
+                if let Some(skipped) = skipped {
+                    self.notify.warn(format!(
+                        "Stable release {} is missing artifacts for {}: {}",
+                        skipped.version,
+                        self.target,
+                        skipped.missing.join(", ")
+                    ));
+                    self.notify.warn(format!(
+                        "Installing prior stable release {} instead, use --strict to fail instead",
+                        pkgset.pkgset
+                    ));
+                }
+
+                pkgset
+            }
+            _ => {
+                fvm_client()?
+                    .fetch_default_package_set_with_variants(channel, &self.target, &variants)
+                    .await?
+            }
+        };
+
+        record_remote_version(channel, &pkgset.pkgset);
+        let profile = match self.profile {
+            Some(profile) => Some(profile),
+            None => Settings::configured_install_profile()?,
+        };
+        let pkgset = match profile {
+            Some(profile) => profile.select(pkgset)?,
+            None => pkgset,
+        };
+        let pkgset = if self.components.is_empty() {
+            pkgset
+        } else {
+            fvm_client()?
+                .compose_package_set(pkgset, &self.components, &variants)
+                .await?
+        };
+
+        for artifact in pkgset.artifacts.iter() {
+            if let Some(component_channel) = &artifact.channel {
+                self.notify.info(format!(
+                    "Using {} {} from {component_channel}",
+                    artifact.name, artifact.version
+                ));
+            }
+        }
+
+        enforce_package_set_policy(&pkgset, self.notify).await?;
+
+        if let Some(metadata) = load_eol_metadata().await {
+            check_eol(&metadata, &pkgset.pkgset, self.accept_eol, self.notify)?;
+        }
+
+        // Binaries for other targets are not run on this host
+        if self.target == TARGET
+            && let Some(metadata) = load_requirements_metadata().await
+        {
+            let host = HostSystem::detect();
+
+            tracing::debug!(?host, "Detected host system");
+            check_requirements(
+                &metadata,
+                &pkgset.pkgset,
+                &self.target,
+                &host,
+                self.ignore_requirements,
+                self.notify,
+            )?;
+        }
+
+        if !variants.is_empty() && pkgset.artifacts.iter().all(|art| art.variant.is_none()) {
+            self.notify
+                .info("No CPU optimized builds published for this release, using baseline builds");
+        }
+
+        for artifact in pkgset.artifacts.iter() {
+            if let Some(variant) = artifact.variant {
+                self.notify.info(format!(
+                    "Using {variant} optimized build for {}",
+                    artifact.name
+                ));
+            }
+        }
+
+        VersionInstaller::new(channel.to_owned(), pkgset, self.notify)
+            .with_transparency_url(self.transparency_url.clone())
+            .with_profile(profile)?
+            .with_verification_report(self.verification_report)
+            .install()
+            .await?;
+
+        InstalledVersion::open(versions_path.join(channel.to_string()), true)
+    }
+
+    /// Removes `channel` from the installed versions.
+    ///
+    /// Returns `false` if the version is not installed, and fails if binaries
+    /// of the version are running.
+    pub fn uninstall(&self, channel: &Channel) -> Result<bool> {
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Ok(false);
+        }
+
+        let running = live_executions(&channel.to_string())?;
+
+        if !running.is_empty() {
+            return Err(Failure::new(
+                FailureKind::Locked,
+                format!(
+                    "Fluvio version {channel} is in use by {} running processes, retry once they exit",
+                    running.len()
+                ),
+            )
+            .into());
+        }
+
+        let version_dir = VersionDirectory::open(version_path)?;
+
+        version_dir.remove()?;
+        remove_shims(&version_dir.manifest.version)?;
+
+        if let Some(mut usage) = UsageTracker::open()? {
+            usage.forget(&channel.to_string())?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Changes the active Fluvio Version
+#[derive(Debug, Clone, Copy)]
+pub struct Switcher {
+    accept_eol: bool,
+    notify: Notify,
+}
+
+impl Default for Switcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Switcher {
+    /// Switcher with output suppressed
+    pub fn new() -> Self {
+        Self {
+            accept_eol: false,
+            notify: Notify::new(true),
+        }
+    }
+
+    /// Switches to end-of-life versions instead of failing
+    pub fn with_accept_eol(mut self, accept_eol: bool) -> Self {
+        self.accept_eol = accept_eol;
+        self
+    }
+
+    pub fn with_notify(mut self, notify: Notify) -> Self {
+        self.notify = notify;
+        self
+    }
+
+    /// Sets the installed `channel` as the active version, failing if the
+    /// version policy does not allow it or the version reached end-of-life
+    pub async fn switch(&self, channel: &Channel) -> Result<InstalledVersion> {
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {channel} is not installed"),
+            )
+            .into());
+        }
+
+        let version_dir = VersionDirectory::open(version_path.clone())?;
+
+        enforce_version_policy(&version_dir.manifest.version, self.notify).await?;
+
+        if let Some(metadata) = load_eol_metadata().await {
+            check_eol(
+                &metadata,
+                &version_dir.manifest.version,
+                self.accept_eol,
+                self.notify,
+            )?;
+        }
+
+        version_dir.set_active()?;
+        InstalledVersion::open(version_path, true)
+    }
+
+    /// The active version, if any
+    pub fn current() -> Result<Option<InstalledVersion>> {
+        let Some(channel) = Settings::open()?.channel else {
+            return Ok(None);
+        };
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Ok(None);
+        }
+
+        InstalledVersion::open(version_path, true).map(Some)
+    }
+}
+
+/// Installed versions, the active version first followed by the remaining
+/// versions sorted by channel in descending order
+pub fn list_installed() -> Result<Vec<InstalledVersion>> {
+    let versions_path = fvm_versions_path()?;
+
+    if !versions_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let active = Settings::open()?.channel;
+    let (mut manifests, maybe_active) =
+        VersionDirectory::scan_versions_manifests(versions_path.clone(), active)?;
+
+    manifests.sort_by(|a, b| b.channel.cmp(&a.channel));
+
+    Ok(maybe_active
+        .into_iter()
+        .map(|manifest| (manifest, true))
+        .chain(manifests.into_iter().map(|manifest| (manifest, false)))
+        .map(|(manifest, active)| InstalledVersion {
+            path: versions_path.join(manifest.channel.to_string()),
+            manifest,
+            active,
+        })
+        .collect())
+}
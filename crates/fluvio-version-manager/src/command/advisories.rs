@@ -0,0 +1,102 @@
+//! Advisories Command
+//!
+//! The `advisories` command lists the security and critical-bug advisories
+//! which apply to the installed Fluvio Versions, or every published
+//! advisory with `--all`.
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+use comfy_table::{Row, Table};
+
+use fvm_core::list_installed;
+
+use crate::common::advisories::{installed_advisories, load_advisories};
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct AdvisoriesOpt {
+    /// List every published advisory instead of the ones applying to
+    /// installed versions
+    #[arg(long)]
+    all: bool,
+    /// Fetch the advisories instead of using the daily cache
+    #[arg(long)]
+    refresh: bool,
+}
+
+impl AdvisoriesOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let Some(feed) = load_advisories(self.refresh).await else {
+            bail!("Advisories are not available, check your connection and try again");
+        };
+        let mut table = Table::new();
+
+        if self.all {
+            if feed.advisories.is_empty() {
+                notify.done("No advisories published");
+                return Ok(());
+            }
+
+            table.set_header(Row::from(["ID", "KIND", "VERSIONS", "FIXED IN", "SUMMARY"]));
+
+            for advisory in &feed.advisories {
+                table.add_row(Row::from([
+                    advisory.id.clone(),
+                    advisory.kind.to_string(),
+                    advisory.versions.to_string(),
+                    advisory
+                        .fixed_in
+                        .as_ref()
+                        .map_or_else(|| String::from("-"), ToString::to_string),
+                    advisory.summary.clone(),
+                ]));
+            }
+
+            table.load_preset(comfy_table::presets::NOTHING);
+            println!("{table}");
+
+            return Ok(());
+        }
+
+        let installed = list_installed()?;
+        let advisories = installed_advisories(&feed, &installed);
+
+        if advisories.is_empty() {
+            notify.done("No advisories apply to the installed versions");
+            return Ok(());
+        }
+
+        table.set_header(Row::from(["VERSION", "ID", "KIND", "FIXED IN", "SUMMARY"]));
+
+        for applied in &advisories {
+            let version = if applied.active {
+                format!("{} (active)", applied.version).bold().to_string()
+            } else {
+                applied.version.to_string()
+            };
+
+            table.add_row(Row::from([
+                version,
+                applied.advisory.id.clone(),
+                applied.advisory.kind.to_string().red().to_string(),
+                applied
+                    .advisory
+                    .fixed_in
+                    .as_ref()
+                    .map_or_else(|| String::from("-"), ToString::to_string),
+                applied.advisory.summary.clone(),
+            ]));
+        }
+
+        table.load_preset(comfy_table::presets::NOTHING);
+        println!("{table}");
+
+        notify.warn(format!(
+            "{} advisories apply to the installed versions",
+            advisories.len()
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+//! Backfill Digests Command
+//!
+//! The `backfill-digests` command is meant for maintainers: it computes the
+//! checksums manifest of releases whose assets lack published digests, to be
+//! attached to the release so older versions gain integrity coverage.
+//! Manifests are written to `<output>/<tag>/checksums.sha256`.
+
+use std::fs::{create_dir_all, write};
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+
+use fluvio_artifacts_util::fvm::{CHECKSUMS_ASSET_NAME, DEFAULT_BACKFILL_CONCURRENCY};
+
+use crate::common::github::fvm_client;
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct BackfillDigestsOpt {
+    /// Release tags to compute manifests for, defaults to the recent
+    /// releases lacking digests
+    #[arg(index = 1, value_name = "TAG")]
+    tags: Vec<String>,
+    /// Number of assets downloaded at once
+    #[arg(long, default_value_t = DEFAULT_BACKFILL_CONCURRENCY)]
+    concurrency: usize,
+    /// Directory the manifests are written to
+    #[arg(long, default_value = ".")]
+    output: PathBuf,
+}
+
+impl BackfillDigestsOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let client = fvm_client()?;
+        let backfills = if self.tags.is_empty() {
+            client.backfill_digests(self.concurrency).await?
+        } else {
+            let mut backfills = Vec::with_capacity(self.tags.len());
+
+            for tag in &self.tags {
+                backfills.push(
+                    client
+                        .backfill_release_digests(tag, self.concurrency)
+                        .await?,
+                );
+            }
+
+            backfills
+        };
+
+        if backfills.is_empty() {
+            notify.done("Every recent release has digests for its assets");
+            return Ok(());
+        }
+
+        let mut incomplete = 0;
+
+        for backfill in backfills {
+            if !backfill.is_complete() {
+                for failure in &backfill.failures {
+                    notify.warn(format!(
+                        "{}: failed to hash {}: {}",
+                        backfill.tag, failure.name, failure.error
+                    ));
+                }
+
+                // A partial manifest would be mistaken for a complete one once attached
+                incomplete += 1;
+                continue;
+            }
+
+            let dir = self.output.join(&backfill.tag);
+            let path = dir.join(CHECKSUMS_ASSET_NAME);
+
+            create_dir_all(&dir)?;
+            write(&path, backfill.manifest.to_string())?;
+            notify.done(format!(
+                "{}: {} assets, {} hashed, written to {}",
+                backfill.tag.bold(),
+                backfill.manifest.len(),
+                backfill.hashed.len(),
+                path.display()
+            ));
+        }
+
+        if incomplete > 0 {
+            notify.help("Retry the failed releases by passing their tags");
+            bail!("Failed to compute the manifest of {incomplete} releases");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+//! Package Cache Commands
+//!
+//! The `verify-cache` command hashes the objects of the content store shared
+//! with the Hub client against their digests, `repair-cache` also evicts the
+//! corrupt objects and optionally downloads them again from the URL they
+//! were downloaded from. Objects unchanged since they were last verified are
+//! skipped unless `--full` is set.
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+
+use fluvio_artifacts_util::store::{ContentStore, StoreVerification};
+
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct VerifyCacheOpt {
+    /// Hash every object, including objects unchanged since they were last
+    /// verified
+    #[arg(long)]
+    full: bool,
+}
+
+impl VerifyCacheOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let Some(store) = ContentStore::open_default() else {
+            notify.warn("The content store is disabled, nothing to verify");
+            return Ok(());
+        };
+        let verification = verify_store(&store, self.full, notify)?;
+
+        if !verification.corrupt.is_empty() {
+            notify.help(format!(
+                "Evict corrupt objects with {}",
+                "fvm repair-cache".bold()
+            ));
+
+            bail!(
+                "{} objects in {} are corrupt",
+                verification.corrupt.len(),
+                store.root().display()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+pub struct RepairCacheOpt {
+    /// Hash every object, including objects unchanged since they were last
+    /// verified
+    #[arg(long)]
+    full: bool,
+    /// Download evicted objects again from the URL they were downloaded from
+    #[arg(long)]
+    redownload: bool,
+}
+
+impl RepairCacheOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let Some(store) = ContentStore::open_default() else {
+            notify.warn("The content store is disabled, nothing to repair");
+            return Ok(());
+        };
+        let verification = verify_store(&store, self.full, notify)?;
+        let mut failed = 0;
+
+        for corrupt in verification.corrupt.iter() {
+            store.evict(&corrupt.digest)?;
+            notify.info(format!("Evicted {}", corrupt.digest));
+
+            if !self.redownload {
+                continue;
+            }
+
+            let Some(source) = &corrupt.source else {
+                notify.warn(format!(
+                    "Source of {} is unknown, it is downloaded again when next installed",
+                    corrupt.digest
+                ));
+                continue;
+            };
+
+            match store.refetch(&corrupt.digest, source).await {
+                Ok(_) => notify.info(format!("Downloaded {} from {source}", corrupt.digest)),
+                Err(err) => {
+                    notify.warn(format!("Failed to download {}: {err}", corrupt.digest));
+                    failed += 1;
+                }
+            }
+        }
+
+        if failed > 0 {
+            bail!("{failed} evicted objects could not be downloaded again");
+        }
+
+        if !verification.corrupt.is_empty() {
+            notify.done(format!(
+                "Evicted {} corrupt objects",
+                verification.corrupt.len()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn verify_store(store: &ContentStore, full: bool, notify: Notify) -> Result<StoreVerification> {
+    notify.info(format!("Verifying {}", store.root().display()));
+
+    let verification = store.verify(full)?;
+
+    for corrupt in verification.corrupt.iter() {
+        notify.warn(format!(
+            "{} does not match its digest",
+            corrupt.path.display()
+        ));
+    }
+
+    if verification.corrupt.is_empty() {
+        notify.done(format!(
+            "Verified {} objects, {} unchanged since last verified",
+            verification.hashed, verification.unchanged
+        ));
+    }
+
+    Ok(verification)
+}
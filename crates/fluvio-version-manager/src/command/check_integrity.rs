@@ -0,0 +1,113 @@
+//! Check Integrity Command
+//!
+//! The `check-integrity` command downloads the artifacts of an installed
+//! Fluvio Version again from their release and compares the binaries they
+//! hold with the installed ones, reporting drift from local tampering, disk
+//! corruption or manual overwrites.
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+use comfy_table::{Row, Table};
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::Channel;
+
+use crate::common::TARGET;
+use crate::common::github::fvm_client;
+use crate::common::integrity::{check_integrity, release_digests};
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+use crate::common::version_directory::VersionDirectory;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct CheckIntegrityOpt {
+    /// Version to check, defaults to the active version
+    #[arg(index = 1)]
+    version: Option<Channel>,
+}
+
+impl CheckIntegrityOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let channel = match &self.version {
+            Some(channel) => channel.to_owned(),
+            None => match Settings::open()?.channel {
+                Some(channel) => channel,
+                None => {
+                    notify.help(format!(
+                        "You can use {} to see installed versions",
+                        "fvm list".bold()
+                    ));
+
+                    bail!("No version provided and no active version set");
+                }
+            },
+        };
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {channel} is not installed"),
+            )
+            .into());
+        }
+
+        let version_dir = VersionDirectory::open(version_path)?;
+        let arch = match &version_dir.manifest.provenance {
+            Some(provenance) => provenance.arch.clone(),
+            None => TARGET.to_string(),
+        };
+
+        notify.info(format!(
+            "Downloading the artifacts of Fluvio version {} to re-derive their checksums",
+            version_dir.manifest.version
+        ));
+
+        let expected = release_digests(&fvm_client()?, &version_dir, &arch).await?;
+        let results = check_integrity(&version_dir, &expected)?;
+        let mut table = Table::new();
+
+        table.set_header(Row::from(["BINARY", "STATUS"]));
+
+        for result in &results {
+            let status = if result.status.is_failure() {
+                result.status.to_string().red()
+            } else {
+                result.status.to_string().normal()
+            };
+
+            table.add_row(Row::from([result.name.clone(), status.to_string()]));
+        }
+
+        table.load_preset(comfy_table::presets::NOTHING);
+        println!("{table}");
+
+        let failures = results
+            .iter()
+            .filter(|result| result.status.is_failure())
+            .count();
+
+        if failures > 0 {
+            notify.help(format!(
+                "Reinstall the version with {}",
+                format!("fvm install {channel}").bold()
+            ));
+
+            return Err(Failure::new(
+                FailureKind::Integrity,
+                format!("{failures} binaries of Fluvio version {channel} drifted from the release"),
+            )
+            .into());
+        }
+
+        notify.done(format!(
+            "{} binaries of Fluvio version {} match the release",
+            results.len(),
+            version_dir.manifest.version
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+//! Clean Command
+//!
+//! Removes temporary directories and downloads left behind by interrupted
+//! FVM operations.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use humantime::parse_duration;
+
+use crate::common::janitor::Janitor;
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct CleanOpt {
+    /// List the entries that would be removed without removing them
+    #[arg(long)]
+    dry_run: bool,
+    /// Only remove entries older than the provided duration (e.g. 1h, 7d)
+    #[arg(long, default_value = "0s", value_parser = parse_duration)]
+    older_than: Duration,
+}
+
+impl CleanOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let Some(mut janitor) = Janitor::open()? else {
+            notify.warn("FVM is not installed, nothing to clean");
+            return Ok(());
+        };
+
+        let paths = janitor.clean(self.older_than, self.dry_run)?;
+
+        if paths.is_empty() {
+            notify.done("Nothing to clean");
+            return Ok(());
+        }
+
+        for path in paths.iter() {
+            if self.dry_run {
+                notify.info(format!("Would remove {}", path.display()));
+            } else {
+                notify.info(format!("Removed {}", path.display()));
+            }
+        }
+
+        if self.dry_run {
+            notify.help(format!(
+                "Run {} to remove {} entries",
+                "fvm clean".bold(),
+                paths.len()
+            ));
+        } else {
+            notify.done(format!("Removed {} entries", paths.len()));
+        }
+
+        Ok(())
+    }
+}
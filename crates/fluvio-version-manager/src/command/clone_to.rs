@@ -0,0 +1,199 @@
+//! Clone To Command
+//!
+//! Exports an installed Fluvio Version (binaries and manifest) as a version
+//! archive into a local path or a remote host reachable through SSH.
+
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use colored::Colorize;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::Channel;
+
+use crate::common::notify::Notify;
+use crate::common::version_archive::VersionArchive;
+use crate::common::version_directory::VersionDirectory;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct CloneToOpt {
+    /// Installed version to export: stable, latest, or named-version x.y.z
+    #[arg(index = 1)]
+    version: Channel,
+    /// Destination for the archive, either a local path or `[user@]host:path`
+    #[arg(index = 2)]
+    destination: String,
+}
+
+/// Where the version archive is written to
+#[derive(Debug, PartialEq, Eq)]
+enum Destination {
+    Local(PathBuf),
+    Ssh { host: String, path: String },
+}
+
+impl Destination {
+    /// Parses `[user@]host:path` as an SSH destination, anything else is
+    /// considered a local path.
+    ///
+    /// Hosts starting with `-` are rejected so they are never taken as SSH
+    /// options.
+    fn parse(s: &str) -> Result<Self> {
+        if let Some((host, path)) = s.split_once(':') {
+            // Avoid confusing Windows drive letters (`C:\`) and paths with
+            // colons after a separator with SSH destinations
+            let is_drive = host.len() == 1 && host.chars().all(|c| c.is_ascii_alphabetic());
+
+            if !host.is_empty() && !is_drive && !host.contains(['/', '\\']) {
+                if host.starts_with('-') {
+                    bail!("Invalid SSH host {host}");
+                }
+
+                return Ok(Self::Ssh {
+                    host: host.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::Local(PathBuf::from(s)))
+    }
+}
+
+/// Quotes `path` for the remote shell, leaving a leading `~/` unquoted so it
+/// is still expanded to the home directory of the remote user
+fn remote_shell_path(path: &str) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', r"'\''"));
+
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", quote(rest)),
+        None => quote(path),
+    }
+}
+
+impl CloneToOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let version_path = fvm_versions_path()?.join(self.version.to_string());
+
+        if !version_path.exists() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {} is not installed", self.version),
+            )
+            .into());
+        }
+
+        let version_dir = VersionDirectory::open(version_path)?;
+        let file_name = VersionArchive::file_name(&version_dir.manifest);
+
+        let archive_path = match Destination::parse(&self.destination)? {
+            Destination::Local(mut path) => {
+                if path.is_dir() {
+                    path = path.join(&file_name);
+                }
+
+                VersionArchive::export(&version_dir, File::create(&path)?)?;
+                notify.done(format!(
+                    "Exported fluvio version {} to {}",
+                    version_dir.manifest.version,
+                    path.display()
+                ));
+
+                path.display().to_string()
+            }
+            Destination::Ssh { host, mut path } => {
+                if path == "~" {
+                    path.push('/');
+                }
+
+                if path.is_empty() || path.ends_with('/') {
+                    path.push_str(&file_name);
+                }
+
+                let mut child = Command::new("ssh")
+                    .arg("--")
+                    .arg(&host)
+                    .arg(format!("cat > {}", remote_shell_path(&path)))
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+
+                // Stream the archive, dropping stdin once done so the remote
+                // `cat` sees the end of the input
+                let exported = match child.stdin.take() {
+                    Some(stdin) => VersionArchive::export(&version_dir, stdin),
+                    None => Err(anyhow!("Failed to open ssh stdin")),
+                };
+                let status = child.wait()?;
+
+                if !status.success() {
+                    bail!("Failed to copy version archive to {host}:{path} ({status})");
+                }
+
+                exported?;
+                notify.done(format!(
+                    "Exported fluvio version {} to {host}:{path}",
+                    version_dir.manifest.version,
+                ));
+
+                path
+            }
+        };
+
+        notify.help(format!(
+            "Install it on the destination host using {}",
+            format!("fvm import {archive_path}").bold()
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clone_destinations() {
+        assert_eq!(
+            Destination::parse("builder@ci-1:/opt/fvm/").unwrap(),
+            Destination::Ssh {
+                host: "builder@ci-1".to_string(),
+                path: "/opt/fvm/".to_string(),
+            }
+        );
+        assert_eq!(
+            Destination::parse("ci-1:").unwrap(),
+            Destination::Ssh {
+                host: "ci-1".to_string(),
+                path: String::new(),
+            }
+        );
+        assert_eq!(
+            Destination::parse("/tmp/fluvio.tar.gz").unwrap(),
+            Destination::Local(PathBuf::from("/tmp/fluvio.tar.gz"))
+        );
+        assert_eq!(
+            Destination::parse("./out/a:b.tar.gz").unwrap(),
+            Destination::Local(PathBuf::from("./out/a:b.tar.gz"))
+        );
+        assert_eq!(
+            Destination::parse(r"C:\fvm\fluvio.tar.gz").unwrap(),
+            Destination::Local(PathBuf::from(r"C:\fvm\fluvio.tar.gz"))
+        );
+        assert!(Destination::parse("-oProxyCommand=sh:/tmp").is_err());
+    }
+
+    #[test]
+    fn quotes_remote_paths() {
+        assert_eq!(
+            remote_shell_path("/opt/fvm/a.tar.gz"),
+            "'/opt/fvm/a.tar.gz'"
+        );
+        assert_eq!(remote_shell_path("~/fvm/a.tar.gz"), "~/'fvm/a.tar.gz'");
+        assert_eq!(remote_shell_path("it's.tar.gz"), r"'it'\''s.tar.gz'");
+    }
+}
@@ -0,0 +1,117 @@
+//! Config Command
+//!
+//! The `config` command exports the settings, mirrors and installed versions
+//! of this host as an environment file, and imports environment files
+//! exported elsewhere, installing the versions missing on this host.
+
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use colored::Colorize;
+
+use fvm_core::{Installer, Switcher, list_installed};
+
+use crate::common::environment::Environment;
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+
+#[derive(Debug, Parser)]
+pub enum ConfigCommand {
+    /// Print the environment of this host as TOML, e.g. `fvm config export > env.toml`
+    Export,
+    /// Apply an environment file, installing the versions it lists
+    Import(ConfigImportOpt),
+}
+
+#[derive(Debug, Parser)]
+pub struct ConfigImportOpt {
+    /// Path to the environment file
+    #[arg(index = 1)]
+    path: PathBuf,
+    /// Apply settings and mirrors without installing versions
+    #[arg(long)]
+    no_install: bool,
+}
+
+/// The `config` command exports and imports FVM environments
+#[derive(Debug, Parser)]
+pub struct ConfigOpt {
+    /// Subcommand to execute
+    #[clap(subcommand)]
+    command: ConfigCommand,
+}
+
+impl ConfigOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        match &self.command {
+            ConfigCommand::Export => Self::export(),
+            ConfigCommand::Import(cmd) => cmd.process(notify).await,
+        }
+    }
+
+    fn export() -> Result<()> {
+        let mut environment = Environment::from_settings(&Settings::open()?);
+
+        for installed in list_installed()? {
+            environment.add_installed(&installed.manifest.channel, &installed.manifest.version);
+        }
+
+        print!("{}", environment.to_toml()?);
+
+        Ok(())
+    }
+}
+
+impl ConfigImportOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let contents = read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let environment = Environment::parse(&contents)?;
+
+        Settings::open()?.apply_environment(&environment)?;
+        notify.done(format!(
+            "Applied settings and mirrors from {}",
+            self.path.display()
+        ));
+
+        if self.no_install {
+            return Ok(());
+        }
+
+        let installed: Vec<_> = list_installed()?
+            .into_iter()
+            .map(|installed| installed.manifest.channel)
+            .collect();
+
+        for channel in environment.channels()? {
+            if installed.contains(&channel) {
+                continue;
+            }
+
+            notify.info(format!(
+                "Installing Fluvio version {}",
+                channel.to_string().bold()
+            ));
+            Installer::new()
+                .with_notify(notify)
+                .install(&channel)
+                .await?;
+        }
+
+        // Installs switch to the installed version
+        if let Some(active) = environment.active_channel()?
+            && Switcher::current()?.is_none_or(|current| current.manifest.channel != active)
+        {
+            let installed = Switcher::new().with_notify(notify).switch(&active).await?;
+
+            notify.done(format!(
+                "Now using Fluvio version {}",
+                installed.manifest.version.to_string().bold()
+            ));
+        }
+
+        Ok(())
+    }
+}
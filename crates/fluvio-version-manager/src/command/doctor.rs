@@ -0,0 +1,172 @@
+//! Doctor Command
+//!
+//! The `doctor` command probes the hosts FVM downloads from, one stage at a
+//! time: DNS resolution, TCP connection, proxy tunnel, TLS handshake and
+//! HTTP request. Each stage is timed and reported, so a failing download
+//! points at the stage and host where it breaks, e.g. a proxy intercepting
+//! TLS.
+
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+use comfy_table::{Row, Table};
+
+use fluvio_artifacts_util::github::{GITHUB_API_URL, GitHubRepo};
+use fluvio_artifacts_util::htclient::probe::{
+    DEFAULT_PROBE_TIMEOUT, ProbeReport, ProbeStage, StageOutcome, probe,
+};
+
+use crate::common::github::github_repo;
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct DoctorOpt {
+    /// URLs to probe instead of the GitHub API, release pages and raw files
+    /// of the configured repository
+    #[arg(long = "url")]
+    urls: Vec<String>,
+    /// Seconds allowed for each network stage
+    #[arg(long, default_value_t = DEFAULT_PROBE_TIMEOUT.as_secs())]
+    timeout: u64,
+}
+
+impl DoctorOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let urls = if self.urls.is_empty() {
+            artifact_sources(&github_repo()?)
+        } else {
+            self.urls.clone()
+        };
+        let timeout = Duration::from_secs(self.timeout);
+        let mut failures = 0;
+
+        for url in urls.iter() {
+            let report = probe(url, timeout).await?;
+
+            match &report.proxy {
+                Some(proxy) => notify.info(format!("Probing {url} through proxy {proxy}")),
+                None => notify.info(format!("Probing {url}")),
+            }
+
+            println!("{}", render(&report));
+
+            if let Some(failure) = report.failure() {
+                failures += 1;
+                notify.warn(format!(
+                    "{} failed at the {} stage with {}",
+                    url, failure.stage, failure.peer
+                ));
+                notify.help(hint(failure.stage, failure.via_proxy));
+            }
+        }
+
+        if failures > 0 {
+            bail!("{failures} of {} probes failed", urls.len());
+        }
+
+        notify.done(format!("All {} hosts are reachable", urls.len()));
+
+        Ok(())
+    }
+}
+
+/// URLs FVM downloads release metadata and artifacts from
+pub(crate) fn artifact_sources(repo: &GitHubRepo) -> Vec<String> {
+    let api_url = repo.api_url.as_deref().unwrap_or(GITHUB_API_URL);
+    let web_url = match repo.api_url.as_deref().filter(|url| *url != GITHUB_API_URL) {
+        Some(api_url) => api_url.trim_end_matches("/api/v3").to_string(),
+        None => "https://github.com".to_string(),
+    };
+
+    vec![
+        format!("{api_url}/repos/{}/{}", repo.owner, repo.name),
+        format!("{web_url}/{}/{}/releases", repo.owner, repo.name),
+        repo.raw_file_url("master", "VERSION"),
+    ]
+}
+
+fn render(report: &ProbeReport) -> Table {
+    let mut table = Table::new();
+
+    table.set_header(Row::from(["STAGE", "PEER", "TIME", "RESULT"]));
+
+    for stage in report.stages.iter() {
+        let peer = if stage.via_proxy {
+            format!("{} (proxy)", stage.peer)
+        } else {
+            stage.peer.clone()
+        };
+        let (time, result) = match &stage.outcome {
+            StageOutcome::Passed(detail) => (
+                format!("{}ms", stage.elapsed.as_millis()),
+                detail.green().to_string(),
+            ),
+            StageOutcome::Failed(err) => (
+                format!("{}ms", stage.elapsed.as_millis()),
+                err.red().to_string(),
+            ),
+            StageOutcome::Skipped => ("-".to_string(), "skipped".dimmed().to_string()),
+        };
+
+        table.add_row(Row::from([stage.stage.to_string(), peer, time, result]));
+    }
+
+    table.load_preset(comfy_table::presets::NOTHING);
+    table
+}
+
+/// What usually breaks at `stage`
+fn hint(stage: ProbeStage, via_proxy: bool) -> &'static str {
+    match (stage, via_proxy) {
+        (ProbeStage::Dns, true) => "The proxy host cannot be resolved, check the proxy variables",
+        (ProbeStage::Dns, false) => "The host cannot be resolved, check the DNS configuration",
+        (ProbeStage::Tcp, true) => "The proxy does not accept connections, check it is running",
+        (ProbeStage::Tcp, false) => {
+            "The connection is blocked, a firewall may require going through a proxy, set HTTPS_PROXY"
+        }
+        (ProbeStage::Tunnel, _) => {
+            "The proxy refused to tunnel to the host, check the proxy credentials and allow list"
+        }
+        (ProbeStage::Tls, true) => {
+            "TLS through the proxy failed, a proxy intercepting TLS must have its CA in the system trust store"
+        }
+        (ProbeStage::Tls, false) => {
+            "TLS failed, check the system clock and trust store, or whether TLS is intercepted"
+        }
+        (ProbeStage::Http, _) => {
+            "The host is reachable but the request failed, the service may be unavailable"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_artifact_sources() {
+        assert_eq!(
+            artifact_sources(&GitHubRepo::default()),
+            vec![
+                "https://api.github.com/repos/fluvio-community/fluvio",
+                "https://github.com/fluvio-community/fluvio/releases",
+                "https://raw.githubusercontent.com/fluvio-community/fluvio/master/VERSION",
+            ]
+        );
+
+        let ghe = GitHubRepo::default()
+            .with_repo("acme", "fluvio")
+            .with_api_url("https://ghe.example.com/api/v3");
+
+        assert_eq!(
+            artifact_sources(&ghe),
+            vec![
+                "https://ghe.example.com/api/v3/repos/acme/fluvio",
+                "https://ghe.example.com/acme/fluvio/releases",
+                "https://ghe.example.com/raw/acme/fluvio/master/VERSION",
+            ]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Import Command
+//!
+//! Installs a Fluvio Version from a version archive produced by
+//! `fvm clone-to`, verifying binaries digests before storing them.
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+
+use fvm_core::Switcher;
+
+use crate::common::notify::Notify;
+use crate::common::version_archive::VersionArchive;
+use crate::common::version_policy::{check_version_policy, load_version_policy};
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct ImportOpt {
+    /// Path to the version archive
+    #[arg(index = 1)]
+    archive: PathBuf,
+    /// Replace the version if it is already installed
+    #[arg(long)]
+    force: bool,
+    /// Set the imported version as active
+    #[arg(long)]
+    switch: bool,
+    /// Number of threads used to verify checksums, defaults to one per core
+    #[arg(long, short)]
+    jobs: Option<usize>,
+}
+
+impl ImportOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let versions_path = fvm_versions_path()?;
+        let archive = File::open(&self.archive)?;
+        let archive_size = archive.metadata()?.len();
+        let policy = load_version_policy().await?;
+        let version_dir = VersionArchive::import(
+            archive,
+            archive_size,
+            &versions_path,
+            self.force,
+            self.jobs,
+            |manifest| match &policy {
+                Some(policy) => check_version_policy(policy, &manifest.version, notify),
+                None => Ok(()),
+            },
+        )?;
+
+        notify.done(format!(
+            "Imported fluvio version {} from {}",
+            version_dir.manifest.version,
+            self.archive.display()
+        ));
+
+        if self.switch {
+            Switcher::new()
+                .with_notify(notify)
+                .switch(&version_dir.manifest.channel)
+                .await?;
+            notify.done(format!(
+                "Now using fluvio version {}",
+                version_dir.manifest.version
+            ));
+        } else {
+            notify.help(format!(
+                "You can set it as active using {}",
+                format!("fvm switch {}", version_dir.manifest.channel).bold()
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Init Command
+//!
+//! Onboards a machine in one command: installs a Fluvio Version with the
+//! components of an install profile and runs the setup steps of the profile.
+
+use anyhow::Result;
+use clap::Parser;
+
+use fluvio_artifacts_util::fvm::Channel;
+use fvm_core::Installer;
+
+use crate::common::TARGET;
+use crate::common::install_profile::InstallProfile;
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+
+use super::setup::SetupOpt;
+
+#[derive(Debug, Parser)]
+pub struct InitOpt {
+    /// Components and setup steps to install
+    #[arg(long, value_enum, default_value_t)]
+    profile: InstallProfile,
+    /// Version to install: stable, latest (or dev), minor version x.y, or named-version x.y.z
+    #[arg(index = 1, default_value_t = Channel::Stable)]
+    version: Channel,
+}
+
+impl InitOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let notify = if self.profile.quiet() {
+            Notify::new(true)
+        } else {
+            notify
+        };
+
+        Settings::open()?.set_install_profile(self.profile)?;
+
+        let installed = Installer::new()
+            .with_target(TARGET)
+            .with_profile(Some(self.profile))
+            .with_notify(notify)
+            .install(&self.version)
+            .await?;
+
+        notify.done(format!(
+            "Installed {} with the {} profile: {}",
+            installed.manifest.version,
+            self.profile,
+            self.profile.components().join(", ")
+        ));
+
+        if self.profile.edits_path() {
+            SetupOpt::default().process(notify).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -3,17 +3,15 @@
 //! Downloads and stores the sepecific Fluvio Version binaries in the local
 //! FVM cache.
 
-use std::fs::create_dir_all;
-
 use anyhow::Result;
 use clap::Parser;
 
-use fluvio_artifacts_util::fvm::{Client, Channel};
+use fluvio_artifacts_util::fvm::{Channel, ComponentSelection};
+use fvm_core::Installer;
 
 use crate::common::TARGET;
+use crate::common::cluster_compatibility::check_cluster_compatibility;
 use crate::common::notify::Notify;
-use crate::common::version_installer::VersionInstaller;
-use crate::common::workdir::fvm_versions_path;
 
 /// The `install` command is responsible of installing the desired Package Set
 #[derive(Debug, Parser)]
@@ -21,27 +19,61 @@ pub struct InstallOpt {
     /// Binaries architecture triple to use
     #[arg(long, env = "FVM_BINARY_ARCH_TRIPLE", default_value = TARGET)]
     target: String,
-    /// Version to install: stable, latest, or named-version x.y.z
+    /// Version to install: stable, latest (or dev), minor version x.y, or named-version x.y.z
     #[arg(index = 1, default_value_t = Channel::Stable)]
     version: Channel,
+    /// Install baseline artifacts even if CPU optimized variants are available
+    #[arg(long)]
+    generic: bool,
+    /// Install the version even if it reached end-of-life
+    #[arg(long)]
+    accept_eol: bool,
+    /// Verify artifact digests against the transparency manifest at this URL,
+    /// `{version}` and `{target}` are replaced
+    #[arg(long, value_name = "URL")]
+    transparency_url: Option<String>,
+    /// Write a verification report with the URLs, digests and sizes of the
+    /// downloads next to the installed binaries
+    #[arg(long)]
+    verification_report: bool,
+    /// Fail if the newest stable release is missing artifacts for the target
+    /// instead of installing the prior stable release
+    #[arg(long)]
+    strict: bool,
+    /// Install the version even if the host glibc or macOS version is older
+    /// than the binaries require
+    #[arg(long)]
+    ignore_requirements: bool,
+    /// Install a binary from another channel, e.g. `smdk@latest`. May be
+    /// repeated.
+    #[arg(long = "component", value_name = "BINARY@CHANNEL")]
+    components: Vec<ComponentSelection>,
+    /// Warn if the installed CLI is not compatible with the platform version
+    /// of the cluster in the active profile
+    #[arg(long)]
+    check_cluster: bool,
 }
 
 impl InstallOpt {
     pub async fn process(&self, notify: Notify) -> Result<()> {
-        let versions_path = fvm_versions_path()?;
+        let installed = Installer::new()
+            .with_target(&self.target)
+            .with_generic(self.generic)
+            .with_accept_eol(self.accept_eol)
+            .with_transparency_url(self.transparency_url.clone())
+            .with_verification_report(self.verification_report.then_some(true))
+            .with_strict(self.strict)
+            .with_ignore_requirements(self.ignore_requirements)
+            .with_components(self.components.clone())
+            .with_notify(notify)
+            .install(&self.version)
+            .await?;
 
-        if !versions_path.exists() {
-            tracing::info!(?versions_path, "Creating versions directory");
-            create_dir_all(&versions_path)?;
+        // Binaries for other targets are not run on this host
+        if self.check_cluster && installed.active && self.target == TARGET {
+            check_cluster_compatibility(&installed.manifest.version, notify).await;
         }
 
-        let client = Client;
-        let pkgset = client
-            .fetch_default_package_set(&self.version, &self.target)
-            .await?;
-
-        VersionInstaller::new(self.version.to_owned(), pkgset, notify)
-            .install()
-            .await
+        Ok(())
     }
 }
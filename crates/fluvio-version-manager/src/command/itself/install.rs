@@ -34,7 +34,10 @@ impl SelfInstallOpt {
             "FVM installed successfully at {}",
             fvm_installation_path.display()
         ));
-        notify.help(format!("Add FVM to PATH using {}", "source $HOME/.fvm/env"));
+        notify.help(format!(
+            "Add FVM to PATH using {} or {}",
+            "fvm setup", "source $HOME/.fvm/env"
+        ));
 
         Ok(())
     }
@@ -1,48 +1,379 @@
-use std::fs::remove_dir_all;
+//! Self Uninstall Command
+//!
+//! Removes the FVM binary, the version shims and the `PATH` changes made by
+//! `fvm setup` to shell profile files. Installed versions, caches and the
+//! binaries they installed are removed as well with `--purge` or once
+//! confirmed, or kept with `--keep-versions`. The binaries directory may be
+//! shared with other tools, e.g. `~/.local/bin`, so only the binaries listed
+//! in the manifests of installed versions are removed from it.
+
+use std::collections::BTreeSet;
+use std::fs::{read_dir, read_to_string, remove_dir_all, remove_file};
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::Parser;
 use dialoguer::Confirm;
 use dialoguer::theme::ColorfulTheme;
 
+use fluvio_artifacts_util::layout::Layout;
+use fluvio_artifacts_util::store::ContentStore;
+
+use crate::common::home_dir;
+use crate::common::manifest::{PACKAGE_SET_MANIFEST_FILENAME, VersionManifest};
 use crate::common::notify::Notify;
-use crate::common::workdir::fvm_workdir_path;
+use crate::common::shell_profile::{Shell, erase_block, remove_block};
+use crate::common::shim::parse_shim_name;
+use crate::common::workdir::{fvm_layout, fvm_workdir_path};
 
 #[derive(Clone, Debug, Parser)]
 pub struct SelfUninstallOpt {
-    /// Skip the confirmation prompt and uninstall FVM
+    /// Skip the confirmation prompts and uninstall FVM, keeping installed
+    /// versions and caches unless `--purge` is set
     #[clap(long)]
     yes: bool,
+    /// Remove installed versions, caches and the binaries they installed
+    #[clap(long, conflicts_with = "keep_versions")]
+    purge: bool,
+    /// Keep installed versions and caches
+    #[clap(long)]
+    keep_versions: bool,
 }
 
 impl SelfUninstallOpt {
     pub async fn process(&self, notify: Notify) -> Result<()> {
-        // Checks if FVM is already installed
         let workdir_path = fvm_workdir_path()?;
+        let layout = fvm_layout()?;
+        let home = home_dir()?;
 
-        if workdir_path.exists() {
-            if self.yes
-                || Confirm::with_theme(&ColorfulTheme::default())
-                    .with_prompt(format!(
-                        "Are you sure you want to uninstall FVM from {}?",
-                        workdir_path.display()
-                    ))
-                    .interact()?
-            {
-                remove_dir_all(&workdir_path)?;
-                notify.done(format!(
-                    "Fluvio Version Manager was removed from {}",
+        if !workdir_path.exists()
+            && UninstallPlan::new(&workdir_path, &layout, &home, false)?.is_empty()
+        {
+            notify.warn(format!(
+                "Aborting uninstallation, no FVM installation found at {}",
+                workdir_path.display()
+            ));
+            return Ok(());
+        }
+
+        if !self.yes
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Are you sure you want to uninstall FVM from {}?",
                     workdir_path.display()
-                ));
+                ))
+                .interact()?
+        {
+            return Ok(());
+        }
+
+        let purge = if self.purge {
+            true
+        } else if self.keep_versions || self.yes {
+            false
+        } else {
+            Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Remove installed versions from {} and caches as well?",
+                    layout.versions_dir.display()
+                ))
+                .default(false)
+                .interact()?
+        };
+        let mut plan = UninstallPlan::new(&workdir_path, &layout, &home, purge)?;
+
+        if purge
+            && let Some(store) = ContentStore::open_default()
+            && store.root().exists()
+        {
+            plan.paths.push(store.root().to_path_buf());
+        }
+
+        plan.apply(notify)?;
+
+        if purge {
+            notify.done(format!(
+                "Fluvio Version Manager was removed from {}",
+                workdir_path.display()
+            ));
+        } else {
+            notify.done(format!(
+                "Fluvio Version Manager was removed, installed versions were kept in {}",
+                layout.versions_dir.display()
+            ));
+        }
+
+        notify.help("Restart your shell for changes to take effect");
+
+        Ok(())
+    }
+}
+
+/// Files and directories removed by `fvm self uninstall`
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UninstallPlan {
+    /// Profile files with a block added by `fvm setup`
+    profiles: Vec<PathBuf>,
+    /// Version shims, such as `fluvio@0.11.8`
+    shims: Vec<PathBuf>,
+    /// Binaries of installed versions in the binaries directory
+    binaries: Vec<PathBuf>,
+    /// Files and directories removed with their contents
+    paths: Vec<PathBuf>,
+}
+
+impl UninstallPlan {
+    /// Lists what is removed from `workdir`, the directories of `layout` and
+    /// the profile files in `home`. Unless `purge` is set, the versions and
+    /// cache directories, and the binaries of installed versions, are kept.
+    /// The binaries directory itself is never removed.
+    fn new(workdir: &Path, layout: &Layout, home: &Path, purge: bool) -> Result<Self> {
+        let mut profiles: Vec<PathBuf> = Vec::new();
+
+        for profile in Shell::ALL
+            .iter()
+            .flat_map(|shell| shell.profile_files(home))
+        {
+            if profiles.contains(&profile) {
+                continue;
             }
 
-            return Ok(());
+            if let Ok(contents) = read_to_string(&profile)
+                && remove_block(&contents).is_some()
+            {
+                profiles.push(profile);
+            }
+        }
+
+        let mut shims = Vec::new();
+
+        if layout.bin_dir.exists() {
+            for entry in read_dir(&layout.bin_dir)? {
+                let path = entry?.path();
+                let is_shim = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(parse_shim_name)
+                    .is_some();
+
+                if is_shim {
+                    shims.push(path);
+                }
+            }
+        }
+
+        shims.sort();
+
+        let paths = if purge {
+            let mut paths: Vec<PathBuf> = Vec::new();
+
+            for dir in [
+                workdir,
+                &layout.versions_dir,
+                &layout.cache_dir,
+                &layout.temp_dir,
+            ] {
+                if dir.exists() && !paths.iter().any(|path| dir.starts_with(path)) {
+                    paths.retain(|path| !path.starts_with(dir));
+                    paths.push(dir.to_path_buf());
+                }
+            }
+
+            paths
+        } else {
+            let kept = [&layout.versions_dir, &layout.cache_dir];
+            let mut paths = Vec::new();
+
+            if workdir.exists() {
+                for entry in read_dir(workdir)? {
+                    let path = entry?.path();
+
+                    if !kept.iter().any(|dir| dir.starts_with(&path)) {
+                        paths.push(path);
+                    }
+                }
+            }
+
+            paths.sort();
+            paths
+        };
+
+        let binaries = if purge {
+            installed_binaries(layout)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            profiles,
+            shims,
+            binaries,
+            paths,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+            && self.shims.is_empty()
+            && self.binaries.is_empty()
+            && self.paths.is_empty()
+    }
+
+    fn apply(&self, notify: Notify) -> Result<()> {
+        for profile in self.profiles.iter() {
+            if erase_block(profile)? {
+                notify.info(format!("Removed FVM from {}", profile.display()));
+            }
+        }
+
+        for shim in self.shims.iter() {
+            remove_file(shim)?;
+        }
+
+        if !self.shims.is_empty() {
+            notify.info(format!("Removed {} version shims", self.shims.len()));
+        }
+
+        for binary in self.binaries.iter() {
+            remove_file(binary)?;
+            tracing::debug!(?binary, "Removed");
+        }
+
+        for path in self.paths.iter() {
+            if path.is_dir() {
+                remove_dir_all(path)?;
+            } else {
+                remove_file(path)?;
+            }
+
+            tracing::debug!(?path, "Removed");
         }
 
-        notify.warn(format!(
-            "Aborting uninstallation, no FVM installation found at {}",
-            workdir_path.display()
-        ));
         Ok(())
     }
 }
+
+/// Binaries in the binaries directory of `layout` which are listed in the
+/// manifests of the installed versions
+fn installed_binaries(layout: &Layout) -> Result<Vec<PathBuf>> {
+    let mut binaries = BTreeSet::new();
+
+    if !layout.versions_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    for entry in read_dir(&layout.versions_dir)? {
+        let manifest_path = entry?.path().join(PACKAGE_SET_MANIFEST_FILENAME);
+
+        if !manifest_path.is_file() {
+            continue;
+        }
+
+        let manifest = match VersionManifest::open(&manifest_path) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                tracing::warn!(%err, "Skipping binaries of unreadable version manifest");
+                continue;
+            }
+        };
+
+        for artifact in manifest.contents.iter().flatten() {
+            let path = layout.bin_dir.join(&artifact.name);
+
+            if path.is_file() {
+                binaries.insert(path);
+            }
+        }
+    }
+
+    Ok(binaries.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use tempfile::TempDir;
+
+    use fluvio_artifacts_util::fvm::Channel;
+    use semver::Version;
+
+    use crate::common::manifest::VersionedArtifact;
+    use crate::common::shell_profile::write_block;
+
+    use super::*;
+
+    fn install(root: &Path) -> (PathBuf, Layout, PathBuf) {
+        let home = root.join("home");
+        let workdir = home.join(".fvm");
+        let layout = Layout::home(&workdir, &home.join(".fluvio"));
+
+        create_dir_all(workdir.join("bin")).unwrap();
+        create_dir_all(layout.versions_dir.join("0.11.8")).unwrap();
+        create_dir_all(&layout.cache_dir).unwrap();
+        create_dir_all(&layout.bin_dir).unwrap();
+        write(workdir.join("bin").join("fvm"), "").unwrap();
+        write(workdir.join("env"), "").unwrap();
+        write(layout.bin_dir.join("fluvio"), "").unwrap();
+        write(layout.bin_dir.join("fluvio@0.11.8"), "").unwrap();
+        write(layout.bin_dir.join("kubectl"), "").unwrap();
+        VersionManifest::new(
+            Channel::Tag(Version::new(0, 11, 8)),
+            Version::new(0, 11, 8),
+            vec![VersionedArtifact::new("fluvio", "0.11.8")],
+        )
+        .write(layout.versions_dir.join("0.11.8"))
+        .unwrap();
+        write(home.join(".zshrc"), "alias k=kubectl\n").unwrap();
+        write_block(&home.join(".bashrc"), &Shell::Bash.profile_block(&[], None)).unwrap();
+
+        (workdir, layout, home)
+    }
+
+    #[test]
+    fn keeps_versions_and_caches() {
+        let tmp = TempDir::new().unwrap();
+        let (workdir, layout, home) = install(tmp.path());
+        let plan = UninstallPlan::new(&workdir, &layout, &home, false).unwrap();
+
+        assert_eq!(plan.profiles, vec![home.join(".bashrc")]);
+        assert_eq!(plan.shims, vec![layout.bin_dir.join("fluvio@0.11.8")]);
+        assert_eq!(plan.paths, vec![workdir.join("bin"), workdir.join("env")]);
+
+        plan.apply(Notify::new(true)).unwrap();
+
+        assert!(layout.versions_dir.join("0.11.8").exists());
+        assert!(layout.bin_dir.join("fluvio").exists());
+        assert!(!workdir.join("bin").exists());
+        assert_eq!(
+            read_to_string(home.join(".zshrc")).unwrap(),
+            "alias k=kubectl\n"
+        );
+        assert!(
+            UninstallPlan::new(&workdir, &layout, &home, false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn purges_versions_and_caches() {
+        let tmp = TempDir::new().unwrap();
+        let (workdir, layout, home) = install(tmp.path());
+        let plan = UninstallPlan::new(&workdir, &layout, &home, true).unwrap();
+
+        assert_eq!(plan.binaries, vec![layout.bin_dir.join("fluvio")]);
+        assert_eq!(plan.paths, vec![workdir.clone()]);
+
+        plan.apply(Notify::new(true)).unwrap();
+
+        assert!(!workdir.exists());
+        assert!(!layout.bin_dir.join("fluvio").exists());
+        assert!(!layout.bin_dir.join("fluvio@0.11.8").exists());
+        assert!(
+            layout.bin_dir.join("kubectl").exists(),
+            "binaries of other tools must be kept"
+        );
+        assert!(home.join(".zshrc").exists());
+    }
+}
@@ -4,12 +4,9 @@ use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
 use semver::Version;
-use octocrab::Octocrab;
-
-use fluvio_artifacts_util::{REPO_NAME, REPO_OWNER};
 
 use crate::{
-    common::{notify::Notify, update_manager::UpdateManager},
+    common::{github::github_repo, notify::Notify, update_manager::UpdateManager},
     VERSION,
 };
 
@@ -51,11 +48,13 @@ impl SelfUpdateOpt {
 
     /// Fetches the `stable` channel tag from the Fluvio Version Manager
     async fn fetch_stable_tag(&self) -> Result<Version> {
-        let octocrab = Octocrab::builder().build()?;
+        let repo = github_repo()?;
 
-        // Use GitHub latest release for fluvio-community/fluvio (non-prerelease)
-        let release = octocrab
-            .repos(REPO_OWNER, REPO_NAME)
+        // Use the latest (non-prerelease) GitHub release of the configured
+        // repository, fluvio-community/fluvio by default
+        let release = repo
+            .octocrab()?
+            .repos(&repo.owner, &repo.name)
             .releases()
             .get_latest()
             .await
@@ -1,17 +1,26 @@
 //! Show Intalled Versions Command
 //!
 //! The `show` command is responsible of listing all the installed Fluvio Versions
+//!
+//! Versions are grouped by channel kind (stable, latest, aliases and tags),
+//! marking the active version, broken installs and channels with a newer
+//! version in the remote versions cached by `fvm install` and `fvm update`.
+
+use std::fs::read_dir;
+use std::path::Path;
 
 use anyhow::{Result, anyhow};
 use clap::Parser;
 use colored::Colorize;
 use comfy_table::{Table, Row};
+use semver::Version;
 
 use fluvio_artifacts_util::fvm::Channel;
 
-use crate::common::manifest::VersionManifest;
 use crate::common::notify::Notify;
+use crate::common::remote_versions::RemoteVersions;
 use crate::common::settings::Settings;
+use crate::common::usage::{UsageTracker, format_last_used, format_size};
 use crate::common::version_directory::VersionDirectory;
 use crate::common::workdir::fvm_versions_path;
 
@@ -20,6 +29,9 @@ pub struct ListOpt {
     /// List included artifacts for this installed version if available
     #[arg(index = 1)]
     channel: Option<Channel>,
+    /// Show disk usage and when each version was last activated or executed
+    #[arg(long, short)]
+    verbose: bool,
 }
 
 impl ListOpt {
@@ -72,10 +84,10 @@ impl ListOpt {
         }
 
         let settings = Settings::open()?;
-        let (manifests, maybe_active) =
-            VersionDirectory::scan_versions_manifests(versions_path, settings.channel)?;
+        let remote = RemoteVersions::cached();
+        let listed = scan_listed_versions(&versions_path, settings.channel.as_ref(), &remote)?;
 
-        if manifests.is_empty() && maybe_active.is_none() {
+        if listed.is_empty() {
             notify.warn("No installed versions found");
             notify.help(format!(
                 "You can install a Fluvio version using the command {}",
@@ -85,37 +97,326 @@ impl ListOpt {
             return Ok(());
         }
 
-        Self::render_table(manifests, maybe_active);
+        let usage = if self.verbose {
+            UsageTracker::open()?
+        } else {
+            None
+        };
+
+        self.render_table(&versions_path, usage, listed)?;
         Ok(())
     }
 
-    /// Creates a `Table` and renders it to the terminal.
-    fn render_table(manifests: Vec<VersionManifest>, maybe_active: Option<VersionManifest>) {
+    /// Creates a `Table` with the versions grouped by channel kind and
+    /// renders it to the terminal.
+    fn render_table(
+        &self,
+        versions_path: &Path,
+        usage: Option<UsageTracker>,
+        listed: Vec<ListedVersion>,
+    ) -> Result<()> {
         let mut table = Table::new();
+        let with_status = listed.iter().any(|version| version.status().is_some());
+        let mut header = vec![" ", "CHANNEL", "VERSION"];
 
-        table.set_header(Row::from([" ", "CHANNEL", "VERSION"]));
+        if self.verbose {
+            header.extend(["SIZE", "LAST ACTIVATED", "LAST EXECUTED"]);
+        }
 
-        if let Some(active) = maybe_active {
-            table.add_row(Row::from([
-                "✓".to_string(),
-                active.channel.to_string(),
-                active.version.to_string(),
-            ]));
+        if with_status {
+            header.push("STATUS");
         }
 
-        let mut sorted_manifests = manifests;
-        sorted_manifests.sort_by(|a, b| b.channel.cmp(&a.channel));
+        table.set_header(Row::from(header));
+
+        for (group, versions) in group_versions(listed) {
+            table.add_row(Row::from(["", group.title()]));
 
-        for manifest in sorted_manifests {
-            table.add_row(Row::from([
-                " ".to_string(),
-                manifest.channel.to_string(),
-                manifest.version.to_string(),
-            ]));
+            for version in versions {
+                let mut row = vec![
+                    version.marker().to_string(),
+                    version.channel.to_string(),
+                    version
+                        .version
+                        .as_ref()
+                        .map_or_else(|| String::from("-"), |version| version.to_string()),
+                ];
+
+                if self.verbose {
+                    let key = version.channel.to_string();
+                    let version_usage = usage
+                        .as_ref()
+                        .map(|usage| usage.get(&key))
+                        .unwrap_or_default();
+                    let size = match version.broken {
+                        Some(_) => String::from("-"),
+                        None => format_size(
+                            VersionDirectory::open(versions_path.join(&key))?.disk_usage()?,
+                        ),
+                    };
+
+                    row.push(size);
+                    row.push(format_last_used(version_usage.last_activated));
+                    row.push(format_last_used(version_usage.last_executed));
+                }
+
+                if with_status {
+                    row.push(version.status().unwrap_or_default());
+                }
+
+                table.add_row(Row::from(row));
+            }
         }
 
         table.load_preset(comfy_table::presets::NOTHING);
 
         println!("{table}");
+        Ok(())
+    }
+}
+
+/// Groups `fvm list` shows versions in, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ChannelGroup {
+    Stable,
+    Latest,
+    /// Minor version channels, e.g. `0.11`, aliases of their newest patch
+    /// release
+    Aliases,
+    /// Version and release tags, which never move
+    Tags,
+}
+
+impl ChannelGroup {
+    fn of(channel: &Channel) -> Self {
+        match channel {
+            Channel::Stable => Self::Stable,
+            Channel::Latest => Self::Latest,
+            Channel::Minor(_, _) => Self::Aliases,
+            Channel::Tag(_) | Channel::Other(_) => Self::Tags,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Stable => "Stable",
+            Self::Latest => "Latest",
+            Self::Aliases => "Aliases",
+            Self::Tags => "Tags",
+        }
+    }
+}
+
+/// An installed version as listed by `fvm list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListedVersion {
+    channel: Channel,
+    /// `None` when the manifest is unreadable
+    version: Option<Version>,
+    active: bool,
+    /// Why the install is broken, e.g. binaries missing from the version
+    /// directory
+    broken: Option<String>,
+    /// Newer version of the channel from the cached remote versions
+    update: Option<Version>,
+}
+
+impl ListedVersion {
+    fn marker(&self) -> &'static str {
+        match (self.active, &self.broken) {
+            (_, Some(_)) => "✗",
+            (true, None) => "✓",
+            (false, None) => " ",
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        if let Some(broken) = &self.broken {
+            return Some(format!("broken: {broken}"));
+        }
+
+        self.update
+            .as_ref()
+            .map(|update| format!("update available: {update}"))
+    }
+}
+
+/// Lists the version directories in `versions_path`, directories without a
+/// readable manifest or missing binaries are listed as broken
+fn scan_listed_versions(
+    versions_path: &Path,
+    active: Option<&Channel>,
+    remote: &RemoteVersions,
+) -> Result<Vec<ListedVersion>> {
+    let mut listed = Vec::new();
+
+    for entry in read_dir(versions_path)? {
+        let path = entry?.path();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        let listed_version = match VersionDirectory::open(path.clone()) {
+            Ok(version_dir) => {
+                let manifest = version_dir.manifest;
+                let missing: Vec<&str> = manifest
+                    .contents
+                    .iter()
+                    .flatten()
+                    .map(|artifact| artifact.name.as_str())
+                    .filter(|name| !path.join(name).exists())
+                    .collect();
+
+                ListedVersion {
+                    active: active == Some(&manifest.channel),
+                    broken: (!missing.is_empty())
+                        .then(|| format!("missing {}", missing.join(", "))),
+                    update: remote
+                        .update_for(&manifest.channel, &manifest.version)
+                        .cloned(),
+                    channel: manifest.channel,
+                    version: Some(manifest.version),
+                }
+            }
+            Err(err) => {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let channel = Channel::parse(&name).unwrap_or(Channel::Other(name));
+
+                tracing::debug!(%err, ?path, "Failed to open version directory");
+
+                ListedVersion {
+                    active: active == Some(&channel),
+                    channel,
+                    version: None,
+                    broken: Some(String::from("unreadable manifest")),
+                    update: None,
+                }
+            }
+        };
+
+        listed.push(listed_version);
+    }
+
+    Ok(listed)
+}
+
+/// Groups `listed` in display order, newest channels first in each group
+fn group_versions(mut listed: Vec<ListedVersion>) -> Vec<(ChannelGroup, Vec<ListedVersion>)> {
+    listed.sort_by(|a, b| b.channel.cmp(&a.channel));
+
+    let mut groups: Vec<(ChannelGroup, Vec<ListedVersion>)> = Vec::new();
+
+    for version in listed {
+        let group = ChannelGroup::of(&version.channel);
+
+        match groups.iter_mut().find(|(existing, _)| *existing == group) {
+            Some((_, versions)) => versions.push(version),
+            None => groups.push((group, vec![version])),
+        }
+    }
+
+    groups.sort_by_key(|(group, _)| *group);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use crate::common::manifest::{VersionManifest, VersionedArtifact};
+
+    use super::*;
+
+    fn install(versions_path: &Path, channel: Channel, version: &str, binaries: &[&str]) {
+        let path = versions_path.join(channel.to_string());
+        let contents = ["fluvio", "fluvio-run"]
+            .map(|name| VersionedArtifact::new(name, version))
+            .to_vec();
+
+        create_dir_all(&path).unwrap();
+
+        for binary in binaries {
+            write(path.join(binary), "").unwrap();
+        }
+
+        VersionManifest::new(channel, Version::parse(version).unwrap(), contents)
+            .write(&path)
+            .unwrap();
+    }
+
+    #[test]
+    fn groups_and_marks_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let versions_path = tmp.path();
+        let all = ["fluvio", "fluvio-run"];
+
+        install(
+            versions_path,
+            Channel::Tag(Version::new(0, 10, 14)),
+            "0.10.14",
+            &all,
+        );
+        install(versions_path, Channel::Minor(0, 11), "0.11.8", &all);
+        install(versions_path, Channel::Stable, "0.12.0", &all);
+        install(
+            versions_path,
+            Channel::Tag(Version::new(0, 11, 2)),
+            "0.11.2",
+            &["fluvio"],
+        );
+        create_dir_all(versions_path.join("0.9.0")).unwrap();
+
+        let mut remote = RemoteVersions::default();
+
+        remote
+            .channels
+            .insert("stable".to_string(), Version::new(0, 12, 1));
+        remote
+            .channels
+            .insert("0.11".to_string(), Version::new(0, 11, 8));
+
+        let listed = scan_listed_versions(versions_path, Some(&Channel::Stable), &remote).unwrap();
+        let rows: Vec<(ChannelGroup, String, &str, Option<String>)> = group_versions(listed)
+            .into_iter()
+            .flat_map(|(group, versions)| {
+                versions.into_iter().map(move |version| {
+                    let marker = version.marker();
+
+                    (group, version.channel.to_string(), marker, version.status())
+                })
+            })
+            .collect();
+        let update = |version: &str| Some(format!("update available: {version}"));
+        let broken = |reason: &str| Some(format!("broken: {reason}"));
+
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    ChannelGroup::Stable,
+                    "stable".to_string(),
+                    "✓",
+                    update("0.12.1")
+                ),
+                (ChannelGroup::Aliases, "0.11".to_string(), " ", None),
+                (
+                    ChannelGroup::Tags,
+                    "0.11.2".to_string(),
+                    "✗",
+                    broken("missing fluvio-run")
+                ),
+                (ChannelGroup::Tags, "0.10.14".to_string(), " ", None),
+                (
+                    ChannelGroup::Tags,
+                    "0.9.0".to_string(),
+                    "✗",
+                    broken("unreadable manifest")
+                ),
+            ]
+        );
     }
 }
@@ -1,8 +1,28 @@
+pub mod advisories;
+pub mod backfill_digests;
+pub mod cache;
+pub mod check_integrity;
+pub mod clean;
+pub mod clone_to;
+pub mod config;
 pub mod current;
+pub mod doctor;
+pub mod import;
+pub mod init;
 pub mod install;
 pub mod itself;
 pub mod list;
+pub mod plugin;
+pub mod prompt;
+pub mod provenance;
+pub mod prune;
+pub mod register_local;
+pub mod run;
+pub mod setup;
+pub mod support_bundle;
 pub mod switch;
 pub mod uninstall;
 pub mod update;
+pub mod verify;
 pub mod version;
+pub mod which_release;
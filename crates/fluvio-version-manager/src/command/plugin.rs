@@ -0,0 +1,109 @@
+//! Plugin Command
+//!
+//! The `plugin` command lists the external `fvm-<name>` subcommands available
+//! and installs new ones from the Hub.
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use comfy_table::{Table, Row};
+
+use crate::common::notify::Notify;
+use crate::common::plugin::{
+    DEFAULT_PLUGIN_REMOTE, FVM_HUB_REMOTE_ENV_VAR, PluginPackage, install_plugin,
+    is_installed_plugin, list_plugins,
+};
+
+#[derive(Debug, Parser)]
+pub enum PluginCommand {
+    /// List plugins found in the plugins directory and in `PATH`
+    List,
+    /// Install a plugin from the Hub into the plugins directory
+    Install(PluginInstallOpt),
+}
+
+#[derive(Debug, Parser)]
+pub struct PluginInstallOpt {
+    /// Plugin to install, e.g. `infinyon/fvm-doctor@0.1.0`. Defaults to the
+    /// latest version
+    plugin: PluginPackage,
+    /// Hub to download the plugin from
+    #[arg(long, env = FVM_HUB_REMOTE_ENV_VAR, default_value = DEFAULT_PLUGIN_REMOTE)]
+    remote: String,
+}
+
+/// The `plugin` command manages external `fvm` subcommands
+#[derive(Debug, Parser)]
+pub struct PluginOpt {
+    /// Subcommand to execute
+    #[clap(subcommand)]
+    command: PluginCommand,
+}
+
+impl PluginOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        match &self.command {
+            PluginCommand::List => Self::list(notify),
+            PluginCommand::Install(cmd) => cmd.process(notify).await,
+        }
+    }
+
+    fn list(notify: Notify) -> Result<()> {
+        let plugins = list_plugins()?;
+
+        if plugins.is_empty() {
+            notify.warn("No plugins found");
+            notify.help(format!(
+                "You can install a plugin using the command {}",
+                "fvm plugin install <group>/<name>".bold()
+            ));
+
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+
+        table.set_header(Row::from(["COMMAND", "SOURCE", "PATH"]));
+
+        for plugin in plugins {
+            let source = if is_installed_plugin(&plugin.path) {
+                "installed"
+            } else {
+                "PATH"
+            };
+
+            table.add_row(Row::from([
+                plugin.name,
+                source.to_string(),
+                plugin.path.display().to_string(),
+            ]));
+        }
+
+        table.load_preset(comfy_table::presets::NOTHING);
+
+        println!("{table}");
+        Ok(())
+    }
+}
+
+impl PluginInstallOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        notify.info(format!(
+            "Downloading plugin {}/{}@{}",
+            self.plugin.group,
+            self.plugin.package_name(),
+            self.plugin.version
+        ));
+
+        let path = install_plugin(&self.plugin, &self.remote).await?;
+
+        notify.done(format!(
+            "Installed plugin {} at {}, run it with {}",
+            self.plugin.name.bold(),
+            path.display(),
+            format!("fvm {}", self.plugin.name).bold()
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//! Prompt Command
+//!
+//! The `prompt` command is run by the shell hook installed with
+//! `fvm setup --prompt` before each prompt. It prints the active Fluvio
+//! version for the prompt, and with `--check` warns when the active version
+//! does not satisfy the version pinned by the project of the working
+//! directory. It reads local state only, so it stays fast enough to run on
+//! every prompt.
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use semver::Version;
+
+use crate::common::notify::Notify;
+use crate::common::project_version::ProjectVersion;
+use crate::common::settings::Settings;
+
+#[derive(Debug, Parser)]
+pub struct PromptOpt {
+    /// Warn when the active version does not satisfy the version pinned by
+    /// the project's `.fvm-version` file, instead of printing the version
+    #[arg(long)]
+    check: bool,
+}
+
+impl PromptOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let Some(settings) = Settings::read_existing()? else {
+            return Ok(());
+        };
+        let Some(channel) = settings.channel else {
+            return Ok(());
+        };
+        let version = settings
+            .version
+            .as_deref()
+            .and_then(|version| Version::parse(version).ok());
+        let pinned = ProjectVersion::find(&std::env::current_dir()?)?
+            .filter(|pinned| !pinned.is_satisfied_by(&channel, version.as_ref()));
+        let active = match &version {
+            Some(version) if !channel.is_version_tag() => format!("{version} ({channel})"),
+            _ => channel.to_string(),
+        };
+
+        if !self.check {
+            match pinned {
+                Some(pinned) => println!("fluvio {active} != {}", pinned.channel),
+                None => println!("fluvio {active}"),
+            }
+
+            return Ok(());
+        }
+
+        if let Some(pinned) = pinned {
+            notify.warn(format!(
+                "This project pins Fluvio {} in {}, but the active version is {active}",
+                pinned.channel,
+                pinned.path.display()
+            ));
+            notify.help(format!(
+                "Use {} to switch to it",
+                format!("fvm switch {}", pinned.channel).bold()
+            ));
+        }
+
+        Ok(())
+    }
+}
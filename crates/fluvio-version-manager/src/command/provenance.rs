@@ -0,0 +1,72 @@
+//! Provenance Command
+//!
+//! The `provenance` command prints how an installed Fluvio Version was
+//! resolved, as recorded in its manifest when it was installed.
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::Channel;
+
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+use crate::common::version_directory::VersionDirectory;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct ProvenanceOpt {
+    /// Version to print the provenance of, defaults to the active version
+    #[arg(index = 1)]
+    version: Option<Channel>,
+    /// Print the provenance as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+impl ProvenanceOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let channel = match &self.version {
+            Some(channel) => channel.to_owned(),
+            None => match Settings::open()?.channel {
+                Some(channel) => channel,
+                None => {
+                    notify.help(format!(
+                        "You can use {} to see installed versions",
+                        "fvm list".bold()
+                    ));
+
+                    bail!("No version provided and no active version set");
+                }
+            },
+        };
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {channel} is not installed"),
+            )
+            .into());
+        }
+
+        let manifest = VersionDirectory::open(version_path)?.manifest;
+        let Some(provenance) = manifest.provenance else {
+            notify.help(format!(
+                "Reinstall the version with {} to record its provenance",
+                format!("fvm install {channel}").bold()
+            ));
+
+            bail!("No provenance recorded for Fluvio version {channel}");
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&provenance)?);
+        } else {
+            println!("{provenance}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Prune Command
+//!
+//! Uninstalls Fluvio Versions which were not activated nor executed for a
+//! period of time. The active version is never pruned, nor versions with
+//! binaries running through a version shim.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use humantime::parse_duration;
+
+use crate::common::lease::live_executions;
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+use crate::common::usage::{UsageTracker, format_size, installed_at, is_unused};
+use crate::common::version_directory::VersionDirectory;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct PruneOpt {
+    /// Remove versions not activated nor executed for this long (e.g. 90d)
+    #[arg(long, value_parser = parse_duration)]
+    unused_for: Duration,
+    /// List the versions that would be removed without removing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl PruneOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let versions_path = fvm_versions_path()?;
+        let Some(mut usage) = UsageTracker::open()? else {
+            notify.warn("FVM is not installed, nothing to prune");
+            return Ok(());
+        };
+
+        if !versions_path.exists() {
+            notify.done("No versions installed, nothing to prune");
+            return Ok(());
+        }
+
+        let active = Settings::open()?.channel.map(|channel| channel.to_string());
+        let mut pruned = 0;
+        let mut in_use = 0;
+        let mut reclaimed = 0;
+
+        for entry in versions_path.read_dir()? {
+            let path = entry?.path();
+
+            if !path.is_dir() {
+                continue;
+            }
+
+            let version_dir = VersionDirectory::open(path.clone())?;
+            let key = version_dir.manifest.channel.to_string();
+
+            if active.as_ref() == Some(&key)
+                || !is_unused(&usage.get(&key), installed_at(&path), self.unused_for)
+            {
+                continue;
+            }
+
+            let pids = live_executions(&key)?;
+
+            if !pids.is_empty() {
+                let pids: Vec<String> = pids.iter().map(|pid| pid.to_string()).collect();
+
+                notify.warn(format!(
+                    "Skipping {}, in use by running processes (PID {})",
+                    key.bold(),
+                    pids.join(", ")
+                ));
+                in_use += 1;
+                continue;
+            }
+
+            let size = version_dir.disk_usage()?;
+
+            if self.dry_run {
+                notify.info(format!(
+                    "Would remove {} ({})",
+                    key.bold(),
+                    format_size(size)
+                ));
+            } else {
+                version_dir.remove()?;
+                usage.forget(&key)?;
+                notify.info(format!("Removed {} ({})", key.bold(), format_size(size)));
+            }
+
+            pruned += 1;
+            reclaimed += size;
+        }
+
+        if in_use > 0 {
+            notify.help(format!(
+                "{in_use} versions are in use, run {} again once their processes exit",
+                "fvm prune".bold()
+            ));
+        }
+
+        if pruned == 0 {
+            notify.done("Nothing to prune");
+        } else if self.dry_run {
+            notify.help(format!(
+                "Run without {} to remove {pruned} versions and reclaim {}",
+                "--dry-run".bold(),
+                format_size(reclaimed)
+            ));
+        } else {
+            notify.done(format!(
+                "Removed {pruned} versions, reclaimed {}",
+                format_size(reclaimed)
+            ));
+        }
+
+        Ok(())
+    }
+}
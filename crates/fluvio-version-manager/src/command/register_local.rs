@@ -0,0 +1,88 @@
+//! Register Local Command
+//!
+//! The `register-local` command registers binaries built from a Fluvio
+//! checkout as a version, e.g. `fvm register-local target/release --version
+//! 0.12.0-local`, so the local build is switched to like any installed
+//! version.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::FVM_INSTALLABLE_BINARIES;
+use fvm_core::Switcher;
+
+use crate::common::local_build::LocalBuild;
+use crate::common::notify::Notify;
+use crate::common::version_policy::enforce_version_policy;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct RegisterLocalOpt {
+    /// Directory holding the built binaries, e.g. `target/release`
+    #[arg(index = 1)]
+    dir: PathBuf,
+    /// Version to register the build as, e.g. `0.12.0-local`
+    #[arg(long)]
+    version: Version,
+    /// Binaries to register, defaults to the binaries installed by FVM
+    #[arg(long = "binary", value_name = "NAME")]
+    binaries: Vec<String>,
+    /// Replace the version if it is already installed
+    #[arg(long)]
+    force: bool,
+    /// Set the registered version as active
+    #[arg(long)]
+    switch: bool,
+}
+
+impl RegisterLocalOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let names: Vec<&str> = if self.binaries.is_empty() {
+            FVM_INSTALLABLE_BINARIES.to_vec()
+        } else {
+            self.binaries.iter().map(String::as_str).collect()
+        };
+
+        enforce_version_policy(&self.version, notify).await?;
+
+        let (checksums, version_dir) = LocalBuild::register(
+            &self.dir,
+            &self.version,
+            &names,
+            &fvm_versions_path()?,
+            self.force,
+        )?;
+
+        for (name, digest) in &checksums.digests {
+            notify.info(format!("{name}: {digest}"));
+        }
+
+        notify.done(format!(
+            "Registered local build in {} as fluvio version {}",
+            self.dir.display(),
+            version_dir.manifest.version
+        ));
+
+        if self.switch {
+            Switcher::new()
+                .with_notify(notify)
+                .switch(&version_dir.manifest.channel)
+                .await?;
+            notify.done(format!(
+                "Now using fluvio version {}",
+                version_dir.manifest.version
+            ));
+        } else {
+            notify.help(format!(
+                "You can set it as active using {}",
+                format!("fvm switch {}", version_dir.manifest.channel).bold()
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! Run Command
+//!
+//! Runs a command with a Fluvio Version on `PATH` without installing or
+//! switching to it, e.g. for CI jobs which need a one-off version. Versions
+//! which are not installed are downloaded to the toolchain cache, see
+//! [`ToolchainCache`].
+
+use std::env::{join_paths, split_paths, var_os};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::Channel;
+
+use crate::common::TARGET;
+use crate::common::github::fvm_client;
+use crate::common::lease::ExecutionLease;
+use crate::common::notify::Notify;
+use crate::common::shim::resolve_version;
+use crate::common::toolchain_cache::{DEFAULT_TOOLCHAIN_CACHE_MIB, ToolchainCache};
+use crate::common::version_directory::VersionDirectory;
+use crate::common::version_policy::enforce_version_policy;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct RunOpt {
+    /// Version to run with: stable, latest (or dev), minor version x.y, or named-version x.y.z
+    #[arg(index = 1)]
+    version: Channel,
+    /// Remove the downloaded version from the toolchain cache once the
+    /// command exits
+    #[arg(long)]
+    evict: bool,
+    /// Size limit of the toolchain cache, least recently used versions are
+    /// evicted beyond it
+    #[arg(long, value_name = "MiB", default_value_t = DEFAULT_TOOLCHAIN_CACHE_MIB)]
+    cache_size: u64,
+    /// Command to run, e.g. `fvm run 0.11.8 -- fluvio version`
+    #[arg(last = true, required = true)]
+    command: Vec<OsString>,
+}
+
+/// Where the binaries of the version come from
+enum Toolchain {
+    Installed {
+        path: PathBuf,
+        _lease: Option<ExecutionLease>,
+    },
+    Cached {
+        path: PathBuf,
+        version: Version,
+        lease: Option<ExecutionLease>,
+    },
+}
+
+impl RunOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let max_cache_size = self.cache_size.checked_mul(1024 * 1024).ok_or_else(|| {
+            anyhow!(
+                "Toolchain cache size of {} MiB is too large",
+                self.cache_size
+            )
+        })?;
+        let cache = ToolchainCache::open()?;
+        let toolchain = self.resolve(&cache, notify).await?;
+        let path = match &toolchain {
+            Toolchain::Installed { path, .. } | Toolchain::Cached { path, .. } => path,
+        };
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| anyhow!("No command to run"))?;
+
+        let status = Command::new(program)
+            .args(args)
+            .env("PATH", path_with(path)?)
+            .status()
+            .map_err(|err| anyhow!("Failed to run {}: {err}", program.to_string_lossy()))?;
+
+        // Releases the lease before exiting, exit doesn't run destructors
+        let keep = match toolchain {
+            Toolchain::Installed { .. } => None,
+            Toolchain::Cached { version, lease, .. } => {
+                drop(lease);
+
+                if self.evict {
+                    cache.evict(&version)?;
+                    None
+                } else {
+                    Some(version)
+                }
+            }
+        };
+
+        for version in cache.enforce_limit(max_cache_size, keep.as_ref())? {
+            tracing::debug!(%version, "Evicted toolchain from cache");
+        }
+
+        std::process::exit(status.code().unwrap_or(1))
+    }
+
+    /// Uses the installed version, then the cached toolchain, downloading the
+    /// toolchain as last resort. Versions not allowed by the version policy
+    /// are rejected before they run or download.
+    async fn resolve(&self, cache: &ToolchainCache, notify: Notify) -> Result<Toolchain> {
+        if let Some(version_dir) = self.installed()? {
+            enforce_version_policy(&version_dir.manifest.version, notify).await?;
+
+            let channel = version_dir.manifest.channel.to_string();
+            // Held until the command exits, so the version is not pruned
+            let lease = ExecutionLease::acquire(&channel)
+                .inspect_err(|err| tracing::warn!(%err, "Failed to acquire execution lease"))
+                .ok();
+
+            return Ok(Toolchain::Installed {
+                path: version_dir.path,
+                _lease: lease,
+            });
+        }
+
+        // Tags are looked up without resolving the release
+        if let Channel::Tag(version) = &self.version {
+            let lease = lease_toolchain(cache, version);
+
+            if let Some(path) = cache.get(version)? {
+                enforce_version_policy(version, notify).await?;
+
+                return Ok(Toolchain::Cached {
+                    path,
+                    version: version.clone(),
+                    lease,
+                });
+            }
+        }
+
+        let package_set = fvm_client()?
+            .fetch_default_package_set(&self.version, TARGET)
+            .await?;
+        let version = package_set.pkgset.clone();
+
+        enforce_version_policy(&version, notify).await?;
+
+        // Leased before the lookup so other runs don't evict it meanwhile
+        let lease = lease_toolchain(cache, &version);
+        let path = match cache.get(&version)? {
+            Some(path) => path,
+            None => cache.fetch(&package_set, notify).await?,
+        };
+
+        Ok(Toolchain::Cached {
+            path,
+            version,
+            lease,
+        })
+    }
+
+    fn installed(&self) -> Result<Option<VersionDirectory>> {
+        if let Channel::Tag(version) = &self.version {
+            return resolve_version(version);
+        }
+
+        let path = fvm_versions_path()?.join(self.version.to_string());
+
+        if !path.is_dir() {
+            return Ok(None);
+        }
+
+        VersionDirectory::open(path).map(Some)
+    }
+}
+
+/// Leases the cached toolchain for `version` until the command exits, so
+/// other runs don't evict it
+fn lease_toolchain(cache: &ToolchainCache, version: &Version) -> Option<ExecutionLease> {
+    cache
+        .lease(version)
+        .inspect_err(|err| tracing::warn!(%err, "Failed to acquire execution lease"))
+        .ok()
+}
+
+/// `PATH` with `dir` taking precedence
+fn path_with(dir: &Path) -> Result<OsString> {
+    let mut paths = vec![dir.to_owned()];
+
+    if let Some(path) = var_os("PATH") {
+        paths.extend(split_paths(&path));
+    }
+
+    Ok(join_paths(paths)?)
+}
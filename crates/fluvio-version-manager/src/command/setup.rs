@@ -0,0 +1,121 @@
+//! Setup Command
+//!
+//! Adds the FVM and Fluvio binaries directories to `PATH` by editing the
+//! user's shell profile files, and optionally sources a prompt hook showing
+//! the active Fluvio version.
+
+use std::fs::{remove_file, write};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+
+use crate::common::home_dir;
+use crate::common::notify::Notify;
+use crate::common::shell_profile::{Shell, erase_block, write_block};
+use crate::common::workdir::{fluvio_binaries_path, fvm_bin_path, fvm_workdir_path};
+
+#[derive(Debug, Default, Parser)]
+pub struct SetupOpt {
+    /// Shell to configure: bash, zsh, fish or sh. Detected from `SHELL` if
+    /// not provided
+    #[arg(long)]
+    shell: Option<Shell>,
+    /// Remove the changes made to shell profile files by `fvm setup`
+    #[arg(long)]
+    remove: bool,
+    /// Skip checking that a new login shell resolves the Fluvio binary
+    #[arg(long)]
+    no_verify: bool,
+    /// Show the active Fluvio version in the prompt, and warn when it differs
+    /// from the version pinned by the project's `.fvm-version` file
+    #[arg(long, conflicts_with = "remove")]
+    prompt: bool,
+}
+
+impl SetupOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let shell = self.shell.unwrap_or_else(Shell::detect);
+        let profiles = shell.profile_files(&home_dir()?);
+        let hook_path = fvm_workdir_path()?.join(shell.prompt_hook_filename());
+
+        if self.remove {
+            for profile in profiles.iter() {
+                if erase_block(profile)? {
+                    notify.done(format!("Removed FVM from {}", profile.display()));
+                }
+            }
+
+            if hook_path.exists() {
+                remove_file(&hook_path)?;
+            }
+
+            return Ok(());
+        }
+
+        let hook = if self.prompt {
+            write(&hook_path, shell.prompt_hook()?)?;
+            notify.done(format!("Wrote prompt hook to {}", hook_path.display()));
+            Some(hook_path.as_path())
+        } else {
+            None
+        };
+
+        let fvm_bin_dir = fvm_bin_path()?
+            .parent()
+            .map(|path| path.to_path_buf())
+            .unwrap_or_default();
+        let block = shell.profile_block(&[fvm_bin_dir, fluvio_binaries_path()?], hook);
+
+        for profile in profiles.iter() {
+            if write_block(profile, &block)? {
+                notify.done(format!("Updated {}", profile.display()));
+            } else {
+                notify.info(format!("{} is already up to date", profile.display()));
+            }
+        }
+
+        if !self.no_verify {
+            self.verify(shell, notify)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a new login shell resolves `fluvio` to the FVM managed
+    /// binary
+    fn verify(&self, shell: Shell, notify: Notify) -> Result<()> {
+        let expected = fluvio_binaries_path()?.join("fluvio");
+
+        match shell.resolve_in_login_shell("fluvio") {
+            Ok(Some(path)) if path == expected => {
+                notify.done(format!("New {shell} sessions will use {}", path.display()));
+            }
+            Ok(Some(path)) => {
+                notify.warn(format!(
+                    "New {shell} sessions resolve fluvio to {} instead of {}",
+                    path.display(),
+                    expected.display()
+                ));
+                notify.help("Remove other Fluvio installations from PATH");
+            }
+            Ok(None) => {
+                notify.warn(format!("New {shell} sessions cannot find fluvio yet"));
+                notify.help(format!(
+                    "Install a Fluvio version using {}",
+                    "fvm install".bold()
+                ));
+            }
+            Err(err) => {
+                tracing::debug!(%err, "Failed to spawn login shell");
+                notify.warn(format!(
+                    "Could not start a {shell} login shell to verify PATH"
+                ));
+            }
+        }
+
+        notify.help("Restart your shell for changes to take effect");
+
+        Ok(())
+    }
+}
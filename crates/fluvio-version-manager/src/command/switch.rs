@@ -6,10 +6,12 @@ use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
 
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
 use fluvio_artifacts_util::fvm::Channel;
+use fvm_core::Switcher;
 
+use crate::common::cluster_compatibility::check_cluster_compatibility;
 use crate::common::notify::Notify;
-use crate::common::version_directory::VersionDirectory;
 use crate::common::workdir::fvm_versions_path;
 
 #[derive(Debug, Parser)]
@@ -17,6 +19,13 @@ pub struct SwitchOpt {
     /// Version to set as active
     #[arg(index = 1)]
     version: Option<Channel>,
+    /// Switch to the version even if it reached end-of-life
+    #[arg(long)]
+    accept_eol: bool,
+    /// Warn if the version is not compatible with the platform version of
+    /// the cluster in the active profile
+    #[arg(long)]
+    check_cluster: bool,
 }
 
 impl SwitchOpt {
@@ -50,11 +59,6 @@ impl SwitchOpt {
         let pkgset_path = versions_path.join(version.to_string());
 
         if !pkgset_path.exists() {
-            notify.warn(format!(
-                "Fluvio version {} is not installed",
-                version.to_string().bold()
-            ));
-
             let help = format!("fvm install {version}");
 
             notify.help(format!(
@@ -62,12 +66,18 @@ impl SwitchOpt {
                 help.bold()
             ));
 
-            return Ok(());
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {version} is not installed"),
+            )
+            .into());
         }
 
-        let version_dir = VersionDirectory::open(pkgset_path)?;
-
-        version_dir.set_active()?;
+        let installed = Switcher::new()
+            .with_accept_eol(self.accept_eol)
+            .with_notify(notify)
+            .switch(version)
+            .await?;
 
         if version.is_version_tag() {
             notify.done(format!(
@@ -78,10 +88,14 @@ impl SwitchOpt {
             notify.done(format!(
                 "Now using Fluvio {} ({})",
                 version.to_string().bold(),
-                version_dir.manifest.version.to_string().bold(),
+                installed.manifest.version.to_string().bold(),
             ));
         }
 
+        if self.check_cluster {
+            check_cluster_compatibility(&installed.manifest.version, notify).await;
+        }
+
         Ok(())
     }
 }
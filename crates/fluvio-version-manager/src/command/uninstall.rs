@@ -8,17 +8,15 @@ use clap::Parser;
 
 use colored::Colorize;
 use fluvio_artifacts_util::fvm::Channel;
+use fvm_core::Installer;
 
 use crate::common::notify::Notify;
-
-use crate::common::version_directory::VersionDirectory;
-
 use crate::common::workdir::fvm_versions_path;
 
 /// The `install` command is responsible of installing the desired Package Set
 #[derive(Debug, Parser)]
 pub struct UninstallOpt {
-    /// Version to install: stable, latest, or named-version x.y.z
+    /// Version to uninstall: stable, latest (or dev), minor version x.y, or named-version x.y.z
     #[arg(index = 1, default_value_t = Channel::Stable)]
     version: Channel,
 }
@@ -32,20 +30,13 @@ impl UninstallOpt {
             return Ok(());
         }
 
-        let pkgset_path = versions_path.join(self.version.to_string());
-
-        if !pkgset_path.exists() {
+        if !Installer::new().uninstall(&self.version)? {
             notify.warn(format!(
                 "Fluvio version {} is not installed",
                 self.version.to_string().bold()
             ));
-
-            return Ok(());
         }
 
-        let version_directory = VersionDirectory::open(pkgset_path)?;
-        version_directory.remove()?;
-
         Ok(())
     }
 }
@@ -1,23 +1,42 @@
-//! Updates version of the current channel to the most recent one
+//! Updates version of the current channel, or of every installed channel,
+//! to the most recent one
 
-use anyhow::{Result, Error};
+use std::fmt::Display;
+
+use anyhow::{Result, Error, anyhow};
 use clap::Args;
 use colored::Colorize;
+use comfy_table::{Row, Table};
+use futures_util::future::join_all;
 
-use fluvio_artifacts_util::fvm::{Client, Channel, PackageSet};
+use fluvio_artifacts_util::fvm::{Channel, PackageSet};
+use fvm_core::{InstalledVersion, list_installed};
+use semver::Version;
 
+use crate::common::advisories::{load_advisories, report_advisories};
+use crate::common::github::fvm_client;
 use crate::common::version_directory::VersionDirectory;
 use crate::common::workdir::fvm_versions_path;
 use crate::common::TARGET;
 use crate::common::notify::Notify;
+use crate::common::remote_versions::record_remote_version;
 use crate::common::settings::Settings;
 use crate::common::version_installer::VersionInstaller;
+use crate::common::version_policy::enforce_package_set_policy;
 
 #[derive(Debug, Args)]
-pub struct UpdateOpt;
+pub struct UpdateOpt {
+    /// Update every installed channel instead of the active one
+    #[arg(long)]
+    all: bool,
+}
 
 impl UpdateOpt {
-    pub async fn process(self, notify: Notify) -> Result<()> {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        if self.all {
+            return update_all(notify).await;
+        }
+
         let settings = Settings::open()?;
         let Some(channel) = settings.channel else {
             notify.info("No channel set, please set a channel first using `fvm switch`");
@@ -38,69 +57,15 @@ impl UpdateOpt {
             );
             return Ok(());
         };
-        let ch_version = Channel::parse(&version)?; // convert to comparable Channel
-        let ps_version = Channel::parse(latest_pkgset.pkgset.to_string())?;
-
-        match channel {
-            Channel::Stable => {
-                if ps_version > ch_version {
-                    notify.info(format!(
-                        "Updating fluvio {} to version {}. Current version is {}.",
-                        channel.to_string().bold(),
-                        latest_pkgset.pkgset,
-                        version
-                    ));
-
-                    return VersionInstaller::new(channel, latest_pkgset, notify)
-                        .install()
-                        .await;
-                }
-
-                if ps_version == ch_version {
-                    // Check for patches
-                    let curr_version_path = fvm_versions_path()?.join(channel.to_string());
-                    let curr_version_dir = VersionDirectory::open(curr_version_path)?;
-                    let curr_version_pkgset = curr_version_dir.as_package_set()?;
-                    let upstream_artifacts = curr_version_pkgset.artifacts_diff(&latest_pkgset);
-
-                    if !upstream_artifacts.is_empty() {
-                        notify.info(format!(
-                            "Found {} packages in this version that needs update.",
-                            upstream_artifacts.len(),
-                        ));
-
-                        return VersionInstaller::new(channel, latest_pkgset, notify)
-                            .update(&upstream_artifacts)
-                            .await;
-                    }
-                }
-
-                notify.done("You are already up to date");
-            }
-            Channel::Latest => {
-                // The latest tag can be very dynamic, so we just check for this
-                // tag to be different than the current version assuming
-                // upstream is always up to date
-                if ps_version != ch_version {
-                    notify.info(format!(
-                        "Updating fluvio {} to version {}. Current version is {}.",
-                        channel.to_string().bold(),
-                        latest_pkgset.pkgset,
-                        version
-                    ));
-
-                    return VersionInstaller::new(channel, latest_pkgset, notify)
-                        .install()
-                        .await;
-                }
 
-                notify.done("You are already up to date");
-            }
-            Channel::Tag(_) | Channel::Other(_) => {
-                notify.warn("Static tags cannot be updated. No changes made.");
-            }
+        match update_channel(channel, &version, latest_pkgset, notify, true).await? {
+            ChannelUpdate::Updated => {}
+            ChannelUpdate::UpToDate => notify.done("You are already up to date"),
+            ChannelUpdate::Static => notify.warn("Static tags cannot be updated. No changes made."),
         }
 
+        check_advisories(notify).await;
+
         Ok(())
     }
 
@@ -111,9 +76,318 @@ impl UpdateOpt {
             ));
         }
 
-        let client = Client;
+        let client = fvm_client()?;
         let pkgset = client.fetch_default_package_set(channel, TARGET).await?;
 
+        record_remote_version(channel, &pkgset.pkgset);
+
         Ok(pkgset)
     }
 }
+
+/// Result of updating a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChannelUpdate {
+    Updated,
+    UpToDate,
+    /// Static version tags are never updated
+    Static,
+}
+
+impl Display for ChannelUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Updated => write!(f, "updated"),
+            Self::UpToDate => write!(f, "up to date"),
+            Self::Static => write!(f, "static tag"),
+        }
+    }
+}
+
+/// Updates every installed channel, resolving the upstream versions
+/// concurrently, and prints a summary of the versions per channel
+async fn update_all(notify: Notify) -> Result<()> {
+    let installed: Vec<InstalledVersion> = list_installed()?
+        .into_iter()
+        .filter(|version| !version.manifest.channel.is_version_tag())
+        .collect();
+
+    if installed.is_empty() {
+        notify.info("No installed channels to update, static version tags are never updated");
+        return Ok(());
+    }
+
+    let client = fvm_client()?;
+    let upstreams = join_all(
+        installed
+            .iter()
+            .map(|version| client.fetch_default_package_set(&version.manifest.channel, TARGET)),
+    )
+    .await;
+    let mut table = Table::new();
+    let mut failures = 0;
+
+    table.set_header(Row::from(["CHANNEL", "FROM", "TO", "STATUS"]));
+
+    for (version, upstream) in installed.into_iter().zip(upstreams) {
+        let channel = version.manifest.channel.clone();
+        let current = version.manifest.version.to_string();
+        let (upstream_version, status) = match upstream {
+            Ok(upstream) => {
+                let upstream_version = upstream.pkgset.to_string();
+
+                record_remote_version(&channel, &upstream.pkgset);
+
+                match update_channel(channel.clone(), &current, upstream, notify, version.active)
+                    .await
+                {
+                    Ok(update) => (upstream_version, update.to_string().normal()),
+                    Err(err) => {
+                        failures += 1;
+                        (upstream_version, format!("failed: {err}").red())
+                    }
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                (String::from("-"), format!("failed: {err}").red())
+            }
+        };
+
+        table.add_row(Row::from([
+            channel.to_string(),
+            current,
+            upstream_version,
+            status.to_string(),
+        ]));
+    }
+
+    table.load_preset(comfy_table::presets::NOTHING);
+    println!("{table}");
+
+    check_advisories(notify).await;
+
+    if failures > 0 {
+        return Err(anyhow!("{failures} channels failed to update"));
+    }
+
+    notify.done("All installed channels are up to date");
+
+    Ok(())
+}
+
+/// Warns about the advisories applying to the installed versions once they
+/// are updated. Advisories are best-effort and never fail the update.
+async fn check_advisories(notify: Notify) {
+    let Some(feed) = load_advisories(false).await else {
+        return;
+    };
+
+    match list_installed() {
+        Ok(installed) => report_advisories(&feed, &installed, notify),
+        Err(err) => tracing::debug!(%err, "Failed to list installed versions for advisories"),
+    }
+}
+
+/// Updates the installed `channel` at `version` to `upstream`, setting it as
+/// the active version when `activate` is set
+async fn update_channel(
+    channel: Channel,
+    version: &str,
+    upstream: PackageSet,
+    notify: Notify,
+    activate: bool,
+) -> Result<ChannelUpdate> {
+    let ch_version = Channel::parse(version)?; // convert to comparable Channel
+    let upstream = match Settings::configured_install_profile()? {
+        Some(profile) => profile.select(upstream)?,
+        None => upstream,
+    };
+    let upstream = compose_installed_components(&channel, upstream).await?;
+    let ps_version = Channel::parse(upstream.pkgset.to_string())?;
+
+    match channel {
+        Channel::Stable | Channel::Minor(_, _) => {
+            if ps_version > ch_version {
+                notify.info(format!(
+                    "Updating fluvio {} to version {}. Current version is {}.",
+                    channel.to_string().bold(),
+                    upstream.pkgset,
+                    version
+                ));
+
+                if is_patch_release(version, &upstream.pkgset) {
+                    return update_incremental(channel, upstream, notify, activate).await;
+                }
+
+                installer(channel, upstream, notify, activate)
+                    .await?
+                    .install()
+                    .await?;
+
+                return Ok(ChannelUpdate::Updated);
+            }
+
+            if ps_version == ch_version {
+                // Check for patches
+                return update_incremental(channel, upstream, notify, activate).await;
+            }
+
+            Ok(ChannelUpdate::UpToDate)
+        }
+        Channel::Latest => {
+            // The latest tag can be very dynamic, so we just check for this
+            // tag to be different than the current version assuming
+            // upstream is always up to date
+            if ps_version != ch_version {
+                notify.info(format!(
+                    "Updating fluvio {} to version {}. Current version is {}.",
+                    channel.to_string().bold(),
+                    upstream.pkgset,
+                    version
+                ));
+
+                installer(channel, upstream, notify, activate)
+                    .await?
+                    .install()
+                    .await?;
+
+                return Ok(ChannelUpdate::Updated);
+            }
+
+            Ok(ChannelUpdate::UpToDate)
+        }
+        Channel::Tag(_) | Channel::Other(_) => Ok(ChannelUpdate::Static),
+    }
+}
+
+/// Composes the components the installed `channel` took from other
+/// channels into `upstream`, so they keep following their own channels
+async fn compose_installed_components(
+    channel: &Channel,
+    upstream: PackageSet,
+) -> Result<PackageSet> {
+    let version_path = fvm_versions_path()?.join(channel.to_string());
+
+    if !version_path.exists() {
+        return Ok(upstream);
+    }
+
+    let components = VersionDirectory::open(version_path)?.manifest.components();
+
+    if components.is_empty() {
+        return Ok(upstream);
+    }
+
+    fvm_client()?
+        .compose_package_set(upstream, &components, &[])
+        .await
+}
+
+/// Installer for `upstream` with the components of the configured install
+/// profile, failing if the version policy does not allow `upstream`
+async fn installer(
+    channel: Channel,
+    upstream: PackageSet,
+    notify: Notify,
+    activate: bool,
+) -> Result<VersionInstaller> {
+    enforce_package_set_policy(&upstream, notify).await?;
+
+    VersionInstaller::new(channel, upstream, notify)
+        .with_activation(activate)
+        .with_profile(Settings::configured_install_profile()?)
+}
+
+/// Whether `upstream` is a patch release on top of the installed `version`
+fn is_patch_release(version: &str, upstream: &Version) -> bool {
+    Version::parse(version)
+        .is_ok_and(|version| version.major == upstream.major && version.minor == upstream.minor)
+}
+
+/// Updates the installed `channel` downloading only the artifacts which
+/// differ from `upstream`. Falls back to a full install when the installed
+/// artifacts are unknown.
+async fn update_incremental(
+    channel: Channel,
+    upstream: PackageSet,
+    notify: Notify,
+    activate: bool,
+) -> Result<ChannelUpdate> {
+    let curr_version_path = fvm_versions_path()?.join(channel.to_string());
+    let curr_version_dir = VersionDirectory::open(curr_version_path)?;
+    let Ok(curr_version_pkgset) = curr_version_dir.as_package_set() else {
+        installer(channel, upstream, notify, activate)
+            .await?
+            .install()
+            .await?;
+
+        return Ok(ChannelUpdate::Updated);
+    };
+    let diff = upstream.diff(&curr_version_pkgset);
+
+    if diff.is_empty() {
+        return Ok(ChannelUpdate::UpToDate);
+    }
+
+    notify.info(format!(
+        "Found {} packages in this version that needs update.",
+        diff.added.len() + diff.changed.len() + diff.removed.len(),
+    ));
+
+    installer(channel, upstream, notify, activate)
+        .await?
+        .update(&diff)
+        .await?;
+
+    Ok(ChannelUpdate::Updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_patch_releases() {
+        assert!(is_patch_release("0.11.8", &Version::new(0, 11, 9)));
+        assert!(!is_patch_release("0.11.8", &Version::new(0, 12, 0)));
+        assert!(!is_patch_release("0.11.8", &Version::new(1, 11, 9)));
+        assert!(!is_patch_release("not-a-version", &Version::new(0, 11, 9)));
+    }
+
+    #[fluvio_future::test]
+    async fn skips_channels_without_newer_versions() {
+        let upstream = PackageSet {
+            pkgset: Version::new(0, 11, 8),
+            arch: TARGET.to_string(),
+            release: None,
+            artifacts: vec![],
+        };
+        let notify = Notify::new(true);
+
+        assert_eq!(
+            update_channel(Channel::Stable, "0.11.9", upstream.clone(), notify, false)
+                .await
+                .unwrap(),
+            ChannelUpdate::UpToDate
+        );
+        assert_eq!(
+            update_channel(Channel::Latest, "0.11.8", upstream.clone(), notify, false)
+                .await
+                .unwrap(),
+            ChannelUpdate::UpToDate
+        );
+        assert_eq!(
+            update_channel(
+                Channel::parse("0.11.0").unwrap(),
+                "0.11.0",
+                upstream,
+                notify,
+                false
+            )
+            .await
+            .unwrap(),
+            ChannelUpdate::Static
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Verify Command
+//!
+//! The `verify` command hashes the binaries of an installed Fluvio Version
+//! and compares them with the digests recorded when it was installed.
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use colored::Colorize;
+use comfy_table::{Table, Row};
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::Channel;
+
+use crate::common::checksum::verify_checksums;
+use crate::common::notify::Notify;
+use crate::common::settings::Settings;
+use crate::common::version_directory::VersionDirectory;
+use crate::common::workdir::fvm_versions_path;
+
+#[derive(Debug, Parser)]
+pub struct VerifyOpt {
+    /// Version to verify, defaults to the active version
+    #[arg(index = 1)]
+    version: Option<Channel>,
+    /// Number of threads used to hash binaries, defaults to one per core
+    #[arg(long, short)]
+    jobs: Option<usize>,
+}
+
+impl VerifyOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let channel = match &self.version {
+            Some(channel) => channel.to_owned(),
+            None => match Settings::open()?.channel {
+                Some(channel) => channel,
+                None => {
+                    notify.help(format!(
+                        "You can use {} to see installed versions",
+                        "fvm list".bold()
+                    ));
+
+                    bail!("No version provided and no active version set");
+                }
+            },
+        };
+        let version_path = fvm_versions_path()?.join(channel.to_string());
+
+        if !version_path.exists() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!("Fluvio version {channel} is not installed"),
+            )
+            .into());
+        }
+
+        let version_dir = VersionDirectory::open(version_path)?;
+        let results = verify_checksums(version_dir.checksum_jobs(), self.jobs)?;
+        let mut table = Table::new();
+
+        table.set_header(Row::from(["BINARY", "STATUS"]));
+
+        for result in &results {
+            let status = if result.status.is_failure() {
+                result.status.to_string().red()
+            } else {
+                result.status.to_string().normal()
+            };
+
+            table.add_row(Row::from([result.job.name.clone(), status.to_string()]));
+        }
+
+        table.load_preset(comfy_table::presets::NOTHING);
+        println!("{table}");
+
+        let failures = results
+            .iter()
+            .filter(|result| result.status.is_failure())
+            .count();
+
+        if failures > 0 {
+            notify.help(format!(
+                "Reinstall the version with {}",
+                format!("fvm install {channel}").bold()
+            ));
+
+            return Err(Failure::new(
+                FailureKind::Integrity,
+                format!("{failures} binaries of Fluvio version {channel} failed verification"),
+            )
+            .into());
+        }
+
+        notify.done(format!(
+            "Verified {} binaries of Fluvio version {}",
+            results.len(),
+            version_dir.manifest.version
+        ));
+
+        Ok(())
+    }
+}
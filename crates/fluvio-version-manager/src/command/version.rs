@@ -12,7 +12,7 @@ use crate::{BINARY_NAME, VERSION};
 pub struct VersionOpt;
 
 impl VersionOpt {
-    pub fn process(self) -> Result<()> {
+    pub fn process(&self) -> Result<()> {
         println!("{BINARY_NAME} CLI: {VERSION}");
         println!("{BINARY_NAME} CLI Arch: {CURRENT_PLATFORM}");
 
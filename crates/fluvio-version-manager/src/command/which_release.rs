@@ -0,0 +1,45 @@
+//! Which Release Command
+//!
+//! The `which-release` command finds the Fluvio releases which shipped a
+//! given version of a binary, e.g. `fluvio-run` at `0.11.8`.
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use semver::Version;
+
+use crate::common::github::fvm_client;
+use crate::common::notify::Notify;
+
+#[derive(Debug, Parser)]
+pub struct WhichReleaseOpt {
+    /// Binary name, e.g. `fluvio-run`
+    binary: String,
+    /// Binary version, e.g. `0.11.8`
+    version: Version,
+}
+
+impl WhichReleaseOpt {
+    pub async fn process(&self, notify: Notify) -> Result<()> {
+        let tags = fvm_client()?
+            .find_release_for(&self.binary, &self.version)
+            .await?;
+
+        if tags.is_empty() {
+            notify.warn(format!(
+                "No recent release ships {}@{}",
+                self.binary.bold(),
+                self.version.to_string().bold()
+            ));
+            notify.help("Only the most recent releases are searched, check the binary name");
+
+            return Ok(());
+        }
+
+        for tag in tags {
+            println!("{tag}");
+        }
+
+        Ok(())
+    }
+}
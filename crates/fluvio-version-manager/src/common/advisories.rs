@@ -0,0 +1,228 @@
+//! Release Advisories
+//!
+//! Surfaces the security and critical-bug advisories which apply to the
+//! installed Fluvio Versions when checking for updates, and lists them with
+//! `fvm advisories`. Advisories are cached in the `advisories.json` file in
+//! the FVM cache directory and refreshed once a day.
+
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::{AdvisoriesFeed, Advisory};
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use crate::api::InstalledVersion;
+
+use super::github::fvm_client;
+use super::notify::Notify;
+use super::workdir::{fvm_layout, fvm_workdir_path};
+
+/// The name of the advisories cache file stored in the cache directory
+pub const ADVISORIES_CACHE_FILENAME: &str = "advisories.json";
+
+/// Age after which cached advisories are fetched again
+pub const ADVISORIES_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Advisory applying to an installed version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstalledAdvisory<'a> {
+    pub version: Version,
+    /// Whether the version is the active one
+    pub active: bool,
+    pub advisory: &'a Advisory,
+}
+
+/// Loads advisories from the cache, fetching them when the cache is stale or
+/// `refresh` is set.
+///
+/// Returns `None` if advisories are not available, advisories never prevent
+/// FVM from working offline.
+pub async fn load_advisories(refresh: bool) -> Option<AdvisoriesFeed> {
+    let workdir = fvm_workdir_path().ok()?;
+    let cache_dir = fvm_layout().ok()?.cache_dir;
+    let cache_path = cache_dir.join(ADVISORIES_CACHE_FILENAME);
+
+    if !refresh && let Some(feed) = read_cache(&cache_path, Some(ADVISORIES_CACHE_TTL)) {
+        return Some(feed);
+    }
+
+    match fvm_client().ok()?.fetch_advisories().await {
+        Ok(feed) => {
+            if workdir.exists()
+                && let Err(err) = create_dir_all(&cache_dir)
+                    .map_err(Into::into)
+                    .and_then(|()| write_cache(&cache_path, &feed))
+            {
+                tracing::debug!(%err, "Failed to cache advisories");
+            }
+
+            Some(feed)
+        }
+        Err(err) => {
+            tracing::debug!(%err, "Failed to fetch advisories, using stale cache");
+            read_cache(&cache_path, None)
+        }
+    }
+}
+
+fn write_cache(path: &Path, feed: &AdvisoriesFeed) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(feed)?)
+}
+
+/// Reads the cached advisories if the cache is younger than `ttl`
+fn read_cache(path: &Path, ttl: Option<Duration>) -> Option<AdvisoriesFeed> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+
+    if let Some(ttl) = ttl
+        && modified.elapsed().map_or(true, |age| age > ttl)
+    {
+        return None;
+    }
+
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+/// Advisories applying to the `installed` versions, ordered by version.
+/// Versions installed by several channels are listed once.
+pub fn installed_advisories<'a>(
+    feed: &'a AdvisoriesFeed,
+    installed: &[InstalledVersion],
+) -> Vec<InstalledAdvisory<'a>> {
+    let mut versions: BTreeMap<&Version, bool> = BTreeMap::new();
+
+    for version in installed {
+        *versions.entry(&version.manifest.version).or_default() |= version.active;
+    }
+
+    versions
+        .into_iter()
+        .flat_map(|(version, active)| {
+            feed.advisories_for(version)
+                .into_iter()
+                .map(move |advisory| InstalledAdvisory {
+                    version: version.clone(),
+                    active,
+                    advisory,
+                })
+        })
+        .collect()
+}
+
+/// Warns about the advisories applying to the `installed` versions
+pub fn report_advisories(feed: &AdvisoriesFeed, installed: &[InstalledVersion], notify: Notify) {
+    let advisories = installed_advisories(feed, installed);
+
+    if advisories.is_empty() {
+        return;
+    }
+
+    for applied in &advisories {
+        let advisory = applied.advisory;
+        let version = if applied.active {
+            format!("{} (active)", applied.version)
+        } else {
+            applied.version.to_string()
+        };
+
+        notify.warn(format!(
+            "Fluvio {} is affected by {} advisory {}: {}",
+            version.bold(),
+            advisory.kind,
+            advisory.id,
+            advisory.summary
+        ));
+
+        if let Some(fixed_in) = &advisory.fixed_in {
+            notify.help(format!(
+                "Fixed in Fluvio {fixed_in}, install it with {}",
+                format!("fvm install {fixed_in}").bold()
+            ));
+        }
+    }
+
+    notify.help(format!(
+        "Use {} to list the advisories",
+        "fvm advisories".bold()
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use fluvio_artifacts_util::fvm::Channel;
+
+    use crate::common::manifest::VersionManifest;
+
+    use super::*;
+
+    fn feed() -> AdvisoriesFeed {
+        serde_json::from_str(
+            r#"{
+                "advisories": [
+                    {
+                        "id": "FLV-2024-001",
+                        "kind": "security",
+                        "versions": "<0.11.2",
+                        "summary": "credentials logged",
+                        "fixed_in": "0.11.2"
+                    },
+                    {
+                        "id": "FLV-2024-002",
+                        "kind": "critical-bug",
+                        "versions": "=0.11.8",
+                        "summary": "data loss"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn installed(channel: Channel, version: Version, active: bool) -> InstalledVersion {
+        InstalledVersion {
+            manifest: VersionManifest::new(channel, version, Vec::new()),
+            path: PathBuf::new(),
+            active,
+        }
+    }
+
+    #[test]
+    fn finds_advisories_of_installed_versions() {
+        let feed = feed();
+        let stable = Version::new(0, 11, 8);
+        let old = Version::new(0, 11, 0);
+        let installed = vec![
+            installed(Channel::Stable, stable.clone(), true),
+            installed(Channel::Tag(stable.clone()), stable.clone(), false),
+            installed(Channel::Tag(old.clone()), old.clone(), false),
+            installed(Channel::Latest, Version::new(0, 12, 0), false),
+        ];
+
+        let advisories = installed_advisories(&feed, &installed);
+
+        assert_eq!(
+            advisories,
+            vec![
+                InstalledAdvisory {
+                    version: old,
+                    active: false,
+                    advisory: &feed.advisories[0],
+                },
+                InstalledAdvisory {
+                    version: stable,
+                    active: true,
+                    advisory: &feed.advisories[1],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Parallel Checksum Verification
+//!
+//! Files are hashed on a dedicated work-stealing pool, so a version with a
+//! few large binaries and many small ones keeps every core busy. Each file is
+//! streamed through the hasher with a fixed size buffer, memory used is
+//! bounded by the number of threads regardless of the file sizes.
+
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+
+use fluvio_artifacts_util::sha256_digest;
+
+/// A file to hash, with the digest it is expected to have
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChecksumJob {
+    pub name: String,
+    pub path: PathBuf,
+    /// Recorded SHA-256 digest, files without one are only hashed
+    pub expected: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The digest matches the recorded digest
+    Verified,
+    /// The digest differs from the recorded digest
+    Mismatch { actual: String },
+    /// No digest was recorded for the file
+    Unrecorded { actual: String },
+    /// The file does not exist
+    Missing,
+    /// The file could not be read
+    Failed(String),
+}
+
+impl ChecksumStatus {
+    /// Whether the file failed verification, files without a recorded digest
+    /// are not considered failures
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::Mismatch { .. } | Self::Missing | Self::Failed(_)
+        )
+    }
+}
+
+impl Display for ChecksumStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verified => write!(f, "verified"),
+            Self::Mismatch { .. } => write!(f, "checksum mismatch"),
+            Self::Unrecorded { .. } => write!(f, "no recorded checksum"),
+            Self::Missing => write!(f, "missing"),
+            Self::Failed(err) => write!(f, "failed: {err}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChecksumResult {
+    pub job: ChecksumJob,
+    pub status: ChecksumStatus,
+}
+
+/// Hashes the files of `jobs` in parallel on `threads` threads, or one per
+/// core when `None`. Results are returned in the order of `jobs`.
+pub fn verify_checksums(
+    jobs: Vec<ChecksumJob>,
+    threads: Option<usize>,
+) -> Result<Vec<ChecksumResult>> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or_default())
+        .thread_name(|index| format!("fvm-checksum-{index}"))
+        .build()?;
+
+    Ok(pool.install(|| jobs.into_par_iter().map(verify_checksum).collect()))
+}
+
+fn verify_checksum(job: ChecksumJob) -> ChecksumResult {
+    let status = if !job.path.is_file() {
+        ChecksumStatus::Missing
+    } else {
+        match sha256_digest(&job.path) {
+            Ok(actual) => match &job.expected {
+                Some(expected) if *expected == actual => ChecksumStatus::Verified,
+                Some(_) => ChecksumStatus::Mismatch { actual },
+                None => ChecksumStatus::Unrecorded { actual },
+            },
+            Err(err) => ChecksumStatus::Failed(err.to_string()),
+        }
+    };
+
+    tracing::debug!(name = job.name, %status, "Verified checksum");
+
+    ChecksumResult { job, status }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const HELLO_DIGEST: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn verifies_checksums_in_order() {
+        let tmp = TempDir::new().unwrap();
+        let jobs: Vec<ChecksumJob> = (0..16)
+            .map(|index| {
+                let name = format!("file-{index}");
+                let path = tmp.path().join(&name);
+
+                write(&path, "hello").unwrap();
+
+                ChecksumJob {
+                    name,
+                    path,
+                    expected: Some(HELLO_DIGEST.to_string()),
+                }
+            })
+            .collect();
+        let results = verify_checksums(jobs.clone(), Some(4)).unwrap();
+
+        assert_eq!(
+            results.iter().map(|r| &r.job).collect::<Vec<_>>(),
+            jobs.iter().collect::<Vec<_>>()
+        );
+        assert!(
+            results
+                .iter()
+                .all(|result| result.status == ChecksumStatus::Verified)
+        );
+    }
+
+    #[test]
+    fn reports_per_file_failures() {
+        let tmp = TempDir::new().unwrap();
+        let tampered = tmp.path().join("tampered");
+        let unrecorded = tmp.path().join("unrecorded");
+
+        write(&tampered, "bye").unwrap();
+        write(&unrecorded, "hello").unwrap();
+
+        let results = verify_checksums(
+            vec![
+                ChecksumJob {
+                    name: "tampered".to_string(),
+                    path: tampered,
+                    expected: Some(HELLO_DIGEST.to_string()),
+                },
+                ChecksumJob {
+                    name: "missing".to_string(),
+                    path: tmp.path().join("missing"),
+                    expected: Some(HELLO_DIGEST.to_string()),
+                },
+                ChecksumJob {
+                    name: "unrecorded".to_string(),
+                    path: unrecorded,
+                    expected: None,
+                },
+            ],
+            None,
+        )
+        .unwrap();
+
+        assert!(matches!(results[0].status, ChecksumStatus::Mismatch { .. }));
+        assert_eq!(results[1].status, ChecksumStatus::Missing);
+        assert_eq!(
+            results[2].status,
+            ChecksumStatus::Unrecorded {
+                actual: HELLO_DIGEST.to_string()
+            }
+        );
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| result.status.is_failure())
+                .count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Cluster Compatibility Checks
+//!
+//! With `--check-cluster`, `fvm install` and `fvm switch` query the platform
+//! version of the cluster in the active Fluvio profile and warn when the
+//! newly activated CLI is not compatible with it, suggesting the newest
+//! stable CLI which is. The platform version is read from the output of
+//! `fluvio version --json`, so the check works with any cluster the CLI can
+//! reach.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::VersionMetadata;
+
+use super::github::fvm_client;
+use super::notify::Notify;
+use super::workdir::{fluvio_binaries_path, fvm_layout};
+
+/// Key of the platform version in the output of `fluvio version --json`
+pub const PLATFORM_VERSION_KEY: &str = "Fluvio Platform";
+
+/// Time given to the CLI to reach the cluster
+pub const PLATFORM_QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Warns when the CLI at `cli` is not compatible with the platform of the
+/// cluster in the active profile. Checks never fail the command.
+pub async fn check_cluster_compatibility(cli: &Version, notify: Notify) {
+    let Ok(fluvio) = fluvio_binaries_path().map(|path| path.join("fluvio")) else {
+        return;
+    };
+    let Some(platform) =
+        fluvio_future::task::spawn_blocking(move || query_platform_version(&fluvio)).await
+    else {
+        notify.warn("Could not reach a cluster to check its platform version");
+        return;
+    };
+
+    match check(cli, &platform).await {
+        Ok(None) => notify.done(format!(
+            "Fluvio CLI {} is compatible with cluster platform {}",
+            cli.to_string().bold(),
+            platform.to_string().bold()
+        )),
+        Ok(Some(suggested)) => {
+            notify.warn(format!(
+                "Fluvio CLI {} is not compatible with cluster platform {}",
+                cli.to_string().bold(),
+                platform.to_string().bold()
+            ));
+
+            match suggested {
+                Some(version) => notify.help(format!(
+                    "Install a compatible CLI with {}",
+                    format!("fvm install {version}").bold()
+                )),
+                None => notify.help("No stable CLI release is compatible with this cluster"),
+            }
+        }
+        Err(err) => {
+            tracing::debug!(%err, "Failed to check cluster compatibility");
+            notify.warn("Could not check cluster compatibility, compatibility matrix unavailable");
+        }
+    }
+}
+
+/// `None` when `cli` is compatible with `platform`, otherwise the newest
+/// stable CLI compatible with it, if any
+async fn check(cli: &Version, platform: &Version) -> Result<Option<Option<Version>>> {
+    let metadata = VersionMetadata::new(fvm_client()?).with_cache_dir(fvm_layout()?.cache_dir);
+
+    if metadata
+        .compatibility_matrix()
+        .await?
+        .is_compatible(cli, platform)
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(metadata.newest_compatible_cli(platform).await?))
+}
+
+/// Runs `fluvio version --json` and reads the platform version, `None` if
+/// the cluster is not reachable within [`PLATFORM_QUERY_TIMEOUT`]
+fn query_platform_version(fluvio: &Path) -> Option<Version> {
+    let mut child = Command::new(fluvio)
+        .args(["version", "--json"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + PLATFORM_QUERY_TIMEOUT;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                tracing::debug!("Timed out querying the cluster platform version");
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    parse_platform_version(&output)
+}
+
+/// Platform version in the output of `fluvio version --json`, the value is
+/// e.g. `0.11.8 (local)` or `Not available (local)`
+fn parse_platform_version(output: &str) -> Option<Version> {
+    let value: serde_json::Value = serde_json::from_str(output).ok()?;
+    let platform = value.get(PLATFORM_VERSION_KEY)?.as_str()?;
+
+    Version::parse(platform.split_whitespace().next()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_platform_version() {
+        assert_eq!(
+            parse_platform_version(
+                r#"{"Fluvio CLI": "0.12.1", "Fluvio Platform": "0.11.8 (local)"}"#
+            ),
+            Some(Version::new(0, 11, 8))
+        );
+        assert_eq!(
+            parse_platform_version(r#"{"Fluvio Platform": "Not available (local)"}"#),
+            None
+        );
+        assert_eq!(parse_platform_version(r#"{"Fluvio CLI": "0.12.1"}"#), None);
+        assert_eq!(parse_platform_version("not json"), None);
+    }
+}
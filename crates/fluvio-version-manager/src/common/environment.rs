@@ -0,0 +1,220 @@
+//! Environment Files
+//!
+//! An environment file captures the toolchain state of an FVM installation
+//! as TOML: settings, the mirrors releases are fetched from, installed
+//! versions and the active version. `fvm config export` writes it and
+//! `fvm config import` applies it on another host, so new team members and
+//! fresh CI images reach the same state with one command.
+//!
+//! The scratch directory is specific to each host and is not exported.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::fvm::Channel;
+use fluvio_artifacts_util::layout::LayoutStyle;
+
+use super::install_profile::InstallProfile;
+use super::notification::NotificationSettings;
+use super::settings::Settings;
+
+/// Toolchain state of an FVM installation
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Environment {
+    /// Channel of the active version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    /// Installed versions pinned to a release, e.g. `0.11.8`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+    /// Installed channels following releases, e.g. `stable` or `0.11`, with
+    /// the version each of them was at when exported
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub mirrors: Mirrors,
+    #[serde(default)]
+    pub settings: EnvironmentSettings,
+}
+
+/// Where releases and the metadata checked on install are fetched from
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Mirrors {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_repository: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_overrides: Option<BTreeMap<String, String>>,
+}
+
+/// Settings shared across hosts, see [`Settings`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EnvironmentSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_profile: Option<InstallProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_report: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_policy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_policy_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency_logs: Option<BTreeMap<String, String>>,
+}
+
+impl Environment {
+    /// Environment with the settings, mirrors and active version of
+    /// `settings`, installed versions are added with [`Self::add_installed`]
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            active: settings.channel.as_ref().map(Channel::to_string),
+            versions: Vec::new(),
+            aliases: BTreeMap::new(),
+            mirrors: Mirrors {
+                github_api_url: settings.github_api_url.clone(),
+                github_repository: settings.github_repository.clone(),
+                transparency_url: settings.transparency_url.clone(),
+                dns_overrides: settings.dns_overrides.clone(),
+            },
+            settings: EnvironmentSettings {
+                layout: settings.layout,
+                install_profile: settings.install_profile,
+                verification_report: settings.verification_report,
+                version_policy_url: settings.version_policy_url.clone(),
+                version_policy_key: settings.version_policy_key.clone(),
+                notifications: settings.notifications.clone(),
+                transparency_logs: settings.transparency_logs.clone(),
+            },
+        }
+    }
+
+    /// Records the installed `channel`, currently at `version`
+    pub fn add_installed(&mut self, channel: &Channel, version: &semver::Version) {
+        match channel {
+            Channel::Tag(_) => self.versions.push(channel.to_string()),
+            _ => {
+                self.aliases
+                    .insert(channel.to_string(), version.to_string());
+            }
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).context("Invalid environment file")
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Channels to install, pinned versions first followed by aliases
+    pub fn channels(&self) -> Result<Vec<Channel>> {
+        self.versions
+            .iter()
+            .chain(self.aliases.keys())
+            .map(|channel| Ok(Channel::parse(channel)?))
+            .collect()
+    }
+
+    /// Channel of the active version
+    pub fn active_channel(&self) -> Result<Option<Channel>> {
+        Ok(self.active.as_deref().map(Channel::parse).transpose()?)
+    }
+
+    /// Overwrites the settings and mirrors of `settings` set by this
+    /// environment, keeping the others
+    pub fn apply_to(&self, settings: &mut Settings) {
+        let mirrors = &self.mirrors;
+        let shared = &self.settings;
+
+        overwrite(&mut settings.github_api_url, &mirrors.github_api_url);
+        overwrite(&mut settings.github_repository, &mirrors.github_repository);
+        overwrite(&mut settings.transparency_url, &mirrors.transparency_url);
+        overwrite(&mut settings.dns_overrides, &mirrors.dns_overrides);
+        overwrite(&mut settings.layout, &shared.layout);
+        overwrite(&mut settings.install_profile, &shared.install_profile);
+        overwrite(
+            &mut settings.verification_report,
+            &shared.verification_report,
+        );
+        overwrite(&mut settings.version_policy_url, &shared.version_policy_url);
+        overwrite(&mut settings.version_policy_key, &shared.version_policy_key);
+        overwrite(&mut settings.notifications, &shared.notifications);
+        overwrite(&mut settings.transparency_logs, &shared.transparency_logs);
+    }
+}
+
+fn overwrite<T: Clone>(setting: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        setting.clone_from(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            channel: Some(Channel::Stable),
+            version: Some("0.11.8".to_string()),
+            tmpdir: Some("/scratch".into()),
+            layout: None,
+            transparency_url: None,
+            install_profile: None,
+            verification_report: Some(true),
+            github_api_url: Some("https://github.example.com/api".to_string()),
+            github_repository: Some("acme/fluvio".to_string()),
+            dns_overrides: None,
+            version_policy_url: None,
+            version_policy_key: None,
+            notifications: None,
+            transparency_logs: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_environments() {
+        let mut environment = Environment::from_settings(&settings());
+
+        environment.add_installed(&Channel::Stable, &Version::new(0, 11, 8));
+        environment.add_installed(
+            &Channel::parse("0.10.14").unwrap(),
+            &Version::new(0, 10, 14),
+        );
+
+        let contents = environment.to_toml().unwrap();
+
+        assert!(!contents.contains("scratch"));
+
+        let environment = Environment::parse(&contents).unwrap();
+
+        assert_eq!(
+            environment.channels().unwrap(),
+            vec![Channel::parse("0.10.14").unwrap(), Channel::Stable]
+        );
+        assert_eq!(environment.active_channel().unwrap(), Some(Channel::Stable));
+        assert_eq!(environment.aliases["stable"], "0.11.8");
+
+        let mut local = settings();
+
+        local.github_repository = None;
+        local.layout = Some(LayoutStyle::Platform);
+        environment.apply_to(&mut local);
+
+        assert_eq!(local.github_repository.as_deref(), Some("acme/fluvio"));
+        assert_eq!(local.layout, Some(LayoutStyle::Platform));
+        assert!(Environment::parse("versions = 1").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+//! End-of-Life Checks
+//!
+//! Warns when installing or switching to a Fluvio Version which reached
+//! end-of-life or has known critical issues. Notices are cached in the
+//! `eol.json` file in the FVM cache directory and refreshed once a day.
+
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::EolMetadata;
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::github::fvm_client;
+use super::notify::Notify;
+use super::workdir::{fvm_layout, fvm_workdir_path};
+
+/// The name of the EOL notices cache file stored in the cache directory
+pub const EOL_CACHE_FILENAME: &str = "eol.json";
+
+/// Age after which cached notices are fetched again
+pub const EOL_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Loads EOL notices from the cache, fetching them when the cache is stale.
+///
+/// Returns `None` if notices are not available, EOL checks never prevent
+/// FVM from working offline.
+pub async fn load_eol_metadata() -> Option<EolMetadata> {
+    let workdir = fvm_workdir_path().ok()?;
+    let cache_dir = fvm_layout().ok()?.cache_dir;
+    let cache_path = cache_dir.join(EOL_CACHE_FILENAME);
+
+    if let Some(metadata) = read_cache(&cache_path, Some(EOL_CACHE_TTL)) {
+        return Some(metadata);
+    }
+
+    match fvm_client().ok()?.fetch_eol_metadata().await {
+        Ok(metadata) => {
+            if workdir.exists()
+                && let Err(err) = create_dir_all(&cache_dir)
+                    .map_err(Into::into)
+                    .and_then(|()| write_cache(&cache_path, &metadata))
+            {
+                tracing::debug!(%err, "Failed to cache EOL notices");
+            }
+
+            Some(metadata)
+        }
+        Err(err) => {
+            tracing::debug!(%err, "Failed to fetch EOL notices, using stale cache");
+            read_cache(&cache_path, None)
+        }
+    }
+}
+
+fn write_cache(path: &Path, metadata: &EolMetadata) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(metadata)?)
+}
+
+/// Reads the cached notices if the cache is younger than `ttl`
+fn read_cache(path: &Path, ttl: Option<Duration>) -> Option<EolMetadata> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+
+    if let Some(ttl) = ttl
+        && modified.elapsed().map_or(true, |age| age > ttl)
+    {
+        return None;
+    }
+
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+/// Prints the notices for `version`. End-of-life versions are rejected
+/// unless `accept_eol` is set.
+pub fn check_eol(
+    metadata: &EolMetadata,
+    version: &Version,
+    accept_eol: bool,
+    notify: Notify,
+) -> Result<()> {
+    let notices = metadata.notices_for(version);
+
+    if notices.is_empty() {
+        return Ok(());
+    }
+
+    let is_eol = metadata.is_eol(version);
+
+    if is_eol {
+        notify.warn(format!(
+            "Fluvio {} is end-of-life and no longer receives fixes",
+            version.to_string().bold()
+        ));
+    }
+
+    for critical in notices.iter().filter_map(|notice| notice.critical.as_ref()) {
+        notify.warn(format!(
+            "Fluvio {} has a known critical issue: {critical}",
+            version.to_string().bold()
+        ));
+    }
+
+    if let Some(upgrade) = metadata.minimal_safe_upgrade(version) {
+        let command = format!("fvm install {upgrade}");
+
+        notify.help(format!(
+            "Upgrade to Fluvio {upgrade} or later with {}",
+            command.bold()
+        ));
+    }
+
+    if is_eol && !accept_eol {
+        bail!("Fluvio {version} is end-of-life, use --accept-eol to use it anyway");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn metadata() -> EolMetadata {
+        serde_json::from_str(
+            r#"{
+                "notices": [
+                    { "versions": "<0.11.0", "eol": true, "upgrade_to": "0.11.0" },
+                    { "versions": "=0.11.1", "critical": "data loss", "upgrade_to": "0.11.2" }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn rejects_eol_unless_accepted() {
+        let metadata = metadata();
+        let notify = Notify::new(true);
+
+        assert!(check_eol(&metadata, &Version::new(0, 10, 0), false, notify).is_err());
+        assert!(check_eol(&metadata, &Version::new(0, 10, 0), true, notify).is_ok());
+        assert!(check_eol(&metadata, &Version::new(0, 11, 1), false, notify).is_ok());
+        assert!(check_eol(&metadata, &Version::new(0, 11, 2), false, notify).is_ok());
+    }
+
+    #[test]
+    fn reads_cache_within_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let cache_path = tmp.path().join(EOL_CACHE_FILENAME);
+
+        assert_eq!(read_cache(&cache_path, None), None);
+
+        write(&cache_path, serde_json::to_string(&metadata()).unwrap()).unwrap();
+
+        assert_eq!(
+            read_cache(&cache_path, Some(EOL_CACHE_TTL)),
+            Some(metadata())
+        );
+        assert_eq!(read_cache(&cache_path, Some(Duration::ZERO)), None);
+        assert_eq!(read_cache(&cache_path, None), Some(metadata()));
+    }
+}
@@ -0,0 +1,78 @@
+//! Exit Codes
+//!
+//! FVM exits with a code telling the cause of a failure, so CI pipelines
+//! branch on it without parsing error messages. The cause is the first
+//! [`FailureKind`] found in the chain of the error, errors of unknown cause
+//! exit with [`FAILURE`].
+
+use fluvio_artifacts_util::failure::{FailureKind, failure_kind};
+
+/// Any failure of unknown cause
+pub const FAILURE: i32 = 1;
+/// Invalid arguments, as reported by `clap`
+pub const USAGE: i32 = 2;
+/// A server could not be reached or answered with a server error
+pub const NETWORK: i32 = 3;
+/// Checksum or integrity verification failed
+pub const INTEGRITY: i32 = 4;
+/// The version, release or artifact does not exist
+pub const NOT_FOUND: i32 = 5;
+/// No artifacts for the target, or the host does not meet the requirements
+pub const INCOMPATIBLE_TARGET: i32 = 6;
+/// The version is in use by running processes
+pub const LOCKED: i32 = 7;
+
+/// Exit codes listed in the help of FVM
+pub const EXIT_CODES_HELP: &str = "\
+Exit Codes:
+  0  Success
+  1  Failure of unknown cause
+  2  Invalid arguments
+  3  Network failure
+  4  Checksum or integrity failure
+  5  Version, release or artifact not found
+  6  Incompatible target or host requirements not met
+  7  Version in use by running processes";
+
+/// Exit code telling the cause of `err`
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    match failure_kind(err) {
+        Some(FailureKind::Network) => NETWORK,
+        Some(FailureKind::Integrity) => INTEGRITY,
+        Some(FailureKind::NotFound) => NOT_FOUND,
+        Some(FailureKind::IncompatibleTarget) => INCOMPATIBLE_TARGET,
+        Some(FailureKind::Locked) => LOCKED,
+        None => FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Context, anyhow};
+    use fluvio_artifacts_util::failure::Failure;
+
+    use super::*;
+
+    #[test]
+    fn exit_codes_tell_the_cause() {
+        let failed = |kind| {
+            Err::<(), _>(Failure::new(kind, "failed"))
+                .context("Failed to install Fluvio")
+                .unwrap_err()
+        };
+
+        assert_eq!(exit_code(&failed(FailureKind::Network)), NETWORK);
+        assert_eq!(exit_code(&failed(FailureKind::Integrity)), INTEGRITY);
+        assert_eq!(exit_code(&failed(FailureKind::NotFound)), NOT_FOUND);
+        assert_eq!(
+            exit_code(&failed(FailureKind::IncompatibleTarget)),
+            INCOMPATIBLE_TARGET
+        );
+        assert_eq!(exit_code(&failed(FailureKind::Locked)), LOCKED);
+        assert_eq!(exit_code(&anyhow!("failed")), FAILURE);
+
+        for code in [NETWORK, INTEGRITY, NOT_FOUND, INCOMPATIBLE_TARGET, LOCKED] {
+            assert!(EXIT_CODES_HELP.contains(&format!("  {code}  ")));
+        }
+    }
+}
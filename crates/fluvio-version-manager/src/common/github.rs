@@ -0,0 +1,85 @@
+//! GitHub Repository Configuration
+//!
+//! Releases are fetched from the Fluvio repository on github.com unless
+//! `FVM_GITHUB_API_URL` and `FVM_GITHUB_REPOSITORY`, or the `github_api_url`
+//! and `github_repository` keys in `settings.toml`, point to another
+//! repository, e.g. a fork on GitHub Enterprise Server.
+
+use std::env::var;
+
+use anyhow::Result;
+
+use fluvio_artifacts_util::fvm::Client;
+use fluvio_artifacts_util::github::GitHubRepo;
+
+use super::settings::Settings;
+
+/// Environment variable with the GitHub API URL, e.g.
+/// `https://ghe.example.com/api/v3`
+pub const FVM_GITHUB_API_URL_ENV_VAR: &str = "FVM_GITHUB_API_URL";
+
+/// Environment variable with the repository releases are fetched from, as
+/// `<owner>/<name>`
+pub const FVM_GITHUB_REPOSITORY_ENV_VAR: &str = "FVM_GITHUB_REPOSITORY";
+
+/// Retrieves the repository releases are fetched from, environment
+/// variables take precedence over `settings.toml`
+pub fn github_repo() -> Result<GitHubRepo> {
+    let repository = match non_empty_var(FVM_GITHUB_REPOSITORY_ENV_VAR) {
+        Some(repository) => Some(repository),
+        None => Settings::configured_github_repository()?,
+    };
+    let api_url = match non_empty_var(FVM_GITHUB_API_URL_ENV_VAR) {
+        Some(api_url) => Some(api_url),
+        None => Settings::configured_github_api_url()?,
+    };
+
+    resolve_github_repo(repository.as_deref(), api_url.as_deref())
+}
+
+/// Client fetching releases from the configured repository
+pub fn fvm_client() -> Result<Client> {
+    Ok(Client::new(github_repo()?))
+}
+
+fn resolve_github_repo(repository: Option<&str>, api_url: Option<&str>) -> Result<GitHubRepo> {
+    let repo = match repository {
+        Some(repository) => repository.parse()?,
+        None => GitHubRepo::default(),
+    };
+
+    Ok(match api_url {
+        Some(api_url) => repo.with_api_url(api_url),
+        None => repo,
+    })
+}
+
+fn non_empty_var(key: &str) -> Option<String> {
+    var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use fluvio_artifacts_util::{REPO_NAME, REPO_OWNER};
+
+    use super::*;
+
+    #[test]
+    fn resolves_configured_repository() {
+        assert_eq!(
+            resolve_github_repo(None, None).unwrap(),
+            GitHubRepo::default()
+        );
+
+        let repo =
+            resolve_github_repo(Some("acme/fluvio"), Some("https://ghe.acme.io/api/v3")).unwrap();
+
+        assert_eq!(repo.to_string(), "acme/fluvio");
+        assert_eq!(repo.api_url.as_deref(), Some("https://ghe.acme.io/api/v3"));
+
+        let repo = resolve_github_repo(None, Some("https://ghe.acme.io/api/v3")).unwrap();
+
+        assert_eq!(repo.to_string(), format!("{REPO_OWNER}/{REPO_NAME}"));
+        assert!(resolve_github_repo(Some("acme"), None).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+//! Install Hooks
+//!
+//! Release archives may ship assets next to the binaries, such as shell
+//! completions or man pages, which are stored in the version directory under
+//! `assets/<binary>`. After installing, each asset is placed into its
+//! standard user location, e.g. `~/.local/share/man/man1` for man pages.
+//!
+//! Placed assets are recorded as hooks in the version manifest, so they are
+//! removed when the version is uninstalled.
+
+use std::collections::HashSet;
+use std::fs::{copy, create_dir_all, read_dir, remove_file};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::fvm::{ARTIFACT_ASSETS_DIR, AssetKind, man_section};
+
+use super::manifest::{PACKAGE_SET_MANIFEST_FILENAME, VersionManifest};
+use super::workdir::fluvio_path;
+
+/// An asset placed outside of the version directory
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct InstallHook {
+    pub kind: AssetKind,
+    /// Asset path relative to the version directory
+    pub source: PathBuf,
+    /// Path the asset is placed at
+    pub destination: PathBuf,
+}
+
+/// Standard locations for each kind of asset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetDirs {
+    pub bash_completions: PathBuf,
+    pub zsh_completions: PathBuf,
+    pub fish_completions: PathBuf,
+    /// Root of the man pages, containing `man1`, `man5` and so on
+    pub man: PathBuf,
+    pub examples: PathBuf,
+}
+
+impl AssetDirs {
+    /// Per user locations, following the XDG base directories on Linux
+    pub fn user() -> Result<Self> {
+        let data_dir =
+            dirs::data_dir().ok_or_else(|| anyhow!("Failed to resolve the data directory"))?;
+        let config_dir =
+            dirs::config_dir().ok_or_else(|| anyhow!("Failed to resolve the config directory"))?;
+
+        Ok(Self {
+            bash_completions: data_dir.join("bash-completion").join("completions"),
+            zsh_completions: data_dir.join("zsh").join("site-functions"),
+            fish_completions: config_dir.join("fish").join("completions"),
+            man: data_dir.join("man"),
+            examples: fluvio_path()?.join("examples"),
+        })
+    }
+
+    /// Location of the asset `file_name` shipped with `binary`, named after
+    /// the conventions of each tool, e.g. `_fluvio` for zsh completions
+    pub fn destination(&self, kind: AssetKind, binary: &str, file_name: &str) -> Option<PathBuf> {
+        match kind {
+            AssetKind::BashCompletion => {
+                let name = file_name.strip_suffix(".bash").unwrap_or(file_name);
+
+                Some(self.bash_completions.join(name))
+            }
+            AssetKind::ZshCompletion => {
+                let name = file_name.strip_suffix(".zsh").unwrap_or(file_name);
+                let name = name.strip_prefix('_').unwrap_or(name);
+
+                Some(self.zsh_completions.join(format!("_{name}")))
+            }
+            AssetKind::FishCompletion => Some(self.fish_completions.join(file_name)),
+            AssetKind::ManPage => {
+                let section = man_section(file_name)?;
+
+                Some(self.man.join(format!("man{section}")).join(file_name))
+            }
+            AssetKind::Example => Some(self.examples.join(binary).join(file_name)),
+        }
+    }
+}
+
+/// Builds the hooks for the assets stored in `version_path`
+pub fn plan_hooks(version_path: &Path, dirs: &AssetDirs) -> Result<Vec<InstallHook>> {
+    let assets_path = version_path.join(ARTIFACT_ASSETS_DIR);
+    let mut hooks = Vec::new();
+
+    if !assets_path.is_dir() {
+        return Ok(hooks);
+    }
+
+    for entry in read_dir(&assets_path)? {
+        let binary_path = entry?.path();
+        let Some(binary) = binary_path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        for file in list_files(&binary_path)? {
+            let Ok(entry) = file.strip_prefix(&binary_path) else {
+                continue;
+            };
+            let Some(file_name) = file.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if let Some(kind) = AssetKind::classify(entry)
+                && let Some(destination) = dirs.destination(kind, binary, file_name)
+            {
+                hooks.push(InstallHook {
+                    kind,
+                    source: file.strip_prefix(version_path)?.to_path_buf(),
+                    destination,
+                });
+            }
+        }
+    }
+
+    hooks.sort_by(|a, b| a.destination.cmp(&b.destination));
+    Ok(hooks)
+}
+
+/// Places the assets of `hooks`, returning the hooks which succeeded. A
+/// failure to place an asset doesn't fail the installation.
+pub fn run_hooks(version_path: &Path, hooks: Vec<InstallHook>) -> Vec<InstallHook> {
+    hooks
+        .into_iter()
+        .filter(|hook| match place_asset(version_path, hook) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(%err, destination = ?hook.destination, "Failed to place asset");
+                false
+            }
+        })
+        .collect()
+}
+
+fn place_asset(version_path: &Path, hook: &InstallHook) -> Result<()> {
+    if let Some(parent) = hook.destination.parent() {
+        create_dir_all(parent)?;
+    }
+
+    if hook.destination.symlink_metadata().is_ok() {
+        remove_file(&hook.destination)?;
+    }
+
+    copy(version_path.join(&hook.source), &hook.destination)?;
+    tracing::debug!(destination = ?hook.destination, "Placed asset");
+
+    Ok(())
+}
+
+/// Removes the assets placed by `hooks`, except for the destinations in
+/// `in_use` which were also placed by other installed versions
+pub fn remove_hooks(hooks: &[InstallHook], in_use: &HashSet<PathBuf>) -> Result<()> {
+    for hook in hooks {
+        if in_use.contains(&hook.destination) || hook.destination.symlink_metadata().is_err() {
+            continue;
+        }
+
+        remove_file(&hook.destination)?;
+        tracing::debug!(destination = ?hook.destination, "Removed asset");
+    }
+
+    Ok(())
+}
+
+/// Destinations placed by the versions in `versions_path` other than the
+/// version at `except`
+pub fn hooks_in_use(versions_path: &Path, except: &Path) -> Result<HashSet<PathBuf>> {
+    let mut in_use = HashSet::new();
+
+    if !versions_path.is_dir() {
+        return Ok(in_use);
+    }
+
+    for entry in read_dir(versions_path)? {
+        let path = entry?.path();
+
+        if path == except {
+            continue;
+        }
+
+        if let Ok(manifest) = VersionManifest::open(path.join(PACKAGE_SET_MANIFEST_FILENAME)) {
+            in_use.extend(
+                manifest
+                    .hooks
+                    .into_iter()
+                    .flatten()
+                    .map(|hook| hook.destination),
+            );
+        }
+    }
+
+    Ok(in_use)
+}
+
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(list_files(&path)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use semver::Version;
+    use tempfile::TempDir;
+
+    use fluvio_artifacts_util::fvm::Channel;
+
+    use super::*;
+
+    fn asset_dirs(root: &Path) -> AssetDirs {
+        AssetDirs {
+            bash_completions: root.join("bash"),
+            zsh_completions: root.join("zsh"),
+            fish_completions: root.join("fish"),
+            man: root.join("man"),
+            examples: root.join("examples"),
+        }
+    }
+
+    fn write_asset(version_path: &Path, path: &str) {
+        let path = version_path.join(ARTIFACT_ASSETS_DIR).join(path);
+
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, "asset").unwrap();
+    }
+
+    #[test]
+    fn names_destinations_after_conventions() {
+        let dirs = asset_dirs(Path::new("/home"));
+
+        assert_eq!(
+            dirs.destination(AssetKind::BashCompletion, "fluvio", "fluvio.bash"),
+            Some(PathBuf::from("/home/bash/fluvio"))
+        );
+        assert_eq!(
+            dirs.destination(AssetKind::ZshCompletion, "fluvio", "fluvio.zsh"),
+            Some(PathBuf::from("/home/zsh/_fluvio"))
+        );
+        assert_eq!(
+            dirs.destination(AssetKind::ManPage, "fluvio", "fluvio-consume.1.gz"),
+            Some(PathBuf::from("/home/man/man1/fluvio-consume.1.gz"))
+        );
+        assert_eq!(
+            dirs.destination(AssetKind::Example, "cdk", "connector.yaml"),
+            Some(PathBuf::from("/home/examples/cdk/connector.yaml"))
+        );
+    }
+
+    #[test]
+    fn places_and_removes_assets() {
+        let version = TempDir::new().unwrap();
+        let home = TempDir::new().unwrap();
+        let dirs = asset_dirs(home.path());
+
+        write_asset(version.path(), "fluvio/completions/fluvio.bash");
+        write_asset(version.path(), "fluvio/man/fluvio.1");
+        write_asset(version.path(), "fluvio/LICENSE");
+
+        let hooks = plan_hooks(version.path(), &dirs).unwrap();
+
+        assert_eq!(hooks.len(), 2);
+        assert_eq!(
+            hooks[0].source,
+            Path::new(ARTIFACT_ASSETS_DIR).join("fluvio/completions/fluvio.bash")
+        );
+
+        let placed = run_hooks(version.path(), hooks);
+
+        assert_eq!(placed.len(), 2);
+        assert!(home.path().join("bash/fluvio").is_file());
+        assert!(home.path().join("man/man1/fluvio.1").is_file());
+
+        let in_use = HashSet::from([home.path().join("man/man1/fluvio.1")]);
+
+        remove_hooks(&placed, &in_use).unwrap();
+        assert!(!home.path().join("bash/fluvio").exists());
+        assert!(home.path().join("man/man1/fluvio.1").exists());
+    }
+
+    #[test]
+    fn collects_hooks_of_other_versions() {
+        let versions = TempDir::new().unwrap();
+        let stable = versions.path().join("stable");
+        let latest = versions.path().join("latest");
+        let hook = InstallHook {
+            kind: AssetKind::ManPage,
+            source: PathBuf::from("assets/fluvio/man/fluvio.1"),
+            destination: PathBuf::from("/man/man1/fluvio.1"),
+        };
+
+        for (path, channel) in [(&stable, Channel::Stable), (&latest, Channel::Latest)] {
+            let mut manifest = VersionManifest::new(channel, Version::new(0, 11, 0), vec![]);
+
+            manifest.hooks = Some(vec![hook.clone()]);
+            create_dir_all(path).unwrap();
+            manifest.write(path).unwrap();
+        }
+
+        assert!(
+            hooks_in_use(versions.path(), &stable)
+                .unwrap()
+                .contains(&hook.destination)
+        );
+
+        std::fs::remove_dir_all(&latest).unwrap();
+        assert!(hooks_in_use(versions.path(), &stable).unwrap().is_empty());
+    }
+}
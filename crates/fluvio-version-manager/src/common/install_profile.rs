@@ -0,0 +1,138 @@
+//! Install Profiles
+//!
+//! Curated selections of components and setup steps chosen with `fvm init`.
+//! The profile is kept in `settings.toml` so installs and updates keep the
+//! same components.
+
+use std::fmt;
+
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::fvm::PackageSet;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallProfile {
+    /// Fluvio CLI, SmartModule and Connector development kits and shell
+    /// completions
+    #[default]
+    Developer,
+    /// Fluvio CLI and cluster binaries
+    Operator,
+    /// Fluvio CLI only, without output or shell profile changes
+    Ci,
+}
+
+impl InstallProfile {
+    /// Names of the binaries installed with this profile
+    pub fn components(&self) -> &'static [&'static str] {
+        match self {
+            Self::Developer => &["fluvio", "smdk", "cdk"],
+            Self::Operator => &["fluvio", "fluvio-run"],
+            Self::Ci => &["fluvio"],
+        }
+    }
+
+    /// Whether completions, man pages and examples are placed
+    pub fn places_assets(&self) -> bool {
+        matches!(self, Self::Developer)
+    }
+
+    /// Whether the shell profile files are edited to add Fluvio to `PATH`
+    pub fn edits_path(&self) -> bool {
+        !matches!(self, Self::Ci)
+    }
+
+    /// Whether output is suppressed
+    pub fn quiet(&self) -> bool {
+        matches!(self, Self::Ci)
+    }
+
+    /// Keeps the artifacts of `package_set` which are components of this
+    /// profile
+    pub fn select(&self, mut package_set: PackageSet) -> Result<PackageSet> {
+        let components = self.components();
+
+        package_set
+            .artifacts
+            .retain(|artifact| components.contains(&artifact.name.as_str()));
+
+        if package_set.artifacts.is_empty() {
+            bail!(
+                "Fluvio {} publishes none of the {self} profile components: {}",
+                package_set.pkgset,
+                components.join(", ")
+            );
+        }
+
+        Ok(package_set)
+    }
+}
+
+impl fmt::Display for InstallProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Developer => write!(f, "developer"),
+            Self::Operator => write!(f, "operator"),
+            Self::Ci => write!(f, "ci"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use fluvio_artifacts_util::fvm::Artifact;
+
+    use super::*;
+
+    fn package_set(names: &[&str]) -> PackageSet {
+        PackageSet {
+            pkgset: Version::new(0, 11, 8),
+            arch: "x86_64-unknown-linux-musl".to_string(),
+            release: None,
+            artifacts: names
+                .iter()
+                .map(|name| Artifact {
+                    name: name.to_string(),
+                    version: Version::new(0, 11, 8),
+                    download_url: format!("https://example.com/{name}.zip"),
+                    sha256_digest: None,
+                    variant: None,
+                    channel: None,
+                    entry: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn names(package_set: &PackageSet) -> Vec<&str> {
+        package_set
+            .artifacts
+            .iter()
+            .map(|artifact| artifact.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn selects_profile_components() {
+        let upstream = package_set(&["fluvio", "fluvio-run", "smdk", "cdk"]);
+
+        assert_eq!(
+            names(&InstallProfile::Developer.select(upstream.clone()).unwrap()),
+            vec!["fluvio", "smdk", "cdk"]
+        );
+        assert_eq!(
+            names(&InstallProfile::Operator.select(upstream.clone()).unwrap()),
+            vec!["fluvio", "fluvio-run"]
+        );
+        assert_eq!(
+            names(&InstallProfile::Ci.select(upstream).unwrap()),
+            vec!["fluvio"]
+        );
+        assert!(InstallProfile::Ci.select(package_set(&["smdk"])).is_err());
+    }
+}
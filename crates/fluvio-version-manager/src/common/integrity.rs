@@ -0,0 +1,221 @@
+//! Installation Integrity
+//!
+//! `fvm verify` compares installed binaries with the digests recorded at
+//! install time, which are only as trustworthy as the version manifest
+//! holding them. Integrity checks re-derive the expected digests instead:
+//! artifacts are downloaded again from their release, checked against the
+//! archive digests recorded at install time, acting as a lockfile, or the
+//! published ones, and the binaries they hold are hashed and compared with
+//! the installed ones. Drift from local tampering, disk corruption or manual
+//! overwrites is reported even when the version manifest was changed too.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use anyhow::{Result, anyhow};
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::{Channel, ChecksumsManifest, Client, Download};
+use fluvio_artifacts_util::sha256_digest;
+
+use super::janitor::TrackedTempDir;
+use super::version_directory::VersionDirectory;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityStatus {
+    /// The installed binary matches the release
+    Intact,
+    /// The installed binary differs from the release
+    Drifted { expected: String, actual: String },
+    /// The installed binary matches the release but the digest recorded in
+    /// the version manifest does not
+    ManifestDrifted { recorded: String, expected: String },
+    /// The installed binary does not exist
+    Missing,
+    /// The release has no artifact for the installed binary
+    Unreleased,
+}
+
+impl IntegrityStatus {
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Self::Intact)
+    }
+}
+
+impl Display for IntegrityStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Intact => write!(f, "intact"),
+            Self::Drifted { .. } => write!(f, "differs from the release"),
+            Self::ManifestDrifted { .. } => write!(f, "recorded checksum differs from the release"),
+            Self::Missing => write!(f, "missing"),
+            Self::Unreleased => write!(f, "not in the release"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityResult {
+    pub name: String,
+    pub status: IntegrityStatus,
+}
+
+/// Digests of the binaries of the artifacts installed in `version_dir`, as
+/// extracted from the artifacts of their releases. Binaries whose release
+/// has no artifact for them are not listed.
+pub async fn release_digests(
+    client: &Client,
+    version_dir: &VersionDirectory,
+    arch: &str,
+) -> Result<ChecksumsManifest> {
+    let contents = version_dir.manifest.contents.as_ref().ok_or_else(|| {
+        anyhow!(
+            "No versioned artifacts manifest available for version: {}",
+            version_dir.manifest.version
+        )
+    })?;
+    let mut by_version: BTreeMap<Version, Vec<_>> = BTreeMap::new();
+
+    for installed in contents {
+        let version = Version::parse(&installed.version)?;
+
+        by_version.entry(version).or_default().push(installed);
+    }
+
+    let scratch = TrackedTempDir::new()?;
+    let mut digests = ChecksumsManifest::default();
+
+    for (version, installed) in by_version {
+        let package_set = client
+            .fetch_package_set(&Channel::Tag(version), arch)
+            .await?;
+
+        for installed in installed {
+            let Some(mut artifact) = package_set
+                .artifacts
+                .iter()
+                .find(|artifact| artifact.name == installed.name)
+                .cloned()
+            else {
+                continue;
+            };
+
+            // The archive digest recorded at install time pins the artifact,
+            // so a release replaced since then fails to download
+            if let Some(recorded) = &installed.sha256_digest {
+                artifact.sha256_digest = Some(recorded.to_owned());
+            }
+
+            let path = artifact.download(scratch.path().to_path_buf()).await?;
+
+            digests.insert(&artifact.name, sha256_digest(&path)?);
+        }
+    }
+
+    Ok(digests)
+}
+
+/// Compares the binaries installed in `version_dir` with the digests of
+/// their releases, see [`release_digests`]
+pub fn check_integrity(
+    version_dir: &VersionDirectory,
+    expected: &ChecksumsManifest,
+) -> Result<Vec<IntegrityResult>> {
+    let mut results = Vec::new();
+
+    for installed in version_dir.manifest.contents.iter().flatten() {
+        let path = version_dir.path.join(&installed.name);
+        let status = match expected.get(&installed.name) {
+            None => IntegrityStatus::Unreleased,
+            Some(_) if !path.is_file() => IntegrityStatus::Missing,
+            Some(expected) => {
+                let actual = sha256_digest(&path)?;
+
+                match &installed.installed_sha256_digest {
+                    _ if actual != expected => IntegrityStatus::Drifted {
+                        expected: expected.to_owned(),
+                        actual,
+                    },
+                    Some(recorded) if recorded != expected => IntegrityStatus::ManifestDrifted {
+                        recorded: recorded.to_owned(),
+                        expected: expected.to_owned(),
+                    },
+                    _ => IntegrityStatus::Intact,
+                }
+            }
+        };
+
+        tracing::debug!(name = installed.name, %status, "Checked integrity");
+        results.push(IntegrityResult {
+            name: installed.name.to_owned(),
+            status,
+        });
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use crate::common::manifest::{VersionManifest, VersionedArtifact};
+
+    use super::*;
+
+    const HELLO_DIGEST: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn reports_drift_from_the_release() {
+        let dir = TempDir::new().unwrap();
+        let version = Version::new(0, 11, 8);
+        let mut contents = Vec::new();
+
+        for name in ["fluvio", "smdk", "cdk", "fluvio-run", "fvm"] {
+            let mut artifact = VersionedArtifact::new(name, "0.11.8");
+
+            artifact.installed_sha256_digest = Some(HELLO_DIGEST.to_string());
+            contents.push(artifact);
+        }
+
+        contents[2].installed_sha256_digest = Some("00".repeat(32));
+        VersionManifest::new(Channel::Tag(version.clone()), version, contents)
+            .write(dir.path())
+            .unwrap();
+
+        for name in ["fluvio", "smdk", "cdk", "fvm"] {
+            write(dir.path().join(name), b"hello").unwrap();
+        }
+
+        write(dir.path().join("smdk"), b"tampered").unwrap();
+
+        let version_dir = VersionDirectory::open(dir.path().to_path_buf()).unwrap();
+        let mut expected = ChecksumsManifest::default();
+
+        for name in ["fluvio", "smdk", "cdk", "fluvio-run"] {
+            expected.insert(name, HELLO_DIGEST);
+        }
+
+        let statuses: Vec<IntegrityStatus> = check_integrity(&version_dir, &expected)
+            .unwrap()
+            .into_iter()
+            .map(|result| result.status)
+            .collect();
+
+        assert_eq!(statuses[0], IntegrityStatus::Intact);
+        assert!(matches!(
+            &statuses[1],
+            IntegrityStatus::Drifted { expected, .. } if expected == HELLO_DIGEST
+        ));
+        assert!(matches!(
+            statuses[2],
+            IntegrityStatus::ManifestDrifted { .. }
+        ));
+        assert_eq!(statuses[3], IntegrityStatus::Missing);
+        assert_eq!(statuses[4], IntegrityStatus::Unreleased);
+        assert!(!statuses[0].is_failure());
+        assert!(statuses[1..].iter().all(IntegrityStatus::is_failure));
+    }
+}
@@ -0,0 +1,461 @@
+//! Janitor
+//!
+//! Keeps track of temporary locations (download directories, staging
+//! directories) created by FVM in the `janitor.json` state file so entries
+//! left behind by interrupted operations can be removed later on.
+//!
+//! Several FVM processes may update the state file at once, e.g. parallel CI
+//! jobs, so updates hold an advisory lock on `janitor.json.lock` and reload
+//! the state before changing it.
+
+use std::ffi::OsString;
+use std::fs::{File, create_dir_all, remove_dir_all, remove_file};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tempfile::TempDir;
+
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::workdir::{fvm_tmp_path, fvm_workdir_path};
+
+/// The name of the janitor state file stored in the FVM workdir
+pub const JANITOR_STATE_FILENAME: &str = "janitor.json";
+
+/// Prefix for directories created in the FVM scratch location
+pub const TEMP_DIR_PREFIX: &str = "fvm-";
+
+/// Age after which tracked entries are considered orphaned when cleaning up
+/// on startup
+pub const STARTUP_CLEANUP_AGE: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Minimum time between two cleanups on startup, commands started meanwhile
+/// skip it
+pub const STARTUP_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A temporary location created by an FVM process
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TrackedPath {
+    pub path: PathBuf,
+    /// Seconds since UNIX Epoch when the entry was created
+    pub created_at: u64,
+    /// Process ID of the FVM process who created the entry
+    pub pid: u32,
+}
+
+impl TrackedPath {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.created_at))
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct JanitorState {
+    pub entries: Vec<TrackedPath>,
+    /// Seconds since UNIX Epoch of the last cleanup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_cleanup: Option<u64>,
+}
+
+impl JanitorState {
+    fn load(state_path: &Path) -> Result<Self> {
+        Ok(
+            load_state(state_path, |contents| Ok(serde_json::from_str(contents)?))?
+                .unwrap_or_default(),
+        )
+    }
+
+    fn orphans(&self, older_than: Duration) -> Vec<TrackedPath> {
+        let mut system = System::new();
+
+        self.entries
+            .iter()
+            .filter(|entry| entry.age() >= older_than)
+            .filter(|entry| !is_running(&mut system, entry.pid))
+            .cloned()
+            .collect()
+    }
+}
+
+pub struct Janitor {
+    state_path: PathBuf,
+    state: JanitorState,
+}
+
+impl Janitor {
+    /// Opens the janitor state file from the FVM workdir.
+    ///
+    /// Returns `None` if FVM is not installed, in such case there is nothing
+    /// to keep track of.
+    pub fn open() -> Result<Option<Self>> {
+        let workdir = fvm_workdir_path()?;
+
+        if !workdir.exists() {
+            return Ok(None);
+        }
+
+        Self::open_at(workdir.join(JANITOR_STATE_FILENAME)).map(Some)
+    }
+
+    /// Opens the janitor state file at the provided path, if the file doesn't
+    /// exist an empty state is used.
+    pub fn open_at(state_path: impl Into<PathBuf>) -> Result<Self> {
+        let state_path = state_path.into();
+        let state = JanitorState::load(&state_path)?;
+
+        Ok(Self { state_path, state })
+    }
+
+    /// Records `path` as a temporary location owned by the current process
+    pub fn track(&mut self, path: &Path) -> Result<()> {
+        self.update(|state| {
+            state.entries.retain(|entry| entry.path != path);
+            state.entries.push(TrackedPath {
+                path: path.to_path_buf(),
+                created_at: now(),
+                pid: std::process::id(),
+            });
+            Ok(())
+        })
+    }
+
+    /// Stops tracking `path`, used once the temporary location is removed
+    /// or persisted.
+    pub fn untrack(&mut self, path: &Path) -> Result<()> {
+        if !self.state.entries.iter().any(|entry| entry.path == path) {
+            return Ok(());
+        }
+
+        self.update(|state| {
+            state.entries.retain(|entry| entry.path != path);
+            Ok(())
+        })
+    }
+
+    /// Retrieves entries at least as old as `older_than` whose owning process
+    /// is no longer running
+    pub fn orphans(&self, older_than: Duration) -> Vec<TrackedPath> {
+        self.state.orphans(older_than)
+    }
+
+    /// Whether the last cleanup is older than `interval`
+    pub fn cleanup_due(&self, interval: Duration) -> bool {
+        self.state
+            .last_cleanup
+            .is_none_or(|at| now().saturating_sub(at) >= interval.as_secs())
+    }
+
+    /// Removes orphaned entries at least as old as `older_than` and returns
+    /// the paths removed from the filesystem.
+    ///
+    /// When `dry_run` is set, nothing is removed and the paths that would be
+    /// removed are returned.
+    pub fn clean(&mut self, older_than: Duration, dry_run: bool) -> Result<Vec<PathBuf>> {
+        if dry_run {
+            return Ok(self
+                .orphans(older_than)
+                .into_iter()
+                .map(|orphan| orphan.path)
+                .filter(|path| path.exists())
+                .collect());
+        }
+
+        self.update(|state| {
+            let orphans = state.orphans(older_than);
+            let mut removed = Vec::with_capacity(orphans.len());
+
+            for orphan in orphans {
+                if orphan.path.exists() {
+                    remove_path(&orphan.path)?;
+                    removed.push(orphan.path.clone());
+                }
+
+                state.entries.retain(|entry| *entry != orphan);
+            }
+
+            state.last_cleanup = Some(now());
+            Ok(removed)
+        })
+    }
+
+    /// Applies `update` to the state file while holding its lock. The state
+    /// is reloaded first, so changes made by other processes since it was
+    /// opened are kept.
+    fn update<T>(&mut self, update: impl FnOnce(&mut JanitorState) -> Result<T>) -> Result<T> {
+        let _lock = self.lock()?;
+
+        self.state = JanitorState::load(&self.state_path)?;
+
+        let result = update(&mut self.state)?;
+
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Takes the exclusive lock on the state file, released on drop
+    fn lock(&self) -> Result<File> {
+        let mut lock_path = OsString::from(self.state_path.as_os_str());
+
+        lock_path.push(".lock");
+
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(PathBuf::from(lock_path))?;
+
+        file.lock()?;
+        Ok(file)
+    }
+
+    fn save(&self) -> Result<()> {
+        write_state(
+            &self.state_path,
+            &serde_json::to_string_pretty(&self.state)?,
+        )
+    }
+}
+
+/// A [`TempDir`] recorded in the janitor state file while alive.
+///
+/// If the process is interrupted before the value is dropped, the directory
+/// is kept in the janitor state file and removed by a later cleanup.
+pub struct TrackedTempDir {
+    inner: Option<TempDir>,
+}
+
+impl TrackedTempDir {
+    /// Creates a directory in the FVM scratch location, see [`fvm_tmp_path`]
+    pub fn new() -> Result<Self> {
+        let tmp_path = fvm_tmp_path()?;
+
+        create_dir_all(&tmp_path)?;
+
+        Ok(Self::track(
+            tempfile::Builder::new()
+                .prefix(TEMP_DIR_PREFIX)
+                .tempdir_in(tmp_path)?,
+        ))
+    }
+
+    pub fn new_in(dir: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::track(TempDir::new_in(dir)?))
+    }
+
+    /// Persists the directory, it won't be removed on drop nor by the janitor
+    pub fn into_path(mut self) -> PathBuf {
+        let tmp_dir = self.inner.take().expect("TempDir is present until drop");
+
+        forget(tmp_dir.path());
+        tmp_dir.into_path()
+    }
+
+    fn track(tmp_dir: TempDir) -> Self {
+        match Janitor::open() {
+            Ok(Some(mut janitor)) => {
+                if let Err(err) = janitor.track(tmp_dir.path()) {
+                    tracing::warn!(%err, "Failed to track temporary directory");
+                }
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "Failed to open janitor state"),
+        }
+
+        Self {
+            inner: Some(tmp_dir),
+        }
+    }
+}
+
+impl Deref for TrackedTempDir {
+    type Target = TempDir;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_ref().expect("TempDir is present until drop")
+    }
+}
+
+impl Drop for TrackedTempDir {
+    fn drop(&mut self) {
+        if let Some(tmp_dir) = self.inner.take() {
+            forget(tmp_dir.path());
+        }
+    }
+}
+
+/// Removes orphaned entries left behind by previous executions, at most once
+/// every [`STARTUP_CLEANUP_INTERVAL`]. Failures are logged but never prevent
+/// the command from running.
+pub fn cleanup_on_startup() {
+    match Janitor::open() {
+        Ok(Some(janitor)) if !janitor.cleanup_due(STARTUP_CLEANUP_INTERVAL) => {}
+        Ok(Some(mut janitor)) => match janitor.clean(STARTUP_CLEANUP_AGE, false) {
+            Ok(removed) => {
+                for path in removed {
+                    tracing::debug!(?path, "Removed orphaned temporary location");
+                }
+            }
+            Err(err) => tracing::warn!(%err, "Failed to clean orphaned temporary locations"),
+        },
+        Ok(None) => {}
+        Err(err) => tracing::warn!(%err, "Failed to open janitor state"),
+    }
+}
+
+/// Stops tracking `path` on a best effort basis
+fn forget(path: &Path) {
+    if let Ok(Some(mut janitor)) = Janitor::open()
+        && let Err(err) = janitor.untrack(path)
+    {
+        tracing::warn!(%err, ?path, "Failed to untrack temporary directory");
+    }
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        remove_dir_all(path)?;
+    } else {
+        remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_running(system: &mut System, pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir;
+
+    use super::*;
+
+    /// PID which is never assigned to a running process
+    const DEAD_PID: u32 = u32::MAX;
+
+    fn orphan(path: PathBuf, age: Duration) -> TrackedPath {
+        TrackedPath {
+            path,
+            created_at: now() - age.as_secs(),
+            pid: DEAD_PID,
+        }
+    }
+
+    #[test]
+    fn tracks_and_untracks_paths() {
+        let tmp = TempDir::new().unwrap();
+        let state_path = tmp.path().join(JANITOR_STATE_FILENAME);
+        let mut janitor = Janitor::open_at(&state_path).unwrap();
+
+        janitor.track(&tmp.path().join("download")).unwrap();
+
+        let reopened = Janitor::open_at(&state_path).unwrap();
+        assert_eq!(reopened.state.entries.len(), 1);
+        assert_eq!(reopened.state.entries[0].pid, std::process::id());
+
+        janitor.untrack(&tmp.path().join("download")).unwrap();
+
+        let reopened = Janitor::open_at(&state_path).unwrap();
+        assert!(reopened.state.entries.is_empty());
+    }
+
+    #[test]
+    fn cleans_only_stale_orphans() {
+        let tmp = TempDir::new().unwrap();
+        let stale = tmp.path().join("stale");
+        let fresh = tmp.path().join("fresh");
+        let owned = tmp.path().join("owned");
+
+        create_dir(&stale).unwrap();
+        create_dir(&fresh).unwrap();
+        create_dir(&owned).unwrap();
+
+        let mut janitor = Janitor::open_at(tmp.path().join(JANITOR_STATE_FILENAME)).unwrap();
+
+        janitor.state.entries = vec![
+            orphan(stale.clone(), Duration::from_secs(60 * 60 * 48)),
+            orphan(fresh.clone(), Duration::from_secs(60)),
+            TrackedPath {
+                path: owned.clone(),
+                created_at: 0,
+                pid: std::process::id(),
+            },
+        ];
+        janitor.save().unwrap();
+
+        let listed = janitor.clean(STARTUP_CLEANUP_AGE, true).unwrap();
+        assert_eq!(listed, vec![stale.clone()]);
+        assert!(stale.exists(), "dry run must not remove anything");
+
+        let removed = janitor.clean(STARTUP_CLEANUP_AGE, false).unwrap();
+        assert_eq!(removed, vec![stale.clone()]);
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+        assert!(owned.exists(), "paths owned by running processes are kept");
+        assert_eq!(janitor.state.entries.len(), 2);
+    }
+
+    #[test]
+    fn drops_entries_for_missing_paths() {
+        let tmp = TempDir::new().unwrap();
+        let mut janitor = Janitor::open_at(tmp.path().join(JANITOR_STATE_FILENAME)).unwrap();
+
+        janitor.state.entries = vec![orphan(
+            tmp.path().join("gone"),
+            Duration::from_secs(60 * 60 * 48),
+        )];
+        janitor.save().unwrap();
+
+        let removed = janitor.clean(Duration::ZERO, false).unwrap();
+
+        assert!(removed.is_empty());
+        assert!(janitor.state.entries.is_empty());
+    }
+
+    #[test]
+    fn keeps_updates_of_other_processes() {
+        let tmp = TempDir::new().unwrap();
+        let state_path = tmp.path().join(JANITOR_STATE_FILENAME);
+        let mut first = Janitor::open_at(&state_path).unwrap();
+        let mut second = Janitor::open_at(&state_path).unwrap();
+
+        first.track(&tmp.path().join("first")).unwrap();
+        second.track(&tmp.path().join("second")).unwrap();
+
+        let reopened = Janitor::open_at(&state_path).unwrap();
+        assert_eq!(reopened.state.entries.len(), 2);
+    }
+
+    #[test]
+    fn records_last_cleanup() {
+        let tmp = TempDir::new().unwrap();
+        let state_path = tmp.path().join(JANITOR_STATE_FILENAME);
+        let mut janitor = Janitor::open_at(&state_path).unwrap();
+
+        assert!(janitor.cleanup_due(STARTUP_CLEANUP_INTERVAL));
+
+        janitor.clean(STARTUP_CLEANUP_AGE, true).unwrap();
+        assert!(janitor.cleanup_due(STARTUP_CLEANUP_INTERVAL));
+
+        janitor.clean(STARTUP_CLEANUP_AGE, false).unwrap();
+
+        let reopened = Janitor::open_at(&state_path).unwrap();
+        assert!(!reopened.cleanup_due(STARTUP_CLEANUP_INTERVAL));
+        assert!(reopened.cleanup_due(Duration::ZERO));
+    }
+}
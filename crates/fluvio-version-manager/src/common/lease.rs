@@ -0,0 +1,129 @@
+//! Execution Leases
+//!
+//! Binaries executed through a version shim hold a lease on their version
+//! while running, a file named after the PID of the shim process in
+//! `~/.fvm/leases/<channel>`. Versions with live leases are not pruned, so a
+//! binary is never deleted while it runs, e.g. on shared build machines.
+//!
+//! Leases left behind by processes which were killed are detected by checking
+//! whether the process is still running, and removed.
+
+use std::fs::{create_dir_all, read_dir, remove_file, write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use sysinfo::System;
+
+use super::janitor::is_running;
+use super::workdir::fvm_workdir_path;
+
+/// Directory in the FVM workdir holding the leases of each version
+pub const FVM_LEASES_DIR: &str = "leases";
+
+/// Retrieves the path to the `~/.fvm/leases` directory
+pub fn fvm_leases_path() -> Result<PathBuf> {
+    Ok(fvm_workdir_path()?.join(FVM_LEASES_DIR))
+}
+
+/// A lease on the version `channel` held by the current process, released
+/// on drop
+#[derive(Debug)]
+pub struct ExecutionLease {
+    path: PathBuf,
+}
+
+impl ExecutionLease {
+    /// Acquires a lease on the installed version `channel`
+    pub fn acquire(channel: &str) -> Result<Self> {
+        Self::acquire_in(&fvm_leases_path()?, channel)
+    }
+
+    pub fn acquire_in(leases_path: &Path, channel: &str) -> Result<Self> {
+        let channel_path = leases_path.join(channel);
+        let path = channel_path.join(std::process::id().to_string());
+
+        create_dir_all(&channel_path)?;
+        write(&path, [])?;
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ExecutionLease {
+    fn drop(&mut self) {
+        if let Err(err) = remove_file(&self.path) {
+            tracing::debug!(%err, path = ?self.path, "Failed to release execution lease");
+        }
+    }
+}
+
+/// PIDs of the processes executing binaries from the installed version
+/// `channel`. Leases of processes no longer running are removed.
+pub fn live_executions(channel: &str) -> Result<Vec<u32>> {
+    live_executions_in(&fvm_leases_path()?, channel)
+}
+
+pub fn live_executions_in(leases_path: &Path, channel: &str) -> Result<Vec<u32>> {
+    let channel_path = leases_path.join(channel);
+    let mut system = System::new();
+    let mut pids = Vec::new();
+
+    if !channel_path.is_dir() {
+        return Ok(pids);
+    }
+
+    for entry in read_dir(&channel_path)? {
+        let path = entry?.path();
+        let Some(pid) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        if is_running(&mut system, pid) {
+            pids.push(pid);
+        } else {
+            tracing::debug!(pid, ?path, "Removing stale execution lease");
+            remove_file(&path)?;
+        }
+    }
+
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn tracks_live_executions() {
+        let tmp = TempDir::new().unwrap();
+        let lease = ExecutionLease::acquire_in(tmp.path(), "stable").unwrap();
+
+        assert_eq!(
+            live_executions_in(tmp.path(), "stable").unwrap(),
+            vec![std::process::id()]
+        );
+        assert!(live_executions_in(tmp.path(), "latest").unwrap().is_empty());
+
+        drop(lease);
+        assert!(live_executions_in(tmp.path(), "stable").unwrap().is_empty());
+    }
+
+    #[test]
+    fn removes_stale_leases() {
+        let tmp = TempDir::new().unwrap();
+        let stale = tmp.path().join("stable").join(u32::MAX.to_string());
+
+        create_dir_all(stale.parent().unwrap()).unwrap();
+        write(&stale, []).unwrap();
+
+        assert!(live_executions_in(tmp.path(), "stable").unwrap().is_empty());
+        assert!(!stale.exists());
+    }
+}
@@ -0,0 +1,170 @@
+//! Local Builds
+//!
+//! Binaries built from a Fluvio checkout, e.g. with `make build-cli`, are
+//! registered as a version, such as `0.12.0-local`, so they are switched to
+//! and run through version shims like any installed version. Binaries are
+//! copied into the version directory and their digests recorded in the
+//! version manifest, so `fvm verify` and shims detect later changes.
+
+use std::fs::{copy, create_dir_all, rename};
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use semver::Version;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::{Channel, ChecksumsManifest};
+
+use super::checksum::{ChecksumJob, verify_checksums};
+use super::executable::set_executable_mode;
+use super::janitor::TrackedTempDir;
+use super::manifest::{VersionManifest, VersionedArtifact};
+use super::provenance::Provenance;
+use super::version_directory::VersionDirectory;
+
+pub struct LocalBuild;
+
+impl LocalBuild {
+    /// Registers the binaries `names` built in `dir` as `version` under
+    /// `versions_path`, returning the checksums manifest of the binaries
+    /// along with the version directory.
+    ///
+    /// Binaries are copied into a staging directory and verified against the
+    /// digests computed in `dir`, so a build overwriting them meanwhile
+    /// never registers a mix of both builds.
+    pub fn register(
+        dir: &Path,
+        version: &Version,
+        names: &[&str],
+        versions_path: &Path,
+        force: bool,
+    ) -> Result<(ChecksumsManifest, VersionDirectory)> {
+        let checksums = ChecksumsManifest::from_local_build(dir, names)?;
+
+        if checksums.is_empty() {
+            return Err(Failure::new(
+                FailureKind::NotFound,
+                format!(
+                    "No binaries found in {}, expected any of: {}",
+                    dir.display(),
+                    names.join(", ")
+                ),
+            )
+            .into());
+        }
+
+        create_dir_all(versions_path)?;
+
+        let staging = TrackedTempDir::new_in(versions_path)?;
+        let mut jobs = Vec::with_capacity(checksums.len());
+
+        for (name, digest) in &checksums.digests {
+            let path = staging.path().join(name);
+
+            copy(dir.join(name), &path)?;
+            jobs.push(ChecksumJob {
+                name: name.to_owned(),
+                path,
+                expected: Some(digest.to_owned()),
+            });
+        }
+
+        let mut contents = Vec::with_capacity(jobs.len());
+
+        for result in verify_checksums(jobs, None)? {
+            if result.status.is_failure() {
+                bail!(
+                    "{} changed while registering the local build: {}",
+                    result.job.name,
+                    result.status
+                );
+            }
+
+            set_executable_mode(&result.job.path)?;
+
+            let mut artifact = VersionedArtifact::new(result.job.name, version.to_string());
+
+            artifact.installed_sha256_digest = result.job.expected;
+            contents.push(artifact);
+        }
+
+        let channel = Channel::Tag(version.to_owned());
+        let mut manifest = VersionManifest::new(channel.clone(), version.to_owned(), contents);
+
+        manifest.provenance = Some(Provenance::local(&channel, version));
+        manifest.write(staging.path())?;
+
+        let version_path = versions_path.join(channel.to_string());
+
+        if version_path.exists() {
+            if !force {
+                bail!(
+                    "Fluvio version {channel} is already installed at {}",
+                    version_path.display()
+                );
+            }
+
+            VersionDirectory::open(version_path.clone())?.remove()?;
+        }
+
+        rename(staging.into_path(), &version_path)?;
+
+        Ok((checksums, VersionDirectory::open(version_path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::consts::EXE_SUFFIX;
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn registers_local_builds_as_versions() {
+        let build = TempDir::new().unwrap();
+        let versions = TempDir::new().unwrap();
+        let version = Version::parse("0.12.0-local").unwrap();
+        let fluvio = format!("fluvio{EXE_SUFFIX}");
+
+        write(build.path().join(&fluvio), b"fluvio").unwrap();
+        write(build.path().join("libfluvio.rlib"), b"library").unwrap();
+
+        let (checksums, version_dir) = LocalBuild::register(
+            build.path(),
+            &version,
+            &["fluvio", "smdk"],
+            versions.path(),
+            false,
+        )
+        .unwrap();
+        let contents = version_dir.manifest.contents.as_ref().unwrap();
+
+        assert_eq!(version_dir.path, versions.path().join("0.12.0-local"));
+        assert_eq!(version_dir.contents, vec![version_dir.path.join(&fluvio)]);
+        assert_eq!(
+            contents[0].installed_sha256_digest.as_deref(),
+            checksums.get(&fluvio)
+        );
+        assert!(
+            version_dir
+                .checksum_jobs()
+                .into_iter()
+                .all(|job| job.expected.is_some())
+        );
+        assert!(
+            LocalBuild::register(build.path(), &version, &["fluvio"], versions.path(), false)
+                .is_err()
+        );
+        assert!(
+            LocalBuild::register(build.path(), &version, &["fluvio"], versions.path(), true)
+                .is_ok()
+        );
+        assert!(
+            LocalBuild::register(versions.path(), &version, &["cdk"], versions.path(), true)
+                .is_err()
+        );
+    }
+}
@@ -4,15 +4,19 @@
 //! for the binaries in a package set. It is used to determine the version of
 //! the binaries
 
-use std::fs::{read_to_string, write};
 use std::path::{PathBuf, Path};
 use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::state::{load_state, strip_checksum_footer, write_state};
 use semver::Version;
 
-use fluvio_artifacts_util::fvm::Channel;
+use fluvio_artifacts_util::fvm::{Artifact, Channel, ComponentSelection};
+
+use super::install_hooks::InstallHook;
+use super::provenance::Provenance;
 
 /// The name of the manifest file for the Package Set
 pub const PACKAGE_SET_MANIFEST_FILENAME: &str = "manifest.json";
@@ -21,6 +25,18 @@ pub const PACKAGE_SET_MANIFEST_FILENAME: &str = "manifest.json";
 pub struct VersionedArtifact {
     pub name: String,
     pub version: String,
+    /// Digest of the downloaded artifact, used to detect changed artifacts
+    /// on updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256_digest: Option<String>,
+    /// Digest of the installed binary, verified before running it through a
+    /// version shim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_sha256_digest: Option<String>,
+    /// Channel the artifact was installed from, when it differs from the
+    /// channel of the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
 }
 
 impl VersionedArtifact {
@@ -28,15 +44,36 @@ impl VersionedArtifact {
         Self {
             name: name.into(),
             version: version.into(),
+            sha256_digest: None,
+            installed_sha256_digest: None,
+            channel: None,
+        }
+    }
+
+    pub fn from_artifact(artifact: &Artifact) -> Self {
+        Self {
+            name: artifact.name.to_owned(),
+            version: artifact.version.to_string(),
+            sha256_digest: artifact.sha256_digest.to_owned(),
+            installed_sha256_digest: None,
+            channel: artifact.channel.to_owned(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct VersionManifest {
     pub channel: Channel,
     pub version: Version,
     pub contents: Option<Vec<VersionedArtifact>>,
+    /// Assets placed outside of the version directory on install, removed
+    /// on uninstall
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Vec<InstallHook>>,
+    /// How the version was resolved, `None` for versions installed by older
+    /// FVM releases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 impl VersionManifest {
@@ -45,14 +82,31 @@ impl VersionManifest {
             channel,
             version,
             contents: Some(contents),
+            hooks: None,
+            provenance: None,
         }
     }
 
-    /// Opens the `manifest.json` file and parses it into a `VersionManifest` struct
+    /// Opens the `manifest.json` file and parses it into a `VersionManifest` struct.
+    /// A corrupt manifest is moved aside and reported as missing.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
-        let contents = read_to_string(path)?;
+        let path = path.as_ref();
+
+        load_state(path, |contents| contents.parse())?
+            .ok_or_else(|| anyhow!("Version manifest {} is missing or corrupt", path.display()))
+    }
 
-        Ok(serde_json::from_str(&contents)?)
+    /// Artifacts installed from other channels than the manifest channel
+    pub fn components(&self) -> Vec<ComponentSelection> {
+        self.contents
+            .iter()
+            .flatten()
+            .filter_map(|artifact| {
+                let channel = artifact.channel.clone()?;
+
+                Some(ComponentSelection::new(artifact.name.clone(), channel))
+            })
+            .collect()
     }
 
     /// Writes the JSON representation of the `VersionManifest` to the
@@ -61,7 +115,7 @@ impl VersionManifest {
         let json = serde_json::to_string_pretty(self)?;
         let path = path.as_ref().join(PACKAGE_SET_MANIFEST_FILENAME);
 
-        write(&path, json)?;
+        write_state(&path, &json)?;
         Ok(path)
     }
 }
@@ -70,14 +124,18 @@ impl FromStr for VersionManifest {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(serde_json::from_str(s)?)
+        Ok(serde_json::from_str(strip_checksum_footer(s)?)?)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::fs::{read_dir, read_to_string, write};
+
     use tempfile::TempDir;
 
+    use fluvio_artifacts_util::state::CHECKSUM_FOOTER_PREFIX;
+
     use super::*;
 
     #[test]
@@ -100,8 +158,9 @@ mod test {
         );
         let manifest = version_manifest.write(tempdir.path()).unwrap();
         let have = read_to_string(manifest).unwrap();
+        let (body, _) = have.split_once(CHECKSUM_FOOTER_PREFIX).unwrap();
 
-        assert_eq!(have, WANT);
+        assert_eq!(body, format!("{WANT}\n"));
     }
 
     #[test]
@@ -119,6 +178,44 @@ mod test {
         assert_eq!(json, serde_json::to_string_pretty(&read_manifest).unwrap());
     }
 
+    #[test]
+    fn records_components_from_other_channels() {
+        let mut smdk = VersionedArtifact::new("smdk", "0.12.0");
+
+        smdk.channel = Some(Channel::Latest);
+
+        let manifest = VersionManifest::new(
+            Channel::Stable,
+            Version::new(0, 11, 8),
+            vec![VersionedArtifact::new("fluvio", "0.11.8"), smdk],
+        );
+        let json = serde_json::to_string(&manifest).unwrap();
+
+        assert!(json.contains(r#""name":"smdk","version":"0.12.0","channel":"latest""#));
+        assert_eq!(
+            json.parse::<VersionManifest>().unwrap().components(),
+            vec![ComponentSelection::new("smdk", Channel::Latest)]
+        );
+    }
+
+    #[test]
+    fn moves_tampered_manifest_aside() {
+        let tempdir = TempDir::new().unwrap();
+        let version_manifest = VersionManifest::new(
+            Channel::Stable,
+            Version::new(0, 8, 0),
+            vec![VersionedArtifact::new("fluvio", "0.11.4")],
+        );
+        let manifest = version_manifest.write(tempdir.path()).unwrap();
+        let tampered = read_to_string(&manifest).unwrap().replace("0.8.0", "0.9.0");
+
+        write(&manifest, tampered).unwrap();
+
+        assert!(VersionManifest::open(&manifest).is_err());
+        assert!(!manifest.exists());
+        assert_eq!(read_dir(tempdir.path()).unwrap().count(), 1);
+    }
+
     #[test]
     fn fails_to_read_manifest_from_invalid_json() {
         const INVALID_MANIFEST: &str = r#"{
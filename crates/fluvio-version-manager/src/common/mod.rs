@@ -1,10 +1,37 @@
+pub mod advisories;
+pub mod checksum;
+pub mod cluster_compatibility;
+pub mod environment;
+pub mod eol;
+pub mod exit_code;
+pub mod github;
 pub mod executable;
+pub mod install_hooks;
+pub mod install_profile;
+pub mod integrity;
+pub mod janitor;
+pub mod lease;
+pub mod local_build;
 pub mod manifest;
+pub mod notification;
 pub mod notify;
+pub mod plugin;
+pub mod project_version;
+pub mod provenance;
+pub mod remote_versions;
+pub mod requirements;
 pub mod settings;
+pub mod shim;
+pub mod shell_profile;
+pub mod toolchain_cache;
+pub mod transparency;
 pub mod update_manager;
+pub mod usage;
+pub mod verification_report;
+pub mod version_archive;
 pub mod version_directory;
 pub mod version_installer;
+pub mod version_policy;
 pub mod workdir;
 
 use std::path::PathBuf;
@@ -18,7 +45,7 @@ use anyhow::{Error, Result};
 pub const TARGET: &str = env!("TARGET");
 
 /// Wrapper on `dirs::home_dir` which returns `anyhow::Error` instead of `Option`.
-pub(super) fn home_dir() -> Result<PathBuf> {
+pub fn home_dir() -> Result<PathBuf> {
     if let Some(home_dir) = dirs::home_dir() {
         Ok(home_dir)
     } else {
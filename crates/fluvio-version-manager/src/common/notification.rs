@@ -0,0 +1,423 @@
+//! Notification Sinks
+//!
+//! [`Notify`](super::notify::Notify) prints messages to the terminal. The
+//! messages of long operations, such as installs, and their completion can
+//! also be sent to sinks configured in the `[notifications]` table of the
+//! `settings.toml` file, so unattended provisioning scripts get completion
+//! and failure signals without parsing the output:
+//!
+//! ```toml
+//! [notifications]
+//! jsonl = "/var/log/fvm.jsonl"
+//! desktop = true
+//! desktop_min_duration = "1m"
+//! webhook_url = "https://ci.example.com/hooks/fvm"
+//! ```
+//!
+//! - `jsonl` appends each message and the completion as a JSON line,
+//!   overridden by `FVM_NOTIFY_JSONL`
+//! - `desktop` shows an OS notification when an operation which took longer
+//!   than `desktop_min_duration`, 30 seconds by default, completes
+//! - `webhook_url` receives the completion as a JSON `POST`, overridden by
+//!   `FVM_NOTIFY_WEBHOOK_URL`
+//!
+//! Sinks receive messages even with `--quiet`, stripped of terminal styling.
+//! Failing to deliver a notification never fails the command.
+
+use std::fs::{OpenOptions, create_dir_all};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::htclient::{self, Request};
+
+/// Environment variable overriding the JSONL file of the settings
+pub const FVM_NOTIFY_JSONL: &str = "FVM_NOTIFY_JSONL";
+
+/// Environment variable overriding the webhook URL of the settings
+pub const FVM_NOTIFY_WEBHOOK_URL: &str = "FVM_NOTIFY_WEBHOOK_URL";
+
+/// Duration after which a completed operation shows a desktop notification
+pub const DEFAULT_DESKTOP_MIN_DURATION: Duration = Duration::from_secs(30);
+
+/// Commands whose messages and completion are sent to the sinks, others
+/// complete too quickly to be worth a notification
+pub const LONG_OPERATIONS: &[&str] = &[
+    "clone-to",
+    "import",
+    "init",
+    "install",
+    "repair-cache",
+    "uninstall",
+    "update",
+    "verify",
+    "verify-cache",
+];
+
+static SINKS: OnceLock<Vec<NotifySink>> = OnceLock::new();
+
+/// The `[notifications]` table of the `settings.toml` file
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NotificationSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jsonl: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub desktop: bool,
+    /// e.g. `1m`, parsed with `humantime`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop_min_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+impl NotificationSettings {
+    /// Sinks configured by these settings
+    pub fn sinks(&self) -> Result<Vec<NotifySink>> {
+        let mut sinks = Vec::new();
+
+        if let Some(path) = &self.jsonl {
+            sinks.push(NotifySink::Jsonl(path.to_owned()));
+        }
+
+        if self.desktop {
+            let min_duration = match &self.desktop_min_duration {
+                Some(duration) => humantime::parse_duration(duration)?,
+                None => DEFAULT_DESKTOP_MIN_DURATION,
+            };
+
+            sinks.push(NotifySink::Desktop { min_duration });
+        }
+
+        if let Some(url) = &self.webhook_url {
+            sinks.push(NotifySink::Webhook(url.to_owned()));
+        }
+
+        Ok(sinks)
+    }
+}
+
+/// A destination of notifications besides the terminal
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotifySink {
+    /// Appends every event to a JSON lines file
+    Jsonl(PathBuf),
+    /// Shows an OS notification for completions which took at least
+    /// `min_duration`
+    Desktop { min_duration: Duration },
+    /// Posts completions to a URL
+    Webhook(String),
+}
+
+impl NotifySink {
+    /// Delivers `event`, sinks ignore the events they are not interested in
+    async fn deliver(&self, event: &NotifyEvent) -> Result<()> {
+        match (self, event) {
+            (Self::Jsonl(path), _) => append_jsonl(path, event),
+            (Self::Desktop { min_duration }, NotifyEvent::Completed { duration_ms, .. })
+                if Duration::from_millis(*duration_ms) >= *min_duration =>
+            {
+                show_desktop_notification(&event.to_string())
+            }
+            (Self::Webhook(url), NotifyEvent::Completed { .. }) => post_webhook(url, event).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyLevel {
+    Info,
+    Done,
+    Warn,
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionStatus {
+    Succeeded,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotifyEvent {
+    /// A message printed with [`Notify`](super::notify::Notify)
+    Message { level: NotifyLevel, message: String },
+    /// The command completed
+    Completed {
+        command: String,
+        status: CompletionStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        duration_ms: u64,
+    },
+}
+
+impl std::fmt::Display for NotifyEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Message { message, .. } => write!(f, "{message}"),
+            Self::Completed {
+                command,
+                status: CompletionStatus::Succeeded,
+                ..
+            } => write!(f, "fvm {command} succeeded"),
+            Self::Completed {
+                command,
+                error: Some(error),
+                ..
+            } => write!(f, "fvm {command} failed: {error}"),
+            Self::Completed { command, .. } => write!(f, "fvm {command} failed"),
+        }
+    }
+}
+
+/// An event as written to JSON lines files and posted to webhooks
+#[derive(Serialize)]
+struct Record<'a> {
+    /// RFC 3339 timestamp
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a NotifyEvent,
+}
+
+impl<'a> Record<'a> {
+    fn now(event: &'a NotifyEvent) -> Self {
+        Self {
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            event,
+        }
+    }
+}
+
+/// Sends the events of this process to `sinks`, only the first call has an
+/// effect
+pub fn install_sinks(sinks: Vec<NotifySink>) {
+    if SINKS.set(sinks).is_err() {
+        tracing::debug!("Notification sinks already installed");
+    }
+}
+
+/// Writes a message to the sinks recording messages, see
+/// [`NotifySink::Jsonl`]
+pub(crate) fn record_message(level: NotifyLevel, message: &str) {
+    let Some(sinks) = SINKS.get() else {
+        return;
+    };
+    let event = NotifyEvent::Message {
+        level,
+        message: strip_styling(message),
+    };
+
+    for sink in sinks {
+        if let NotifySink::Jsonl(path) = sink
+            && let Err(err) = append_jsonl(path, &event)
+        {
+            tracing::debug!(%err, ?path, "Failed to record notification");
+        }
+    }
+}
+
+/// Sends the completion of `command` to the installed sinks
+pub async fn notify_completion(command: &str, result: &Result<()>, duration: Duration) {
+    let Some(sinks) = SINKS.get() else {
+        return;
+    };
+    let event = NotifyEvent::Completed {
+        command: command.to_string(),
+        status: match result {
+            Ok(()) => CompletionStatus::Succeeded,
+            Err(_) => CompletionStatus::Failed,
+        },
+        error: result
+            .as_ref()
+            .err()
+            .map(|err| strip_styling(&err.to_string())),
+        duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+    };
+
+    for sink in sinks {
+        if let Err(err) = sink.deliver(&event).await {
+            tracing::warn!(%err, ?sink, "Failed to deliver completion notification");
+        }
+    }
+}
+
+fn append_jsonl(path: &Path, event: &NotifyEvent) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_vec(&Record::now(event))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    line.push(b'\n');
+    // A single write keeps lines whole when processes share the file
+    file.write_all(&line)?;
+
+    Ok(())
+}
+
+async fn post_webhook(url: &str, event: &NotifyEvent) -> Result<()> {
+    let body = serde_json::to_vec(&Record::now(event))?;
+    let request = Request::post(url)
+        .header(htclient::http::header::CONTENT_TYPE, "application/json")
+        .body(body)?;
+    let response = htclient::send(request).await?;
+
+    if !response.status().is_success() {
+        bail!("Webhook responded with status {}", response.status());
+    }
+
+    Ok(())
+}
+
+fn show_desktop_notification(message: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"FVM\"",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        let mut command = Command::new("osascript");
+
+        command.args(["-e", &script]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("notify-send");
+
+        command.args(["FVM", message]);
+        command
+    } else {
+        tracing::debug!("Desktop notifications are not supported on this platform");
+        return Ok(());
+    };
+
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+
+    Ok(())
+}
+
+/// Removes the ANSI escape sequences `colored` styles messages with
+fn strip_styling(message: &str) -> String {
+    let mut stripped = String::with_capacity(message.len());
+    let mut chars = message.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter, e.g. `\x1b[1m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use fluvio_artifacts_util::fixture::{FixtureResponse, FixtureServer};
+
+    use super::*;
+
+    #[test]
+    fn builds_sinks_from_settings() {
+        let settings: NotificationSettings = toml::from_str(
+            r#"
+            jsonl = "fvm.jsonl"
+            desktop = true
+            desktop_min_duration = "1m"
+            webhook_url = "https://ci.example.com/hooks/fvm"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.sinks().unwrap(),
+            vec![
+                NotifySink::Jsonl(PathBuf::from("fvm.jsonl")),
+                NotifySink::Desktop {
+                    min_duration: Duration::from_secs(60)
+                },
+                NotifySink::Webhook("https://ci.example.com/hooks/fvm".to_string()),
+            ]
+        );
+        assert!(NotificationSettings::default().sinks().unwrap().is_empty());
+    }
+
+    #[fluvio_future::test]
+    async fn delivers_events_to_sinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jsonl = tmp.path().join("logs").join("fvm.jsonl");
+        let server = FixtureServer::start().unwrap();
+        let message = NotifyEvent::Message {
+            level: NotifyLevel::Warn,
+            message: strip_styling("Installing \x1b[1m0.11.8\x1b[0m"),
+        };
+        let completed = NotifyEvent::Completed {
+            command: "install".to_string(),
+            status: CompletionStatus::Failed,
+            error: Some("checksum mismatch".to_string()),
+            duration_ms: 1200,
+        };
+
+        server.route("/hooks/fvm", FixtureResponse::json(&serde_json::json!({})));
+
+        let webhook = NotifySink::Webhook(format!("{}/hooks/fvm", server.url()));
+
+        for event in [&message, &completed] {
+            NotifySink::Jsonl(jsonl.clone())
+                .deliver(event)
+                .await
+                .unwrap();
+            webhook.deliver(event).await.unwrap();
+        }
+
+        let lines: Vec<serde_json::Value> = read_to_string(&jsonl)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "message");
+        assert_eq!(lines[0]["message"], "Installing 0.11.8");
+        assert_eq!(lines[1]["status"], "failed");
+        assert_eq!(lines[1]["error"], "checksum mismatch");
+        assert!(lines[1]["timestamp"].is_string());
+        assert_eq!(
+            completed.to_string(),
+            "fvm install failed: checksum mismatch"
+        );
+
+        // Only the completion is posted
+        let requests = server.requests();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].target, "/hooks/fvm");
+
+        let unreachable = NotifySink::Webhook(format!("{}/missing", server.url()));
+
+        assert!(unreachable.deliver(&completed).await.is_err());
+    }
+}
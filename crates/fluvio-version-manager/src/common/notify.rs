@@ -1,5 +1,9 @@
 use colored::Colorize;
 
+use fluvio_artifacts_util::progress::LineSink;
+
+use super::notification::{NotifyLevel, record_message};
+
 #[derive(Copy, Clone, Debug)]
 pub struct Notify {
     /// Whether to suppress all output
@@ -12,24 +16,44 @@ impl Notify {
     }
 
     pub fn info(&self, message: impl AsRef<str>) {
+        record_message(NotifyLevel::Info, message.as_ref());
+
         if !self.quiet {
             println!("{}: {}", "info".blue().bold(), message.as_ref());
         }
     }
 
     pub fn done(&self, message: impl AsRef<str>) {
+        record_message(NotifyLevel::Done, message.as_ref());
+
         if !self.quiet {
             println!("{}: {}", "done".green().bold(), message.as_ref());
         }
     }
 
     pub fn warn(&self, message: impl AsRef<str>) {
+        record_message(NotifyLevel::Warn, message.as_ref());
+
         if !self.quiet {
             println!("{}: {}", "warn".yellow().bold(), message.as_ref());
         }
     }
 
+    /// Whether all output is suppressed
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Sink writing progress lines as info notifications
+    pub fn line_sink(&self) -> LineSink {
+        let notify = *self;
+
+        LineSink::new(move |line| notify.info(line))
+    }
+
     pub fn help(&self, message: impl AsRef<str>) {
+        record_message(NotifyLevel::Help, message.as_ref());
+
         if !self.quiet {
             println!("{}: {}", "help".purple().bold(), message.as_ref());
         }
@@ -0,0 +1,352 @@
+//! FVM Plugins
+//!
+//! Like `cargo` and `git`, unknown subcommands are dispatched to external
+//! executables: `fvm audit` runs `fvm-audit` from the plugins directory,
+//! `~/.fvm/plugins`, or from `PATH`. Plugins installed from the Hub live in
+//! the plugins directory, which takes precedence over `PATH`.
+
+use std::collections::BTreeMap;
+use std::env::{split_paths, var_os};
+use std::ffi::OsString;
+use std::fs::{create_dir_all, read_dir};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Result, bail};
+use semver::Version;
+
+use fluvio_artifacts_util::hub::{
+    HubTokenStore, binary_package_uri, check_package_compatibility, get_package, save_package,
+    verify_package_published_at,
+};
+use fluvio_artifacts_util::timestamp::TimestampTrust;
+use fluvio_artifacts_util::verification::{VERIFICATION_REPORT_FILENAME, VerifiedDownload};
+
+use super::TARGET;
+use super::executable::set_executable_mode;
+use super::settings::Settings;
+use super::verification_report::{fvm_report, verification_reports_enabled};
+use super::workdir::{FVM_WORKSPACE_ENV_VAR, fvm_bin_path, fvm_workdir_path, fvm_workspace_root};
+
+/// Prefix of plugin executables, e.g. `fvm-doctor`
+pub const PLUGIN_PREFIX: &str = "fvm-";
+
+/// FVM Plugins Directory Name
+pub const FVM_PLUGINS_DIR: &str = "plugins";
+
+/// Default Hub the plugins are installed from
+pub const DEFAULT_PLUGIN_REMOTE: &str = "https://hub.infinyon.cloud";
+
+/// Hub Remote Environment Variable, overrides [`DEFAULT_PLUGIN_REMOTE`]
+pub const FVM_HUB_REMOTE_ENV_VAR: &str = "FVM_HUB_REMOTE";
+
+/// Path to the FVM binary, passed to plugins so they can call back into FVM
+pub const FVM_BIN_ENV_VAR: &str = "FVM_BIN";
+
+/// An external `fvm-<name>` executable
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plugin {
+    /// Subcommand name, without the `fvm-` prefix
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Retrieves the path to the `~/.fvm/plugins` directory
+pub fn fvm_plugins_path() -> Result<PathBuf> {
+    Ok(fvm_workdir_path()?.join(FVM_PLUGINS_DIR))
+}
+
+/// Directories searched for plugins: the plugins directory, then `PATH`
+pub fn plugin_search_dirs() -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![fvm_plugins_path()?];
+
+    if let Some(path) = var_os("PATH") {
+        dirs.extend(split_paths(&path));
+    }
+
+    Ok(dirs)
+}
+
+/// File name of the executable for the plugin `name`
+pub fn plugin_file_name(name: &str) -> String {
+    format!("{PLUGIN_PREFIX}{name}{}", std::env::consts::EXE_SUFFIX)
+}
+
+/// Parses a plugin name out of an executable file name
+fn parse_plugin_file_name(file_name: &str) -> Option<String> {
+    let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+    let name = name
+        .strip_suffix(std::env::consts::EXE_SUFFIX)
+        .unwrap_or(name);
+
+    if name.is_empty() || name.contains('.') {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+/// Finds the executable for the plugin `name`
+pub fn find_plugin(name: &str) -> Result<Option<PathBuf>> {
+    Ok(find_plugin_in(name, &plugin_search_dirs()?))
+}
+
+fn find_plugin_in(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let file_name = plugin_file_name(name);
+
+    dirs.iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// Lists the plugins available, sorted by name. When a plugin is found more
+/// than once, the one which would be dispatched to is listed.
+pub fn list_plugins() -> Result<Vec<Plugin>> {
+    Ok(list_plugins_in(&plugin_search_dirs()?))
+}
+
+fn list_plugins_in(dirs: &[PathBuf]) -> Vec<Plugin> {
+    let mut plugins = BTreeMap::new();
+
+    for dir in dirs {
+        let Ok(entries) = read_dir(dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if !path.is_file() {
+                continue;
+            }
+
+            if let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(parse_plugin_file_name)
+            {
+                plugins.entry(name).or_insert(path);
+            }
+        }
+    }
+
+    plugins
+        .into_iter()
+        .map(|(name, path)| Plugin { name, path })
+        .collect()
+}
+
+/// Runs the plugin `name` with `args`, returning the exit code
+pub fn run_plugin(name: &str, args: Vec<OsString>) -> Result<i32> {
+    let Some(path) = find_plugin(name)? else {
+        bail!(
+            "no such command: `{name}`, no `{}` executable was found in {} or PATH. Run `fvm --help` for the available commands",
+            plugin_file_name(name),
+            fvm_plugins_path()?.display()
+        );
+    };
+    let mut command = Command::new(path);
+
+    command.args(args);
+
+    if let Ok(fvm_bin) = std::env::current_exe().or_else(|_| fvm_bin_path()) {
+        command.env(FVM_BIN_ENV_VAR, fvm_bin);
+    }
+
+    if let Some(root) = fvm_workspace_root() {
+        command.env(FVM_WORKSPACE_ENV_VAR, root);
+    }
+
+    let status = command.status()?;
+
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Hub package of a plugin, written as `group/name[@version]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PluginPackage {
+    pub group: String,
+    /// Plugin name, without the `fvm-` prefix
+    pub name: String,
+    pub version: String,
+}
+
+impl PluginPackage {
+    /// Name of the package on the Hub, e.g. `fvm-doctor`
+    pub fn package_name(&self) -> String {
+        format!("{PLUGIN_PREFIX}{}", self.name)
+    }
+}
+
+impl std::str::FromStr for PluginPackage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (package, version) = match s.split_once('@') {
+            Some((package, version)) => (package, version),
+            None => (s, "latest"),
+        };
+        let Some((group, name)) = package.split_once('/') else {
+            bail!("invalid plugin \"{s}\", expected <group>/<name>[@<version>]");
+        };
+        let name = name.strip_prefix(PLUGIN_PREFIX).unwrap_or(name);
+
+        if group.is_empty() || name.is_empty() || version.is_empty() || name.contains('/') {
+            bail!("invalid plugin \"{s}\", expected <group>/<name>[@<version>]");
+        }
+
+        Ok(Self {
+            group: group.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+/// Downloads the plugin `package` from `remote` into the plugins directory,
+/// returning the path of the installed executable. Packages declaring they
+/// are incompatible with this host or the active Fluvio version are
+/// rejected, as are packages whose publish date proof doesn't verify with
+/// the `transparency_logs` of the settings. When verification reports
+/// are enabled, the report is written next to the executable, e.g.
+/// `fvm-doctor.verification-report.json`.
+pub async fn install_plugin(package: &PluginPackage, remote: &str) -> Result<PathBuf> {
+    let uri = binary_package_uri(
+        remote,
+        &package.group,
+        &package.package_name(),
+        &package.version,
+        TARGET,
+    );
+    let store_path = HubTokenStore::default_path()?;
+    let mut store = HubTokenStore::load(&store_path)?;
+    let response = get_package(&uri, remote, &package.group, &mut store, &store_path).await?;
+    let platform_version =
+        Settings::configured_version()?.and_then(|version| Version::parse(&version).ok());
+
+    check_package_compatibility(response.body(), TARGET, platform_version.as_ref())?;
+
+    if let Some(logs) = Settings::configured_transparency_logs()?
+        && let Some(proven_at) =
+            verify_package_published_at(response.body(), &TimestampTrust { logs })?
+    {
+        tracing::debug!(%proven_at, plugin = %package.name, "Verified plugin publish date");
+    }
+
+    let plugins_path = fvm_plugins_path()?;
+
+    create_dir_all(&plugins_path)?;
+
+    let path = plugins_path.join(plugin_file_name(&package.name));
+
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    save_package(response.body(), &path)?;
+    set_executable_mode(&path)?;
+
+    if verification_reports_enabled()? {
+        let mut report = fvm_report(
+            format!(
+                "{}/{}@{}",
+                package.group,
+                package.package_name(),
+                package.version
+            ),
+            TARGET,
+        );
+
+        report.push(VerifiedDownload::from_bytes(
+            package.package_name(),
+            &package.version,
+            &uri,
+            None,
+            response.body(),
+        )?);
+        report.write(plugins_path.join(format!(
+            "{}.{VERIFICATION_REPORT_FILENAME}",
+            plugin_file_name(&package.name)
+        )))?;
+    }
+
+    Ok(path)
+}
+
+/// Whether `path` is in the plugins directory, as opposed to `PATH`
+pub fn is_installed_plugin(path: &Path) -> bool {
+    fvm_plugins_path().is_ok_and(|plugins_path| path.starts_with(plugins_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn parses_plugin_file_names() {
+        assert_eq!(
+            parse_plugin_file_name(&plugin_file_name("doctor")),
+            Some("doctor".to_string())
+        );
+        assert_eq!(parse_plugin_file_name("fvm-"), None);
+        assert_eq!(parse_plugin_file_name("fvm-doctor.sh"), None);
+        assert_eq!(parse_plugin_file_name("fluvio"), None);
+    }
+
+    #[test]
+    fn finds_plugins_in_search_order() {
+        let plugins_dir = TempDir::new().unwrap();
+        let path_dir = TempDir::new().unwrap();
+        let dirs = vec![
+            plugins_dir.path().to_path_buf(),
+            path_dir.path().to_path_buf(),
+        ];
+
+        write(path_dir.path().join(plugin_file_name("doctor")), "").unwrap();
+        write(path_dir.path().join(plugin_file_name("audit")), "").unwrap();
+        write(plugins_dir.path().join(plugin_file_name("doctor")), "").unwrap();
+        write(plugins_dir.path().join("fluvio"), "").unwrap();
+
+        assert_eq!(
+            find_plugin_in("doctor", &dirs),
+            Some(plugins_dir.path().join(plugin_file_name("doctor")))
+        );
+        assert_eq!(find_plugin_in("missing", &dirs), None);
+
+        let plugins = list_plugins_in(&dirs);
+        let names: Vec<&str> = plugins.iter().map(|plugin| plugin.name.as_str()).collect();
+
+        assert_eq!(names, vec!["audit", "doctor"]);
+        assert!(plugins[1].path.starts_with(plugins_dir.path()));
+    }
+
+    #[test]
+    fn parses_plugin_packages() {
+        let package: PluginPackage = "infinyon/fvm-doctor@0.1.0".parse().unwrap();
+
+        assert_eq!(package.group, "infinyon");
+        assert_eq!(package.name, "doctor");
+        assert_eq!(package.version, "0.1.0");
+        assert_eq!(package.package_name(), "fvm-doctor");
+        assert_eq!(
+            binary_package_uri(
+                "https://hub.example.com/",
+                &package.group,
+                &package.package_name(),
+                &package.version,
+                "x86_64-unknown-linux-musl"
+            ),
+            "https://hub.example.com/hub/v1/bpkg/download/infinyon/fvm-doctor/0.1.0/x86_64-unknown-linux-musl"
+        );
+        assert_eq!(
+            "acme/audit".parse::<PluginPackage>().unwrap().version,
+            "latest"
+        );
+        assert!("doctor".parse::<PluginPackage>().is_err());
+        assert!("acme/".parse::<PluginPackage>().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Project Version
+//!
+//! Projects pin the Fluvio version they expect by committing a
+//! `.fvm-version` file at their root, holding a channel such as `stable`,
+//! `0.11` or `0.11.8`. The shell prompt hook installed by
+//! `fvm setup --prompt` warns when the active version does not satisfy the
+//! version pinned by the project of the working directory.
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::Channel;
+
+/// Name of the file pinning the Fluvio version of a project
+pub const PROJECT_VERSION_FILENAME: &str = ".fvm-version";
+
+/// Fluvio version pinned by a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectVersion {
+    /// Path to the `.fvm-version` file
+    pub path: PathBuf,
+    pub channel: Channel,
+}
+
+impl ProjectVersion {
+    /// Finds the version pinned by the closest `.fvm-version` file in `dir`
+    /// or its ancestors
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        let Some(path) = dir
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_VERSION_FILENAME))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let contents = read_to_string(&path)?;
+        let channel = Channel::parse(contents.trim())
+            .with_context(|| format!("Invalid Fluvio version in {}", path.display()))?;
+
+        Ok(Some(Self { path, channel }))
+    }
+
+    /// Whether the active `channel`, resolved to `version`, satisfies the
+    /// pinned version. Versions satisfy the tags and minor channels they
+    /// belong to, so projects pinning `0.11` accept a `stable` install of
+    /// `0.11.8`.
+    pub fn is_satisfied_by(&self, channel: &Channel, version: Option<&Version>) -> bool {
+        match (&self.channel, version) {
+            (Channel::Tag(pinned), Some(version)) => pinned == version,
+            (Channel::Minor(major, minor), Some(version)) => {
+                version.major == *major && version.minor == *minor
+            }
+            (pinned, _) => pinned == channel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use super::*;
+
+    #[test]
+    fn finds_closest_pinned_version() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("connectors").join("http-source");
+
+        create_dir_all(&nested).unwrap();
+        assert_eq!(ProjectVersion::find(&nested).unwrap(), None);
+
+        write(root.path().join(PROJECT_VERSION_FILENAME), "0.11\n").unwrap();
+
+        let pinned = ProjectVersion::find(&nested).unwrap().unwrap();
+        let stable = Some(Version::new(0, 11, 8));
+
+        assert_eq!(pinned.path, root.path().join(PROJECT_VERSION_FILENAME));
+        assert!(pinned.is_satisfied_by(&Channel::Stable, stable.as_ref()));
+        assert!(!pinned.is_satisfied_by(&Channel::Stable, Some(&Version::new(0, 12, 0))));
+
+        write(nested.join(PROJECT_VERSION_FILENAME), "latest").unwrap();
+
+        let pinned = ProjectVersion::find(&nested).unwrap().unwrap();
+
+        assert!(pinned.is_satisfied_by(&Channel::Latest, None));
+        assert!(!pinned.is_satisfied_by(&Channel::Stable, stable.as_ref()));
+
+        write(nested.join(PROJECT_VERSION_FILENAME), "stabel").unwrap();
+        assert!(ProjectVersion::find(&nested).is_err());
+    }
+}
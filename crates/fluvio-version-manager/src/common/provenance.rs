@@ -0,0 +1,117 @@
+//! Version Provenance
+//!
+//! Records how an installed Fluvio Version was resolved: the channel it was
+//! installed from, the release tag and commit it was resolved to, and the
+//! FVM version which resolved it. The provenance is stored in the version
+//! manifest, and printed by `fvm provenance` so build systems can stamp
+//! their artifacts with the exact toolchain used.
+
+use std::fmt::Display;
+use std::time::SystemTime;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::fvm::{Channel, PackageSet, ReleaseInfo};
+
+use super::TARGET;
+use super::verification_report::FVM_VERSION;
+
+/// How an installed version was resolved
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Provenance {
+    /// Channel the version was installed from, e.g. `stable`
+    pub channel: Channel,
+    /// Version the channel resolved to
+    pub version: Version,
+    pub arch: String,
+    /// Release the version was resolved from, `None` for versions imported
+    /// from archives or registered from local builds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseInfo>,
+    /// Tool which resolved the version, e.g. `fvm 0.11.8`
+    pub resolver: String,
+    /// When the version was resolved, in RFC 3339 format
+    pub resolved_at: String,
+}
+
+impl Provenance {
+    /// Provenance of `package_set` resolved from `channel` by this FVM now
+    pub fn resolve(channel: &Channel, package_set: &PackageSet) -> Self {
+        Self {
+            channel: channel.to_owned(),
+            version: package_set.pkgset.clone(),
+            arch: package_set.arch.clone(),
+            release: package_set.release.clone(),
+            resolver: format!("fvm {}", FVM_VERSION.trim()),
+            resolved_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        }
+    }
+
+    /// Provenance of a local build of `version` registered by this FVM now
+    pub fn local(channel: &Channel, version: &Version) -> Self {
+        Self {
+            channel: channel.to_owned(),
+            version: version.to_owned(),
+            arch: TARGET.to_string(),
+            release: None,
+            resolver: format!("fvm {}", FVM_VERSION.trim()),
+            resolved_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        }
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "channel: {}", self.channel)?;
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "arch: {}", self.arch)?;
+
+        if let Some(release) = &self.release {
+            writeln!(f, "release: {}", release.tag)?;
+
+            if let Some(commit) = &release.commit {
+                writeln!(f, "commit: {commit}")?;
+            }
+
+            if let Some(published_at) = &release.published_at {
+                writeln!(f, "published at: {}", published_at.to_rfc3339())?;
+            }
+        }
+
+        writeln!(f, "resolver: {}", self.resolver)?;
+        write!(f, "resolved at: {}", self.resolved_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_provenance_of_package_set() {
+        let package_set = PackageSet {
+            pkgset: Version::new(0, 11, 8),
+            arch: "aarch64-apple-darwin".to_string(),
+            release: Some(ReleaseInfo {
+                tag: "v0.11.8".to_string(),
+                commit: Some("4c1e7c4bc3f3a1d5b5a1b0a1e2d3c4b5a6f7e8d9".to_string()),
+                published_at: None,
+            }),
+            artifacts: Vec::new(),
+        };
+        let provenance = Provenance::resolve(&Channel::Stable, &package_set);
+        let printed = provenance.to_string();
+
+        assert_eq!(provenance.version, Version::new(0, 11, 8));
+        assert!(provenance.resolver.starts_with("fvm "));
+        assert!(printed.contains("release: v0.11.8"));
+        assert!(printed.contains("commit: 4c1e7c4bc3f3a1d5b5a1b0a1e2d3c4b5a6f7e8d9"));
+        assert!(!printed.contains("published at"));
+        assert_eq!(
+            serde_json::from_str::<Provenance>(&serde_json::to_string(&provenance).unwrap())
+                .unwrap(),
+            provenance
+        );
+    }
+}
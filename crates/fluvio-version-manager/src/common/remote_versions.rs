@@ -0,0 +1,128 @@
+//! Remote Channel Versions
+//!
+//! Versions channels resolved to the last time FVM fetched them, when
+//! installing or updating. `fvm list` reads them to mark channels with an
+//! available update without reaching the network. Versions are cached in the
+//! `remote_versions.json` file in the FVM cache directory.
+
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use anyhow::Result;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::fvm::Channel;
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::workdir::{fvm_layout, fvm_workdir_path};
+
+/// The name of the remote versions cache file stored in the cache directory
+pub const REMOTE_VERSIONS_CACHE_FILENAME: &str = "remote_versions.json";
+
+/// Version of each channel, keyed by channel name
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteVersions {
+    #[serde(default)]
+    pub channels: BTreeMap<String, Version>,
+}
+
+impl RemoteVersions {
+    /// Reads the cached versions, empty when nothing is cached
+    pub fn cached() -> Self {
+        fvm_layout()
+            .ok()
+            .and_then(|layout| read_cache(&layout.cache_dir.join(REMOTE_VERSIONS_CACHE_FILENAME)))
+            .unwrap_or_default()
+    }
+
+    /// Version `channel` resolved to when last fetched, `None` for version
+    /// tags which never move
+    pub fn get(&self, channel: &Channel) -> Option<&Version> {
+        if channel.is_version_tag() {
+            return None;
+        }
+
+        self.channels.get(&channel.to_string())
+    }
+
+    /// Newer version `channel` resolves to than the installed `version`
+    pub fn update_for(&self, channel: &Channel, version: &Version) -> Option<&Version> {
+        self.get(channel).filter(|remote| *remote > version)
+    }
+}
+
+/// Records the version `channel` resolved to. Failing to cache it never
+/// fails the command.
+pub fn record_remote_version(channel: &Channel, version: &Version) {
+    if channel.is_version_tag() {
+        return;
+    }
+
+    let result = fvm_workdir_path().and_then(|workdir| {
+        if !workdir.exists() {
+            return Ok(());
+        }
+
+        let cache_dir = fvm_layout()?.cache_dir;
+        let cache_path = cache_dir.join(REMOTE_VERSIONS_CACHE_FILENAME);
+        let mut versions = read_cache(&cache_path).unwrap_or_default();
+
+        versions
+            .channels
+            .insert(channel.to_string(), version.clone());
+        create_dir_all(&cache_dir)?;
+        write_cache(&cache_path, &versions)
+    });
+
+    if let Err(err) = result {
+        tracing::debug!(%err, "Failed to cache remote channel version");
+    }
+}
+
+fn write_cache(path: &Path, versions: &RemoteVersions) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(versions)?)
+}
+
+fn read_cache(path: &Path) -> Option<RemoteVersions> {
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_updates_of_moving_channels() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_path = tmp.path().join(REMOTE_VERSIONS_CACHE_FILENAME);
+        let mut versions = RemoteVersions::default();
+
+        versions
+            .channels
+            .insert("stable".to_string(), Version::new(0, 12, 1));
+        versions
+            .channels
+            .insert("0.11".to_string(), Version::new(0, 11, 9));
+        write_cache(&cache_path, &versions).unwrap();
+
+        let versions = read_cache(&cache_path).unwrap();
+
+        assert_eq!(
+            versions.update_for(&Channel::Stable, &Version::new(0, 12, 0)),
+            Some(&Version::new(0, 12, 1))
+        );
+        assert_eq!(
+            versions.update_for(&Channel::Minor(0, 11), &Version::new(0, 11, 9)),
+            None
+        );
+        assert_eq!(
+            versions.update_for(&Channel::Latest, &Version::new(0, 12, 0)),
+            None
+        );
+        assert_eq!(versions.get(&Channel::Tag(Version::new(0, 12, 1))), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! Host System Requirement Checks
+//!
+//! Refuses to install binaries the host cannot run, e.g. binaries linked
+//! against a newer glibc than the host provides, which would otherwise fail
+//! later with `GLIBC_2.34 not found`. Requirements are cached in the
+//! `requirements.json` file in the FVM cache directory and refreshed once a
+//! day.
+
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::{HostSystem, RequirementsMetadata};
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::github::fvm_client;
+use super::notify::Notify;
+use super::workdir::{fvm_layout, fvm_workdir_path};
+
+/// The name of the requirements cache file stored in the cache directory
+pub const REQUIREMENTS_CACHE_FILENAME: &str = "requirements.json";
+
+/// Age after which cached requirements are fetched again
+pub const REQUIREMENTS_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Loads requirements from the cache, fetching them when the cache is stale.
+///
+/// Returns `None` if requirements are not available, installs are not
+/// blocked when working offline.
+pub async fn load_requirements_metadata() -> Option<RequirementsMetadata> {
+    let workdir = fvm_workdir_path().ok()?;
+    let cache_dir = fvm_layout().ok()?.cache_dir;
+    let cache_path = cache_dir.join(REQUIREMENTS_CACHE_FILENAME);
+
+    if let Some(metadata) = read_cache(&cache_path, Some(REQUIREMENTS_CACHE_TTL)) {
+        return Some(metadata);
+    }
+
+    match fvm_client().ok()?.fetch_requirements_metadata().await {
+        Ok(metadata) => {
+            if workdir.exists()
+                && let Err(err) = create_dir_all(&cache_dir)
+                    .map_err(Into::into)
+                    .and_then(|()| write_cache(&cache_path, &metadata))
+            {
+                tracing::debug!(%err, "Failed to cache host requirements");
+            }
+
+            Some(metadata)
+        }
+        Err(err) => {
+            tracing::debug!(%err, "Failed to fetch host requirements, using stale cache");
+            read_cache(&cache_path, None)
+        }
+    }
+}
+
+fn write_cache(path: &Path, metadata: &RequirementsMetadata) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(metadata)?)
+}
+
+/// Reads the cached requirements if the cache is younger than `ttl`
+fn read_cache(path: &Path, ttl: Option<Duration>) -> Option<RequirementsMetadata> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+
+    if let Some(ttl) = ttl
+        && modified.elapsed().map_or(true, |age| age > ttl)
+    {
+        return None;
+    }
+
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+/// Checks the requirements of the binaries of `version` for `target`
+/// against `host`. Requirements the host is known not to meet are rejected
+/// unless `ignore` is set, requirements which could not be checked only
+/// print a warning.
+pub fn check_requirements(
+    metadata: &RequirementsMetadata,
+    version: &Version,
+    target: &str,
+    host: &HostSystem,
+    ignore: bool,
+    notify: Notify,
+) -> Result<()> {
+    let unmet = metadata.unmet(version, target, host);
+
+    if unmet.is_empty() {
+        return Ok(());
+    }
+
+    for requirement in unmet.iter() {
+        notify.warn(format!(
+            "Fluvio {} for {target}: {requirement}",
+            version.to_string().bold()
+        ));
+    }
+
+    if unmet.iter().any(|requirement| requirement.is_known()) {
+        if ignore {
+            notify.warn("The installed binaries are not expected to run on this system");
+            return Ok(());
+        }
+
+        let command = String::from("fvm install <version> --target <triple>");
+
+        notify.help(format!(
+            "Install an older version, or a statically linked musl build with {}",
+            command.bold()
+        ));
+        return Err(Failure::new(
+            FailureKind::IncompatibleTarget,
+            format!(
+                "The host system does not meet the requirements of Fluvio {version}, use --ignore-requirements to install it anyway"
+            ),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use fluvio_artifacts_util::fvm::Libc;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const GNU: &str = "x86_64-unknown-linux-gnu";
+
+    fn metadata() -> RequirementsMetadata {
+        serde_json::from_str(r#"{ "requirements": [{ "versions": ">=0.12.0", "glibc": "2.34" }] }"#)
+            .unwrap()
+    }
+
+    fn host(libc: Libc) -> HostSystem {
+        HostSystem { libc, macos: None }
+    }
+
+    #[test]
+    fn rejects_unmet_requirements_unless_ignored() {
+        let metadata = metadata();
+        let notify = Notify::new(true);
+        let version = Version::new(0, 12, 0);
+        let old = host(Libc::Glibc("2.31".parse().unwrap()));
+
+        assert!(check_requirements(&metadata, &version, GNU, &old, false, notify).is_err());
+        assert!(check_requirements(&metadata, &version, GNU, &old, true, notify).is_ok());
+        assert!(
+            check_requirements(&metadata, &Version::new(0, 11, 9), GNU, &old, false, notify)
+                .is_ok()
+        );
+        // Only warns when the C library is not detected
+        assert!(
+            check_requirements(
+                &metadata,
+                &version,
+                GNU,
+                &host(Libc::Unknown),
+                false,
+                notify
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn reads_cache_within_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let cache_path = tmp.path().join(REQUIREMENTS_CACHE_FILENAME);
+
+        assert_eq!(read_cache(&cache_path, None), None);
+
+        write(&cache_path, serde_json::to_string(&metadata()).unwrap()).unwrap();
+
+        assert_eq!(
+            read_cache(&cache_path, Some(REQUIREMENTS_CACHE_TTL)),
+            Some(metadata())
+        );
+        assert_eq!(read_cache(&cache_path, Some(Duration::ZERO)), None);
+    }
+}
@@ -1,12 +1,20 @@
-use std::fs::{write, read_to_string};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Error, Result};
+use anyhow::{Context, Error, Result};
 use serde::{Deserialize, Serialize};
 
 use fluvio_artifacts_util::fvm::Channel;
+use fluvio_artifacts_util::htclient::dns::DnsOverrides;
+use fluvio_artifacts_util::layout::LayoutStyle;
+use fluvio_artifacts_util::state::write_atomic;
 
+use super::environment::Environment;
+use super::install_profile::InstallProfile;
 use super::manifest::VersionManifest;
+use super::notification::{FVM_NOTIFY_JSONL, FVM_NOTIFY_WEBHOOK_URL, NotificationSettings, NotifySink};
 use super::workdir::fvm_workdir_path;
 
 pub const SETTINGS_TOML_FILENAME: &str = "settings.toml";
@@ -20,6 +28,52 @@ pub struct Settings {
     pub channel: Option<Channel>,
     /// The specific version in use
     pub version: Option<String>,
+    /// Scratch directory for downloads, overridden by `FVM_TMPDIR`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmpdir: Option<PathBuf>,
+    /// Directory layout style, overridden by `FLUVIO_LAYOUT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutStyle>,
+    /// Transparency manifest URL template artifact digests are verified
+    /// against, overridden by `FVM_TRANSPARENCY_URL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency_url: Option<String>,
+    /// Components and setup steps chosen with `fvm init`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_profile: Option<InstallProfile>,
+    /// Whether installs write a verification report next to the binaries,
+    /// overridden by `FVM_VERIFICATION_REPORT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_report: Option<bool>,
+    /// GitHub API releases are fetched from, overridden by
+    /// `FVM_GITHUB_API_URL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_api_url: Option<String>,
+    /// Repository releases are fetched from as `<owner>/<name>`, overridden by
+    /// `FVM_GITHUB_REPOSITORY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_repository: Option<String>,
+    /// Addresses hosts are pinned to instead of being resolved, as
+    /// comma separated addresses per host. `FLUVIO_DNS_OVERRIDES` takes
+    /// precedence for the hosts it pins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_overrides: Option<BTreeMap<String, String>>,
+    /// URL of the signed version policy installs and switches are restricted
+    /// to, overridden by `FVM_VERSION_POLICY_URL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_policy_url: Option<String>,
+    /// Base64 Ed25519 key the version policy is signed with, overridden by
+    /// `FVM_VERSION_POLICY_KEY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_policy_key: Option<String>,
+    /// Sinks long operations are reported to besides the terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationSettings>,
+    /// Base64 Ed25519 keys of the transparency logs trusted to prove the
+    /// publish date of plugin packages, by log name. Plugins with a proof
+    /// which doesn't verify are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparency_logs: Option<BTreeMap<String, String>>,
 }
 
 impl Settings {
@@ -38,6 +92,18 @@ impl Settings {
         let initial = Self {
             channel: None,
             version: None,
+            tmpdir: None,
+            layout: None,
+            transparency_url: None,
+            install_profile: None,
+            verification_report: None,
+            github_api_url: None,
+            github_repository: None,
+            dns_overrides: None,
+            version_policy_url: None,
+            version_policy_key: None,
+            notifications: None,
+            transparency_logs: None,
         };
 
         initial.save()?;
@@ -48,18 +114,142 @@ impl Settings {
 
     /// Opens the `settings.toml` file and parses it into a `Settings` struct.
     ///
-    /// If the file doesn't exist, it will be created.
+    /// If the file doesn't exist it will be created. A file which cannot be
+    /// parsed is reported as an error and left untouched, so hand edits are
+    /// never lost.
     pub fn open() -> Result<Self> {
-        let settings_path = Self::settings_file_path()?;
+        match Self::read_existing()? {
+            Some(settings) => Ok(settings),
+            None => Self::init(),
+        }
+    }
+
+    /// Reads the `version` key without creating the `settings.toml` file
+    pub fn configured_version() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.version))
+    }
+
+    /// Reads the `tmpdir` key without creating the `settings.toml` file
+    pub fn configured_tmpdir() -> Result<Option<PathBuf>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.tmpdir))
+    }
+
+    /// Reads the `layout` key without creating the `settings.toml` file
+    pub fn configured_layout() -> Result<Option<LayoutStyle>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.layout))
+    }
+
+    /// Reads the `transparency_url` key without creating the `settings.toml`
+    /// file
+    pub fn configured_transparency_url() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.transparency_url))
+    }
+
+    /// Reads the `install_profile` key without creating the `settings.toml`
+    /// file
+    pub fn configured_install_profile() -> Result<Option<InstallProfile>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.install_profile))
+    }
+
+    /// Reads the `verification_report` key without creating the
+    /// `settings.toml` file
+    pub fn configured_verification_report() -> Result<Option<bool>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.verification_report))
+    }
+
+    /// Reads the `github_api_url` key without creating the `settings.toml`
+    /// file
+    pub fn configured_github_api_url() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.github_api_url))
+    }
+
+    /// Reads the `github_repository` key without creating the
+    /// `settings.toml` file
+    pub fn configured_github_repository() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.github_repository))
+    }
+
+    /// Reads the `dns_overrides` table without creating the `settings.toml`
+    /// file
+    pub fn configured_dns_overrides() -> Result<DnsOverrides> {
+        let mut overrides = DnsOverrides::default();
+        let hosts = Self::read_existing()?
+            .and_then(|settings| settings.dns_overrides)
+            .unwrap_or_default();
+
+        for (host, addresses) in hosts {
+            for address in addresses.split(',').filter(|addr| !addr.trim().is_empty()) {
+                let target = address
+                    .parse()
+                    .map_err(|err| Error::msg(format!("Invalid DNS override for {host}: {err}")))?;
+
+                overrides.insert(&host, target);
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Reads the `version_policy_url` key without creating the
+    /// `settings.toml` file
+    pub fn configured_version_policy_url() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.version_policy_url))
+    }
+
+    /// Reads the `version_policy_key` key without creating the
+    /// `settings.toml` file
+    pub fn configured_version_policy_key() -> Result<Option<String>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.version_policy_key))
+    }
+
+    /// Reads the `transparency_logs` table without creating the
+    /// `settings.toml` file
+    pub fn configured_transparency_logs() -> Result<Option<BTreeMap<String, String>>> {
+        Ok(Self::read_existing()?.and_then(|settings| settings.transparency_logs))
+    }
+
+    /// Notification sinks of the `notifications` table, without creating
+    /// the `settings.toml` file. `FVM_NOTIFY_JSONL` and
+    /// `FVM_NOTIFY_WEBHOOK_URL` take precedence over the settings.
+    pub fn configured_notification_sinks() -> Result<Vec<NotifySink>> {
+        let mut notifications = Self::read_existing()?
+            .and_then(|settings| settings.notifications)
+            .unwrap_or_default();
+
+        if let Some(path) = std::env::var_os(FVM_NOTIFY_JSONL) {
+            notifications.jsonl = Some(PathBuf::from(path));
+        }
 
-        if !settings_path.exists() {
-            Self::init()?;
+        if let Ok(url) = std::env::var(FVM_NOTIFY_WEBHOOK_URL) {
+            notifications.webhook_url = Some(url);
         }
 
-        let contents = read_to_string(settings_path)?;
-        let settings: Settings = toml::from_str(&contents)?;
+        notifications.sinks()
+    }
+
+    /// Reads the settings without creating the `settings.toml` file, `None`
+    /// if it doesn't exist
+    pub fn read_existing() -> Result<Option<Self>> {
+        Self::read_from(&Self::settings_file_path()?)
+    }
+
+    /// Reads the settings at `path`, `None` if the file doesn't exist.
+    ///
+    /// `settings.toml` is meant to be edited by hand, so it is written
+    /// without a checksum footer. Footers written by previous versions are
+    /// TOML comments and are ignored.
+    fn read_from(path: &Path) -> Result<Option<Self>> {
+        let contents = match read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error::new(err).context(format!("Failed to read {}", path.display())));
+            }
+        };
 
-        Ok(settings)
+        toml::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", path.display()))
     }
 
     /// Update settings file to keep track of active Fluvio Version
@@ -71,14 +261,26 @@ impl Settings {
         Ok(())
     }
 
+    /// Sets the install profile used by installs and updates
+    pub fn set_install_profile(&mut self, profile: InstallProfile) -> Result<()> {
+        self.install_profile = Some(profile);
+        self.save()?;
+
+        Ok(())
+    }
+
+    /// Applies the settings and mirrors of an exported environment
+    pub fn apply_environment(&mut self, environment: &Environment) -> Result<()> {
+        environment.apply_to(self);
+        self.save()
+    }
+
     /// Saves the `settings.toml` file to disk, overwriting the previous version
     fn save(&self) -> Result<()> {
         let settings_path = Self::settings_file_path()?;
         let settings_str = toml::to_string(&self)?;
 
-        write(settings_path, settings_str)?;
-
-        Ok(())
+        write_atomic(settings_path, settings_str.as_bytes())
     }
 
     /// Retrieves the path to the `settings.toml` file for this host
@@ -96,6 +298,8 @@ pub mod tests {
 
     use semver::Version;
 
+    use tempfile::TempDir;
+
     use crate::common::{home_dir, manifest::VersionManifest};
 
     use super::*;
@@ -239,6 +443,8 @@ version = "0.12.0"
             channel: Channel::Stable,
             version: Version::parse(VERSION).unwrap(),
             contents: None,
+            hooks: None,
+            provenance: None,
         };
 
         let mut settings = Settings::open().unwrap();
@@ -253,4 +459,39 @@ version = "0.12.0"
 
         delete_fvm_dir();
     }
+
+    #[test]
+    fn reads_hand_edited_settings() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SETTINGS_TOML_FILENAME);
+
+        assert!(Settings::read_from(&path).unwrap().is_none());
+
+        // Legacy checksum footer no longer matching the edited contents
+        std::fs::write(
+            &path,
+            "channel = \"latest\"\nversion = \"0.12.0\"\n# checksum: sha256:0000\n",
+        )
+        .unwrap();
+
+        let settings = Settings::read_from(&path).unwrap().unwrap();
+
+        assert_eq!(settings.channel, Some(Channel::Latest));
+        assert_eq!(settings.version.as_deref(), Some("0.12.0"));
+    }
+
+    #[test]
+    fn reports_invalid_settings_without_resetting() {
+        const INVALID: &str = "channel = \"stable\"\nversion = \n";
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SETTINGS_TOML_FILENAME);
+
+        std::fs::write(&path, INVALID).unwrap();
+
+        let err = Settings::read_from(&path).unwrap_err();
+
+        assert!(err.to_string().starts_with("Failed to parse"));
+        assert_eq!(read_to_string(&path).unwrap(), INVALID);
+    }
 }
@@ -0,0 +1,398 @@
+//! Shell Profile
+//!
+//! Detects the user's shell and edits its profile files so the FVM and Fluvio
+//! binaries directories are included in `PATH`. Changes are kept between
+//! marker comments so they can be updated or removed later on.
+
+use std::env::var;
+use std::fmt::Display;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+
+/// Marker used to identify the beginning of the FVM block in profile files
+pub const PROFILE_BLOCK_START: &str = "# >>> fvm setup >>>";
+
+/// Marker used to identify the end of the FVM block in profile files
+pub const PROFILE_BLOCK_END: &str = "# <<< fvm setup <<<";
+
+const PROMPT_HOOK_HEADER: &str =
+    "# Generated by `fvm setup --prompt`, regenerate it instead of editing it\n";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Sh,
+}
+
+impl Shell {
+    /// Every supported shell
+    pub const ALL: [Shell; 4] = [Self::Bash, Self::Zsh, Self::Fish, Self::Sh];
+
+    /// Detects the user's shell from the `SHELL` environment variable, falls
+    /// back to `sh` when the shell is unknown.
+    pub fn detect() -> Self {
+        var("SHELL")
+            .ok()
+            .and_then(|shell| {
+                Path::new(&shell)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.parse().ok())
+            })
+            .unwrap_or(Self::Sh)
+    }
+
+    /// Profile files loaded by this shell for interactive and login sessions,
+    /// relative to the `home` directory.
+    ///
+    /// Bash login shells only read `.profile` when there is no
+    /// `.bash_profile`, so `.bash_profile` is used only if it exists already.
+    pub fn profile_files(&self, home: &Path) -> Vec<PathBuf> {
+        match self {
+            Self::Bash => {
+                let bash_profile = home.join(".bash_profile");
+                let login_profile = if bash_profile.exists() {
+                    bash_profile
+                } else {
+                    home.join(".profile")
+                };
+
+                vec![home.join(".bashrc"), login_profile]
+            }
+            Self::Zsh => vec![home.join(".zshrc")],
+            Self::Fish => vec![
+                home.join(".config")
+                    .join("fish")
+                    .join("conf.d")
+                    .join("fvm.fish"),
+            ],
+            Self::Sh => vec![home.join(".profile")],
+        }
+    }
+
+    /// Builds the block of shell code that adds the provided directories to
+    /// `PATH` and sources the prompt `hook` file if any, wrapped in marker
+    /// comments.
+    pub fn profile_block(&self, paths: &[PathBuf], hook: Option<&Path>) -> String {
+        let paths = paths
+            .iter()
+            .map(|path| format!("\"{}\"", path.display()))
+            .collect::<Vec<String>>();
+        let body = match self {
+            Self::Fish => format!("fish_add_path -g {}", paths.join(" ")),
+            _ => paths
+                .iter()
+                .rev()
+                .map(|path| {
+                    format!(
+                        "case \":${{PATH}}:\" in *:{path}:*) ;; *) export PATH={path}:\"$PATH\" ;; esac"
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        };
+        let source = match (self, hook) {
+            (Self::Fish, Some(hook)) => {
+                format!("\ntest -f \"{0}\"; and source \"{0}\"", hook.display())
+            }
+            (_, Some(hook)) => format!("\n[ -f \"{0}\" ] && . \"{0}\"", hook.display()),
+            (_, None) => String::new(),
+        };
+
+        format!("{PROFILE_BLOCK_START}\n{body}{source}\n{PROFILE_BLOCK_END}\n")
+    }
+
+    /// Name of the prompt hook file, written to the FVM directory
+    pub fn prompt_hook_filename(&self) -> String {
+        format!("prompt.{self}")
+    }
+
+    /// Shell code which shows the active Fluvio version in the prompt and
+    /// warns, when changing directories, if it does not satisfy the version
+    /// pinned by the project, see `fvm prompt`
+    pub fn prompt_hook(&self) -> Result<String> {
+        let hook = match self {
+            Self::Bash => {
+                r#"__fvm_prompt() {
+  FVM_PROMPT="$(command fvm prompt 2>/dev/null)"
+  if [ "$PWD" != "${__FVM_PROMPT_PWD:-}" ]; then
+    __FVM_PROMPT_PWD="$PWD"
+    command fvm prompt --check 2>/dev/null
+  fi
+}
+case ";${PROMPT_COMMAND:-};" in
+  *";__fvm_prompt;"*) ;;
+  *) PROMPT_COMMAND="__fvm_prompt;${PROMPT_COMMAND:-}" ;;
+esac
+case "$PS1" in
+  *FVM_PROMPT*) ;;
+  *) PS1='${FVM_PROMPT:+($FVM_PROMPT) }'"$PS1" ;;
+esac
+"#
+            }
+            Self::Zsh => {
+                r#"__fvm_prompt() {
+  FVM_PROMPT="$(command fvm prompt 2>/dev/null)"
+  if [ "$PWD" != "${__FVM_PROMPT_PWD:-}" ]; then
+    __FVM_PROMPT_PWD="$PWD"
+    command fvm prompt --check 2>/dev/null
+  fi
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __fvm_prompt
+setopt PROMPT_SUBST
+case "$PROMPT" in
+  *FVM_PROMPT*) ;;
+  *) PROMPT='${FVM_PROMPT:+($FVM_PROMPT) }'"$PROMPT" ;;
+esac
+"#
+            }
+            Self::Fish => {
+                r#"function __fvm_prompt --on-event fish_prompt
+    set -g FVM_PROMPT (command fvm prompt 2>/dev/null)
+    if test "$PWD" != "$__fvm_prompt_pwd"
+        set -g __fvm_prompt_pwd $PWD
+        command fvm prompt --check 2>/dev/null
+    end
+end
+if not functions -q fish_right_prompt
+    function fish_right_prompt
+        test -n "$FVM_PROMPT"; and echo -n "($FVM_PROMPT)"
+    end
+end
+"#
+            }
+            Self::Sh => bail!("Prompt hooks are not supported for sh, use bash, zsh or fish"),
+        };
+
+        Ok(format!("{PROMPT_HOOK_HEADER}{hook}"))
+    }
+
+    /// Resolves `binary` in a new login shell, returning the path to the
+    /// binary if found.
+    pub fn resolve_in_login_shell(&self, binary: &str) -> Result<Option<PathBuf>> {
+        let output = Command::new(self.to_string())
+            .arg("-l")
+            .arg("-c")
+            .arg(format!("command -v {binary}"))
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        if path.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(PathBuf::from(path)))
+    }
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bash => write!(f, "bash"),
+            Self::Zsh => write!(f, "zsh"),
+            Self::Fish => write!(f, "fish"),
+            Self::Sh => write!(f, "sh"),
+        }
+    }
+}
+
+impl FromStr for Shell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            "sh" | "dash" | "ksh" => Ok(Self::Sh),
+            _ => bail!("Unsupported shell {s}, expected one of: bash, zsh, fish, sh"),
+        }
+    }
+}
+
+/// Inserts `block` into `contents`, replacing a previous FVM block if any.
+pub fn apply_block(contents: &str, block: &str) -> String {
+    let mut next = remove_block(contents).unwrap_or_else(|| contents.to_string());
+
+    if !next.is_empty() && !next.ends_with('\n') {
+        next.push('\n');
+    }
+
+    next.push_str(block);
+    next
+}
+
+/// Removes the FVM block from `contents`, returns `None` if there is no
+/// block to remove.
+pub fn remove_block(contents: &str) -> Option<String> {
+    let start = contents.find(PROFILE_BLOCK_START)?;
+    let end = contents[start..].find(PROFILE_BLOCK_END)? + start + PROFILE_BLOCK_END.len();
+    let end = if contents[end..].starts_with('\n') {
+        end + 1
+    } else {
+        end
+    };
+
+    Some(format!("{}{}", &contents[..start], &contents[end..]))
+}
+
+/// Writes `block` into the profile file at `path`. Returns `false` if the
+/// file already contained the same block.
+pub fn write_block(path: &Path, block: &str) -> Result<bool> {
+    let contents = if path.exists() {
+        read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let next = apply_block(&contents, block);
+
+    if next == contents {
+        return Ok(false);
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    write(path, next)?;
+    Ok(true)
+}
+
+/// Removes the FVM block from the profile file at `path`. Returns `false` if
+/// the file has no FVM block.
+pub fn erase_block(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let contents = read_to_string(path)?;
+
+    if let Some(next) = remove_block(&contents) {
+        write(path, next)?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn block() -> String {
+        Shell::Bash.profile_block(&[PathBuf::from("/home/fluvio/.fvm/bin")], None)
+    }
+
+    #[test]
+    fn parses_shell_names() {
+        assert_eq!(Shell::from_str("zsh").unwrap(), Shell::Zsh);
+        assert_eq!(Shell::from_str("dash").unwrap(), Shell::Sh);
+        assert!(Shell::from_str("powershell").is_err());
+    }
+
+    #[test]
+    fn applies_block_idempotently() {
+        let original = "export EDITOR=vim";
+        let once = apply_block(original, &block());
+        let twice = apply_block(&once, &block());
+
+        assert_eq!(once, twice);
+        assert!(once.starts_with("export EDITOR=vim\n# >>> fvm setup >>>"));
+        assert_eq!(once.matches(PROFILE_BLOCK_START).count(), 1);
+    }
+
+    #[test]
+    fn replaces_outdated_block() {
+        let outdated = apply_block(
+            "",
+            &Shell::Bash.profile_block(&[PathBuf::from("/old")], None),
+        );
+        let updated = apply_block(&outdated, &block());
+
+        assert!(!updated.contains("/old"));
+        assert!(updated.contains("/home/fluvio/.fvm/bin"));
+    }
+
+    #[test]
+    fn removes_block_and_keeps_user_contents() {
+        let contents = format!("alias ll='ls -l'\n{}export EDITOR=vim\n", block());
+        let removed = remove_block(&contents).unwrap();
+
+        assert_eq!(removed, "alias ll='ls -l'\nexport EDITOR=vim\n");
+        assert!(remove_block(&removed).is_none());
+    }
+
+    #[test]
+    fn writes_and_erases_profile_files() {
+        let home = TempDir::new().unwrap();
+        let profile = &Shell::Fish.profile_files(home.path())[0];
+        let block = Shell::Fish.profile_block(&[PathBuf::from("/opt/fvm/bin")], None);
+
+        assert!(write_block(profile, &block).unwrap());
+        assert!(!write_block(profile, &block).unwrap());
+        assert!(
+            read_to_string(profile)
+                .unwrap()
+                .contains("fish_add_path -g \"/opt/fvm/bin\"")
+        );
+        assert!(erase_block(profile).unwrap());
+        assert!(!erase_block(profile).unwrap());
+    }
+
+    #[test]
+    fn uses_existing_bash_login_profile() {
+        let home = TempDir::new().unwrap();
+
+        assert_eq!(
+            Shell::Bash.profile_files(home.path()),
+            vec![home.path().join(".bashrc"), home.path().join(".profile")]
+        );
+
+        write(home.path().join(".bash_profile"), "").unwrap();
+
+        assert_eq!(
+            Shell::Bash.profile_files(home.path()),
+            vec![
+                home.path().join(".bashrc"),
+                home.path().join(".bash_profile")
+            ]
+        );
+    }
+
+    #[test]
+    fn sources_prompt_hooks() {
+        let hook = PathBuf::from("/home/fluvio/.fvm/prompt.zsh");
+        let block = Shell::Zsh.profile_block(&[], Some(&hook));
+
+        assert!(block.contains(
+            "[ -f \"/home/fluvio/.fvm/prompt.zsh\" ] && . \"/home/fluvio/.fvm/prompt.zsh\"\n# <<< fvm setup <<<"
+        ));
+        assert!(
+            Shell::Fish
+                .profile_block(&[], Some(&hook))
+                .contains("; and source \"/home/fluvio/.fvm/prompt.zsh\"")
+        );
+        assert!(
+            Shell::Bash
+                .prompt_hook()
+                .unwrap()
+                .contains("PROMPT_COMMAND=\"__fvm_prompt;")
+        );
+        assert_eq!(Shell::Fish.prompt_hook_filename(), "prompt.fish");
+        assert!(Shell::Sh.prompt_hook().is_err());
+    }
+}
@@ -0,0 +1,302 @@
+//! Version Shims
+//!
+//! Every installed version gets version-suffixed entries in `~/.fluvio/bin`,
+//! e.g. `fluvio@0.11.8`, so a specific version can be invoked without
+//! switching. Shims point to the FVM binary, which detects the suffixed name
+//! it was invoked with, resolves the binary through the version manifest and
+//! runs it once its digest matches the digest recorded at install time and
+//! the version policy allows the version.
+
+use std::ffi::OsString;
+use std::fs::{read_dir, remove_file};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Result, bail};
+use semver::Version;
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::sha256_digest;
+
+use super::lease::ExecutionLease;
+use super::manifest::VersionManifest;
+use super::notify::Notify;
+use super::usage::UsageTracker;
+use super::version_directory::VersionDirectory;
+use super::version_policy::enforce_version_policy;
+use super::workdir::{fluvio_binaries_path, fvm_bin_path, fvm_versions_path};
+
+/// Separator between the binary name and the version in shim names
+pub const SHIM_SEPARATOR: char = '@';
+
+/// Name of the shim for `binary` at `version`, e.g. `fluvio@0.11.8`
+pub fn shim_name(binary: &str, version: &Version) -> String {
+    match binary.strip_suffix(".exe") {
+        Some(binary) => format!("{binary}{SHIM_SEPARATOR}{version}.exe"),
+        None => format!("{binary}{SHIM_SEPARATOR}{version}"),
+    }
+}
+
+/// Parses a shim name into the binary name and version
+pub fn parse_shim_name(name: &str) -> Option<(String, Version)> {
+    let (binary, version) = name.split_once(SHIM_SEPARATOR)?;
+    let (version, exe) = match version.strip_suffix(".exe") {
+        Some(version) => (version, ".exe"),
+        None => (version, ""),
+    };
+    let version = Version::parse(version).ok()?;
+
+    if binary.is_empty() {
+        return None;
+    }
+
+    Some((format!("{binary}{exe}"), version))
+}
+
+/// Creates shims for the binaries in `version_dir`. Does nothing if FVM is
+/// not installed, as shims point to the installed FVM binary.
+pub fn install_shims(version_dir: &VersionDirectory) -> Result<Vec<PathBuf>> {
+    let fvm_bin = fvm_bin_path()?;
+
+    if !fvm_bin.exists() {
+        tracing::debug!(?fvm_bin, "FVM binary not installed, skipping shims");
+        return Ok(Vec::new());
+    }
+
+    let bin_dir = fluvio_binaries_path()?;
+    let mut shims = Vec::new();
+
+    for entry in version_dir.contents.iter() {
+        let Some(binary) = entry.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let shim = bin_dir.join(shim_name(binary, &version_dir.manifest.version));
+
+        if shim.symlink_metadata().is_ok() {
+            remove_file(&shim)?;
+        }
+
+        link_fvm(&fvm_bin, &shim)?;
+        shims.push(shim);
+    }
+
+    Ok(shims)
+}
+
+/// Removes the shims for `version` unless another installed version
+/// directory still provides it
+pub fn remove_shims(version: &Version) -> Result<()> {
+    if resolve_version(version)?.is_some() {
+        return Ok(());
+    }
+
+    let bin_dir = fluvio_binaries_path()?;
+
+    if !bin_dir.exists() {
+        return Ok(());
+    }
+
+    for entry in read_dir(bin_dir)? {
+        let path = entry?.path();
+        let is_shim = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_shim_name)
+            .is_some_and(|(_, shim_version)| shim_version == *version);
+
+        if is_shim {
+            remove_file(&path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the installed version directory for `version`, preferring the
+/// directory named after the version over channel directories
+pub fn resolve_version(version: &Version) -> Result<Option<VersionDirectory>> {
+    let versions_path = fvm_versions_path()?;
+
+    resolve_version_in(&versions_path, version)
+}
+
+fn resolve_version_in(versions_path: &Path, version: &Version) -> Result<Option<VersionDirectory>> {
+    let exact = versions_path.join(version.to_string());
+
+    if exact.is_dir() {
+        return VersionDirectory::open(exact).map(Some);
+    }
+
+    if !versions_path.is_dir() {
+        return Ok(None);
+    }
+
+    for entry in read_dir(versions_path)? {
+        let path = entry?.path();
+
+        if path.is_dir()
+            && let Ok(version_dir) = VersionDirectory::open(path)
+            && version_dir.manifest.version == *version
+        {
+            return Ok(Some(version_dir));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Runs `binary` from the installed `version` with `args`, returning the
+/// exit code
+pub async fn run_shim(
+    binary: &str,
+    version: &Version,
+    args: Vec<OsString>,
+    notify: Notify,
+) -> Result<i32> {
+    let version_dir = resolve_version(version)?.ok_or_else(|| {
+        Failure::new(
+            FailureKind::NotFound,
+            format!(
+                "Fluvio version {version} is not installed, install it with `fvm install {version}`"
+            ),
+        )
+    })?;
+
+    enforce_version_policy(version, notify).await?;
+
+    let channel = version_dir.manifest.channel.to_string();
+    // Held until the binary exits, so the version is not pruned meanwhile
+    let _lease = ExecutionLease::acquire(&channel)
+        .inspect_err(|err| tracing::warn!(%err, "Failed to acquire execution lease"))
+        .ok();
+    let binary_path = verified_binary(&version_dir.path, &version_dir.manifest, binary)?;
+
+    if let Some(mut usage) = UsageTracker::open()? {
+        usage.record_execution(&channel)?;
+    }
+
+    let status = Command::new(binary_path).args(args).status()?;
+
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Path to `binary` in a version directory, failing if it was modified since
+/// it was installed
+fn verified_binary(
+    version_path: &Path,
+    manifest: &VersionManifest,
+    binary: &str,
+) -> Result<PathBuf> {
+    let binary_path = version_path.join(binary);
+
+    if !binary_path.is_file() {
+        bail!(
+            "{binary} is not part of Fluvio version {}",
+            manifest.version
+        );
+    }
+
+    let expected = manifest
+        .contents
+        .iter()
+        .flatten()
+        .find(|artifact| artifact.name == binary)
+        .and_then(|artifact| artifact.installed_sha256_digest.as_deref());
+
+    match expected {
+        Some(expected) => {
+            let actual = sha256_digest(&binary_path)?;
+
+            if actual != expected {
+                bail!(
+                    "Refusing to run {}, its digest no longer matches the installed binary. Reinstall it with `fvm install {}`",
+                    binary_path.display(),
+                    manifest.channel
+                );
+            }
+        }
+        None => tracing::warn!(
+            ?binary_path,
+            "No digest recorded for binary, skipping verification"
+        ),
+    }
+
+    Ok(binary_path)
+}
+
+#[cfg(unix)]
+fn link_fvm(fvm_bin: &Path, shim: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fvm_bin, shim)?;
+    Ok(())
+}
+
+/// Windows requires privileges for symbolic links, so shims are copies of
+/// the FVM binary
+#[cfg(not(unix))]
+fn link_fvm(fvm_bin: &Path, shim: &Path) -> Result<()> {
+    std::fs::copy(fvm_bin, shim)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use tempfile::TempDir;
+
+    use fluvio_artifacts_util::fvm::Channel;
+
+    use crate::common::manifest::VersionedArtifact;
+
+    use super::*;
+
+    #[test]
+    fn parses_shim_names() {
+        let version = Version::new(0, 11, 8);
+
+        assert_eq!(shim_name("fluvio", &version), "fluvio@0.11.8");
+        assert_eq!(shim_name("fluvio.exe", &version), "fluvio@0.11.8.exe");
+        assert_eq!(
+            parse_shim_name("fluvio-run@0.11.8"),
+            Some((String::from("fluvio-run"), version.clone()))
+        );
+        assert_eq!(
+            parse_shim_name("fluvio@0.11.8.exe"),
+            Some((String::from("fluvio.exe"), version))
+        );
+        assert_eq!(parse_shim_name("fluvio"), None);
+        assert_eq!(parse_shim_name("fluvio@stable"), None);
+        assert_eq!(parse_shim_name("@0.11.8"), None);
+    }
+
+    #[test]
+    fn resolves_versions_and_verifies_digests() {
+        let tmp = TempDir::new().unwrap();
+        let stable = tmp.path().join("stable");
+        let version = Version::new(0, 11, 8);
+
+        create_dir_all(&stable).unwrap();
+        write(stable.join("fluvio"), b"fluvio binary").unwrap();
+
+        let mut artifact = VersionedArtifact::new("fluvio", "0.11.8");
+
+        artifact.installed_sha256_digest = Some(sha256_digest(stable.join("fluvio")).unwrap());
+        VersionManifest::new(Channel::Stable, version.clone(), vec![artifact])
+            .write(&stable)
+            .unwrap();
+
+        let version_dir = resolve_version_in(tmp.path(), &version).unwrap().unwrap();
+
+        assert_eq!(version_dir.path, stable);
+        assert!(
+            resolve_version_in(tmp.path(), &Version::new(0, 11, 9))
+                .unwrap()
+                .is_none()
+        );
+        assert!(verified_binary(&stable, &version_dir.manifest, "fluvio").is_ok());
+        assert!(verified_binary(&stable, &version_dir.manifest, "cdk").is_err());
+
+        write(stable.join("fluvio"), b"tampered binary").unwrap();
+        assert!(verified_binary(&stable, &version_dir.manifest, "fluvio").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Ephemeral Toolchains
+//!
+//! `fvm run` executes commands with versions which are not installed. Their
+//! binaries are downloaded to `toolchains/<version>` in the FVM cache
+//! directory and reused by later runs. Once the cache grows over its size
+//! limit, the least recently used toolchains are evicted.
+//!
+//! Runs hold an [`ExecutionLease`] on their toolchain, in
+//! `~/.fvm/leases/toolchains/<version>`, so toolchains are never evicted
+//! while a command runs with them.
+
+use std::fs::{File, create_dir_all, read_dir, remove_dir_all, rename};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::{Download, PackageSet};
+
+use super::executable::set_executable_mode;
+use super::janitor::TrackedTempDir;
+use super::lease::{ExecutionLease, fvm_leases_path, live_executions_in};
+use super::notify::Notify;
+use super::workdir::fvm_layout;
+
+/// Directory in the FVM cache directory holding the toolchains
+pub const TOOLCHAINS_DIR: &str = "toolchains";
+
+/// File written on each use of a toolchain, its modification time orders
+/// evictions
+pub const LAST_USED_FILENAME: &str = ".last-used";
+
+/// Default size limit of the toolchain cache, in MiB
+pub const DEFAULT_TOOLCHAIN_CACHE_MIB: u64 = 1024;
+
+/// A toolchain in the cache
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedToolchain {
+    pub version: Version,
+    pub path: PathBuf,
+    /// Total size of the binaries in bytes
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Cache of toolchains downloaded by `fvm run`
+#[derive(Clone, Debug)]
+pub struct ToolchainCache {
+    root: PathBuf,
+    leases: PathBuf,
+}
+
+impl ToolchainCache {
+    /// Opens the cache in the FVM cache directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::new(
+            fvm_layout()?.cache_dir.join(TOOLCHAINS_DIR),
+            fvm_leases_path()?.join(TOOLCHAINS_DIR),
+        ))
+    }
+
+    pub fn new(root: impl Into<PathBuf>, leases: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            leases: leases.into(),
+        }
+    }
+
+    /// Acquires a lease on the toolchain for `version`, it is not evicted
+    /// until the lease is dropped
+    pub fn lease(&self, version: &Version) -> Result<ExecutionLease> {
+        ExecutionLease::acquire_in(&self.leases, &version.to_string())
+    }
+
+    /// Whether a running command holds a lease on the toolchain for `version`
+    pub fn is_leased(&self, version: &Version) -> Result<bool> {
+        Ok(!live_executions_in(&self.leases, &version.to_string())?.is_empty())
+    }
+
+    /// Path to the cached toolchain for `version`, marking it as used
+    pub fn get(&self, version: &Version) -> Result<Option<PathBuf>> {
+        let path = self.root.join(version.to_string());
+
+        if !path.is_dir() {
+            return Ok(None);
+        }
+
+        touch(&path)?;
+        Ok(Some(path))
+    }
+
+    /// Downloads the binaries of `package_set` into the cache
+    pub async fn fetch(&self, package_set: &PackageSet, notify: Notify) -> Result<PathBuf> {
+        create_dir_all(&self.root)?;
+
+        // Staged in the cache so the toolchain is moved into place atomically
+        let staging = TrackedTempDir::new_in(&self.root)?;
+
+        for artifact in package_set.artifacts.iter() {
+            notify.info(format!(
+                "Downloading {}@{} to the toolchain cache",
+                artifact.name, artifact.version
+            ));
+
+            let path = artifact.download(staging.path().to_path_buf()).await?;
+
+            set_executable_mode(&path)?;
+        }
+
+        self.insert(&package_set.pkgset, &staging.into_path())
+    }
+
+    /// Moves the toolchain staged in `staged` into the cache as `version`
+    pub fn insert(&self, version: &Version, staged: &Path) -> Result<PathBuf> {
+        let path = self.root.join(version.to_string());
+
+        if path.exists() {
+            remove_dir_all(&path)?;
+        }
+
+        rename(staged, &path)?;
+        touch(&path)?;
+
+        Ok(path)
+    }
+
+    /// Toolchains in the cache, least recently used first
+    pub fn list(&self) -> Result<Vec<CachedToolchain>> {
+        let mut toolchains = Vec::new();
+
+        if !self.root.is_dir() {
+            return Ok(toolchains);
+        }
+
+        for entry in read_dir(&self.root)? {
+            let path = entry?.path();
+            let Some(version) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| Version::parse(name).ok())
+            else {
+                continue;
+            };
+            let last_used = path
+                .join(LAST_USED_FILENAME)
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+
+            toolchains.push(CachedToolchain {
+                size: dir_size(&path)?,
+                version,
+                path,
+                last_used,
+            });
+        }
+
+        toolchains.sort_by_key(|toolchain| toolchain.last_used);
+        Ok(toolchains)
+    }
+
+    /// Removes the toolchain for `version`, returns `false` if it is not
+    /// cached or a running command holds a lease on it
+    pub fn evict(&self, version: &Version) -> Result<bool> {
+        let path = self.root.join(version.to_string());
+
+        if !path.is_dir() {
+            return Ok(false);
+        }
+
+        if self.is_leased(version)? {
+            tracing::debug!(%version, "Toolchain is in use, not evicting it");
+            return Ok(false);
+        }
+
+        remove_dir_all(path)?;
+        Ok(true)
+    }
+
+    /// Evicts the least recently used toolchains, except `keep` and the ones
+    /// in use, until the cache is at most `max_size` bytes. Returns the
+    /// evicted versions.
+    pub fn enforce_limit(&self, max_size: u64, keep: Option<&Version>) -> Result<Vec<Version>> {
+        let toolchains = self.list()?;
+        let mut total: u64 = toolchains.iter().map(|toolchain| toolchain.size).sum();
+        let mut evicted = Vec::new();
+
+        for toolchain in toolchains {
+            if total <= max_size {
+                break;
+            }
+
+            if keep == Some(&toolchain.version) || self.is_leased(&toolchain.version)? {
+                continue;
+            }
+
+            remove_dir_all(&toolchain.path)?;
+            total = total.saturating_sub(toolchain.size);
+            evicted.push(toolchain.version);
+        }
+
+        Ok(evicted)
+    }
+}
+
+fn touch(toolchain_path: &Path) -> Result<()> {
+    File::create(toolchain_path.join(LAST_USED_FILENAME))?.set_modified(SystemTime::now())?;
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+
+    for entry in read_dir(path)? {
+        let metadata = entry?.metadata()?;
+
+        if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn cache_toolchain(cache: &ToolchainCache, version: &Version, size: usize, age: u64) {
+        let staged = cache.root.with_file_name(format!("staged-{version}"));
+
+        create_dir_all(&cache.root).unwrap();
+        create_dir_all(&staged).unwrap();
+        write(staged.join("fluvio"), vec![0u8; size]).unwrap();
+
+        let path = cache.insert(version, &staged).unwrap();
+
+        File::options()
+            .write(true)
+            .open(path.join(LAST_USED_FILENAME))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_toolchains() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ToolchainCache::new(tmp.path().join(TOOLCHAINS_DIR), tmp.path().join("leases"));
+        let (oldest, older, recent) = (
+            Version::new(0, 11, 6),
+            Version::new(0, 11, 7),
+            Version::new(0, 11, 8),
+        );
+
+        cache_toolchain(&cache, &oldest, 100, 300);
+        cache_toolchain(&cache, &older, 100, 200);
+        cache_toolchain(&cache, &recent, 100, 100);
+
+        assert_eq!(
+            cache
+                .list()
+                .unwrap()
+                .into_iter()
+                .map(|toolchain| toolchain.version)
+                .collect::<Vec<_>>(),
+            vec![oldest.clone(), older.clone(), recent.clone()]
+        );
+
+        // The oldest toolchain is kept while in use
+        assert_eq!(
+            cache.enforce_limit(150, Some(&oldest)).unwrap(),
+            vec![older.clone(), recent.clone()]
+        );
+        assert!(cache.get(&oldest).unwrap().is_some());
+        assert!(cache.get(&recent).unwrap().is_none());
+        assert!(cache.enforce_limit(150, None).unwrap().is_empty());
+        assert!(cache.evict(&oldest).unwrap());
+        assert!(!cache.evict(&oldest).unwrap());
+    }
+
+    #[test]
+    fn keeps_leased_toolchains() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ToolchainCache::new(tmp.path().join(TOOLCHAINS_DIR), tmp.path().join("leases"));
+        let (older, recent) = (Version::new(0, 11, 7), Version::new(0, 11, 8));
+
+        cache_toolchain(&cache, &older, 100, 200);
+        cache_toolchain(&cache, &recent, 100, 100);
+
+        let lease = cache.lease(&older).unwrap();
+
+        assert!(!cache.evict(&older).unwrap());
+        assert_eq!(cache.enforce_limit(0, None).unwrap(), vec![recent]);
+        assert!(cache.get(&older).unwrap().is_some());
+
+        drop(lease);
+        assert_eq!(cache.enforce_limit(0, None).unwrap(), vec![older]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Transparency Checks
+//!
+//! Opt-in verification of downloaded artifacts against the digests the
+//! project published for a release, configured with `FVM_TRANSPARENCY_URL`
+//! or the `transparency_url` key in `settings.toml`. When configured, an
+//! installation fails if the manifest can't be fetched or any digest isn't
+//! listed in it.
+
+use std::env::var;
+
+use anyhow::{Result, anyhow};
+
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::{
+    Artifact, Client, PackageSet, TransparencyManifest, transparency_manifest_url,
+};
+
+use super::notify::Notify;
+use super::settings::Settings;
+
+/// Environment variable with the transparency manifest URL template
+pub const FVM_TRANSPARENCY_URL_ENV_VAR: &str = "FVM_TRANSPARENCY_URL";
+
+/// Retrieves the transparency manifest URL template from
+/// `FVM_TRANSPARENCY_URL`, then the `transparency_url` key in `settings.toml`
+pub fn transparency_url() -> Result<Option<String>> {
+    if let Ok(url) = var(FVM_TRANSPARENCY_URL_ENV_VAR)
+        && !url.is_empty()
+    {
+        return Ok(Some(url));
+    }
+
+    Settings::configured_transparency_url()
+}
+
+/// Fetches the transparency manifest for `package_set` from `template` and
+/// verifies the digests of `artifacts` were released
+pub async fn verify_transparency(
+    template: &str,
+    package_set: &PackageSet,
+    artifacts: &[Artifact],
+    notify: Notify,
+) -> Result<()> {
+    let url = transparency_manifest_url(template, &package_set.pkgset, &package_set.arch);
+
+    notify.info(format!("Verifying artifact digests against {url}"));
+
+    let manifest = Client::default()
+        .fetch_transparency_manifest(&url)
+        .await
+        .map_err(|err| anyhow!("Failed to fetch transparency manifest: {err}"))?;
+
+    check_transparency(&manifest, package_set, artifacts)?;
+    notify.done("Artifact digests match the released digests");
+
+    Ok(())
+}
+
+fn check_transparency(
+    manifest: &TransparencyManifest,
+    package_set: &PackageSet,
+    artifacts: &[Artifact],
+) -> Result<()> {
+    let issues = manifest.verify(&package_set.pkgset, &package_set.arch, artifacts);
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+
+    let issues = issues
+        .iter()
+        .map(|issue| format!("  - {issue}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Err(Failure::new(
+        FailureKind::Integrity,
+        format!("Artifacts don't match the transparency manifest, refusing to install:\n{issues}"),
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use super::*;
+
+    fn package_set(digest: &str) -> PackageSet {
+        PackageSet {
+            pkgset: Version::new(0, 11, 8),
+            arch: "aarch64-apple-darwin".to_string(),
+            release: None,
+            artifacts: vec![Artifact {
+                name: "fluvio".to_string(),
+                version: Version::new(0, 11, 8),
+                download_url: "https://example.com/fluvio.zip".to_string(),
+                sha256_digest: Some(digest.to_string()),
+                variant: None,
+                channel: None,
+                entry: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn rejects_unreleased_digests() {
+        let manifest: TransparencyManifest = serde_json::from_str(
+            r#"{ "version": "0.11.8", "target": "aarch64-apple-darwin", "artifacts": { "fluvio": "aa11" } }"#,
+        )
+        .unwrap();
+        let released = package_set("aa11");
+        let tampered = package_set("ff00");
+
+        assert!(check_transparency(&manifest, &released, &released.artifacts).is_ok());
+
+        let err = check_transparency(&manifest, &tampered, &tampered.artifacts).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("fluvio digest ff00 differs from the released digest aa11")
+        );
+    }
+}
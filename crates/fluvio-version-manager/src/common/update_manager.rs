@@ -2,12 +2,13 @@ use std::path::PathBuf;
 
 use anyhow::{bail, Result};
 use semver::Version;
-use tempfile::TempDir;
 
-use fluvio_artifacts_util::fvm::{Client as FvmClient, Channel as FvmChannel, Download as _};
+use fluvio_artifacts_util::fvm::{Channel as FvmChannel, Download as _};
 
 use crate::common::executable::{remove_fvm_binary_if_exists, set_executable_mode};
 
+use super::github::fvm_client;
+use super::janitor::TrackedTempDir;
 use super::notify::Notify;
 use super::workdir::fvm_bin_path;
 use super::TARGET;
@@ -37,10 +38,10 @@ impl UpdateManager {
     }
 
     /// Downloads Fluvio Version Manager binary into a temporary directory
-    async fn download(&self, version: &Version) -> Result<(TempDir, PathBuf)> {
-        let tmp_dir = TempDir::new()?;
+    async fn download(&self, version: &Version) -> Result<(TrackedTempDir, PathBuf)> {
+        let tmp_dir = TrackedTempDir::new()?;
         let channel = FvmChannel::Tag(version.clone());
-        let client = FvmClient;
+        let client = fvm_client()?;
 
         // Fetch the unfiltered package set for the requested version and
         // current target so that the `fvm` binary artifact is included.
@@ -0,0 +1,253 @@
+//! Version Usage
+//!
+//! Keeps track of when each installed version was last activated with
+//! `fvm switch` and last executed, in the `usage.json` state file. This data
+//! is used to tell which versions are safe to prune.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::workdir::fvm_workdir_path;
+
+/// The name of the usage state file stored in the FVM workdir
+pub const USAGE_STATE_FILENAME: &str = "usage.json";
+
+/// Usage timestamps for an installed version, in seconds since UNIX Epoch
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct VersionUsage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activated: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_executed: Option<u64>,
+}
+
+impl VersionUsage {
+    /// Most recent of the activation and execution timestamps
+    pub fn last_used(&self) -> Option<u64> {
+        self.last_activated.max(self.last_executed)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UsageState {
+    /// Usage by version directory name (e.g. `stable`, `0.11.4`)
+    pub versions: HashMap<String, VersionUsage>,
+}
+
+pub struct UsageTracker {
+    state_path: PathBuf,
+    state: UsageState,
+}
+
+impl UsageTracker {
+    /// Opens the usage state file from the FVM workdir.
+    ///
+    /// Returns `None` if FVM is not installed.
+    pub fn open() -> Result<Option<Self>> {
+        let workdir = fvm_workdir_path()?;
+
+        if !workdir.exists() {
+            return Ok(None);
+        }
+
+        Self::open_at(workdir.join(USAGE_STATE_FILENAME)).map(Some)
+    }
+
+    /// Opens the usage state file at the provided path, if the file doesn't
+    /// exist an empty state is used.
+    pub fn open_at(state_path: impl Into<PathBuf>) -> Result<Self> {
+        let state_path = state_path.into();
+        let state = load_state(&state_path, |contents| Ok(serde_json::from_str(contents)?))?
+            .unwrap_or_default();
+
+        Ok(Self { state_path, state })
+    }
+
+    pub fn get(&self, version: &str) -> VersionUsage {
+        self.state
+            .versions
+            .get(version)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Records `version` as activated now
+    pub fn record_activation(&mut self, version: &str) -> Result<()> {
+        self.state
+            .versions
+            .entry(version.to_string())
+            .or_default()
+            .last_activated = Some(now());
+        self.save()
+    }
+
+    /// Records `version` as executed now, through a version shim
+    pub fn record_execution(&mut self, version: &str) -> Result<()> {
+        self.state
+            .versions
+            .entry(version.to_string())
+            .or_default()
+            .last_executed = Some(now());
+        self.save()
+    }
+
+    /// Stops tracking `version`, used once the version is uninstalled
+    pub fn forget(&mut self, version: &str) -> Result<()> {
+        if self.state.versions.remove(version).is_some() {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        write_state(
+            &self.state_path,
+            &serde_json::to_string_pretty(&self.state)?,
+        )
+    }
+}
+
+/// Determines if a version was not used for at least `unused_for`.
+///
+/// Versions never activated nor executed are measured from `installed_at`.
+pub fn is_unused(usage: &VersionUsage, installed_at: u64, unused_for: Duration) -> bool {
+    let last_used = usage.last_used().unwrap_or(installed_at);
+
+    now().saturating_sub(last_used) >= unused_for.as_secs()
+}
+
+/// Seconds since UNIX Epoch when the version at `path` was installed, based
+/// on the modification time of the directory
+pub fn installed_at(path: &Path) -> u64 {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Formats a timestamp relative to now, e.g. `3d ago`
+pub fn format_last_used(timestamp: Option<u64>) -> String {
+    let Some(timestamp) = timestamp else {
+        return String::from("never");
+    };
+    let elapsed = now().saturating_sub(timestamp);
+
+    match elapsed {
+        0..60 => String::from("just now"),
+        60..3_600 => format!("{}m ago", elapsed / 60),
+        3_600..86_400 => format!("{}h ago", elapsed / 3_600),
+        _ => format!("{}d ago", elapsed / 86_400),
+    }
+}
+
+/// Formats a size in bytes using binary units, e.g. `12.5 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn records_and_forgets_versions() {
+        let tmp = TempDir::new().unwrap();
+        let state_path = tmp.path().join(USAGE_STATE_FILENAME);
+        let mut usage = UsageTracker::open_at(&state_path).unwrap();
+
+        usage.record_activation("stable").unwrap();
+
+        let reopened = UsageTracker::open_at(&state_path).unwrap();
+        let stable = reopened.get("stable");
+
+        assert!(stable.last_activated.is_some());
+        assert_eq!(stable.last_executed, None);
+        assert_eq!(reopened.get("0.11.4"), VersionUsage::default());
+
+        usage.record_execution("stable").unwrap();
+        assert!(
+            UsageTracker::open_at(&state_path)
+                .unwrap()
+                .get("stable")
+                .last_executed
+                .is_some()
+        );
+
+        usage.forget("stable").unwrap();
+        assert_eq!(
+            UsageTracker::open_at(&state_path).unwrap().get("stable"),
+            VersionUsage::default()
+        );
+    }
+
+    #[test]
+    fn determines_unused_versions() {
+        let day = 60 * 60 * 24;
+        let installed_at = now() - 200 * day;
+        let unused_for = Duration::from_secs(90 * day);
+
+        assert!(is_unused(
+            &VersionUsage::default(),
+            installed_at,
+            unused_for
+        ));
+        assert!(!is_unused(
+            &VersionUsage {
+                last_activated: Some(now() - 100 * day),
+                last_executed: Some(now() - day),
+            },
+            installed_at,
+            unused_for
+        ));
+        assert!(is_unused(
+            &VersionUsage {
+                last_activated: Some(now() - 100 * day),
+                last_executed: None,
+            },
+            installed_at,
+            unused_for
+        ));
+    }
+
+    #[test]
+    fn formats_sizes_and_timestamps() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(45 * 1024 * 1024), "45.0 MiB");
+        assert_eq!(format_last_used(None), "never");
+        assert_eq!(format_last_used(Some(now() - 7_200)), "2h ago");
+        assert_eq!(format_last_used(Some(now() - 3 * 86_400)), "3d ago");
+    }
+}
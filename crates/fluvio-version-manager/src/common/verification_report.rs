@@ -0,0 +1,58 @@
+//! Verification Reports
+//!
+//! When enabled with `FVM_VERIFICATION_REPORT=1` or the
+//! `verification_report` key in `settings.toml`, installs write a
+//! `verification-report.json` file next to the installed binaries, see
+//! [`VerificationReport`].
+
+use std::env::var;
+
+use anyhow::Result;
+
+use fluvio_artifacts_util::verification::VerificationReport;
+
+use super::settings::Settings;
+
+/// Environment variable enabling verification reports
+pub const FVM_VERIFICATION_REPORT_ENV_VAR: &str = "FVM_VERIFICATION_REPORT";
+
+/// Version of FVM recorded in reports
+pub const FVM_VERSION: &str = include_str!("../../../../VERSION");
+
+/// Whether installs write verification reports, `FVM_VERIFICATION_REPORT`
+/// takes precedence over `settings.toml`
+pub fn verification_reports_enabled() -> Result<bool> {
+    if let Ok(value) = var(FVM_VERIFICATION_REPORT_ENV_VAR)
+        && let Some(enabled) = parse_flag(&value)
+    {
+        return Ok(enabled);
+    }
+
+    Ok(Settings::configured_verification_report()?.unwrap_or_default())
+}
+
+/// Empty report for the downloads of `subject` built for `target`
+pub fn fvm_report(subject: impl Into<String>, target: impl Into<String>) -> VerificationReport {
+    VerificationReport::new(subject, target, "fvm", FVM_VERSION.trim())
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(parse_flag("1"), Some(true));
+        assert_eq!(parse_flag(" On "), Some(true));
+        assert_eq!(parse_flag("false"), Some(false));
+        assert_eq!(parse_flag(""), None);
+    }
+}
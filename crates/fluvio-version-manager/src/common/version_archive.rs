@@ -0,0 +1,526 @@
+//! Version Archive
+//!
+//! A version archive is a gzipped tarball holding the contents of a version
+//! directory (`~/.fvm/versions/<channel>`) along with a checksums file. It is
+//! used to copy a known-good installation between hosts without access to
+//! GitHub.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, create_dir_all, rename};
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use fluvio_artifacts_util::extraction::{ExtractionBudget, ExtractionLimits};
+use fluvio_artifacts_util::failure::{Failure, FailureKind};
+use fluvio_artifacts_util::fvm::Channel;
+
+use super::checksum::{ChecksumJob, ChecksumStatus, verify_checksums};
+use super::executable::set_executable_mode;
+use super::janitor::TrackedTempDir;
+use super::manifest::{PACKAGE_SET_MANIFEST_FILENAME, VersionManifest};
+use super::version_directory::VersionDirectory;
+
+/// The name of the checksums file included in every version archive
+pub const VERSION_ARCHIVE_CHECKSUMS_FILENAME: &str = "checksums.json";
+
+/// Extension used for version archives
+pub const VERSION_ARCHIVE_EXT: &str = "tar.gz";
+
+/// SHA-256 digests for the binaries included in a version archive, keyed by
+/// file name.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ArchiveChecksums {
+    pub sha256: BTreeMap<String, String>,
+}
+
+pub struct VersionArchive;
+
+impl VersionArchive {
+    /// Builds the default archive file name for a version directory,
+    /// e.g. `fluvio-stable-0.11.8.tar.gz`
+    pub fn file_name(manifest: &VersionManifest) -> String {
+        if manifest.channel.is_version_tag() {
+            format!("fluvio-{}.{VERSION_ARCHIVE_EXT}", manifest.version)
+        } else {
+            format!(
+                "fluvio-{}-{}.{VERSION_ARCHIVE_EXT}",
+                manifest.channel, manifest.version
+            )
+        }
+    }
+
+    /// Writes the contents of the [`VersionDirectory`] into `writer` as a
+    /// gzipped tarball including the manifest and the binaries checksums.
+    pub fn export<W: Write>(version_dir: &VersionDirectory, writer: W) -> Result<()> {
+        let encoder = GzEncoder::new(writer, Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let mut checksums = ArchiveChecksums::default();
+
+        for entry in &version_dir.contents {
+            let filename = entry
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or(anyhow!(
+                    "Failed to get filename from path: {}",
+                    entry.display()
+                ))?;
+            let bytes = std::fs::read(entry)?;
+
+            checksums
+                .sha256
+                .insert(filename.to_string(), sha256_hex(&bytes));
+            append_bytes(&mut builder, filename, &bytes, 0o755)?;
+        }
+
+        let manifest = serde_json::to_vec_pretty(&version_dir.manifest)?;
+        append_bytes(
+            &mut builder,
+            PACKAGE_SET_MANIFEST_FILENAME,
+            &manifest,
+            0o644,
+        )?;
+
+        let checksums = serde_json::to_vec_pretty(&checksums)?;
+        append_bytes(
+            &mut builder,
+            VERSION_ARCHIVE_CHECKSUMS_FILENAME,
+            &checksums,
+            0o644,
+        )?;
+
+        builder.into_inner()?.finish()?.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads a version archive from `reader` and installs it under
+    /// `versions_path`, verifying every binary against the archive checksums.
+    ///
+    /// Contents are unpacked into a staging directory first so a failed
+    /// verification never leaves a partial version directory behind. Binaries
+    /// are streamed to disk and hashed in parallel on `threads` threads, see
+    /// [`verify_checksums`]. Unpacking is metered by the [`ExtractionLimits`]
+    /// of the environment for an archive of `archive_size` bytes.
+    ///
+    /// Archives are untrusted: their checksums catch corruption but not
+    /// tampering, so the channel of the manifest must name a single version
+    /// directory under `versions_path`. `admit` is called with the manifest
+    /// before any binary is verified, and rejects the version by returning an
+    /// error, e.g. when the version policy does not allow it.
+    pub fn import<R: Read>(
+        reader: R,
+        archive_size: u64,
+        versions_path: &Path,
+        force: bool,
+        threads: Option<usize>,
+        admit: impl FnOnce(&VersionManifest) -> Result<()>,
+    ) -> Result<VersionDirectory> {
+        create_dir_all(versions_path)?;
+
+        let staging = TrackedTempDir::new_in(versions_path)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(reader));
+        let mut budget = ExtractionLimits::from_env()?.budget(archive_size);
+        let mut manifest = None;
+        let mut checksums = None;
+        let mut files = BTreeSet::new();
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+
+            budget.count_entry()?;
+
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?.to_path_buf();
+            let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+                bail!("Invalid entry in version archive: {}", path.display());
+            };
+
+            if path.components().count() != 1 {
+                bail!(
+                    "Unexpected nested entry in version archive: {}",
+                    path.display()
+                );
+            }
+
+            match filename {
+                PACKAGE_SET_MANIFEST_FILENAME => {
+                    manifest = Some(read_entry(filename, &mut entry, &mut budget)?)
+                }
+                VERSION_ARCHIVE_CHECKSUMS_FILENAME => {
+                    checksums = Some(read_entry(filename, &mut entry, &mut budget)?)
+                }
+                _ => {
+                    let filename = filename.to_string();
+
+                    budget.copy(
+                        &filename,
+                        None,
+                        &mut entry,
+                        &mut File::create(staging.path().join(&filename))?,
+                    )?;
+                    files.insert(filename);
+                }
+            }
+        }
+
+        let manifest = manifest.ok_or(anyhow!(
+            "Version archive is missing {PACKAGE_SET_MANIFEST_FILENAME}"
+        ))?;
+        let manifest: VersionManifest = std::str::from_utf8(&manifest)?.parse()?;
+        let channel_dir = channel_dir_name(&manifest.channel)?;
+
+        admit(&manifest)?;
+
+        let checksums = checksums.ok_or(anyhow!(
+            "Version archive is missing {VERSION_ARCHIVE_CHECKSUMS_FILENAME}"
+        ))?;
+        let checksums: ArchiveChecksums = serde_json::from_slice(&checksums)?;
+
+        for name in checksums.sha256.keys() {
+            if !files.contains(name) {
+                bail!("Version archive is missing binary {name} listed in checksums");
+            }
+        }
+
+        let mut jobs = Vec::with_capacity(files.len());
+
+        for name in files {
+            let Some(expected) = checksums.sha256.get(&name) else {
+                bail!("Version archive includes {name} which has no recorded checksum");
+            };
+
+            jobs.push(ChecksumJob {
+                path: staging.path().join(&name),
+                expected: Some(expected.to_owned()),
+                name,
+            });
+        }
+
+        for result in verify_checksums(jobs, threads)? {
+            if let ChecksumStatus::Mismatch { actual } = &result.status {
+                let name = &result.job.name;
+                let expected = result.job.expected.as_deref().unwrap_or_default();
+
+                tracing::error!(name, %expected, %actual, "Checksum mismatch on import");
+                return Err(Failure::new(
+                    FailureKind::Integrity,
+                    format!("DANGER: Checksum did not match for {name} in version archive"),
+                )
+                .into());
+            }
+
+            if result.status.is_failure() {
+                bail!(
+                    "Failed to verify {} in version archive: {}",
+                    result.job.name,
+                    result.status
+                );
+            }
+
+            set_executable_mode(&result.job.path)?;
+        }
+
+        manifest.write(staging.path())?;
+
+        let version_path = versions_path.join(channel_dir);
+
+        if version_path.exists() {
+            if !force {
+                bail!(
+                    "Fluvio version {} is already installed at {}",
+                    manifest.channel,
+                    version_path.display()
+                );
+            }
+
+            VersionDirectory::open(version_path.clone())?.remove()?;
+        }
+
+        rename(staging.into_path(), &version_path)?;
+
+        VersionDirectory::open(version_path)
+    }
+}
+
+/// Appends `bytes` as a regular file at `path` to a tar archive
+pub fn append_bytes<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    bytes: &[u8],
+    mode: u32,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+
+    header.set_size(bytes.len() as u64);
+    header.set_mode(mode);
+    header.set_cksum();
+    builder.append_data(&mut header, path, bytes)?;
+
+    Ok(())
+}
+
+/// Reads the metadata entry `name`, the size in its header is not trusted
+fn read_entry<R: Read>(
+    name: &str,
+    entry: &mut tar::Entry<R>,
+    budget: &mut ExtractionBudget,
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    budget.copy(name, None, entry, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Name of the version directory of `channel`. Manifests are deserialized
+/// without going through [`Channel::from_str`], so the channel is parsed
+/// again and must be a single path segment, e.g. `{"other": "../../x"}` is
+/// rejected.
+fn channel_dir_name(channel: &Channel) -> Result<String> {
+    let name = channel.to_string();
+    let invalid = || anyhow!("Invalid channel {name:?} in version archive manifest");
+
+    if Channel::from_str(&name).map_err(|_| invalid())? != *channel || name.contains(['/', '\\']) {
+        return Err(invalid());
+    }
+
+    let mut components = Path::new(&name).components();
+
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(name),
+        _ => Err(invalid()),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{copy, read_dir};
+    use std::path::PathBuf;
+
+    use fluvio_artifacts_util::fvm::Channel;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn make_version_directory() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join("version")
+            .join("0.10.14");
+
+        for entry in read_dir(fixtures).unwrap() {
+            let entry_path = entry.unwrap().path();
+            let target_path = tmp.path().join(entry_path.file_name().unwrap());
+
+            copy(&entry_path, &target_path).unwrap();
+        }
+
+        tmp
+    }
+
+    fn export_fixture() -> (TempDir, PathBuf) {
+        let version_tmp = make_version_directory();
+        let version_dir = VersionDirectory::open(version_tmp.path().to_path_buf()).unwrap();
+        let out = TempDir::new().unwrap();
+        let archive_path = out
+            .path()
+            .join(VersionArchive::file_name(&version_dir.manifest));
+        let file = File::create(&archive_path).unwrap();
+
+        VersionArchive::export(&version_dir, file).unwrap();
+
+        (out, archive_path)
+    }
+
+    fn archive_size(path: &Path) -> u64 {
+        path.metadata().unwrap().len()
+    }
+
+    /// Exports the fixture version directory, passing the contents of every
+    /// entry through `rewrite`
+    fn rewrite_export(rewrite: impl Fn(&str, Vec<u8>) -> Vec<u8>) -> Vec<u8> {
+        let version_tmp = make_version_directory();
+        let version_dir = VersionDirectory::open(version_tmp.path().to_path_buf()).unwrap();
+        let mut archive: Vec<u8> = Vec::new();
+
+        VersionArchive::export(&version_dir, &mut archive).unwrap();
+
+        let mut source = tar::Archive::new(GzDecoder::new(archive.as_slice()));
+        let mut rewritten = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        for entry in source.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let mut bytes = Vec::new();
+
+            entry.read_to_end(&mut bytes).unwrap();
+            append_bytes(&mut rewritten, &path, &rewrite(&path, bytes), 0o755).unwrap();
+        }
+
+        rewritten.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn exports_and_imports_version_directory() {
+        let (_out, archive_path) = export_fixture();
+        let versions = TempDir::new().unwrap();
+        let imported = VersionArchive::import(
+            File::open(&archive_path).unwrap(),
+            archive_size(&archive_path),
+            versions.path(),
+            false,
+            None,
+            |_| Ok(()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported.path,
+            versions.path().join("0.10.14"),
+            "version must be stored under its channel name"
+        );
+        assert_eq!(
+            imported.manifest.channel,
+            Channel::parse("0.10.14").unwrap()
+        );
+        assert_eq!(imported.contents.len(), 1);
+    }
+
+    #[test]
+    fn refuses_to_overwrite_installed_version_without_force() {
+        let (_out, archive_path) = export_fixture();
+        let versions = TempDir::new().unwrap();
+
+        VersionArchive::import(
+            File::open(&archive_path).unwrap(),
+            archive_size(&archive_path),
+            versions.path(),
+            false,
+            None,
+            |_| Ok(()),
+        )
+        .unwrap();
+
+        let result = VersionArchive::import(
+            File::open(&archive_path).unwrap(),
+            archive_size(&archive_path),
+            versions.path(),
+            false,
+            None,
+            |_| Ok(()),
+        );
+
+        assert!(result.is_err());
+        VersionArchive::import(
+            File::open(&archive_path).unwrap(),
+            archive_size(&archive_path),
+            versions.path(),
+            true,
+            None,
+            |_| Ok(()),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn does_not_import_rejected_version() {
+        let (_out, archive_path) = export_fixture();
+        let versions = TempDir::new().unwrap();
+        let result = VersionArchive::import(
+            File::open(&archive_path).unwrap(),
+            archive_size(&archive_path),
+            versions.path(),
+            false,
+            None,
+            |manifest| bail!("Fluvio version {} is not allowed", manifest.version),
+        );
+
+        assert!(result.unwrap_err().to_string().contains("not allowed"));
+        assert!(!versions.path().join("0.10.14").exists());
+    }
+
+    #[test]
+    fn fails_to_import_tampered_archive() {
+        // Replace the binary contents while keeping the original checksums
+        let tampered = rewrite_export(|path, bytes| {
+            if path == "fluvio" {
+                b"not-fluvio".to_vec()
+            } else {
+                bytes
+            }
+        });
+        let versions = TempDir::new().unwrap();
+        let result = VersionArchive::import(
+            tampered.as_slice(),
+            tampered.len() as u64,
+            versions.path(),
+            false,
+            None,
+            |_| Ok(()),
+        );
+
+        assert!(result.is_err());
+        assert!(
+            result.err().unwrap().to_string().contains("Checksum"),
+            "error must report the checksum mismatch"
+        );
+        assert!(
+            !versions.path().join("0.10.14").exists(),
+            "no version directory must be created on failure"
+        );
+    }
+
+    #[test]
+    fn rejects_channels_escaping_versions_directory() {
+        for channel in [
+            r#"{"other": "../1"}"#,
+            r#"{"other": "a/1"}"#,
+            r#"{"other": "..\\1"}"#,
+        ] {
+            let archive = rewrite_export(|path, bytes| {
+                if path == PACKAGE_SET_MANIFEST_FILENAME {
+                    let mut manifest: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+                    manifest["channel"] = serde_json::from_str(channel).unwrap();
+                    serde_json::to_vec(&manifest).unwrap()
+                } else {
+                    bytes
+                }
+            });
+            let root = TempDir::new().unwrap();
+            let versions = root.path().join("versions");
+            let result = VersionArchive::import(
+                archive.as_slice(),
+                archive.len() as u64,
+                &versions,
+                true,
+                None,
+                |_| Ok(()),
+            );
+
+            assert!(
+                result.unwrap_err().to_string().contains("Invalid channel"),
+                "{channel} must be rejected"
+            );
+            assert_eq!(read_dir(root.path()).unwrap().count(), 1);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs::{read_dir, copy, create_dir_all, remove_dir_all};
 use std::fs::remove_file;
 
@@ -8,8 +9,12 @@ use anyhow::{bail, Result};
 use fluvio_artifacts_util::fvm::{Artifact, Channel, PackageSet};
 use semver::Version;
 
+use crate::common::checksum::ChecksumJob;
+use crate::common::install_hooks::{hooks_in_use, remove_hooks};
 use crate::common::manifest::{PACKAGE_SET_MANIFEST_FILENAME, VersionManifest};
 use crate::common::settings::Settings;
+use crate::common::shim::install_shims;
+use crate::common::usage::UsageTracker;
 use crate::common::workdir::fluvio_binaries_path;
 use crate::common::TARGET;
 
@@ -70,8 +75,15 @@ impl VersionDirectory {
         })
     }
 
-    /// Deletes this [`VersionDirectory`] directory
+    /// Deletes this [`VersionDirectory`] directory, along with the assets
+    /// placed by its install hooks which no other version placed
     pub fn remove(&self) -> Result<()> {
+        if let Some(hooks) = &self.manifest.hooks
+            && let Some(versions_path) = self.path.parent()
+        {
+            remove_hooks(hooks, &hooks_in_use(versions_path, &self.path)?)?;
+        }
+
         if self.path.exists() {
             tracing::info!(?self.path, "Removing version directory");
             remove_dir_all(&self.path)?;
@@ -80,6 +92,56 @@ impl VersionDirectory {
         Ok(())
     }
 
+    /// Total size in bytes of the files in this [`VersionDirectory`]
+    pub fn disk_usage(&self) -> Result<u64> {
+        let mut total = self
+            .path
+            .join(PACKAGE_SET_MANIFEST_FILENAME)
+            .metadata()?
+            .len();
+
+        for entry in &self.contents {
+            total += entry.metadata()?.len();
+        }
+
+        Ok(total)
+    }
+
+    /// Checksum jobs for the binaries in this [`VersionDirectory`], expected to
+    /// match the digests recorded in the manifest at install time. Binaries
+    /// listed in the manifest but missing from disk are included, so they
+    /// are reported as missing.
+    pub fn checksum_jobs(&self) -> Vec<ChecksumJob> {
+        let mut jobs: BTreeMap<String, ChecksumJob> = BTreeMap::new();
+
+        for entry in &self.contents {
+            if let Some(name) = entry.file_name().and_then(|name| name.to_str()) {
+                jobs.insert(
+                    name.to_string(),
+                    ChecksumJob {
+                        name: name.to_string(),
+                        path: entry.to_owned(),
+                        expected: None,
+                    },
+                );
+            }
+        }
+
+        for artifact in self.manifest.contents.iter().flatten() {
+            let job = jobs
+                .entry(artifact.name.clone())
+                .or_insert_with(|| ChecksumJob {
+                    name: artifact.name.clone(),
+                    path: self.path.join(&artifact.name),
+                    expected: None,
+                });
+
+            job.expected = artifact.installed_sha256_digest.clone();
+        }
+
+        jobs.into_values().collect()
+    }
+
     /// Sets this version as the active Fluvio Version
     pub fn set_active(&self) -> Result<()> {
         // Verify `~/.fluvio/bin` exists and create it if it doesn't
@@ -113,6 +175,14 @@ impl VersionDirectory {
 
         Settings::open()?.update_from_manifest(&self.manifest)?;
 
+        if let Err(err) = install_shims(self) {
+            tracing::warn!(%err, "Failed to install version shims");
+        }
+
+        if let Some(mut usage) = UsageTracker::open()? {
+            usage.record_activation(&self.manifest.channel.to_string())?;
+        }
+
         Ok(())
     }
 
@@ -168,13 +238,17 @@ impl VersionDirectory {
                         version,
                         name: va.name.clone(),
                         download_url: String::from("N/A"),
-                        sha256_digest: None,
+                        sha256_digest: va.sha256_digest.clone(),
+                        variant: None,
+                        channel: va.channel.clone(),
+                        entry: None,
                     })
                 })
                 .collect();
             let pkgset = PackageSet {
                 pkgset: self.manifest.version.clone(),
                 arch: String::from(TARGET),
+                release: None,
                 artifacts,
             };
 
@@ -282,6 +356,27 @@ mod tests {
         assert_eq!(version_dir.manifest, manifest);
     }
 
+    #[test]
+    fn builds_checksum_jobs_from_manifest() {
+        let tmpdir = make_version_directory().unwrap();
+        let mut version_dir = VersionDirectory::open(tmpdir.path().to_path_buf()).unwrap();
+        let mut fluvio = VersionedArtifact::new(TEST_BINARY_NAME, "0.10.14");
+
+        fluvio.installed_sha256_digest = Some(TEST_BINARY_CHECKSUM.to_string());
+        version_dir.manifest.contents = Some(vec![
+            fluvio,
+            VersionedArtifact::new("fluvio-run", "0.10.14"),
+        ]);
+
+        let jobs = version_dir.checksum_jobs();
+
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].name, TEST_BINARY_NAME);
+        assert_eq!(jobs[0].expected.as_deref(), Some(TEST_BINARY_CHECKSUM));
+        assert_eq!(jobs[1].path, tmpdir.path().join("fluvio-run"));
+        assert!(!jobs[1].path.exists());
+    }
+
     #[test]
     fn fails_to_open_directory_without_manifest() {
         let tmpdir = make_version_directory().unwrap();
@@ -388,16 +483,27 @@ mod tests {
                 VersionedArtifact {
                     name: String::from("fluvio"),
                     version: String::from("0.11.8"),
+                    sha256_digest: None,
+                    installed_sha256_digest: None,
+                    channel: None,
                 },
                 VersionedArtifact {
                     name: String::from("fluvio-cloud"),
                     version: String::from("0.2.22"),
+                    sha256_digest: None,
+                    installed_sha256_digest: None,
+                    channel: None,
                 },
                 VersionedArtifact {
                     name: String::from("cdk"),
                     version: String::from("0.11.8"),
+                    sha256_digest: None,
+                    installed_sha256_digest: None,
+                    channel: None,
                 },
             ]),
+            hooks: None,
+            provenance: None,
         };
         let version_directory = VersionDirectory {
             manifest: version_manifest,
@@ -407,24 +513,34 @@ mod tests {
         let package_set = PackageSet {
             pkgset: Version::parse("0.11.8").unwrap(),
             arch: TARGET.to_owned(),
+            release: None,
             artifacts: vec![
                 Artifact {
                     name: String::from("fluvio"),
                     version: Version::parse("0.11.8").unwrap(),
                     download_url: String::from("N/A"),
                     sha256_digest: None,
+                    variant: None,
+                    channel: None,
+                    entry: None,
                 },
                 Artifact {
                     name: String::from("fluvio-cloud"),
                     version: Version::parse("0.2.22").unwrap(),
                     download_url: String::from("N/A"),
                     sha256_digest: None,
+                    variant: None,
+                    channel: None,
+                    entry: None,
                 },
                 Artifact {
                     name: String::from("cdk"),
                     version: Version::parse("0.11.8").unwrap(),
                     download_url: String::from("N/A"),
                     sha256_digest: None,
+                    variant: None,
+                    channel: None,
+                    entry: None,
                 },
             ],
         };
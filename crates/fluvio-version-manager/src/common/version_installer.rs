@@ -1,21 +1,47 @@
-use std::path::PathBuf;
-use std::fs::{copy, create_dir, remove_file, rename};
+use std::path::{Path, PathBuf};
+use std::fs::{copy, create_dir, create_dir_all, remove_dir_all, remove_file, rename};
 
 use anyhow::{anyhow, Result};
-use tempfile::TempDir;
+use futures_util::{StreamExt, TryStreamExt};
+use fluvio_future::task::{run_block_on, spawn_blocking};
 
-use fluvio_artifacts_util::fvm::{Artifact, Channel, Download, PackageSet};
+use fluvio_artifacts_util::htclient::transfer::format_bytes;
+use fluvio_artifacts_util::progress::{ProgressGroup, ProgressMode, ProgressOutput};
+use fluvio_artifacts_util::sha256_digest;
+use fluvio_artifacts_util::verification::{VERIFICATION_REPORT_FILENAME, VerificationReport};
+use fluvio_artifacts_util::fvm::{
+    ARTIFACT_ASSETS_DIR, Artifact, Channel, Download, PackageSet, PackageSetDiff,
+};
 
 use super::executable::set_executable_mode;
+use super::install_hooks::{AssetDirs, InstallHook, hooks_in_use, plan_hooks, remove_hooks, run_hooks};
+use super::install_profile::InstallProfile;
+use super::janitor::TrackedTempDir;
 use super::manifest::{VersionManifest, VersionedArtifact, PACKAGE_SET_MANIFEST_FILENAME};
 use super::notify::Notify;
+use super::provenance::Provenance;
+use super::transparency::{transparency_url, verify_transparency};
+use super::verification_report::{fvm_report, verification_reports_enabled};
 use super::version_directory::VersionDirectory;
 use super::workdir::fvm_versions_path;
 
+/// Number of artifacts downloaded at once, each on its own blocking thread
+/// as the HTTP client blocks on I/O
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
 pub struct VersionInstaller {
     channel: Channel,
     package_set: PackageSet,
     notify: Notify,
+    /// Whether the version is set as the active version once installed
+    activate: bool,
+    /// Transparency manifest URL template, defaults to the configured one
+    transparency_url: Option<String>,
+    /// Whether completions, man pages and examples are placed
+    assets: bool,
+    /// Whether a verification report is written, defaults to the configured
+    /// setting
+    verification_report: Option<bool>,
 }
 
 impl VersionInstaller {
@@ -24,32 +50,71 @@ impl VersionInstaller {
             channel,
             package_set,
             notify,
+            activate: true,
+            transparency_url: None,
+            assets: true,
+            verification_report: None,
         }
     }
 
+    /// Sets whether the version is set as the active version once installed,
+    /// defaults to `true`
+    pub fn with_activation(mut self, activate: bool) -> Self {
+        self.activate = activate;
+        self
+    }
+
+    /// Verifies artifact digests against the transparency manifest at
+    /// `transparency_url` instead of the configured one
+    pub fn with_transparency_url(mut self, transparency_url: Option<String>) -> Self {
+        self.transparency_url = transparency_url;
+        self
+    }
+
+    /// Installs only the components of `profile`, all of the package set is
+    /// installed without a profile
+    pub fn with_profile(mut self, profile: Option<InstallProfile>) -> Result<Self> {
+        if let Some(profile) = profile {
+            self.package_set = profile.select(self.package_set)?;
+            self.assets = profile.places_assets();
+        }
+
+        Ok(self)
+    }
+
+    /// Writes a verification report next to the binaries instead of using
+    /// the configured setting
+    pub fn with_verification_report(mut self, verification_report: Option<bool>) -> Self {
+        self.verification_report = verification_report;
+        self
+    }
+
     pub async fn install(&self) -> Result<()> {
-        let tmp_dir = self.download(&self.package_set.artifacts).await?;
+        let (tmp_dir, report) = self.download(&self.package_set.artifacts).await?;
         let version_path = self
             .store_artifacts(&tmp_dir, &self.package_set.artifacts)
             .await?;
-        let contents = self
-            .package_set
-            .artifacts
-            .iter()
-            .map(|art| VersionedArtifact::new(art.name.to_owned(), art.version.to_string()))
-            .collect::<Vec<VersionedArtifact>>();
-        let manifest = VersionManifest::new(
+
+        self.write_report(report, &version_path)?;
+        let contents = self.versioned_contents(&version_path)?;
+        let mut manifest = VersionManifest::new(
             self.channel.to_owned(),
             self.package_set.pkgset.clone(),
             contents,
         );
 
+        manifest.hooks = self.place_assets(&version_path, &[])?;
+        manifest.provenance = Some(Provenance::resolve(&self.channel, &self.package_set));
         manifest.write(&version_path)?;
         self.notify.done(format!(
             "Installed fluvio version {}",
             self.package_set.pkgset
         ));
 
+        if !self.activate {
+            return Ok(());
+        }
+
         let version_dir = VersionDirectory::open(version_path)?;
 
         version_dir.set_active()?;
@@ -60,87 +125,249 @@ impl VersionInstaller {
         Ok(())
     }
 
-    pub async fn update(&self, upstream_artifacts: &[Artifact]) -> Result<()> {
-        let tmp_dir = self.download(upstream_artifacts).await?;
-        let version_path = self.store_artifacts(&tmp_dir, upstream_artifacts).await?;
+    /// Brings the installed version up to date with the package set,
+    /// downloading only the artifacts in `diff`
+    pub async fn update(&self, diff: &PackageSetDiff) -> Result<()> {
+        let downloads = diff.downloads();
+        let (tmp_dir, report) = self.download(&downloads).await?;
+        let version_path = self.store_artifacts(&tmp_dir, &downloads).await?;
+
+        self.write_report(report, &version_path)?;
         let mut manifest = VersionManifest::open(version_path.join(PACKAGE_SET_MANIFEST_FILENAME))?;
-        let mut old_versions: Vec<VersionedArtifact> = Vec::with_capacity(upstream_artifacts.len());
-
-        if let Some(ref contents) = manifest.contents {
-            let next: Vec<VersionedArtifact> =
-                contents
-                    .iter()
-                    .fold(Vec::with_capacity(contents.len()), |mut acc, vers_artf| {
-                        if let Some(upstr_art) = upstream_artifacts
-                            .iter()
-                            .find(|art| art.name == vers_artf.name)
-                        {
-                            acc.push(VersionedArtifact::new(
-                                upstr_art.name.to_owned(),
-                                upstr_art.version.to_string(),
-                            ));
-                            old_versions.push(vers_artf.to_owned());
-                        } else {
-                            acc.push(vers_artf.to_owned());
-                        }
-
-                        acc
-                    });
-
-            manifest.contents = Some(next);
+        let previous = manifest.contents.take().unwrap_or_default();
+
+        for name in diff.removed.iter() {
+            let path = version_path.join(name);
+            let assets_path = version_path.join(ARTIFACT_ASSETS_DIR).join(name);
+
+            if path.exists() {
+                remove_file(&path)?;
+            }
+
+            if assets_path.exists() {
+                remove_dir_all(&assets_path)?;
+            }
+
+            self.notify.info(format!("Removed {name}"));
         }
 
+        manifest.version = self.package_set.pkgset.clone();
+        manifest.contents = Some(self.versioned_contents(&version_path)?);
+        manifest.hooks =
+            self.place_assets(&version_path, manifest.hooks.as_deref().unwrap_or_default())?;
+        manifest.provenance = Some(Provenance::resolve(&self.channel, &self.package_set));
         manifest.write(&version_path)?;
 
-        old_versions.iter().for_each(|old_var| {
-            if let Some(new_var) = upstream_artifacts
-                .iter()
-                .find(|art| art.name == old_var.name)
-            {
-                self.notify.info(format!(
-                    "Updated {} from {} to {}",
-                    old_var.name, old_var.version, new_var.version
-                ));
+        for artifact in diff.added.iter() {
+            self.notify
+                .info(format!("Added {}@{}", artifact.name, artifact.version));
+        }
+
+        for artifact in diff.changed.iter() {
+            match previous.iter().find(|old| old.name == artifact.name) {
+                Some(old) if old.version != artifact.version.to_string() => {
+                    self.notify.info(format!(
+                        "Updated {} from {} to {}",
+                        artifact.name, old.version, artifact.version
+                    ))
+                }
+                _ => self.notify.info(format!(
+                    "Updated {} to a new build of {}",
+                    artifact.name, artifact.version
+                )),
             }
-        });
+        }
 
-        let version_dir = VersionDirectory::open(version_path)?;
-        version_dir.set_active()?;
+        if self.activate {
+            let version_dir = VersionDirectory::open(version_path)?;
+            version_dir.set_active()?;
+        }
 
         Ok(())
     }
 
+    /// Manifest entries for the package set artifacts, including the digest
+    /// of each binary stored in `version_path`
+    fn versioned_contents(&self, version_path: &Path) -> Result<Vec<VersionedArtifact>> {
+        self.package_set
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                let mut versioned = VersionedArtifact::from_artifact(artifact);
+                let binary_path = version_path.join(&artifact.name);
+
+                if binary_path.is_file() {
+                    versioned.installed_sha256_digest = Some(sha256_digest(&binary_path)?);
+                }
+
+                Ok(versioned)
+            })
+            .collect()
+    }
+
+    /// Runs the install hooks for the assets stored in `version_path`,
+    /// removing the assets placed by `previous` hooks which are no longer
+    /// shipped. Returns the hooks to record in the manifest.
+    fn place_assets(
+        &self,
+        version_path: &Path,
+        previous: &[InstallHook],
+    ) -> Result<Option<Vec<InstallHook>>> {
+        let dirs = AssetDirs::user()?;
+        let hooks = if self.assets {
+            plan_hooks(version_path, &dirs)?
+        } else {
+            Vec::new()
+        };
+        let stale: Vec<InstallHook> = previous
+            .iter()
+            .filter(|old| !hooks.iter().any(|hook| hook.destination == old.destination))
+            .cloned()
+            .collect();
+
+        if let Some(versions_path) = version_path.parent() {
+            remove_hooks(&stale, &hooks_in_use(versions_path, version_path)?)?;
+        }
+
+        let placed = run_hooks(version_path, hooks);
+
+        if placed.is_empty() {
+            return Ok(None);
+        }
+
+        self.notify.info(format!(
+            "Placed {} assets (completions, man pages, examples)",
+            placed.len()
+        ));
+
+        Ok(Some(placed))
+    }
+
     /// Downloads the specified artifacts to the temporary directory and
-    /// returns a reference to the temporary directory [`TempDir`].
+    /// returns a reference to the temporary directory [`TrackedTempDir`],
+    /// along with the verification report of the downloads when enabled.
     ///
     /// The `tmp_dir` must be dropped after copying the binaries to the
-    /// destination directory. By dropping [`TempDir`] the directory will be
-    /// deleted from the filesystem.
-    async fn download(&self, artifacts: &[Artifact]) -> Result<TempDir> {
-        let tmp_dir = TempDir::new()?;
-
-        for (idx, artf) in artifacts.iter().enumerate() {
-            self.notify.info(format!(
-                "Downloading ({}/{}): {}@{}",
-                idx + 1,
-                artifacts.len(),
-                artf.name,
-                artf.version
-            ));
-
-            let artf_path = artf.download(tmp_dir.path().to_path_buf()).await?;
+    /// destination directory. By dropping [`TrackedTempDir`] the directory
+    /// will be deleted from the filesystem.
+    async fn download(
+        &self,
+        artifacts: &[Artifact],
+    ) -> Result<(TrackedTempDir, Option<VerificationReport>)> {
+        let template = match &self.transparency_url {
+            Some(url) => Some(url.to_owned()),
+            None => transparency_url()?,
+        };
+
+        if let Some(template) = template {
+            verify_transparency(&template, &self.package_set, artifacts, self.notify).await?;
+        }
+
+        let tmp_dir = TrackedTempDir::new()?;
+        let report_enabled = match self.verification_report {
+            Some(enabled) => enabled,
+            None => verification_reports_enabled()?,
+        };
+        let mut report = report_enabled.then(|| {
+            fvm_report(
+                format!("fluvio {}", self.package_set.pkgset),
+                &self.package_set.arch,
+            )
+        });
+
+        let group = ProgressGroup::new(
+            "Downloads",
+            artifacts.len(),
+            ProgressMode::detect(self.notify.is_quiet(), ProgressOutput::Stdout),
+            ProgressOutput::Stdout,
+        )
+        .with_line_sink(self.notify.line_sink());
+        let downloads = artifacts.iter().enumerate().map(|(idx, artf)| {
+            let task = group.add_transfer(
+                &artf.name,
+                None,
+                format!(
+                    "Downloading ({}/{}): {}@{}",
+                    idx + 1,
+                    artifacts.len(),
+                    artf.name,
+                    artf.version
+                ),
+            );
+            let target_dir = tmp_dir.path().to_path_buf();
+            let artf = artf.clone();
+
+            spawn_blocking(move || {
+                let mut last_progress = None;
+                let downloaded =
+                    run_block_on(artf.download_with_progress(target_dir, &mut |progress| {
+                        task.update(progress);
+                        last_progress = Some(*progress);
+                    }));
+
+                match (&downloaded, last_progress) {
+                    (Ok(_), Some(progress)) => task.finish_transfer(
+                        Some(&progress),
+                        format!(
+                            "Downloaded {} {} in {:.1}s ({}/s)",
+                            artf.name,
+                            format_bytes(progress.received),
+                            progress.elapsed.as_secs_f64(),
+                            format_bytes(progress.average_speed() as u64)
+                        ),
+                    ),
+                    (Ok(_), None) => task.done(true, 0),
+                    (Err(_), _) => task.fail(format!("Failed to download {}", artf.name)),
+                }
+
+                downloaded
+            })
+        });
+        let downloaded = futures_util::stream::iter(downloads)
+            .buffered(MAX_CONCURRENT_DOWNLOADS)
+            .try_collect::<Vec<_>>()
+            .await;
+
+        group.finish();
+
+        for (artf_path, verified) in downloaded? {
             set_executable_mode(&artf_path)?;
+
+            if let Some(report) = report.as_mut() {
+                report.push(verified);
+            }
         }
 
-        Ok(tmp_dir)
+        Ok((tmp_dir, report))
+    }
+
+    /// Writes the verification `report` to the version directory
+    fn write_report(&self, report: Option<VerificationReport>, version_path: &Path) -> Result<()> {
+        let Some(report) = report else {
+            return Ok(());
+        };
+        let report_path = version_path.join(VERIFICATION_REPORT_FILENAME);
+
+        report.write(&report_path)?;
+        self.notify.info(format!(
+            "Wrote verification report to {}",
+            report_path.display()
+        ));
+
+        Ok(())
     }
 
     /// Allocates artifacts in the FVM `versions` directory for future use.
     /// Returns the path to the allocated version directory.
     ///
     /// If an artifact with the same name exists in the destination directory,
-    /// it will be removed before copying the new artifact.
-    async fn store_artifacts(&self, tmp_dir: &TempDir, artifacts: &[Artifact]) -> Result<PathBuf> {
+    /// it will be removed before copying the new artifact. The same applies
+    /// to the assets shipped with the artifact.
+    async fn store_artifacts(
+        &self,
+        tmp_dir: &TrackedTempDir,
+        artifacts: &[Artifact],
+    ) -> Result<PathBuf> {
         let version_path = fvm_versions_path()?.join(self.channel.to_string());
 
         if !version_path.exists() {
@@ -170,8 +397,58 @@ impl VersionInstaller {
                     )
                 })?;
             }
+
+            self.store_assets(tmp_dir, &version_path, &artif.name)?;
         }
 
         Ok(version_path)
     }
+
+    /// Moves the assets extracted for `name` into `version_path`
+    fn store_assets(
+        &self,
+        tmp_dir: &TrackedTempDir,
+        version_path: &Path,
+        name: &str,
+    ) -> Result<()> {
+        let src = tmp_dir.path().join(ARTIFACT_ASSETS_DIR).join(name);
+        let dst = version_path.join(ARTIFACT_ASSETS_DIR).join(name);
+
+        if dst.exists() {
+            remove_dir_all(&dst)?;
+        }
+
+        if !src.is_dir() {
+            return Ok(());
+        }
+
+        if let Some(parent) = dst.parent() {
+            create_dir_all(parent)?;
+        }
+
+        if rename(&src, &dst).is_err() {
+            copy_dir(&src, &dst)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
+    create_dir_all(dst)?;
+
+    for entry in std::fs::read_dir(src)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+
+        if path.is_dir() {
+            copy_dir(&path, &dst.join(file_name))?;
+        } else {
+            copy(&path, dst.join(file_name))?;
+        }
+    }
+
+    Ok(())
 }
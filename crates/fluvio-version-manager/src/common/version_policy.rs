@@ -0,0 +1,229 @@
+//! Version Policy Enforcement
+//!
+//! Opt-in restriction of `fvm install`, `fvm update`, `fvm switch`,
+//! `fvm run` and version shims to the versions allowed by a team, configured with `FVM_VERSION_POLICY_URL` and
+//! `FVM_VERSION_POLICY_KEY`, or the `version_policy_url` and
+//! `version_policy_key` keys in `settings.toml`. The signed policy is cached
+//! in the `version-policy.json` file in the FVM cache directory and verified
+//! each time it is used. When enforcement is configured and no valid policy
+//! is available, these commands fail.
+
+use std::env::var;
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use colored::Colorize;
+use semver::Version;
+
+use fluvio_artifacts_util::fvm::{Client, PackageSet, SignedVersionPolicy, VersionPolicy};
+use fluvio_artifacts_util::state::{load_state, write_state};
+
+use super::notify::Notify;
+use super::settings::Settings;
+use super::workdir::{fvm_layout, fvm_workdir_path};
+
+/// Environment variable with the URL of the signed version policy
+pub const FVM_VERSION_POLICY_URL_ENV_VAR: &str = "FVM_VERSION_POLICY_URL";
+
+/// Environment variable with the base64 Ed25519 key the policy is signed with
+pub const FVM_VERSION_POLICY_KEY_ENV_VAR: &str = "FVM_VERSION_POLICY_KEY";
+
+/// The name of the version policy cache file stored in the cache directory
+pub const VERSION_POLICY_CACHE_FILENAME: &str = "version-policy.json";
+
+/// Age after which the cached policy is fetched again
+pub const VERSION_POLICY_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Where the policy is published and the key it is signed with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionPolicySource {
+    pub url: String,
+    pub key: String,
+}
+
+/// Retrieves the policy URL and key from the environment, then from
+/// `settings.toml`. Returns `None` if enforcement is not configured.
+pub fn version_policy_source() -> Result<Option<VersionPolicySource>> {
+    let non_empty = |name: &str| var(name).ok().filter(|value| !value.trim().is_empty());
+    let url = match non_empty(FVM_VERSION_POLICY_URL_ENV_VAR) {
+        Some(url) => Some(url),
+        None => Settings::configured_version_policy_url()?,
+    };
+    let key = match non_empty(FVM_VERSION_POLICY_KEY_ENV_VAR) {
+        Some(key) => Some(key),
+        None => Settings::configured_version_policy_key()?,
+    };
+
+    match (url, key) {
+        (Some(url), Some(key)) => Ok(Some(VersionPolicySource { url, key })),
+        (None, None) => Ok(None),
+        (Some(_), None) => bail!(
+            "A version policy URL is configured without {FVM_VERSION_POLICY_KEY_ENV_VAR}, the key the policy is signed with"
+        ),
+        (None, Some(_)) => bail!(
+            "A version policy key is configured without {FVM_VERSION_POLICY_URL_ENV_VAR}, the URL the policy is published at"
+        ),
+    }
+}
+
+/// Loads the enforced version policy, `None` if enforcement is not
+/// configured. The cached policy is used when it is fresh or the policy
+/// cannot be fetched.
+pub async fn load_version_policy() -> Result<Option<VersionPolicy>> {
+    let Some(source) = version_policy_source()? else {
+        return Ok(None);
+    };
+    let workdir = fvm_workdir_path()?;
+    let cache_dir = fvm_layout()?.cache_dir;
+    let cache_path = cache_dir.join(VERSION_POLICY_CACHE_FILENAME);
+
+    if let Some(signed) = read_cache(&cache_path, Some(VERSION_POLICY_CACHE_TTL))
+        && let Ok(policy) = signed.verify(&source.key)
+    {
+        return Ok(Some(policy));
+    }
+
+    let signed = match Client::default().fetch_version_policy(&source.url).await {
+        Ok(signed) => signed,
+        Err(err) => {
+            tracing::debug!(%err, "Failed to fetch version policy, using stale cache");
+
+            read_cache(&cache_path, None).ok_or_else(|| {
+                anyhow!(
+                    "Failed to fetch the version policy from {}: {err}",
+                    source.url
+                )
+            })?
+        }
+    };
+    let policy = signed.verify(&source.key)?;
+
+    if workdir.exists()
+        && let Err(err) = create_dir_all(&cache_dir)
+            .map_err(Into::into)
+            .and_then(|()| write_cache(&cache_path, &signed))
+    {
+        tracing::debug!(%err, "Failed to cache version policy");
+    }
+
+    Ok(Some(policy))
+}
+
+fn write_cache(path: &Path, signed: &SignedVersionPolicy) -> Result<()> {
+    write_state(path, &serde_json::to_string_pretty(signed)?)
+}
+
+/// Reads the cached policy if the cache is younger than `ttl`
+fn read_cache(path: &Path, ttl: Option<Duration>) -> Option<SignedVersionPolicy> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+
+    if let Some(ttl) = ttl
+        && modified.elapsed().map_or(true, |age| age > ttl)
+    {
+        return None;
+    }
+
+    load_state(path, |contents| Ok(serde_json::from_str(contents)?))
+        .ok()
+        .flatten()
+}
+
+/// Rejects `version` if `policy` does not allow it
+pub fn check_version_policy(
+    policy: &VersionPolicy,
+    version: &Version,
+    notify: Notify,
+) -> Result<()> {
+    if policy.allows(version) {
+        return Ok(());
+    }
+
+    let allowed = policy
+        .allowed
+        .iter()
+        .map(|req| req.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    notify.help(format!("Allowed versions: {}", allowed.bold()));
+
+    if let Some(contact) = &policy.contact {
+        notify.help(format!("Ask {contact} to allow another version"));
+    }
+
+    bail!("Fluvio {version} is not allowed by the version policy of your team")
+}
+
+/// Rejects `version` if enforcement is configured and the policy does not
+/// allow it
+pub async fn enforce_version_policy(version: &Version, notify: Notify) -> Result<()> {
+    if let Some(policy) = load_version_policy().await? {
+        check_version_policy(&policy, version, notify)?;
+    }
+
+    Ok(())
+}
+
+/// Rejects `pkgset` if enforcement is configured and the policy does not
+/// allow its version, or the versions of components composed from other
+/// channels
+pub async fn enforce_package_set_policy(pkgset: &PackageSet, notify: Notify) -> Result<()> {
+    let Some(policy) = load_version_policy().await? else {
+        return Ok(());
+    };
+
+    check_version_policy(&policy, &pkgset.pkgset, notify)?;
+
+    // Components composed from other channels have their own versions
+    for artifact in pkgset.artifacts.iter() {
+        if artifact.channel.is_some() {
+            check_version_policy(&policy, &artifact.version, notify)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn policy() -> VersionPolicy {
+        serde_json::from_str(r#"{ "allowed": ["~0.11.8"], "contact": "the platform team" }"#)
+            .unwrap()
+    }
+
+    #[test]
+    fn rejects_versions_not_allowed() {
+        let notify = Notify::new(true);
+
+        assert!(check_version_policy(&policy(), &Version::new(0, 11, 9), notify).is_ok());
+        assert!(check_version_policy(&policy(), &Version::new(0, 12, 0), notify).is_err());
+    }
+
+    #[test]
+    fn reads_cache_within_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let cache_path = tmp.path().join(VERSION_POLICY_CACHE_FILENAME);
+        let signed = SignedVersionPolicy {
+            payload: serde_json::to_string(&policy()).unwrap(),
+            signature: String::from("c2lnbmF0dXJl"),
+        };
+
+        assert_eq!(read_cache(&cache_path, None), None);
+
+        write(&cache_path, serde_json::to_string(&signed).unwrap()).unwrap();
+
+        assert_eq!(
+            read_cache(&cache_path, Some(VERSION_POLICY_CACHE_TTL)),
+            Some(signed)
+        );
+        assert_eq!(read_cache(&cache_path, Some(Duration::ZERO)), None);
+    }
+}
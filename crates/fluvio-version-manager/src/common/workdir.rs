@@ -1,12 +1,17 @@
 //! The `Workdir` is the directory used by Fluvio Version Manager (FVM) to
 //! store its files and binaries.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::env::var;
+use std::fs::{create_dir_all, write};
+use std::sync::OnceLock;
 
 use anyhow::Result;
 
+use fluvio_artifacts_util::layout::{Layout, LayoutStyle};
+
 use super::home_dir;
+use super::settings::Settings;
 
 /// Home Directory for Fluvio
 pub const FLUVIO_HOME_DIR: &str = ".fluvio";
@@ -22,11 +27,83 @@ pub const FVM_BINARY_NAME: &str = "fvm";
 /// Here is where all the versions are stored
 pub const FVM_VERSIONS_DIR: &str = "versions";
 
+/// FVM Temporary Directory Name
+///
+/// Scratch location for downloads when no other location is configured
+pub const FVM_TMP_DIR: &str = "tmp";
+
 /// FVM Workdir Name Environment Variable
 pub const FVM_WORKDIR_NAME_ENV_VAR: &str = "FVM_WORKDIR_NAME";
 
-/// Retrieves the path to the `~/.fvm` directory in the host system
+/// FVM Temporary Directory Environment Variable
+pub const FVM_TMPDIR_ENV_VAR: &str = "FVM_TMPDIR";
+
+/// FVM Workspace Environment Variable
+///
+/// Root directory of a workspace keeping its FVM state in `<root>/.fvm`
+pub const FVM_WORKSPACE_ENV_VAR: &str = "FVM_WORKSPACE";
+
+/// Entries of the workspace `.fvm` directory which should not be committed
+const WORKSPACE_GITIGNORE: &str = "tmp/\nleases/\n";
+
+/// Workspace root set with `--workspace`, takes precedence over
+/// `FVM_WORKSPACE`
+static WORKSPACE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keeps FVM state in `<root>/.fvm` for the rest of the process, used by
+/// `--workspace`. Only the first call has an effect.
+pub fn set_fvm_workspace_root(root: PathBuf) {
+    if WORKSPACE_ROOT.set(root).is_err() {
+        tracing::debug!("FVM workspace root is already set");
+    }
+}
+
+/// Root of the workspace FVM state lives in, if any, from `--workspace` or
+/// `FVM_WORKSPACE`
+pub fn fvm_workspace_root() -> Option<PathBuf> {
+    WORKSPACE_ROOT.get().cloned().or_else(|| {
+        var(FVM_WORKSPACE_ENV_VAR)
+            .ok()
+            .filter(|root| !root.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Finds the root of the workspace containing `start`: the closest
+/// directory with a `.fvm` directory, other than the one in the user home,
+/// then the closest repository root with a `.git` entry, then `start`
+pub fn find_workspace_root(start: &Path, home: Option<&Path>) -> PathBuf {
+    let existing = start
+        .ancestors()
+        .filter(|dir| Some(*dir) != home)
+        .find(|dir| dir.join(FVM_HOME_DIR).is_dir());
+    let repository = || start.ancestors().find(|dir| dir.join(".git").exists());
+
+    existing.or_else(repository).unwrap_or(start).to_path_buf()
+}
+
+/// Creates the workspace `.fvm` directory along with a `.gitignore` for the
+/// scratch directories, so the rest of the state can be committed or cached
+pub fn create_workspace_workdir(root: &Path) -> Result<PathBuf> {
+    let workdir = root.join(FVM_HOME_DIR);
+    let gitignore = workdir.join(".gitignore");
+
+    create_dir_all(&workdir)?;
+
+    if !gitignore.exists() {
+        write(gitignore, WORKSPACE_GITIGNORE)?;
+    }
+
+    Ok(workdir)
+}
+
+/// Retrieves the path to the `~/.fvm` directory in the host system, or to
+/// `<root>/.fvm` in workspace mode
 pub fn fvm_workdir_path() -> Result<PathBuf> {
+    if let Some(root) = fvm_workspace_root() {
+        return Ok(root.join(FVM_HOME_DIR));
+    }
+
     let fvm_path = home_dir()?;
 
     if let Ok(workdir_name) = var(FVM_WORKDIR_NAME_ENV_VAR) {
@@ -43,9 +120,69 @@ pub fn fvm_bin_path() -> Result<PathBuf> {
     Ok(fvm_workdir_path()?.join("bin").join(FVM_BINARY_NAME))
 }
 
-/// Retrieves the path to the `~/.fvm/versions` directory in the host system
+/// Resolves the directory layout for versions, binaries, caches and scratch
+/// files.
+///
+/// The layout style is read from `FLUVIO_LAYOUT`, then the `layout` key in
+/// `settings.toml`, and defaults to the home layout in `~/.fvm` and
+/// `~/.fluvio`. Each directory can be overridden through its environment
+/// variable, see [`Layout`].
+///
+/// In workspace mode every directory, including the binaries of the active
+/// version, lives in `<root>/.fvm`.
+pub fn fvm_layout() -> Result<Layout> {
+    if fvm_workspace_root().is_some() {
+        let workdir = fvm_workdir_path()?;
+
+        return Ok(Layout::home(&workdir, &workdir).with_env_overrides());
+    }
+
+    let style = match LayoutStyle::from_env()? {
+        Some(style) => style,
+        None => Settings::configured_layout()?.unwrap_or_default(),
+    };
+    let layout = Layout::resolve(style, &fvm_workdir_path()?, &fluvio_path()?)?;
+
+    Ok(layout.with_env_overrides())
+}
+
+/// Retrieves the path to the versions directory, `~/.fvm/versions` by
+/// default
 pub fn fvm_versions_path() -> Result<PathBuf> {
-    Ok(fvm_workdir_path()?.join(FVM_VERSIONS_DIR))
+    Ok(fvm_layout()?.versions_dir)
+}
+
+/// Retrieves the scratch directory used for downloads and staging.
+///
+/// Uses `FVM_TMPDIR`, then the `tmpdir` key in `settings.toml`, and defaults
+/// to the temporary directory of the layout, `~/.fvm/tmp` by default. The
+/// default lives on the same filesystem as the `versions` directory so
+/// artifacts are moved into place with a rename instead of a copy, and large
+/// downloads don't fill a small tmpfs.
+pub fn fvm_tmp_path() -> Result<PathBuf> {
+    let from_env = var(FVM_TMPDIR_ENV_VAR).ok().map(PathBuf::from);
+    let from_settings = if from_env.is_none() {
+        Settings::configured_tmpdir()?
+    } else {
+        None
+    };
+
+    Ok(resolve_tmp_path(
+        from_env,
+        from_settings,
+        &fvm_layout()?.temp_dir,
+    ))
+}
+
+fn resolve_tmp_path(
+    from_env: Option<PathBuf>,
+    from_settings: Option<PathBuf>,
+    default: &Path,
+) -> PathBuf {
+    from_env
+        .or(from_settings)
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(|| default.to_path_buf())
 }
 
 /// Retrieves the path to the `~/.fluvio` directory in the host system.
@@ -53,9 +190,10 @@ pub fn fluvio_path() -> Result<PathBuf> {
     Ok(home_dir()?.join(FLUVIO_HOME_DIR))
 }
 
-/// Retrieves the path to the `~/.fluvio/bin` directory in the host system.
+/// Retrieves the path to the binaries directory of the active version,
+/// `~/.fluvio/bin` by default.
 pub fn fluvio_binaries_path() -> Result<PathBuf> {
-    Ok(fluvio_path()?.join("bin"))
+    Ok(fvm_layout()?.bin_dir)
 }
 
 #[cfg(test)]
@@ -86,6 +224,52 @@ mod tests {
         assert_eq!(fvm_version_path, fvm_path.join(FVM_VERSIONS_DIR));
     }
 
+    #[test]
+    fn test_resolve_tmp_path() {
+        let workdir = Path::new("/home/user/.fvm");
+        let default = workdir.join(FVM_TMP_DIR);
+
+        assert_eq!(resolve_tmp_path(None, None, &default), default);
+        assert_eq!(
+            resolve_tmp_path(None, Some(PathBuf::from("/scratch")), &default),
+            PathBuf::from("/scratch")
+        );
+        assert_eq!(
+            resolve_tmp_path(
+                Some(PathBuf::from("/mnt/big")),
+                Some(PathBuf::from("/scratch")),
+                &default
+            ),
+            PathBuf::from("/mnt/big")
+        );
+        assert_eq!(
+            resolve_tmp_path(Some(PathBuf::new()), None, &default),
+            default
+        );
+    }
+
+    #[test]
+    fn test_find_workspace_root() {
+        let home = tempfile::TempDir::new().unwrap();
+        let repo = home.path().join("repo");
+        let nested = repo.join("crates").join("app");
+
+        create_dir_all(home.path().join(FVM_HOME_DIR)).unwrap();
+        create_dir_all(&nested).unwrap();
+
+        assert_eq!(find_workspace_root(&nested, Some(home.path())), nested);
+
+        create_dir_all(repo.join(".git")).unwrap();
+        assert_eq!(find_workspace_root(&nested, Some(home.path())), repo);
+
+        let workdir = create_workspace_workdir(&repo.join("crates")).unwrap();
+        assert_eq!(
+            find_workspace_root(&nested, Some(home.path())),
+            repo.join("crates")
+        );
+        assert!(workdir.join(".gitignore").is_file());
+    }
+
     #[test]
     fn test_fluvio_path() {
         let fluvio_path = fluvio_path().expect("Failed to get fluvio path");
@@ -0,0 +1,24 @@
+//! Fluvio Version Manager (FVM) library
+//!
+//! Manages Fluvio toolchains the same way the `fvm` CLI does, for tools
+//! which install or switch Fluvio versions without shelling out to `fvm`.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use fluvio_artifacts_util::fvm::Channel;
+//! use fvm_core::{Installer, Switcher};
+//!
+//! let installed = Installer::new().install(&Channel::Stable).await?;
+//! Switcher::new().switch(&installed.manifest.channel).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+#[doc(hidden)]
+pub mod common;
+
+pub use api::{InstalledVersion, Installer, Switcher, list_installed};
+pub use common::install_profile::InstallProfile;
+pub use common::manifest::VersionManifest;
+pub use common::notify::Notify;
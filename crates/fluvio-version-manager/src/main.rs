@@ -1,18 +1,54 @@
 mod command;
-mod common;
+
+use std::ffi::OsString;
+
+use std::time::Instant;
 
 use anyhow::{Result, bail};
+use fvm_core::common;
 use clap::Parser;
+use fluvio_artifacts_util::htclient::dns::set_dns_overrides;
+use fluvio_artifacts_util::htclient::stats::stats as http_stats;
 use command::uninstall::UninstallOpt;
 
+use self::command::advisories::AdvisoriesOpt;
+use self::command::backfill_digests::BackfillDigestsOpt;
+use self::command::cache::{RepairCacheOpt, VerifyCacheOpt};
+use self::command::check_integrity::CheckIntegrityOpt;
+use self::command::clean::CleanOpt;
+use self::command::clone_to::CloneToOpt;
+use self::command::config::ConfigOpt;
 use self::command::current::CurrentOpt;
+use self::command::doctor::DoctorOpt;
+use self::command::import::ImportOpt;
+use self::command::init::InitOpt;
 use self::command::install::InstallOpt;
 use self::command::itself::SelfOpt;
 use self::command::list::ListOpt;
+use self::command::plugin::PluginOpt;
+use self::command::prompt::PromptOpt;
+use self::command::provenance::ProvenanceOpt;
+use self::command::prune::PruneOpt;
+use self::command::register_local::RegisterLocalOpt;
+use self::command::run::RunOpt;
+use self::command::setup::SetupOpt;
+use self::command::support_bundle::SupportBundleOpt;
 use self::command::switch::SwitchOpt;
 use self::command::update::UpdateOpt;
+use self::command::verify::VerifyOpt;
 use self::command::version::VersionOpt;
+use self::command::which_release::WhichReleaseOpt;
+use self::common::home_dir;
+use self::common::exit_code::{EXIT_CODES_HELP, exit_code};
+use self::common::janitor::cleanup_on_startup;
+use self::common::notification::{LONG_OPERATIONS, install_sinks, notify_completion};
 use self::common::notify::Notify;
+use self::common::plugin::run_plugin;
+use self::common::settings::Settings;
+use self::common::shim::{parse_shim_name, run_shim};
+use self::common::workdir::{
+    create_workspace_workdir, find_workspace_root, fvm_workspace_root, set_fvm_workspace_root,
+};
 
 /// Binary name is read from `Cargo.toml` `[[bin]]` section
 pub const BINARY_NAME: &str = env!("CARGO_BIN_NAME");
@@ -21,8 +57,28 @@ pub const BINARY_NAME: &str = env!("CARGO_BIN_NAME");
 pub const VERSION: &str = include_str!("../../../VERSION");
 
 #[fluvio_future::main_async]
-async fn main() -> Result<()> {
+async fn main() {
     fluvio_future::subscriber::init_tracer(None);
+
+    if let Err(err) = run().await {
+        eprintln!("Error: {err:?}");
+        std::process::exit(exit_code(&err));
+    }
+}
+
+async fn run() -> Result<()> {
+    // Invoked through a version shim, e.g. `fluvio@0.11.8`
+    let mut args = std::env::args_os();
+    let shim = args
+        .next()
+        .and_then(|arg0| {
+            std::path::Path::new(&arg0)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(str::to_string)
+        })
+        .and_then(|name| parse_shim_name(&name));
+
     if rustls::crypto::CryptoProvider::get_default().is_none()
         && rustls::crypto::aws_lc_rs::default_provider()
             .install_default()
@@ -31,9 +87,19 @@ async fn main() -> Result<()> {
         bail!("Failed to install AWS-LC-Rust as default crypto provider");
     }
 
+    if let Some((binary, version)) = shim {
+        let code = run_shim(&binary, &version, args.collect(), Notify::new(false)).await?;
+
+        std::process::exit(code);
+    }
+
     let args = Cli::parse();
+    let code = args.process().await?;
+
+    if code != 0 {
+        std::process::exit(code);
+    }
 
-    args.process().await?;
     Ok(())
 }
 
@@ -43,28 +109,92 @@ async fn main() -> Result<()> {
     about = "Fluvio Version Manager (FVM)",
     max_term_width = 100,
     arg_required_else_help = true,
+    after_long_help = EXIT_CODES_HELP,
 )]
 pub struct Cli {
     #[clap(long, short = 'q', help = "Suppress all output")]
     quiet: bool,
+    /// Print the HTTP requests, connections and bytes transferred once the
+    /// command completes
+    #[clap(long, short = 'v')]
+    verbose: bool,
+    /// Keep installed versions and the active version in `.fvm` at the root
+    /// of the current repository instead of the home directory, also enabled
+    /// by setting `FVM_WORKSPACE` to the root directory
+    #[clap(long, global = true)]
+    workspace: bool,
     #[command(subcommand)]
     command: Command,
 }
 
 #[derive(Debug, Parser)]
 pub enum Command {
+    /// List the security and critical-bug advisories of installed Fluvio Versions
+    #[command(name = "advisories")]
+    Advisories(AdvisoriesOpt),
+    /// Compute checksums manifests of releases lacking asset digests, for maintainers
+    #[command(name = "backfill-digests", hide = true)]
+    BackfillDigests(BackfillDigestsOpt),
+    /// Compare the binaries of an installed Fluvio Version with the artifacts of its release
+    #[command(name = "check-integrity")]
+    CheckIntegrity(CheckIntegrityOpt),
+    /// Remove temporary files left behind by interrupted operations
+    #[command(name = "clean")]
+    Clean(CleanOpt),
+    /// Export an installed Fluvio Version to a local path or SSH host
+    #[command(name = "clone-to")]
+    CloneTo(CloneToOpt),
+    /// Export or import settings, mirrors and installed versions
+    #[command(name = "config")]
+    Config(ConfigOpt),
     /// Print the current active Fluvio Version
     #[command(name = "current")]
     Current(CurrentOpt),
+    /// Probe the hosts FVM downloads from, reporting where connections fail
+    #[command(name = "doctor")]
+    Doctor(DoctorOpt),
     /// Manage FVM
     #[command(name = "self")]
     Itself(SelfOpt),
+    /// Import a Fluvio Version from an archive created with `clone-to`
+    #[command(name = "import")]
+    Import(ImportOpt),
+    /// Install a Fluvio Version with the components of a profile and set up PATH
+    #[command(name = "init")]
+    Init(InitOpt),
     /// Install a Fluvio Version
     #[command(name = "install")]
     Install(InstallOpt),
     /// List installed Fluvio Versions
     #[command(name = "list")]
     List(ListOpt),
+    /// List and install `fvm-<name>` plugins
+    #[command(name = "plugin")]
+    Plugin(PluginOpt),
+    /// Print the active Fluvio Version for shell prompts, see `fvm setup --prompt`
+    #[command(name = "prompt", hide = true)]
+    Prompt(PromptOpt),
+    /// Print how an installed Fluvio Version was resolved
+    #[command(name = "provenance")]
+    Provenance(ProvenanceOpt),
+    /// Uninstall Fluvio Versions which were not used for a period of time
+    #[command(name = "prune")]
+    Prune(PruneOpt),
+    /// Register binaries built from a Fluvio checkout as a Fluvio Version
+    #[command(name = "register-local")]
+    RegisterLocal(RegisterLocalOpt),
+    /// Evict corrupt objects from the package cache, optionally downloading them again
+    #[command(name = "repair-cache")]
+    RepairCache(RepairCacheOpt),
+    /// Run a command with a Fluvio Version on PATH, downloading it if not installed
+    #[command(name = "run")]
+    Run(RunOpt),
+    /// Add FVM and Fluvio binaries to PATH in shell profile files
+    #[command(name = "setup")]
+    Setup(SetupOpt),
+    /// Gather FVM state and environment details into a tarball for bug reports
+    #[command(name = "support-bundle")]
+    SupportBundle(SupportBundleOpt),
     /// Set a installed Fluvio Version as active
     #[command(name = "switch")]
     Switch(SwitchOpt),
@@ -74,25 +204,140 @@ pub enum Command {
     /// Updates the current channel version to the most recent
     #[command(name = "update")]
     Update(UpdateOpt),
+    /// Verify the binaries of an installed Fluvio Version against their recorded checksums
+    #[command(name = "verify")]
+    Verify(VerifyOpt),
+    /// Hash the objects of the package cache against their digests
+    #[command(name = "verify-cache")]
+    VerifyCache(VerifyCacheOpt),
     /// Prints version information
     Version(VersionOpt),
+    /// Find the releases which shipped a version of a binary
+    #[command(name = "which-release")]
+    WhichRelease(WhichReleaseOpt),
+    /// Runs the `fvm-<name>` plugin for unknown subcommands
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+impl Command {
+    /// Name of the subcommand as typed, e.g. `repair-cache` or the name of an
+    /// external plugin
+    fn name(&self) -> &str {
+        match self {
+            Command::Advisories(_) => "advisories",
+            Command::BackfillDigests(_) => "backfill-digests",
+            Command::CheckIntegrity(_) => "check-integrity",
+            Command::Clean(_) => "clean",
+            Command::CloneTo(_) => "clone-to",
+            Command::Config(_) => "config",
+            Command::Current(_) => "current",
+            Command::Doctor(_) => "doctor",
+            Command::Itself(_) => "self",
+            Command::Import(_) => "import",
+            Command::Init(_) => "init",
+            Command::Install(_) => "install",
+            Command::List(_) => "list",
+            Command::Plugin(_) => "plugin",
+            Command::Prompt(_) => "prompt",
+            Command::Provenance(_) => "provenance",
+            Command::Prune(_) => "prune",
+            Command::RegisterLocal(_) => "register-local",
+            Command::RepairCache(_) => "repair-cache",
+            Command::Run(_) => "run",
+            Command::Setup(_) => "setup",
+            Command::SupportBundle(_) => "support-bundle",
+            Command::Switch(_) => "switch",
+            Command::Uninstall(_) => "uninstall",
+            Command::Update(_) => "update",
+            Command::Verify(_) => "verify",
+            Command::VerifyCache(_) => "verify-cache",
+            Command::Version(_) => "version",
+            Command::WhichRelease(_) => "which-release",
+            Command::External(args) => args
+                .first()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 impl Cli {
-    async fn process(&self) -> Result<()> {
-        let args = Cli::parse();
-        let command = args.command;
+    /// Runs the command, returning the exit code of external plugins or 0
+    async fn process(&self) -> Result<i32> {
+        let command_name = self.command.name();
         let notify = Notify::new(self.quiet);
 
-        match command {
+        if self.workspace {
+            let root = find_workspace_root(&std::env::current_dir()?, home_dir().ok().as_deref());
+
+            set_fvm_workspace_root(root);
+        }
+
+        // The prompt hook runs before every shell prompt, so it only reads
+        // the state and skips the startup housekeeping
+        if !matches!(self.command, Command::Prompt(_)) {
+            if let Some(root) = fvm_workspace_root() {
+                let workdir = create_workspace_workdir(&root)?;
+
+                tracing::debug!(?workdir, "Using workspace FVM state");
+            }
+
+            set_dns_overrides(Settings::configured_dns_overrides()?);
+            cleanup_on_startup();
+        }
+
+        if LONG_OPERATIONS.contains(&command_name) {
+            install_sinks(Settings::configured_notification_sinks()?);
+        }
+
+        let started = Instant::now();
+        let mut plugin_exit_code = 0;
+        let result = match &self.command {
+            Command::Advisories(cmd) => cmd.process(notify).await,
+            Command::BackfillDigests(cmd) => cmd.process(notify).await,
+            Command::CheckIntegrity(cmd) => cmd.process(notify).await,
+            Command::Clean(cmd) => cmd.process(notify).await,
+            Command::CloneTo(cmd) => cmd.process(notify).await,
+            Command::Config(cmd) => cmd.process(notify).await,
             Command::Current(cmd) => cmd.process(notify).await,
+            Command::Doctor(cmd) => cmd.process(notify).await,
             Command::Itself(cmd) => cmd.process(notify).await,
+            Command::Import(cmd) => cmd.process(notify).await,
+            Command::Init(cmd) => cmd.process(notify).await,
             Command::Install(cmd) => cmd.process(notify).await,
             Command::List(cmd) => cmd.process(notify).await,
+            Command::Plugin(cmd) => cmd.process(notify).await,
+            Command::Prompt(cmd) => cmd.process(notify).await,
+            Command::Provenance(cmd) => cmd.process(notify).await,
+            Command::Prune(cmd) => cmd.process(notify).await,
+            Command::RegisterLocal(cmd) => cmd.process(notify).await,
+            Command::RepairCache(cmd) => cmd.process(notify).await,
+            Command::Run(cmd) => cmd.process(notify).await,
+            Command::Setup(cmd) => cmd.process(notify).await,
+            Command::SupportBundle(cmd) => cmd.process(notify).await,
             Command::Switch(cmd) => cmd.process(notify).await,
             Command::Uninstall(cmd) => cmd.process(notify).await,
             Command::Update(cmd) => cmd.process(notify).await,
+            Command::Verify(cmd) => cmd.process(notify).await,
+            Command::VerifyCache(cmd) => cmd.process(notify).await,
             Command::Version(cmd) => cmd.process(),
+            Command::WhichRelease(cmd) => cmd.process(notify).await,
+            Command::External(args) => {
+                let mut args = args.iter().cloned();
+                let name = args.next().unwrap_or_default();
+
+                run_plugin(&name.to_string_lossy(), args.collect())
+                    .map(|code| plugin_exit_code = code)
+            }
+        };
+
+        if self.verbose {
+            notify.info(format!("Network: {}", http_stats().snapshot()));
         }
+
+        notify_completion(command_name, &result, started.elapsed()).await;
+
+        result.map(|()| plugin_exit_code)
     }
 }
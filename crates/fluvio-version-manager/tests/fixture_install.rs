@@ -0,0 +1,56 @@
+//! Installs a version served by the fixture server into a temporary home,
+//! exercising the package set resolution, downloads, checksum validation
+//! and version directory layout end to end.
+
+#![cfg(unix)]
+
+use semver::Version;
+use tempfile::TempDir;
+
+use fluvio_artifacts_util::fixture::{FixtureRelease, FixtureServer};
+use fluvio_artifacts_util::fvm::Channel;
+use fvm_core::Installer;
+use fvm_core::common::TARGET;
+use fvm_core::common::github::FVM_GITHUB_API_URL_ENV_VAR;
+
+#[fluvio_future::test]
+async fn installs_from_fixture_server() {
+    let home = TempDir::new().unwrap();
+    let server = FixtureServer::start().unwrap();
+    let release = FixtureRelease::new(Version::new(0, 11, 8))
+        .with_binary("fluvio", TARGET, b"#!/bin/sh\necho fluvio")
+        .unwrap()
+        .with_binary("fluvio-run", TARGET, b"#!/bin/sh\necho fluvio-run")
+        .unwrap()
+        .with_binary("smdk", "unknown-target", b"#!/bin/sh\necho smdk")
+        .unwrap();
+
+    server.add_release(release);
+
+    // SAFETY: this is the only test of the binary, no other thread reads the
+    // environment
+    unsafe {
+        std::env::set_var("HOME", home.path());
+        std::env::set_var("FLUVIO_CONTENT_STORE", "off");
+        std::env::set_var(
+            FVM_GITHUB_API_URL_ENV_VAR,
+            server.github_repo().api_url.unwrap(),
+        );
+    }
+
+    let installed = Installer::new()
+        .with_generic(true)
+        .install(&Channel::Stable)
+        .await
+        .unwrap();
+
+    assert!(installed.active);
+    assert_eq!(installed.manifest.version, Version::new(0, 11, 8));
+    assert!(installed.path.starts_with(home.path()));
+    assert_eq!(
+        std::fs::read(installed.path.join("fluvio")).unwrap(),
+        b"#!/bin/sh\necho fluvio"
+    );
+    assert!(installed.path.join("fluvio-run").is_file());
+    assert!(!installed.path.join("smdk").exists());
+}
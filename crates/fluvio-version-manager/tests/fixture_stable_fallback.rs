@@ -0,0 +1,67 @@
+//! Installs `stable` while the newest release is still missing artifacts
+//! for the target, which falls back to the prior stable release unless
+//! `--strict` is used.
+
+#![cfg(unix)]
+
+use semver::Version;
+use tempfile::TempDir;
+
+use fluvio_artifacts_util::fixture::{FixtureRelease, FixtureServer};
+use fluvio_artifacts_util::fvm::Channel;
+use fvm_core::Installer;
+use fvm_core::common::TARGET;
+use fvm_core::common::github::FVM_GITHUB_API_URL_ENV_VAR;
+
+#[fluvio_future::test]
+async fn falls_back_to_prior_complete_stable_release() {
+    let home = TempDir::new().unwrap();
+    let server = FixtureServer::start().unwrap();
+    let complete = FixtureRelease::new(Version::new(0, 11, 8))
+        .with_binary("fluvio", TARGET, b"#!/bin/sh\necho fluvio 0.11.8")
+        .unwrap()
+        .with_binary("fluvio-run", TARGET, b"#!/bin/sh\necho fluvio-run 0.11.8")
+        .unwrap();
+    // `fluvio-run` is not uploaded yet for the target
+    let incomplete = FixtureRelease::new(Version::new(0, 11, 9))
+        .with_binary("fluvio", TARGET, b"#!/bin/sh\necho fluvio 0.11.9")
+        .unwrap()
+        .with_binary("fluvio-run", "unknown-target", b"#!/bin/sh\necho fluvio-run")
+        .unwrap();
+
+    server.add_release(complete);
+    server.add_release(incomplete);
+
+    // SAFETY: this is the only test of the binary, no other thread reads the
+    // environment
+    unsafe {
+        std::env::set_var("HOME", home.path());
+        std::env::set_var("FLUVIO_CONTENT_STORE", "off");
+        std::env::set_var(
+            FVM_GITHUB_API_URL_ENV_VAR,
+            server.github_repo().api_url.unwrap(),
+        );
+    }
+
+    let err = Installer::new()
+        .with_generic(true)
+        .with_strict(true)
+        .install(&Channel::Stable)
+        .await
+        .unwrap_err();
+
+    assert!(format!("{err:#}").contains("missing artifacts"));
+
+    let installed = Installer::new()
+        .with_generic(true)
+        .install(&Channel::Stable)
+        .await
+        .unwrap();
+
+    assert_eq!(installed.manifest.version, Version::new(0, 11, 8));
+    assert_eq!(
+        std::fs::read(installed.path.join("fluvio")).unwrap(),
+        b"#!/bin/sh\necho fluvio 0.11.8"
+    );
+    assert!(installed.path.join("fluvio-run").is_file());
+}
@@ -2,6 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::task::{Context, Poll};
 use std::time::Duration;
 
@@ -24,6 +25,7 @@ use fluvio_protocol::record::Offset;
 use fluvio_protocol::link::ErrorCode;
 use fluvio_spu_schema::produce::ProduceResponse;
 use fluvio_protocol::record::Record;
+use fluvio_protocol::Encoder;
 use fluvio_socket::SocketError;
 use fluvio_types::{PartitionId, Timestamp, PartitionCount};
 
@@ -31,6 +33,7 @@ use crate::producer::record::{BatchMetadata, FutureRecordMetadata, PartialFuture
 use crate::producer::ProducerError;
 use crate::error::Result;
 
+use super::config::BufferOverflow;
 use super::event::EventHandler;
 use super::memory_batch::{MemoryBatch, MemoryBatchStatus};
 
@@ -64,6 +67,7 @@ pub(crate) struct RecordAccumulator {
     queue_size: usize,
     batches: Arc<RwLock<HashMap<PartitionId, BatchHandler>>>,
     compression: Compression,
+    memory_budget: Option<Arc<MemoryBudget>>,
 }
 
 impl RecordAccumulator {
@@ -83,9 +87,21 @@ impl RecordAccumulator {
             batch_size,
             compression,
             queue_size,
+            memory_budget: None,
         }
     }
 
+    /// Limit the bytes buffered by the batches of all partitions to `max_bytes`.
+    pub(crate) fn with_memory_budget(
+        mut self,
+        max_bytes: Option<usize>,
+        overflow: BufferOverflow,
+    ) -> Self {
+        self.memory_budget =
+            max_bytes.map(|max_bytes| Arc::new(MemoryBudget::new(max_bytes, overflow)));
+        self
+    }
+
     pub(crate) async fn add_partition(
         &self,
         partition_id: PartitionId,
@@ -107,10 +123,21 @@ impl RecordAccumulator {
     ) -> Result<PushRecord, ProducerError> {
         let created_at = Instant::now();
 
-        let batches_lock = self.batches.read().await;
-        let (batch_events, batches_lock) = batches_lock
+        let (batch_events, batches_lock) = self
+            .batches
+            .read()
+            .await
             .get(&partition_id)
+            .cloned()
             .ok_or(ProducerError::PartitionNotFound(partition_id))?;
+        let batch_events = &batch_events;
+
+        // Wait, drop batches or fail if the record does not fit in the memory
+        // budget. The reservation moves into the batch the record is added to.
+        let mut reserved = match &self.memory_budget {
+            Some(budget) => Some(budget.reserve(record.write_size(0), &self.batches).await?),
+            None => None,
+        };
 
         // Wait for space in the batch queue
         self.wait_for_space(batches_lock.clone()).await?;
@@ -118,7 +145,7 @@ impl RecordAccumulator {
 
         // If the last batch is not full, push the record to it
         if let Some(batch) = batches.back_mut() {
-            match batch.push_record(record) {
+            match batch.push_record(record, &mut reserved) {
                 Ok(ProduceBatchStatus::Added(push_record)) => {
                     if batch.is_full() {
                         batch_events.notify_batch_full().await;
@@ -134,7 +161,14 @@ impl RecordAccumulator {
 
                     // Create and push a new batch if needed
                     let push_record = self
-                        .create_and_new_batch(batch_events, &mut batches, record, 1, created_at)
+                        .create_and_new_batch(
+                            batch_events,
+                            &mut batches,
+                            record,
+                            &mut reserved,
+                            1,
+                            created_at,
+                        )
                         .await?;
 
                     return Ok(PushRecord::new(
@@ -151,7 +185,14 @@ impl RecordAccumulator {
 
         // Create and push a new batch if needed
         let push_record = self
-            .create_and_new_batch(batch_events, &mut batches, record, 1, created_at)
+            .create_and_new_batch(
+                batch_events,
+                &mut batches,
+                record,
+                &mut reserved,
+                1,
+                created_at,
+            )
             .await?;
 
         Ok(PushRecord::new(
@@ -183,6 +224,7 @@ impl RecordAccumulator {
         batch_events: &BatchEvents,
         batches: &mut VecDeque<ProducerBatch>,
         record: Record,
+        reserved: &mut Option<MemoryReservation>,
         attempts: usize,
         created_at: Instant,
     ) -> Result<PartialFutureRecordMetadata, ProducerError> {
@@ -198,9 +240,10 @@ impl RecordAccumulator {
             self.batch_size,
             self.compression,
             created_at,
-        );
+        )
+        .with_memory_budget(self.memory_budget.clone());
 
-        match batch.push_record(record) {
+        match batch.push_record(record, reserved) {
             Ok(ProduceBatchStatus::Added(push_record)) => {
                 batch_events.notify_new_batch().await;
                 if batch.is_full() {
@@ -222,6 +265,7 @@ impl RecordAccumulator {
                     batch_events,
                     batches,
                     record,
+                    reserved,
                     attempts + 1,
                     created_at,
                 ))
@@ -236,6 +280,160 @@ impl RecordAccumulator {
     }
 }
 
+/// Bytes buffered by the batches of all partitions, limited to `max_bytes`.
+///
+/// Batches hold a [`MemoryReservation`] of their size, released once they are
+/// sent or dropped.
+pub(crate) struct MemoryBudget {
+    max_bytes: usize,
+    overflow: BufferOverflow,
+    buffered: AtomicUsize,
+    free_space_event: Event,
+}
+
+impl MemoryBudget {
+    fn new(max_bytes: usize, overflow: BufferOverflow) -> Self {
+        Self {
+            max_bytes,
+            overflow,
+            buffered: AtomicUsize::new(0),
+            free_space_event: Event::new(),
+        }
+    }
+
+    /// Reserve `record_size` bytes in the budget shared by the batches of all
+    /// partitions, making room for them first.
+    ///
+    /// The check and the reservation are a single atomic update, so concurrent
+    /// producers never reserve more than the budget together. A record is
+    /// always accepted when nothing is buffered, so a single record larger
+    /// than the budget does not block the producer forever.
+    async fn reserve(
+        self: &Arc<Self>,
+        record_size: usize,
+        partitions: &RwLock<HashMap<PartitionId, BatchHandler>>,
+    ) -> Result<MemoryReservation, ProducerError> {
+        let started_at = Instant::now();
+
+        loop {
+            // Listen before checking so a release in between is not missed
+            let space_listener = self.free_space_event.listen();
+            let reserved =
+                self.buffered
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |buffered| {
+                        (buffered == 0 || buffered + record_size <= self.max_bytes)
+                            .then_some(buffered + record_size)
+                    });
+
+            if reserved.is_ok() {
+                return Ok(MemoryReservation {
+                    budget: self.clone(),
+                    bytes: record_size,
+                });
+            }
+
+            match self.overflow {
+                BufferOverflow::Error => {
+                    return Err(ProducerError::MemoryBudgetExceeded(self.max_bytes));
+                }
+                BufferOverflow::DropOldest => {
+                    let partitions = partitions.read().await.clone();
+
+                    if !drop_oldest_batch(&partitions).await {
+                        self.buffered.fetch_add(record_size, Ordering::AcqRel);
+
+                        return Ok(MemoryReservation {
+                            budget: self.clone(),
+                            bytes: record_size,
+                        });
+                    }
+                }
+                BufferOverflow::Block => {
+                    let remaining = RECORD_ENQUEUE_TIMEOUT
+                        .checked_sub(started_at.elapsed())
+                        .ok_or(ProducerError::BatchQueueWaitTimeout)?;
+
+                    timeout(remaining, space_listener)
+                        .await
+                        .map_err(|_| ProducerError::BatchQueueWaitTimeout)?;
+                }
+            }
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.buffered.fetch_sub(bytes, Ordering::AcqRel);
+        self.free_space_event.notify(usize::MAX);
+    }
+}
+
+/// Bytes of a batch counted in a [`MemoryBudget`], released when dropped
+struct MemoryReservation {
+    budget: Arc<MemoryBudget>,
+    bytes: usize,
+}
+
+impl MemoryReservation {
+    fn new(budget: Arc<MemoryBudget>) -> Self {
+        Self { budget, bytes: 0 }
+    }
+
+    /// Update the reservation to the `bytes` now buffered by the batch
+    fn resize(&mut self, bytes: usize) {
+        if bytes > self.bytes {
+            self.budget
+                .buffered
+                .fetch_add(bytes - self.bytes, Ordering::AcqRel);
+        } else if bytes < self.bytes {
+            self.budget.release(self.bytes - bytes);
+        }
+
+        self.bytes = bytes;
+    }
+
+    /// Take over the bytes of `other`, e.g. the reservation of a record added
+    /// to the batch
+    fn absorb(&mut self, mut other: MemoryReservation) {
+        self.bytes += std::mem::take(&mut other.bytes);
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.release(self.bytes);
+    }
+}
+
+/// Discard the oldest batch across partitions, the records of the batch
+/// resolve with an error. Returns `false` if there is no batch to discard.
+async fn drop_oldest_batch(partitions: &HashMap<PartitionId, BatchHandler>) -> bool {
+    let mut oldest: Option<(Timestamp, &BatchesDeque)> = None;
+
+    for (_, deque) in partitions.values() {
+        if let Some(elapsed) = deque
+            .batches
+            .read()
+            .await
+            .front()
+            .map(ProducerBatch::elapsed)
+            && oldest.is_none_or(|(oldest_elapsed, _)| elapsed > oldest_elapsed)
+        {
+            oldest = Some((elapsed, deque));
+        }
+    }
+
+    let Some((_, deque)) = oldest else {
+        return false;
+    };
+
+    if deque.batches.write().await.pop_front().is_some() {
+        trace!("Dropped the oldest batch to stay within the memory budget");
+        deque.free_space_event.notify(1);
+    }
+
+    true
+}
+
 /// An event that is triggered when a batch is full.
 #[derive(Debug)]
 pub struct ProduceCompletionBatchEvent {
@@ -276,6 +474,7 @@ pub(crate) struct ProducerBatch {
     pub(crate) notify: Sender<ProducePartitionResponseFuture>,
     batch_metadata: Arc<BatchMetadata>,
     batch: MemoryBatch,
+    reservation: Option<MemoryReservation>,
 }
 impl ProducerBatch {
     fn new(
@@ -292,20 +491,46 @@ impl ProducerBatch {
             notify: sender,
             batch_metadata,
             batch,
+            reservation: None,
         }
     }
 
+    /// Count the size of the batch in the `budget` shared by all partitions.
+    fn with_memory_budget(mut self, budget: Option<Arc<MemoryBudget>>) -> Self {
+        self.reservation = budget.map(MemoryReservation::new);
+        self
+    }
+
     /// Add a record to the batch.
     /// Return ProducerError::BatchFull if record does not fit in the batch, so
     /// the RecordAccumulator can create more batches if needed.
-    fn push_record(&mut self, record: Record) -> Result<ProduceBatchStatus, ProducerError> {
-        match self.batch.push_record(record) {
+    ///
+    /// The `reserved` bytes of the record are moved into the reservation of
+    /// the batch once the record is added.
+    fn push_record(
+        &mut self,
+        record: Record,
+        reserved: &mut Option<MemoryReservation>,
+    ) -> Result<ProduceBatchStatus, ProducerError> {
+        let status = match self.batch.push_record(record) {
             Ok(MemoryBatchStatus::Added(offset)) => Ok(ProduceBatchStatus::Added(
                 PartialFutureRecordMetadata::new(offset, self.batch_metadata.clone()),
             )),
             Ok(MemoryBatchStatus::NotAdded(record)) => Ok(ProduceBatchStatus::NotAdded(record)),
             Err(err) => Err(err),
+        };
+
+        if let Some(reservation) = &mut self.reservation {
+            if let Ok(ProduceBatchStatus::Added(_)) = &status
+                && let Some(reserved) = reserved.take()
+            {
+                reservation.absorb(reserved);
+            }
+
+            reservation.resize(self.batch.current_size_uncompressed());
         }
+
+        status
     }
 
     pub(crate) fn is_full(&self) -> bool {
@@ -448,22 +673,22 @@ mod test {
         );
 
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
 
         assert!(!pb.is_full());
 
         assert!(matches!(
-            pb.push_record(record),
+            pb.push_record(record, &mut None),
             Ok(ProduceBatchStatus::NotAdded(_))
         ));
     }
@@ -484,22 +709,22 @@ mod test {
         );
 
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
 
         assert!(pb.is_full());
 
         assert!(matches!(
-            pb.push_record(record),
+            pb.push_record(record, &mut None),
             Ok(ProduceBatchStatus::NotAdded(_))
         ));
     }
@@ -522,21 +747,21 @@ mod test {
         );
 
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
         assert!(matches!(
-            pb.push_record(record.clone()),
+            pb.push_record(record.clone(), &mut None),
             Ok(ProduceBatchStatus::Added(_))
         ));
 
         assert!(pb.is_full());
 
-        assert!(pb.push_record(record).is_err());
+        assert!(pb.push_record(record, &mut None).is_err());
     }
 
     #[fluvio_future::test]
@@ -631,6 +856,98 @@ mod test {
         );
     }
 
+    #[fluvio_future::test]
+    async fn test_record_accumulator_memory_budget_error() {
+        let accumulator = RecordAccumulator::new(1_048_576, 1_048_576, 10, 1, Compression::None)
+            .with_memory_budget(Some(1), BufferOverflow::Error);
+
+        // The first record is accepted even if it is larger than the budget
+        accumulator
+            .push_record(Record::from(("key", "value")), 0)
+            .await
+            .expect("failed push");
+
+        assert!(matches!(
+            accumulator
+                .push_record(Record::from(("key", "value")), 0)
+                .await,
+            Err(ProducerError::MemoryBudgetExceeded(1))
+        ));
+    }
+
+    #[fluvio_future::test]
+    async fn test_record_accumulator_memory_budget_drop_oldest() {
+        let accumulator = RecordAccumulator::new(1_048_576, 1_048_576, 10, 2, Compression::None)
+            .with_memory_budget(Some(1), BufferOverflow::DropOldest);
+
+        let dropped = accumulator
+            .push_record(Record::from(("key", "value")), 0)
+            .await
+            .expect("failed push");
+        accumulator
+            .push_record(Record::from(("key_2", "value_2")), 1)
+            .await
+            .expect("failed push");
+
+        let batches = accumulator.batches().await;
+        let buffered = |partition: PartitionId| batches.get(&partition).unwrap().1.clone();
+
+        assert!(buffered(0).batches.read().await.is_empty());
+        assert_eq!(buffered(1).batches.read().await.len(), 1);
+        assert!(dropped.future.wait().await.is_err());
+    }
+
+    #[fluvio_future::test]
+    async fn test_record_accumulator_memory_budget_block() {
+        let accumulator = RecordAccumulator::new(1_048_576, 1_048_576, 10, 1, Compression::None)
+            .with_memory_budget(Some(1), BufferOverflow::Block);
+
+        accumulator
+            .push_record(Record::from(("key", "value")), 0)
+            .await
+            .expect("failed push");
+
+        let batches = accumulator.batches().await;
+        let deque = batches.get(&0).unwrap().1.clone();
+        let blocked = accumulator.push_record(Record::from(("key_2", "value_2")), 0);
+        let flush = async {
+            fluvio_future::timer::sleep(Duration::from_millis(50)).await;
+            deque.batches.write().await.pop_front().is_some()
+        };
+
+        // The blocked record is accepted once the buffered batch is sent
+        let (pushed, flushed) = futures_util::future::join(blocked, flush).await;
+
+        assert!(flushed);
+        assert!(pushed.is_ok());
+        assert_eq!(deque.batches.read().await.len(), 1);
+    }
+
+    #[fluvio_future::test]
+    async fn test_memory_budget_reservations() {
+        let budget = Arc::new(MemoryBudget::new(10, BufferOverflow::Error));
+        let partitions = RwLock::new(HashMap::new());
+
+        let first = budget
+            .reserve(8, &partitions)
+            .await
+            .expect("failed reserve");
+        assert!(matches!(
+            budget.reserve(8, &partitions).await,
+            Err(ProducerError::MemoryBudgetExceeded(10))
+        ));
+        let second = budget
+            .reserve(2, &partitions)
+            .await
+            .expect("failed reserve");
+        assert_eq!(budget.buffered.load(Ordering::Acquire), 10);
+
+        drop(first);
+        assert_eq!(budget.buffered.load(Ordering::Acquire), 2);
+        drop(second);
+        assert_eq!(budget.buffered.load(Ordering::Acquire), 0);
+    }
+
     #[fluvio_future::test]
     async fn test_produce_partition_response_future_ready() {
         //given
@@ -83,6 +83,13 @@ pub struct TopicProducerConfig {
     /// Maximum amount of batches waiting in the queue before sending to the SPU.
     #[builder(default = "default_batch_queue_size()")]
     pub(crate) batch_queue_size: usize,
+    /// Maximum amount of bytes buffered by the batches of all partitions before sending to the SPU.
+    /// If not set, only `batch_queue_size` limits the buffered batches.
+    #[builder(setter(into, strip_option), default)]
+    pub(crate) max_buffered_bytes: Option<usize>,
+    /// [`BufferOverflow`] behavior when buffering a record would exceed `max_buffered_bytes`.
+    #[builder(default)]
+    pub(crate) buffer_overflow: BufferOverflow,
     /// Time to wait before sending messages to the server.
     #[builder(default = "default_linger_duration()")]
     pub(crate) linger: Duration,
@@ -149,6 +156,14 @@ impl TopicProducerConfig {
         self.batch_queue_size
     }
 
+    pub fn max_buffered_bytes(&self) -> Option<usize> {
+        self.max_buffered_bytes
+    }
+
+    pub fn buffer_overflow(&self) -> BufferOverflow {
+        self.buffer_overflow
+    }
+
     pub fn compression(&self) -> Option<Compression> {
         self.compression
     }
@@ -177,6 +192,8 @@ impl Default for TopicProducerConfig {
             batch_size: default_batch_size(),
             max_request_size: default_max_request_size(),
             batch_queue_size: default_batch_queue_size(),
+            max_buffered_bytes: None,
+            buffer_overflow: BufferOverflow::default(),
             partitioner: default_partitioner(),
             compression: None,
             timeout: default_timeout(),
@@ -198,6 +215,19 @@ pub enum DeliverySemantic {
     AtLeastOnce(RetryPolicy),
 }
 
+/// Defines what the producer does with a record when the buffered batches reach
+/// [`TopicProducerConfig::max_buffered_bytes`].
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Hash)]
+pub enum BufferOverflow {
+    /// Wait until enough buffered batches are sent to the SPU.
+    #[default]
+    Block,
+    /// Discard the oldest buffered batches, their records fail with an error.
+    DropOldest,
+    /// Fail the record with [`crate::ProducerError::MemoryBudgetExceeded`].
+    Error,
+}
+
 /// Defines parameters of retries in [`DeliverySemantic::AtLeastOnce`] delivery semantic.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub struct RetryPolicy {
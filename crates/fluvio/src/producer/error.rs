@@ -27,4 +27,6 @@ pub enum ProducerError {
     ProduceRequestRetryTimeout(#[from] TimeoutError),
     #[error("the batch enqueue timeout limit reached")]
     BatchQueueWaitTimeout,
+    #[error("buffered records exceeded the producer memory budget ({0} bytes)")]
+    MemoryBudgetExceeded(usize),
 }
@@ -58,7 +58,7 @@ pub use self::accumulator::ProducerCallback;
 pub use self::accumulator::ProduceCompletionBatchEvent;
 pub use self::config::{
     TopicProducerConfigBuilder, TopicProducerConfig, TopicProducerConfigBuilderError,
-    DeliverySemantic, RetryPolicy, RetryStrategy,
+    DeliverySemantic, RetryPolicy, RetryStrategy, BufferOverflow,
 };
 pub use self::error::ProducerError;
 use self::event::EventHandler;
@@ -541,7 +541,8 @@ where
             config.batch_queue_size,
             partition_count,
             compression,
-        );
+        )
+        .with_memory_budget(config.max_buffered_bytes, config.buffer_overflow);
 
         let partitions = spu_pool.partitions().clone();
 
@@ -425,8 +425,10 @@ setup_file() {
     # Checks the version is set as active in list list
     run bash -c 'fvm list'
     assert_line --index 0 --partial "    CHANNEL  VERSION"
-    assert_line --index 1 --partial " ✓  stable   $STABLE_VERSION"
-    assert_line --index 2 --partial "    0.10.14  0.10.14"
+    assert_line --index 1 --partial "    Stable"
+    assert_line --index 2 --partial " ✓  stable   $STABLE_VERSION"
+    assert_line --index 3 --partial "    Tags"
+    assert_line --index 4 --partial "    0.10.14  0.10.14"
     assert_success
 
     # Checks contents for the stable channel
@@ -468,8 +470,10 @@ setup_file() {
     # Checks the version is set as active in list
     run bash -c 'fvm list'
     assert_line --index 0 --partial "    CHANNEL  VERSION"
-    assert_line --index 1 --partial " ✓  0.10.14  0.10.14"
+    assert_line --index 1 --partial "    Stable"
     assert_line --index 2 --partial "    stable   $STABLE_VERSION"
+    assert_line --index 3 --partial "    Tags"
+    assert_line --index 4 --partial " ✓  0.10.14  0.10.14"
     assert_success
 
     # Checks current command output